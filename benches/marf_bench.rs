@@ -3,34 +3,79 @@ extern crate criterion;
 extern crate blockstack_lib;
 extern crate rand;
 
-use blockstack_lib::chainstate::burn::BlockHeaderHash;
-use blockstack_lib::chainstate::stacks::index::{marf::MARF, storage::TrieFileStorage, MARFValue};
+use blockstack_lib::chainstate::stacks::index::backend::{
+    MemoryTrieBackend, SqliteTrieBackend, TrieStorageBackend,
+};
+use blockstack_lib::chainstate::stacks::index::{
+    marf::{MarfConnection, MARF},
+    storage::TrieFileStorage,
+    MARFValue, MarfTrieId,
+};
+use blockstack_lib::chainstate::stacks::StacksBlockId;
 
 use criterion::Criterion;
 use rand::prelude::*;
+use std::env;
 use std::fs;
 
+/// Where the benchmarked MARF keeps its tries.  Set MARF_BENCH_BACKEND=memory to benchmark the
+/// in-memory backend; the default is SQLite.
+#[derive(Clone, Copy)]
+enum Backend {
+    Sqlite,
+    Memory,
+}
+
+impl Backend {
+    fn from_env() -> Backend {
+        match env::var("MARF_BENCH_BACKEND").as_ref().map(|s| s.as_str()) {
+            Ok("memory") => Backend::Memory,
+            Ok("sqlite") | Err(_) => Backend::Sqlite,
+            Ok(x) => panic!("Unknown MARF_BENCH_BACKEND '{}'", x),
+        }
+    }
+
+    fn open(&self, filename: &str) -> TrieFileStorage<StacksBlockId> {
+        let (db_path, backend): (&str, Box<dyn TrieStorageBackend<StacksBlockId>>) = match self {
+            Backend::Sqlite => {
+                if fs::metadata(filename).is_ok() {
+                    fs::remove_file(filename).unwrap();
+                };
+                (filename, Box::new(SqliteTrieBackend::new()))
+            }
+            Backend::Memory => (":memory:", Box::new(MemoryTrieBackend::new())),
+        };
+        TrieFileStorage::open_with_backend(db_path, backend).unwrap()
+    }
+}
+
+fn block_header_at(i: u32) -> StacksBlockId {
+    let mut block_header = i.to_le_bytes().to_vec();
+    block_header.resize(32, 0);
+    StacksBlockId::from_bytes(block_header.as_slice()).unwrap()
+}
+
 fn benchmark_marf_usage(
+    backend: Backend,
     filename: &str,
     blocks: u32,
     writes_per_block: u32,
     reads_per_block: u32,
     batch: bool,
-) {
-    if fs::metadata(filename).is_ok() {
-        fs::remove_file(filename).unwrap();
-    };
-    let f = TrieFileStorage::new(filename).unwrap();
-    let mut block_header = BlockHeaderHash::from_bytes(&[0u8; 32]).unwrap();
+) -> MARF<StacksBlockId> {
+    let f = backend.open(filename);
     let mut marf = MARF::from_storage(f);
-    marf.begin(&TrieFileStorage::block_sentinel(), &block_header)
-        .unwrap();
+    let mut parent = StacksBlockId::sentinel();
 
     let mut rng = rand::thread_rng();
 
     let mut values = vec![];
 
     for i in 0..blocks {
+        let block_header = block_header_at(i);
+        let mut tx = marf.begin_tx().unwrap();
+        tx.begin(&parent, &block_header).unwrap();
+
         if batch {
             let mut batch_keys = Vec::new();
             let mut batch_vals = Vec::new();
@@ -42,40 +87,35 @@ fn benchmark_marf_usage(
                 batch_vals.push(MARFValue(value.clone()));
                 values.push((key, MARFValue(value)));
             }
-            marf.insert_batch(&batch_keys, batch_vals).unwrap();
+            tx.insert_batch(&batch_keys, batch_vals).unwrap();
         } else {
             for k in 0..writes_per_block {
                 let key = format!("{}::{}", i, k);
                 let mut value = [0u8; 40];
                 rng.fill_bytes(&mut value);
-                marf.insert(&key, MARFValue(value.clone())).unwrap();
+                tx.insert(&key, MARFValue(value.clone())).unwrap();
                 values.push((key, MARFValue(value)));
             }
         }
 
         for _k in 0..reads_per_block {
             let (key, value) = values.as_slice().choose(&mut rng).unwrap();
-            assert_eq!(marf.get(&block_header, key).unwrap().unwrap(), *value);
+            assert_eq!(tx.get(&block_header, key).unwrap().unwrap(), *value);
         }
 
-        let mut next_block_header = (i + 1).to_le_bytes().to_vec();
-        next_block_header.resize(32, 0);
-        let next_block_header = BlockHeaderHash::from_bytes(next_block_header.as_slice()).unwrap();
-
-        marf.commit().unwrap();
-        marf.begin(&block_header, &next_block_header).unwrap();
-        block_header = next_block_header;
+        tx.commit().unwrap();
+        parent = block_header;
     }
-    marf.commit().unwrap();
+    marf
 }
 
-fn benchmark_marf_read(filename: &str, reads: u32, block: u32, writes_per_block: u32) {
-    let f = TrieFileStorage::new(filename).unwrap();
-    let mut block_header = block.to_le_bytes().to_vec();
-    block_header.resize(32, 0);
-    let block_header = BlockHeaderHash::from_bytes(block_header.as_slice()).unwrap();
-
-    let mut marf = MARF::from_storage(f);
+fn benchmark_marf_read(
+    marf: &mut MARF<StacksBlockId>,
+    reads: u32,
+    block: u32,
+    writes_per_block: u32,
+) {
+    let block_header = block_header_at(block - 1);
 
     let mut rng = rand::thread_rng();
 
@@ -88,30 +128,35 @@ fn benchmark_marf_read(filename: &str, reads: u32, block: u32, writes_per_block:
 }
 
 pub fn basic_usage_benchmark(c: &mut Criterion) {
-    c.bench_function("marf_setup_1000b_5kW", |b| {
-        b.iter(|| benchmark_marf_usage("/tmp/db.1k.sqlite", 1000, 5000, 0, false))
+    let backend = Backend::from_env();
+
+    c.bench_function("marf_setup_1000b_5kW", move |b| {
+        b.iter(|| benchmark_marf_usage(backend, "/tmp/db.1k.sqlite", 1000, 5000, 0, false))
     });
-    c.bench_function("marf_setup_400b_5kW", |b| {
-        b.iter(|| benchmark_marf_usage("/tmp/db.400.sqlite", 1000, 5000, 0, false))
+    c.bench_function("marf_setup_400b_5kW", move |b| {
+        b.iter(|| benchmark_marf_usage(backend, "/tmp/db.400.sqlite", 400, 5000, 0, false))
     });
-    c.bench_function("marf_read_1000b_1kW", |b| {
-        b.iter(|| benchmark_marf_read("/tmp/db.1k.sqlite", 1000, 1000, 5000))
+
+    c.bench_function("marf_read_1000b_1kW", move |b| {
+        let mut marf = benchmark_marf_usage(backend, "/tmp/db.1k.sqlite", 1000, 5000, 0, false);
+        b.iter(|| benchmark_marf_read(&mut marf, 1000, 1000, 5000))
     });
-    c.bench_function("marf_read_400b_1kW", |b| {
-        b.iter(|| benchmark_marf_read("/tmp/db.400.sqlite", 1000, 400, 5000))
+    c.bench_function("marf_read_400b_1kW", move |b| {
+        let mut marf = benchmark_marf_usage(backend, "/tmp/db.400.sqlite", 400, 5000, 0, false);
+        b.iter(|| benchmark_marf_read(&mut marf, 1000, 400, 5000))
     });
 
-    c.bench_function("marf_usage_1b_10kW_0kR", |b| {
-        b.iter(|| benchmark_marf_usage("/tmp/foo.bar.z.sqlite", 1, 10000, 0, false))
+    c.bench_function("marf_usage_1b_10kW_0kR", move |b| {
+        b.iter(|| benchmark_marf_usage(backend, "/tmp/foo.bar.z.sqlite", 1, 10000, 0, false))
     });
-    c.bench_function("marf_usage_10b_1kW_2kR", |b| {
-        b.iter(|| benchmark_marf_usage("/tmp/foo.bar.z.sqlite", 10, 1000, 2000, false))
+    c.bench_function("marf_usage_10b_1kW_2kR", move |b| {
+        b.iter(|| benchmark_marf_usage(backend, "/tmp/foo.bar.z.sqlite", 10, 1000, 2000, false))
     });
-    c.bench_function("marf_usage_100b_5kW_20kR", |b| {
-        b.iter(|| benchmark_marf_usage("/tmp/foo.bar.z.sqlite", 20, 5000, 20000, false))
+    c.bench_function("marf_usage_100b_5kW_20kR", move |b| {
+        b.iter(|| benchmark_marf_usage(backend, "/tmp/foo.bar.z.sqlite", 20, 5000, 20000, false))
     });
-    c.bench_function("marf_usage_batches_10b_1kW_2kR", |b| {
-        b.iter(|| benchmark_marf_usage("/tmp/foo.bar.z.sqlite", 10, 1000, 2000, true))
+    c.bench_function("marf_usage_batches_10b_1kW_2kR", move |b| {
+        b.iter(|| benchmark_marf_usage(backend, "/tmp/foo.bar.z.sqlite", 10, 1000, 2000, true))
     });
 }
 
@@ -23,20 +23,25 @@ extern crate blockstack_lib;
 
 use blockstack_lib::address::AddressHashMode;
 use blockstack_lib::burnchains::Address;
+use blockstack_lib::chainstate::burn::{BlockHeaderHash, ConsensusHash};
 use blockstack_lib::chainstate::stacks::{
     StacksAddress, StacksBlock, StacksPrivateKey, StacksPublicKey, StacksTransaction,
     StacksTransactionSigner, TokenTransferMemo, TransactionAuth, TransactionContractCall,
     TransactionPayload, TransactionSmartContract, TransactionSpendingCondition, TransactionVersion,
     C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
 };
-use blockstack_lib::net::{Error as NetError, StacksMessageCodec};
+use blockstack_lib::net::light_client::{Error as LightClientError, ProofVerifier};
+use blockstack_lib::net::{
+    AccountEntryResponse, ContractSrcResponse, Error as NetError, MapEntryResponse,
+    MarfProofResponse, StacksMessageCodec,
+};
 use blockstack_lib::util::{
     hash::hex_bytes, hash::to_hex, log, retry::LogReader, strings::StacksString,
 };
 use blockstack_lib::vm;
 use blockstack_lib::vm::{
     errors::{Error as ClarityError, RuntimeErrorType},
-    types::PrincipalData,
+    types::{PrincipalData, QualifiedContractIdentifier},
     ClarityName, ContractName, Value,
 };
 use std::convert::TryFrom;
@@ -64,6 +69,7 @@ This CLI has these methods:
   generate-sk      used to generate a secret key for transaction signing
   token-transfer   used to generate and sign a transfer transaction
   addresses        used to get both Bitcoin and Stacks addresses from a private key
+  verify-proof     used to check the MARF proofs in a node's RPC response
 
 For usage information on those methods, call `blockstack-cli [method] -h`
 
@@ -131,6 +137,43 @@ The decode-tx command decodes a serialized Stacks block and prints it to stdout
 The block, if given, must be a hex string.  Alternatively, you may pass - instead, and the
 raw binary block will be read from stdin";
 
+const VERIFY_PROOF_USAGE: &str = "blockstack-cli (options) verify-proof [header-chain-file] [consensus-hash] [block-hash] [response-file-or-stdin] [response-type] [args...]
+
+The verify-proof command checks the MARF proofs in a JSON response from a node's RPC
+interface, against the state root of a chain tip the caller trusts.  If the proofs
+are valid, this command outputs the chain tip and the proven values as JSON, and exits
+with code 0.
+
+The header chain file is a JSON object listing the burnchain headers the caller trusts,
+and the Stacks block headers from an ancestor up to the chain tip, oldest first:
+
+   {
+     \"burn_headers\": [
+       { \"block_height\": 666050, \"block_hash\": \"...\", \"parent_block_hash\": \"...\" }
+     ],
+     \"stacks_headers\": [
+       { \"consensus_hash\": \"...\", \"burn_header_hash\": \"...\", \"burn_header_height\": 666050,
+         \"anchored_header\": \"(hex-encoded, consensus-serialized block header)\" }
+     ]
+   }
+
+The chain tip is the last Stacks block header.  It must have the given block hash (the
+block_header_hash of the winning block-commit), and it must have been chosen in the
+sortition with the given consensus hash.  The response is read from the given file,
+or from stdin if - is given.  The response type is one of:
+
+  account [principal]                                  a GET /v2/accounts response
+  map-entry [contract-id] [map-name] [key-hex]         a POST /v2/map_entry response
+  contract-src [contract-id]                           a GET /v2/contracts/source response
+  proofs [key...]                                      a POST /v2/proofs response
+
+e.g.,
+
+   blockstack-cli verify-proof headers.json 0d4fe2e2a44f8e7b8d39e2e5a6b0a3c0f6b4c5d1 \\
+      4b1f6d7cb0cd1e2e0e2cf2c8f6e6d3b5a1f4e2d9c8b7a6f5e4d3c2b1a0f9e8d7 account.json \\
+      account SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4
+";
+
 #[derive(Debug)]
 enum CliError {
     ClarityRuntimeError(RuntimeErrorType),
@@ -184,6 +227,12 @@ impl From<NetError> for CliError {
     }
 }
 
+impl From<LightClientError> for CliError {
+    fn from(value: LightClientError) -> Self {
+        CliError::Message(format!("Proof verification failed: {}", value))
+    }
+}
+
 impl From<std::num::ParseIntError> for CliError {
     fn from(value: std::num::ParseIntError) -> Self {
         CliError::Message(format!("Failed to parse integer: {}", value))
@@ -608,6 +657,89 @@ fn decode_block(args: &[String], _version: TransactionVersion) -> Result<String,
     }
 }
 
+fn parse_response<T: serde::de::DeserializeOwned>(response: &str) -> Result<T, CliError> {
+    serde_json::from_str(response)
+        .map_err(|e| CliError::Message(format!("Failed to parse response: {:?}", &e)))
+}
+
+fn verify_proof(args: &[String], _version: TransactionVersion) -> Result<String, CliError> {
+    if (args.len() >= 1 && args[0] == "-h") || args.len() < 5 {
+        return Err(CliError::Message(format!(
+            "Usage: {}\n",
+            VERIFY_PROOF_USAGE
+        )));
+    }
+
+    let header_chain = fs::read_to_string(&args[0])?;
+    let consensus_hash = ConsensusHash::from_hex(&args[1])
+        .map_err(|_e| CliError::Message("Failed to decode consensus hash".into()))?;
+    let block_hash = BlockHeaderHash::from_hex(&args[2])
+        .map_err(|_e| CliError::Message("Failed to decode block hash".into()))?;
+    let response = if args[3] == "-" {
+        let mut response = String::new();
+        io::stdin().read_to_string(&mut response)?;
+        response
+    } else {
+        fs::read_to_string(&args[3])?
+    };
+    let response_type = args[4].as_str();
+    let response_args = &args[5..];
+
+    let verifier = ProofVerifier::from_json(&header_chain, &consensus_hash, &block_hash)?;
+
+    let parse_contract = |contract: &str| {
+        QualifiedContractIdentifier::parse(contract)
+            .map_err(|_e| CliError::Message(format!("Invalid contract identifier: {}", contract)))
+    };
+
+    let values = match (response_type, response_args.len()) {
+        ("account", 1) => {
+            let principal = PrincipalData::parse(&response_args[0]).map_err(|_e| {
+                CliError::Message(format!("Invalid principal: {}", &response_args[0]))
+            })?;
+            let account: AccountEntryResponse = parse_response(&response)?;
+            verifier.verify_account(&principal, &account)?;
+            serde_json::to_value(&account)
+        }
+        ("map-entry", 3) => {
+            let contract = parse_contract(&response_args[0])?;
+            let map_name = ClarityName::try_from(response_args[1].clone())
+                .map_err(|_e| CliError::Message("Invalid map name".into()))?;
+            let key = Value::try_deserialize_hex_untyped(&response_args[2])?;
+            let map_entry: MapEntryResponse = parse_response(&response)?;
+            verifier.verify_map_entry(&contract, &map_name, &key, &map_entry)?;
+            serde_json::to_value(&map_entry)
+        }
+        ("contract-src", 1) => {
+            let contract = parse_contract(&response_args[0])?;
+            let contract_src: ContractSrcResponse = parse_response(&response)?;
+            verifier.verify_contract_src(&contract, &contract_src)?;
+            serde_json::to_value(&contract_src)
+        }
+        ("proofs", n) if n > 0 => {
+            let marf_proof: MarfProofResponse = parse_response(&response)?;
+            let values = verifier.verify_marf_proof(response_args, &marf_proof)?;
+            serde_json::to_value(&values)
+        }
+        _ => {
+            return Err(CliError::Message(format!(
+                "Usage: {}\n",
+                VERIFY_PROOF_USAGE
+            )));
+        }
+    }
+    .expect("Failed to serialize response to JSON");
+
+    let mut result = serde_json::Map::new();
+    result.insert("tip".into(), format!("{}", verifier.tip()).into());
+    result.insert(
+        "state_root".into(),
+        format!("{}", verifier.state_root()).into(),
+    );
+    result.insert("verified".into(), values);
+    Ok(serde_json::Value::Object(result).to_string())
+}
+
 fn main() {
     let mut argv: Vec<String> = env::args().collect();
 
@@ -647,6 +779,7 @@ fn main_handler(mut argv: Vec<String>) -> Result<String, CliError> {
             "addresses" => get_addresses(args, tx_version),
             "decode-tx" => decode_transaction(args, tx_version),
             "decode-block" => decode_block(args, tx_version),
+            "verify-proof" => verify_proof(args, tx_version),
             _ => Err(CliError::Usage),
         }
     } else {
@@ -943,4 +1076,38 @@ mod test {
         let result = main_handler(to_string_vec(&block_args)).unwrap();
         eprintln!("result:\n{}", result);
     }
+
+    #[test]
+    fn verify_proof_rejects_bad_header_chain() {
+        let header_chain_path = "/tmp/blockstack-cli-verify-proof-headers.json";
+        let response_path = "/tmp/blockstack-cli-verify-proof-response.json";
+        fs::write(
+            header_chain_path,
+            "{\"burn_headers\": [], \"stacks_headers\": []}",
+        )
+        .unwrap();
+        fs::write(
+            response_path,
+            "{\"balance\": \"0x00\", \"locked\": \"0x00\", \"unlock_height\": 0, \"nonce\": 0}",
+        )
+        .unwrap();
+
+        let usage_args = ["verify-proof", "-h"];
+        assert!(
+            format!("{}", main_handler(to_string_vec(&usage_args)).unwrap_err())
+                .contains("verify-proof")
+        );
+
+        let verify_args = [
+            "verify-proof",
+            header_chain_path,
+            "0000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            response_path,
+            "account",
+            "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4",
+        ];
+        let result = main_handler(to_string_vec(&verify_args)).unwrap_err();
+        assert!(format!("{}", result).contains("No Stacks block headers"));
+    }
 }
@@ -45,7 +45,7 @@ use chainstate::burn::{
 };
 
 use chainstate::coordinator::{Error as CoordinatorError, PoxAnchorBlockStatus, RewardCycleInfo};
-use core::CHAINSTATE_VERSION;
+use core::SORTITION_DB_VERSION;
 
 use chainstate::burn::operations::{
     leader_block_commit::{RewardSetInfo, OUTPUTS_PER_COMMIT},
@@ -1938,7 +1938,7 @@ impl SortitionDB {
 
         db_tx.execute(
             "INSERT INTO db_config (version) VALUES (?1)",
-            &[&CHAINSTATE_VERSION],
+            &[&SORTITION_DB_VERSION],
         )?;
 
         db_tx.instantiate_index()?;
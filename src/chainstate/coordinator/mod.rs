@@ -20,11 +20,12 @@ use std::time::Duration;
 
 use burnchains::{
     db::{BurnchainBlockData, BurnchainDB},
-    Burnchain, BurnchainBlockHeader, BurnchainHeaderHash, Error as BurnchainError, Txid,
+    Address, Burnchain, BurnchainBlockHeader, BurnchainHeaderHash, Error as BurnchainError, Txid,
 };
 use chainstate::burn::{
     db::sortdb::{PoxId, SortitionDB, SortitionId},
     operations::leader_block_commit::RewardSetInfo,
+    operations::LeaderBlockCommitOp,
     BlockHeaderHash, BlockSnapshot, ConsensusHash,
 };
 use chainstate::stacks::{
@@ -111,6 +112,42 @@ pub trait BlockEventDispatcher {
     );
 
     fn dispatch_boot_receipts(&mut self, receipts: Vec<StacksTransactionReceipt>);
+
+    /// Announce a newly-processed sortition, along with the PoX rewards paid by its block commits
+    /// (recipient and total amount), and the total amount burnt.
+    fn announce_burn_block(
+        &self,
+        snapshot: &BlockSnapshot,
+        rewards: Vec<(StacksAddress, u64)>,
+        burns: u64,
+    );
+}
+
+/// Tally up the PoX rewards and burns committed by a sortition's block commits.
+/// Each commit pays its burn fee in equal parts to its commit outputs; outputs to the burn
+/// address (and commits without any outputs) count as burns.
+pub fn calculate_paid_rewards(
+    block_commits: &[LeaderBlockCommitOp],
+) -> (Vec<(StacksAddress, u64)>, u64) {
+    let mut rewards: Vec<(StacksAddress, u64)> = vec![];
+    let mut burns = 0u64;
+    for commit in block_commits.iter() {
+        if commit.commit_outs.len() == 0 {
+            burns = burns.saturating_add(commit.burn_fee);
+            continue;
+        }
+        let amount_per_output = commit.burn_fee / (commit.commit_outs.len() as u64);
+        for recipient in commit.commit_outs.iter() {
+            if recipient.is_burn() {
+                burns = burns.saturating_add(amount_per_output);
+            } else if let Some(entry) = rewards.iter_mut().find(|(addr, _)| addr == recipient) {
+                entry.1 = entry.1.saturating_add(amount_per_output);
+            } else {
+                rewards.push((recipient.clone(), amount_per_output));
+            }
+        }
+    }
+    (rewards, burns)
 }
 
 pub struct ChainsCoordinator<
@@ -227,6 +264,8 @@ impl<'a, T: BlockEventDispatcher>
         block_limit: ExecutionCost,
         dispatcher: &mut T,
         comms: CoordinatorReceivers,
+        contract_event_index: bool,
+        transaction_index: bool,
         boot_block_exec: F,
     ) where
         F: FnOnce(&mut ClarityTx),
@@ -238,7 +277,7 @@ impl<'a, T: BlockEventDispatcher>
         let sortition_db = SortitionDB::open(&burnchain.get_db_path(), true).unwrap();
         let burnchain_blocks_db =
             BurnchainDB::open(&burnchain.get_burnchaindb_path(), false).unwrap();
-        let (chain_state_db, receipts) = StacksChainState::open_and_exec(
+        let (mut chain_state_db, receipts) = StacksChainState::open_and_exec(
             stacks_mainnet,
             stacks_chain_id,
             chain_state_path,
@@ -274,6 +313,13 @@ impl<'a, T: BlockEventDispatcher>
         .unwrap();
         dispatcher.dispatch_boot_receipts(receipts);
 
+        if contract_event_index {
+            chain_state_db.enable_contract_event_index().unwrap();
+        }
+        if transaction_index {
+            chain_state_db.enable_transaction_index();
+        }
+
         let canonical_sortition_tip =
             SortitionDB::get_canonical_sortition_tip(sortition_db.conn()).unwrap();
 
@@ -351,6 +397,12 @@ impl<'a, T: BlockEventDispatcher, U: RewardSetProvider> ChainsCoordinator<'a, T,
             notifier: (),
         }
     }
+
+    /// The chainstate this coordinator processes Stacks blocks into
+    #[cfg(test)]
+    pub fn chainstate(&mut self) -> &mut StacksChainState {
+        &mut self.chain_state_db
+    }
 }
 
 pub fn get_next_recipients<U: RewardSetProvider>(
@@ -499,10 +551,19 @@ impl<'a, T: BlockEventDispatcher, N: CoordinatorNotices, U: RewardSetProvider>
                 })?
                 .0;
 
-            let sortition_id = next_snapshot.sortition_id;
-
             self.notifier.notify_sortition_processed();
 
+            if let Some(dispatcher) = self.dispatcher {
+                let block_commits = SortitionDB::get_block_commits_by_block(
+                    self.sortition_db.conn(),
+                    &next_snapshot.sortition_id,
+                )?;
+                let (rewards, burns) = calculate_paid_rewards(&block_commits);
+                dispatcher.announce_burn_block(&next_snapshot, rewards, burns);
+            }
+
+            let sortition_id = next_snapshot.sortition_id;
+
             debug!(
                 "Sortition processed: {} (tip {} height {})",
                 &sortition_id, &next_snapshot.burn_header_hash, next_snapshot.block_height
@@ -237,6 +237,14 @@ impl BlockEventDispatcher for NullEventDispatcher {
     }
 
     fn dispatch_boot_receipts(&mut self, receipts: Vec<StacksTransactionReceipt>) {}
+
+    fn announce_burn_block(
+        &self,
+        _snapshot: &BlockSnapshot,
+        _rewards: Vec<(StacksAddress, u64)>,
+        _burns: u64,
+    ) {
+    }
 }
 
 pub fn make_coordinator<'a>(
@@ -2009,6 +2017,48 @@ fn reveal_block<T: BlockEventDispatcher, N: CoordinatorNotices, U: RewardSetProv
     coord.handle_new_stacks_block().unwrap();
 }
 
+#[test]
+fn test_calculate_paid_rewards() {
+    let recipient_1 = p2pkh_from(&StacksPrivateKey::new());
+    let recipient_2 = p2pkh_from(&StacksPrivateKey::new());
+    let burn_addr = StacksAddress::burn_address(false);
+
+    let make_commit = |burn_fee: u64, commit_outs: Vec<StacksAddress>| LeaderBlockCommitOp {
+        block_header_hash: BlockHeaderHash([0; 32]),
+        burn_fee,
+        input: BurnchainSigner {
+            num_sigs: 1,
+            hash_mode: address::AddressHashMode::SerializeP2PKH,
+            public_keys: vec![StacksPublicKey::from_private(&StacksPrivateKey::new())],
+        },
+        key_block_ptr: 1,
+        key_vtxindex: 1,
+        memo: vec![],
+        new_seed: VRFSeed([0; 32]),
+        commit_outs,
+
+        parent_block_ptr: 0,
+        parent_vtxindex: 0,
+
+        txid: next_txid(),
+        vtxindex: 1,
+        block_height: 0,
+        burn_header_hash: BurnchainHeaderHash([0; 32]),
+    };
+
+    let commits = vec![
+        make_commit(100, vec![recipient_1.clone(), recipient_2.clone()]),
+        make_commit(50, vec![recipient_1.clone(), burn_addr.clone()]),
+        make_commit(30, vec![]),
+    ];
+
+    let (rewards, burns) = calculate_paid_rewards(&commits);
+    assert_eq!(rewards, vec![(recipient_1, 75), (recipient_2, 50)]);
+    assert_eq!(burns, 55);
+
+    assert_eq!(calculate_paid_rewards(&[]), (vec![], 0));
+}
+
 fn preprocess_block(
     chain_state: &mut StacksChainState,
     sort_db: &SortitionDB,
@@ -143,6 +143,7 @@ pub enum MemPoolRejection {
     NoSuchChainTip(ConsensusHash, BlockHeaderHash),
     ConflictingNonceInMempool,
     TooMuchChaining,
+    MempoolFull,
     DBError(db_error),
     Other(String),
 }
@@ -197,6 +198,12 @@ impl MemPoolRejection {
                 Some(json!({"message": e.to_string()})),
             ),
             ConflictingNonceInMempool => ("ConflictingNonceInMempool", None),
+            MempoolFull => (
+                "MempoolFull",
+                Some(
+                    json!({"message": "Mempool is full, and the fee rate is too low to evict another transaction"}),
+                ),
+            ),
             ContractAlreadyExists(id) => (
                 "ContractAlreadyExists",
                 Some(json!({ "contract_identifier": id.to_string() })),
@@ -2277,6 +2284,82 @@ impl StacksChainState {
         Ok(cnt as u64)
     }
 
+    /// Get the fee rates paid by the transactions mined in the last `num_blocks` anchored blocks
+    /// leading up to and including `tip`, as well as by the transactions in the microblocks those
+    /// blocks confirm.  Coinbases are not included.  Fee rates are returned in no particular order.
+    pub fn get_recent_fee_rates(
+        &self,
+        tip: &StacksBlockId,
+        num_blocks: u64,
+    ) -> Result<Vec<u64>, Error> {
+        let mut fee_rates = vec![];
+        let mut cursor = StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+            self.headers_db(),
+            tip,
+        )?;
+
+        for _ in 0..num_blocks {
+            let header_info = match cursor {
+                Some(header_info) => header_info,
+                None => break,
+            };
+            if header_info.block_height == 0 {
+                // boot block -- nothing mined here
+                break;
+            }
+
+            let block_hash = header_info.anchored_header.block_hash();
+            if let Some(block) = StacksChainState::load_block(
+                &self.blocks_path,
+                &header_info.consensus_hash,
+                &block_hash,
+            )? {
+                for tx in block.txs.iter() {
+                    if let TransactionPayload::Coinbase(_) = tx.payload {
+                        continue;
+                    }
+                    fee_rates.push(tx.get_fee_rate());
+                }
+            }
+
+            let parent_info = match StacksChainState::get_parent_block_id(
+                self.headers_db(),
+                &header_info.index_block_hash(),
+            )? {
+                Some(parent_id) => {
+                    StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                        self.headers_db(),
+                        &parent_id,
+                    )?
+                }
+                None => None,
+            };
+
+            // transactions in the parent's microblock stream that this block confirmed
+            if let Some(ref parent_info) = parent_info {
+                if header_info.anchored_header.parent_microblock != EMPTY_MICROBLOCK_PARENT_HASH {
+                    if let Some(microblocks) = StacksChainState::load_staging_microblock_stream(
+                        &self.blocks_db,
+                        &self.blocks_path,
+                        &parent_info.consensus_hash,
+                        &parent_info.anchored_header.block_hash(),
+                        header_info.anchored_header.parent_microblock_sequence,
+                    )? {
+                        for microblock in microblocks.iter() {
+                            for tx in microblock.txs.iter() {
+                                fee_rates.push(tx.get_fee_rate());
+                            }
+                        }
+                    }
+                }
+            }
+
+            cursor = parent_info;
+        }
+
+        Ok(fee_rates)
+    }
+
     /// Given an index anchor block hash, get the index microblock hash for a confirmed microblock stream.
     pub fn get_confirmed_microblock_index_hash(
         &mut self,
@@ -3995,6 +4078,19 @@ impl StacksChainState {
         .expect("FATAL: failed to advance chain tip");
 
         chainstate_tx.log_transactions_processed(&new_tip.index_block_hash(), &txs_receipts);
+        chainstate_tx.index_transaction_receipts(
+            &new_tip.index_block_hash(),
+            &block,
+            &microblocks,
+            &txs_receipts,
+        )?;
+        chainstate_tx.log_contract_events(
+            &new_tip.index_block_hash(),
+            new_tip.block_height,
+            &block,
+            &microblocks,
+            &txs_receipts,
+        )?;
 
         let epoch_receipt = StacksEpochReceipt {
             header: new_tip,
@@ -26,8 +26,14 @@ use chainstate::stacks::*;
 
 use std::path::{Path, PathBuf};
 
+use rusqlite::types::ToSql;
+use rusqlite::Row;
+
 use util::db::Error as db_error;
-use util::db::{query_count, query_rows, DBConn};
+use util::db::{
+    get_ancestor_block_hash, get_ancestor_block_height, query_count, query_rows, u64_to_sql,
+    DBConn, FromColumn, FromRow,
+};
 
 use util::strings::StacksString;
 
@@ -36,6 +42,7 @@ use util::hash::to_hex;
 use chainstate::burn::db::sortdb::*;
 
 use net::Error as net_error;
+use net::MAX_CONTRACT_EVENTS_PAGE_SIZE;
 
 use vm::types::{PrincipalData, QualifiedContractIdentifier, StandardPrincipalData};
 
@@ -54,6 +61,75 @@ use vm::database::ClarityDatabase;
 
 use vm::contracts::Contract;
 
+use burnchains::Txid;
+
+/// A smart contract event, as recorded in the contract event index
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractEventEntry {
+    pub contract_identifier: QualifiedContractIdentifier,
+    pub topic: String,
+    pub block_height: u64,
+    pub index_block_hash: StacksBlockId,
+    /// hash and sequence number of the microblock that contained the event's transaction, if any
+    pub microblock_hash: Option<BlockHeaderHash>,
+    pub microblock_sequence: Option<u16>,
+    pub txid: Txid,
+    /// position of the event's transaction in its anchored block or microblock
+    pub tx_index: u32,
+    pub event_index: u32,
+    pub value: Value,
+}
+
+impl FromRow<ContractEventEntry> for ContractEventEntry {
+    fn from_row<'a>(row: &'a Row) -> Result<ContractEventEntry, db_error> {
+        let contract_identifier_str: String = row.get("contract_identifier");
+        let contract_identifier = QualifiedContractIdentifier::parse(&contract_identifier_str)
+            .map_err(|_e| db_error::ParseError)?;
+        let topic: String = row.get("topic");
+        let block_height = u64::from_column(row, "block_height")?;
+        let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+        let microblock_hash_hex: Option<String> = row.get("microblock_hash");
+        let microblock_hash = match microblock_hash_hex {
+            Some(hex) => Some(BlockHeaderHash::from_hex(&hex).map_err(|_e| db_error::ParseError)?),
+            None => None,
+        };
+        let microblock_sequence_i64: Option<i64> = row.get("microblock_sequence");
+        let microblock_sequence = match microblock_sequence_i64 {
+            Some(seq) if seq >= 0 && seq <= (u16::max_value() as i64) => Some(seq as u16),
+            Some(_) => return Err(db_error::ParseError),
+            None => None,
+        };
+        let txid = Txid::from_column(row, "txid")?;
+
+        let tx_index_i64: i64 = row.get("tx_index");
+        let event_index_i64: i64 = row.get("event_index");
+        if tx_index_i64 < 0
+            || tx_index_i64 > (u32::max_value() as i64)
+            || event_index_i64 < 0
+            || event_index_i64 > (u32::max_value() as i64)
+        {
+            return Err(db_error::ParseError);
+        }
+
+        let value_hex: String = row.get("value");
+        let value =
+            Value::try_deserialize_hex_untyped(&value_hex).map_err(|_e| db_error::ParseError)?;
+
+        Ok(ContractEventEntry {
+            contract_identifier,
+            topic,
+            block_height,
+            index_block_hash,
+            microblock_hash,
+            microblock_sequence,
+            txid,
+            tx_index: tx_index_i64 as u32,
+            event_index: event_index_i64 as u32,
+            value,
+        })
+    }
+}
+
 impl StacksChainState {
     pub fn get_contract<T: ClarityConnection>(
         clarity_tx: &mut T,
@@ -85,4 +161,305 @@ impl StacksChainState {
             })
             .map_err(Error::ClarityError)
     }
+
+    /// Maintain the contract event index for the blocks this chainstate processes from now on,
+    /// creating its table if it doesn't exist yet.  Events from blocks processed while the index
+    /// was disabled are not backfilled.
+    pub fn enable_contract_event_index(&mut self) -> Result<(), Error> {
+        StacksChainState::instantiate_contract_event_index(&mut self.headers_state_index)?;
+        self.contract_event_index = true;
+        Ok(())
+    }
+
+    /// Is the contract event index maintained by this chainstate?
+    pub fn contract_event_index_enabled(&self) -> bool {
+        self.contract_event_index
+    }
+
+    /// Page through the events a contract emitted in the fork ending at `tip`, in the order in
+    /// which they were emitted.  Only events from blocks with heights in [from_height, to_height]
+    /// are considered, and of those, only events with the given topic if one is given.  At most
+    /// MAX_CONTRACT_EVENTS_PAGE_SIZE events are returned.  Returns None if the node doesn't
+    /// maintain a contract event index.
+    pub fn get_contract_events(
+        &self,
+        contract_id: &QualifiedContractIdentifier,
+        tip: &StacksBlockId,
+        topic: Option<&str>,
+        from_height: u64,
+        to_height: u64,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Option<Vec<ContractEventEntry>>, Error> {
+        if !self.contract_event_index {
+            return Ok(None);
+        }
+
+        // only confirmed events are indexed
+        let tip = match self.unconfirmed_state {
+            Some(ref unconfirmed) if unconfirmed.unconfirmed_chain_tip == *tip => {
+                unconfirmed.confirmed_chain_tip.clone()
+            }
+            _ => tip.clone(),
+        };
+
+        // there can be events from more than one fork at the same height, so only look at the
+        // blocks found by walking back through this fork, from the highest block in range to the
+        // lowest.
+        let tip_height = match get_ancestor_block_height(&self.headers_state_index, &tip, &tip)? {
+            Some(tip_height) => tip_height,
+            None => return Ok(Some(vec![])),
+        };
+        let to_height = to_height.min(tip_height);
+        if from_height > to_height {
+            return Ok(Some(vec![]));
+        }
+        let highest_block =
+            match get_ancestor_block_hash(&self.headers_state_index, to_height, &tip)? {
+                Some(highest_block) => highest_block,
+                None => return Ok(Some(vec![])),
+            };
+
+        let contract_id_str = contract_id.to_string();
+        let from_height_sql = u64_to_sql(from_height)?;
+        let offset_sql = u64_to_sql(offset.min(i64::max_value() as u64))?;
+        let limit_sql = u64_to_sql(limit.min(MAX_CONTRACT_EVENTS_PAGE_SIZE))?;
+        let mut sql = "WITH RECURSIVE fork(block_id, parent_block_id, height) AS ( \
+                           SELECT index_block_hash, parent_block_id, block_height FROM block_headers WHERE index_block_hash = ?1 \
+                           UNION ALL \
+                           SELECT block_headers.index_block_hash, block_headers.parent_block_id, block_headers.block_height \
+                           FROM block_headers JOIN fork ON block_headers.index_block_hash = fork.parent_block_id \
+                           WHERE fork.height > ?2 \
+                       ) \
+                       SELECT contract_events.* FROM contract_events JOIN fork ON contract_events.index_block_hash = fork.block_id \
+                       WHERE contract_events.contract_identifier = ?3 AND contract_events.block_height >= ?2"
+            .to_string();
+        let mut args: Vec<&dyn ToSql> = vec![
+            &highest_block,
+            &from_height_sql,
+            &contract_id_str,
+            &limit_sql,
+            &offset_sql,
+        ];
+        if let Some(ref topic) = topic {
+            sql.push_str(" AND contract_events.topic = ?6");
+            args.push(topic);
+        }
+        // a block's parent microblock stream is processed before the block's own transactions
+        sql.push_str(
+            " ORDER BY contract_events.block_height, contract_events.index_block_hash, \
+             contract_events.microblock_sequence IS NULL, contract_events.microblock_sequence, \
+             contract_events.tx_index, contract_events.event_index \
+             LIMIT ?4 OFFSET ?5",
+        );
+
+        let events = query_rows(self.headers_db(), &sql, &args).map_err(Error::DBError)?;
+        Ok(Some(events))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chainstate::stacks::db::test::*;
+    use chainstate::stacks::events::*;
+    use core::*;
+    use vm::costs::ExecutionCost;
+
+    fn make_print_receipt(
+        contract_id: &QualifiedContractIdentifier,
+        nonce: u64,
+        values: &[Value],
+        post_condition_aborted: bool,
+    ) -> StacksTransactionReceipt {
+        let privk = StacksPrivateKey::from_hex(
+            "6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001",
+        )
+        .unwrap();
+        let auth = TransactionAuth::from_p2pkh(&privk).unwrap();
+        let mut tx = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            auth,
+            TransactionPayload::new_contract_call(
+                StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap(),
+                "hello-world",
+                "add-unit",
+                vec![],
+            )
+            .unwrap(),
+        );
+        tx.set_origin_nonce(nonce);
+
+        let events = values
+            .iter()
+            .map(|value| {
+                StacksTransactionEvent::SmartContractEvent(SmartContractEventData {
+                    key: (contract_id.clone(), "print".to_string()),
+                    value: value.clone(),
+                })
+            })
+            .collect();
+
+        StacksTransactionReceipt {
+            transaction: tx,
+            events,
+            post_condition_aborted,
+            result: Value::okay_true(),
+            stx_burned: 0,
+            contract_analysis: None,
+            execution_cost: ExecutionCost::zero(),
+        }
+    }
+
+    #[test]
+    fn test_contract_event_index() {
+        let mut chainstate = instantiate_chainstate(false, 0x80000000, "test-contract-event-index");
+        let contract_id = QualifiedContractIdentifier::parse(
+            "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.hello-world",
+        )
+        .unwrap();
+        let other_contract_id =
+            QualifiedContractIdentifier::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.other")
+                .unwrap();
+        let genesis = StacksBlockHeader::make_index_block_hash(
+            &FIRST_BURNCHAIN_CONSENSUS_HASH,
+            &FIRST_STACKS_BLOCK_HASH,
+        );
+
+        // the anchored block's receipts come first, followed by its parent microblock stream's
+        let receipts = vec![
+            make_print_receipt(&contract_id, 2, &[Value::Int(2), Value::Int(3)], false),
+            make_print_receipt(&contract_id, 3, &[Value::Int(4)], true),
+            make_print_receipt(&other_contract_id, 0, &[Value::Int(0)], false),
+            make_print_receipt(&contract_id, 1, &[Value::Int(1)], false),
+        ];
+        let block = StacksBlock {
+            header: StacksBlockHeader::genesis_block_header(),
+            txs: vec![
+                receipts[0].transaction.clone(),
+                receipts[1].transaction.clone(),
+            ],
+        };
+        let microblock = StacksMicroblock::first_unsigned(
+            &block.block_hash(),
+            vec![
+                receipts[2].transaction.clone(),
+                receipts[3].transaction.clone(),
+            ],
+        );
+
+        let log_events = |chainstate: &mut StacksChainState| {
+            let (chainstate_tx, _) = chainstate.chainstate_tx_begin().unwrap();
+            chainstate_tx
+                .log_contract_events(&genesis, 0, &block, &[microblock.clone()], &receipts)
+                .unwrap();
+            chainstate_tx.commit().unwrap();
+        };
+
+        // nothing is indexed or served while the index is disabled
+        assert!(!chainstate.contract_event_index_enabled());
+        log_events(&mut chainstate);
+        assert!(chainstate
+            .get_contract_events(&contract_id, &genesis, None, 0, 10, 0, 100)
+            .unwrap()
+            .is_none());
+
+        chainstate.enable_contract_event_index().unwrap();
+        assert!(chainstate.contract_event_index_enabled());
+        assert!(chainstate
+            .get_contract_events(&contract_id, &genesis, None, 0, 10, 0, 100)
+            .unwrap()
+            .unwrap()
+            .is_empty());
+
+        log_events(&mut chainstate);
+        let events = chainstate
+            .get_contract_events(&contract_id, &genesis, None, 0, 10, 0, 100)
+            .unwrap()
+            .unwrap();
+
+        // aborted transactions' and other contracts' events are not indexed, and microblock
+        // events come before the anchored block's
+        let values: Vec<_> = events.iter().map(|e| e.value.clone()).collect();
+        assert_eq!(values, vec![Value::Int(1), Value::Int(2), Value::Int(3)]);
+
+        // transactions are indexed by their position in their block or microblock
+        assert_eq!(events[0].tx_index, 1);
+        assert_eq!(events[0].event_index, 0);
+        assert_eq!(events[0].microblock_hash, Some(microblock.block_hash()));
+        assert_eq!(events[0].microblock_sequence, Some(0));
+        assert_eq!(events[0].txid, receipts[3].transaction.txid());
+
+        assert_eq!(events[2].tx_index, 0);
+        assert_eq!(events[2].event_index, 1);
+        assert_eq!(events[2].microblock_hash, None);
+        assert_eq!(events[2].microblock_sequence, None);
+        assert_eq!(events[2].txid, receipts[0].transaction.txid());
+
+        // mismatched receipts are rejected
+        {
+            let (chainstate_tx, _) = chainstate.chainstate_tx_begin().unwrap();
+            assert!(chainstate_tx
+                .log_contract_events(&genesis, 0, &block, &[], &receipts)
+                .is_err());
+        }
+
+        // paging
+        let events = chainstate
+            .get_contract_events(&contract_id, &genesis, None, 0, 10, 1, 1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].value, Value::Int(2));
+
+        // events from a block that isn't in the tip's fork are left out
+        {
+            let (chainstate_tx, _) = chainstate.chainstate_tx_begin().unwrap();
+            chainstate_tx
+                .log_contract_events(
+                    &StacksBlockId([0x11; 32]),
+                    0,
+                    &block,
+                    &[microblock.clone()],
+                    &receipts,
+                )
+                .unwrap();
+            chainstate_tx.commit().unwrap();
+        }
+        let events = chainstate
+            .get_contract_events(&contract_id, &genesis, None, 0, 10, 0, 100)
+            .unwrap()
+            .unwrap();
+        assert_eq!(events.len(), 3);
+        assert!(events.iter().all(|e| e.index_block_hash == genesis));
+
+        // topic and height filters
+        assert!(chainstate
+            .get_contract_events(&contract_id, &genesis, Some("other"), 0, 10, 0, 100)
+            .unwrap()
+            .unwrap()
+            .is_empty());
+        assert!(chainstate
+            .get_contract_events(&contract_id, &genesis, None, 1, 10, 0, 100)
+            .unwrap()
+            .unwrap()
+            .is_empty());
+
+        // the index is off by default on every open, and enabling it on a chainstate that was
+        // created without it creates its table
+        chainstate
+            .headers_db()
+            .execute("DROP TABLE contract_events", rusqlite::NO_PARAMS)
+            .unwrap();
+        drop(chainstate);
+
+        let mut chainstate = open_chainstate(false, 0x80000000, "test-contract-event-index");
+        assert!(!chainstate.contract_event_index_enabled());
+        chainstate.enable_contract_event_index().unwrap();
+        assert!(chainstate
+            .get_contract_events(&contract_id, &genesis, None, 0, 10, 0, 100)
+            .unwrap()
+            .unwrap()
+            .is_empty());
+    }
 }
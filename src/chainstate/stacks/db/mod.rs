@@ -50,6 +50,7 @@ use chainstate::stacks::index::{MARFValue, MarfTrieId, TrieHash};
 use chainstate::stacks::Error;
 use chainstate::stacks::*;
 
+use chainstate::stacks::index::prune::MarfPruneSummary;
 use chainstate::stacks::index::storage::TrieFileStorage;
 
 use chainstate::burn::db::sortdb::BlockHeaderCache;
@@ -103,6 +104,8 @@ pub struct StacksChainState {
     cached_miner_payments: MinerPaymentCache,
     pub block_limit: ExecutionCost,
     pub unconfirmed_state: Option<UnconfirmedState>,
+    contract_event_index: bool,
+    transaction_index: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -341,6 +344,8 @@ pub struct ChainstateTx<'a> {
     pub headers_tx: StacksDBTx<'a>,
     pub blocks_tx: BlocksDBTx<'a>,
     pub miner_payment_cache: &'a mut MinerPaymentCache,
+    pub contract_event_index: bool,
+    pub transaction_index: bool,
 }
 
 impl<'a> ChainstateTx<'a> {
@@ -379,6 +384,69 @@ impl<'a> ChainstateTx<'a> {
         _events: &[StacksTransactionReceipt],
     ) {
     }
+
+    /// Record the smart contract events from a newly-processed anchored block.  `receipts` must
+    /// contain the receipts for the anchored block's transactions, followed by the receipts for
+    /// the transactions in its parent microblock stream.  Does nothing unless the contract event
+    /// index is enabled.
+    pub fn log_contract_events(
+        &self,
+        block_id: &StacksBlockId,
+        block_height: u64,
+        block: &StacksBlock,
+        microblocks: &[StacksMicroblock],
+        receipts: &[StacksTransactionReceipt],
+    ) -> Result<(), Error> {
+        use util::db::u64_to_sql;
+        use vm::database::ClaritySerializable;
+
+        if !self.contract_event_index {
+            return Ok(());
+        }
+
+        let positions = StacksChainState::transaction_receipt_positions(
+            block_id,
+            block,
+            microblocks,
+            receipts,
+        )?;
+
+        let insert = "INSERT OR REPLACE INTO contract_events \
+                      (contract_identifier, topic, block_height, index_block_hash, microblock_hash, microblock_sequence, txid, tx_index, event_index, value) \
+                      VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)";
+        for ((microblock_opt, tx_index), receipt) in positions.into_iter().zip(receipts.iter()) {
+            // events from aborted transactions never materialize
+            if receipt.post_condition_aborted {
+                continue;
+            }
+            let txid = receipt.transaction.txid();
+            let microblock_hash = microblock_opt.map(|mblock| mblock.block_hash());
+            let microblock_sequence = microblock_opt.map(|mblock| mblock.header.sequence);
+            for (event_index, event) in receipt.events.iter().enumerate() {
+                if let StacksTransactionEvent::SmartContractEvent(ref event_data) = event {
+                    let contract_identifier = event_data.key.0.to_string();
+                    let value = event_data.value.serialize();
+                    let args: &[&dyn ToSql] = &[
+                        &contract_identifier,
+                        &event_data.key.1,
+                        &u64_to_sql(block_height)?,
+                        block_id,
+                        &microblock_hash,
+                        &microblock_sequence,
+                        &txid,
+                        &u64_to_sql(tx_index as u64)?,
+                        &u64_to_sql(event_index as u64)?,
+                        &value,
+                    ];
+                    self.headers_tx
+                        .tx()
+                        .execute(insert, args)
+                        .map_err(|e| Error::DBError(db_error::SqliteError(e)))?;
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Opaque structure for streaming block and microblock data from disk
@@ -395,6 +463,60 @@ pub struct BlockStreamData {
     in_staging: bool,
 }
 
+const CONTRACT_EVENTS_TABLE_SQL: &'static str = r#"
+    -- smart contract (print) events from committed transactions.  Like the transaction index,
+    -- the same event can be recorded in more than one fork.
+    CREATE TABLE IF NOT EXISTS contract_events(
+        contract_identifier TEXT NOT NULL,
+        topic TEXT NOT NULL,
+        block_height INTEGER NOT NULL,
+        index_block_hash TEXT NOT NULL,     -- anchored block that confirmed the event's transaction, either directly or via its parent microblock stream
+        microblock_hash TEXT,               -- set only if the transaction was mined in a microblock
+        microblock_sequence INTEGER,        -- set only if the transaction was mined in a microblock
+        txid TEXT NOT NULL,
+        tx_index INTEGER NOT NULL,          -- position of the transaction in its anchored block or microblock
+        event_index INTEGER NOT NULL,       -- position of the event in the transaction's events
+        value TEXT NOT NULL,                -- hex-encoded, consensus-serialized Clarity value
+
+        PRIMARY KEY(index_block_hash,txid,event_index)
+    );
+    "#;
+
+const CONTRACT_EVENTS_INDEX_SQL: &'static str = r#"
+    CREATE INDEX IF NOT EXISTS contract_events_contract_index ON contract_events(contract_identifier,block_height);
+    "#;
+
+const TRANSACTION_INDEX_TABLE_SQL: &'static str = r#"
+    -- confirmed transactions, indexed by txid.  The same transaction can be confirmed in
+    -- more than one fork, so there can be more than one row per txid.
+    CREATE TABLE IF NOT EXISTS transaction_index(
+        txid TEXT NOT NULL,
+        index_block_hash TEXT NOT NULL,         -- anchored block that confirmed this transaction, either directly or via its parent microblock stream
+        microblock_hash TEXT,                   -- set only if the transaction was mined in a microblock
+        microblock_sequence INTEGER,            -- set only if the transaction was mined in a microblock
+        tx_index INTEGER NOT NULL,              -- position of the transaction in its anchored block or microblock
+        tx_hex TEXT NOT NULL,
+        result TEXT NOT NULL,                   -- hex-encoded, consensus-serialized Clarity value
+        post_condition_aborted INTEGER NOT NULL,
+        events TEXT NOT NULL,                   -- JSON array of the transaction's events
+        execution_cost TEXT NOT NULL,
+
+        PRIMARY KEY(txid,index_block_hash)
+    );
+    "#;
+
+const TRANSACTION_INDEX_INDEX_SQL: &'static str = r#"
+    CREATE INDEX IF NOT EXISTS transaction_index_block_index ON transaction_index(index_block_hash);
+    "#;
+
+/// Chainstate schema versions that can be upgraded to CHAINSTATE_VERSION, and the statements
+/// that upgrade them.  The transaction index starts out empty on an upgraded node, and only
+/// covers blocks processed after the upgrade.
+const CHAINSTATE_MIGRATIONS: &'static [(&'static str, &'static [&'static str])] = &[(
+    "23.0.0.0",
+    &[TRANSACTION_INDEX_TABLE_SQL, TRANSACTION_INDEX_INDEX_SQL],
+)];
+
 // TODO: keep track of when microblock equivocations occur (maybe in the MARF?), so that once we
 // process a PoisonMicroblock transaction, no further blocks may build off of any descendent fork.
 const STACKS_CHAIN_STATE_SQL: &'static [&'static str] = &[
@@ -446,6 +568,8 @@ const STACKS_CHAIN_STATE_SQL: &'static [&'static str] = &[
     CREATE INDEX txid_tx_index ON transactions(txid);
     CREATE INDEX index_block_hash_tx_index ON transactions(index_block_hash);
     "#,
+    TRANSACTION_INDEX_TABLE_SQL,
+    TRANSACTION_INDEX_INDEX_SQL,
     r#"
     CREATE INDEX block_headers_hash_index ON block_headers(block_hash,block_height);
     CREATE INDEX block_index_hash_index ON block_headers(index_block_hash,consensus_hash,block_hash);
@@ -551,7 +675,7 @@ impl StacksChainState {
             // instantiate!
             StacksChainState::instantiate_headers_db(mainnet, chain_id, index_path)
         } else {
-            let marf = StacksChainState::open_index(index_path)?;
+            let mut marf = StacksChainState::open_index(index_path)?;
             // sanity check
             let db_config = query_row::<DBConfig, _>(
                 marf.sqlite_conn(),
@@ -568,24 +692,63 @@ impl StacksChainState {
                 return Err(Error::InvalidChainstateDB);
             }
 
-            if db_config.version != CHAINSTATE_VERSION {
+            if db_config.chain_id != chain_id {
                 error!(
-                    "Invalid chain state database: expected version = {}, got {}",
-                    CHAINSTATE_VERSION, db_config.version
+                    "Invalid chain ID: expected {}, got {}",
+                    chain_id, db_config.chain_id
                 );
                 return Err(Error::InvalidChainstateDB);
             }
 
-            if db_config.chain_id != chain_id {
+            if db_config.version != CHAINSTATE_VERSION {
+                StacksChainState::migrate_headers_db(&mut marf, &db_config.version)?;
+            }
+
+            Ok(marf)
+        }
+    }
+
+    /// Create the contract event index's table, if it doesn't exist yet.
+    fn instantiate_contract_event_index(marf: &mut MARF<StacksBlockId>) -> Result<(), Error> {
+        let dbtx = StacksDBTx::new(marf, ());
+        dbtx.tx().execute(CONTRACT_EVENTS_TABLE_SQL, NO_PARAMS)?;
+        dbtx.tx().execute(CONTRACT_EVENTS_INDEX_SQL, NO_PARAMS)?;
+        dbtx.commit()?;
+        Ok(())
+    }
+
+    /// Upgrade a headers db created by an older node to CHAINSTATE_VERSION.
+    fn migrate_headers_db(marf: &mut MARF<StacksBlockId>, version: &str) -> Result<(), Error> {
+        let migration = CHAINSTATE_MIGRATIONS
+            .iter()
+            .find(|(from_version, _)| *from_version == version);
+
+        let cmds = match migration {
+            Some((_, cmds)) => cmds,
+            None => {
                 error!(
-                    "Invalid chain ID: expected {}, got {}",
-                    chain_id, db_config.chain_id
+                    "Invalid chain state database: expected version = {}, got {}",
+                    CHAINSTATE_VERSION, version
                 );
                 return Err(Error::InvalidChainstateDB);
             }
+        };
 
-            Ok(marf)
+        info!(
+            "Migrating chain state database from version {} to {}",
+            version, CHAINSTATE_VERSION
+        );
+
+        let dbtx = StacksDBTx::new(marf, ());
+        {
+            let tx = dbtx.tx();
+            for cmd in cmds.iter() {
+                tx.execute(cmd, NO_PARAMS)?;
+            }
+            tx.execute("UPDATE db_config SET version = ?1", &[&CHAINSTATE_VERSION])?;
         }
+        dbtx.commit()?;
+        Ok(())
     }
 
     pub fn open_index(marf_path: &str) -> Result<MARF<StacksBlockId>, Error> {
@@ -927,6 +1090,8 @@ impl StacksChainState {
             cached_miner_payments: MinerPaymentCache::new(),
             block_limit: block_limit,
             unconfirmed_state: None,
+            contract_event_index: false,
+            transaction_index: false,
         };
 
         let mut receipts = vec![];
@@ -980,6 +1145,8 @@ impl StacksChainState {
             headers_tx: headers_tx,
             blocks_tx: blocks_tx,
             miner_payment_cache: &mut self.cached_miner_payments,
+            contract_event_index: self.contract_event_index,
+            transaction_index: self.transaction_index,
         };
 
         Ok((chainstate_tx, clarity_instance))
@@ -1079,6 +1246,32 @@ impl StacksChainState {
         self.clarity_state.with_marf(f)
     }
 
+    /// Prune the Clarity state of blocks more than `keep_depth` blocks below the canonical
+    /// Stacks chain tip, so reads at them fail instead of being served.  Must not be called
+    /// while other threads have the chainstate open, since their MARF reads aren't isolated from
+    /// the tries moving.  Returns None if there's no chain tip yet.
+    pub fn prune_clarity_state(
+        &mut self,
+        sortdb: &SortitionDB,
+        keep_depth: u32,
+    ) -> Result<Option<MarfPruneSummary>, Error> {
+        let tip = match self.get_stacks_chain_tip(sortdb)? {
+            Some(tip) => tip,
+            None => {
+                return Ok(None);
+            }
+        };
+        let index_block_hash =
+            StacksBlockHeader::make_index_block_hash(&tip.consensus_hash, &tip.anchored_block_hash);
+        let summary = self.clarity_state.prune(&index_block_hash, keep_depth)?;
+        Ok(Some(summary))
+    }
+
+    /// Has the Clarity state at the given block been pruned?
+    pub fn is_clarity_state_pruned(&self, index_block_hash: &StacksBlockId) -> Result<bool, Error> {
+        Ok(self.clarity_state.is_block_pruned(index_block_hash)?)
+    }
+
     fn begin_read_only_clarity_tx<'a>(
         &'a mut self,
         burn_dbconn: &'a dyn BurnStateDB,
@@ -1159,6 +1352,88 @@ impl StacksChainState {
         }
     }
 
+    /// Run to_do in a throwaway Clarity block built on top of the given chain tip.  The block is
+    /// ephemeral -- its writes are only held in memory and it is always rolled back, so it
+    /// neither persists anything nor takes a write lock on the Clarity MARF.  If the tip refers to the
+    /// unconfirmed state, the block is built on top of the confirmed chain tip and the
+    /// unconfirmed microblock stream is replayed into it first.  to_do may spend at most
+    /// `cost_limit`, regardless of what replaying the microblock stream cost.  If the tip doesn't
+    /// exist, then return None.
+    pub fn with_throwaway_clarity_tx<F, R>(
+        &mut self,
+        burn_dbconn: &dyn BurnStateDB,
+        parent_tip: &StacksBlockId,
+        cost_limit: &ExecutionCost,
+        to_do: F,
+    ) -> Result<Option<R>, Error>
+    where
+        F: FnOnce(&mut ClarityTx) -> R,
+    {
+        let (confirmed_tip, mblock_seq_opt) = match self.unconfirmed_state {
+            Some(ref unconfirmed_state)
+                if unconfirmed_state.unconfirmed_chain_tip == *parent_tip =>
+            {
+                (
+                    unconfirmed_state.confirmed_chain_tip.clone(),
+                    unconfirmed_state.applied_mblock_seq(),
+                )
+            }
+            _ => (parent_tip.clone(), None),
+        };
+
+        let (parent_consensus_hash, parent_block_hash) =
+            match self.get_block_header_hashes(&confirmed_tip)? {
+                Some(hashes) => hashes,
+                None => {
+                    return Ok(None);
+                }
+            };
+
+        let microblocks = match mblock_seq_opt {
+            Some(last_seq) => StacksChainState::load_staging_microblock_stream(
+                &self.blocks_db,
+                &self.blocks_path,
+                &parent_consensus_hash,
+                &parent_block_hash,
+                last_seq,
+            )?
+            .unwrap_or(vec![]),
+            None => vec![],
+        };
+
+        let conf = self.config();
+        let parent_index_block =
+            StacksChainState::get_parent_index_block(&parent_consensus_hash, &parent_block_hash);
+        let new_index_block = StacksBlockHeader::make_index_block_hash(
+            &MINER_BLOCK_CONSENSUS_HASH,
+            &MINER_BLOCK_HEADER_HASH,
+        );
+        let inner_clarity_tx = self.clarity_state.begin_ephemeral(
+            &parent_index_block,
+            &new_index_block,
+            self.headers_state_index.sqlite_conn(),
+            burn_dbconn,
+        );
+        let mut clarity_tx = ClarityTx {
+            block: inner_clarity_tx,
+            config: conf,
+        };
+
+        if microblocks.len() > 0 {
+            if let Err((e, _)) =
+                StacksChainState::process_microblocks_transactions(&mut clarity_tx, &microblocks)
+            {
+                clarity_tx.rollback_block();
+                return Err(e);
+            }
+        }
+
+        clarity_tx.connection().set_cost_limit(cost_limit.clone());
+        let result = to_do(&mut clarity_tx);
+        clarity_tx.rollback_block();
+        Ok(Some(result))
+    }
+
     fn get_parent_index_block(
         parent_consensus_hash: &ConsensusHash,
         parent_block: &BlockHeaderHash,
@@ -1467,4 +1742,50 @@ pub mod test {
             assert!(contract_res.is_some());
         }
     }
+
+    #[test]
+    fn test_open_chainstate_migrates_transaction_index() {
+        let chainstate = instantiate_chainstate(false, 0x80000000, "migrate-transaction-index");
+
+        // roll the headers db back to the schema of the previous version
+        chainstate
+            .headers_db()
+            .execute("DROP TABLE transaction_index", NO_PARAMS)
+            .unwrap();
+        chainstate
+            .headers_db()
+            .execute("UPDATE db_config SET version = ?1", &[&"23.0.0.0"])
+            .unwrap();
+        drop(chainstate);
+
+        let chainstate = open_chainstate(false, 0x80000000, "migrate-transaction-index");
+        let version: String = chainstate
+            .headers_db()
+            .query_row("SELECT version FROM db_config", NO_PARAMS, |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CHAINSTATE_VERSION);
+
+        let count: i64 = chainstate
+            .headers_db()
+            .query_row("SELECT COUNT(*) FROM transaction_index", NO_PARAMS, |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(count, 0);
+        drop(chainstate);
+
+        // an unknown version is still rejected
+        let chainstate = open_chainstate(false, 0x80000000, "migrate-transaction-index");
+        chainstate
+            .headers_db()
+            .execute("UPDATE db_config SET version = ?1", &[&"1.0.0.0"])
+            .unwrap();
+        drop(chainstate);
+
+        let path = chainstate_path("migrate-transaction-index");
+        match StacksChainState::open(false, 0x80000000, &path) {
+            Err(Error::InvalidChainstateDB) => {}
+            _ => panic!("expected InvalidChainstateDB"),
+        }
+    }
 }
@@ -28,12 +28,18 @@ use chainstate::stacks::*;
 
 use std::path::{Path, PathBuf};
 
+use rusqlite::types::ToSql;
+use rusqlite::Row;
+
 use util::db::Error as db_error;
-use util::db::{query_count, query_rows, DBConn};
+use util::db::{
+    get_ancestor_block_height, query_count, query_rows, u64_to_sql, DBConn, DBTx, FromColumn,
+    FromRow,
+};
 
 use util::strings::StacksString;
 
-use util::hash::to_hex;
+use util::hash::{hex_bytes, to_hex};
 
 use chainstate::burn::db::sortdb::*;
 
@@ -59,10 +65,85 @@ pub use vm::analysis::errors::CheckErrors;
 use vm::analysis::types::ContractAnalysis;
 use vm::clarity::Error as clarity_error;
 
-use vm::database::ClarityDatabase;
+use vm::database::{ClarityDatabase, ClaritySerializable};
 
 use vm::contracts::Contract;
 
+/// A confirmed transaction, as recorded in the transaction index
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionIndexEntry {
+    pub txid: Txid,
+    /// index hash of the anchored block that confirmed this transaction
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    /// hash and sequence number of the microblock that contained this transaction, if any
+    pub microblock_hash: Option<BlockHeaderHash>,
+    pub microblock_sequence: Option<u16>,
+    /// position of this transaction in its anchored block or microblock
+    pub tx_index: u32,
+    pub tx: StacksTransaction,
+    pub result: Value,
+    pub post_condition_aborted: bool,
+    /// JSON-serialized events, in the same form sent to event observers
+    pub events: Vec<serde_json::Value>,
+    pub execution_cost: ExecutionCost,
+}
+
+impl FromRow<TransactionIndexEntry> for TransactionIndexEntry {
+    fn from_row<'a>(row: &'a Row) -> Result<TransactionIndexEntry, db_error> {
+        let txid = Txid::from_column(row, "txid")?;
+        let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+        let block_height = u64::from_column(row, "block_height")?;
+
+        let microblock_hash_hex: Option<String> = row.get("microblock_hash");
+        let microblock_hash = match microblock_hash_hex {
+            Some(hex) => Some(BlockHeaderHash::from_hex(&hex).map_err(|_e| db_error::ParseError)?),
+            None => None,
+        };
+        let microblock_sequence_i64: Option<i64> = row.get("microblock_sequence");
+        let microblock_sequence = match microblock_sequence_i64 {
+            Some(seq) if seq >= 0 && seq <= (u16::max_value() as i64) => Some(seq as u16),
+            Some(_) => return Err(db_error::ParseError),
+            None => None,
+        };
+        let tx_index_i64: i64 = row.get("tx_index");
+        if tx_index_i64 < 0 || tx_index_i64 > (u32::max_value() as i64) {
+            return Err(db_error::ParseError);
+        }
+
+        let tx_hex: String = row.get("tx_hex");
+        let tx_bytes = hex_bytes(&tx_hex).map_err(|_e| db_error::ParseError)?;
+        let tx = StacksTransaction::consensus_deserialize(&mut &tx_bytes[..])
+            .map_err(|_e| db_error::ParseError)?;
+
+        let result_hex: String = row.get("result");
+        let result =
+            Value::try_deserialize_hex_untyped(&result_hex).map_err(|_e| db_error::ParseError)?;
+
+        let post_condition_aborted: bool = row.get("post_condition_aborted");
+
+        let events_json: String = row.get("events");
+        let events: Vec<serde_json::Value> =
+            serde_json::from_str(&events_json).map_err(|_e| db_error::ParseError)?;
+
+        let execution_cost: ExecutionCost = row.get("execution_cost");
+
+        Ok(TransactionIndexEntry {
+            txid,
+            index_block_hash,
+            block_height,
+            microblock_hash,
+            microblock_sequence,
+            tx_index: tx_index_i64 as u32,
+            tx,
+            result,
+            post_condition_aborted,
+            events,
+            execution_cost,
+        })
+    }
+}
+
 // make it possible to have a set of Values
 impl std::hash::Hash for Value {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
@@ -228,6 +309,67 @@ impl From<TransactionNonceMismatch> for MemPoolRejection {
     }
 }
 
+impl<'a> ChainstateTx<'a> {
+    /// Add the transactions confirmed by a newly-processed anchored block to the transaction
+    /// index.  `receipts` must contain the receipts for the anchored block's transactions,
+    /// followed by the receipts for the transactions in its parent microblock stream.  Does
+    /// nothing unless the transaction index is enabled.
+    pub fn index_transaction_receipts(
+        &self,
+        index_block_hash: &StacksBlockId,
+        block: &StacksBlock,
+        microblocks: &[StacksMicroblock],
+        receipts: &[StacksTransactionReceipt],
+    ) -> Result<(), Error> {
+        if !self.transaction_index {
+            return Ok(());
+        }
+
+        let positions = StacksChainState::transaction_receipt_positions(
+            index_block_hash,
+            block,
+            microblocks,
+            receipts,
+        )?;
+
+        let sql = "INSERT OR REPLACE INTO transaction_index \
+                   (txid, index_block_hash, microblock_hash, microblock_sequence, tx_index, tx_hex, result, post_condition_aborted, events, execution_cost) \
+                   VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)";
+
+        for ((microblock_opt, tx_index), receipt) in positions.into_iter().zip(receipts.iter()) {
+            let txid = receipt.transaction.txid();
+            let microblock_hash = microblock_opt.map(|mblock| mblock.block_hash());
+            let microblock_sequence = microblock_opt.map(|mblock| mblock.header.sequence);
+            let tx_hex = to_hex(&receipt.transaction.serialize_to_vec());
+            let result = receipt.result.serialize();
+            let events: Vec<serde_json::Value> = receipt
+                .events
+                .iter()
+                .map(|event| event.json_serialize(&txid, !receipt.post_condition_aborted))
+                .collect();
+            let events_json = serde_json::Value::Array(events).to_string();
+
+            let args: &[&dyn ToSql] = &[
+                &txid,
+                index_block_hash,
+                &microblock_hash,
+                &microblock_sequence,
+                &u64_to_sql(tx_index as u64)?,
+                &tx_hex,
+                &result,
+                &receipt.post_condition_aborted,
+                &events_json,
+                &receipt.execution_cost,
+            ];
+            self.headers_tx
+                .tx()
+                .execute(sql, args)
+                .map_err(|e| Error::DBError(db_error::SqliteError(e)))?;
+        }
+        Ok(())
+    }
+}
+
 impl StacksChainState {
     /// Get the payer account
     fn get_payer_account<T: ClarityConnection>(
@@ -372,7 +514,7 @@ impl StacksChainState {
     /// Apply a post-conditions check.
     /// Return true if they all pass.
     /// Return false if at least one fails.
-    fn check_transaction_postconditions(
+    pub fn check_transaction_postconditions(
         post_conditions: &Vec<TransactionPostCondition>,
         post_condition_mode: &TransactionPostConditionMode,
         origin_account: &StacksAccount,
@@ -883,6 +1025,90 @@ impl StacksChainState {
 
         Ok((fee, tx_receipt))
     }
+
+    /// Find where each of a newly-processed anchored block's transaction receipts came from:
+    /// the microblock that contained the transaction (if any), and the transaction's position
+    /// within its anchored block or microblock.  `receipts` must contain the receipts for the
+    /// anchored block's transactions, followed by the receipts for the transactions in its
+    /// parent microblock stream.
+    pub fn transaction_receipt_positions<'a>(
+        index_block_hash: &StacksBlockId,
+        block: &StacksBlock,
+        microblocks: &'a [StacksMicroblock],
+        receipts: &[StacksTransactionReceipt],
+    ) -> Result<Vec<(Option<&'a StacksMicroblock>, usize)>, Error> {
+        let mut positions = vec![];
+        for (i, _) in block.txs.iter().enumerate() {
+            positions.push((None, i));
+        }
+        for microblock in microblocks.iter() {
+            for (i, _) in microblock.txs.iter().enumerate() {
+                positions.push((Some(microblock), i));
+            }
+        }
+        if positions.len() != receipts.len() {
+            error!(
+                "Receipt count {} does not match transaction count {} in {}",
+                receipts.len(),
+                positions.len(),
+                index_block_hash
+            );
+            return Err(Error::InvalidStacksBlock(format!(
+                "Receipt count {} does not match transaction count {}",
+                receipts.len(),
+                positions.len()
+            )));
+        }
+        Ok(positions)
+    }
+
+    /// Maintain the transaction index for the blocks this chainstate processes from now on.
+    /// Transactions from blocks processed while the index was disabled are not backfilled.
+    pub fn enable_transaction_index(&mut self) {
+        self.transaction_index = true;
+    }
+
+    /// Is the transaction index maintained by this chainstate?
+    pub fn transaction_index_enabled(&self) -> bool {
+        self.transaction_index
+    }
+
+    /// Look up a transaction in the transaction index, as confirmed in the fork ending at `tip`.
+    /// Returns None if the transaction is not confirmed in that fork, or if this chainstate
+    /// doesn't maintain a transaction index.
+    pub fn get_indexed_transaction(
+        &self,
+        txid: &Txid,
+        tip: &StacksBlockId,
+    ) -> Result<Option<TransactionIndexEntry>, Error> {
+        if !self.transaction_index {
+            return Ok(None);
+        }
+
+        // only confirmed transactions are indexed, so search from the unconfirmed state's
+        // confirmed tip if we're asked about the unconfirmed tip
+        let tip = match self.unconfirmed_state {
+            Some(ref unconfirmed) if unconfirmed.unconfirmed_chain_tip == *tip => {
+                unconfirmed.confirmed_chain_tip.clone()
+            }
+            _ => tip.clone(),
+        };
+
+        let sql = "SELECT transaction_index.*, block_headers.block_height FROM transaction_index \
+                   JOIN block_headers ON transaction_index.index_block_hash = block_headers.index_block_hash \
+                   WHERE transaction_index.txid = ?1";
+        let args: &[&dyn ToSql] = &[txid];
+        let entries: Vec<TransactionIndexEntry> = query_rows(self.headers_db(), sql, args)?;
+
+        for entry in entries.into_iter() {
+            if get_ancestor_block_height(&self.headers_state_index, &entry.index_block_hash, &tip)?
+                .is_some()
+            {
+                return Ok(Some(entry));
+            }
+        }
+        Ok(None)
+    }
 }
 
 #[cfg(test)]
@@ -6674,5 +6900,90 @@ pub mod test {
             assert!(false)
         };
     }
+
+    #[test]
+    fn test_transaction_index() {
+        use core::{FIRST_BURNCHAIN_CONSENSUS_HASH, FIRST_STACKS_BLOCK_HASH};
+
+        let mut chainstate = instantiate_chainstate(false, 0x80000000, "test-transaction-index");
+        let genesis = StacksBlockHeader::make_index_block_hash(
+            &FIRST_BURNCHAIN_CONSENSUS_HASH,
+            &FIRST_STACKS_BLOCK_HASH,
+        );
+
+        let privk = StacksPrivateKey::from_hex(
+            "6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001",
+        )
+        .unwrap();
+        let make_receipt = |nonce: u64| {
+            let mut tx = StacksTransaction::new(
+                TransactionVersion::Testnet,
+                TransactionAuth::from_p2pkh(&privk).unwrap(),
+                TransactionPayload::TokenTransfer(
+                    PrincipalData::from(StandardPrincipalData(1, [0x11; 20])),
+                    123,
+                    TokenTransferMemo([0u8; 34]),
+                ),
+            );
+            tx.set_origin_nonce(nonce);
+            StacksTransactionReceipt {
+                transaction: tx,
+                events: vec![],
+                post_condition_aborted: false,
+                result: Value::okay_true(),
+                stx_burned: 0,
+                contract_analysis: None,
+                execution_cost: ExecutionCost::zero(),
+            }
+        };
+
+        // the anchored block's receipts come first, followed by its parent microblock stream's
+        let receipts = vec![make_receipt(1), make_receipt(0)];
+        let block = StacksBlock {
+            header: StacksBlockHeader::genesis_block_header(),
+            txs: vec![receipts[0].transaction.clone()],
+        };
+        let microblock = StacksMicroblock::first_unsigned(
+            &block.block_hash(),
+            vec![receipts[1].transaction.clone()],
+        );
+        let txid = receipts[1].transaction.txid();
+
+        let index_receipts = |chainstate: &mut StacksChainState| {
+            let (chainstate_tx, _) = chainstate.chainstate_tx_begin().unwrap();
+            chainstate_tx
+                .index_transaction_receipts(&genesis, &block, &[microblock.clone()], &receipts)
+                .unwrap();
+            chainstate_tx.commit().unwrap();
+        };
+
+        // nothing is indexed or served while the index is disabled
+        assert!(!chainstate.transaction_index_enabled());
+        index_receipts(&mut chainstate);
+        assert!(chainstate
+            .get_indexed_transaction(&txid, &genesis)
+            .unwrap()
+            .is_none());
+
+        chainstate.enable_transaction_index();
+        assert!(chainstate.transaction_index_enabled());
+        assert!(chainstate
+            .get_indexed_transaction(&txid, &genesis)
+            .unwrap()
+            .is_none());
+
+        index_receipts(&mut chainstate);
+        let entry = chainstate
+            .get_indexed_transaction(&txid, &genesis)
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.tx, receipts[1].transaction);
+        assert_eq!(entry.index_block_hash, genesis);
+        assert_eq!(entry.microblock_hash, Some(microblock.block_hash()));
+        assert_eq!(entry.microblock_sequence, Some(0));
+        assert_eq!(entry.tx_index, 0);
+        assert_eq!(entry.result, Value::okay_true());
+    }
+
     // TODO: test poison microblock
 }
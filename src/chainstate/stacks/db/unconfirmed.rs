@@ -41,6 +41,15 @@ use vm::database::NULL_HEADER_DB;
 
 use vm::costs::ExecutionCost;
 
+/// Receipts for the transactions of one microblock that was applied to the unconfirmed state
+#[derive(Debug, Clone)]
+pub struct UnconfirmedMicroblockReceipts {
+    /// the anchored block this microblock builds off of
+    pub anchored_block_id: StacksBlockId,
+    pub header: StacksMicroblockHeader,
+    pub receipts: Vec<StacksTransactionReceipt>,
+}
+
 pub struct UnconfirmedState {
     pub confirmed_chain_tip: StacksBlockId,
     pub unconfirmed_chain_tip: StacksBlockId,
@@ -100,7 +109,8 @@ impl UnconfirmedState {
 
     /// Append a sequence of microblocks to this unconfirmed state.
     /// Microblocks with sequence less than the self.last_mblock_seq will be silently ignored.
-    /// Produce the total fees, total burns, and total list of transaction receipts.
+    /// Produce the total fees, total burns, and the transaction receipts of each newly-applied
+    /// microblock.
     /// Updates internal cost_so_far count.
     /// Idempotent.
     fn append_microblocks(
@@ -108,7 +118,7 @@ impl UnconfirmedState {
         chainstate: &StacksChainState,
         burn_dbconn: &dyn BurnStateDB,
         mblocks: Vec<StacksMicroblock>,
-    ) -> Result<(u128, u128, Vec<StacksTransactionReceipt>), Error> {
+    ) -> Result<(u128, u128, Vec<UnconfirmedMicroblockReceipts>), Error> {
         if self.last_mblock_seq == u16::max_value() {
             // drop them
             return Ok((0, 0, vec![]));
@@ -146,8 +156,9 @@ impl UnconfirmedState {
 
                 let seq = mblock.header.sequence;
                 let mblock_hash = mblock.block_hash();
+                let mblock_header = mblock.header.clone();
 
-                let (stx_fees, stx_burns, mut receipts) =
+                let (stx_fees, stx_burns, receipts) =
                     match StacksChainState::process_microblocks_transactions(
                         &mut clarity_tx,
                         &vec![mblock],
@@ -164,7 +175,11 @@ impl UnconfirmedState {
 
                 total_fees += stx_fees;
                 total_burns += stx_burns;
-                all_receipts.append(&mut receipts);
+                all_receipts.push(UnconfirmedMicroblockReceipts {
+                    anchored_block_id: self.confirmed_chain_tip.clone(),
+                    header: mblock_header,
+                    receipts,
+                });
 
                 last_mblock = Some(mblock_hash);
                 last_mblock_seq = seq;
@@ -179,6 +194,19 @@ impl UnconfirmedState {
         Ok((total_fees, total_burns, all_receipts))
     }
 
+    /// Get the sequence number of the last microblock applied to this unconfirmed state, or None
+    /// if no microblocks have been applied.  Read-only unconfirmed state doesn't track which
+    /// microblocks were applied, so all of them are assumed to be.
+    pub fn applied_mblock_seq(&self) -> Option<u16> {
+        if self.last_mblock_seq == u16::max_value() {
+            Some(u16::max_value())
+        } else if self.last_mblock.is_some() {
+            Some(self.last_mblock_seq)
+        } else {
+            None
+        }
+    }
+
     /// Load up Stacks microblock stream to process
     fn load_child_microblocks(
         &self,
@@ -206,7 +234,7 @@ impl UnconfirmedState {
         &mut self,
         chainstate: &StacksChainState,
         burn_dbconn: &dyn BurnStateDB,
-    ) -> Result<(u128, u128, Vec<StacksTransactionReceipt>), Error> {
+    ) -> Result<(u128, u128, Vec<UnconfirmedMicroblockReceipts>), Error> {
         if self.last_mblock_seq == u16::max_value() {
             // no-op
             return Ok((0, 0, vec![]));
@@ -243,7 +271,15 @@ impl StacksChainState {
         burn_dbconn: &dyn BurnStateDB,
         anchored_block_id: StacksBlockId,
         anchored_block_cost: ExecutionCost,
-    ) -> Result<(UnconfirmedState, u128, u128, Vec<StacksTransactionReceipt>), Error> {
+    ) -> Result<
+        (
+            UnconfirmedState,
+            u128,
+            u128,
+            Vec<UnconfirmedMicroblockReceipts>,
+        ),
+        Error,
+    > {
         let mut unconfirmed_state =
             UnconfirmedState::new(self, anchored_block_id, anchored_block_cost)?;
         let (fees, burns, receipts) = unconfirmed_state.refresh(self, burn_dbconn)?;
@@ -259,7 +295,7 @@ impl StacksChainState {
         &mut self,
         burn_dbconn: &dyn BurnStateDB,
         canonical_tip: StacksBlockId,
-    ) -> Result<(u128, u128, Vec<StacksTransactionReceipt>), Error> {
+    ) -> Result<(u128, u128, Vec<UnconfirmedMicroblockReceipts>), Error> {
         debug!("Reload unconfirmed state off of {}", &canonical_tip);
 
         let unconfirmed_state = self.unconfirmed_state.take();
@@ -293,7 +329,7 @@ impl StacksChainState {
     pub fn refresh_unconfirmed_state(
         &mut self,
         burn_dbconn: &dyn BurnStateDB,
-    ) -> Result<(u128, u128, Vec<StacksTransactionReceipt>), Error> {
+    ) -> Result<(u128, u128, Vec<UnconfirmedMicroblockReceipts>), Error> {
         let mut unconfirmed_state = self.unconfirmed_state.take();
         let res = if let Some(ref mut unconfirmed_state) = unconfirmed_state {
             debug!(
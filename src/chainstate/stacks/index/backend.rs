@@ -0,0 +1,1030 @@
+// Copyright (C) 2013-2020 Blocstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Where the MARF keeps its tries.
+//!
+//! `TrieFileStorage` reads and writes trie blobs, the local block IDs that back-pointers refer
+//! to, the extension locks and the pruned-trie markers through a `TrieStorageBackend`.  The
+//! SQLite connection it also holds is always there -- it carries the side tables that live
+//! next to the MARF (i.e. the chainstate headers and the Clarity side store), and its
+//! transactions are the MARF's transactions.  `SqliteTrieBackend` keeps the tries in that
+//! same database (the `marf_data` table), so a MARF transaction covers both.
+//! `MemoryTrieBackend` keeps them in RAM instead, and mirrors the MARF's transactions with an
+//! undo log.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+
+use rusqlite::Connection;
+
+use chainstate::stacks::index::bits::{read_hash_bytes, read_node_hash_bytes, read_nodetype};
+use chainstate::stacks::index::node::{TrieNodeType, TriePtr};
+use chainstate::stacks::index::storage::TrieStorageConnection;
+use chainstate::stacks::index::{trie_sql, Error, MarfTrieId, TrieHash};
+
+/// Storage for the tries of a MARF.  Every method is handed the SQLite connection (or open
+/// transaction) of the `TrieFileStorage` it belongs to; backends that don't keep anything in
+/// SQLite ignore it.
+pub trait TrieStorageBackend<T: MarfTrieId>: Send {
+    /// Get the local ID of a (confirmed or unconfirmed) trie
+    fn get_block_identifier(&self, conn: &Connection, bhh: &T) -> Result<u32, Error>;
+    fn get_confirmed_block_identifier(
+        &self,
+        conn: &Connection,
+        bhh: &T,
+    ) -> Result<Option<u32>, Error>;
+    fn get_unconfirmed_block_identifier(
+        &self,
+        conn: &Connection,
+        bhh: &T,
+    ) -> Result<Option<u32>, Error>;
+    fn get_block_hash(&self, conn: &Connection, local_id: u32) -> Result<T, Error>;
+    /// Get the local IDs of all tries, confirmed or not, in ascending order
+    fn get_all_block_identifiers(&self, conn: &Connection) -> Result<Vec<u32>, Error>;
+    fn count_all_tries(&self, conn: &Connection) -> Result<u32, Error>;
+    /// Get the highest local ID of any confirmed trie (0 if there are none)
+    fn count_blocks(&self, conn: &Connection) -> Result<u32, Error>;
+
+    /// Store a new confirmed trie, and get its local ID
+    fn write_trie_blob(&mut self, conn: &Connection, bhh: &T, data: &[u8]) -> Result<u32, Error>;
+    /// Store a confirmed trie under a given local ID, i.e. one restored from a snapshot
+    fn write_trie_blob_with_id(
+        &mut self,
+        conn: &Connection,
+        block_id: u32,
+        bhh: &T,
+        data: &[u8],
+    ) -> Result<(), Error>;
+    /// Store a trie built by a miner, replacing any earlier one for the same block
+    fn write_trie_blob_to_mined(
+        &mut self,
+        conn: &Connection,
+        bhh: &T,
+        data: &[u8],
+    ) -> Result<u32, Error>;
+    /// Store an unconfirmed trie, replacing any earlier one for the same block
+    fn write_trie_blob_to_unconfirmed(
+        &mut self,
+        conn: &Connection,
+        bhh: &T,
+        data: &[u8],
+    ) -> Result<u32, Error>;
+    fn read_trie_blob(&self, conn: &Connection, block_id: u32) -> Result<Vec<u8>, Error>;
+    /// Replace the contents of a trie, i.e. with a compacted copy of itself
+    fn update_trie_blob(
+        &mut self,
+        conn: &Connection,
+        block_id: u32,
+        data: &[u8],
+    ) -> Result<(), Error>;
+    fn drop_unconfirmed_trie(&mut self, conn: &Connection, bhh: &T) -> Result<(), Error>;
+
+    /// Get the hash of the block whose trie the given trie was built on
+    fn get_trie_parent(&self, conn: &Connection, block_id: u32) -> Result<T, Error>;
+    fn read_node_type(
+        &self,
+        conn: &Connection,
+        block_id: u32,
+        ptr: &TriePtr,
+    ) -> Result<(TrieNodeType, TrieHash), Error>;
+    fn get_node_hash_bytes(
+        &self,
+        conn: &Connection,
+        block_id: u32,
+        ptr: &TriePtr,
+    ) -> Result<TrieHash, Error>;
+    fn get_node_hash_bytes_by_bhh(
+        &self,
+        conn: &Connection,
+        bhh: &T,
+        ptr: &TriePtr,
+    ) -> Result<TrieHash, Error>;
+    #[cfg(test)]
+    fn read_all_block_hashes_and_roots(
+        &self,
+        conn: &Connection,
+    ) -> Result<Vec<(TrieHash, T)>, Error>;
+
+    /// Lock a block so no one else extends to it.  Returns false if it's already locked, or if
+    /// it's a confirmed block that already has a trie.
+    fn lock_bhh_for_extension(
+        &mut self,
+        conn: &Connection,
+        bhh: &T,
+        unconfirmed: bool,
+    ) -> Result<bool, Error>;
+    fn drop_lock(&mut self, conn: &Connection, bhh: &T) -> Result<(), Error>;
+
+    /// Can this backend have pruned tries?  If not, pruned-trie checks are skipped.
+    fn has_pruned_tries(&self, conn: &Connection) -> Result<bool, Error>;
+    fn is_trie_pruned(&self, conn: &Connection, block_id: u32) -> Result<bool, Error>;
+    fn set_trie_pruned(&mut self, conn: &Connection, block_id: u32) -> Result<(), Error>;
+
+    /// Drop all tries and locks
+    fn clear_tables(&mut self, conn: &Connection) -> Result<(), Error>;
+
+    /// Called when the storage opens a transaction.  Anything written since then is undone by
+    /// `rollback_tx()`, unless `commit_tx()` is called first.
+    fn begin_tx(&mut self);
+    fn commit_tx(&mut self);
+    /// Undo an abandoned transaction, if there is one.  A no-op otherwise.
+    fn rollback_tx(&mut self);
+
+    /// Get a handle to the same tries for a read-only view of the storage
+    fn reopen_readonly(&self) -> Box<dyn TrieStorageBackend<T>>;
+}
+
+/// Keeps the tries in the `marf_data` and `mined_blocks` tables of the MARF's own SQLite
+/// database.  This is the default backend.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SqliteTrieBackend;
+
+impl SqliteTrieBackend {
+    pub fn new() -> SqliteTrieBackend {
+        SqliteTrieBackend
+    }
+}
+
+impl<T: MarfTrieId> TrieStorageBackend<T> for SqliteTrieBackend {
+    fn get_block_identifier(&self, conn: &Connection, bhh: &T) -> Result<u32, Error> {
+        trie_sql::get_block_identifier(conn, bhh)
+    }
+
+    fn get_confirmed_block_identifier(
+        &self,
+        conn: &Connection,
+        bhh: &T,
+    ) -> Result<Option<u32>, Error> {
+        trie_sql::get_confirmed_block_identifier(conn, bhh)
+    }
+
+    fn get_unconfirmed_block_identifier(
+        &self,
+        conn: &Connection,
+        bhh: &T,
+    ) -> Result<Option<u32>, Error> {
+        trie_sql::get_unconfirmed_block_identifier(conn, bhh)
+    }
+
+    fn get_block_hash(&self, conn: &Connection, local_id: u32) -> Result<T, Error> {
+        trie_sql::get_block_hash(conn, local_id)
+    }
+
+    fn get_all_block_identifiers(&self, conn: &Connection) -> Result<Vec<u32>, Error> {
+        trie_sql::get_all_block_identifiers(conn)
+    }
+
+    fn count_all_tries(&self, conn: &Connection) -> Result<u32, Error> {
+        trie_sql::count_all_tries(conn)
+    }
+
+    fn count_blocks(&self, conn: &Connection) -> Result<u32, Error> {
+        trie_sql::count_blocks(conn)
+    }
+
+    fn write_trie_blob(&mut self, conn: &Connection, bhh: &T, data: &[u8]) -> Result<u32, Error> {
+        trie_sql::write_trie_blob(conn, bhh, data)
+    }
+
+    fn write_trie_blob_with_id(
+        &mut self,
+        conn: &Connection,
+        block_id: u32,
+        bhh: &T,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        trie_sql::write_trie_blob_with_id(conn, block_id, bhh, data)
+    }
+
+    fn write_trie_blob_to_mined(
+        &mut self,
+        conn: &Connection,
+        bhh: &T,
+        data: &[u8],
+    ) -> Result<u32, Error> {
+        trie_sql::write_trie_blob_to_mined(conn, bhh, data)
+    }
+
+    fn write_trie_blob_to_unconfirmed(
+        &mut self,
+        conn: &Connection,
+        bhh: &T,
+        data: &[u8],
+    ) -> Result<u32, Error> {
+        trie_sql::write_trie_blob_to_unconfirmed(conn, bhh, data)
+    }
+
+    fn read_trie_blob(&self, conn: &Connection, block_id: u32) -> Result<Vec<u8>, Error> {
+        trie_sql::read_trie_blob(conn, block_id)
+    }
+
+    fn update_trie_blob(
+        &mut self,
+        conn: &Connection,
+        block_id: u32,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        trie_sql::update_trie_blob(conn, block_id, data)
+    }
+
+    fn drop_unconfirmed_trie(&mut self, conn: &Connection, bhh: &T) -> Result<(), Error> {
+        trie_sql::drop_unconfirmed_trie(conn, bhh)
+    }
+
+    fn get_trie_parent(&self, conn: &Connection, block_id: u32) -> Result<T, Error> {
+        trie_sql::get_trie_parent(conn, block_id)
+    }
+
+    fn read_node_type(
+        &self,
+        conn: &Connection,
+        block_id: u32,
+        ptr: &TriePtr,
+    ) -> Result<(TrieNodeType, TrieHash), Error> {
+        trie_sql::read_node_type(conn, block_id, ptr)
+    }
+
+    fn get_node_hash_bytes(
+        &self,
+        conn: &Connection,
+        block_id: u32,
+        ptr: &TriePtr,
+    ) -> Result<TrieHash, Error> {
+        trie_sql::get_node_hash_bytes(conn, block_id, ptr)
+    }
+
+    fn get_node_hash_bytes_by_bhh(
+        &self,
+        conn: &Connection,
+        bhh: &T,
+        ptr: &TriePtr,
+    ) -> Result<TrieHash, Error> {
+        trie_sql::get_node_hash_bytes_by_bhh(conn, bhh, ptr)
+    }
+
+    #[cfg(test)]
+    fn read_all_block_hashes_and_roots(
+        &self,
+        conn: &Connection,
+    ) -> Result<Vec<(TrieHash, T)>, Error> {
+        trie_sql::read_all_block_hashes_and_roots(conn)
+    }
+
+    fn lock_bhh_for_extension(
+        &mut self,
+        conn: &Connection,
+        bhh: &T,
+        unconfirmed: bool,
+    ) -> Result<bool, Error> {
+        trie_sql::tx_lock_bhh_for_extension(conn, bhh, unconfirmed)
+    }
+
+    fn drop_lock(&mut self, conn: &Connection, bhh: &T) -> Result<(), Error> {
+        trie_sql::drop_lock(conn, bhh)
+    }
+
+    fn has_pruned_tries(&self, conn: &Connection) -> Result<bool, Error> {
+        trie_sql::has_pruned_tries_table(conn)
+    }
+
+    fn is_trie_pruned(&self, conn: &Connection, block_id: u32) -> Result<bool, Error> {
+        trie_sql::is_trie_pruned(conn, block_id)
+    }
+
+    fn set_trie_pruned(&mut self, conn: &Connection, block_id: u32) -> Result<(), Error> {
+        trie_sql::set_trie_pruned(conn, block_id)
+    }
+
+    fn clear_tables(&mut self, conn: &Connection) -> Result<(), Error> {
+        trie_sql::clear_tables(conn)
+    }
+
+    // the SQLite transaction takes care of these
+    fn begin_tx(&mut self) {}
+    fn commit_tx(&mut self) {}
+    fn rollback_tx(&mut self) {}
+
+    fn reopen_readonly(&self) -> Box<dyn TrieStorageBackend<T>> {
+        Box::new(SqliteTrieBackend)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct MemoryTrie {
+    block_hash: [u8; 32],
+    data: Vec<u8>,
+    unconfirmed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct MemoryMinedTrie {
+    block_hash: [u8; 32],
+    data: Vec<u8>,
+}
+
+/// The in-RAM counterparts of the `marf_data`, `mined_blocks`, `block_extension_locks` and
+/// `marf_pruned_tries` tables.  Local IDs are handed out the way SQLite hands out row IDs.
+#[derive(Debug, Default)]
+struct MemoryTrieTables {
+    tries: BTreeMap<u32, MemoryTrie>,
+    block_ids: HashMap<[u8; 32], u32>,
+    mined: BTreeMap<u32, MemoryMinedTrie>,
+    locks: HashSet<[u8; 32]>,
+    pruned: HashSet<u32>,
+}
+
+/// What to put back to undo one write
+#[derive(Debug)]
+enum MemoryTrieUndo {
+    Trie(u32, Option<MemoryTrie>),
+    Mined(u32, Option<MemoryMinedTrie>),
+    Lock([u8; 32], bool),
+    Pruned(u32, bool),
+}
+
+impl MemoryTrieTables {
+    fn next_trie_id(&self) -> u32 {
+        self.tries.keys().next_back().map(|id| id + 1).unwrap_or(1)
+    }
+
+    fn next_mined_id(&self) -> u32 {
+        self.mined.keys().next_back().map(|id| id + 1).unwrap_or(1)
+    }
+
+    fn set_trie(&mut self, block_id: u32, trie: Option<MemoryTrie>) -> Option<MemoryTrie> {
+        let old = self.tries.remove(&block_id);
+        if let Some(ref old_trie) = old {
+            self.block_ids.remove(&old_trie.block_hash);
+        }
+        if let Some(trie) = trie {
+            self.block_ids.insert(trie.block_hash.clone(), block_id);
+            self.tries.insert(block_id, trie);
+        }
+        old
+    }
+
+    fn set_mined(
+        &mut self,
+        block_id: u32,
+        trie: Option<MemoryMinedTrie>,
+    ) -> Option<MemoryMinedTrie> {
+        match trie {
+            Some(trie) => self.mined.insert(block_id, trie),
+            None => self.mined.remove(&block_id),
+        }
+    }
+
+    fn set_lock(&mut self, block_hash: [u8; 32], locked: bool) -> bool {
+        if locked {
+            !self.locks.insert(block_hash)
+        } else {
+            self.locks.remove(&block_hash)
+        }
+    }
+
+    fn set_pruned(&mut self, block_id: u32, pruned: bool) -> bool {
+        if pruned {
+            !self.pruned.insert(block_id)
+        } else {
+            self.pruned.remove(&block_id)
+        }
+    }
+
+    fn undo(&mut self, undo: MemoryTrieUndo) {
+        match undo {
+            MemoryTrieUndo::Trie(block_id, trie) => {
+                self.set_trie(block_id, trie);
+            }
+            MemoryTrieUndo::Mined(block_id, trie) => {
+                self.set_mined(block_id, trie);
+            }
+            MemoryTrieUndo::Lock(block_hash, locked) => {
+                self.set_lock(block_hash, locked);
+            }
+            MemoryTrieUndo::Pruned(block_id, pruned) => {
+                self.set_pruned(block_id, pruned);
+            }
+        }
+    }
+
+    fn get_trie(&self, block_id: u32) -> Result<&MemoryTrie, Error> {
+        self.tries.get(&block_id).ok_or(Error::NotFoundError)
+    }
+}
+
+/// Keeps the tries in RAM, i.e. for tests and benchmarks.  Handles made by
+/// `reopen_readonly()` share the same tries.  A transaction's writes are visible to every
+/// handle as soon as they're made, and an abandoned transaction is only undone once the
+/// storage that opened it is used again.
+pub struct MemoryTrieBackend {
+    tables: Arc<Mutex<MemoryTrieTables>>,
+    undo_log: Option<Vec<MemoryTrieUndo>>,
+}
+
+impl MemoryTrieBackend {
+    pub fn new() -> MemoryTrieBackend {
+        MemoryTrieBackend {
+            tables: Arc::new(Mutex::new(MemoryTrieTables::default())),
+            undo_log: None,
+        }
+    }
+
+    fn with_tables<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&MemoryTrieTables) -> R,
+    {
+        let tables = self
+            .tables
+            .lock()
+            .expect("FATAL: in-memory MARF tables lock is poisoned");
+        f(&tables)
+    }
+
+    fn with_tables_mut<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut MemoryTrieTables, &mut Vec<MemoryTrieUndo>) -> R,
+    {
+        let mut tables = self
+            .tables
+            .lock()
+            .expect("FATAL: in-memory MARF tables lock is poisoned");
+        let mut undos = vec![];
+        let ret = f(&mut tables, &mut undos);
+        if let Some(ref mut undo_log) = self.undo_log {
+            undo_log.append(&mut undos);
+        }
+        ret
+    }
+
+    fn read_trie<F, R>(&self, block_id: u32, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&[u8]) -> Result<R, Error>,
+    {
+        self.with_tables(|tables| f(&tables.get_trie(block_id)?.data))
+    }
+}
+
+impl Default for MemoryTrieBackend {
+    fn default() -> MemoryTrieBackend {
+        MemoryTrieBackend::new()
+    }
+}
+
+impl<T: MarfTrieId> TrieStorageBackend<T> for MemoryTrieBackend {
+    fn get_block_identifier(&self, _conn: &Connection, bhh: &T) -> Result<u32, Error> {
+        self.with_tables(|tables| {
+            tables
+                .block_ids
+                .get(bhh.as_bytes())
+                .cloned()
+                .ok_or(Error::NotFoundError)
+        })
+    }
+
+    fn get_confirmed_block_identifier(
+        &self,
+        _conn: &Connection,
+        bhh: &T,
+    ) -> Result<Option<u32>, Error> {
+        self.with_tables(|tables| {
+            Ok(tables
+                .block_ids
+                .get(bhh.as_bytes())
+                .filter(|id| !tables.tries[id].unconfirmed)
+                .cloned())
+        })
+    }
+
+    fn get_unconfirmed_block_identifier(
+        &self,
+        _conn: &Connection,
+        bhh: &T,
+    ) -> Result<Option<u32>, Error> {
+        self.with_tables(|tables| {
+            Ok(tables
+                .block_ids
+                .get(bhh.as_bytes())
+                .filter(|id| tables.tries[id].unconfirmed)
+                .cloned())
+        })
+    }
+
+    fn get_block_hash(&self, _conn: &Connection, local_id: u32) -> Result<T, Error> {
+        self.with_tables(|tables| match tables.tries.get(&local_id) {
+            Some(trie) => Ok(T::from_bytes(trie.block_hash.clone())),
+            None => {
+                error!("Failed to get block header hash of local ID {}", local_id);
+                Err(Error::NotFoundError)
+            }
+        })
+    }
+
+    fn get_all_block_identifiers(&self, _conn: &Connection) -> Result<Vec<u32>, Error> {
+        self.with_tables(|tables| Ok(tables.tries.keys().cloned().collect()))
+    }
+
+    fn count_all_tries(&self, _conn: &Connection) -> Result<u32, Error> {
+        self.with_tables(|tables| Ok(tables.tries.len() as u32))
+    }
+
+    fn count_blocks(&self, _conn: &Connection) -> Result<u32, Error> {
+        self.with_tables(|tables| {
+            Ok(tables
+                .tries
+                .iter()
+                .rev()
+                .find(|(_, trie)| !trie.unconfirmed)
+                .map(|(id, _)| *id)
+                .unwrap_or(0))
+        })
+    }
+
+    fn write_trie_blob(&mut self, conn: &Connection, bhh: &T, data: &[u8]) -> Result<u32, Error> {
+        let block_id = self.with_tables(|tables| tables.next_trie_id());
+        TrieStorageBackend::<T>::write_trie_blob_with_id(self, conn, block_id, bhh, data)?;
+        Ok(block_id)
+    }
+
+    fn write_trie_blob_with_id(
+        &mut self,
+        _conn: &Connection,
+        block_id: u32,
+        bhh: &T,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        self.with_tables_mut(|tables, undos| {
+            if tables.tries.contains_key(&block_id) || tables.block_ids.contains_key(bhh.as_bytes())
+            {
+                return Err(Error::ExistsError);
+            }
+            let trie = MemoryTrie {
+                block_hash: bhh.clone().to_bytes(),
+                data: data.to_vec(),
+                unconfirmed: false,
+            };
+            undos.push(MemoryTrieUndo::Trie(
+                block_id,
+                tables.set_trie(block_id, Some(trie)),
+            ));
+            Ok(())
+        })
+    }
+
+    fn write_trie_blob_to_mined(
+        &mut self,
+        _conn: &Connection,
+        bhh: &T,
+        data: &[u8],
+    ) -> Result<u32, Error> {
+        Ok(self.with_tables_mut(|tables, undos| {
+            let replaced: Vec<u32> = tables
+                .mined
+                .iter()
+                .filter(|(_, trie)| trie.block_hash == bhh.as_bytes())
+                .map(|(id, _)| *id)
+                .collect();
+            for old_id in replaced.into_iter() {
+                undos.push(MemoryTrieUndo::Mined(
+                    old_id,
+                    tables.set_mined(old_id, None),
+                ));
+            }
+            let block_id = tables.next_mined_id();
+            let trie = MemoryMinedTrie {
+                block_hash: bhh.clone().to_bytes(),
+                data: data.to_vec(),
+            };
+            undos.push(MemoryTrieUndo::Mined(
+                block_id,
+                tables.set_mined(block_id, Some(trie)),
+            ));
+            block_id
+        }))
+    }
+
+    fn write_trie_blob_to_unconfirmed(
+        &mut self,
+        _conn: &Connection,
+        bhh: &T,
+        data: &[u8],
+    ) -> Result<u32, Error> {
+        Ok(self.with_tables_mut(|tables, undos| {
+            if let Some(old_id) = tables.block_ids.get(bhh.as_bytes()).cloned() {
+                if !tables.tries[&old_id].unconfirmed {
+                    panic!("BUG: tried to overwrite confirmed MARF trie {}", bhh);
+                }
+                undos.push(MemoryTrieUndo::Trie(old_id, tables.set_trie(old_id, None)));
+            }
+            let block_id = tables.next_trie_id();
+            let trie = MemoryTrie {
+                block_hash: bhh.clone().to_bytes(),
+                data: data.to_vec(),
+                unconfirmed: true,
+            };
+            undos.push(MemoryTrieUndo::Trie(
+                block_id,
+                tables.set_trie(block_id, Some(trie)),
+            ));
+            block_id
+        }))
+    }
+
+    fn read_trie_blob(&self, _conn: &Connection, block_id: u32) -> Result<Vec<u8>, Error> {
+        self.read_trie(block_id, |data| Ok(data.to_vec()))
+    }
+
+    fn update_trie_blob(
+        &mut self,
+        _conn: &Connection,
+        block_id: u32,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        self.with_tables_mut(|tables, undos| {
+            let mut trie = match tables.tries.get(&block_id) {
+                Some(trie) => trie.clone(),
+                None => {
+                    return Ok(());
+                }
+            };
+            trie.data = data.to_vec();
+            undos.push(MemoryTrieUndo::Trie(
+                block_id,
+                tables.set_trie(block_id, Some(trie)),
+            ));
+            Ok(())
+        })
+    }
+
+    fn drop_unconfirmed_trie(&mut self, _conn: &Connection, bhh: &T) -> Result<(), Error> {
+        self.with_tables_mut(|tables, undos| {
+            if let Some(block_id) = tables.block_ids.get(bhh.as_bytes()).cloned() {
+                if tables.tries[&block_id].unconfirmed {
+                    undos.push(MemoryTrieUndo::Trie(
+                        block_id,
+                        tables.set_trie(block_id, None),
+                    ));
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn get_trie_parent(&self, _conn: &Connection, block_id: u32) -> Result<T, Error> {
+        self.read_trie(block_id, |data| {
+            let parent_hash_bytes = read_hash_bytes(&mut Cursor::new(data))?;
+            Ok(T::from_bytes(parent_hash_bytes))
+        })
+    }
+
+    fn read_node_type(
+        &self,
+        _conn: &Connection,
+        block_id: u32,
+        ptr: &TriePtr,
+    ) -> Result<(TrieNodeType, TrieHash), Error> {
+        self.read_trie(block_id, |data| read_nodetype(&mut Cursor::new(data), ptr))
+    }
+
+    fn get_node_hash_bytes(
+        &self,
+        _conn: &Connection,
+        block_id: u32,
+        ptr: &TriePtr,
+    ) -> Result<TrieHash, Error> {
+        self.read_trie(block_id, |data| {
+            Ok(TrieHash(read_node_hash_bytes(&mut Cursor::new(data), ptr)?))
+        })
+    }
+
+    fn get_node_hash_bytes_by_bhh(
+        &self,
+        conn: &Connection,
+        bhh: &T,
+        ptr: &TriePtr,
+    ) -> Result<TrieHash, Error> {
+        let block_id = TrieStorageBackend::<T>::get_block_identifier(self, conn, bhh)?;
+        TrieStorageBackend::<T>::get_node_hash_bytes(self, conn, block_id, ptr)
+    }
+
+    #[cfg(test)]
+    fn read_all_block_hashes_and_roots(
+        &self,
+        _conn: &Connection,
+    ) -> Result<Vec<(TrieHash, T)>, Error> {
+        self.with_tables(|tables| {
+            let start = TrieStorageConnection::<T>::root_ptr_disk() as usize;
+            tables
+                .tries
+                .values()
+                .filter(|trie| !trie.unconfirmed)
+                .map(|trie| {
+                    let trie_hash = TrieHash(read_hash_bytes(&mut &trie.data[start..])?);
+                    Ok((trie_hash, T::from_bytes(trie.block_hash.clone())))
+                })
+                .collect()
+        })
+    }
+
+    fn lock_bhh_for_extension(
+        &mut self,
+        _conn: &Connection,
+        bhh: &T,
+        unconfirmed: bool,
+    ) -> Result<bool, Error> {
+        self.with_tables_mut(|tables, undos| {
+            // confirmed tries can only be extended once.
+            // unconfirmed tries can be overwritten.
+            if !unconfirmed && tables.block_ids.contains_key(bhh.as_bytes()) {
+                return Ok(false);
+            }
+            let block_hash = bhh.clone().to_bytes();
+            if tables.locks.contains(&block_hash) {
+                return Ok(false);
+            }
+            undos.push(MemoryTrieUndo::Lock(
+                block_hash.clone(),
+                tables.set_lock(block_hash, true),
+            ));
+            Ok(true)
+        })
+    }
+
+    fn drop_lock(&mut self, _conn: &Connection, bhh: &T) -> Result<(), Error> {
+        self.with_tables_mut(|tables, undos| {
+            let block_hash = bhh.clone().to_bytes();
+            undos.push(MemoryTrieUndo::Lock(
+                block_hash.clone(),
+                tables.set_lock(block_hash, false),
+            ));
+            Ok(())
+        })
+    }
+
+    fn has_pruned_tries(&self, _conn: &Connection) -> Result<bool, Error> {
+        Ok(true)
+    }
+
+    fn is_trie_pruned(&self, _conn: &Connection, block_id: u32) -> Result<bool, Error> {
+        self.with_tables(|tables| Ok(tables.pruned.contains(&block_id)))
+    }
+
+    fn set_trie_pruned(&mut self, _conn: &Connection, block_id: u32) -> Result<(), Error> {
+        self.with_tables_mut(|tables, undos| {
+            undos.push(MemoryTrieUndo::Pruned(
+                block_id,
+                tables.set_pruned(block_id, true),
+            ));
+            Ok(())
+        })
+    }
+
+    fn clear_tables(&mut self, _conn: &Connection) -> Result<(), Error> {
+        self.with_tables_mut(|tables, undos| {
+            let trie_ids: Vec<u32> = tables.tries.keys().cloned().collect();
+            for block_id in trie_ids.into_iter() {
+                undos.push(MemoryTrieUndo::Trie(
+                    block_id,
+                    tables.set_trie(block_id, None),
+                ));
+            }
+            let mined_ids: Vec<u32> = tables.mined.keys().cloned().collect();
+            for block_id in mined_ids.into_iter() {
+                undos.push(MemoryTrieUndo::Mined(
+                    block_id,
+                    tables.set_mined(block_id, None),
+                ));
+            }
+            let locks: Vec<[u8; 32]> = tables.locks.iter().cloned().collect();
+            for block_hash in locks.into_iter() {
+                undos.push(MemoryTrieUndo::Lock(
+                    block_hash.clone(),
+                    tables.set_lock(block_hash, false),
+                ));
+            }
+            let pruned: Vec<u32> = tables.pruned.iter().cloned().collect();
+            for block_id in pruned.into_iter() {
+                undos.push(MemoryTrieUndo::Pruned(
+                    block_id,
+                    tables.set_pruned(block_id, false),
+                ));
+            }
+            Ok(())
+        })
+    }
+
+    fn begin_tx(&mut self) {
+        TrieStorageBackend::<T>::rollback_tx(self);
+        self.undo_log = Some(vec![]);
+    }
+
+    fn commit_tx(&mut self) {
+        self.undo_log = None;
+    }
+
+    fn rollback_tx(&mut self) {
+        if let Some(undo_log) = self.undo_log.take() {
+            if undo_log.len() > 0 {
+                debug!("Roll back {} in-memory MARF writes", undo_log.len());
+            }
+            let mut tables = self
+                .tables
+                .lock()
+                .expect("FATAL: in-memory MARF tables lock is poisoned");
+            for undo in undo_log.into_iter().rev() {
+                tables.undo(undo);
+            }
+        }
+    }
+
+    fn reopen_readonly(&self) -> Box<dyn TrieStorageBackend<T>> {
+        Box::new(MemoryTrieBackend {
+            tables: self.tables.clone(),
+            undo_log: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use chainstate::burn::BlockHeaderHash;
+    use chainstate::stacks::index::marf::{MarfConnection, MARF};
+    use chainstate::stacks::index::node::TriePath;
+    use chainstate::stacks::index::storage::TrieFileStorage;
+    use chainstate::stacks::index::MARFValue;
+
+    fn make_backends() -> Vec<Box<dyn TrieStorageBackend<BlockHeaderHash>>> {
+        vec![
+            Box::new(SqliteTrieBackend::new()),
+            Box::new(MemoryTrieBackend::new()),
+        ]
+    }
+
+    /// Build the same MARF on each backend: a chain of 6 blocks, and a fork off the third.
+    fn make_test_marf(
+        backend: Box<dyn TrieStorageBackend<BlockHeaderHash>>,
+    ) -> MARF<BlockHeaderHash> {
+        let storage = TrieFileStorage::open_with_backend(":memory:", backend).unwrap();
+        let mut marf = MARF::from_storage(storage);
+
+        let mut parent = BlockHeaderHash::sentinel();
+        for i in 0..6u8 {
+            let block = BlockHeaderHash([i + 1; 32]);
+            marf.begin(&parent, &block).unwrap();
+            marf.insert(
+                &format!("key-{}", i),
+                MARFValue::from_value(&format!("value-{}", i)),
+            )
+            .unwrap();
+            marf.insert("shared", MARFValue::from_value(&format!("shared-{}", i)))
+                .unwrap();
+            marf.commit().unwrap();
+            parent = block;
+        }
+
+        marf.begin(&BlockHeaderHash([3; 32]), &BlockHeaderHash([0xfe; 32]))
+            .unwrap();
+        marf.insert("fork-key", MARFValue::from_value("fork-value"))
+            .unwrap();
+        marf.commit().unwrap();
+        marf
+    }
+
+    #[test]
+    fn test_backends_store_the_same_marf() {
+        let mut marfs: Vec<_> = make_backends().into_iter().map(make_test_marf).collect();
+        let tip = BlockHeaderHash([6; 32]);
+        let fork_tip = BlockHeaderHash([0xfe; 32]);
+
+        let mut root_hashes = vec![];
+        for marf in marfs.iter_mut() {
+            let mut hashes = vec![marf.get_root_hash_at(&fork_tip).unwrap()];
+            for i in 0..6u8 {
+                hashes.push(
+                    marf.get_root_hash_at(&BlockHeaderHash([i + 1; 32]))
+                        .unwrap(),
+                );
+            }
+            root_hashes.push(hashes);
+
+            for i in 0..6u8 {
+                assert_eq!(
+                    marf.get(&tip, &format!("key-{}", i)).unwrap(),
+                    Some(MARFValue::from_value(&format!("value-{}", i)))
+                );
+            }
+            assert_eq!(
+                marf.get(&tip, "shared").unwrap(),
+                Some(MARFValue::from_value("shared-5"))
+            );
+            assert_eq!(
+                marf.get(&fork_tip, "shared").unwrap(),
+                Some(MARFValue::from_value("shared-2"))
+            );
+            assert_eq!(marf.get(&tip, "fork-key").unwrap(), None);
+
+            // proofs from deep in the chain verify against the tip's root
+            let (value, proof) = marf.get_with_proof(&tip, "key-0").unwrap().unwrap();
+            let root_to_block = marf
+                .borrow_storage_backend()
+                .read_root_to_block_table()
+                .unwrap();
+            assert!(proof.verify(
+                &TriePath::from_key("key-0"),
+                &value,
+                &marf.get_root_hash_at(&tip).unwrap(),
+                &root_to_block
+            ));
+        }
+        assert_eq!(root_hashes[0], root_hashes[1]);
+    }
+
+    #[test]
+    fn test_backends_roll_back_abandoned_transactions() {
+        let block = BlockHeaderHash([0x01; 32]);
+        for backend in make_backends().into_iter() {
+            let mut storage = TrieFileStorage::open_with_backend(":memory:", backend).unwrap();
+
+            {
+                let mut tx = storage.transaction().unwrap();
+                tx.write_trie_blob_with_id(1, &block, &[0u8; 64]).unwrap();
+                assert_eq!(tx.count_all_tries().unwrap(), 1);
+                // dropped without committing
+            }
+            assert_eq!(storage.connection().count_all_tries().unwrap(), 0);
+            assert_eq!(
+                storage
+                    .connection()
+                    .get_confirmed_block_identifier(&block)
+                    .unwrap(),
+                None
+            );
+
+            let mut tx = storage.transaction().unwrap();
+            tx.write_trie_blob_with_id(1, &block, &[0u8; 64]).unwrap();
+            tx.commit_tx();
+            assert_eq!(storage.connection().count_all_tries().unwrap(), 1);
+            assert_eq!(
+                storage
+                    .connection()
+                    .get_confirmed_block_identifier(&block)
+                    .unwrap(),
+                Some(1)
+            );
+        }
+    }
+
+    #[test]
+    fn test_memory_backend_prune_and_snapshot() {
+        let tip = BlockHeaderHash([6; 32]);
+        let mut marf = make_test_marf(Box::new(MemoryTrieBackend::new()));
+        let tip_root_hash = marf.get_root_hash_at(&tip).unwrap();
+
+        // a read-only view sees the same tries
+        let mut snapshot = vec![];
+        let mut storage = marf.reopen_storage_readonly().unwrap();
+        let summary = storage.export_snapshot(&tip, None, &mut snapshot).unwrap();
+        assert_eq!(summary.num_tries, 6);
+        assert_eq!(summary.tip_root_hash, tip_root_hash);
+
+        // ...which can be restored into the other backend
+        let mut imported_storage = TrieFileStorage::<BlockHeaderHash>::new_memory().unwrap();
+        imported_storage
+            .import_snapshot(&mut &snapshot[..], None, Some(&tip_root_hash))
+            .unwrap();
+        let mut imported_marf = MARF::from_storage(imported_storage);
+        assert_eq!(imported_marf.get_root_hash_at(&tip).unwrap(), tip_root_hash);
+        assert_eq!(
+            imported_marf.get(&tip, "key-0").unwrap(),
+            Some(MARFValue::from_value("value-0"))
+        );
+
+        let summary = marf.prune(&tip, 2).unwrap();
+        assert_eq!(summary.num_pruned_tries, 3);
+        assert!(summary.bytes_after < summary.bytes_before);
+        assert_eq!(marf.get_root_hash_at(&tip).unwrap(), tip_root_hash);
+        assert_eq!(
+            marf.get(&tip, "key-0").unwrap(),
+            Some(MARFValue::from_value("value-0"))
+        );
+        match marf.get(&BlockHeaderHash([1; 32]), "key-0") {
+            Err(Error::PrunedError) => {}
+            x => panic!("Expected a pruned error, got {:?}", &x),
+        }
+    }
+}
@@ -40,7 +40,9 @@ use chainstate::stacks::index::storage::{
 };
 
 use chainstate::stacks::index::{
-    proofs::TrieMerkleProof, MARFValue, MarfTrieId, TrieHash, TRIEHASH_ENCODED_SIZE,
+    proofs::{TrieMerkleMultiProof, TrieMerkleProof},
+    prune::MarfPruneSummary,
+    MARFValue, MarfTrieId, TrieHash, TRIEHASH_ENCODED_SIZE,
 };
 
 use chainstate::stacks::index::trie::Trie;
@@ -348,6 +350,31 @@ impl<'a, T: MarfTrieId> MarfTransaction<'a, T> {
         Ok(())
     }
 
+    /// Insert the given (key, value) pair into the MARF.  Inserting the same key twice silently
+    /// overwrites the existing key.  Must be called after a call to .begin() (will fail otherwise)
+    pub fn insert(&mut self, key: &str, value: MARFValue) -> Result<(), Error> {
+        if self.storage.readonly() {
+            return Err(Error::ReadOnlyError);
+        }
+
+        let block_hash = match self.open_chain_tip {
+            None => Err(Error::WriteNotBegunError),
+            Some(WriteChainTip { ref block_hash, .. }) => Ok(block_hash.clone()),
+        }?;
+
+        let marf_leaf = TrieLeaf::from_value(&vec![], value);
+        let path = TriePath::from_key(key);
+        let (cur_block_hash, cur_block_id) = self.storage.get_cur_block_and_id();
+
+        let result = MARF::insert_leaf(&mut self.storage, &block_hash, &path, &marf_leaf);
+
+        // restore
+        self.storage
+            .open_block_maybe_id(&cur_block_hash, cur_block_id)?;
+
+        result
+    }
+
     /// Begin extending the MARF to an unconfirmed trie.  The resulting trie will have a block hash
     /// equal to MARF::make_unconfirmed_block_hash(chain_tip) to avoid collision
     /// and block hash reuse.
@@ -704,6 +731,10 @@ impl<T: MarfTrieId> MARF<T> {
         path: &TriePath,
     ) -> Result<(TrieCursor<T>, TrieNodeType), Error> {
         storage.open_block(block_hash)?;
+        if storage.is_cur_block_pruned()? {
+            // only the root hash and the nodes still reachable from later tries are left
+            return Err(Error::PrunedError);
+        }
 
         let mut cursor = TrieCursor::new(path, storage.root_trieptr());
 
@@ -1100,6 +1131,37 @@ impl<T: MarfTrieId> MARF<T> {
         Ok(Some((marf_value, proof)))
     }
 
+    /// Look up several keys at once, and prove the values of the ones that are present with a
+    /// single multi-key proof.  The proof covers the found keys in the order they were given.
+    pub fn get_with_multi_proof(
+        &mut self,
+        block_hash: &T,
+        keys: &[String],
+    ) -> Result<(Vec<Option<MARFValue>>, TrieMerkleMultiProof<T>), Error> {
+        let mut conn = self.storage.connection();
+        let mut values = Vec::with_capacity(keys.len());
+        let mut proofs = vec![];
+        for key in keys.iter() {
+            let marf_value = match MARF::get_by_key(&mut conn, block_hash, key) {
+                Ok(Some(x)) => x,
+                Ok(None) | Err(Error::NotFoundError) => {
+                    values.push(None);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            proofs.push(TrieMerkleProof::from_raw_entry(
+                &mut conn,
+                key,
+                &marf_value,
+                block_hash,
+            )?);
+            values.push(Some(marf_value));
+        }
+        let proof = TrieMerkleMultiProof::from_proofs(&proofs)?;
+        Ok((values, proof))
+    }
+
     pub fn get_bhh_at_height(&mut self, block_hash: &T, height: u32) -> Result<Option<T>, Error> {
         MARF::get_block_at_height(&mut self.storage.connection(), height, block_hash)
     }
@@ -1301,6 +1363,11 @@ impl<T: MarfTrieId> MARF<T> {
             ));
         }
 
+        if conn.is_block_pruned(bhh)? {
+            test_debug!("block {} has been pruned", bhh);
+            return Err(Error::PrunedError);
+        }
+
         // test open
         let result = conn.open_block(bhh);
 
@@ -1322,6 +1389,20 @@ impl<T: MarfTrieId> MARF<T> {
         self.storage.transaction().unwrap()
     }
 
+    /// Has the given block's trie been pruned?  Its state can't be read any longer if so.
+    pub fn is_block_pruned(&self, bhh: &T) -> Result<bool, Error> {
+        self.storage.is_block_pruned(bhh)
+    }
+
+    /// Prune the tries of the ancestors of `tip` that are more than `keep_depth` blocks below
+    /// it.  See `TrieFileStorage::prune()`.
+    pub fn prune(&mut self, tip: &T, keep_depth: u32) -> Result<MarfPruneSummary, Error> {
+        if self.open_chain_tip.is_some() {
+            return Err(Error::InProgressError);
+        }
+        self.storage.prune(tip, keep_depth)
+    }
+
     /// Reopen storage read-only
     pub fn reopen_storage_readonly(&self) -> Result<TrieFileStorage<T>, Error> {
         self.storage.reopen_readonly()
@@ -3512,4 +3593,33 @@ mod test {
             assert!(false);
         }
     }
+
+    #[test]
+    fn marf_tx_insert() {
+        let f = TrieFileStorage::new_memory().unwrap();
+        let mut marf = MARF::from_storage(f);
+        let block_header = BlockHeaderHash::from_bytes(&[1u8; 32]).unwrap();
+
+        let mut tx = marf.begin_tx().unwrap();
+        match tx.insert("foo", MARFValue::from(1u32)) {
+            Err(Error::WriteNotBegunError) => {}
+            x => panic!("Expected WriteNotBegunError, got {:?}", x),
+        }
+
+        tx.begin(&BlockHeaderHash::sentinel(), &block_header)
+            .unwrap();
+        tx.insert("foo", MARFValue::from(1u32)).unwrap();
+        tx.insert("bar", MARFValue::from(2u32)).unwrap();
+        tx.insert("foo", MARFValue::from(3u32)).unwrap();
+        tx.commit().unwrap();
+
+        assert_eq!(
+            marf.get(&block_header, "foo").unwrap(),
+            Some(MARFValue::from(3u32))
+        );
+        assert_eq!(
+            marf.get(&block_header, "bar").unwrap(),
+            Some(MARFValue::from(2u32))
+        );
+    }
 }
@@ -14,10 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+pub mod backend;
 pub mod bits;
 pub mod marf;
 pub mod node;
 pub mod proofs;
+pub mod prune;
+pub mod snapshot;
 pub mod storage;
 pub mod trie;
 pub mod trie_sql;
@@ -283,6 +286,7 @@ pub enum Error {
     CursorError(node::CursorError),
     RestoreMarfBlockError(Box<Error>),
     NonMatchingForks([u8; 32], [u8; 32]),
+    PrunedError,
 }
 
 impl From<io::Error> for Error {
@@ -345,6 +349,7 @@ impl fmt::Display for Error {
             Error::NonMatchingForks(_, _) => {
                 write!(f, "The supplied blocks are not in the same fork")
             }
+            Error::PrunedError => write!(f, "The block's trie has been pruned"),
             Error::RequestedIdentifierForExtensionTrie => {
                 write!(f, "BUG: MARF requested the identifier for a RAM trie")
             }
@@ -1468,6 +1468,10 @@ impl<T: MarfTrieId> TrieMerkleProof<T> {
 
         loop {
             storage.open_block(&block_header)?;
+            if storage.is_cur_block_pruned()? {
+                // can't walk from this trie's root any longer
+                return Err(Error::PrunedError);
+            }
 
             trace!(
                 "Walk {:?} path {:?} to leaf or backptr",
@@ -1595,6 +1599,365 @@ impl<T: MarfTrieId> TrieMerkleProof<T> {
     }
 }
 
+/// A reference from one key's proof to an entry of a multi-key proof.  `chr` is the child of a
+/// node entry that's on the key's path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrieMerkleMultiProofRef {
+    pub index: u32,
+    pub chr: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrieMerkleMultiProofEntry<T: MarfTrieId> {
+    /// An intermediate node and the hashes of all of its children.  The hash of a child that's
+    /// on the path of every key through this node is zeroed, since the verifier computes it.
+    Node((ProofTrieNode<T>, Vec<TrieHash>)),
+    Leaf((u8, TrieLeaf)),
+    Shunt((i64, Vec<TrieHash>)),
+}
+
+define_u8_enum!( TrieMerkleMultiProofEntryIndicator {
+    Node = 0, Leaf = 1, Shunt = 2
+});
+
+/// A Merkle proof for several keys at once.  The proofs of keys looked up at the same tip pass
+/// through many of the same trie nodes and back-pointer shunts, so each distinct entry is
+/// stored once and each key's proof is a list of references to entries.  A key's proof can be
+/// rebuilt exactly as `TrieMerkleProof::from_path()` would have made it, so it is checked with
+/// the single-key verifier.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrieMerkleMultiProof<T: MarfTrieId> {
+    pub entries: Vec<TrieMerkleMultiProofEntry<T>>,
+    pub key_proofs: Vec<Vec<TrieMerkleMultiProofRef>>,
+}
+
+impl StacksMessageCodec for TrieMerkleMultiProofRef {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), ::net::Error> {
+        self.index.consensus_serialize(fd)?;
+        self.chr.consensus_serialize(fd)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<TrieMerkleMultiProofRef, ::net::Error> {
+        let index = read_next(fd)?;
+        let chr = read_next(fd)?;
+        Ok(TrieMerkleMultiProofRef { index, chr })
+    }
+}
+
+impl<T: MarfTrieId> StacksMessageCodec for TrieMerkleMultiProofEntry<T> {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), ::net::Error> {
+        match self {
+            TrieMerkleMultiProofEntry::Node((node, hashes)) => {
+                (TrieMerkleMultiProofEntryIndicator::Node as u8).consensus_serialize(fd)?;
+                node.consensus_serialize(fd)?;
+                hashes.consensus_serialize(fd)
+            }
+            TrieMerkleMultiProofEntry::Leaf((chr, leaf)) => {
+                (TrieMerkleMultiProofEntryIndicator::Leaf as u8).consensus_serialize(fd)?;
+                chr.consensus_serialize(fd)?;
+                leaf.consensus_serialize(fd)
+            }
+            TrieMerkleMultiProofEntry::Shunt((idx, hashes)) => {
+                (TrieMerkleMultiProofEntryIndicator::Shunt as u8).consensus_serialize(fd)?;
+                idx.consensus_serialize(fd)?;
+                hashes.consensus_serialize(fd)
+            }
+        }
+    }
+
+    fn consensus_deserialize<R: Read>(
+        fd: &mut R,
+    ) -> Result<TrieMerkleMultiProofEntry<T>, ::net::Error> {
+        let type_byte =
+            TrieMerkleMultiProofEntryIndicator::from_u8(read_next(fd)?).ok_or_else(|| {
+                ::net::Error::DeserializeError("Bad type byte in Trie Merkle multi-proof".into())
+            })?;
+
+        let entry = match type_byte {
+            TrieMerkleMultiProofEntryIndicator::Node => {
+                let node = read_next(fd)?;
+                let hashes = read_next(fd)?;
+                TrieMerkleMultiProofEntry::Node((node, hashes))
+            }
+            TrieMerkleMultiProofEntryIndicator::Leaf => {
+                let chr = read_next(fd)?;
+                let leaf = read_next(fd)?;
+                TrieMerkleMultiProofEntry::Leaf((chr, leaf))
+            }
+            TrieMerkleMultiProofEntryIndicator::Shunt => {
+                let idx = read_next(fd)?;
+                let hashes = read_next(fd)?;
+                TrieMerkleMultiProofEntry::Shunt((idx, hashes))
+            }
+        };
+        Ok(entry)
+    }
+}
+
+impl<T: MarfTrieId> StacksMessageCodec for TrieMerkleMultiProof<T> {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), ::net::Error> {
+        self.entries.consensus_serialize(fd)?;
+        self.key_proofs.consensus_serialize(fd)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<TrieMerkleMultiProof<T>, ::net::Error> {
+        let entries = read_next(fd)?;
+        let key_proofs = read_next(fd)?;
+        Ok(TrieMerkleMultiProof {
+            entries,
+            key_proofs,
+        })
+    }
+}
+
+/// Index of the child of `node` at `chr`, i.e. the one a proof node leaves out
+fn proof_node_child_index<T: MarfTrieId>(node: &ProofTrieNode<T>, chr: u8) -> Option<usize> {
+    node.ptrs()
+        .iter()
+        .position(|ptr| ptr.id != TrieNodeID::Empty as u8 && ptr.chr == chr)
+}
+
+impl<T: MarfTrieId> TrieMerkleMultiProof<T> {
+    pub fn to_hex(&self) -> String {
+        let mut marf_proof = vec![];
+        self.consensus_serialize(&mut marf_proof)
+            .expect("Write error on memory buffer");
+        to_hex(&marf_proof)
+    }
+
+    /// Combine the proofs of several keys into one.
+    pub fn from_proofs(proofs: &[TrieMerkleProof<T>]) -> Result<TrieMerkleMultiProof<T>, Error> {
+        // node entries are built up with the child hashes each key's proof knows; a child hash
+        // nobody knows yet is None
+        let mut entries: Vec<(TrieMerkleMultiProofEntry<T>, Vec<Option<TrieHash>>)> = vec![];
+        let mut entries_by_bytes: HashMap<Vec<u8>, Vec<usize>> = HashMap::new();
+        let mut key_proofs = Vec::with_capacity(proofs.len());
+
+        for proof in proofs.iter() {
+            let mut key_proof = Vec::with_capacity(proof.len());
+            for proof_entry in proof.iter() {
+                let (chr, entry, known_hashes) = match proof_entry {
+                    TrieMerkleProofType::Node4((chr, node, hashes)) => {
+                        TrieMerkleMultiProof::node_entry(*chr, node, hashes)?
+                    }
+                    TrieMerkleProofType::Node16((chr, node, hashes)) => {
+                        TrieMerkleMultiProof::node_entry(*chr, node, hashes)?
+                    }
+                    TrieMerkleProofType::Node48((chr, node, hashes)) => {
+                        TrieMerkleMultiProof::node_entry(*chr, node, hashes)?
+                    }
+                    TrieMerkleProofType::Node256((chr, node, hashes)) => {
+                        TrieMerkleMultiProof::node_entry(*chr, node, hashes)?
+                    }
+                    TrieMerkleProofType::Leaf((chr, leaf)) => (
+                        *chr,
+                        TrieMerkleMultiProofEntry::Leaf((*chr, leaf.clone())),
+                        vec![],
+                    ),
+                    TrieMerkleProofType::Shunt((idx, hashes)) => (
+                        0,
+                        TrieMerkleMultiProofEntry::Shunt((*idx, hashes.clone())),
+                        vec![],
+                    ),
+                };
+
+                // node entries are keyed by the node alone, so keys that take different
+                // children of the same node share it
+                let mut entry_bytes = vec![];
+                match entry {
+                    TrieMerkleMultiProofEntry::Node((ref node, _)) => {
+                        node.consensus_serialize(&mut entry_bytes)
+                    }
+                    _ => entry.consensus_serialize(&mut entry_bytes),
+                }
+                .expect("Write error on memory buffer");
+
+                let candidates = entries_by_bytes.entry(entry_bytes).or_insert_with(Vec::new);
+                let mut found = None;
+                for index in candidates.iter() {
+                    let existing_hashes = &entries[*index].1;
+                    let compatible = existing_hashes.len() == known_hashes.len()
+                        && existing_hashes
+                            .iter()
+                            .zip(known_hashes.iter())
+                            .all(|pair| match pair {
+                                (Some(ref h1), Some(ref h2)) => h1 == h2,
+                                _ => true,
+                            });
+                    if compatible {
+                        found = Some(*index);
+                        break;
+                    }
+                }
+
+                let index = match found {
+                    Some(index) => {
+                        let existing_hashes = &mut entries[index].1;
+                        for (existing, known) in existing_hashes.iter_mut().zip(known_hashes) {
+                            if existing.is_none() {
+                                *existing = known;
+                            }
+                        }
+                        index
+                    }
+                    None => {
+                        candidates.push(entries.len());
+                        entries.push((entry, known_hashes));
+                        entries.len() - 1
+                    }
+                };
+                key_proof.push(TrieMerkleMultiProofRef {
+                    index: index as u32,
+                    chr,
+                });
+            }
+            key_proofs.push(key_proof);
+        }
+
+        let entries = entries
+            .into_iter()
+            .map(|(entry, known_hashes)| match entry {
+                TrieMerkleMultiProofEntry::Node((node, _)) => {
+                    let hashes = known_hashes
+                        .into_iter()
+                        .map(|hash_opt| hash_opt.unwrap_or(TrieHash([0u8; 32])))
+                        .collect();
+                    TrieMerkleMultiProofEntry::Node((node, hashes))
+                }
+                _ => entry,
+            })
+            .collect();
+
+        Ok(TrieMerkleMultiProof {
+            entries,
+            key_proofs,
+        })
+    }
+
+    /// Turn a single-key proof node into a multi-proof node entry, with the hashes of all of
+    /// its children except the one at `chr`.
+    fn node_entry(
+        chr: u8,
+        node: &ProofTrieNode<T>,
+        hashes: &[TrieHash],
+    ) -> Result<(u8, TrieMerkleMultiProofEntry<T>, Vec<Option<TrieHash>>), Error> {
+        let path_index = proof_node_child_index(node, chr).ok_or_else(|| {
+            Error::CorruptionError(format!("No child 0x{:02x} in proof node", chr))
+        })?;
+        let mut known_hashes = Vec::with_capacity(hashes.len() + 1);
+        known_hashes.extend(hashes[0..path_index].iter().cloned().map(Some));
+        known_hashes.push(None);
+        known_hashes.extend(hashes[path_index..].iter().cloned().map(Some));
+        Ok((
+            chr,
+            TrieMerkleMultiProofEntry::Node((node.clone(), vec![])),
+            known_hashes,
+        ))
+    }
+
+    /// Rebuild the proof of the `i`th key.  Returns None if the multi-proof is malformed.
+    pub fn key_proof(&self, i: usize) -> Option<TrieMerkleProof<T>> {
+        let key_proof = self.key_proofs.get(i)?;
+        let mut proof = Vec::with_capacity(key_proof.len());
+        for entry_ref in key_proof.iter() {
+            let proof_entry = match self.entries.get(entry_ref.index as usize)? {
+                TrieMerkleMultiProofEntry::Node((node, all_hashes)) => {
+                    if node.ptrs().len() != all_hashes.len() {
+                        return None;
+                    }
+                    let path_index = proof_node_child_index(node, entry_ref.chr)?;
+                    let mut hashes = all_hashes.clone();
+                    hashes.remove(path_index);
+
+                    match TrieNodeID::from_u8(node.id)? {
+                        TrieNodeID::Node4 if hashes.len() == 3 => {
+                            let mut hash_slice = [TrieHash([0u8; 32]); 3];
+                            hash_slice.copy_from_slice(&hashes);
+                            TrieMerkleProofType::Node4((entry_ref.chr, node.clone(), hash_slice))
+                        }
+                        TrieNodeID::Node16 if hashes.len() == 15 => {
+                            let mut hash_slice = [TrieHash([0u8; 32]); 15];
+                            hash_slice.copy_from_slice(&hashes);
+                            TrieMerkleProofType::Node16((entry_ref.chr, node.clone(), hash_slice))
+                        }
+                        TrieNodeID::Node48 if hashes.len() == 47 => {
+                            let mut hash_slice = [TrieHash([0u8; 32]); 47];
+                            hash_slice.copy_from_slice(&hashes);
+                            TrieMerkleProofType::Node48((entry_ref.chr, node.clone(), hash_slice))
+                        }
+                        TrieNodeID::Node256 if hashes.len() == 255 => {
+                            let mut hash_slice = [TrieHash([0u8; 32]); 255];
+                            hash_slice.copy_from_slice(&hashes);
+                            TrieMerkleProofType::Node256((entry_ref.chr, node.clone(), hash_slice))
+                        }
+                        _ => {
+                            trace!("Malformed multi-proof node entry {}", entry_ref.index);
+                            return None;
+                        }
+                    }
+                }
+                TrieMerkleMultiProofEntry::Leaf((chr, leaf)) => {
+                    TrieMerkleProofType::Leaf((*chr, leaf.clone()))
+                }
+                TrieMerkleMultiProofEntry::Shunt((idx, hashes)) => {
+                    TrieMerkleProofType::Shunt((*idx, hashes.clone()))
+                }
+            };
+            proof.push(proof_entry);
+        }
+        Some(TrieMerkleProof(proof))
+    }
+
+    /// Verify that each of the given keys has the given value as of the trie with the given
+    /// root hash.  The keys must be in the same order as when the proof was made.
+    pub fn verify(
+        &self,
+        entries: &[(String, MARFValue)],
+        root_hash: &TrieHash,
+        root_to_block: &HashMap<TrieHash, T>,
+    ) -> bool {
+        if entries.len() != self.key_proofs.len() {
+            trace!(
+                "Multi-proof is for {} keys, not {}",
+                self.key_proofs.len(),
+                entries.len()
+            );
+            return false;
+        }
+        for (i, (key, value)) in entries.iter().enumerate() {
+            let proof = match self.key_proof(i) {
+                Some(proof) => proof,
+                None => {
+                    return false;
+                }
+            };
+            if !proof.verify(&TriePath::from_key(key), value, root_hash, root_to_block) {
+                trace!("Invalid proof for key {}", key);
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Make a proof that each of the given keys has the given value as of the given block.
+    pub fn from_raw_entries(
+        storage: &mut TrieStorageConnection<T>,
+        entries: &[(String, MARFValue)],
+        root_block_header: &T,
+    ) -> Result<TrieMerkleMultiProof<T>, Error> {
+        let mut proofs = Vec::with_capacity(entries.len());
+        for (key, value) in entries.iter() {
+            proofs.push(TrieMerkleProof::from_raw_entry(
+                storage,
+                key,
+                value,
+                root_block_header,
+            )?);
+        }
+        TrieMerkleMultiProof::from_proofs(&proofs)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -1670,4 +2033,80 @@ mod test {
         let marf_value_1 = MARFValue::from_value(&old_v);
         assert!(proof_1.verify(&triepath_1, &marf_value_1, &root_hash_1, &root_to_block));
     }
+
+    #[test]
+    fn multi_proof_shares_nodes() {
+        let mut m = MARF::from_path(":memory:").unwrap();
+
+        let mut parent = BlockHeaderHash::sentinel();
+        let mut keys = vec![];
+        let mut root_hash = TrieHash([0u8; 32]);
+        let mut tip = parent.clone();
+
+        // spread the keys out over several blocks, so their proofs need back-pointer shunts
+        for i in 0..8u8 {
+            let block = BlockHeaderHash([i; 32]);
+            m.begin(&parent, &block).unwrap();
+            for j in 0..8u8 {
+                let key = format!("K-{}-{}", i, j);
+                let value = format!("V-{}-{}", i, j);
+                m.insert(&key, MARFValue::from_value(&value)).unwrap();
+                keys.push(key);
+            }
+            let (_, hash) = Trie::read_root(&mut m.borrow_storage_backend()).unwrap();
+            root_hash = hash;
+            m.commit().unwrap();
+            parent = block.clone();
+            tip = block;
+        }
+
+        let root_to_block = m
+            .borrow_storage_backend()
+            .read_root_to_block_table()
+            .unwrap();
+
+        // prove one key from each block, plus one that isn't there
+        let mut lookup: Vec<String> = (0..8).map(|i| keys[i * 8 + i].clone()).collect();
+        lookup.insert(3, "K-missing".to_string());
+
+        let (values, multi_proof) = m.get_with_multi_proof(&tip, &lookup).unwrap();
+        assert_eq!(values.len(), lookup.len());
+        assert!(values[3].is_none());
+
+        let entries: Vec<(String, MARFValue)> = lookup
+            .iter()
+            .zip(values.iter())
+            .filter_map(|(key, value)| value.clone().map(|v| (key.clone(), v)))
+            .collect();
+        assert_eq!(entries.len(), 8);
+        assert!(multi_proof.verify(&entries, &root_hash, &root_to_block));
+
+        // each key's proof is rebuilt exactly, and the batch is smaller than the sum of its parts
+        let mut single_proofs_len = 0;
+        for (i, (key, value)) in entries.iter().enumerate() {
+            let (single_value, single_proof) = m.get_with_proof(&tip, key).unwrap().unwrap();
+            assert_eq!(&single_value, value);
+            assert_eq!(multi_proof.key_proof(i).unwrap().0, single_proof.0);
+            single_proofs_len += single_proof.to_hex().len();
+        }
+        assert!(multi_proof.to_hex().len() < single_proofs_len);
+
+        // codec round-trip
+        let mut bytes = vec![];
+        multi_proof.consensus_serialize(&mut bytes).unwrap();
+        let decoded: TrieMerkleMultiProof<BlockHeaderHash> = read_next(&mut &bytes[..]).unwrap();
+        assert_eq!(decoded, multi_proof);
+        assert!(decoded.verify(&entries, &root_hash, &root_to_block));
+
+        // wrong values, reordered keys, and missing keys are all rejected
+        let mut bad_entries = entries.clone();
+        bad_entries[2].1 = MARFValue::from_value("nope");
+        assert!(!multi_proof.verify(&bad_entries, &root_hash, &root_to_block));
+
+        let mut swapped_entries = entries.clone();
+        swapped_entries.swap(0, 1);
+        assert!(!multi_proof.verify(&swapped_entries, &root_hash, &root_to_block));
+
+        assert!(!multi_proof.verify(&entries[1..], &root_hash, &root_to_block));
+    }
 }
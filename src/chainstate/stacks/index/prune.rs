@@ -0,0 +1,499 @@
+// Copyright (C) 2013-2020 Blocstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+/// Pruning of the tries of deep ancestors, for nodes that don't need to serve historic state.
+///
+/// A MARF trie only stores the nodes that changed in its block; everything else is reached
+/// through back-pointers into ancestor tries.  Pruning the ancestors of a tip that are more
+/// than N blocks below it keeps, in each such trie, only the nodes that some un-pruned trie
+/// (i.e. a recent block, a fork, or an unconfirmed trie) can still reach through
+/// back-pointers, plus a childless copy of its root so that its root hash can still be read
+/// when computing the ancestor hashes of new tries.  Everything else is dropped and the
+/// remaining nodes are packed together; back-pointers into the trie are rewritten to match.
+///
+/// Reads at a pruned block fail with `Error::PrunedError`, as do Merkle proofs that would
+/// need to walk through a pruned trie.  Pruning moves nodes around, so it must not be done
+/// while anything else is reading the MARF.
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::Cursor;
+
+use chainstate::burn::BLOCK_HEADER_HASH_ENCODED_SIZE;
+
+use chainstate::stacks::index::bits::{get_node_byte_len, read_nodetype, write_nodetype_bytes};
+use chainstate::stacks::index::node::{
+    clear_backptr, is_backptr, TrieNode16, TrieNode256, TrieNode4, TrieNode48, TrieNodeID,
+    TrieNodeType, TriePtr,
+};
+use chainstate::stacks::index::storage::TrieFileStorage;
+use chainstate::stacks::index::{trie_sql, Error, MarfTrieId, TrieHash, TRIEHASH_ENCODED_SIZE};
+
+use util::log;
+
+// offset of the root node in a trie blob, after the parent block hash and local block ID
+const TRIE_ROOT_PTR: u32 = BLOCK_HEADER_HASH_ENCODED_SIZE as u32 + 4;
+
+/// What pruning did
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MarfPruneSummary {
+    pub num_pruned_tries: u32,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    /// hashes of the values that no leaf refers to any longer, which the side store can drop
+    pub dead_value_hashes: Vec<String>,
+}
+
+/// Read every node in a trie blob, keyed by offset.  Nodes are packed back-to-back after the
+/// blob's header, and each one starts with its hash and then its node ID.
+fn read_trie_nodes(trie_blob: &[u8]) -> Result<BTreeMap<u32, (TrieNodeType, TrieHash)>, Error> {
+    let mut f = Cursor::new(trie_blob);
+    let mut nodes = BTreeMap::new();
+    let mut offset = TRIE_ROOT_PTR as usize;
+    while offset < trie_blob.len() {
+        let id = *trie_blob
+            .get(offset + TRIEHASH_ENCODED_SIZE)
+            .ok_or_else(|| Error::CorruptionError(format!("Truncated trie node at {}", offset)))?;
+        let ptr = TriePtr::new(clear_backptr(id), 0, offset as u32);
+        let (node, hash) = read_nodetype(&mut f, &ptr)?;
+        let next_offset = offset + get_node_byte_len(&node);
+        nodes.insert(offset as u32, (node, hash));
+        offset = next_offset;
+    }
+    Ok(nodes)
+}
+
+/// Find the given nodes and every node below them in the same trie
+fn find_subtrees(
+    nodes: &BTreeMap<u32, (TrieNodeType, TrieHash)>,
+    roots: &BTreeSet<u32>,
+) -> Result<BTreeSet<u32>, Error> {
+    let mut found = BTreeSet::new();
+    let mut frontier: Vec<u32> = roots.iter().cloned().collect();
+    while let Some(offset) = frontier.pop() {
+        if !found.insert(offset) {
+            continue;
+        }
+        let (node, _) = nodes
+            .get(&offset)
+            .ok_or_else(|| Error::CorruptionError(format!("No trie node at {}", offset)))?;
+        for ptr in node.ptrs().iter() {
+            if ptr.id() != TrieNodeID::Empty as u8 && !is_backptr(ptr.id()) {
+                frontier.push(ptr.ptr());
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// Copy of a node without its children
+fn strip_children(node: &TrieNodeType) -> TrieNodeType {
+    match node {
+        TrieNodeType::Node4(ref data) => TrieNodeType::Node4(TrieNode4::new(&data.path)),
+        TrieNodeType::Node16(ref data) => TrieNodeType::Node16(TrieNode16::new(&data.path)),
+        TrieNodeType::Node48(ref data) => TrieNodeType::Node48(TrieNode48::new(&data.path)),
+        TrieNodeType::Node256(ref data) => TrieNodeType::Node256(TrieNode256::new(&data.path)),
+        TrieNodeType::Leaf(_) => node.clone(),
+    }
+}
+
+/// Point a node's children at their new offsets.  `relocations` maps a pruned trie's local
+/// block ID to where each of its kept nodes moved; `own_relocation` does the same for the
+/// node's own trie, if it was pruned.
+fn relocate_ptrs(
+    node: &mut TrieNodeType,
+    own_relocation: Option<&HashMap<u32, u32>>,
+    relocations: &HashMap<u32, HashMap<u32, u32>>,
+) -> Result<(), Error> {
+    if node.is_leaf() {
+        return Ok(());
+    }
+    for ptr in node.ptrs_mut().iter_mut() {
+        if ptr.id() == TrieNodeID::Empty as u8 {
+            continue;
+        }
+        let relocation = if is_backptr(ptr.id()) {
+            relocations.get(&ptr.back_block)
+        } else {
+            own_relocation
+        };
+        if let Some(relocation) = relocation {
+            ptr.ptr = *relocation.get(&ptr.ptr).ok_or_else(|| {
+                Error::CorruptionError(format!(
+                    "Pruned away trie node {} in block {}",
+                    ptr.ptr, ptr.back_block
+                ))
+            })?;
+        }
+    }
+    Ok(())
+}
+
+fn write_trie_nodes(
+    header: &[u8],
+    nodes: &BTreeMap<u32, (TrieNodeType, TrieHash)>,
+) -> Result<Vec<u8>, Error> {
+    let mut f = Cursor::new(header.to_vec());
+    f.set_position(header.len() as u64);
+    for (offset, (node, hash)) in nodes.iter() {
+        if f.position() != *offset as u64 {
+            return Err(Error::CorruptionError(format!(
+                "Trie node at {} would be written at {}",
+                offset,
+                f.position()
+            )));
+        }
+        write_nodetype_bytes(&mut f, node, hash.clone())?;
+    }
+    Ok(f.into_inner())
+}
+
+impl<T: MarfTrieId> TrieFileStorage<T> {
+    /// Prune the tries of the ancestors of `tip` that are more than `keep_depth` blocks below
+    /// it (the tip is at depth 0).  Returns what was done, including the value hashes that
+    /// the side store no longer needs.  Pruning an already-pruned ancestor again drops
+    /// whatever is no longer reachable from the newly-pruned tries.
+    pub fn prune(&mut self, tip: &T, keep_depth: u32) -> Result<MarfPruneSummary, Error> {
+        if self.readonly() {
+            return Err(Error::ReadOnlyError);
+        }
+        if self.is_extending() {
+            return Err(Error::InProgressError);
+        }
+
+        // find the tries to prune, newest first
+        let mut pruned_ids = vec![];
+        let mut cur = tip.clone();
+        let mut depth = 0u32;
+        loop {
+            let block_id = self
+                .connection()
+                .get_confirmed_block_identifier(&cur)?
+                .ok_or_else(|| {
+                    if depth == 0 {
+                        Error::NotFoundError
+                    } else {
+                        Error::CorruptionError(format!("Missing ancestor trie {}", &cur))
+                    }
+                })?;
+            if depth > keep_depth {
+                pruned_ids.push(block_id);
+            }
+            let parent: T = self.connection().get_trie_parent(block_id)?;
+            if parent == T::sentinel() {
+                break;
+            }
+            cur = parent;
+            depth += 1;
+        }
+
+        let mut summary = MarfPruneSummary::default();
+        if pruned_ids.len() == 0 {
+            return Ok(summary);
+        }
+
+        let mut tx = self.transaction()?;
+        let all_ids = tx.get_all_block_identifiers()?;
+        let pruned_set: BTreeSet<u32> = pruned_ids.iter().cloned().collect();
+
+        // step 1: find the nodes of the pruned tries that the other tries point to
+        let mut targets: HashMap<u32, BTreeSet<u32>> = HashMap::new();
+        for block_id in all_ids.iter() {
+            if pruned_set.contains(block_id) {
+                continue;
+            }
+            let trie_blob = tx.read_trie_blob(*block_id)?;
+            for (node, _) in read_trie_nodes(&trie_blob)?.values() {
+                for ptr in node.ptrs().iter() {
+                    if is_backptr(ptr.id()) && pruned_set.contains(&ptr.back_block) {
+                        targets
+                            .entry(ptr.back_block)
+                            .or_insert_with(BTreeSet::new)
+                            .insert(ptr.ptr());
+                    }
+                }
+            }
+        }
+
+        // step 2: from newest to oldest, decide which nodes of each pruned trie to keep, and
+        // where they'll go.  Kept nodes can point to nodes in older pruned tries, which must
+        // then be kept too.
+        let mut kept_nodes: HashMap<u32, BTreeSet<u32>> = HashMap::new();
+        let mut stripped_roots = BTreeSet::new();
+        let mut relocations: HashMap<u32, HashMap<u32, u32>> = HashMap::new();
+        let mut maybe_dead_values = BTreeSet::new();
+        for block_id in pruned_ids.iter() {
+            let trie_blob = tx.read_trie_blob(*block_id)?;
+            let nodes = read_trie_nodes(&trie_blob)?;
+            let mut kept = match targets.remove(block_id) {
+                Some(block_targets) => find_subtrees(&nodes, &block_targets)?,
+                None => BTreeSet::new(),
+            };
+            // the root stays (without its children, unless they're needed), so its hash can
+            // still be read
+            if kept.insert(TRIE_ROOT_PTR) {
+                stripped_roots.insert(*block_id);
+            }
+
+            let mut relocation = HashMap::new();
+            let mut next_offset = TRIE_ROOT_PTR;
+            for (offset, (node, _)) in nodes.iter() {
+                if !kept.contains(offset) {
+                    if let TrieNodeType::Leaf(ref leaf) = node {
+                        maybe_dead_values.insert(leaf.data.to_hex());
+                    }
+                    continue;
+                }
+                if !(*offset == TRIE_ROOT_PTR && stripped_roots.contains(block_id)) {
+                    for ptr in node.ptrs().iter() {
+                        if is_backptr(ptr.id()) && pruned_set.contains(&ptr.back_block) {
+                            targets
+                                .entry(ptr.back_block)
+                                .or_insert_with(BTreeSet::new)
+                                .insert(ptr.ptr());
+                        }
+                    }
+                }
+                relocation.insert(*offset, next_offset);
+                next_offset += get_node_byte_len(node) as u32;
+            }
+
+            kept_nodes.insert(*block_id, kept);
+            relocations.insert(*block_id, relocation);
+        }
+
+        // step 3: rewrite every trie whose nodes moved or that points to nodes that moved
+        for block_id in all_ids.iter() {
+            let trie_blob = tx.read_trie_blob(*block_id)?;
+            let nodes = read_trie_nodes(&trie_blob)?;
+            let own_relocation = relocations.get(block_id);
+
+            let mut new_nodes = BTreeMap::new();
+            for (offset, (node, hash)) in nodes.into_iter() {
+                let mut node = match kept_nodes.get(block_id) {
+                    Some(kept) if !kept.contains(&offset) => {
+                        continue;
+                    }
+                    Some(_) if offset == TRIE_ROOT_PTR && stripped_roots.contains(block_id) => {
+                        strip_children(&node)
+                    }
+                    _ => node,
+                };
+                relocate_ptrs(&mut node, own_relocation, &relocations)?;
+                if let TrieNodeType::Leaf(ref leaf) = node {
+                    maybe_dead_values.remove(&leaf.data.to_hex());
+                }
+                let new_offset = match own_relocation {
+                    Some(relocation) => relocation[&offset],
+                    None => offset,
+                };
+                new_nodes.insert(new_offset, (node, hash));
+            }
+
+            let new_trie_blob =
+                write_trie_nodes(&trie_blob[0..TRIE_ROOT_PTR as usize], &new_nodes)?;
+            if new_trie_blob != trie_blob {
+                tx.update_trie_blob(*block_id, &new_trie_blob)?;
+            }
+            if pruned_set.contains(block_id) {
+                tx.set_trie_pruned(*block_id)?;
+            }
+
+            summary.bytes_before += trie_blob.len() as u64;
+            summary.bytes_after += new_trie_blob.len() as u64;
+        }
+
+        summary.num_pruned_tries = pruned_ids.len() as u32;
+        summary.dead_value_hashes = maybe_dead_values.into_iter().collect();
+        if trie_sql::has_fork_storage(tx.sqlite_tx())? {
+            trie_sql::delete_fork_storage_values(tx.sqlite_tx(), &summary.dead_value_hashes)?;
+        }
+
+        tx.commit_tx();
+
+        debug!(
+            "Pruned {} tries below {} (depth {}): {} bytes -> {} bytes, {} dead values",
+            summary.num_pruned_tries,
+            tip,
+            keep_depth,
+            summary.bytes_before,
+            summary.bytes_after,
+            summary.dead_value_hashes.len()
+        );
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::fs;
+
+    use chainstate::burn::BlockHeaderHash;
+    use chainstate::stacks::index::marf::{MarfConnection, MARF};
+    use chainstate::stacks::index::MARFValue;
+
+    use rusqlite::{types::ToSql, OptionalExtension, NO_PARAMS};
+
+    use util::db::SQL_FORK_STORAGE_TABLE;
+
+    fn insert_value(marf: &mut MARF<BlockHeaderHash>, key: &str, value: &str) {
+        marf.insert(key, MARFValue::from_value(value)).unwrap();
+        marf.sqlite_conn()
+            .execute(
+                "INSERT OR REPLACE INTO __fork_storage (value_hash, value) VALUES (?1, ?2)",
+                &[&MARFValue::from_value(value).to_hex() as &dyn ToSql, &value],
+            )
+            .unwrap();
+    }
+
+    fn has_side_value(marf: &MARF<BlockHeaderHash>, value: &str) -> bool {
+        marf.sqlite_conn()
+            .query_row(
+                "SELECT 1 FROM __fork_storage WHERE value_hash = ?1",
+                &[&MARFValue::from_value(value).to_hex() as &dyn ToSql],
+                |_row| (),
+            )
+            .optional()
+            .unwrap()
+            .is_some()
+    }
+
+    #[test]
+    fn test_marf_prune() {
+        let path = "/tmp/test_marf_prune.sqlite";
+        if fs::metadata(path).is_ok() {
+            fs::remove_file(path).unwrap();
+        }
+        let mut marf: MARF<BlockHeaderHash> = MARF::from_path(path).unwrap();
+        marf.sqlite_conn()
+            .execute(SQL_FORK_STORAGE_TABLE, NO_PARAMS)
+            .unwrap();
+
+        // block i inserts key-i, and overwrites "shared"
+        let mut parent = BlockHeaderHash::sentinel();
+        for i in 0..8u8 {
+            let block = BlockHeaderHash([i + 1; 32]);
+            marf.begin(&parent, &block).unwrap();
+            insert_value(&mut marf, &format!("key-{}", i), &format!("value-{}", i));
+            insert_value(&mut marf, "shared", &format!("shared-{}", i));
+            marf.commit().unwrap();
+            parent = block;
+        }
+        let tip = parent;
+
+        // a fork off of the second block
+        let fork_block = BlockHeaderHash([0xfe; 32]);
+        marf.begin(&BlockHeaderHash([2; 32]), &fork_block).unwrap();
+        insert_value(&mut marf, "fork-key", "fork-value");
+        marf.commit().unwrap();
+
+        let root_hashes: Vec<_> = (0..8u8)
+            .map(|i| {
+                marf.get_root_hash_at(&BlockHeaderHash([i + 1; 32]))
+                    .unwrap()
+            })
+            .collect();
+
+        let summary = marf.prune(&tip, 2).unwrap();
+        assert_eq!(summary.num_pruned_tries, 5);
+        assert!(summary.bytes_after < summary.bytes_before);
+
+        // state at the tip, at the retained blocks, and on the fork is intact
+        for i in 0..8u8 {
+            assert_eq!(
+                marf.get(&tip, &format!("key-{}", i)).unwrap(),
+                Some(MARFValue::from_value(&format!("value-{}", i)))
+            );
+        }
+        assert_eq!(
+            marf.get(&tip, "shared").unwrap(),
+            Some(MARFValue::from_value("shared-7"))
+        );
+        assert_eq!(
+            marf.get(&BlockHeaderHash([6; 32]), "shared").unwrap(),
+            Some(MARFValue::from_value("shared-5"))
+        );
+        assert_eq!(
+            marf.get(&fork_block, "shared").unwrap(),
+            Some(MARFValue::from_value("shared-1"))
+        );
+        assert_eq!(
+            marf.get(&fork_block, "key-0").unwrap(),
+            Some(MARFValue::from_value("value-0"))
+        );
+
+        // state at pruned blocks is gone, but their root hashes are still there
+        match marf.get(&BlockHeaderHash([3; 32]), "shared") {
+            Err(Error::PrunedError) => {}
+            x => panic!("Expected pruned error, got {:?}", &x),
+        }
+        assert!(marf.is_block_pruned(&BlockHeaderHash([1; 32])).unwrap());
+        assert!(!marf.is_block_pruned(&BlockHeaderHash([6; 32])).unwrap());
+        assert!(!marf.is_block_pruned(&fork_block).unwrap());
+        for i in 0..8u8 {
+            assert_eq!(
+                marf.get_root_hash_at(&BlockHeaderHash([i + 1; 32]))
+                    .unwrap(),
+                root_hashes[i as usize]
+            );
+        }
+
+        // overwritten values only the pruned blocks referred to are dropped from the side store
+        for i in 2..5 {
+            assert!(!has_side_value(&marf, &format!("shared-{}", i)));
+        }
+        assert!(summary
+            .dead_value_hashes
+            .contains(&MARFValue::from_value("shared-2").to_hex()));
+        assert!(has_side_value(&marf, "shared-1"));
+        assert!(has_side_value(&marf, "shared-5"));
+        assert!(has_side_value(&marf, "value-0"));
+        assert!(has_side_value(&marf, "fork-value"));
+
+        // the chain can still be extended, and pruned again
+        let next_block = BlockHeaderHash([9; 32]);
+        marf.begin(&tip, &next_block).unwrap();
+        match marf.check_ancestor_block_hash(&BlockHeaderHash([3; 32])) {
+            Err(Error::PrunedError) => {}
+            x => panic!("Expected pruned error, got {:?}", &x),
+        }
+        marf.check_ancestor_block_hash(&BlockHeaderHash([7; 32]))
+            .unwrap();
+        insert_value(&mut marf, "shared", "shared-8");
+        marf.commit().unwrap();
+
+        let summary = marf.prune(&next_block, 2).unwrap();
+        assert_eq!(summary.num_pruned_tries, 6);
+        assert!(summary
+            .dead_value_hashes
+            .contains(&MARFValue::from_value("shared-5").to_hex()));
+        for i in 0..8u8 {
+            assert_eq!(
+                marf.get(&next_block, &format!("key-{}", i)).unwrap(),
+                Some(MARFValue::from_value(&format!("value-{}", i)))
+            );
+        }
+        assert_eq!(
+            marf.get(&next_block, "shared").unwrap(),
+            Some(MARFValue::from_value("shared-8"))
+        );
+        assert_eq!(
+            marf.get(&fork_block, "key-1").unwrap(),
+            Some(MARFValue::from_value("value-1"))
+        );
+        assert!(marf.is_block_pruned(&BlockHeaderHash([6; 32])).unwrap());
+    }
+}
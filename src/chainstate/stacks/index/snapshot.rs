@@ -0,0 +1,910 @@
+// Copyright (C) 2013-2020 Blocstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+/// Portable snapshots of a MARF's state at a given chain tip.
+///
+/// A snapshot holds the trie of the tip and of each of its ancestors (their local block IDs
+/// are kept, since back-pointers refer to them), followed by the `__fork_storage` rows whose
+/// value hashes appear in those tries.  Tries on other forks are left out.  If the MARF backs
+/// a Clarity database, the snapshot also carries the rows of its side store: the
+/// `data_table` values whose hashes appear in the tries, and the `metadata_table` rows of the
+/// exported blocks.
+///
+/// On import, every node hash of every trie is recomputed from the node and its children,
+/// and each root hash from its node hash and the root hashes of its skip-list ancestors, so
+/// a snapshot whose tip root hash matches a trusted one can't carry altered trie contents.
+/// The same goes for `__fork_storage` and Clarity `data_table` rows, which are checked against
+/// the value hashes in the tries.
+///
+/// Clarity `metadata_table` rows are NOT covered by the root hash.  They hold each contract's
+/// source, parsed AST and type-checker analysis, of which the MARF only commits to the hash of
+/// the source.  So they are not stored on import: they are only checked to belong to an
+/// imported block, and handed back to the caller, which must check each contract's source
+/// against the MARF and re-derive the rest from it (see
+/// `ClarityInstance::import_contract_metadata()`).
+///
+/// Layout (integers are big-endian):
+///   magic (8 bytes) | version (u32) | tip (32 bytes) | tip root hash (32 bytes) |
+///   number of tries (u32) |
+///   per trie, from the first block to the tip:
+///     block ID (u32) | block hash (32 bytes) | root hash (32 bytes) | length (u64) | trie blob |
+///   number of side-store rows (u32) |
+///   per row: value hash length (u32) | value hash | value length (u32) | value
+///   number of Clarity data rows (u32) |
+///   per row: value hash length (u32) | value hash | value length (u32) | value
+///   number of Clarity metadata rows (u32) |
+///   per row: block hash (32 bytes) | key length (u32) | key | value length (u32) | value
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::io::{Cursor, Read, Write};
+
+use chainstate::burn::BLOCK_HEADER_HASH_ENCODED_SIZE;
+
+use chainstate::stacks::index::bits::{
+    get_leaf_hash, get_nodetype_hash_bytes, read_hash_bytes, read_nodetype,
+};
+use chainstate::stacks::index::node::{is_backptr, TrieNodeID, TrieNodeType, TriePtr};
+use chainstate::stacks::index::storage::TrieFileStorage;
+use chainstate::stacks::index::{trie_sql, BlockMap, Error, MARFValue, MarfTrieId, TrieHash};
+
+use rusqlite::{types::ToSql, Connection, OptionalExtension, NO_PARAMS};
+
+use util::db::SQL_FORK_STORAGE_TABLE;
+use util::log;
+
+pub const MARF_SNAPSHOT_MAGIC: &[u8; 8] = b"MARFSNAP";
+pub const MARF_SNAPSHOT_VERSION: u32 = 2;
+
+// upper bound on a single side-store value hash or value, so a bad snapshot can't make us
+// allocate without bound
+const MARF_SNAPSHOT_MAX_FIELD_LEN: u32 = 1 << 30;
+
+/// What a snapshot contained
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarfSnapshotSummary<T: MarfTrieId> {
+    pub tip: T,
+    pub tip_root_hash: TrieHash,
+    pub num_tries: u32,
+    pub num_side_rows: u32,
+    pub num_clarity_data_rows: u32,
+    pub num_clarity_metadata_rows: u32,
+}
+
+/// A Clarity `metadata_table` row read from a snapshot, which import_snapshot() leaves to the
+/// caller to verify.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClarityMetadataRow<T: MarfTrieId> {
+    pub block_hash: T,
+    pub key: String,
+    pub value: String,
+}
+
+fn write_u32<W: Write>(w: &mut W, v: u32) -> Result<(), Error> {
+    w.write_all(&v.to_be_bytes()).map_err(Error::IOError)
+}
+
+fn write_u64<W: Write>(w: &mut W, v: u64) -> Result<(), Error> {
+    w.write_all(&v.to_be_bytes()).map_err(Error::IOError)
+}
+
+fn write_field<W: Write>(w: &mut W, field: &[u8]) -> Result<(), Error> {
+    write_u32(w, field.len() as u32)?;
+    w.write_all(field).map_err(Error::IOError)
+}
+
+fn read_bytes<R: Read>(r: &mut R, len: usize) -> Result<Vec<u8>, Error> {
+    let mut buf = Vec::new();
+    r.take(len as u64)
+        .read_to_end(&mut buf)
+        .map_err(Error::IOError)?;
+    if buf.len() != len {
+        return Err(Error::CorruptionError(format!(
+            "Truncated snapshot: expected {} bytes, got {}",
+            len,
+            buf.len()
+        )));
+    }
+    Ok(buf)
+}
+
+fn read_u32<R: Read>(r: &mut R) -> Result<u32, Error> {
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(&read_bytes(r, 4)?);
+    Ok(u32::from_be_bytes(bytes))
+}
+
+fn read_u64<R: Read>(r: &mut R) -> Result<u64, Error> {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&read_bytes(r, 8)?);
+    Ok(u64::from_be_bytes(bytes))
+}
+
+fn read_field<R: Read>(r: &mut R) -> Result<String, Error> {
+    let len = read_u32(r)?;
+    if len > MARF_SNAPSHOT_MAX_FIELD_LEN {
+        return Err(Error::CorruptionError(format!(
+            "Snapshot field is too long ({} bytes)",
+            len
+        )));
+    }
+    String::from_utf8(read_bytes(r, len as usize)?)
+        .map_err(|_| Error::CorruptionError("Snapshot field is not UTF-8".to_string()))
+}
+
+/// Local block IDs of the tries imported so far, so back-pointers in the next trie can be
+/// hashed the same way the MARF hashes them.
+struct SnapshotBlockMap<T: MarfTrieId> {
+    block_hashes: HashMap<u32, T>,
+}
+
+impl<T: MarfTrieId> BlockMap for SnapshotBlockMap<T> {
+    type TrieId = T;
+
+    fn get_block_hash(&self, id: u32) -> Result<T, Error> {
+        self.block_hashes
+            .get(&id)
+            .cloned()
+            .ok_or(Error::NotFoundError)
+    }
+
+    fn get_block_hash_caching(&mut self, id: u32) -> Result<&T, Error> {
+        self.block_hashes.get(&id).ok_or(Error::NotFoundError)
+    }
+}
+
+/// Recompute the hash of the node at `ptr` in a trie blob, checking the hashes of its children
+/// in the same trie against the ones stored with them along the way.  As in
+/// `TrieStorageConnection::write_children_hashes`, a back-pointed child contributes the hash of
+/// the block it lives in.  Returns the recomputed hash and the stored one.
+fn check_node_hashes<T: MarfTrieId>(
+    f: &mut Cursor<&[u8]>,
+    ptr: &TriePtr,
+    block_map: &mut SnapshotBlockMap<T>,
+) -> Result<(TrieHash, TrieHash), Error> {
+    let (node, stored_hash) = read_nodetype(f, ptr)?;
+    let node_hash = match node {
+        TrieNodeType::Leaf(ref leaf) => get_leaf_hash(leaf),
+        _ => {
+            let mut child_hashes = Vec::with_capacity(node.ptrs().len());
+            for child_ptr in node.ptrs().iter() {
+                if child_ptr.id() == TrieNodeID::Empty as u8 {
+                    child_hashes.push(TrieHash::from_data(&[]));
+                } else if is_backptr(child_ptr.id()) {
+                    let block_hash =
+                        block_map
+                            .get_block_hash(child_ptr.back_block())
+                            .map_err(|_| {
+                                Error::CorruptionError(format!(
+                                    "Back-pointer to unknown trie {}",
+                                    child_ptr.back_block()
+                                ))
+                            })?;
+                    child_hashes.push(TrieHash(block_hash.to_bytes()));
+                } else {
+                    // nodes are stored breadth-first, so children always come after their
+                    // parents.  This also rules out cycles.
+                    if child_ptr.ptr() <= ptr.ptr() {
+                        return Err(Error::CorruptionError(format!(
+                            "Trie node at {} points back to {}",
+                            ptr.ptr(),
+                            child_ptr.ptr()
+                        )));
+                    }
+                    let (child_hash, stored_child_hash) =
+                        check_node_hashes(f, child_ptr, block_map)?;
+                    if child_hash != stored_child_hash {
+                        return Err(Error::CorruptionError(format!(
+                            "Trie node at {} has hash {}, but its contents hash to {}",
+                            child_ptr.ptr(),
+                            &stored_child_hash,
+                            &child_hash
+                        )));
+                    }
+                    child_hashes.push(child_hash);
+                }
+            }
+            get_nodetype_hash_bytes::<T, _>(&node, &child_hashes, block_map)
+        }
+    };
+    Ok((node_hash, stored_hash))
+}
+
+/// Recompute a trie's root hash from its nodes and the root hashes of its skip-list ancestors
+/// (the tries 1, 2, 4, 8, ... blocks before it), and check it and every node hash beneath it
+/// against the hashes stored in the blob.  Returns the root hash.
+fn check_trie_hashes<T: MarfTrieId>(
+    trie_blob: &[u8],
+    block_map: &mut SnapshotBlockMap<T>,
+    ancestor_root_hashes: &[TrieHash],
+) -> Result<TrieHash, Error> {
+    let mut f = Cursor::new(trie_blob);
+    let root_ptr = TriePtr::new(
+        TrieNodeID::Node256 as u8,
+        0,
+        BLOCK_HEADER_HASH_ENCODED_SIZE as u32 + 4,
+    );
+    let (node_hash, stored_root_hash) = check_node_hashes(&mut f, &root_ptr, block_map)?;
+
+    // same as Trie::get_trie_root_hash()
+    let root_hash = if ancestor_root_hashes.len() == 0 {
+        node_hash
+    } else {
+        let mut hashes = Vec::with_capacity(ancestor_root_hashes.len() + 1);
+        hashes.push(node_hash);
+        hashes.extend_from_slice(ancestor_root_hashes);
+        TrieHash::from_data_array(hashes.as_slice())
+    };
+
+    if root_hash != stored_root_hash {
+        return Err(Error::CorruptionError(format!(
+            "Trie has root hash {}, but its contents hash to {}",
+            &stored_root_hash, &root_hash
+        )));
+    }
+    Ok(root_hash)
+}
+
+/// Find the values of all leaves stored in a trie blob.  Back-pointed children live in
+/// ancestor tries, and are found when those tries are scanned.
+fn get_trie_leaf_values(trie_blob: &[u8]) -> Result<Vec<MARFValue>, Error> {
+    let mut f = Cursor::new(trie_blob);
+    let mut values = vec![];
+    let mut frontier = VecDeque::new();
+    frontier.push_back(TriePtr::new(
+        TrieNodeID::Node256 as u8,
+        0,
+        BLOCK_HEADER_HASH_ENCODED_SIZE as u32 + 4,
+    ));
+
+    while let Some(ptr) = frontier.pop_front() {
+        let (node, _) = read_nodetype(&mut f, &ptr)?;
+        match node {
+            TrieNodeType::Leaf(ref leaf) => {
+                values.push(leaf.data.clone());
+            }
+            _ => {
+                for child_ptr in node.ptrs().iter() {
+                    if child_ptr.id() != TrieNodeID::Empty as u8 && !is_backptr(child_ptr.id()) {
+                        frontier.push_back(child_ptr.clone());
+                    }
+                }
+            }
+        }
+    }
+    Ok(values)
+}
+
+impl<T: MarfTrieId> TrieFileStorage<T> {
+    /// Write a snapshot of the MARF as of the given (confirmed) tip.  If the MARF backs a
+    /// Clarity database, pass its side store to include that too.
+    pub fn export_snapshot<W: Write>(
+        &mut self,
+        tip: &T,
+        clarity_side_store: Option<&Connection>,
+        w: &mut W,
+    ) -> Result<MarfSnapshotSummary<T>, Error> {
+        // walk back to the first block to find the tries to export
+        let mut chain = vec![];
+        let mut cur = tip.clone();
+        loop {
+            let block_id = self
+                .connection()
+                .get_confirmed_block_identifier(&cur)?
+                .ok_or_else(|| {
+                    if chain.len() == 0 {
+                        Error::NotFoundError
+                    } else {
+                        Error::CorruptionError(format!("Missing ancestor trie {}", &cur))
+                    }
+                })?;
+            if self.is_block_pruned(&cur)? {
+                // a snapshot must carry the full state of every trie
+                error!(
+                    "Cannot export a MARF snapshot: trie {} has been pruned",
+                    &cur
+                );
+                return Err(Error::PrunedError);
+            }
+            let parent: T = self.connection().get_trie_parent(block_id)?;
+            chain.push((block_id, cur));
+            if parent == T::sentinel() {
+                break;
+            }
+            cur = parent;
+        }
+        chain.reverse();
+
+        let tip_root_hash = self.connection().read_block_root_hash(tip)?;
+
+        w.write_all(MARF_SNAPSHOT_MAGIC).map_err(Error::IOError)?;
+        write_u32(w, MARF_SNAPSHOT_VERSION)?;
+        w.write_all(tip.as_bytes()).map_err(Error::IOError)?;
+        w.write_all(tip_root_hash.as_bytes())
+            .map_err(Error::IOError)?;
+        write_u32(w, chain.len() as u32)?;
+
+        let mut value_hashes = BTreeSet::new();
+        for (block_id, block_hash) in chain.iter() {
+            let root_hash = self.connection().read_block_root_hash(block_hash)?;
+            let trie_blob = self.connection().read_trie_blob(*block_id)?;
+
+            write_u32(w, *block_id)?;
+            w.write_all(block_hash.as_bytes()).map_err(Error::IOError)?;
+            w.write_all(root_hash.as_bytes()).map_err(Error::IOError)?;
+            write_u64(w, trie_blob.len() as u64)?;
+            w.write_all(&trie_blob).map_err(Error::IOError)?;
+
+            for value in get_trie_leaf_values(&trie_blob)?.into_iter() {
+                value_hashes.insert(value.to_hex());
+            }
+        }
+
+        let mut side_rows = vec![];
+        if trie_sql::has_fork_storage(self.sqlite_conn())? {
+            for value_hash in value_hashes.iter() {
+                let value_opt: Option<String> = self
+                    .sqlite_conn()
+                    .query_row(
+                        "SELECT value FROM __fork_storage WHERE value_hash = ?1",
+                        &[value_hash as &dyn ToSql],
+                        |row| row.get(0),
+                    )
+                    .optional()?;
+                if let Some(value) = value_opt {
+                    side_rows.push((value_hash.clone(), value));
+                }
+            }
+        }
+
+        write_u32(w, side_rows.len() as u32)?;
+        for (value_hash, value) in side_rows.iter() {
+            write_field(w, value_hash.as_bytes())?;
+            write_field(w, value.as_bytes())?;
+        }
+
+        let mut clarity_data_rows = vec![];
+        let mut clarity_metadata_rows = vec![];
+        if let Some(conn) = clarity_side_store {
+            for value_hash in value_hashes.iter() {
+                let value_opt: Option<String> = conn
+                    .query_row(
+                        "SELECT value FROM data_table WHERE key = ?1",
+                        &[value_hash as &dyn ToSql],
+                        |row| row.get(0),
+                    )
+                    .optional()?;
+                if let Some(value) = value_opt {
+                    clarity_data_rows.push((value_hash.clone(), value));
+                }
+            }
+
+            let mut stmt =
+                conn.prepare("SELECT key, value FROM metadata_table WHERE blockhash = ?1")?;
+            for (_, block_hash) in chain.iter() {
+                let rows = stmt.query_map(&[block_hash as &dyn ToSql], |row| {
+                    let key: String = row.get(0);
+                    let value: String = row.get(1);
+                    (key, value)
+                })?;
+                for row in rows {
+                    let (key, value) = row?;
+                    clarity_metadata_rows.push((block_hash.clone(), key, value));
+                }
+            }
+        }
+
+        write_u32(w, clarity_data_rows.len() as u32)?;
+        for (value_hash, value) in clarity_data_rows.iter() {
+            write_field(w, value_hash.as_bytes())?;
+            write_field(w, value.as_bytes())?;
+        }
+
+        write_u32(w, clarity_metadata_rows.len() as u32)?;
+        for (block_hash, key, value) in clarity_metadata_rows.iter() {
+            w.write_all(block_hash.as_bytes()).map_err(Error::IOError)?;
+            write_field(w, key.as_bytes())?;
+            write_field(w, value.as_bytes())?;
+        }
+
+        debug!(
+            "Exported MARF snapshot at {} ({} tries, {} side-store rows, {} Clarity data rows, {} Clarity metadata rows)",
+            tip,
+            chain.len(),
+            side_rows.len(),
+            clarity_data_rows.len(),
+            clarity_metadata_rows.len()
+        );
+
+        Ok(MarfSnapshotSummary {
+            tip: tip.clone(),
+            tip_root_hash,
+            num_tries: chain.len() as u32,
+            num_side_rows: side_rows.len() as u32,
+            num_clarity_data_rows: clarity_data_rows.len() as u32,
+            num_clarity_metadata_rows: clarity_metadata_rows.len() as u32,
+        })
+    }
+
+    /// Load a snapshot into this (empty) MARF.  Each trie's node and root hashes are recomputed
+    /// from its contents and its ancestors' root hashes, and each side-store value is checked
+    /// against its hash.  If `expected_root_hash` is given (i.e. from a trusted block header),
+    /// the tip's root hash must match it; otherwise, the snapshot is only checked to be
+    /// internally consistent.  Clarity side-store rows are loaded into `clarity_side_store` if
+    /// given, and skipped otherwise.  Clarity metadata rows are not stored, but returned for the
+    /// caller to verify (see the module docs).  Nothing is stored unless the whole snapshot
+    /// checks out.
+    pub fn import_snapshot<R: Read>(
+        &mut self,
+        r: &mut R,
+        clarity_side_store: Option<&mut Connection>,
+        expected_root_hash: Option<&TrieHash>,
+    ) -> Result<(MarfSnapshotSummary<T>, Vec<ClarityMetadataRow<T>>), Error> {
+        if self.connection().count_all_tries()? > 0 {
+            error!("Refusing to import a MARF snapshot into a non-empty MARF");
+            return Err(Error::ExistsError);
+        }
+
+        let magic = read_bytes(r, MARF_SNAPSHOT_MAGIC.len())?;
+        if magic[..] != MARF_SNAPSHOT_MAGIC[..] {
+            return Err(Error::CorruptionError(
+                "Not a MARF snapshot: bad magic".to_string(),
+            ));
+        }
+        let version = read_u32(r)?;
+        if version != MARF_SNAPSHOT_VERSION {
+            return Err(Error::CorruptionError(format!(
+                "Unsupported MARF snapshot version {}",
+                version
+            )));
+        }
+
+        let tip = T::from_bytes(read_hash_bytes(r)?);
+        let tip_root_hash = TrieHash(read_hash_bytes(r)?);
+        if let Some(expected_root_hash) = expected_root_hash {
+            if *expected_root_hash != tip_root_hash {
+                return Err(Error::CorruptionError(format!(
+                    "Snapshot root hash {} does not match expected root hash {}",
+                    &tip_root_hash, expected_root_hash
+                )));
+            }
+        }
+
+        let num_tries = read_u32(r)?;
+        if num_tries == 0 {
+            return Err(Error::CorruptionError(
+                "MARF snapshot has no tries".to_string(),
+            ));
+        }
+
+        let mut tx = self.transaction()?;
+        let mut block_map = SnapshotBlockMap {
+            block_hashes: HashMap::new(),
+        };
+        let mut root_hashes: Vec<TrieHash> = vec![];
+        let mut last_block_hash = T::sentinel();
+        let mut last_root_hash = TrieHash([0u8; 32]);
+        for height in 0..num_tries {
+            let block_id = read_u32(r)?;
+            let block_hash = T::from_bytes(read_hash_bytes(r)?);
+            let root_hash = TrieHash(read_hash_bytes(r)?);
+            let trie_len = read_u64(r)?;
+            if trie_len < BLOCK_HEADER_HASH_ENCODED_SIZE as u64 + 4
+                || trie_len > u32::max_value() as u64
+            {
+                return Err(Error::CorruptionError(format!(
+                    "Invalid trie length {} for {}",
+                    trie_len, &block_hash
+                )));
+            }
+            let trie_blob = read_bytes(r, trie_len as usize)?;
+
+            // tries must form a single chain from the first block to the tip
+            let mut parent_hash_bytes = [0u8; 32];
+            parent_hash_bytes.copy_from_slice(&trie_blob[0..BLOCK_HEADER_HASH_ENCODED_SIZE]);
+            let parent = T::from_bytes(parent_hash_bytes);
+            if parent != last_block_hash {
+                return Err(Error::CorruptionError(format!(
+                    "Trie {} is built on {}, not {}",
+                    &block_hash, &parent, &last_block_hash
+                )));
+            }
+
+            if block_map.block_hashes.contains_key(&block_id) {
+                return Err(Error::CorruptionError(format!(
+                    "Trie {} reuses block ID {}",
+                    &block_hash, block_id
+                )));
+            }
+
+            // the trie at height h mixes in the root hashes of the tries at heights
+            // h - 1, h - 2, h - 4, ...
+            let mut ancestor_root_hashes = vec![];
+            let mut log_depth = 0;
+            while log_depth < 32 && (1u32 << log_depth) <= height {
+                ancestor_root_hashes
+                    .push(root_hashes[(height - (1u32 << log_depth)) as usize].clone());
+                log_depth += 1;
+            }
+
+            let trie_root_hash =
+                check_trie_hashes(&trie_blob, &mut block_map, &ancestor_root_hashes).map_err(
+                    |e| match e {
+                        Error::CorruptionError(msg) => {
+                            Error::CorruptionError(format!("Trie {}: {}", &block_hash, msg))
+                        }
+                        e => e,
+                    },
+                )?;
+            if trie_root_hash != root_hash {
+                return Err(Error::CorruptionError(format!(
+                    "Root hash mismatch for {}: snapshot says {}, trie has {}",
+                    &block_hash, &root_hash, &trie_root_hash
+                )));
+            }
+
+            tx.write_trie_blob_with_id(block_id, &block_hash, &trie_blob)?;
+
+            block_map.block_hashes.insert(block_id, block_hash.clone());
+            root_hashes.push(root_hash.clone());
+            last_block_hash = block_hash;
+            last_root_hash = root_hash;
+        }
+
+        if last_block_hash != tip || last_root_hash != tip_root_hash {
+            return Err(Error::CorruptionError(format!(
+                "Snapshot tip {} does not match its last trie {}",
+                &tip, &last_block_hash
+            )));
+        }
+
+        let num_side_rows = read_u32(r)?;
+        if num_side_rows > 0 {
+            tx.sqlite_tx().execute(SQL_FORK_STORAGE_TABLE, NO_PARAMS)?;
+        }
+        for _ in 0..num_side_rows {
+            let value_hash = read_field(r)?;
+            let value = read_field(r)?;
+            if MARFValue::from_value(&value).to_hex() != value_hash {
+                return Err(Error::CorruptionError(format!(
+                    "Side-store value does not match its hash {}",
+                    &value_hash
+                )));
+            }
+            tx.sqlite_tx().execute(
+                "INSERT OR REPLACE INTO __fork_storage (value_hash, value) VALUES (?1, ?2)",
+                &[&value_hash as &dyn ToSql, &value],
+            )?;
+        }
+
+        let mut clarity_tx_opt = match clarity_side_store {
+            Some(conn) => Some(conn.transaction()?),
+            None => None,
+        };
+
+        let num_clarity_data_rows = read_u32(r)?;
+        for _ in 0..num_clarity_data_rows {
+            let value_hash = read_field(r)?;
+            let value = read_field(r)?;
+            if MARFValue::from_value(&value).to_hex() != value_hash {
+                return Err(Error::CorruptionError(format!(
+                    "Clarity side-store value does not match its hash {}",
+                    &value_hash
+                )));
+            }
+            if let Some(clarity_tx) = clarity_tx_opt.as_mut() {
+                clarity_tx.execute(
+                    "REPLACE INTO data_table (key, value) VALUES (?1, ?2)",
+                    &[&value_hash as &dyn ToSql, &value],
+                )?;
+            }
+        }
+
+        let num_clarity_metadata_rows = read_u32(r)?;
+        let mut clarity_metadata_rows = vec![];
+        for _ in 0..num_clarity_metadata_rows {
+            let block_hash = T::from_bytes(read_hash_bytes(r)?);
+            let key = read_field(r)?;
+            let value = read_field(r)?;
+            if !block_map
+                .block_hashes
+                .values()
+                .any(|imported| *imported == block_hash)
+            {
+                return Err(Error::CorruptionError(format!(
+                    "Clarity metadata row for {} belongs to no imported trie",
+                    &block_hash
+                )));
+            }
+            clarity_metadata_rows.push(ClarityMetadataRow {
+                block_hash,
+                key,
+                value,
+            });
+        }
+
+        if let Some(clarity_tx) = clarity_tx_opt {
+            clarity_tx.commit()?;
+        }
+        tx.commit_tx();
+
+        debug!(
+            "Imported MARF snapshot at {} ({} tries, {} side-store rows, {} Clarity data rows, {} Clarity metadata rows)",
+            &tip, num_tries, num_side_rows, num_clarity_data_rows, num_clarity_metadata_rows
+        );
+
+        let summary = MarfSnapshotSummary {
+            tip,
+            tip_root_hash,
+            num_tries,
+            num_side_rows,
+            num_clarity_data_rows,
+            num_clarity_metadata_rows,
+        };
+        Ok((summary, clarity_metadata_rows))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::fs;
+
+    use chainstate::burn::BlockHeaderHash;
+    use chainstate::stacks::index::marf::{MarfConnection, MARF};
+
+    use chainstate::stacks::StacksBlockId;
+    use vm::database::SqliteConnection;
+
+    /// Make a MARF with a chain of `num_blocks` blocks, plus a fork off of the second block.
+    /// Block i inserts key-i, whose value is also written to the side store.
+    fn make_test_marf(path: &str, num_blocks: u8) -> (MARF<BlockHeaderHash>, BlockHeaderHash) {
+        if fs::metadata(path).is_ok() {
+            fs::remove_file(path).unwrap();
+        }
+        let mut marf = MARF::from_path(path).unwrap();
+        marf.sqlite_conn()
+            .execute(SQL_FORK_STORAGE_TABLE, NO_PARAMS)
+            .unwrap();
+
+        let mut parent = BlockHeaderHash::sentinel();
+        for i in 0..num_blocks {
+            let block = BlockHeaderHash([i + 1; 32]);
+            let value = format!("value-{}", i);
+            marf.begin(&parent, &block).unwrap();
+            marf.insert(&format!("key-{}", i), MARFValue::from_value(&value))
+                .unwrap();
+            marf.commit().unwrap();
+            marf.sqlite_conn()
+                .execute(
+                    "INSERT INTO __fork_storage (value_hash, value) VALUES (?1, ?2)",
+                    &[
+                        &MARFValue::from_value(&value).to_hex() as &dyn ToSql,
+                        &value,
+                    ],
+                )
+                .unwrap();
+            parent = block;
+        }
+
+        let fork_block = BlockHeaderHash([0xfe; 32]);
+        let fork_value = "fork-value".to_string();
+        marf.begin(&BlockHeaderHash([2; 32]), &fork_block).unwrap();
+        marf.insert("fork-key", MARFValue::from_value(&fork_value))
+            .unwrap();
+        marf.commit().unwrap();
+        marf.sqlite_conn()
+            .execute(
+                "INSERT INTO __fork_storage (value_hash, value) VALUES (?1, ?2)",
+                &[
+                    &MARFValue::from_value(&fork_value).to_hex() as &dyn ToSql,
+                    &fork_value,
+                ],
+            )
+            .unwrap();
+
+        (marf, parent)
+    }
+
+    #[test]
+    fn test_marf_snapshot_export_import() {
+        let (mut marf, tip) = make_test_marf("/tmp/test_marf_snapshot_export_import.sqlite", 5);
+        let tip_root_hash = marf.get_root_hash_at(&tip).unwrap();
+
+        let mut snapshot = vec![];
+        let mut storage = marf.reopen_storage_readonly().unwrap();
+        let summary = storage.export_snapshot(&tip, None, &mut snapshot).unwrap();
+        assert_eq!(summary.tip, tip);
+        assert_eq!(summary.tip_root_hash, tip_root_hash);
+        assert_eq!(summary.num_tries, 5);
+        assert_eq!(summary.num_side_rows, 5);
+        assert_eq!(summary.num_clarity_data_rows, 0);
+        assert_eq!(summary.num_clarity_metadata_rows, 0);
+
+        let mut imported_storage = TrieFileStorage::new_memory().unwrap();
+        let (imported_summary, metadata_rows) = imported_storage
+            .import_snapshot(&mut &snapshot[..], None, Some(&tip_root_hash))
+            .unwrap();
+        assert_eq!(imported_summary, summary);
+        assert!(metadata_rows.is_empty());
+
+        let mut imported_marf = MARF::from_storage(imported_storage);
+        assert_eq!(imported_marf.get_root_hash_at(&tip).unwrap(), tip_root_hash);
+        for i in 0..5 {
+            let value = format!("value-{}", i);
+            let marf_value = imported_marf
+                .get(&tip, &format!("key-{}", i))
+                .unwrap()
+                .unwrap();
+            assert_eq!(marf_value, MARFValue::from_value(&value));
+
+            let stored_value: String = imported_marf
+                .sqlite_conn()
+                .query_row(
+                    "SELECT value FROM __fork_storage WHERE value_hash = ?1",
+                    &[&marf_value.to_hex() as &dyn ToSql],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(stored_value, value);
+        }
+
+        // the fork was left out
+        assert!(!imported_marf
+            .borrow_storage_backend()
+            .has_block(&BlockHeaderHash([0xfe; 32]))
+            .unwrap());
+
+        // can't import over an existing MARF
+        let mut imported_storage = imported_marf.reopen_storage_readonly().unwrap();
+        assert!(imported_storage
+            .import_snapshot(&mut &snapshot[..], None, None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_marf_snapshot_clarity_side_store() {
+        let (marf, tip) = make_test_marf("/tmp/test_marf_snapshot_clarity_side_store.sqlite", 3);
+
+        // a Clarity side store with a value for each key, and metadata for a block on the
+        // chain and for the fork
+        let mut side_store = SqliteConnection::memory().unwrap();
+        for i in 0..3 {
+            let value = format!("value-{}", i);
+            side_store.put(&MARFValue::from_value(&value).to_hex(), &value);
+        }
+        side_store.put(&MARFValue::from_value("fork-value").to_hex(), "fork-value");
+        let chain_block = StacksBlockId([2; 32]);
+        let fork_block = StacksBlockId([0xfe; 32]);
+        side_store.insert_metadata(&chain_block, "contract", "key", "chain-metadata");
+        side_store.insert_metadata(&fork_block, "contract", "key", "fork-metadata");
+
+        let mut snapshot = vec![];
+        let mut storage = marf.reopen_storage_readonly().unwrap();
+        let summary = storage
+            .export_snapshot(&tip, Some(side_store.mut_conn()), &mut snapshot)
+            .unwrap();
+        assert_eq!(summary.num_clarity_data_rows, 3);
+        assert_eq!(summary.num_clarity_metadata_rows, 1);
+
+        let mut imported_side_store = SqliteConnection::memory().unwrap();
+        let mut imported_storage = TrieFileStorage::<BlockHeaderHash>::new_memory().unwrap();
+        let (imported_summary, metadata_rows) = imported_storage
+            .import_snapshot(
+                &mut &snapshot[..],
+                Some(imported_side_store.mut_conn()),
+                None,
+            )
+            .unwrap();
+        assert_eq!(imported_summary, summary);
+        assert_eq!(
+            metadata_rows,
+            vec![ClarityMetadataRow {
+                block_hash: BlockHeaderHash([2; 32]),
+                key: "clr-meta::contract::key".to_string(),
+                value: "chain-metadata".to_string(),
+            }]
+        );
+
+        for i in 0..3 {
+            let value = format!("value-{}", i);
+            assert_eq!(
+                imported_side_store.get(&MARFValue::from_value(&value).to_hex()),
+                Some(value)
+            );
+        }
+        assert_eq!(
+            imported_side_store.get(&MARFValue::from_value("fork-value").to_hex()),
+            None
+        );
+        // metadata rows are left to the caller to verify
+        assert_eq!(
+            imported_side_store.get_metadata(&chain_block, "contract", "key"),
+            None
+        );
+        assert_eq!(
+            imported_side_store.get_metadata(&fork_block, "contract", "key"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_marf_snapshot_import_rejects_bad_snapshots() {
+        let (mut marf, tip) = make_test_marf(
+            "/tmp/test_marf_snapshot_import_rejects_bad_snapshots.sqlite",
+            3,
+        );
+
+        let mut snapshot = vec![];
+        let mut storage = marf.reopen_storage_readonly().unwrap();
+        storage.export_snapshot(&tip, None, &mut snapshot).unwrap();
+
+        // wrong expected root hash
+        let mut imported_storage = TrieFileStorage::<BlockHeaderHash>::new_memory().unwrap();
+        match imported_storage.import_snapshot(
+            &mut &snapshot[..],
+            None,
+            Some(&TrieHash([0x01; 32])),
+        ) {
+            Err(Error::CorruptionError(_)) => {}
+            x => panic!("Expected corruption error, got {:?}", &x),
+        }
+
+        // tampered tip root hash
+        let mut bad_tip_root = snapshot.clone();
+        bad_tip_root[MARF_SNAPSHOT_MAGIC.len() + 4 + 32] ^= 0x01;
+        assert!(imported_storage
+            .import_snapshot(&mut &bad_tip_root[..], None, None)
+            .is_err());
+
+        // tampered leaf in a trie, with every recorded hash left as it was.  Only recomputing
+        // the trie's hashes catches this.
+        let leaf_value = MARFValue::from_value("value-1");
+        let leaf_offset = snapshot
+            .windows(leaf_value.0.len())
+            .position(|window| window == &leaf_value.0[..])
+            .unwrap();
+        let mut bad_leaf = snapshot.clone();
+        bad_leaf[leaf_offset] ^= 0x01;
+        match imported_storage.import_snapshot(
+            &mut &bad_leaf[..],
+            None,
+            Some(&marf.get_root_hash_at(&tip).unwrap()),
+        ) {
+            Err(Error::CorruptionError(_)) => {}
+            x => panic!("Expected corruption error, got {:?}", &x),
+        }
+
+        // tampered side-store value (the last fork storage row, before the empty Clarity
+        // data and metadata sections)
+        let mut bad_value = snapshot.clone();
+        let last = bad_value.len() - 9;
+        bad_value[last] ^= 0x01;
+        match imported_storage.import_snapshot(&mut &bad_value[..], None, None) {
+            Err(Error::CorruptionError(_)) => {}
+            x => panic!("Expected corruption error, got {:?}", &x),
+        }
+
+        // truncated
+        assert!(imported_storage
+            .import_snapshot(&mut &snapshot[..snapshot.len() - 1], None, None)
+            .is_err());
+
+        // nothing was stored by the failed imports
+        assert!(imported_storage
+            .import_snapshot(&mut &snapshot[..], None, None)
+            .is_ok());
+    }
+}
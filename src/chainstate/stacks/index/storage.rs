@@ -37,6 +37,8 @@ use chainstate::burn::BLOCK_HEADER_HASH_ENCODED_SIZE;
 
 use chainstate::stacks::index::{trie_sql, BlockMap, MarfTrieId, TrieHash, TRIEHASH_ENCODED_SIZE};
 
+use chainstate::stacks::index::backend::{SqliteTrieBackend, TrieStorageBackend};
+
 use chainstate::stacks::index::bits::{
     get_node_byte_len, get_node_hash, read_block_identifier, read_hash_bytes, read_node_hash_bytes,
     read_nodetype, read_root_hash, write_nodetype_bytes,
@@ -82,7 +84,7 @@ impl<T: MarfTrieId> BlockMap for TrieFileStorage<T> {
     type TrieId = T;
 
     fn get_block_hash(&self, id: u32) -> Result<T, Error> {
-        trie_sql::get_block_hash(&self.db, id)
+        self.backend.get_block_hash(&self.db, id)
     }
 
     fn get_block_hash_caching(&mut self, id: u32) -> Result<&T, Error> {
@@ -99,7 +101,7 @@ impl<'a, T: MarfTrieId> BlockMap for TrieStorageConnection<'a, T> {
     type TrieId = T;
 
     fn get_block_hash(&self, id: u32) -> Result<T, Error> {
-        trie_sql::get_block_hash(&self.db, id)
+        self.backend.get_block_hash(&self.db, id)
     }
 
     fn get_block_hash_caching(&mut self, id: u32) -> Result<&T, Error> {
@@ -128,7 +130,7 @@ impl<T: MarfTrieId> BlockMap for TrieSqlHashMapCursor<'_, T> {
     type TrieId = T;
 
     fn get_block_hash(&self, id: u32) -> Result<T, Error> {
-        trie_sql::get_block_hash(&self.db, id)
+        self.backend.get_block_hash(&self.db, id)
     }
 
     fn get_block_hash_caching(&mut self, id: u32) -> Result<&T, Error> {
@@ -572,19 +574,24 @@ impl<T: MarfTrieId> NodeHashReader for TrieRAM<T> {
     }
 }
 
-pub struct TrieSqlCursor<'a> {
+pub struct TrieSqlCursor<'a, T: MarfTrieId> {
     db: &'a Connection,
+    backend: &'a dyn TrieStorageBackend<T>,
     block_id: u32,
 }
 
 pub struct TrieSqlHashMapCursor<'a, T: MarfTrieId> {
     db: &'a Connection,
+    backend: &'a dyn TrieStorageBackend<T>,
     cache: &'a mut HashMap<u32, T>,
 }
 
-impl NodeHashReader for TrieSqlCursor<'_> {
+impl<T: MarfTrieId> NodeHashReader for TrieSqlCursor<'_, T> {
     fn read_node_hash_bytes<W: Write>(&mut self, ptr: &TriePtr, w: &mut W) -> Result<(), Error> {
-        trie_sql::read_node_hash_bytes(self.db, w, self.block_id, ptr)
+        let hash = self
+            .backend
+            .get_node_hash_bytes(self.db, self.block_id, ptr)?;
+        w.write_all(hash.as_bytes()).map_err(|e| e.into())
     }
 }
 
@@ -634,6 +641,7 @@ pub struct TrieStorageTransaction<'a, T: MarfTrieId>(TrieStorageConnection<'a, T
 pub struct TrieStorageConnection<'a, T: MarfTrieId> {
     pub db_path: &'a str,
     db: SqliteConnection<'a>,
+    backend: &'a mut (dyn TrieStorageBackend<T> + 'static),
     data: &'a mut TrieStorageTransientData<T>,
 
     // used in testing in order to short-circuit block-height lookups
@@ -668,6 +676,10 @@ struct TrieStorageTransientData<T: MarfTrieId> {
 
     block_hash_cache: HashMap<u32, T>,
 
+    /// whether or not the database can have pruned tries, so the (common) case where it
+    /// can't costs nothing to check.
+    has_pruned_tries_table: bool,
+
     readonly: bool,
     unconfirmed: bool,
 }
@@ -679,6 +691,7 @@ pub struct TrieFileStorage<T: MarfTrieId> {
     pub db_path: String,
 
     db: Connection,
+    backend: Box<dyn TrieStorageBackend<T>>,
     data: TrieStorageTransientData<T>,
 
     // used in testing in order to short-circuit block-height lookups
@@ -689,8 +702,10 @@ pub struct TrieFileStorage<T: MarfTrieId> {
 
 impl<T: MarfTrieId> TrieFileStorage<T> {
     pub fn connection<'a>(&'a mut self) -> TrieStorageConnection<'a, T> {
+        self.backend.rollback_tx();
         TrieStorageConnection {
             db: SqliteConnection::ConnRef(&self.db),
+            backend: &mut *self.backend,
             db_path: &self.db_path,
             data: &mut self.data,
 
@@ -704,9 +719,11 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
             return Err(Error::ReadOnlyError);
         }
         let tx = tx_begin_immediate(&mut self.db)?;
+        self.backend.begin_tx();
 
         Ok(TrieStorageTransaction(TrieStorageConnection {
             db: SqliteConnection::Tx(tx),
+            backend: &mut *self.backend,
             db_path: &self.db_path,
             data: &mut self.data,
 
@@ -723,6 +740,7 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
         db_path: &str,
         readonly: bool,
         unconfirmed: bool,
+        backend: Box<dyn TrieStorageBackend<T>>,
     ) -> Result<TrieFileStorage<T>, Error> {
         let mut create_flag = false;
         let open_flags = if db_path != ":memory:" {
@@ -763,15 +781,17 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
 
         let db_path = db_path.to_string();
 
-        if create_flag {
+        if create_flag || !readonly {
             trie_sql::create_tables_if_needed(&mut db)?;
         }
+        let has_pruned_tries_table = backend.has_pruned_tries(&db)?;
 
         debug!("Opened TrieFileStorage {};", db_path);
 
         let ret = TrieFileStorage {
             db_path,
             db,
+            backend,
 
             data: TrieStorageTransientData {
                 last_extended: None,
@@ -790,6 +810,8 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
                 trie_ancestor_hash_bytes_cache: None,
                 block_hash_cache: HashMap::new(),
 
+                has_pruned_tries_table,
+
                 readonly: readonly,
                 unconfirmed: unconfirmed,
             },
@@ -809,15 +831,24 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
     }
 
     pub fn open(db_path: &str) -> Result<TrieFileStorage<T>, Error> {
-        TrieFileStorage::open_opts(db_path, false, false)
+        TrieFileStorage::open_opts(db_path, false, false, Box::new(SqliteTrieBackend::new()))
     }
 
     pub fn open_readonly(db_path: &str) -> Result<TrieFileStorage<T>, Error> {
-        TrieFileStorage::open_opts(db_path, true, false)
+        TrieFileStorage::open_opts(db_path, true, false, Box::new(SqliteTrieBackend::new()))
     }
 
     pub fn open_unconfirmed(db_path: &str) -> Result<TrieFileStorage<T>, Error> {
-        TrieFileStorage::open_opts(db_path, false, true)
+        TrieFileStorage::open_opts(db_path, false, true, Box::new(SqliteTrieBackend::new()))
+    }
+
+    /// Open the storage with its tries kept in the given backend.  The SQLite database at
+    /// `db_path` (which can be ":memory:") still holds the MARF's side tables.
+    pub fn open_with_backend(
+        db_path: &str,
+        backend: Box<dyn TrieStorageBackend<T>>,
+    ) -> Result<TrieFileStorage<T>, Error> {
+        TrieFileStorage::open_opts(db_path, false, false, backend)
     }
 
     pub fn readonly(&self) -> bool {
@@ -828,6 +859,23 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
         self.data.unconfirmed
     }
 
+    /// Is a trie being built in RAM that hasn't been flushed yet?
+    pub fn is_extending(&self) -> bool {
+        self.data.last_extended.is_some()
+    }
+
+    /// Has the given block's trie been pruned?  Unknown blocks haven't been.
+    pub fn is_block_pruned(&self, bhh: &T) -> Result<bool, Error> {
+        if !self.data.has_pruned_tries_table {
+            return Ok(false);
+        }
+        match self.backend.get_block_identifier(&self.db, bhh) {
+            Ok(block_id) => self.backend.is_trie_pruned(&self.db, block_id),
+            Err(Error::NotFoundError) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
     pub fn reopen_readonly(&self) -> Result<TrieFileStorage<T>, Error> {
         let db = Connection::open_with_flags(&self.db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
         db.busy_handler(Some(tx_busy_handler))?;
@@ -838,6 +886,7 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
         let ret = TrieFileStorage {
             db_path: self.db_path.clone(),
             db: db,
+            backend: self.backend.reopen_readonly(),
 
             data: TrieStorageTransientData {
                 last_extended: self.data.last_extended.clone(),
@@ -856,6 +905,8 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
                 trie_ancestor_hash_bytes_cache: None,
                 block_hash_cache: self.data.block_hash_cache.clone(),
 
+                has_pruned_tries_table: self.data.has_pruned_tries_table,
+
                 readonly: true,
                 unconfirmed: true,
             },
@@ -886,6 +937,7 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
         let ret = TrieFileStorage {
             db_path: self.db_path.to_string(),
             db: db,
+            backend: self.0.backend.reopen_readonly(),
 
             data: TrieStorageTransientData {
                 last_extended: None,
@@ -904,6 +956,8 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
                 trie_ancestor_hash_bytes_cache: None,
                 block_hash_cache: HashMap::new(),
 
+                has_pruned_tries_table: self.data.has_pruned_tries_table,
+
                 readonly: true,
                 unconfirmed: true,
             },
@@ -945,7 +999,7 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
                     if self.data.unconfirmed {
                         return Err(Error::UnconfirmedError);
                     }
-                    trie_sql::write_trie_blob(&self.db, bhh, &buffer)?
+                    self.0.backend.write_trie_blob(&self.0.db, bhh, &buffer)?
                 }
                 FlushOptions::NewHeader(real_bhh) => {
                     // If we opened a block with a given hash, but want to store it as a block with a *different*
@@ -964,23 +1018,29 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
                         // switch over state
                         self.data.cur_block = real_bhh.clone();
                     }
-                    trie_sql::write_trie_blob(&self.db, real_bhh, &buffer)?
+                    self.0
+                        .backend
+                        .write_trie_blob(&self.0.db, real_bhh, &buffer)?
                 }
                 FlushOptions::MinedTable(real_bhh) => {
                     if self.data.unconfirmed {
                         return Err(Error::UnconfirmedError);
                     }
-                    trie_sql::write_trie_blob_to_mined(&self.db, real_bhh, &buffer)?
+                    self.0
+                        .backend
+                        .write_trie_blob_to_mined(&self.0.db, real_bhh, &buffer)?
                 }
                 FlushOptions::UnconfirmedTable => {
                     if !self.data.unconfirmed {
                         return Err(Error::UnconfirmedError);
                     }
-                    trie_sql::write_trie_blob_to_unconfirmed(&self.db, bhh, &buffer)?
+                    self.0
+                        .backend
+                        .write_trie_blob_to_unconfirmed(&self.0.db, bhh, &buffer)?
                 }
             };
 
-            trie_sql::drop_lock(&self.db, bhh)?;
+            self.0.backend.drop_lock(&self.0.db, bhh)?;
 
             debug!("Flush: identifier of {} is {}", flush_options, block_id);
         }
@@ -1008,7 +1068,9 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
         self.clear_cached_ancestor_hashes_bytes();
         if !self.data.readonly {
             if let Some((ref bhh, _)) = self.data.last_extended.take() {
-                trie_sql::drop_lock(&self.db, bhh)
+                self.0
+                    .backend
+                    .drop_lock(&self.0.db, bhh)
                     .expect("Corruption: Failed to drop the extended trie lock");
             }
             self.data.last_extended = None;
@@ -1020,9 +1082,13 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
     pub fn drop_unconfirmed_trie(&mut self, bhh: &T) {
         self.clear_cached_ancestor_hashes_bytes();
         if !self.data.readonly && self.data.unconfirmed {
-            trie_sql::drop_unconfirmed_trie(&self.db, bhh)
+            self.0
+                .backend
+                .drop_unconfirmed_trie(&self.0.db, bhh)
                 .expect("Corruption: Failed to drop unconfirmed trie");
-            trie_sql::drop_lock(&self.db, bhh)
+            self.0
+                .backend
+                .drop_lock(&self.0.db, bhh)
                 .expect("Corruption: Failed to drop the extended trie lock");
             self.data.last_extended = None;
             self.data.cur_block_id = None;
@@ -1042,7 +1108,7 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
             return Err(Error::UnconfirmedError);
         }
 
-        if trie_sql::get_block_identifier(&self.db, bhh).is_ok() {
+        if self.0.backend.get_block_identifier(&self.0.db, bhh).is_ok() {
             warn!("Block already exists: {}", &bhh);
             return Err(Error::ExistsError);
         }
@@ -1057,10 +1123,10 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
         let trie_buf = TrieRAM::new(bhh, size_hint, &self.data.cur_block);
 
         // place a lock on this block, so we can't extend to it again
-        if !trie_sql::lock_bhh_for_extension(self.sqlite_tx(), bhh, false)? {
-            warn!("Block already extended: {}", &bhh);
-            return Err(Error::ExistsError);
-        }
+        // (a left-over lock from an abandoned extension doesn't stop us)
+        self.0
+            .backend
+            .lock_bhh_for_extension(&self.0.db, bhh, false)?;
 
         self.switch_trie(bhh, trie_buf);
         Ok(())
@@ -1078,27 +1144,34 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
         self.flush()?;
 
         // try to load up the trie
-        let (trie_buf, created) =
-            if let Some(block_id) = trie_sql::get_unconfirmed_block_identifier(&self.db, bhh)? {
-                debug!("Reload unconfirmed trie {} ({})", bhh, block_id);
-
-                // restore trie
-                let mut fd = trie_sql::open_trie_blob(&self.db, block_id)?;
-                (TrieRAM::load(&mut fd, bhh)?, false)
-            } else {
-                debug!("Instantiate unconfirmed trie {}", bhh);
-
-                // new trie
-                let size_hint = match self.data.last_extended {
-                    Some((_, ref trie_storage)) => 2 * trie_storage.size_hint(),
-                    None => (1024), // don't try to guess _byte_ allocation here.
-                };
+        let (trie_buf, created) = if let Some(block_id) = self
+            .0
+            .backend
+            .get_unconfirmed_block_identifier(&self.0.db, bhh)?
+        {
+            debug!("Reload unconfirmed trie {} ({})", bhh, block_id);
+
+            // restore trie
+            let trie_blob = self.0.backend.read_trie_blob(&self.0.db, block_id)?;
+            (TrieRAM::load(&mut Cursor::new(trie_blob), bhh)?, false)
+        } else {
+            debug!("Instantiate unconfirmed trie {}", bhh);
 
-                (TrieRAM::new(bhh, size_hint, &self.data.cur_block), true)
+            // new trie
+            let size_hint = match self.data.last_extended {
+                Some((_, ref trie_storage)) => 2 * trie_storage.size_hint(),
+                None => 1024, // don't try to guess _byte_ allocation here.
             };
 
+            (TrieRAM::new(bhh, size_hint, &self.data.cur_block), true)
+        };
+
         // place a lock on this block, so we can't extend to it again
-        if !trie_sql::tx_lock_bhh_for_extension(&self.db, bhh, true)? {
+        if !self
+            .0
+            .backend
+            .lock_bhh_for_extension(&self.0.db, bhh, true)?
+        {
             warn!("Block already extended: {}", &bhh);
             return Err(Error::ExistsError);
         }
@@ -1115,7 +1188,7 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
         debug!("Format TrieFileStorage");
 
         // blow away db
-        trie_sql::clear_tables(self.sqlite_tx())?;
+        self.0.backend.clear_tables(&self.0.db)?;
 
         match self.data.last_extended {
             Some((_, ref mut trie_storage)) => trie_storage.format()?,
@@ -1130,6 +1203,30 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
         Ok(())
     }
 
+    /// Store a trie under a given local ID, i.e. one restored from a snapshot.  The ID must be
+    /// preserved, since back-pointers in descendant tries refer to it.
+    pub fn write_trie_blob_with_id(
+        &mut self,
+        block_id: u32,
+        bhh: &T,
+        trie_blob: &[u8],
+    ) -> Result<(), Error> {
+        self.0
+            .backend
+            .write_trie_blob_with_id(&self.0.db, block_id, bhh, trie_blob)
+    }
+
+    /// Replace a stored trie, i.e. with a compacted copy of itself.
+    pub fn update_trie_blob(&mut self, block_id: u32, trie_blob: &[u8]) -> Result<(), Error> {
+        self.0
+            .backend
+            .update_trie_blob(&self.0.db, block_id, trie_blob)
+    }
+
+    pub fn set_trie_pruned(&mut self, block_id: u32) -> Result<(), Error> {
+        self.0.backend.set_trie_pruned(&self.0.db, block_id)
+    }
+
     pub fn sqlite_tx(&self) -> &Transaction<'a> {
         match &self.0.db {
             SqliteConnection::Tx(ref tx) => tx,
@@ -1142,6 +1239,7 @@ impl<'a, T: MarfTrieId> TrieStorageTransaction<'a, T> {
     }
 
     pub fn commit_tx(self) {
+        self.0.backend.commit_tx();
         match self.0.db {
             SqliteConnection::Tx(tx) => {
                 tx.commit().expect("CORRUPTION: Failed to commit MARF");
@@ -1235,21 +1333,24 @@ impl<'a, T: MarfTrieId> TrieStorageConnection<'a, T> {
     }
 
     /// Read the Trie root node's hash from the block table.
-    #[cfg(test)]
     pub fn read_block_root_hash(&self, bhh: &T) -> Result<TrieHash, Error> {
         let root_hash_ptr = TriePtr::new(
             TrieNodeID::Node256 as u8,
             0,
             TrieStorageConnection::<T>::root_ptr_disk(),
         );
-        trie_sql::get_node_hash_bytes_by_bhh(&self.db, bhh, &root_hash_ptr)
+        self.backend
+            .get_node_hash_bytes_by_bhh(&self.db, bhh, &root_hash_ptr)
     }
 
     /// Generate a mapping between Trie root hashes and the blocks that contain them
     #[cfg(test)]
     pub fn read_root_to_block_table(&mut self) -> Result<HashMap<TrieHash, T>, Error> {
-        let mut ret =
-            HashMap::from_iter(trie_sql::read_all_block_hashes_and_roots(&self.db)?.into_iter());
+        let mut ret = HashMap::from_iter(
+            self.backend
+                .read_all_block_hashes_and_roots(&self.db)?
+                .into_iter(),
+        );
 
         let last_extended = match self.data.last_extended.take() {
             Some((bhh, trie_ram)) => {
@@ -1281,7 +1382,7 @@ impl<'a, T: MarfTrieId> TrieStorageConnection<'a, T> {
     }
 
     pub fn has_confirmed_block(&self, bhh: &T) -> Result<bool, Error> {
-        match trie_sql::get_confirmed_block_identifier(&self.db, bhh) {
+        match self.backend.get_confirmed_block_identifier(&self.db, bhh) {
             Ok(Some(_)) => Ok(true),
             Ok(None) => Ok(false),
             Err(e) => Err(e),
@@ -1289,7 +1390,7 @@ impl<'a, T: MarfTrieId> TrieStorageConnection<'a, T> {
     }
 
     pub fn has_unconfirmed_block(&self, bhh: &T) -> Result<bool, Error> {
-        match trie_sql::get_unconfirmed_block_identifier(&self.db, bhh) {
+        match self.backend.get_unconfirmed_block_identifier(&self.db, bhh) {
             Ok(Some(_)) => Ok(true),
             Ok(None) => Ok(false),
             Err(e) => Err(e),
@@ -1300,6 +1401,52 @@ impl<'a, T: MarfTrieId> TrieStorageConnection<'a, T> {
         Ok(self.has_confirmed_block(bhh)? || self.has_unconfirmed_block(bhh)?)
     }
 
+    pub fn get_confirmed_block_identifier(&self, bhh: &T) -> Result<Option<u32>, Error> {
+        self.backend.get_confirmed_block_identifier(&self.db, bhh)
+    }
+
+    /// Get the local IDs of all stored tries, confirmed or not
+    pub fn get_all_block_identifiers(&self) -> Result<Vec<u32>, Error> {
+        self.backend.get_all_block_identifiers(&self.db)
+    }
+
+    pub fn count_all_tries(&self) -> Result<u32, Error> {
+        self.backend.count_all_tries(&self.db)
+    }
+
+    /// Get the hash of the block whose trie the given stored trie was built on
+    pub fn get_trie_parent(&self, block_id: u32) -> Result<T, Error> {
+        self.backend.get_trie_parent(&self.db, block_id)
+    }
+
+    /// Read a stored trie in its on-disk encoding
+    pub fn read_trie_blob(&self, block_id: u32) -> Result<Vec<u8>, Error> {
+        self.backend.read_trie_blob(&self.db, block_id)
+    }
+
+    /// Has the given block's trie been pruned?  Unknown blocks haven't been.
+    pub fn is_block_pruned(&self, bhh: &T) -> Result<bool, Error> {
+        if !self.data.has_pruned_tries_table {
+            return Ok(false);
+        }
+        match self.backend.get_block_identifier(&self.db, bhh) {
+            Ok(block_id) => self.backend.is_trie_pruned(&self.db, block_id),
+            Err(Error::NotFoundError) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Has the currently-opened trie been pruned?  A trie that's still being built can't have
+    /// been.
+    pub fn is_cur_block_pruned(&self) -> Result<bool, Error> {
+        match self.data.cur_block_id {
+            Some(block_id) if self.data.has_pruned_tries_table => {
+                self.backend.is_trie_pruned(&self.db, block_id)
+            }
+            _ => Ok(false),
+        }
+    }
+
     // used for providing a option<block identifier> when re-opening a block --
     //   because the previously open block may have been the last_extended block,
     //   id may have been None.
@@ -1342,7 +1489,7 @@ impl<'a, T: MarfTrieId> TrieStorageConnection<'a, T> {
             // just reset to newly opened state
             self.data.cur_block = sentinel;
             // did we write to the sentinel?
-            self.data.cur_block_id = trie_sql::get_block_identifier(&self.db, bhh).ok();
+            self.data.cur_block_id = self.backend.get_block_identifier(&self.db, bhh).ok();
             return Ok(());
         }
 
@@ -1357,7 +1504,7 @@ impl<'a, T: MarfTrieId> TrieStorageConnection<'a, T> {
         }
 
         // opening a different Trie than the one we're extending
-        self.data.cur_block_id = Some(trie_sql::get_block_identifier(&self.db, bhh)?);
+        self.data.cur_block_id = Some(self.backend.get_block_identifier(&self.db, bhh)?);
         self.data.cur_block = bhh.clone();
 
         Ok(())
@@ -1373,7 +1520,7 @@ impl<'a, T: MarfTrieId> TrieStorageConnection<'a, T> {
             }
         }
 
-        trie_sql::get_block_identifier(&self.db, bhh).ok()
+        self.backend.get_block_identifier(&self.db, bhh).ok()
     }
 
     pub fn get_cur_block_identifier(&mut self) -> Result<u32, Error> {
@@ -1450,6 +1597,7 @@ impl<'a, T: MarfTrieId> TrieStorageConnection<'a, T> {
 
         let mut map = TrieSqlHashMapCursor {
             db: &self.db,
+            backend: &*self.backend,
             cache: &mut self.data.block_hash_cache,
         };
 
@@ -1468,6 +1616,7 @@ impl<'a, T: MarfTrieId> TrieStorageConnection<'a, T> {
         // otherwise, the current block is open as an FD
         let mut cursor = TrieSqlCursor {
             db: &self.db,
+            backend: &*self.backend,
             block_id: self.data.cur_block_id.ok_or_else(|| {
                 error!("Failed to get cur block as hash reader");
                 Error::NotFoundError
@@ -1513,7 +1662,7 @@ impl<'a, T: MarfTrieId> TrieStorageConnection<'a, T> {
 
         // some other block or ptr, or cache miss
         match self.data.cur_block_id {
-            Some(block_id) => trie_sql::get_node_hash_bytes(&self.db, block_id, ptr),
+            Some(block_id) => self.backend.get_node_hash_bytes(&self.db, block_id, ptr),
             None => {
                 error!("Not found (no file is open)");
                 Err(Error::NotFoundError)
@@ -1545,7 +1694,7 @@ impl<'a, T: MarfTrieId> TrieStorageConnection<'a, T> {
 
         // some other block
         match self.data.cur_block_id {
-            Some(id) => trie_sql::read_node_type(&self.db, id, &clear_ptr),
+            Some(id) => self.backend.read_node_type(&self.db, id, &clear_ptr),
             None => {
                 error!("Not found (no file is open)");
                 Err(Error::NotFoundError)
@@ -1620,7 +1769,9 @@ impl<'a, T: MarfTrieId> TrieStorageConnection<'a, T> {
             0
         };
         result
-            + (trie_sql::count_blocks(&self.db)
+            + (self
+                .backend
+                .count_blocks(&self.db)
                 .expect("Corruption: SQL Error on a non-fallible query.") as usize)
     }
 }
@@ -90,12 +90,17 @@ static SQL_EXTENSION_LOCKS_TABLE: &str = "
 CREATE TABLE IF NOT EXISTS block_extension_locks (block_hash TEXT PRIMARY KEY);
 ";
 
+static SQL_MARF_PRUNED_TRIES_TABLE: &str = "
+CREATE TABLE IF NOT EXISTS marf_pruned_tries (block_id INTEGER PRIMARY KEY);
+";
+
 pub fn create_tables_if_needed(conn: &mut Connection) -> Result<(), Error> {
     let tx = tx_begin_immediate(conn)?;
 
     tx.execute_batch(SQL_MARF_DATA_TABLE)?;
     tx.execute_batch(SQL_MARF_MINED_TABLE)?;
     tx.execute_batch(SQL_EXTENSION_LOCKS_TABLE)?;
+    tx.execute_batch(SQL_MARF_PRUNED_TRIES_TABLE)?;
 
     tx.commit().map_err(|e| e.into())
 }
@@ -164,6 +169,22 @@ pub fn write_trie_blob<T: MarfTrieId>(
     Ok(block_id)
 }
 
+/// Write a trie blob under a given local block ID, i.e. one restored from a snapshot.  The ID
+/// must be preserved, since back-pointers in descendant tries refer to it.
+pub fn write_trie_blob_with_id<T: MarfTrieId>(
+    conn: &Connection,
+    block_id: u32,
+    block_hash: &T,
+    data: &[u8],
+) -> Result<(), Error> {
+    let args: &[&dyn ToSql] = &[&block_id, block_hash, &data, &0];
+    conn.execute(
+        "INSERT INTO marf_data (block_id, block_hash, data, unconfirmed) VALUES (?, ?, ?, ?)",
+        args,
+    )?;
+    Ok(())
+}
+
 pub fn write_trie_blob_to_mined<T: MarfTrieId>(
     conn: &Connection,
     block_hash: &T,
@@ -210,6 +231,97 @@ pub fn open_trie_blob<'a>(conn: &'a Connection, block_id: u32) -> Result<Blob<'a
     Ok(blob)
 }
 
+pub fn read_trie_blob(conn: &Connection, block_id: u32) -> Result<Vec<u8>, Error> {
+    conn.query_row(
+        "SELECT data FROM marf_data WHERE block_id = ?",
+        &[block_id],
+        |row| row.get("data"),
+    )
+    .map_err(|e| e.into())
+}
+
+/// Replace the contents of a trie blob, i.e. with a compacted copy of itself.
+pub fn update_trie_blob(conn: &Connection, block_id: u32, data: &[u8]) -> Result<(), Error> {
+    let args: &[&dyn ToSql] = &[&data, &block_id];
+    conn.execute("UPDATE marf_data SET data = ? WHERE block_id = ?", args)?;
+    Ok(())
+}
+
+/// Get the local IDs of all tries, confirmed or not
+pub fn get_all_block_identifiers(conn: &Connection) -> Result<Vec<u32>, Error> {
+    let mut s = conn.prepare("SELECT block_id FROM marf_data ORDER BY block_id")?;
+    let rows = s.query_and_then(NO_PARAMS, |row| -> Result<u32, Error> {
+        Ok(row.get("block_id"))
+    })?;
+    rows.collect()
+}
+
+/// Does this database have the table of pruned tries?  Databases created before pruning
+/// existed only get it once they're opened read/write.
+pub fn has_pruned_tries_table(conn: &Connection) -> Result<bool, Error> {
+    let found = conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'marf_pruned_tries'",
+            NO_PARAMS,
+            |_row| (),
+        )
+        .optional()?
+        .is_some();
+    Ok(found)
+}
+
+/// Does this database have a `__fork_storage` side store (i.e. is it the chainstate index)?
+pub fn has_fork_storage(conn: &Connection) -> Result<bool, Error> {
+    let found = conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '__fork_storage'",
+            NO_PARAMS,
+            |_row| (),
+        )
+        .optional()?
+        .is_some();
+    Ok(found)
+}
+
+/// Drop side-store values that no trie refers to any longer
+pub fn delete_fork_storage_values(conn: &Connection, value_hashes: &[String]) -> Result<(), Error> {
+    for value_hash in value_hashes.iter() {
+        conn.execute(
+            "DELETE FROM __fork_storage WHERE value_hash = ?",
+            &[value_hash as &dyn ToSql],
+        )?;
+    }
+    Ok(())
+}
+
+pub fn is_trie_pruned(conn: &Connection, block_id: u32) -> Result<bool, Error> {
+    let found = conn
+        .query_row(
+            "SELECT 1 FROM marf_pruned_tries WHERE block_id = ?",
+            &[block_id],
+            |_row| (),
+        )
+        .optional()?
+        .is_some();
+    Ok(found)
+}
+
+pub fn set_trie_pruned(conn: &Connection, block_id: u32) -> Result<(), Error> {
+    conn.execute(
+        "INSERT OR IGNORE INTO marf_pruned_tries (block_id) VALUES (?)",
+        &[block_id],
+    )?;
+    Ok(())
+}
+
+/// Get the hash of the block whose trie the given trie was built on (the sentinel if it's the
+/// first trie)
+pub fn get_trie_parent<T: MarfTrieId>(conn: &Connection, block_id: u32) -> Result<T, Error> {
+    let mut blob = open_trie_blob(conn, block_id)?;
+    let parent_hash_bytes = read_hash_bytes(&mut blob)?;
+    Ok(T::from_bytes(parent_hash_bytes))
+}
+
 #[cfg(test)]
 pub fn read_all_block_hashes_and_roots<T: MarfTrieId>(
     conn: &Connection,
@@ -368,6 +480,15 @@ pub fn lock_bhh_for_extension<T: MarfTrieId>(
     Ok(true)
 }
 
+pub fn count_all_tries(conn: &Connection) -> Result<u32, Error> {
+    let result = conn.query_row(
+        "SELECT COUNT(*) AS count FROM marf_data",
+        NO_PARAMS,
+        |row| row.get("count"),
+    )?;
+    Ok(result)
+}
+
 pub fn count_blocks(conn: &Connection) -> Result<u32, Error> {
     let result = conn.query_row(
         "SELECT IFNULL(MAX(block_id), 0) AS count FROM marf_data WHERE unconfirmed = 0",
@@ -398,9 +519,10 @@ pub fn clear_lock_data(conn: &Connection) -> Result<(), Error> {
     Ok(())
 }
 
-pub fn clear_tables(tx: &Transaction) -> Result<(), Error> {
+pub fn clear_tables(tx: &Connection) -> Result<(), Error> {
     tx.execute("DELETE FROM block_extension_locks", NO_PARAMS)?;
     tx.execute("DELETE FROM marf_data", NO_PARAMS)?;
     tx.execute("DELETE FROM mined_blocks", NO_PARAMS)?;
+    tx.execute("DELETE FROM marf_pruned_tries", NO_PARAMS)?;
     Ok(())
 }
@@ -48,6 +48,7 @@ use util::vrf::*;
 use core::mempool::*;
 use core::*;
 
+use vm::costs::ExecutionCostDimension;
 use vm::database::BurnStateDB;
 
 ///
@@ -335,6 +336,125 @@ impl<'a> Drop for StacksMicroblockBuilder<'a> {
     }
 }
 
+/// How much of each dimension of its execution budget an anchored block used, and which
+/// dimensions kept the miner from including more transactions
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockCostUtilization {
+    pub consumed: ExecutionCost,
+    pub budget: ExecutionCost,
+    /// dimensions that a candidate transaction would have overflowed
+    pub saturated: Vec<ExecutionCostDimension>,
+    /// number of candidates that were not tried, because their recorded cost would not fit
+    pub skipped_txs: u64,
+}
+
+impl BlockCostUtilization {
+    pub fn new(budget: ExecutionCost) -> BlockCostUtilization {
+        BlockCostUtilization {
+            consumed: ExecutionCost::zero(),
+            budget,
+            saturated: vec![],
+            skipped_txs: 0,
+        }
+    }
+
+    /// Percentage (0-100) of the budget consumed in the given dimension
+    pub fn percent(&self, dimension: ExecutionCostDimension) -> u64 {
+        let budget = self.budget.get(dimension);
+        if budget == 0 {
+            return 100;
+        }
+        ((self.consumed.get(dimension) as u128) * 100 / (budget as u128)) as u64
+    }
+
+    /// Would a transaction with the given cost fit into what's left of the budget?  If not,
+    /// remember which dimensions it would overflow.
+    fn check_fits(&mut self, so_far: &ExecutionCost, tx_cost: &ExecutionCost) -> bool {
+        let mut projected = so_far.clone();
+        let overflowed = match projected.add(tx_cost) {
+            Ok(_) => projected.exceeded_dimensions(&self.budget),
+            Err(_) => ExecutionCostDimension::ALL.to_vec(),
+        };
+        for dimension in overflowed.iter() {
+            if !self.saturated.contains(dimension) {
+                debug!("Block budget saturated in {}", dimension);
+                self.saturated.push(*dimension);
+            }
+        }
+        overflowed.len() == 0
+    }
+}
+
+fn cost_delta(after: &ExecutionCost, before: &ExecutionCost) -> Option<ExecutionCost> {
+    let mut delta = after.clone();
+    match delta.sub(before) {
+        Ok(_) => Some(delta),
+        Err(_) => None,
+    }
+}
+
+/// Strategy for the order in which StacksBlockBuilder::build_anchored_block() considers
+/// mempool transactions.  Implementations feed batches of candidates to `todo`, which tries to
+/// mine each one in turn.
+pub trait TransactionSelector {
+    fn iterate_candidates(
+        &self,
+        mempool: &MemPoolDB,
+        tip_consensus_hash: &ConsensusHash,
+        tip_block_hash: &BlockHeaderHash,
+        tip_height: u64,
+        chainstate: &mut StacksChainState,
+        todo: &mut dyn FnMut(Vec<MemPoolTxInfo>) -> Result<(), Error>,
+    ) -> Result<(), Error>;
+}
+
+/// Consider transactions in the order in which they arrived in the mempool
+pub struct ArrivalTimeSelector;
+
+/// Consider the best-paying transactions first, scoring each transaction together with the
+/// lower-nonce transactions from its origin that must be mined before it
+pub struct FeeRateSelector;
+
+impl TransactionSelector for ArrivalTimeSelector {
+    fn iterate_candidates(
+        &self,
+        mempool: &MemPoolDB,
+        tip_consensus_hash: &ConsensusHash,
+        tip_block_hash: &BlockHeaderHash,
+        tip_height: u64,
+        chainstate: &mut StacksChainState,
+        todo: &mut dyn FnMut(Vec<MemPoolTxInfo>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        mempool.iterate_candidates(
+            tip_consensus_hash,
+            tip_block_hash,
+            tip_height,
+            chainstate,
+            |available_txs| todo(available_txs),
+        )
+    }
+}
+
+impl TransactionSelector for FeeRateSelector {
+    fn iterate_candidates(
+        &self,
+        mempool: &MemPoolDB,
+        tip_consensus_hash: &ConsensusHash,
+        tip_block_hash: &BlockHeaderHash,
+        tip_height: u64,
+        chainstate: &mut StacksChainState,
+        todo: &mut dyn FnMut(Vec<MemPoolTxInfo>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        mempool.iterate_candidates_by_fee_rate(
+            tip_consensus_hash,
+            tip_block_hash,
+            tip_height,
+            chainstate,
+            |package| todo(package),
+        )
+    }
+}
+
 impl StacksBlockBuilder {
     fn from_parent_pubkey_hash(
         miner_id: usize,
@@ -366,6 +486,7 @@ impl StacksBlockBuilder {
             total_confirmed_streamed_fees: 0,
             total_streamed_fees: 0,
             bytes_so_far: bytes_so_far,
+            last_tx_cost: None,
             anchored_done: false,
             prev_microblock_header: StacksMicroblockHeader::first_unsigned(
                 &EMPTY_MICROBLOCK_PARENT_HASH,
@@ -498,6 +619,7 @@ impl StacksBlockBuilder {
         tx: &StacksTransaction,
         tx_len: u64,
     ) -> Result<(), Error> {
+        self.last_tx_cost = None;
         if self.bytes_so_far + tx_len >= MAX_EPOCH_SIZE.into() {
             return Err(Error::BlockTooBigError);
         }
@@ -513,6 +635,7 @@ impl StacksBlockBuilder {
                 ));
             }
 
+            let block_cost_before = clarity_tx.cost_so_far();
             let (fee, _receipt) = StacksChainState::process_transaction(clarity_tx, tx, true)
                 .map_err(|e| match e {
                     Error::CostOverflowError(cost_before, cost_after, total_budget) => {
@@ -522,11 +645,13 @@ impl StacksBlockBuilder {
                             &cost_after,
                             &total_budget
                         );
+                        self.last_tx_cost = cost_delta(&cost_after, &cost_before);
                         clarity_tx.reset_cost(cost_before);
                         Error::BlockTooBigError
                     }
                     _ => e,
                 })?;
+            self.last_tx_cost = cost_delta(&clarity_tx.cost_so_far(), &block_cost_before);
 
             debug!("Include tx {}", tx.txid());
 
@@ -933,6 +1058,8 @@ impl StacksBlockBuilder {
 
     /// Given access to the mempool, mine an anchored block with no more than the given execution cost.
     ///   returns the assembled block, and the consumed execution budget.
+    /// Transactions are considered in the order in which they arrived (see ArrivalTimeSelector);
+    ///   use build_anchored_block_with_selector() to choose a different order.
     pub fn build_anchored_block(
         chainstate_handle: &StacksChainState, // not directly used; used as a handle to open other chainstates
         burn_dbconn: &dyn BurnStateDB,
@@ -944,6 +1071,36 @@ impl StacksBlockBuilder {
         coinbase_tx: &StacksTransaction,
         execution_budget: ExecutionCost,
     ) -> Result<(StacksBlock, ExecutionCost, u64), Error> {
+        StacksBlockBuilder::build_anchored_block_with_selector(
+            chainstate_handle,
+            burn_dbconn,
+            mempool,
+            parent_stacks_header,
+            total_burn,
+            proof,
+            pubkey_hash,
+            coinbase_tx,
+            execution_budget,
+            &ArrivalTimeSelector,
+        )
+        .map(|(block, utilization, size)| (block, utilization.consumed, size))
+    }
+
+    /// Given access to the mempool, mine an anchored block with no more than the given execution cost,
+    ///   considering mempool transactions in the order chosen by `selector`.
+    ///   returns the assembled block, how much of the execution budget it used, and its size.
+    pub fn build_anchored_block_with_selector(
+        chainstate_handle: &StacksChainState, // not directly used; used as a handle to open other chainstates
+        burn_dbconn: &dyn BurnStateDB,
+        mempool: &MemPoolDB,
+        parent_stacks_header: &StacksHeaderInfo, // Stacks header we're building off of
+        total_burn: u64, // the burn so far on the burnchain (i.e. from the last burnchain block)
+        proof: VRFProof, // proof over the burnchain's last seed
+        pubkey_hash: Hash160,
+        coinbase_tx: &StacksTransaction,
+        execution_budget: ExecutionCost,
+        selector: &dyn TransactionSelector,
+    ) -> Result<(StacksBlock, BlockCostUtilization, u64), Error> {
         if let TransactionPayload::Coinbase(..) = coinbase_tx.payload {
         } else {
             return Err(Error::MemPoolError(
@@ -963,6 +1120,7 @@ impl StacksBlockBuilder {
         );
 
         let (mut header_reader_chainstate, _) = chainstate_handle.reopen()?; // used for reading block headers during an epoch
+        let mut utilization = BlockCostUtilization::new(execution_budget.clone());
         let (mut chainstate, _) = chainstate_handle.reopen_limited(execution_budget)?; // used for processing a block up to the given limit
 
         let mut builder = StacksBlockBuilder::make_block_builder(
@@ -979,12 +1137,13 @@ impl StacksBlockBuilder {
         let mut mined_origin_nonces: HashMap<StacksAddress, u64> = HashMap::new(); // map addrs of mined transaction origins to the nonces we used
         let mut mined_sponsor_nonces: HashMap<StacksAddress, u64> = HashMap::new(); // map addrs of mined transaction sponsors to the nonces we used
 
-        let result = mempool.iterate_candidates(
+        let result = selector.iterate_candidates(
+            mempool,
             &tip_consensus_hash,
             &tip_block_hash,
             tip_height,
             &mut header_reader_chainstate,
-            |available_txs| {
+            &mut |available_txs| {
                 for txinfo in available_txs.into_iter() {
                     // skip transactions early if we can
                     if considered.contains(&txinfo.tx.txid()) {
@@ -1007,16 +1166,57 @@ impl StacksBlockBuilder {
 
                     considered.insert(txinfo.tx.txid());
 
-                    match builder.try_mine_tx_with_len(
+                    // don't bother trying transactions that are known not to fit
+                    let recorded_cost_opt =
+                        match MemPoolDB::get_tx_cost(mempool.conn(), &txinfo.tx.txid()) {
+                            Ok(recorded_cost_opt) => recorded_cost_opt,
+                            Err(e) => {
+                                warn!(
+                                    "Failed to load recorded cost of tx {}: {:?}",
+                                    &txinfo.tx.txid(),
+                                    &e
+                                );
+                                None
+                            }
+                        };
+                    if let Some(recorded_cost) = recorded_cost_opt {
+                        if !utilization.check_fits(&epoch_tx.cost_so_far(), &recorded_cost) {
+                            debug!(
+                                "Skip tx {}: recorded cost {} does not fit in the block budget",
+                                &txinfo.tx.txid(),
+                                &recorded_cost
+                            );
+                            utilization.skipped_txs += 1;
+                            continue;
+                        }
+                    }
+
+                    let cost_before = epoch_tx.cost_so_far();
+                    let mine_result = builder.try_mine_tx_with_len(
                         &mut epoch_tx,
                         &txinfo.tx,
                         txinfo.metadata.len,
-                    ) {
+                    );
+
+                    if let Some(ref tx_cost) = builder.last_tx_cost {
+                        if let Err(e) = mempool.record_tx_cost(&txinfo.tx.txid(), tx_cost) {
+                            warn!(
+                                "Failed to record cost of tx {}: {:?}",
+                                &txinfo.tx.txid(),
+                                &e
+                            );
+                        }
+                    }
+
+                    match mine_result {
                         Ok(_) => {}
                         Err(Error::BlockTooBigError) => {
                             // done mining -- our execution budget is exceeded.
                             // Make the block from the transactions we did manage to get
                             debug!("Block budget exceeded on tx {}", &txinfo.tx.txid());
+                            if let Some(ref tx_cost) = builder.last_tx_cost {
+                                utilization.check_fits(&cost_before, tx_cost);
+                            }
                         }
                         Err(Error::InvalidStacksTransaction(_, true)) => {
                             // if we have an invalid transaction that was quietly ignored, don't warn here either
@@ -1055,8 +1255,17 @@ impl StacksBlockBuilder {
         // save the block so we can build microblocks off of it
         let block = builder.mine_anchored_block(&mut epoch_tx);
         let size = builder.bytes_so_far;
-        let consumed = builder.epoch_finish(epoch_tx);
-        Ok((block, consumed, size))
+        utilization.consumed = builder.epoch_finish(epoch_tx);
+
+        debug!(
+            "Anchored block {} consumed {} of budget {}; saturated: {:?}, skipped {} txs",
+            block.block_hash(),
+            &utilization.consumed,
+            &utilization.budget,
+            &utilization.saturated,
+            utilization.skipped_txs
+        );
+        Ok((block, utilization, size))
     }
 }
 
@@ -5582,6 +5791,80 @@ pub mod test {
         tx_signer.get_tx().unwrap()
     }
 
+    #[test]
+    fn test_block_cost_utilization() {
+        let budget = ExecutionCost {
+            write_length: 100,
+            write_count: 10,
+            read_length: 100,
+            read_count: 10,
+            runtime: 0,
+        };
+        let mut utilization = BlockCostUtilization::new(budget.clone());
+        let so_far = ExecutionCost {
+            write_length: 50,
+            write_count: 9,
+            read_length: 0,
+            read_count: 5,
+            runtime: 0,
+        };
+
+        // fits
+        let tx_cost = ExecutionCost {
+            write_length: 50,
+            write_count: 1,
+            read_length: 10,
+            read_count: 1,
+            runtime: 0,
+        };
+        assert!(utilization.check_fits(&so_far, &tx_cost));
+        assert_eq!(utilization.saturated, vec![]);
+
+        // overflows write_count
+        let tx_cost = ExecutionCost {
+            write_length: 1,
+            write_count: 2,
+            read_length: 10,
+            read_count: 1,
+            runtime: 0,
+        };
+        assert!(!utilization.check_fits(&so_far, &tx_cost));
+        assert_eq!(
+            utilization.saturated,
+            vec![ExecutionCostDimension::WriteCount]
+        );
+
+        // overflows write_count again, and runtime
+        let tx_cost = ExecutionCost {
+            write_length: 1,
+            write_count: 2,
+            read_length: 10,
+            read_count: 1,
+            runtime: 1,
+        };
+        assert!(!utilization.check_fits(&so_far, &tx_cost));
+        assert_eq!(
+            utilization.saturated,
+            vec![
+                ExecutionCostDimension::WriteCount,
+                ExecutionCostDimension::Runtime
+            ]
+        );
+
+        // cost overflow counts as overflowing every dimension
+        let mut utilization = BlockCostUtilization::new(ExecutionCost::max_value());
+        assert!(!utilization.check_fits(&ExecutionCost::max_value(), &tx_cost));
+        assert_eq!(utilization.saturated, ExecutionCostDimension::ALL.to_vec());
+
+        let mut utilization = BlockCostUtilization::new(budget);
+        utilization.consumed = so_far;
+        assert_eq!(utilization.percent(ExecutionCostDimension::WriteLength), 50);
+        assert_eq!(utilization.percent(ExecutionCostDimension::WriteCount), 90);
+        assert_eq!(utilization.percent(ExecutionCostDimension::ReadLength), 0);
+        assert_eq!(utilization.percent(ExecutionCostDimension::ReadCount), 50);
+        assert_eq!(utilization.percent(ExecutionCostDimension::Runtime), 100);
+    }
+
     #[test]
     fn test_build_anchored_blocks_empty() {
         let peer_config = TestPeerConfig::new("test_build_anchored_blocks_empty", 2000, 2001);
@@ -6170,6 +6453,223 @@ pub mod test {
         }
     }
 
+    #[test]
+    fn test_build_anchored_blocks_fee_rate_prioritized() {
+        let mut privks_low_fee = vec![];
+        let mut privks_high_fee = vec![];
+        let mut initial_balances = vec![];
+        let num_blocks = 5;
+        for _ in 0..(2 * num_blocks) {
+            let pk = StacksPrivateKey::new();
+            let addr = StacksAddress::from_public_keys(
+                C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+                &AddressHashMode::SerializeP2PKH,
+                1,
+                &vec![StacksPublicKey::from_private(&pk)],
+            )
+            .unwrap()
+            .to_account_principal();
+
+            if privks_low_fee.len() < num_blocks {
+                privks_low_fee.push(pk);
+            } else {
+                privks_high_fee.push(pk);
+            }
+            initial_balances.push((addr, 10000000000));
+        }
+
+        let mut peer_config = TestPeerConfig::new(
+            "test_build_anchored_blocks_fee_rate_prioritized",
+            40240,
+            40241,
+        );
+        peer_config.initial_balances = initial_balances;
+
+        let mut peer = TestPeer::new(peer_config);
+
+        let chainstate_path = peer.chainstate_path.clone();
+
+        let mut expensive_part = vec![];
+        for i in 0..100 {
+            expensive_part.push(format!("(define-data-var var-{} int 0)", i));
+        }
+        let contract = format!(
+            "{}
+            (define-data-var bar int 0)
+            (define-public (get-bar) (ok (var-get bar)))",
+            expensive_part.join("\n")
+        );
+
+        for tenure_id in 0..num_blocks {
+            let tip =
+                SortitionDB::get_canonical_burn_chain_tip(&peer.sortdb.as_ref().unwrap().conn())
+                    .unwrap();
+
+            let (burn_ops, stacks_block, microblocks) = peer.make_tenure(
+                |ref mut miner,
+                 ref mut sortdb,
+                 ref mut chainstate,
+                 vrf_proof,
+                 ref parent_opt,
+                 ref parent_microblock_header_opt| {
+                    let parent_tip = match parent_opt {
+                        None => StacksChainState::get_genesis_header_info(chainstate.headers_db())
+                            .unwrap(),
+                        Some(block) => {
+                            let ic = sortdb.index_conn();
+                            let snapshot =
+                                SortitionDB::get_block_snapshot_for_winning_stacks_block(
+                                    &ic,
+                                    &tip.sortition_id,
+                                    &block.block_hash(),
+                                )
+                                .unwrap()
+                                .unwrap(); // succeeds because we don't fork
+                            StacksChainState::get_anchored_block_header_info(
+                                chainstate.headers_db(),
+                                &snapshot.consensus_hash,
+                                &snapshot.winning_stacks_block_hash,
+                            )
+                            .unwrap()
+                            .unwrap()
+                        }
+                    };
+
+                    let parent_header_hash = parent_tip.anchored_header.block_hash();
+                    let parent_consensus_hash = parent_tip.consensus_hash.clone();
+                    let coinbase_tx = make_coinbase(miner, tenure_id);
+
+                    let mut mempool = MemPoolDB::open(false, 0x80000000, &chainstate_path).unwrap();
+
+                    if tenure_id == 0 {
+                        let anchored_block = StacksBlockBuilder::build_anchored_block(
+                            chainstate,
+                            &sortdb.index_conn(),
+                            &mempool,
+                            &parent_tip,
+                            tip.total_burn,
+                            vrf_proof,
+                            Hash160([tenure_id as u8; 20]),
+                            &coinbase_tx,
+                            ExecutionCost::max_value(),
+                        )
+                        .unwrap();
+                        return (anchored_block.0, vec![]);
+                    }
+
+                    // forget the previous tenure's crowded-out low-fee contract
+                    mempool
+                        .conn()
+                        .execute("DELETE FROM mempool", rusqlite::NO_PARAMS)
+                        .unwrap();
+
+                    // arrives first, but pays a quarter of the fee rate (both pay enough to be
+                    // admitted to the mempool)
+                    let low_fee_tx = make_user_contract_publish(
+                        &privks_low_fee[tenure_id],
+                        0,
+                        (2 * contract.len()) as u64,
+                        &format!("low-fee-{}", tenure_id),
+                        &contract,
+                    );
+                    mempool
+                        .submit(&parent_consensus_hash, &parent_header_hash, low_fee_tx)
+                        .unwrap();
+
+                    let high_fee_tx = make_user_contract_publish(
+                        &privks_high_fee[tenure_id],
+                        0,
+                        (8 * contract.len()) as u64,
+                        &format!("high-fee-{}", tenure_id),
+                        &contract,
+                    );
+                    mempool
+                        .submit(&parent_consensus_hash, &parent_header_hash, high_fee_tx)
+                        .unwrap();
+
+                    // find out what both contracts cost together, and then only allow for
+                    // three quarters of that -- enough for one of them, but not both.
+                    let (full_block, full_cost, _) = StacksBlockBuilder::build_anchored_block(
+                        chainstate,
+                        &sortdb.index_conn(),
+                        &mempool,
+                        &parent_tip,
+                        tip.total_burn,
+                        vrf_proof.clone(),
+                        Hash160([tenure_id as u8; 20]),
+                        &coinbase_tx,
+                        ExecutionCost::max_value(),
+                    )
+                    .unwrap();
+                    assert_eq!(full_block.txs.len(), 3);
+
+                    let execution_cost = ExecutionCost {
+                        write_length: full_cost.write_length * 3 / 4,
+                        write_count: full_cost.write_count * 3 / 4,
+                        read_length: full_cost.read_length * 3 / 4,
+                        read_count: full_cost.read_count * 3 / 4,
+                        runtime: full_cost.runtime * 3 / 4,
+                    };
+
+                    // in arrival order, the low-fee contract crowds out the high-fee one
+                    let (arrival_block, _, _) =
+                        StacksBlockBuilder::build_anchored_block_with_selector(
+                            chainstate,
+                            &sortdb.index_conn(),
+                            &mempool,
+                            &parent_tip,
+                            tip.total_burn,
+                            vrf_proof.clone(),
+                            Hash160([tenure_id as u8; 20]),
+                            &coinbase_tx,
+                            execution_cost.clone(),
+                            &ArrivalTimeSelector,
+                        )
+                        .unwrap();
+                    assert_eq!(arrival_block.txs.len(), 2);
+                    assert_eq!(
+                        arrival_block.txs[1].get_fee_rate(),
+                        (2 * contract.len()) as u64
+                    );
+
+                    let anchored_block = StacksBlockBuilder::build_anchored_block_with_selector(
+                        chainstate,
+                        &sortdb.index_conn(),
+                        &mempool,
+                        &parent_tip,
+                        tip.total_burn,
+                        vrf_proof,
+                        Hash160([tenure_id as u8; 20]),
+                        &coinbase_tx,
+                        execution_cost,
+                        &FeeRateSelector,
+                    )
+                    .unwrap();
+                    (anchored_block.0, vec![])
+                },
+            );
+
+            peer.next_burnchain_block(burn_ops.clone());
+            peer.process_stacks_epoch_at_tip(&stacks_block, &microblocks);
+
+            if tenure_id > 0 {
+                // only the high-fee contract made it in
+                assert_eq!(stacks_block.txs.len(), 2);
+                match stacks_block.txs[1].payload {
+                    TransactionPayload::SmartContract(ref contract_tx) => {
+                        assert_eq!(
+                            contract_tx.name,
+                            ContractName::from(format!("high-fee-{}", tenure_id).as_str())
+                        );
+                    }
+                    _ => {
+                        assert!(false);
+                    }
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_build_anchored_blocks_multiple_chaintips() {
         let mut privks = vec![];
@@ -846,6 +846,7 @@ pub struct StacksBlockBuilder {
     pub total_streamed_fees: u64,
     anchored_done: bool,
     bytes_so_far: u64,
+    last_tx_cost: Option<ExecutionCost>,
     prev_microblock_header: StacksMicroblockHeader,
     miner_privkey: StacksPrivateKey,
     miner_payouts: Option<Vec<MinerReward>>,
@@ -23,6 +23,8 @@ use rusqlite::Transaction;
 use rusqlite::NO_PARAMS;
 
 use std::cmp;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::fmt;
 use std::ops::Deref;
 use std::ops::DerefMut;
 
@@ -41,6 +43,7 @@ use std::io::Read;
 use std::path::{Path, PathBuf};
 
 use util::db::query_row;
+use util::db::query_row_columns;
 use util::db::query_rows;
 use util::db::tx_begin_immediate;
 use util::db::tx_busy_handler;
@@ -53,12 +56,68 @@ use util::get_epoch_time_secs;
 use core::FIRST_BURNCHAIN_CONSENSUS_HASH;
 use core::FIRST_STACKS_BLOCK_HASH;
 
+use vm::costs::ExecutionCost;
+
 use rusqlite::Error as SqliteError;
 
 // maximum number of confirmations a transaction can have before it's garbage-collected
 pub const MEMPOOL_MAX_TRANSACTION_AGE: u64 = 256;
 pub const MAXIMUM_MEMPOOL_TX_CHAINING: u64 = 5;
 
+// default minimum fee-rate increase required for a transaction to replace another with the same
+// origin (or sponsor) nonce
+pub const MEMPOOL_DEFAULT_MIN_RBF_FEE_RATE_INCREMENT: u64 = 1;
+
+// default upper bounds on the number of transactions and total transaction bytes in the mempool
+pub const MEMPOOL_DEFAULT_MAX_TX_COUNT: u64 = 100_000;
+pub const MEMPOOL_DEFAULT_MAX_TX_BYTES: u64 = 256 * 1024 * 1024;
+
+// number of eviction candidates to fetch at a time when making room in a full mempool
+const MEMPOOL_EVICTION_BATCH_SIZE: i64 = 16;
+
+/// Why a transaction left the mempool without being mined
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MemPoolDropReason {
+    /// replaced by a transaction with the same nonce and a higher fee rate
+    ReplaceByFee,
+    /// removed by garbage collection after MEMPOOL_MAX_TRANSACTION_AGE blocks
+    StaleGarbageCollect,
+    /// evicted to make room for a higher fee-rate transaction while the mempool was full
+    Evicted,
+}
+
+impl fmt::Display for MemPoolDropReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MemPoolDropReason::ReplaceByFee => write!(f, "ReplaceByFee"),
+            MemPoolDropReason::StaleGarbageCollect => write!(f, "StaleGarbageCollect"),
+            MemPoolDropReason::Evicted => write!(f, "Evicted"),
+        }
+    }
+}
+
+/// Tunable mempool admission policy
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemPoolSettings {
+    /// A transaction with the same origin (or sponsor) nonce as an existing mempool transaction
+    /// only replaces it if its fee rate is at least this much higher.
+    pub min_rbf_fee_rate_increment: u64,
+    /// Maximum number of transactions the mempool will hold.
+    pub max_tx_count: u64,
+    /// Maximum total size, in bytes, of the transactions the mempool will hold.
+    pub max_tx_bytes: u64,
+}
+
+impl Default for MemPoolSettings {
+    fn default() -> MemPoolSettings {
+        MemPoolSettings {
+            min_rbf_fee_rate_increment: MEMPOOL_DEFAULT_MIN_RBF_FEE_RATE_INCREMENT,
+            max_tx_count: MEMPOOL_DEFAULT_MAX_TX_COUNT,
+            max_tx_bytes: MEMPOOL_DEFAULT_MAX_TX_BYTES,
+        }
+    }
+}
+
 pub struct MemPoolAdmitter {
     // mempool admission should have its own chain state view.
     //   the mempool admitter interacts with the chain state
@@ -176,6 +235,64 @@ impl FromRow<MemPoolTxInfo> for MemPoolTxInfo {
     }
 }
 
+/// Fee-rate score of the best package (a prefix of the nonce-ordered transactions) that can be
+/// taken from one chain of mempool transactions
+#[derive(Debug, PartialEq, Eq)]
+struct PackageScore {
+    chain_index: usize,
+    num_txs: usize,
+    total_fee: u128,
+    total_len: u128,
+    first_accept_time: u64,
+}
+
+impl PackageScore {
+    /// Find the best-paying non-empty prefix of a chain.  On a tie, the shorter prefix wins.
+    fn best_in_chain(chain_index: usize, chain: &VecDeque<MemPoolTxInfo>) -> PackageScore {
+        let mut best: Option<PackageScore> = None;
+        let mut total_fee = 0u128;
+        let mut total_len = 0u128;
+        for (i, txinfo) in chain.iter().enumerate() {
+            total_fee += (txinfo.metadata.fee_rate as u128) * (txinfo.metadata.len as u128);
+            total_len += txinfo.metadata.len as u128;
+            let candidate = PackageScore {
+                chain_index,
+                num_txs: i + 1,
+                total_fee,
+                total_len,
+                first_accept_time: chain[0].metadata.accept_time,
+            };
+            best = match best {
+                Some(best) if best.cmp_fee_rate(&candidate) != cmp::Ordering::Less => Some(best),
+                _ => Some(candidate),
+            };
+        }
+        best.expect("BUG: empty transaction chain")
+    }
+
+    fn cmp_fee_rate(&self, other: &PackageScore) -> cmp::Ordering {
+        // compare total_fee / total_len without dividing
+        let total_len = cmp::max(self.total_len, 1);
+        let other_total_len = cmp::max(other.total_len, 1);
+        (self.total_fee * other_total_len).cmp(&(other.total_fee * total_len))
+    }
+}
+
+impl Ord for PackageScore {
+    /// Higher fee rate first; then earlier arrival; then lower chain index, for determinism
+    fn cmp(&self, other: &PackageScore) -> cmp::Ordering {
+        self.cmp_fee_rate(other)
+            .then(other.first_accept_time.cmp(&self.first_accept_time))
+            .then(other.chain_index.cmp(&self.chain_index))
+    }
+}
+
+impl PartialOrd for PackageScore {
+    fn partial_cmp(&self, other: &PackageScore) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 const MEMPOOL_SQL: &'static [&'static str] = &[
     r#"
     CREATE TABLE mempool(
@@ -207,15 +324,81 @@ const MEMPOOL_SQL: &'static [&'static str] = &[
     "#,
 ];
 
+/// Tables and indexes added to the mempool schema after its initial release.  These are (re)created on
+/// every open, so that mempool databases instantiated by older nodes pick them up as well.
+const MEMPOOL_SCHEMA_UPGRADES: &'static [&'static str] = &[
+    r#"
+    -- transactions that were evicted from the mempool by a replace-by-fee
+    CREATE TABLE IF NOT EXISTS mempool_replaced(
+        txid TEXT NOT NULL,
+        replaced_by TEXT NOT NULL,
+        replace_time INTEGER NOT NULL,
+        announced INTEGER NOT NULL,     -- 1 if this replacement was reported to observers
+        PRIMARY KEY (txid)
+    );
+    "#,
+    r#"
+    -- transactions that were evicted from a full mempool to make room for a higher fee-rate one
+    CREATE TABLE IF NOT EXISTS mempool_evicted(
+        txid TEXT NOT NULL,
+        evicted_by TEXT NOT NULL,
+        evict_time INTEGER NOT NULL,
+        announced INTEGER NOT NULL,     -- 1 if this eviction was reported to observers
+        PRIMARY KEY (txid)
+    );
+    "#,
+    r#"
+    -- execution cost each transaction consumed the last time a miner tried to mine it.
+    -- If the transaction exceeded the block budget, this is the cost up to the point it was aborted.
+    CREATE TABLE IF NOT EXISTS tx_costs(
+        txid TEXT NOT NULL,
+        execution_cost TEXT NOT NULL,
+        PRIMARY KEY (txid)
+    );
+    "#,
+    r#"
+    -- used to find the lowest fee-rate transactions to evict once the mempool is full
+    CREATE INDEX IF NOT EXISTS by_fee_rate ON mempool(fee_rate);
+    "#,
+    r#"
+    -- number of transactions in the mempool and their total length, kept up to date by the
+    -- triggers below so admission doesn't have to scan the mempool to enforce its size cap
+    CREATE TABLE IF NOT EXISTS mempool_size(
+        id INTEGER NOT NULL CHECK (id = 0),
+        tx_count INTEGER NOT NULL,
+        tx_bytes INTEGER NOT NULL,
+        PRIMARY KEY (id)
+    );
+    "#,
+    r#"
+    INSERT OR IGNORE INTO mempool_size (id, tx_count, tx_bytes)
+        SELECT 0, COUNT(*), IFNULL(SUM(length), 0) FROM mempool;
+    "#,
+    r#"
+    CREATE TRIGGER IF NOT EXISTS mempool_size_on_insert AFTER INSERT ON mempool
+    BEGIN
+        UPDATE mempool_size SET tx_count = tx_count + 1, tx_bytes = tx_bytes + NEW.length WHERE id = 0;
+    END;
+    "#,
+    r#"
+    CREATE TRIGGER IF NOT EXISTS mempool_size_on_delete AFTER DELETE ON mempool
+    BEGIN
+        UPDATE mempool_size SET tx_count = tx_count - 1, tx_bytes = tx_bytes - OLD.length WHERE id = 0;
+    END;
+    "#,
+];
+
 pub struct MemPoolDB {
     db: DBConn,
     path: String,
     admitter: MemPoolAdmitter,
+    settings: MemPoolSettings,
 }
 
 pub struct MemPoolTx<'a> {
     tx: DBTx<'a>,
     admitter: &'a mut MemPoolAdmitter,
+    settings: &'a MemPoolSettings,
 }
 
 impl<'a> Deref for MemPoolTx<'a> {
@@ -232,8 +415,16 @@ impl<'a> DerefMut for MemPoolTx<'a> {
 }
 
 impl<'a> MemPoolTx<'a> {
-    pub fn new(tx: DBTx<'a>, admitter: &'a mut MemPoolAdmitter) -> MemPoolTx<'a> {
-        MemPoolTx { tx, admitter }
+    pub fn new(
+        tx: DBTx<'a>,
+        admitter: &'a mut MemPoolAdmitter,
+        settings: &'a MemPoolSettings,
+    ) -> MemPoolTx<'a> {
+        MemPoolTx {
+            tx,
+            admitter,
+            settings,
+        }
     }
 
     pub fn commit(self) -> Result<(), db_error> {
@@ -325,12 +516,40 @@ impl MemPoolDB {
         Ok(())
     }
 
+    /// Add any tables that are missing from a mempool db created by an older node.
+    fn upgrade_mempool_db(conn: &mut DBConn) -> Result<(), db_error> {
+        let tx = tx_begin_immediate(conn)?;
+
+        for cmd in MEMPOOL_SCHEMA_UPGRADES {
+            tx.execute(cmd, NO_PARAMS).map_err(db_error::SqliteError)?;
+        }
+
+        tx.commit().map_err(db_error::SqliteError)?;
+        Ok(())
+    }
+
     /// Open the mempool db within the chainstate directory.
     /// The chainstate must be instantiated already.
     pub fn open(
         mainnet: bool,
         chain_id: u32,
         chainstate_path: &str,
+    ) -> Result<MemPoolDB, db_error> {
+        MemPoolDB::open_with_settings(
+            mainnet,
+            chain_id,
+            chainstate_path,
+            MemPoolSettings::default(),
+        )
+    }
+
+    /// Open the mempool db within the chainstate directory, using the given admission policy.
+    /// The chainstate must be instantiated already.
+    pub fn open_with_settings(
+        mainnet: bool,
+        chain_id: u32,
+        chainstate_path: &str,
+        settings: MemPoolSettings,
     ) -> Result<MemPoolDB, db_error> {
         match fs::metadata(chainstate_path) {
             Ok(md) => {
@@ -379,11 +598,13 @@ impl MemPoolDB {
             // instantiate!
             MemPoolDB::instantiate_mempool_db(&mut conn)?;
         }
+        MemPoolDB::upgrade_mempool_db(&mut conn)?;
 
         Ok(MemPoolDB {
             db: conn,
             path: db_path.to_string(),
             admitter: admitter,
+            settings: settings,
         })
     }
 
@@ -590,13 +811,171 @@ impl MemPoolDB {
         }
     }
 
+    ///
+    /// Iterate over candidates in the mempool, best-paying first.
+    ///  A transaction can only be mined after the lower-nonce transactions from the same
+    ///  origin, so each transaction is scored together with its unmined ancestors as a
+    ///  package, whose fee rate is the package's total fee over its total length.  todo will
+    ///  be called once for each package, with its transactions sorted in nonce order,
+    ///  in decreasing order of package fee rate.  Candidates are drawn from the same
+    ///  ancestor chain tips as iterate_candidates().
+    pub fn iterate_candidates_by_fee_rate<F, E>(
+        &self,
+        tip_consensus_hash: &ConsensusHash,
+        tip_block_hash: &BlockHeaderHash,
+        tip_height: u64,
+        chainstate: &mut StacksChainState,
+        mut todo: F,
+    ) -> Result<(), E>
+    where
+        F: FnMut(Vec<MemPoolTxInfo>) -> Result<(), E>,
+        E: From<db_error> + From<ChainstateError>,
+    {
+        let mut candidates = vec![];
+        self.iterate_candidates(
+            tip_consensus_hash,
+            tip_block_hash,
+            tip_height,
+            chainstate,
+            |available_txs| -> Result<(), E> {
+                candidates.extend(available_txs);
+                Ok(())
+            },
+        )?;
+
+        for package in MemPoolDB::order_by_package_fee_rate(candidates) {
+            todo(package)?;
+        }
+        Ok(())
+    }
+
+    /// The (account, nonce) pairs a transaction uses up: its origin's nonce, and its sponsor's
+    /// nonce if it is sponsored.
+    fn nonce_slots(metadata: &MemPoolTxMetadata) -> Vec<(StacksAddress, u64)> {
+        let mut slots = vec![(metadata.origin_address.clone(), metadata.origin_nonce)];
+        if (&metadata.sponsor_address, metadata.sponsor_nonce)
+            != (&metadata.origin_address, metadata.origin_nonce)
+        {
+            slots.push((metadata.sponsor_address.clone(), metadata.sponsor_nonce));
+        }
+        slots
+    }
+
+    /// Group transactions into chains that must be mined in order, and repeatedly take the
+    /// best-paying package (a prefix of some chain) until there are none left.
+    ///
+    /// A transaction has to come after the one using the previous nonce of its origin, and also
+    /// after the one using the previous nonce of its sponsor, so a sponsored transaction ties its
+    /// origin's and its sponsor's nonces into the same chain.  Only the best-paying transaction
+    /// is kept for each account nonce.  Transactions whose nonces wait on each other in a cycle
+    /// can never be mined, and are left out.
+    fn order_by_package_fee_rate(candidates: Vec<MemPoolTxInfo>) -> Vec<Vec<MemPoolTxInfo>> {
+        let mut candidates = candidates;
+        candidates.sort_by(|tx1, tx2| tx2.metadata.fee_rate.cmp(&tx1.metadata.fee_rate));
+
+        let mut slots: HashMap<(StacksAddress, u64), usize> = HashMap::new();
+        let mut txs = vec![];
+        for txinfo in candidates.into_iter() {
+            let tx_slots = MemPoolDB::nonce_slots(&txinfo.metadata);
+            if tx_slots.iter().any(|slot| slots.contains_key(slot)) {
+                // a better-paying transaction already uses this nonce
+                continue;
+            }
+            for slot in tx_slots.into_iter() {
+                slots.insert(slot, txs.len());
+            }
+            txs.push(Some(txinfo));
+        }
+
+        // link each transaction to the ones using the next nonce of its origin or sponsor
+        let mut num_deps = vec![0; txs.len()];
+        let mut dependents = vec![vec![]; txs.len()];
+        let mut group = (0..txs.len()).collect::<Vec<_>>();
+        for ((address, nonce), &index) in slots.iter() {
+            if *nonce == 0 {
+                continue;
+            }
+            if let Some(&prior) = slots.get(&(address.clone(), nonce - 1)) {
+                num_deps[index] += 1;
+                dependents[prior].push(index);
+
+                let (root, prior_root) = (
+                    MemPoolDB::group_root(&mut group, index),
+                    MemPoolDB::group_root(&mut group, prior),
+                );
+                group[root] = prior_root;
+            }
+        }
+
+        // lay out each group as a chain, taking whichever ready transaction pays best next
+        let mut ready = BinaryHeap::new();
+        for (index, txinfo) in txs.iter().enumerate() {
+            if num_deps[index] == 0 {
+                let metadata = &txinfo.as_ref().unwrap().metadata;
+                ready.push((
+                    metadata.fee_rate,
+                    cmp::Reverse(metadata.accept_time),
+                    cmp::Reverse(index),
+                ));
+            }
+        }
+
+        let mut chain_indexes: HashMap<usize, usize> = HashMap::new();
+        let mut chains: Vec<VecDeque<MemPoolTxInfo>> = vec![];
+        while let Some((_, _, cmp::Reverse(index))) = ready.pop() {
+            for &dependent in dependents[index].iter() {
+                num_deps[dependent] -= 1;
+                if num_deps[dependent] == 0 {
+                    let metadata = &txs[dependent].as_ref().unwrap().metadata;
+                    ready.push((
+                        metadata.fee_rate,
+                        cmp::Reverse(metadata.accept_time),
+                        cmp::Reverse(dependent),
+                    ));
+                }
+            }
+
+            let root = MemPoolDB::group_root(&mut group, index);
+            let chain_index = *chain_indexes.entry(root).or_insert_with(|| {
+                chains.push(VecDeque::new());
+                chains.len() - 1
+            });
+            chains[chain_index].push_back(txs[index].take().unwrap());
+        }
+
+        let mut packages = BinaryHeap::new();
+        for (chain_index, chain) in chains.iter().enumerate() {
+            packages.push(PackageScore::best_in_chain(chain_index, chain));
+        }
+
+        let mut ordered = vec![];
+        while let Some(best) = packages.pop() {
+            let chain = &mut chains[best.chain_index];
+            ordered.push(chain.drain(0..best.num_txs).collect());
+            if chain.len() > 0 {
+                packages.push(PackageScore::best_in_chain(best.chain_index, chain));
+            }
+        }
+        ordered
+    }
+
+    /// Find the representative of a transaction's group of nonce-linked transactions
+    fn group_root(group: &mut Vec<usize>, index: usize) -> usize {
+        let mut root = index;
+        while group[root] != root {
+            root = group[root];
+        }
+        group[index] = root;
+        root
+    }
+
     pub fn conn(&self) -> &DBConn {
         &self.db
     }
 
     pub fn tx_begin<'a>(&'a mut self) -> Result<MemPoolTx<'a>, db_error> {
         let tx = tx_begin_immediate(&mut self.db)?;
-        Ok(MemPoolTx::new(tx, &mut self.admitter))
+        Ok(MemPoolTx::new(tx, &mut self.admitter, &self.settings))
     }
 
     fn db_has_tx(conn: &DBConn, txid: &Txid) -> Result<bool, db_error> {
@@ -714,6 +1093,25 @@ impl MemPoolDB {
         Ok(rows)
     }
 
+    /// Get the txids of all transactions in the mempool, across all tips.
+    pub fn get_all_txids(conn: &DBConn) -> Result<Vec<Txid>, db_error> {
+        let sql = "SELECT txid FROM mempool".to_string();
+        query_row_columns(conn, &sql, NO_PARAMS, "txid")
+    }
+
+    /// Get up to `count` transactions whose txids sort strictly after `txid`, in txid order.
+    /// Used to page through the whole mempool when serving mempool sync requests.
+    pub fn get_txs_after_txid(
+        conn: &DBConn,
+        txid: &Txid,
+        count: u64,
+    ) -> Result<Vec<MemPoolTxInfo>, db_error> {
+        let sql = "SELECT * FROM mempool WHERE txid > ?1 ORDER BY txid ASC LIMIT ?2";
+        let args: &[&dyn ToSql] = &[txid, &u64_to_sql(count)?];
+        let rows = query_rows::<MemPoolTxInfo, _>(conn, &sql, args)?;
+        Ok(rows)
+    }
+
     /// Get a transaction's metadata, given address and nonce, and whether the address is used as a sponsor or an origin.
     /// Faster than getting the MemPoolTxInfo, since no deserialization will be needed.
     /// Used to see if there exists a transaction with this info, so as to implement replace-by-fee
@@ -790,21 +1188,33 @@ impl MemPoolDB {
         let length = tx_bytes.len() as u64;
 
         // do we already have txs with either the same origin nonce or sponsor nonce ?
-        let prior_tx = {
-            match MemPoolDB::get_tx_metadata_by_address(tx, true, origin_address, origin_nonce)? {
-                Some(prior_tx) => Some(prior_tx),
-                None => MemPoolDB::get_tx_metadata_by_address(
-                    tx,
-                    false,
-                    sponsor_address,
-                    sponsor_nonce,
-                )?,
+        let prior_origin_tx =
+            MemPoolDB::get_tx_metadata_by_address(tx, true, origin_address, origin_nonce)?;
+        let prior_sponsor_tx =
+            MemPoolDB::get_tx_metadata_by_address(tx, false, sponsor_address, sponsor_nonce)?;
+
+        let mut prior_txs = vec![];
+        if let Some(prior_tx) = prior_origin_tx {
+            prior_txs.push(prior_tx);
+        }
+        if let Some(prior_tx) = prior_sponsor_tx {
+            if prior_txs.iter().all(|p| p.txid != prior_tx.txid) {
+                prior_txs.push(prior_tx);
             }
-        };
+        }
 
         // if so, is this a replace-by-fee? or a replace-in-chain-tip?
-        let add_tx = if let Some(prior_tx) = prior_tx {
-            if estimated_fee > prior_tx.estimated_fee {
+        // The new tx must be able to replace every tx it conflicts with.
+        let min_increment = tx.settings.min_rbf_fee_rate_increment;
+        for prior_tx in prior_txs.iter() {
+            if prior_tx.txid == txid {
+                // re-submission of the same tx
+                continue;
+            }
+
+            let add_tx = if fee_rate > prior_tx.fee_rate
+                && fee_rate - prior_tx.fee_rate >= min_increment
+            {
                 // is this a replace-by-fee ?
                 true
             } else if !tx.is_block_in_fork(
@@ -816,21 +1226,39 @@ impl MemPoolDB {
                 // is this a replace-across-fork ?
                 true
             } else {
-                // there's a >= fee tx in this fork, cannot add
-                info!("TX conflicts with sponsor/origin nonce in same fork with >= fee: new_txid={}, old_txid={}, origin_addr={}, origin_nonce={}, sponsor_addr={}, sponsor_nonce={}, new_fee={}, old_fee={}",
-                      txid, prior_tx.txid, origin_address, origin_nonce, sponsor_address, sponsor_nonce, estimated_fee, prior_tx.estimated_fee);
+                // there's a tx in this fork whose fee rate is not sufficiently lower, cannot add
+                info!("TX conflicts with sponsor/origin nonce in same fork without a sufficient fee rate increase: new_txid={}, old_txid={}, origin_addr={}, origin_nonce={}, sponsor_addr={}, sponsor_nonce={}, new_fee_rate={}, old_fee_rate={}, min_increment={}",
+                      txid, prior_tx.txid, origin_address, origin_nonce, sponsor_address, sponsor_nonce, fee_rate, prior_tx.fee_rate, min_increment);
                 false
+            };
+
+            if !add_tx {
+                return Err(MemPoolRejection::ConflictingNonceInMempool);
             }
-        } else {
-            // no conflicting TX with this origin/sponsor, go ahead and add
-            true
-        };
+        }
+
+        // make sure there's room for this tx
+        MemPoolDB::make_room_for_tx(
+            tx,
+            &txid,
+            length,
+            fee_rate,
+            origin_address,
+            sponsor_address,
+            &prior_txs,
+        )?;
 
-        if !add_tx {
-            return Err(MemPoolRejection::ConflictingNonceInMempool);
+        // remove the txs this one replaces.  An INSERT OR REPLACE would drop them too, but
+        // without firing the triggers that keep the mempool size up to date.
+        for prior_tx in prior_txs.iter() {
+            tx.execute(
+                "DELETE FROM mempool WHERE txid = ?1",
+                &[&prior_tx.txid as &dyn ToSql],
+            )
+            .map_err(|e| MemPoolRejection::DBError(db_error::SqliteError(e)))?;
         }
 
-        let sql = "INSERT OR REPLACE INTO mempool (
+        let sql = "INSERT INTO mempool (
             txid,
             origin_address,
             origin_nonce,
@@ -864,17 +1292,243 @@ impl MemPoolDB {
 
         tx.execute(sql, args)
             .map_err(|e| MemPoolRejection::DBError(db_error::SqliteError(e)))?;
+
+        // remember what got replaced, so it can be reported as dropped
+        for prior_tx in prior_txs.iter() {
+            if prior_tx.txid == txid {
+                continue;
+            }
+            debug!("Mempool tx {} replaced by {}", &prior_tx.txid, &txid);
+            MemPoolDB::record_replaced_tx(tx, &prior_tx.txid, &txid)?;
+        }
+        Ok(())
+    }
+
+    /// Get the number of transactions in the mempool, and their total size in bytes.
+    pub fn get_mempool_size(conn: &DBConn) -> Result<(u64, u64), db_error> {
+        let sql = "SELECT tx_count, tx_bytes FROM mempool_size WHERE id = 0";
+        let (count, bytes): (i64, i64) = conn
+            .query_row(sql, NO_PARAMS, |row| (row.get(0), row.get(1)))
+            .map_err(db_error::SqliteError)?;
+        Ok((count as u64, bytes as u64))
+    }
+
+    /// Evict transactions until a new transaction of the given length and fee rate fits within
+    /// the mempool's size limits, not counting the transactions it replaces.
+    /// Only transactions with a strictly lower fee rate are evicted, lowest fee rate first.  A
+    /// transaction is never evicted while a later nonce of its origin or sponsor account is still
+    /// in the mempool (as either an origin or a sponsor nonce), and transactions that share an
+    /// account with the new transaction are never evicted.  Evictions are recorded so they can be
+    /// reported as dropped.
+    /// Fails with MemPoolRejection::MempoolFull if no room can be made; in that case, the
+    /// caller must roll back the mempool transaction.
+    fn make_room_for_tx<'a>(
+        tx: &mut MemPoolTx<'a>,
+        txid: &Txid,
+        length: u64,
+        fee_rate: u64,
+        origin_address: &StacksAddress,
+        sponsor_address: &StacksAddress,
+        replaced_txs: &[MemPoolTxMetadata],
+    ) -> Result<(), MemPoolRejection> {
+        let (mut count, mut bytes) = MemPoolDB::get_mempool_size(tx)?;
+        for replaced_tx in replaced_txs.iter() {
+            if replaced_tx.txid != *txid {
+                count = count.saturating_sub(1);
+                bytes = bytes.saturating_sub(replaced_tx.len);
+            }
+        }
+        if !replaced_txs
+            .iter()
+            .any(|replaced_tx| replaced_tx.txid == *txid)
+        {
+            count = count.saturating_add(1);
+            bytes = bytes.saturating_add(length);
+        }
+
+        let max_count = tx.settings.max_tx_count;
+        let max_bytes = tx.settings.max_tx_bytes;
+        if count <= max_count && bytes <= max_bytes {
+            return Ok(());
+        }
+
+        let sql = "SELECT * FROM mempool AS m WHERE m.fee_rate < ?1
+                       AND m.origin_address NOT IN (?2, ?3) AND m.sponsor_address NOT IN (?2, ?3)
+                       AND NOT EXISTS (SELECT 1 FROM mempool AS n WHERE
+                           (n.origin_address = m.origin_address AND n.origin_nonce > m.origin_nonce) OR
+                           (n.sponsor_address = m.origin_address AND n.sponsor_nonce > m.origin_nonce) OR
+                           (n.origin_address = m.sponsor_address AND n.origin_nonce > m.sponsor_nonce) OR
+                           (n.sponsor_address = m.sponsor_address AND n.sponsor_nonce > m.sponsor_nonce))
+                   ORDER BY m.fee_rate ASC, m.accept_time DESC LIMIT ?4";
+
+        // Evicting a tx never makes another candidate ineligible, so evict from a batch of
+        // candidates and only query for more once it runs out.  A tx whose later nonces all get
+        // evicted becomes a candidate in the next batch.
+        let mut candidates = vec![].into_iter();
+        while count > max_count || bytes > max_bytes {
+            let victim = match candidates.next() {
+                Some(victim) => victim,
+                None => {
+                    // the txs this one replaces are never victims, so look far enough to skip
+                    // over them
+                    let args: &[&dyn ToSql] = &[
+                        &u64_to_sql(fee_rate)?,
+                        &origin_address.to_string(),
+                        &sponsor_address.to_string(),
+                        &((replaced_txs.len() as i64) + MEMPOOL_EVICTION_BATCH_SIZE),
+                    ];
+                    let batch: Vec<MemPoolTxMetadata> =
+                        query_rows::<MemPoolTxMetadata, _>(tx, sql, args)?
+                            .into_iter()
+                            .filter(|candidate| {
+                                replaced_txs
+                                    .iter()
+                                    .all(|replaced_tx| replaced_tx.txid != candidate.txid)
+                            })
+                            .collect();
+                    candidates = batch.into_iter();
+                    match candidates.next() {
+                        Some(victim) => victim,
+                        None => {
+                            info!("Mempool is full; rejecting tx {} (fee_rate={}, length={}): count={} (max {}), bytes={} (max {})",
+                                  txid, fee_rate, length, count, max_count, bytes, max_bytes);
+                            return Err(MemPoolRejection::MempoolFull);
+                        }
+                    }
+                }
+            };
+
+            debug!(
+                "Evict mempool tx {} (fee_rate={}) to make room for {} (fee_rate={})",
+                &victim.txid, victim.fee_rate, txid, fee_rate
+            );
+            tx.execute(
+                "DELETE FROM mempool WHERE txid = ?1",
+                &[&victim.txid as &dyn ToSql],
+            )
+            .map_err(|e| MemPoolRejection::DBError(db_error::SqliteError(e)))?;
+            MemPoolDB::record_evicted_tx(tx, &victim.txid, txid)?;
+
+            count = count.saturating_sub(1);
+            bytes = bytes.saturating_sub(victim.len);
+        }
+        Ok(())
+    }
+
+    /// Record that a transaction was evicted from a full mempool.
+    fn record_evicted_tx<'a>(
+        tx: &mut MemPoolTx<'a>,
+        evicted_txid: &Txid,
+        evicted_by: &Txid,
+    ) -> Result<(), db_error> {
+        let sql = "INSERT OR REPLACE INTO mempool_evicted (txid, evicted_by, evict_time, announced) VALUES (?1, ?2, ?3, 0)";
+        let args: &[&dyn ToSql] = &[
+            evicted_txid,
+            evicted_by,
+            &u64_to_sql(get_epoch_time_secs())?,
+        ];
+        tx.execute(sql, args).map_err(db_error::SqliteError)?;
+        Ok(())
+    }
+
+    /// Record that a transaction was evicted from the mempool by a replacement.
+    fn record_replaced_tx<'a>(
+        tx: &mut MemPoolTx<'a>,
+        replaced_txid: &Txid,
+        replaced_by: &Txid,
+    ) -> Result<(), db_error> {
+        let sql = "INSERT OR REPLACE INTO mempool_replaced (txid, replaced_by, replace_time, announced) VALUES (?1, ?2, ?3, 0)";
+        let args: &[&dyn ToSql] = &[
+            replaced_txid,
+            replaced_by,
+            &u64_to_sql(get_epoch_time_secs())?,
+        ];
+        tx.execute(sql, args).map_err(db_error::SqliteError)?;
+        Ok(())
+    }
+
+    /// Get the txid of the transaction that replaced the given transaction, if it was replaced.
+    pub fn get_replacing_txid(conn: &DBConn, txid: &Txid) -> Result<Option<Txid>, db_error> {
+        let sql = "SELECT replaced_by FROM mempool_replaced WHERE txid = ?1".to_string();
+        let mut txids: Vec<Txid> =
+            query_row_columns(conn, &sql, &[txid as &dyn ToSql], "replaced_by")?;
+        Ok(txids.pop())
+    }
+
+    /// Record the execution cost a miner observed for a transaction.
+    pub fn record_tx_cost(&self, txid: &Txid, cost: &ExecutionCost) -> Result<(), db_error> {
+        let sql = "INSERT OR REPLACE INTO tx_costs (txid, execution_cost) VALUES (?1, ?2)";
+        let args: &[&dyn ToSql] = &[txid, cost];
+        self.db.execute(sql, args).map_err(db_error::SqliteError)?;
         Ok(())
     }
 
+    /// Get the execution cost a miner last observed for a transaction, if any.
+    pub fn get_tx_cost(conn: &DBConn, txid: &Txid) -> Result<Option<ExecutionCost>, db_error> {
+        conn.query_row(
+            "SELECT execution_cost FROM tx_costs WHERE txid = ?1",
+            &[txid as &dyn ToSql],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(db_error::SqliteError)
+    }
+
+    /// Get the txids of all replaced transactions that have not yet been announced, and mark
+    /// them as announced.
+    pub fn take_unannounced_replaced_txids<'a>(
+        tx: &mut MemPoolTx<'a>,
+    ) -> Result<Vec<Txid>, db_error> {
+        let sql = "SELECT txid FROM mempool_replaced WHERE announced = 0 ORDER BY replace_time ASC"
+            .to_string();
+        let txids: Vec<Txid> = query_row_columns(tx, &sql, NO_PARAMS, "txid")?;
+        tx.execute(
+            "UPDATE mempool_replaced SET announced = 1 WHERE announced = 0",
+            NO_PARAMS,
+        )
+        .map_err(db_error::SqliteError)?;
+        Ok(txids)
+    }
+
+    /// Get the txids of all evicted transactions that have not yet been announced, and mark
+    /// them as announced.
+    pub fn take_unannounced_evicted_txids<'a>(
+        tx: &mut MemPoolTx<'a>,
+    ) -> Result<Vec<Txid>, db_error> {
+        let sql = "SELECT txid FROM mempool_evicted WHERE announced = 0 ORDER BY evict_time ASC"
+            .to_string();
+        let txids: Vec<Txid> = query_row_columns(tx, &sql, NO_PARAMS, "txid")?;
+        tx.execute(
+            "UPDATE mempool_evicted SET announced = 1 WHERE announced = 0",
+            NO_PARAMS,
+        )
+        .map_err(db_error::SqliteError)?;
+        Ok(txids)
+    }
+
     /// Garbage-collect the mempool.  Remove transactions that have a given number of
-    /// confirmations.
-    pub fn garbage_collect<'a>(tx: &mut MemPoolTx<'a>, min_height: u64) -> Result<(), db_error> {
-        let sql = "DELETE FROM mempool WHERE height < ?1";
+    /// confirmations.  Returns the txids of the removed transactions.
+    pub fn garbage_collect<'a>(
+        tx: &mut MemPoolTx<'a>,
+        min_height: u64,
+    ) -> Result<Vec<Txid>, db_error> {
         let args: &[&dyn ToSql] = &[&u64_to_sql(min_height)?];
+        let sql = "SELECT txid FROM mempool WHERE height < ?1".to_string();
+        let txids: Vec<Txid> = query_row_columns(tx, &sql, args, "txid")?;
 
+        let sql = "DELETE FROM mempool WHERE height < ?1";
         tx.execute(sql, args).map_err(db_error::SqliteError)?;
-        Ok(())
+
+        // forget announced replacements whose replacing tx is gone too
+        let sql = "DELETE FROM mempool_replaced WHERE announced = 1 AND replaced_by NOT IN (SELECT txid FROM mempool)";
+        tx.execute(sql, NO_PARAMS).map_err(db_error::SqliteError)?;
+
+        let sql = "DELETE FROM mempool_evicted WHERE announced = 1";
+        tx.execute(sql, NO_PARAMS).map_err(db_error::SqliteError)?;
+
+        let sql = "DELETE FROM tx_costs WHERE txid NOT IN (SELECT txid FROM mempool)";
+        tx.execute(sql, NO_PARAMS).map_err(db_error::SqliteError)?;
+        Ok(txids)
     }
 
     /// Scan the chain tip for all available transactions (but do not remove them!)
@@ -1043,10 +1697,12 @@ mod tests {
 
     use address::AddressHashMode;
     use burnchains::Address;
+    use burnchains::Txid;
     use chainstate::burn::{BlockHeaderHash, VRFSeed};
     use net::{Error as NetError, StacksMessageCodec};
     use util::{hash::hex_bytes, hash::to_hex, hash::*, log, secp256k1::*, strings::StacksString};
     use vm::{
+        costs::ExecutionCost,
         database::HeadersDB,
         database::NULL_BURN_STATE_DB,
         errors::Error as ClarityError,
@@ -1065,7 +1721,7 @@ mod tests {
         C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
     };
 
-    use super::MemPoolDB;
+    use super::{MemPoolDB, MemPoolSettings};
     use util::db::{DBConn, FromRow};
 
     use chainstate::burn::ConsensusHash;
@@ -1079,6 +1735,52 @@ mod tests {
     const SK_2: &'static str = "4ce9a8f7539ea93753a36405b16e8b57e15a552430410709c2b6d65dca5c02e201";
     const SK_3: &'static str = "cb95ddd0fe18ec57f4f3533b95ae564b3f1ae063dbf75b46334bd86245aef78501";
 
+    /// Add `tx` to the mempool at a fixed chain tip and height, after setting its origin nonce
+    /// and fee rate, with the given origin and sponsor accounts.  Returns its txid along with
+    /// the admission result.
+    fn add_sponsored_tx_at(
+        mempool_tx: &mut super::MemPoolTx,
+        tx: &mut StacksTransaction,
+        origin: &StacksAddress,
+        origin_nonce: u64,
+        sponsor: &StacksAddress,
+        sponsor_nonce: u64,
+        fee_rate: u64,
+    ) -> (Txid, Result<(), MemPoolRejection>) {
+        tx.set_origin_nonce(origin_nonce);
+        tx.set_fee_rate(fee_rate);
+        let txid = tx.txid();
+        let mut tx_bytes = vec![];
+        tx.consensus_serialize(&mut tx_bytes).unwrap();
+        let estimated_fee = fee_rate * (tx_bytes.len() as u64);
+        let res = MemPoolDB::try_add_tx(
+            mempool_tx,
+            &ConsensusHash([0x1; 20]),
+            &BlockHeaderHash([0x2; 32]),
+            txid.clone(),
+            tx_bytes,
+            estimated_fee,
+            fee_rate,
+            100,
+            origin,
+            origin_nonce,
+            sponsor,
+            sponsor_nonce,
+        );
+        (txid, res)
+    }
+
+    /// Like add_sponsored_tx_at, but for a tx that pays its own fee.
+    fn add_tx_at(
+        mempool_tx: &mut super::MemPoolTx,
+        tx: &mut StacksTransaction,
+        addr: &StacksAddress,
+        nonce: u64,
+        fee_rate: u64,
+    ) -> (Txid, Result<(), MemPoolRejection>) {
+        add_sponsored_tx_at(mempool_tx, tx, addr, nonce, addr, nonce, fee_rate)
+    }
+
     #[test]
     fn mempool_db_init() {
         let _chainstate = instantiate_chainstate(false, 0x80000000, "mempool_db_init");
@@ -1086,6 +1788,173 @@ mod tests {
         let _mempool = MemPoolDB::open(false, 0x80000000, &chainstate_path).unwrap();
     }
 
+    #[test]
+    fn mempool_db_upgrade_adds_missing_tables() {
+        let _chainstate =
+            instantiate_chainstate(false, 0x80000000, "mempool_db_upgrade_adds_missing_tables");
+        let chainstate_path = chainstate_path("mempool_db_upgrade_adds_missing_tables");
+        let mempool = MemPoolDB::open(false, 0x80000000, &chainstate_path).unwrap();
+
+        // simulate a mempool db created before the replacement and cost tables existed
+        mempool
+            .conn()
+            .execute("DROP TABLE mempool_replaced", rusqlite::NO_PARAMS)
+            .unwrap();
+        mempool
+            .conn()
+            .execute("DROP TABLE tx_costs", rusqlite::NO_PARAMS)
+            .unwrap();
+        drop(mempool);
+
+        let mut mempool = MemPoolDB::open(false, 0x80000000, &chainstate_path).unwrap();
+        let txid = Txid([0x11; 32]);
+        let cost = ExecutionCost {
+            write_length: 1,
+            write_count: 2,
+            read_length: 3,
+            read_count: 4,
+            runtime: 5,
+        };
+        mempool.record_tx_cost(&txid, &cost).unwrap();
+        assert_eq!(
+            MemPoolDB::get_tx_cost(mempool.conn(), &txid).unwrap(),
+            Some(cost)
+        );
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        MemPoolDB::garbage_collect(&mut mempool_tx, 0).unwrap();
+        mempool_tx.commit().unwrap();
+
+        // re-opening an up-to-date db is a no-op
+        let _mempool = MemPoolDB::open(false, 0x80000000, &chainstate_path).unwrap();
+    }
+
+    #[test]
+    fn mempool_db_has_fee_rate_index() {
+        let _chainstate =
+            instantiate_chainstate(false, 0x80000000, "mempool_db_has_fee_rate_index");
+        let chainstate_path = chainstate_path("mempool_db_has_fee_rate_index");
+        let has_index = |mempool: &MemPoolDB| -> bool {
+            let count: i64 = mempool
+                .conn()
+                .query_row(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND name = 'by_fee_rate' AND tbl_name = 'mempool'",
+                    rusqlite::NO_PARAMS,
+                    |row| row.get(0),
+                )
+                .unwrap();
+            count == 1
+        };
+
+        let mempool = MemPoolDB::open(false, 0x80000000, &chainstate_path).unwrap();
+        assert!(has_index(&mempool));
+
+        // an older mempool db without the index picks it up when it is re-opened
+        mempool
+            .conn()
+            .execute("DROP INDEX by_fee_rate", rusqlite::NO_PARAMS)
+            .unwrap();
+        assert!(!has_index(&mempool));
+        drop(mempool);
+
+        let mempool = MemPoolDB::open(false, 0x80000000, &chainstate_path).unwrap();
+        assert!(has_index(&mempool));
+    }
+
+    #[test]
+    fn mempool_size_is_tracked() {
+        let _chainstate = instantiate_chainstate(false, 0x80000000, "mempool_size_is_tracked");
+        let chainstate_path = chainstate_path("mempool_size_is_tracked");
+        let mut mempool = MemPoolDB::open(false, 0x80000000, &chainstate_path).unwrap();
+
+        let mut tx = codec_all_transactions(
+            &TransactionVersion::Testnet,
+            0x80000000,
+            &TransactionAnchorMode::Any,
+            &TransactionPostConditionMode::Allow,
+        )
+        .pop()
+        .unwrap();
+        let mut tx_bytes = vec![];
+        tx.consensus_serialize(&mut tx_bytes).unwrap();
+        let tx_len = tx_bytes.len() as u64;
+
+        let addr_1 = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x11; 20]),
+        };
+        let addr_2 = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x22; 20]),
+        };
+
+        let scanned_size = |conn: &DBConn| -> (u64, u64) {
+            let (count, bytes): (i64, i64) = conn
+                .query_row(
+                    "SELECT COUNT(*), IFNULL(SUM(length), 0) FROM mempool",
+                    rusqlite::NO_PARAMS,
+                    |row| (row.get(0), row.get(1)),
+                )
+                .unwrap();
+            (count as u64, bytes as u64)
+        };
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        add_tx_at(&mut mempool_tx, &mut tx, &addr_1, 0, 1)
+            .1
+            .unwrap();
+        add_tx_at(&mut mempool_tx, &mut tx, &addr_1, 1, 1)
+            .1
+            .unwrap();
+        add_tx_at(&mut mempool_tx, &mut tx, &addr_2, 0, 2)
+            .1
+            .unwrap();
+        assert_eq!(
+            MemPoolDB::get_mempool_size(&mempool_tx).unwrap(),
+            (3, 3 * tx_len)
+        );
+
+        // a replacement takes the place of the tx it replaces
+        add_tx_at(&mut mempool_tx, &mut tx, &addr_2, 0, 10)
+            .1
+            .unwrap();
+        assert_eq!(
+            MemPoolDB::get_mempool_size(&mempool_tx).unwrap(),
+            (3, 3 * tx_len)
+        );
+        assert_eq!(scanned_size(&mempool_tx), (3, 3 * tx_len));
+        mempool_tx.commit().unwrap();
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        MemPoolDB::garbage_collect(&mut mempool_tx, 101).unwrap();
+        assert_eq!(MemPoolDB::get_mempool_size(&mempool_tx).unwrap(), (0, 0));
+
+        add_tx_at(&mut mempool_tx, &mut tx, &addr_1, 0, 1)
+            .1
+            .unwrap();
+        add_tx_at(&mut mempool_tx, &mut tx, &addr_2, 0, 2)
+            .1
+            .unwrap();
+        mempool_tx.commit().unwrap();
+
+        // an older mempool db without the size table has it filled in when it is re-opened
+        for sql in &[
+            "DROP TRIGGER mempool_size_on_insert",
+            "DROP TRIGGER mempool_size_on_delete",
+            "DROP TABLE mempool_size",
+        ] {
+            mempool.conn().execute(sql, rusqlite::NO_PARAMS).unwrap();
+        }
+        drop(mempool);
+
+        let mempool = MemPoolDB::open(false, 0x80000000, &chainstate_path).unwrap();
+        assert_eq!(
+            MemPoolDB::get_mempool_size(mempool.conn()).unwrap(),
+            (2, 2 * tx_len)
+        );
+        assert_eq!(scanned_size(mempool.conn()), (2, 2 * tx_len));
+    }
+
     #[test]
     fn mempool_do_not_replace_tx() {
         let mut chainstate = instantiate_chainstate(false, 0x80000000, "mempool_do_not_replace_tx");
@@ -1374,6 +2243,10 @@ mod tests {
             // was replaced
             assert!(!MemPoolDB::db_has_tx(&mempool_tx, &old_txid).unwrap());
             assert!(MemPoolDB::db_has_tx(&mempool_tx, &txid).unwrap());
+            assert_eq!(
+                MemPoolDB::get_replacing_txid(&mempool_tx, &old_txid).unwrap(),
+                Some(txid.clone())
+            );
 
             let tx_info_after = MemPoolDB::get_tx_metadata_by_address(
                 &mempool_tx,
@@ -1478,10 +2351,61 @@ mod tests {
         .unwrap();
         assert_eq!(txs.len(), 0);
 
+        eprintln!("record tx costs");
+        let all_txs = MemPoolDB::get_txs_after(
+            &mempool.db,
+            &ConsensusHash([0x1; 20]),
+            &BlockHeaderHash([0x2; 32]),
+            0,
+            num_txs,
+        )
+        .unwrap();
+        for (i, tx_info) in all_txs.iter().enumerate() {
+            let txid = tx_info.tx.txid();
+            assert_eq!(MemPoolDB::get_tx_cost(&mempool.db, &txid).unwrap(), None);
+
+            let cost = ExecutionCost::runtime(i as u64 + 1);
+            mempool.record_tx_cost(&txid, &cost).unwrap();
+            assert_eq!(
+                MemPoolDB::get_tx_cost(&mempool.db, &txid).unwrap(),
+                Some(cost)
+            );
+        }
+
+        eprintln!("page through all txs by txid");
+        let mut all_txids = MemPoolDB::get_all_txids(&mempool.db).unwrap();
+        assert_eq!(all_txids.len() as u64, num_txs);
+        all_txids.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+
+        let mut paged_txids = vec![];
+        let mut last_txid = Txid([0u8; 32]);
+        loop {
+            let page = MemPoolDB::get_txs_after_txid(&mempool.db, &last_txid, 3).unwrap();
+            if page.len() == 0 {
+                break;
+            }
+            assert!(page.len() <= 3);
+            for tx_info in page.into_iter() {
+                last_txid = tx_info.metadata.txid.clone();
+                paged_txids.push(tx_info.metadata.txid);
+            }
+        }
+        assert_eq!(paged_txids, all_txids);
+
         eprintln!("garbage-collect");
         let mut mempool_tx = mempool.tx_begin().unwrap();
-        MemPoolDB::garbage_collect(&mut mempool_tx, 101).unwrap();
+        let collected = MemPoolDB::garbage_collect(&mut mempool_tx, 101).unwrap();
         mempool_tx.commit().unwrap();
+        assert_eq!(collected.len() as u64, num_txs);
+        assert_eq!(MemPoolDB::get_all_txids(&mempool.db).unwrap().len(), 0);
+
+        // recorded costs are forgotten along with their transactions
+        for tx_info in all_txs.iter() {
+            assert_eq!(
+                MemPoolDB::get_tx_cost(&mempool.db, &tx_info.tx.txid()).unwrap(),
+                None
+            );
+        }
 
         let txs = MemPoolDB::get_txs_after(
             &mempool.db,
@@ -1493,4 +2417,390 @@ mod tests {
         .unwrap();
         assert_eq!(txs.len(), 0);
     }
+
+    #[test]
+    fn mempool_rbf_min_fee_rate_increment() {
+        let _chainstate =
+            instantiate_chainstate(false, 0x80000000, "mempool_rbf_min_fee_rate_increment");
+        let chainstate_path = chainstate_path("mempool_rbf_min_fee_rate_increment");
+        let mut mempool = MemPoolDB::open_with_settings(
+            false,
+            0x80000000,
+            &chainstate_path,
+            MemPoolSettings {
+                min_rbf_fee_rate_increment: 10,
+                ..MemPoolSettings::default()
+            },
+        )
+        .unwrap();
+
+        let mut tx = codec_all_transactions(
+            &TransactionVersion::Testnet,
+            0x80000000,
+            &TransactionAnchorMode::Any,
+            &TransactionPostConditionMode::Allow,
+        )
+        .pop()
+        .unwrap();
+
+        let origin_address = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x11; 20]),
+        };
+        let sponsor_address = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x22; 20]),
+        };
+        let origin_nonce = tx.get_origin_nonce();
+        let sponsor_nonce = tx.get_sponsor_nonce().unwrap_or(origin_nonce);
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        let (first_txid, res) = add_sponsored_tx_at(
+            &mut mempool_tx,
+            &mut tx,
+            &origin_address,
+            origin_nonce,
+            &sponsor_address,
+            sponsor_nonce,
+            100,
+        );
+        res.unwrap();
+
+        // not enough of a fee-rate bump
+        let (low_txid, res) = add_sponsored_tx_at(
+            &mut mempool_tx,
+            &mut tx,
+            &origin_address,
+            origin_nonce,
+            &sponsor_address,
+            sponsor_nonce,
+            109,
+        );
+        match res.unwrap_err() {
+            MemPoolRejection::ConflictingNonceInMempool => {}
+            e => panic!("Unexpected rejection: {:?}", &e),
+        }
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &first_txid).unwrap());
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, &low_txid).unwrap());
+        assert!(MemPoolDB::take_unannounced_replaced_txids(&mut mempool_tx)
+            .unwrap()
+            .is_empty());
+
+        // sufficient fee-rate bump
+        let (bumped_txid, res) = add_sponsored_tx_at(
+            &mut mempool_tx,
+            &mut tx,
+            &origin_address,
+            origin_nonce,
+            &sponsor_address,
+            sponsor_nonce,
+            110,
+        );
+        res.unwrap();
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, &first_txid).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &bumped_txid).unwrap());
+        assert_eq!(
+            MemPoolDB::get_replacing_txid(&mempool_tx, &first_txid).unwrap(),
+            Some(bumped_txid.clone())
+        );
+
+        // replacement is reported exactly once
+        assert_eq!(
+            MemPoolDB::take_unannounced_replaced_txids(&mut mempool_tx).unwrap(),
+            vec![first_txid.clone()]
+        );
+        assert!(MemPoolDB::take_unannounced_replaced_txids(&mut mempool_tx)
+            .unwrap()
+            .is_empty());
+        mempool_tx.commit().unwrap();
+
+        // replacement records are dropped along with their replacing transactions
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        MemPoolDB::garbage_collect(&mut mempool_tx, 101).unwrap();
+        assert_eq!(
+            MemPoolDB::get_replacing_txid(&mempool_tx, &first_txid).unwrap(),
+            None
+        );
+        mempool_tx.commit().unwrap();
+    }
+
+    #[test]
+    fn mempool_evict_lowest_fee_rate() {
+        let _chainstate =
+            instantiate_chainstate(false, 0x80000000, "mempool_evict_lowest_fee_rate");
+        let chainstate_path = chainstate_path("mempool_evict_lowest_fee_rate");
+        let mut mempool = MemPoolDB::open_with_settings(
+            false,
+            0x80000000,
+            &chainstate_path,
+            MemPoolSettings {
+                max_tx_count: 3,
+                ..MemPoolSettings::default()
+            },
+        )
+        .unwrap();
+
+        let mut tx = codec_all_transactions(
+            &TransactionVersion::Testnet,
+            0x80000000,
+            &TransactionAnchorMode::Any,
+            &TransactionPostConditionMode::Allow,
+        )
+        .pop()
+        .unwrap();
+
+        let addr_1 = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x11; 20]),
+        };
+        let addr_2 = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x22; 20]),
+        };
+        let addr_3 = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x33; 20]),
+        };
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+
+        // addr_1 has a cheap tx followed by an expensive one; addr_2 has a mid-priced tx
+        let (cheap_txid, res) = add_tx_at(&mut mempool_tx, &mut tx, &addr_1, 0, 1);
+        res.unwrap();
+        let (chained_txid, res) = add_tx_at(&mut mempool_tx, &mut tx, &addr_1, 1, 100);
+        res.unwrap();
+        let (mid_txid, res) = add_tx_at(&mut mempool_tx, &mut tx, &addr_2, 0, 10);
+        res.unwrap();
+        assert_eq!(MemPoolDB::get_mempool_size(&mempool_tx).unwrap().0, 3);
+        mempool_tx.commit().unwrap();
+
+        // full, and a tx that pays less than any evictable tx is rejected
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        let (rejected_txid, res) = add_tx_at(&mut mempool_tx, &mut tx, &addr_3, 0, 9);
+        match res.unwrap_err() {
+            MemPoolRejection::MempoolFull => {}
+            e => panic!("Unexpected rejection: {:?}", &e),
+        }
+        drop(mempool_tx);
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, &rejected_txid).unwrap());
+        assert_eq!(MemPoolDB::get_mempool_size(&mempool_tx).unwrap().0, 3);
+
+        // the cheapest tx is not evicted, since a later nonce from its origin remains.
+        let (new_txid, res) = add_tx_at(&mut mempool_tx, &mut tx, &addr_3, 0, 20);
+        res.unwrap();
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &cheap_txid).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &chained_txid).unwrap());
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, &mid_txid).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &new_txid).unwrap());
+        assert_eq!(MemPoolDB::get_mempool_size(&mempool_tx).unwrap().0, 3);
+        mempool_tx.commit().unwrap();
+    }
+
+    #[test]
+    fn mempool_evict_to_byte_cap_respecting_sponsor_chains() {
+        let _chainstate = instantiate_chainstate(
+            false,
+            0x80000000,
+            "mempool_evict_to_byte_cap_respecting_sponsor_chains",
+        );
+        let chainstate_path =
+            chainstate_path("mempool_evict_to_byte_cap_respecting_sponsor_chains");
+
+        let mut tx = codec_all_transactions(
+            &TransactionVersion::Testnet,
+            0x80000000,
+            &TransactionAnchorMode::Any,
+            &TransactionPostConditionMode::Allow,
+        )
+        .pop()
+        .unwrap();
+        let mut tx_bytes = vec![];
+        tx.consensus_serialize(&mut tx_bytes).unwrap();
+        let tx_len = tx_bytes.len() as u64;
+
+        // room for three txs by size, but not by count
+        let mut mempool = MemPoolDB::open_with_settings(
+            false,
+            0x80000000,
+            &chainstate_path,
+            MemPoolSettings {
+                max_tx_bytes: 3 * tx_len,
+                ..MemPoolSettings::default()
+            },
+        )
+        .unwrap();
+
+        let addr_1 = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x11; 20]),
+        };
+        let addr_2 = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x22; 20]),
+        };
+        let addr_3 = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x33; 20]),
+        };
+        let addr_4 = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x44; 20]),
+        };
+        let addr_5 = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x55; 20]),
+        };
+
+        // addr_2 sponsors the cheapest tx, and its next nonce is spent as an origin.
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        let (sponsored_txid, res) =
+            add_sponsored_tx_at(&mut mempool_tx, &mut tx, &addr_1, 0, &addr_2, 0, 1);
+        res.unwrap();
+        let (chained_txid, res) =
+            add_sponsored_tx_at(&mut mempool_tx, &mut tx, &addr_2, 1, &addr_2, 1, 100);
+        res.unwrap();
+        let (mid_txid, res) =
+            add_sponsored_tx_at(&mut mempool_tx, &mut tx, &addr_3, 0, &addr_3, 0, 10);
+        res.unwrap();
+        assert_eq!(
+            MemPoolDB::get_mempool_size(&mempool_tx).unwrap(),
+            (3, 3 * tx_len)
+        );
+        mempool_tx.commit().unwrap();
+
+        // the sponsored tx is not evicted, since that would orphan its sponsor's later nonce
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        let (new_txid, res) =
+            add_sponsored_tx_at(&mut mempool_tx, &mut tx, &addr_4, 0, &addr_4, 0, 20);
+        res.unwrap();
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &sponsored_txid).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &chained_txid).unwrap());
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, &mid_txid).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &new_txid).unwrap());
+        assert_eq!(
+            MemPoolDB::get_mempool_size(&mempool_tx).unwrap(),
+            (3, 3 * tx_len)
+        );
+        mempool_tx.commit().unwrap();
+
+        // nothing else is evictable
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        let (_, res) = add_sponsored_tx_at(&mut mempool_tx, &mut tx, &addr_5, 0, &addr_5, 0, 15);
+        match res.unwrap_err() {
+            MemPoolRejection::MempoolFull => {}
+            e => panic!("Unexpected rejection: {:?}", &e),
+        }
+        drop(mempool_tx);
+
+        // the eviction is reported exactly once
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        assert_eq!(
+            MemPoolDB::take_unannounced_evicted_txids(&mut mempool_tx).unwrap(),
+            vec![mid_txid]
+        );
+        assert_eq!(
+            MemPoolDB::take_unannounced_evicted_txids(&mut mempool_tx).unwrap(),
+            vec![]
+        );
+        assert_eq!(
+            MemPoolDB::take_unannounced_replaced_txids(&mut mempool_tx).unwrap(),
+            vec![]
+        );
+        mempool_tx.commit().unwrap();
+    }
+
+    #[test]
+    fn mempool_iterate_candidates_by_fee_rate() {
+        let mut chainstate =
+            instantiate_chainstate(false, 0x80000000, "mempool_iterate_candidates_by_fee_rate");
+        let chainstate_path = chainstate_path("mempool_iterate_candidates_by_fee_rate");
+        let mut mempool = MemPoolDB::open(false, 0x80000000, &chainstate_path).unwrap();
+
+        let mut tx = codec_all_transactions(
+            &TransactionVersion::Testnet,
+            0x80000000,
+            &TransactionAnchorMode::Any,
+            &TransactionPostConditionMode::Allow,
+        )
+        .pop()
+        .unwrap();
+
+        let addr_1 = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x11; 20]),
+        };
+        let addr_2 = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x22; 20]),
+        };
+        let addr_3 = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x33; 20]),
+        };
+        let addr_4 = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x44; 20]),
+        };
+        let addr_5 = StacksAddress {
+            version: 22,
+            bytes: Hash160([0x55; 20]),
+        };
+
+        let tip = (ConsensusHash([0x1; 20]), BlockHeaderHash([0x2; 32]));
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+
+        // addr_1's expensive tx can only be mined after its cheap one, so the two are scored
+        // together, and come after addr_2's tx, which pays more than their average.
+        let (cheap_txid, res) = add_tx_at(&mut mempool_tx, &mut tx, &addr_1, 0, 1);
+        res.unwrap();
+        let (chained_txid, res) = add_tx_at(&mut mempool_tx, &mut tx, &addr_1, 1, 100);
+        res.unwrap();
+        let (best_txid, res) = add_tx_at(&mut mempool_tx, &mut tx, &addr_2, 0, 60);
+        res.unwrap();
+        let (low_txid, res) = add_tx_at(&mut mempool_tx, &mut tx, &addr_3, 0, 10);
+        res.unwrap();
+        // addr_3's second tx pays less than its first, so it is a package of its own
+        let (low_chained_txid, res) = add_tx_at(&mut mempool_tx, &mut tx, &addr_3, 1, 2);
+        res.unwrap();
+        // addr_4's tx is the first from its origin, but addr_5 sponsors it with its second
+        // nonce, so it can only be mined after addr_5's own cheap tx and is scored with it
+        let (sponsor_txid, res) = add_tx_at(&mut mempool_tx, &mut tx, &addr_5, 0, 3);
+        res.unwrap();
+        let (sponsored_txid, res) =
+            add_sponsored_tx_at(&mut mempool_tx, &mut tx, &addr_4, 0, &addr_5, 1, 80);
+        res.unwrap();
+        mempool_tx.commit().unwrap();
+
+        let mut packages = vec![];
+        mempool
+            .iterate_candidates_by_fee_rate::<_, ChainstateError>(
+                &tip.0,
+                &tip.1,
+                100,
+                &mut chainstate,
+                |package| {
+                    packages.push(
+                        package
+                            .into_iter()
+                            .map(|txinfo| txinfo.tx.txid())
+                            .collect::<Vec<_>>(),
+                    );
+                    Ok(())
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            packages,
+            vec![
+                vec![best_txid],
+                vec![cheap_txid, chained_txid],
+                vec![sponsor_txid, sponsored_txid],
+                vec![low_txid],
+                vec![low_chained_txid],
+            ]
+        );
+    }
 }
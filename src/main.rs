@@ -43,6 +43,8 @@ use blockstack_lib::chainstate::burn::BlockHeaderHash;
 use blockstack_lib::chainstate::burn::ConsensusHash;
 use blockstack_lib::chainstate::stacks::index::marf::MarfConnection;
 use blockstack_lib::chainstate::stacks::index::marf::MARF;
+use blockstack_lib::chainstate::stacks::index::storage::TrieFileStorage;
+use blockstack_lib::chainstate::stacks::index::TrieHash;
 use blockstack_lib::chainstate::stacks::StacksBlockHeader;
 use blockstack_lib::chainstate::stacks::*;
 use blockstack_lib::net::StacksMessageCodec;
@@ -339,6 +341,133 @@ fn main() {
         return;
     }
 
+    // A Clarity MARF lives at DIR/marf, next to its side store at DIR/data.sqlite
+    let clarity_side_store_path = |marf_path: &str| -> Option<String> {
+        let path = std::path::Path::new(marf_path);
+        if path.file_name().and_then(|name| name.to_str()) != Some("marf") {
+            return None;
+        }
+        path.with_file_name("data.sqlite")
+            .to_str()
+            .map(|side_store_path| side_store_path.to_string())
+    };
+
+    if argv[1] == "marf-export-snapshot" {
+        if argv.len() < 5 {
+            eprintln!(
+                "Usage: {} marf-export-snapshot MARF_PATH INDEX_BLOCK_HASH SNAPSHOT_PATH",
+                argv[0]
+            );
+            process::exit(1);
+        }
+
+        let marf_path = &argv[2];
+        let tip = StacksBlockId::from_hex(&argv[3]).expect("Bad index block hash");
+        let snapshot_path = &argv[4];
+
+        let mut storage = TrieFileStorage::<StacksBlockId>::open_readonly(marf_path)
+            .expect("Failed to open MARF");
+        let clarity_side_store = clarity_side_store_path(marf_path)
+            .filter(|side_store_path| fs::metadata(side_store_path).is_ok())
+            .map(|side_store_path| {
+                Connection::open_with_flags(&side_store_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+                    .expect(&format!("Failed to open {}", &side_store_path))
+            });
+        let mut snapshot_file = io::BufWriter::new(
+            fs::File::create(snapshot_path).expect(&format!("Failed to create {}", snapshot_path)),
+        );
+        let summary = storage
+            .export_snapshot(&tip, clarity_side_store.as_ref(), &mut snapshot_file)
+            .expect("Failed to export MARF snapshot");
+        snapshot_file.flush().expect("Failed to write snapshot");
+
+        println!(
+            "Exported {} tries, {} side-store rows, {} Clarity data rows and {} Clarity metadata rows at {} (root hash {})",
+            summary.num_tries,
+            summary.num_side_rows,
+            summary.num_clarity_data_rows,
+            summary.num_clarity_metadata_rows,
+            &summary.tip,
+            &summary.tip_root_hash
+        );
+        return;
+    }
+
+    if argv[1] == "marf-import-snapshot" {
+        if argv.len() < 5 {
+            eprintln!(
+                "Usage: {} marf-import-snapshot MARF_PATH SNAPSHOT_PATH (EXPECTED_ROOT_HASH | --unsafe-no-root-check)",
+                argv[0]
+            );
+            eprintln!("EXPECTED_ROOT_HASH is the state index root of the trusted block header the snapshot was taken at.");
+            eprintln!("With --unsafe-no-root-check, the snapshot is only checked to be internally consistent.");
+            eprintln!("Clarity contract sources are checked against the hashes the MARF commits to, and their ASTs and analyses are derived from them again.");
+            process::exit(1);
+        }
+
+        let marf_path = &argv[2];
+        let snapshot_path = &argv[3];
+        let expected_root_hash = if argv[4] == "--unsafe-no-root-check" {
+            eprintln!(
+                "WARNING: importing snapshot without checking its root hash against a trusted one"
+            );
+            None
+        } else {
+            Some(TrieHash::from_hex(&argv[4]).expect("Bad root hash"))
+        };
+
+        let mut storage =
+            TrieFileStorage::<StacksBlockId>::open(marf_path).expect("Failed to open MARF");
+        let mut clarity_side_store = clarity_side_store_path(marf_path).map(|side_store_path| {
+            // create the side store's tables if need be
+            vm::database::SqliteConnection::initialize(&side_store_path)
+                .expect(&format!("Failed to instantiate {}", &side_store_path));
+            Connection::open(&side_store_path)
+                .expect(&format!("Failed to open {}", &side_store_path))
+        });
+        let mut snapshot_file = io::BufReader::new(
+            fs::File::open(snapshot_path).expect(&format!("Failed to open {}", snapshot_path)),
+        );
+        let (summary, clarity_metadata_rows) = storage
+            .import_snapshot(
+                &mut snapshot_file,
+                clarity_side_store.as_mut(),
+                expected_root_hash.as_ref(),
+            )
+            .expect("Failed to import MARF snapshot");
+
+        if clarity_side_store.is_some() {
+            drop(storage);
+            drop(clarity_side_store);
+            let clarity_dir = std::path::Path::new(marf_path)
+                .parent()
+                .and_then(|dir| dir.to_str())
+                .expect("Bad MARF path");
+            let marfed_kv = vm::database::MarfedKV::open(clarity_dir, None)
+                .expect(&format!("Failed to open {}", clarity_dir));
+            let mut clarity_instance =
+                vm::clarity::ClarityInstance::new(marfed_kv, vm::costs::ExecutionCost::max_value());
+            let num_contracts = clarity_instance
+                .import_contract_metadata(&summary.tip, clarity_metadata_rows)
+                .expect("Failed to import Clarity contract metadata");
+            println!(
+                "Checked and re-derived the metadata of {} contracts",
+                num_contracts
+            );
+        }
+
+        println!(
+            "Imported {} tries, {} side-store rows, {} Clarity data rows and {} Clarity metadata rows at {} (root hash {})",
+            summary.num_tries,
+            summary.num_side_rows,
+            summary.num_clarity_data_rows,
+            summary.num_clarity_metadata_rows,
+            &summary.tip,
+            &summary.tip_root_hash
+        );
+        return;
+    }
+
     if argv[1] == "docgen" {
         println!("{}", vm::docs::make_json_api_reference());
         return;
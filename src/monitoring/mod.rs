@@ -122,3 +122,23 @@ pub fn update_active_miners_count_gauge(value: i64) {
     #[cfg(feature = "monitoring_prom")]
     prometheus::ACTIVE_MINERS_COUNT_GAUGE.set(value);
 }
+
+#[allow(unused_variables)]
+pub fn update_mempool_tx_count_gauge(value: i64) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::MEMPOOL_TX_COUNT_GAUGE.set(value);
+}
+
+#[allow(unused_variables)]
+pub fn update_mempool_tx_bytes_gauge(value: i64) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::MEMPOOL_TX_BYTES_GAUGE.set(value);
+}
+
+#[allow(unused_variables)]
+pub fn update_mined_block_cost_utilization_gauge(dimension: &str, percent: i64) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::MINED_BLOCK_COST_UTILIZATION_GAUGE
+        .with_label_values(&[dimension])
+        .set(percent);
+}
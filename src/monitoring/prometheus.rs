@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use prometheus::{IntCounter, IntGauge};
+use prometheus::{IntCounter, IntGauge, IntGaugeVec};
 
 lazy_static! {
     pub static ref RPC_CALL_COUNTER: IntCounter = register_int_counter!(opts!(
@@ -142,4 +142,19 @@ lazy_static! {
         "Total number of active miners.",
         labels! {"handler" => "all",}
     )).unwrap();
+    pub static ref MEMPOOL_TX_COUNT_GAUGE: IntGauge = register_int_gauge!(opts!(
+        "stacks_node_mempool_transactions",
+        "Number of transactions in the mempool.",
+        labels! {"handler" => "all",}
+    )).unwrap();
+    pub static ref MEMPOOL_TX_BYTES_GAUGE: IntGauge = register_int_gauge!(opts!(
+        "stacks_node_mempool_bytes",
+        "Total size in bytes of the transactions in the mempool.",
+        labels! {"handler" => "all",}
+    )).unwrap();
+    pub static ref MINED_BLOCK_COST_UTILIZATION_GAUGE: IntGaugeVec = register_int_gauge_vec!(opts!(
+        "stacks_node_mined_block_cost_utilization_percent",
+        "Percentage of each execution cost dimension's budget used by the last block mined by node.",
+        labels! {"handler" => "all",}
+    ), &["dimension"]).unwrap();
 }
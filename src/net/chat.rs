@@ -146,6 +146,7 @@ pub struct NeighborStats {
     pub block_push_rx_counts: VecDeque<(u64, u64)>, // (count, num bytes)
     pub microblocks_push_rx_counts: VecDeque<(u64, u64)>, // (count, num bytes)
     pub transaction_push_rx_counts: VecDeque<(u64, u64)>, // (count, num bytes)
+    pub mempool_sync_rx_counts: VecDeque<(u64, u64)>, // (count, num requests)
     pub relayed_messages: HashMap<NeighborAddress, RelayStats>,
 }
 
@@ -169,6 +170,7 @@ impl NeighborStats {
             block_push_rx_counts: VecDeque::new(),
             microblocks_push_rx_counts: VecDeque::new(),
             transaction_push_rx_counts: VecDeque::new(),
+            mempool_sync_rx_counts: VecDeque::new(),
             relayed_messages: HashMap::new(),
         }
     }
@@ -208,6 +210,14 @@ impl NeighborStats {
         }
     }
 
+    pub fn add_mempool_sync_request(&mut self) -> () {
+        self.mempool_sync_rx_counts
+            .push_back((get_epoch_time_secs(), 1));
+        while self.mempool_sync_rx_counts.len() > NUM_BLOCK_POINTS {
+            self.mempool_sync_rx_counts.pop_front();
+        }
+    }
+
     pub fn add_relayer(&mut self, addr: &NeighborAddress, num_bytes: u64) -> () {
         if let Some(stats) = self.relayed_messages.get_mut(addr) {
             stats.num_messages += 1;
@@ -287,6 +297,11 @@ impl NeighborStats {
         NeighborStats::get_bandwidth(&self.transaction_push_rx_counts, BLOCK_POINT_LIFETIME)
     }
 
+    /// Get the rate at which a peer sends us GetMempool requests, in requests per second
+    pub fn get_mempool_sync_request_rate(&self) -> f64 {
+        NeighborStats::get_bandwidth(&self.mempool_sync_rx_counts, BLOCK_POINT_LIFETIME)
+    }
+
     /// Determine how many of a particular message this peer has received
     pub fn get_message_recv_count(&self, msg_id: StacksMessageID) -> u64 {
         *(self.msg_rx_counts.get(&msg_id).unwrap_or(&0))
@@ -594,6 +609,16 @@ impl ConversationP2P {
         self.connection.has_public_key()
     }
 
+    /// Did this peer advertise in its handshake that it answers GetMempool requests?
+    pub fn supports_mempool_sync(&self) -> bool {
+        self.peer_services & (ServiceFlags::MEMPOOL_SYNC as u16) != 0
+    }
+
+    /// Did this peer advertise in its handshake that it can rebuild CompactBlocks?
+    pub fn supports_compact_blocks(&self) -> bool {
+        self.peer_services & (ServiceFlags::COMPACT_BLOCKS as u16) != 0
+    }
+
     pub fn get_public_key(&self) -> Option<StacksPublicKey> {
         self.connection.get_public_key()
     }
@@ -1419,6 +1444,67 @@ impl ConversationP2P {
         self.sign_and_reply(local_peer, burnchain_view, preamble, response)
     }
 
+    /// Create a response to an inbound GetBlockTxs request, but unsigned.
+    /// Replies with a NoSuchBlock nack if we don't have the block, or if it doesn't have a
+    /// transaction at one of the requested indexes.
+    pub fn make_getblocktxs_response(
+        chainstate: &StacksChainState,
+        get_block_txs: &GetBlockTxsData,
+    ) -> Result<StacksMessageType, net_error> {
+        let block = match StacksChainState::load_block(
+            &chainstate.blocks_path,
+            &get_block_txs.consensus_hash,
+            &get_block_txs.block_hash,
+        )? {
+            Some(block) => block,
+            None => {
+                debug!(
+                    "No such block {}/{}",
+                    &get_block_txs.consensus_hash, &get_block_txs.block_hash
+                );
+                return Ok(StacksMessageType::Nack(NackData::new(
+                    NackErrorCodes::NoSuchBlock,
+                )));
+            }
+        };
+
+        let mut txs = Vec::with_capacity(get_block_txs.indexes.len());
+        for index in get_block_txs.indexes.iter() {
+            match block.txs.get(*index as usize) {
+                Some(tx) => txs.push(tx.clone()),
+                None => {
+                    debug!(
+                        "Block {}/{} has no transaction {}",
+                        &get_block_txs.consensus_hash, &get_block_txs.block_hash, index
+                    );
+                    return Ok(StacksMessageType::Nack(NackData::new(
+                        NackErrorCodes::NoSuchBlock,
+                    )));
+                }
+            }
+        }
+
+        Ok(StacksMessageType::BlockTxs(BlockTxsData {
+            consensus_hash: get_block_txs.consensus_hash.clone(),
+            block_hash: get_block_txs.block_hash.clone(),
+            txs,
+        }))
+    }
+
+    /// Handle an inbound GetBlockTxs request, sent by a peer that could not rebuild a compact
+    /// block we pushed from its mempool.
+    fn handle_getblocktxs(
+        &mut self,
+        local_peer: &LocalPeer,
+        chainstate: &StacksChainState,
+        burnchain_view: &BurnchainView,
+        preamble: &Preamble,
+        get_block_txs: &GetBlockTxsData,
+    ) -> Result<ReplyHandleP2P, net_error> {
+        let response = ConversationP2P::make_getblocktxs_response(chainstate, get_block_txs)?;
+        self.sign_and_reply(local_peer, burnchain_view, preamble, response)
+    }
+
     /// Create a response an inbound GetPoxInv request, but unsigned.
     /// Returns a reply handle to the generated message (possibly a nack)
     pub fn make_getpoxinv_response(
@@ -1698,6 +1784,29 @@ impl ConversationP2P {
         Ok(None)
     }
 
+    /// Validate a GetMempool request -- make sure the peer isn't asking for pages too quickly.
+    /// The request itself is answered by the PeerNetwork, since it needs the mempool.
+    /// Returns a reply handle to a NACK if the peer is throttled.
+    fn validate_mempool_sync_request(
+        &mut self,
+        local_peer: &LocalPeer,
+        chain_view: &BurnchainView,
+        preamble: &Preamble,
+    ) -> Result<Option<ReplyHandleP2P>, net_error> {
+        self.stats.add_mempool_sync_request();
+
+        if self.connection.options.max_mempool_sync_request_rate > 0
+            && self.stats.get_mempool_sync_request_rate()
+                > (self.connection.options.max_mempool_sync_request_rate as f64)
+        {
+            debug!("Neighbor {:?} exceeded max mempool sync request rate of {} requests/sec (currently at {})", &self.to_neighbor_key(), self.connection.options.max_mempool_sync_request_rate, self.stats.get_mempool_sync_request_rate());
+            return self
+                .reply_nack(local_peer, chain_view, preamble, NackErrorCodes::Throttled)
+                .and_then(|handle| Ok(Some(handle)));
+        }
+        Ok(None)
+    }
+
     /// Handle an inbound authenticated p2p data-plane message.
     /// Return the message if not handled
     fn handle_data_message(
@@ -1786,6 +1895,42 @@ impl ConversationP2P {
                     }
                 }
             }
+            StacksMessageType::GetBlockTxs(ref get_block_txs) => self.handle_getblocktxs(
+                local_peer,
+                chainstate,
+                chain_view,
+                &msg.preamble,
+                get_block_txs,
+            ),
+            StacksMessageType::CompactBlock(_) => {
+                monitoring::increment_stx_blocks_received_counter();
+
+                // not handled here, but it's a block push, so do the same accounting as for
+                // full blocks.
+                match self.validate_blocks_push(
+                    local_peer,
+                    chain_view,
+                    &msg.preamble,
+                    msg.relayers.clone(),
+                )? {
+                    Some(handle) => Ok(handle),
+                    None => {
+                        // will forward upstream
+                        return Ok(Some(msg));
+                    }
+                }
+            }
+            StacksMessageType::GetMempool(_) => {
+                // not handled here, but do some accounting -- we can't be asked to scan our
+                // mempool too often.
+                match self.validate_mempool_sync_request(local_peer, chain_view, &msg.preamble)? {
+                    Some(handle) => Ok(handle),
+                    None => {
+                        // will forward upstream
+                        return Ok(Some(msg));
+                    }
+                }
+            }
             _ => {
                 // all else will forward upstream
                 return Ok(Some(msg));
@@ -2674,6 +2819,19 @@ mod test {
                 Secp256k1PublicKey::from_private(&local_peer_2.private_key)
             );
             assert_eq!(convo_1.data_url, "http://peer2.com".into());
+
+            // both sides learned that the other answers mempool sync requests
+            assert!(convo_1.supports_mempool_sync());
+            assert!(convo_2.supports_mempool_sync());
+
+            // ...and compact blocks
+            assert!(convo_1.supports_compact_blocks());
+            assert!(convo_2.supports_compact_blocks());
+
+            // a peer that only advertises relaying does neither
+            convo_1.peer_services = ServiceFlags::RELAY as u16;
+            assert!(!convo_1.supports_mempool_sync());
+            assert!(!convo_1.supports_compact_blocks());
         })
     }
 
@@ -24,6 +24,7 @@ use burnchains::BurnchainHeaderHash;
 use burnchains::BurnchainView;
 use burnchains::PrivateKey;
 use burnchains::PublicKey;
+use burnchains::Txid;
 
 use chainstate::burn::BlockHeaderHash;
 use chainstate::burn::ConsensusHash;
@@ -517,6 +518,263 @@ impl StacksMessageCodec for PoxInvData {
     }
 }
 
+impl StacksMessageCodec for TxidBloomFilter {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), net_error> {
+        write_next(fd, &self.seed)?;
+        write_next(fd, &self.num_hashes)?;
+        write_next(fd, &self.bitlen)?;
+        write_next(fd, &self.bitvec)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<TxidBloomFilter, net_error> {
+        let seed: u32 = read_next(fd)?;
+        let num_hashes: u8 = read_next(fd)?;
+        if num_hashes == 0 || num_hashes > MEMPOOL_SYNC_BLOOM_MAX_HASHES {
+            return Err(net_error::DeserializeError(
+                "Invalid TxidBloomFilter hash count".to_string(),
+            ));
+        }
+
+        let bitlen: u32 = read_next(fd)?;
+        if bitlen == 0 || bitlen > MEMPOOL_SYNC_BLOOM_MAX_BITLEN {
+            return Err(net_error::DeserializeError(
+                "Invalid TxidBloomFilter bitlen".to_string(),
+            ));
+        }
+
+        let bitvec: Vec<u8> = read_next_exact::<_, u8>(fd, BITVEC_LEN!(bitlen))?;
+        Ok(TxidBloomFilter {
+            seed,
+            num_hashes,
+            bitlen,
+            bitvec,
+        })
+    }
+}
+
+impl StacksMessageCodec for GetMempoolData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), net_error> {
+        write_next(fd, &self.tx_filter)?;
+        write_next(fd, &self.last_txid)?;
+        write_next(fd, &self.max_txs)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<GetMempoolData, net_error> {
+        let tx_filter: TxidBloomFilter = read_next(fd)?;
+        let last_txid: Txid = read_next(fd)?;
+        let max_txs: u32 = read_next(fd)?;
+        if max_txs == 0 || max_txs > MEMPOOL_SYNC_PAGE_MAX {
+            return Err(net_error::DeserializeError(
+                "Invalid GetMempoolData page size".to_string(),
+            ));
+        }
+        Ok(GetMempoolData {
+            tx_filter,
+            last_txid,
+            max_txs,
+        })
+    }
+}
+
+impl StacksMessageCodec for MempoolTxsData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), net_error> {
+        write_next(fd, &self.txs)?;
+        write_next(fd, &self.last_txid)?;
+        write_next(fd, &(if self.has_more { 1u8 } else { 0u8 }))?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<MempoolTxsData, net_error> {
+        let txs: Vec<StacksTransaction> = {
+            // loose upper-bound
+            let mut bound_read = BoundReader::from_reader(fd, MAX_MESSAGE_LEN as u64);
+            read_next_at_most::<_, StacksTransaction>(&mut bound_read, MEMPOOL_SYNC_PAGE_MAX)
+        }?;
+        let last_txid: Txid = read_next(fd)?;
+        let has_more = match read_next::<u8, _>(fd)? {
+            0 => false,
+            1 => true,
+            _ => {
+                return Err(net_error::DeserializeError(
+                    "Invalid MempoolTxsData has_more flag".to_string(),
+                ));
+            }
+        };
+        Ok(MempoolTxsData {
+            txs,
+            last_txid,
+            has_more,
+        })
+    }
+}
+
+impl CompactBlockData {
+    /// Short ID of a transaction in a compact block.  It's keyed to the block so that
+    /// short-ID collisions can't be ground out ahead of time.
+    pub fn short_txid(block_hash: &BlockHeaderHash, txid: &Txid) -> u64 {
+        let mut hasher = Sha512Trunc256::new();
+        hasher.input(block_hash.as_bytes());
+        hasher.input(txid.as_bytes());
+
+        let mut short_id_bytes = [0u8; 8];
+        short_id_bytes.copy_from_slice(&hasher.result()[0..8]);
+        u64::from_be_bytes(short_id_bytes)
+    }
+
+    /// Make a compact block from a full block.  The coinbase is always sent in full, since no
+    /// peer will have it in its mempool.
+    pub fn from_block(consensus_hash: &ConsensusHash, block: &StacksBlock) -> CompactBlockData {
+        let block_hash = block.block_hash();
+        let mut short_txids = Vec::with_capacity(block.txs.len());
+        let mut prefilled_txs = vec![];
+        for (i, tx) in block.txs.iter().enumerate() {
+            short_txids.push(CompactBlockData::short_txid(&block_hash, &tx.txid()));
+            if let TransactionPayload::Coinbase(_) = tx.payload {
+                prefilled_txs.push((i as u16, tx.clone()));
+            }
+        }
+        CompactBlockData {
+            consensus_hash: consensus_hash.clone(),
+            header: block.header.clone(),
+            short_txids,
+            prefilled_txs,
+        }
+    }
+
+    pub fn block_hash(&self) -> BlockHeaderHash {
+        self.header.block_hash()
+    }
+}
+
+impl StacksMessageCodec for (u16, StacksTransaction) {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), net_error> {
+        write_next(fd, &self.0)?;
+        write_next(fd, &self.1)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<(u16, StacksTransaction), net_error> {
+        let index: u16 = read_next(fd)?;
+        let tx: StacksTransaction = read_next(fd)?;
+        Ok((index, tx))
+    }
+}
+
+/// Transaction indexes in compact block messages must be strictly increasing, which also makes
+/// them unique.
+fn check_tx_indexes_increasing<I: Iterator<Item = u16>>(indexes: I) -> bool {
+    let mut last_index: Option<u16> = None;
+    for index in indexes {
+        if let Some(last) = last_index {
+            if index <= last {
+                return false;
+            }
+        }
+        last_index = Some(index);
+    }
+    true
+}
+
+impl StacksMessageCodec for CompactBlockData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), net_error> {
+        write_next(fd, &self.consensus_hash)?;
+        write_next(fd, &self.header)?;
+        write_next(fd, &self.short_txids)?;
+        write_next(fd, &self.prefilled_txs)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<CompactBlockData, net_error> {
+        let consensus_hash: ConsensusHash = read_next(fd)?;
+        let header: StacksBlockHeader = read_next(fd)?;
+        let short_txids: Vec<u64> = read_next_at_most(fd, COMPACT_BLOCK_MAX_TXS)?;
+        if short_txids.len() == 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid CompactBlockData: no transactions".to_string(),
+            ));
+        }
+
+        let prefilled_txs: Vec<(u16, StacksTransaction)> = {
+            let mut bound_read = BoundReader::from_reader(fd, MAX_BLOCK_LEN as u64);
+            read_next_at_most(&mut bound_read, short_txids.len() as u32)
+        }?;
+        if !check_tx_indexes_increasing(prefilled_txs.iter().map(|(i, _)| *i)) {
+            return Err(net_error::DeserializeError(
+                "Invalid CompactBlockData: prefilled indexes are not increasing".to_string(),
+            ));
+        }
+        if let Some((last_index, _)) = prefilled_txs.last() {
+            if (*last_index as usize) >= short_txids.len() {
+                return Err(net_error::DeserializeError(
+                    "Invalid CompactBlockData: prefilled index out of range".to_string(),
+                ));
+            }
+        }
+
+        Ok(CompactBlockData {
+            consensus_hash,
+            header,
+            short_txids,
+            prefilled_txs,
+        })
+    }
+}
+
+impl StacksMessageCodec for GetBlockTxsData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), net_error> {
+        write_next(fd, &self.consensus_hash)?;
+        write_next(fd, &self.block_hash)?;
+        write_next(fd, &self.indexes)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<GetBlockTxsData, net_error> {
+        let consensus_hash: ConsensusHash = read_next(fd)?;
+        let block_hash: BlockHeaderHash = read_next(fd)?;
+        let indexes: Vec<u16> = read_next_at_most(fd, COMPACT_BLOCK_MAX_TXS)?;
+        if indexes.len() == 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid GetBlockTxsData: no indexes".to_string(),
+            ));
+        }
+        if !check_tx_indexes_increasing(indexes.iter().map(|i| *i)) {
+            return Err(net_error::DeserializeError(
+                "Invalid GetBlockTxsData: indexes are not increasing".to_string(),
+            ));
+        }
+        Ok(GetBlockTxsData {
+            consensus_hash,
+            block_hash,
+            indexes,
+        })
+    }
+}
+
+impl StacksMessageCodec for BlockTxsData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), net_error> {
+        write_next(fd, &self.consensus_hash)?;
+        write_next(fd, &self.block_hash)?;
+        write_next(fd, &self.txs)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<BlockTxsData, net_error> {
+        let consensus_hash: ConsensusHash = read_next(fd)?;
+        let block_hash: BlockHeaderHash = read_next(fd)?;
+        let txs: Vec<StacksTransaction> = {
+            let mut bound_read = BoundReader::from_reader(fd, MAX_BLOCK_LEN as u64);
+            read_next_at_most(&mut bound_read, COMPACT_BLOCK_MAX_TXS)
+        }?;
+        Ok(BlockTxsData {
+            consensus_hash,
+            block_hash,
+            txs,
+        })
+    }
+}
+
 impl StacksMessageCodec for (ConsensusHash, BurnchainHeaderHash) {
     fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), net_error> {
         write_next(fd, &self.0)?;
@@ -904,6 +1162,11 @@ impl StacksMessageType {
             StacksMessageType::Pong(ref _m) => StacksMessageID::Pong,
             StacksMessageType::NatPunchRequest(ref _m) => StacksMessageID::NatPunchRequest,
             StacksMessageType::NatPunchReply(ref _m) => StacksMessageID::NatPunchReply,
+            StacksMessageType::GetMempool(ref _m) => StacksMessageID::GetMempool,
+            StacksMessageType::MempoolTxs(ref _m) => StacksMessageID::MempoolTxs,
+            StacksMessageType::CompactBlock(ref _m) => StacksMessageID::CompactBlock,
+            StacksMessageType::GetBlockTxs(ref _m) => StacksMessageID::GetBlockTxs,
+            StacksMessageType::BlockTxs(ref _m) => StacksMessageID::BlockTxs,
         }
     }
 
@@ -928,6 +1191,11 @@ impl StacksMessageType {
             StacksMessageType::Pong(ref _m) => "Pong",
             StacksMessageType::NatPunchRequest(ref _m) => "NatPunchRequest",
             StacksMessageType::NatPunchReply(ref _m) => "NatPunchReply",
+            StacksMessageType::GetMempool(ref _m) => "GetMempool",
+            StacksMessageType::MempoolTxs(ref _m) => "MempoolTxs",
+            StacksMessageType::CompactBlock(ref _m) => "CompactBlock",
+            StacksMessageType::GetBlockTxs(ref _m) => "GetBlockTxs",
+            StacksMessageType::BlockTxs(ref _m) => "BlockTxs",
         }
     }
 
@@ -986,6 +1254,33 @@ impl StacksMessageType {
             StacksMessageType::NatPunchReply(ref m) => {
                 format!("NatPunchReply({},{}:{})", m.nonce, &m.addrbytes, m.port)
             }
+            StacksMessageType::GetMempool(ref m) => format!(
+                "GetMempool({},{},{})",
+                &m.last_txid, m.max_txs, m.tx_filter.bitlen
+            ),
+            StacksMessageType::MempoolTxs(ref m) => format!(
+                "MempoolTxs({:?},{},{})",
+                m.txs.iter().map(|tx| tx.txid()).collect::<Vec<Txid>>(),
+                &m.last_txid,
+                m.has_more
+            ),
+            StacksMessageType::CompactBlock(ref m) => format!(
+                "CompactBlock({}/{},{},{})",
+                &m.consensus_hash,
+                &m.block_hash(),
+                m.short_txids.len(),
+                m.prefilled_txs.len()
+            ),
+            StacksMessageType::GetBlockTxs(ref m) => format!(
+                "GetBlockTxs({}/{},{:?})",
+                &m.consensus_hash, &m.block_hash, &m.indexes
+            ),
+            StacksMessageType::BlockTxs(ref m) => format!(
+                "BlockTxs({}/{},{:?})",
+                &m.consensus_hash,
+                &m.block_hash,
+                m.txs.iter().map(|tx| tx.txid()).collect::<Vec<Txid>>()
+            ),
         }
     }
 }
@@ -1019,6 +1314,11 @@ impl StacksMessageCodec for StacksMessageID {
             x if x == StacksMessageID::Pong as u8 => StacksMessageID::Pong,
             x if x == StacksMessageID::NatPunchRequest as u8 => StacksMessageID::NatPunchRequest,
             x if x == StacksMessageID::NatPunchReply as u8 => StacksMessageID::NatPunchReply,
+            x if x == StacksMessageID::GetMempool as u8 => StacksMessageID::GetMempool,
+            x if x == StacksMessageID::MempoolTxs as u8 => StacksMessageID::MempoolTxs,
+            x if x == StacksMessageID::CompactBlock as u8 => StacksMessageID::CompactBlock,
+            x if x == StacksMessageID::GetBlockTxs as u8 => StacksMessageID::GetBlockTxs,
+            x if x == StacksMessageID::BlockTxs as u8 => StacksMessageID::BlockTxs,
             _ => {
                 return Err(net_error::DeserializeError(
                     "Unknown message ID".to_string(),
@@ -1052,6 +1352,11 @@ impl StacksMessageCodec for StacksMessageType {
             StacksMessageType::Pong(ref m) => write_next(fd, m)?,
             StacksMessageType::NatPunchRequest(ref nonce) => write_next(fd, nonce)?,
             StacksMessageType::NatPunchReply(ref m) => write_next(fd, m)?,
+            StacksMessageType::GetMempool(ref m) => write_next(fd, m)?,
+            StacksMessageType::MempoolTxs(ref m) => write_next(fd, m)?,
+            StacksMessageType::CompactBlock(ref m) => write_next(fd, m)?,
+            StacksMessageType::GetBlockTxs(ref m) => write_next(fd, m)?,
+            StacksMessageType::BlockTxs(ref m) => write_next(fd, m)?,
         }
         Ok(())
     }
@@ -1129,6 +1434,26 @@ impl StacksMessageCodec for StacksMessageType {
                 let m: NatPunchData = read_next(fd)?;
                 StacksMessageType::NatPunchReply(m)
             }
+            StacksMessageID::GetMempool => {
+                let m: GetMempoolData = read_next(fd)?;
+                StacksMessageType::GetMempool(m)
+            }
+            StacksMessageID::MempoolTxs => {
+                let m: MempoolTxsData = read_next(fd)?;
+                StacksMessageType::MempoolTxs(m)
+            }
+            StacksMessageID::CompactBlock => {
+                let m: CompactBlockData = read_next(fd)?;
+                StacksMessageType::CompactBlock(m)
+            }
+            StacksMessageID::GetBlockTxs => {
+                let m: GetBlockTxsData = read_next(fd)?;
+                StacksMessageType::GetBlockTxs(m)
+            }
+            StacksMessageID::BlockTxs => {
+                let m: BlockTxsData = read_next(fd)?;
+                StacksMessageType::BlockTxs(m)
+            }
             StacksMessageID::Reserved => {
                 return Err(net_error::DeserializeError(
                     "Unsupported message ID 'reserved'".to_string(),
@@ -1406,6 +1731,7 @@ impl ProtocolFamily for StacksP2P {
 pub mod test {
     use super::*;
 
+    use chainstate::stacks::test::make_codec_test_block;
     use util::hash::hex_bytes;
     use util::secp256k1::*;
 
@@ -2047,6 +2373,189 @@ pub mod test {
         check_codec_and_corruption::<NatPunchData>(&data, &bytes);
     }
 
+    #[test]
+    fn codec_GetMempoolData() {
+        let data = GetMempoolData {
+            tx_filter: TxidBloomFilter {
+                seed: 0x01020304,
+                num_hashes: 2,
+                bitlen: 9,
+                bitvec: vec![0xff, 0x01],
+            },
+            last_txid: Txid([0x11; 32]),
+            max_txs: 0x20,
+        };
+        let mut bytes = vec![
+            // seed
+            0x01, 0x02, 0x03, 0x04, // num hashes
+            0x02, // bitlen
+            0x00, 0x00, 0x00, 0x09, // bitvec
+            0x00, 0x00, 0x00, 0x02, 0xff, 0x01,
+        ];
+        // last txid
+        bytes.append(&mut vec![0x11; 32]);
+        // max txs
+        bytes.append(&mut vec![0x00, 0x00, 0x00, 0x20]);
+
+        check_codec_and_corruption::<GetMempoolData>(&data, &bytes);
+
+        // should fail to decode if the page size is out of range
+        let mut empty_page = data.clone();
+        empty_page.max_txs = 0;
+        assert!(check_deserialize_failure::<GetMempoolData>(&empty_page));
+
+        let mut big_page = data.clone();
+        big_page.max_txs = MEMPOOL_SYNC_PAGE_MAX + 1;
+        assert!(check_deserialize_failure::<GetMempoolData>(&big_page));
+
+        // should fail to decode if the bloom filter is malformed
+        let mut no_hashes = data.clone();
+        no_hashes.tx_filter.num_hashes = 0;
+        assert!(check_deserialize_failure::<GetMempoolData>(&no_hashes));
+
+        let mut too_many_hashes = data.clone();
+        too_many_hashes.tx_filter.num_hashes = MEMPOOL_SYNC_BLOOM_MAX_HASHES + 1;
+        assert!(check_deserialize_failure::<GetMempoolData>(
+            &too_many_hashes
+        ));
+
+        let mut short_bitlen = data.clone();
+        short_bitlen.tx_filter.bitlen = 8;
+        assert!(check_deserialize_failure::<GetMempoolData>(&short_bitlen));
+
+        let mut too_big_bitlen = data.clone();
+        too_big_bitlen.tx_filter.bitlen = MEMPOOL_SYNC_BLOOM_MAX_BITLEN + 1;
+        too_big_bitlen.tx_filter.bitvec =
+            vec![0xff; BITVEC_LEN!(MEMPOOL_SYNC_BLOOM_MAX_BITLEN + 1) as usize];
+        assert!(check_deserialize_failure::<GetMempoolData>(&too_big_bitlen));
+    }
+
+    #[test]
+    fn codec_MempoolTxsData() {
+        let data = MempoolTxsData {
+            txs: vec![],
+            last_txid: Txid([0x22; 32]),
+            has_more: false,
+        };
+        let mut bytes = vec![
+            // txs
+            0x00, 0x00, 0x00, 0x00,
+        ];
+        // last txid
+        bytes.append(&mut vec![0x22; 32]);
+        // has_more
+        bytes.push(0x00);
+
+        check_codec_and_corruption::<MempoolTxsData>(&data, &bytes);
+
+        // has_more must be 0 or 1
+        let mut bad_flag_bytes = bytes.clone();
+        let flag_idx = bad_flag_bytes.len() - 1;
+        bad_flag_bytes[flag_idx] = 0x02;
+        assert!(check_deserialize(MempoolTxsData::consensus_deserialize(
+            &mut &bad_flag_bytes[..]
+        )));
+
+        // can't have too many transactions
+        let mut too_many_txs_bytes = (MEMPOOL_SYNC_PAGE_MAX + 1).to_be_bytes().to_vec();
+        too_many_txs_bytes.append(&mut bytes[4..].to_vec());
+        assert!(check_deserialize(MempoolTxsData::consensus_deserialize(
+            &mut &too_many_txs_bytes[..]
+        )));
+    }
+
+    #[test]
+    fn codec_CompactBlockData() {
+        let block = make_codec_test_block(3);
+        let data = CompactBlockData::from_block(&ConsensusHash([0x11; 20]), &block);
+
+        // only the coinbase is prefilled
+        assert_eq!(data.short_txids.len(), 3);
+        assert_eq!(data.prefilled_txs, vec![(0, block.txs[0].clone())]);
+        for (tx, short_txid) in block.txs.iter().zip(data.short_txids.iter()) {
+            assert_eq!(
+                *short_txid,
+                CompactBlockData::short_txid(&block.block_hash(), &tx.txid())
+            );
+        }
+
+        let mut bytes = vec![0x11; 20];
+        block.header.consensus_serialize(&mut bytes).unwrap();
+        // short txids
+        bytes.append(&mut vec![0x00, 0x00, 0x00, 0x03]);
+        for short_txid in data.short_txids.iter() {
+            bytes.append(&mut short_txid.to_be_bytes().to_vec());
+        }
+        // prefilled txs
+        bytes.append(&mut vec![0x00, 0x00, 0x00, 0x01, 0x00, 0x00]);
+        block.txs[0].consensus_serialize(&mut bytes).unwrap();
+
+        check_codec_and_corruption::<CompactBlockData>(&data, &bytes);
+
+        // must have at least one transaction
+        let mut no_txs = data.clone();
+        no_txs.short_txids.clear();
+        no_txs.prefilled_txs.clear();
+        assert!(check_deserialize_failure::<CompactBlockData>(&no_txs));
+
+        // prefilled indexes must be in range
+        let mut out_of_range = data.clone();
+        out_of_range.prefilled_txs = vec![(3, block.txs[0].clone())];
+        assert!(check_deserialize_failure::<CompactBlockData>(&out_of_range));
+
+        // prefilled indexes must be strictly increasing
+        let mut not_increasing = data.clone();
+        not_increasing.prefilled_txs = vec![(1, block.txs[1].clone()), (1, block.txs[2].clone())];
+        assert!(check_deserialize_failure::<CompactBlockData>(
+            &not_increasing
+        ));
+    }
+
+    #[test]
+    fn codec_GetBlockTxsData() {
+        let data = GetBlockTxsData {
+            consensus_hash: ConsensusHash([0x11; 20]),
+            block_hash: BlockHeaderHash([0x22; 32]),
+            indexes: vec![1, 0x0203],
+        };
+        let mut bytes = vec![0x11; 20];
+        bytes.append(&mut vec![0x22; 32]);
+        bytes.append(&mut vec![0x00, 0x00, 0x00, 0x02, 0x00, 0x01, 0x02, 0x03]);
+
+        check_codec_and_corruption::<GetBlockTxsData>(&data, &bytes);
+
+        let mut no_indexes = data.clone();
+        no_indexes.indexes.clear();
+        assert!(check_deserialize_failure::<GetBlockTxsData>(&no_indexes));
+
+        let mut not_increasing = data.clone();
+        not_increasing.indexes = vec![2, 1];
+        assert!(check_deserialize_failure::<GetBlockTxsData>(
+            &not_increasing
+        ));
+
+        let mut duplicate = data.clone();
+        duplicate.indexes = vec![1, 1];
+        assert!(check_deserialize_failure::<GetBlockTxsData>(&duplicate));
+    }
+
+    #[test]
+    fn codec_BlockTxsData() {
+        let block = make_codec_test_block(3);
+        let data = BlockTxsData {
+            consensus_hash: ConsensusHash([0x11; 20]),
+            block_hash: block.block_hash(),
+            txs: block.txs[1..].to_vec(),
+        };
+        let mut bytes = vec![0x11; 20];
+        bytes.append(&mut block.block_hash().as_bytes().to_vec());
+        bytes.append(&mut vec![0x00, 0x00, 0x00, 0x02]);
+        block.txs[1].consensus_serialize(&mut bytes).unwrap();
+        block.txs[2].consensus_serialize(&mut bytes).unwrap();
+
+        check_codec_and_corruption::<BlockTxsData>(&data, &bytes);
+    }
+
     #[test]
     fn codec_StacksMessage() {
         let payloads: Vec<StacksMessageType> = vec![
@@ -2163,6 +2672,36 @@ pub mod test {
                 port: 12345,
                 nonce: 0x12345678,
             }),
+            StacksMessageType::GetMempool(GetMempoolData {
+                tx_filter: TxidBloomFilter {
+                    seed: 0x01020304,
+                    num_hashes: 3,
+                    bitlen: 12,
+                    bitvec: vec![0x55, 0x05],
+                },
+                last_txid: Txid([0x11; 32]),
+                max_txs: MEMPOOL_SYNC_PAGE_MAX,
+            }),
+            // TODO: MempoolTxs with transactions
+            StacksMessageType::MempoolTxs(MempoolTxsData {
+                txs: vec![],
+                last_txid: Txid([0x22; 32]),
+                has_more: true,
+            }),
+            StacksMessageType::CompactBlock(CompactBlockData::from_block(
+                &ConsensusHash([0x11; 20]),
+                &make_codec_test_block(3),
+            )),
+            StacksMessageType::GetBlockTxs(GetBlockTxsData {
+                consensus_hash: ConsensusHash([0x11; 20]),
+                block_hash: BlockHeaderHash([0x22; 32]),
+                indexes: vec![1, 2],
+            }),
+            StacksMessageType::BlockTxs(BlockTxsData {
+                consensus_hash: ConsensusHash([0x11; 20]),
+                block_hash: BlockHeaderHash([0x22; 32]),
+                txs: make_codec_test_block(3).txs,
+            }),
         ];
 
         let mut maximal_relayers: Vec<RelayData> = vec![];
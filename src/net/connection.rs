@@ -50,6 +50,7 @@ use net::MAX_MESSAGE_LEN;
 
 use net::download::BLOCK_DOWNLOAD_INTERVAL;
 use net::inv::INV_SYNC_INTERVAL;
+use net::memsync::MEMPOOL_SYNC_INTERVAL;
 use net::neighbors::{
     NEIGHBOR_REQUEST_TIMEOUT, NEIGHBOR_WALK_INTERVAL, NUM_INITIAL_WALKS, WALK_MAX_DURATION,
     WALK_MIN_DURATION, WALK_RESET_INTERVAL, WALK_RESET_PROB, WALK_RETRY_COUNT, WALK_STATE_TIMEOUT,
@@ -349,15 +350,21 @@ pub struct ConnectionOptions {
     pub dns_timeout: u128,
     pub max_inflight_blocks: u64,
     pub read_only_call_limit: ExecutionCost,
+    pub simulation_call_limit: ExecutionCost,
     pub maximum_call_argument_size: u32,
     pub max_block_push_bandwidth: u64,
     pub max_microblocks_push_bandwidth: u64,
     pub max_transaction_push_bandwidth: u64,
+    pub mempool_sync_interval: u64,
+    pub mempool_sync_page_size: u32,
+    pub mempool_sync_max_pages: u64,
+    pub max_mempool_sync_request_rate: u64,
     pub max_sockets: usize,
     pub public_ip_address: Option<(PeerAddress, u16)>,
     pub public_ip_request_timeout: u64,
     pub public_ip_timeout: u64,
     pub public_ip_max_retries: u64,
+    pub admin_rpc_token: Option<String>,
 
     // fault injection
     pub disable_neighbor_walk: bool,
@@ -371,6 +378,7 @@ pub struct ConnectionOptions {
     pub disable_pingbacks: bool,
     pub disable_inbound_walks: bool,
     pub disable_natpunch: bool,
+    pub disable_mempool_sync: bool,
 }
 
 impl std::default::Default for ConnectionOptions {
@@ -415,15 +423,28 @@ impl std::default::Default for ConnectionOptions {
                 read_count: 10,
                 runtime: 10000000,
             },
+            // budget for each RPC dry-run, simulation or fee estimate, which may write
+            simulation_call_limit: ExecutionCost {
+                write_length: 1000000,
+                write_count: 1000,
+                read_length: 1000000,
+                read_count: 1000,
+                runtime: 100000000,
+            },
             maximum_call_argument_size: 20 * BOUND_VALUE_SERIALIZATION_HEX,
             max_block_push_bandwidth: 0, // infinite upload bandwidth allowed
             max_microblocks_push_bandwidth: 0, // infinite upload bandwidth allowed
             max_transaction_push_bandwidth: 0, // infinite upload bandwidth allowed
-            max_sockets: 800,            // maximum number of client sockets we'll ever register
-            public_ip_address: None,     // resolve it at runtime by default
+            mempool_sync_interval: MEMPOOL_SYNC_INTERVAL, // how often to fetch missing transactions from a neighbor's mempool
+            mempool_sync_page_size: 128, // how many transactions to ask for in one GetMempool request
+            mempool_sync_max_pages: 16,  // how many GetMempool requests to send per mempool sync
+            max_mempool_sync_request_rate: 10, // how many GetMempool requests per second a neighbor may send us (0 for no limit)
+            max_sockets: 800, // maximum number of client sockets we'll ever register
+            public_ip_address: None, // resolve it at runtime by default
             public_ip_request_timeout: 60, // how often we can attempt to look up our public IP address
             public_ip_timeout: 3600,       // re-learn the public IP ever hour, if it's not given
             public_ip_max_retries: 3, // maximum number of retries before self-throttling for $public_ip_timeout
+            admin_rpc_token: None, // admin RPC endpoints are disabled unless a bearer token is set
 
             // no faults on by default
             disable_neighbor_walk: false,
@@ -437,6 +458,7 @@ impl std::default::Default for ConnectionOptions {
             disable_pingbacks: false,
             disable_inbound_walks: false,
             disable_natpunch: false,
+            disable_mempool_sync: false,
         }
     }
 }
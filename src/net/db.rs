@@ -52,7 +52,7 @@ use net::Neighbor;
 use net::NeighborAddress;
 use net::NeighborKey;
 use net::PeerAddress;
-use net::ServiceFlags;
+use net::LOCAL_PEER_SERVICES;
 
 use burnchains::PrivateKey;
 use burnchains::PublicKey;
@@ -160,7 +160,7 @@ impl LocalPeer {
 
         let addr = addrbytes;
         let port = port;
-        let services = ServiceFlags::RELAY;
+        let services = LOCAL_PEER_SERVICES;
 
         info!(
             "Peer's public key: {}",
@@ -175,7 +175,7 @@ impl LocalPeer {
             private_key_expire: key_expire,
             addrbytes: addr,
             port: port,
-            services: services as u16,
+            services: services,
             data_url: data_url,
             public_ip_address: None,
         }
@@ -453,11 +453,14 @@ impl PeerDB {
         let local_peer_args: &[&dyn ToSql] = &[
             &p2p_port,
             &data_url.as_str(),
+            &LOCAL_PEER_SERVICES,
             &network_id,
             &parent_network_id,
         ];
 
-        match self.conn.execute("UPDATE local_peer SET port = ?, data_url = ? WHERE network_id = ? AND parent_network_id = ?",
+        // also refresh the advertised services, so nodes upgraded from an older release pick up
+        // any newly-supported ones.
+        match self.conn.execute("UPDATE local_peer SET port = ?, data_url = ?, services = ? WHERE network_id = ? AND parent_network_id = ?",
                                 local_peer_args) {
             Ok(_) => Ok(()),
             Err(e) => Err(db_error::SqliteError(e))
@@ -1205,6 +1208,34 @@ impl PeerDB {
         Ok(())
     }
 
+    /// Remove an allowed CIDR prefix, and clear the allow flag of all peers it covered
+    pub fn remove_allow_cidr<'a>(
+        tx: &mut Transaction<'a>,
+        prefix: &PeerAddress,
+        mask: u32,
+    ) -> Result<(), db_error> {
+        assert!(mask > 0 && mask <= 128);
+        PeerDB::remove_cidr_prefix(tx, "allowed_prefixes", prefix, mask)?;
+
+        debug!("Remove allow {}/{}", &prefix, mask);
+        PeerDB::apply_cidr_filter(tx, prefix, mask, "allowed", 0)?;
+        Ok(())
+    }
+
+    /// Remove a denied CIDR prefix, and clear the deny flag of all peers it covered
+    pub fn remove_deny_cidr<'a>(
+        tx: &mut Transaction<'a>,
+        prefix: &PeerAddress,
+        mask: u32,
+    ) -> Result<(), db_error> {
+        assert!(mask > 0 && mask <= 128);
+        PeerDB::remove_cidr_prefix(tx, "denied_prefixes", prefix, mask)?;
+
+        debug!("Remove deny {}/{}", &prefix, mask);
+        PeerDB::apply_cidr_filter(tx, prefix, mask, "denied", 0)?;
+        Ok(())
+    }
+
     /// Get random neighbors, optionally always including allowed neighbors
     pub fn get_random_neighbors(
         conn: &DBConn,
@@ -1379,7 +1410,7 @@ mod test {
         );
         assert_eq!(local_peer.port, NETWORK_P2P_PORT);
         assert_eq!(local_peer.addrbytes, PeerAddress::from_ipv4(127, 0, 0, 1));
-        assert_eq!(local_peer.services, ServiceFlags::RELAY as u16);
+        assert_eq!(local_peer.services, LOCAL_PEER_SERVICES);
     }
 
     #[test]
@@ -2017,6 +2048,55 @@ mod test {
         assert_eq!(n1.denied, i64::max_value());
         assert_eq!(n2.allowed, 12345);
         assert_eq!(n2.denied, 67890);
+
+        {
+            // remove both prefixes
+            let mut tx = db.tx_begin().unwrap();
+            PeerDB::remove_deny_cidr(
+                &mut tx,
+                &PeerAddress([
+                    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    0x00, 0x00, 0x00,
+                ]),
+                64,
+            )
+            .unwrap();
+            PeerDB::remove_allow_cidr(
+                &mut tx,
+                &PeerAddress([
+                    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    0x00, 0x00, 0x00,
+                ]),
+                48,
+            )
+            .unwrap();
+            tx.commit().unwrap();
+        }
+
+        assert!(PeerDB::get_denied_cidrs(db.conn()).unwrap().is_empty());
+        assert!(PeerDB::get_allowed_cidrs(db.conn()).unwrap().is_empty());
+
+        let n1 = PeerDB::get_peer(
+            db.conn(),
+            neighbor_1.addr.network_id,
+            &neighbor_1.addr.addrbytes,
+            neighbor_1.addr.port,
+        )
+        .unwrap()
+        .unwrap();
+        let n2 = PeerDB::get_peer(
+            db.conn(),
+            neighbor_2.addr.network_id,
+            &neighbor_2.addr.addrbytes,
+            neighbor_2.addr.port,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(n1.allowed, 0);
+        assert_eq!(n1.denied, 0);
+        assert_eq!(n2.allowed, 12345);
+        assert_eq!(n2.denied, 67890);
     }
 
     #[test]
@@ -31,9 +31,16 @@ use serde::{Deserialize, Serialize};
 use serde_json;
 
 use net::codec::{read_next, write_next};
+use net::AdminCidrList;
+use net::AdminCidrRequestBody;
+use net::AdminPeerRequestBody;
+use net::AdminRequest;
 use net::CallReadOnlyRequestBody;
 use net::ClientError;
+use net::ContractEventsQuery;
+use net::DryRunCallRequestBody;
 use net::Error as net_error;
+use net::FeeRateEstimateRequestBody;
 use net::HttpContentType;
 use net::HttpRequestMetadata;
 use net::HttpRequestPreamble;
@@ -42,6 +49,7 @@ use net::HttpResponseMetadata;
 use net::HttpResponsePreamble;
 use net::HttpResponseType;
 use net::HttpVersion;
+use net::MarfProofRequestBody;
 use net::MessageSequence;
 use net::NeighborAddress;
 use net::PeerAddress;
@@ -50,16 +58,21 @@ use net::ProtocolFamily;
 use net::StacksHttpMessage;
 use net::StacksHttpPreamble;
 use net::StacksMessageCodec;
+use net::TransactionStatusResponse;
 use net::HTTP_PREAMBLE_MAX_ENCODED_SIZE;
 use net::HTTP_PREAMBLE_MAX_NUM_HEADERS;
 use net::HTTP_REQUEST_ID_RESERVED;
+use net::MAX_ADMIN_BAN_DURATION;
+use net::MAX_CONTRACT_EVENTS_PAGE_SIZE;
+use net::MAX_MARF_PROOF_KEYS;
 use net::MAX_MESSAGE_LEN;
 use net::MAX_MICROBLOCKS_UNCONFIRMED;
 
 use burnchains::{Address, Txid};
 use chainstate::burn::BlockHeaderHash;
 use chainstate::stacks::{
-    StacksAddress, StacksBlock, StacksBlockId, StacksMicroblock, StacksPublicKey, StacksTransaction,
+    StacksAddress, StacksBlock, StacksBlockId, StacksMicroblock, StacksPublicKey,
+    StacksTransaction, TransactionPayload, TransactionPostCondition, TransactionPostConditionMode,
 };
 
 use util::hash::hex_bytes;
@@ -99,6 +112,10 @@ lazy_static! {
     static ref PATH_GETMICROBLOCKS_UNCONFIRMED: Regex =
         Regex::new(r#"^/v2/microblocks/unconfirmed/([0-9a-f]{64})/([0-9]{1,5})$"#).unwrap();
     static ref PATH_POSTTRANSACTION: Regex = Regex::new(r#"^/v2/transactions$"#).unwrap();
+    static ref PATH_POST_SIMULATE_TRANSACTION: Regex =
+        Regex::new(r#"^/v2/transactions/simulate$"#).unwrap();
+    static ref PATH_GETTRANSACTION: Regex =
+        Regex::new(r#"^/v2/transactions/(0x)?([0-9a-f]{64})$"#).unwrap();
     static ref PATH_POSTMICROBLOCK: Regex = Regex::new(r#"^/v2/microblocks$"#).unwrap();
     static ref PATH_GET_ACCOUNT: Regex = Regex::new(&format!(
         "^/v2/accounts/(?P<principal>{})$",
@@ -115,17 +132,35 @@ lazy_static! {
         *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX, *CLARITY_NAME_REGEX
     ))
     .unwrap();
+    static ref PATH_POST_CALL_DRY_RUN: Regex = Regex::new(&format!(
+        "^/v2/contracts/call-dry-run/(?P<address>{})/(?P<contract>{})/(?P<function>{})$",
+        *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX, *CLARITY_NAME_REGEX
+    ))
+    .unwrap();
     static ref PATH_GET_CONTRACT_SRC: Regex = Regex::new(&format!(
         "^/v2/contracts/source/(?P<address>{})/(?P<contract>{})$",
         *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX
     ))
     .unwrap();
+    static ref PATH_GET_CONTRACT_EVENTS: Regex = Regex::new(&format!(
+        "^/v2/contracts/events/(?P<address>{})/(?P<contract>{})$",
+        *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX
+    ))
+    .unwrap();
     static ref PATH_GET_CONTRACT_ABI: Regex = Regex::new(&format!(
         "^/v2/contracts/interface/(?P<address>{})/(?P<contract>{})$",
         *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX
     ))
     .unwrap();
     static ref PATH_GET_TRANSFER_COST: Regex = Regex::new("^/v2/fees/transfer$").unwrap();
+    static ref PATH_POST_FEE_RATE_ESTIMATE: Regex = Regex::new("^/v2/fees/transaction$").unwrap();
+    static ref PATH_POST_MARF_PROOF: Regex = Regex::new("^/v2/proofs$").unwrap();
+    static ref PATH_ADMIN_PEERS: Regex = Regex::new("^/v2/admin/peers$").unwrap();
+    static ref PATH_ADMIN_PEERS_UPDATE: Regex =
+        Regex::new("^/v2/admin/peers/(ban|unban)$").unwrap();
+    static ref PATH_ADMIN_CIDRS: Regex = Regex::new("^/v2/admin/cidrs$").unwrap();
+    static ref PATH_ADMIN_CIDRS_UPDATE: Regex =
+        Regex::new("^/v2/admin/cidrs/(allow|deny)/(add|remove)$").unwrap();
     static ref PATH_OPTIONS_WILDCARD: Regex = Regex::new("^/v2/.{0,4096}$").unwrap();
 }
 
@@ -829,6 +864,14 @@ fn empty_headers<W: Write>(_fd: &mut W) -> Result<(), net_error> {
     Ok(())
 }
 
+fn bearer_token_headers<W: Write>(fd: &mut W, token_opt: Option<&String>) -> Result<(), net_error> {
+    if let Some(token) = token_opt {
+        fd.write_all(format!("Authorization: Bearer {}\r\n", token).as_bytes())
+            .map_err(net_error::WriteError)?;
+    }
+    Ok(())
+}
+
 fn keep_alive_headers<W: Write>(fd: &mut W, md: &HttpResponseMetadata) -> Result<(), net_error> {
     match md.client_version {
         HttpVersion::Http10 => {
@@ -1453,6 +1496,16 @@ impl HttpRequestType {
                 &PATH_POSTTRANSACTION,
                 &HttpRequestType::parse_posttransaction,
             ),
+            (
+                "POST",
+                &PATH_POST_SIMULATE_TRANSACTION,
+                &HttpRequestType::parse_simulate_transaction,
+            ),
+            (
+                "GET",
+                &PATH_GETTRANSACTION,
+                &HttpRequestType::parse_gettransaction,
+            ),
             (
                 "POST",
                 &PATH_POSTMICROBLOCK,
@@ -1473,6 +1526,16 @@ impl HttpRequestType {
                 &PATH_GET_TRANSFER_COST,
                 &HttpRequestType::parse_get_transfer_cost,
             ),
+            (
+                "POST",
+                &PATH_POST_FEE_RATE_ESTIMATE,
+                &HttpRequestType::parse_post_fee_rate_estimate,
+            ),
+            (
+                "POST",
+                &PATH_POST_MARF_PROOF,
+                &HttpRequestType::parse_post_marf_proof,
+            ),
             (
                 "GET",
                 &PATH_GET_CONTRACT_SRC,
@@ -1483,11 +1546,41 @@ impl HttpRequestType {
                 &PATH_GET_CONTRACT_ABI,
                 &HttpRequestType::parse_get_contract_abi,
             ),
+            (
+                "GET",
+                &PATH_GET_CONTRACT_EVENTS,
+                &HttpRequestType::parse_get_contract_events,
+            ),
             (
                 "POST",
                 &PATH_POST_CALL_READ_ONLY,
                 &HttpRequestType::parse_call_read_only,
             ),
+            (
+                "POST",
+                &PATH_POST_CALL_DRY_RUN,
+                &HttpRequestType::parse_call_dry_run,
+            ),
+            (
+                "GET",
+                &PATH_ADMIN_PEERS,
+                &HttpRequestType::parse_admin_peers,
+            ),
+            (
+                "POST",
+                &PATH_ADMIN_PEERS_UPDATE,
+                &HttpRequestType::parse_admin_peers_update,
+            ),
+            (
+                "GET",
+                &PATH_ADMIN_CIDRS,
+                &HttpRequestType::parse_admin_cidrs,
+            ),
+            (
+                "POST",
+                &PATH_ADMIN_CIDRS_UPDATE,
+                &HttpRequestType::parse_admin_cidrs_update,
+            ),
             (
                 "OPTIONS",
                 &PATH_OPTIONS_WILDCARD,
@@ -1610,6 +1703,255 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_post_fee_rate_estimate<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < MAX_MESSAGE_LEN) {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: invalid body length for PostFeeRateEstimate ({})",
+                content_len
+            )));
+        }
+
+        if preamble.content_type != Some(HttpContentType::JSON) {
+            return Err(net_error::DeserializeError(
+                "Invalid content-type: expected application/json".to_string(),
+            ));
+        }
+
+        let body: FeeRateEstimateRequestBody = serde_json::from_reader(fd)
+            .map_err(|_e| net_error::DeserializeError("Failed to parse JSON body".into()))?;
+
+        let payload_hex = if body.transaction_payload.starts_with("0x") {
+            &body.transaction_payload[2..]
+        } else {
+            &body.transaction_payload
+        };
+
+        let payload_bytes = hex_bytes(payload_hex).map_err(|_e| {
+            net_error::DeserializeError("Failed to parse transaction payload hex".into())
+        })?;
+
+        let payload =
+            TransactionPayload::consensus_deserialize(&mut &payload_bytes[..]).map_err(|_e| {
+                net_error::DeserializeError("Failed to deserialize transaction payload".into())
+            })?;
+
+        let sender = match body.sender {
+            Some(ref sender) => Some(PrincipalData::parse(sender).map_err(|_e| {
+                net_error::DeserializeError("Failed to parse sender principal".into())
+            })?),
+            None => None,
+        };
+
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::PostFeeRateEstimate(
+            HttpRequestMetadata::from_preamble(preamble),
+            payload,
+            sender,
+            body.estimated_len,
+            tip,
+        ))
+    }
+
+    fn parse_post_marf_proof<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < MAX_MESSAGE_LEN) {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: invalid body length for PostMarfProof ({})",
+                content_len
+            )));
+        }
+
+        if preamble.content_type != Some(HttpContentType::JSON) {
+            return Err(net_error::DeserializeError(
+                "Invalid content-type: expected application/json".to_string(),
+            ));
+        }
+
+        let body: MarfProofRequestBody = serde_json::from_reader(fd)
+            .map_err(|_e| net_error::DeserializeError("Failed to parse JSON body".into()))?;
+
+        if body.keys.len() == 0 || body.keys.len() > MAX_MARF_PROOF_KEYS {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: expected between 1 and {} keys, got {}",
+                MAX_MARF_PROOF_KEYS,
+                body.keys.len()
+            )));
+        }
+
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::PostMarfProof(
+            HttpRequestMetadata::from_preamble(preamble),
+            body.keys,
+            tip,
+        ))
+    }
+
+    /// Get the bearer token from the Authorization header, if given
+    fn get_bearer_token(preamble: &HttpRequestPreamble) -> Option<String> {
+        preamble.headers.get("authorization").and_then(|value| {
+            let mut parts = value.trim().splitn(2, ' ');
+            match (parts.next(), parts.next()) {
+                (Some(scheme), Some(token)) if scheme.eq_ignore_ascii_case("bearer") => {
+                    Some(token.trim().to_string())
+                }
+                _ => None,
+            }
+        })
+    }
+
+    /// Read a JSON body for one of the admin requests
+    fn parse_admin_json_body<R: Read, T: serde::de::DeserializeOwned>(
+        preamble: &HttpRequestPreamble,
+        fd: &mut R,
+    ) -> Result<T, net_error> {
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < MAX_MESSAGE_LEN) {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: invalid body length for Admin ({})",
+                content_len
+            )));
+        }
+
+        if preamble.content_type != Some(HttpContentType::JSON) {
+            return Err(net_error::DeserializeError(
+                "Invalid content-type: expected application/json".to_string(),
+            ));
+        }
+
+        serde_json::from_reader(fd)
+            .map_err(|_e| net_error::DeserializeError("Failed to parse JSON body".into()))
+    }
+
+    fn parse_admin_peers<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for Admin".to_string(),
+            ));
+        }
+
+        Ok(HttpRequestType::Admin(
+            HttpRequestMetadata::from_preamble(preamble),
+            HttpRequestType::get_bearer_token(preamble),
+            AdminRequest::GetPeers,
+        ))
+    }
+
+    fn parse_admin_peers_update<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        regex: &Captures,
+        _query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let body: AdminPeerRequestBody = HttpRequestType::parse_admin_json_body(preamble, fd)?;
+        if let Some(duration) = body.duration {
+            if duration > MAX_ADMIN_BAN_DURATION {
+                return Err(net_error::ClientError(ClientError::Message(format!(
+                    "Invalid ban duration: {} exceeds the maximum of {} seconds",
+                    duration, MAX_ADMIN_BAN_DURATION
+                ))));
+            }
+        }
+        let admin_req = match regex.get(1).map(|m| m.as_str()) {
+            Some("ban") => AdminRequest::BanPeer(body.ip, body.port, body.duration),
+            Some("unban") => AdminRequest::UnbanPeer(body.ip, body.port),
+            _ => {
+                return Err(net_error::DeserializeError(
+                    "Failed to match path to admin peer operation".to_string(),
+                ));
+            }
+        };
+
+        Ok(HttpRequestType::Admin(
+            HttpRequestMetadata::from_preamble(preamble),
+            HttpRequestType::get_bearer_token(preamble),
+            admin_req,
+        ))
+    }
+
+    fn parse_admin_cidrs<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for Admin".to_string(),
+            ));
+        }
+
+        Ok(HttpRequestType::Admin(
+            HttpRequestMetadata::from_preamble(preamble),
+            HttpRequestType::get_bearer_token(preamble),
+            AdminRequest::GetCidrs,
+        ))
+    }
+
+    fn parse_admin_cidrs_update<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        regex: &Captures,
+        _query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let body: AdminCidrRequestBody = HttpRequestType::parse_admin_json_body(preamble, fd)?;
+        let (prefix, mask) = PeerAddress::from_cidr_str(&body.cidr).ok_or_else(|| {
+            net_error::ClientError(ClientError::Message(format!(
+                "Invalid CIDR prefix: {}",
+                &body.cidr
+            )))
+        })?;
+
+        let list = match regex.get(1).map(|m| m.as_str()) {
+            Some("allow") => AdminCidrList::Allow,
+            Some("deny") => AdminCidrList::Deny,
+            _ => {
+                return Err(net_error::DeserializeError(
+                    "Failed to match path to admin CIDR list".to_string(),
+                ));
+            }
+        };
+
+        let admin_req = match regex.get(2).map(|m| m.as_str()) {
+            Some("add") => AdminRequest::AddCidr(list, prefix, mask),
+            Some("remove") => AdminRequest::RemoveCidr(list, prefix, mask),
+            _ => {
+                return Err(net_error::DeserializeError(
+                    "Failed to match path to admin CIDR operation".to_string(),
+                ));
+            }
+        };
+
+        Ok(HttpRequestType::Admin(
+            HttpRequestMetadata::from_preamble(preamble),
+            HttpRequestType::get_bearer_token(preamble),
+            admin_req,
+        ))
+    }
+
     /// check whether the given option query string
     ///   sets proof=0 (setting proof to false).
     /// Defaults to _true_
@@ -1782,13 +2124,24 @@ impl HttpRequestType {
         ))
     }
 
-    fn parse_get_contract_arguments(
+    fn parse_call_dry_run<R: Read>(
+        protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
         captures: &Captures,
-    ) -> Result<(HttpRequestMetadata, StacksAddress, ContractName), net_error> {
-        if preamble.get_content_length() != 0 {
+        query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < protocol.maximum_call_argument_size) {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: invalid body length for DryRunCall ({})",
+                content_len
+            )));
+        }
+
+        if preamble.content_type != Some(HttpContentType::JSON) {
             return Err(net_error::DeserializeError(
-                "Invalid Http request: expected 0-length body".to_string(),
+                "Invalid content-type: expected application/json".to_string(),
             ));
         }
 
@@ -1797,20 +2150,95 @@ impl HttpRequestType {
         })?;
         let contract_name = ContractName::try_from(captures["contract"].to_string())
             .map_err(|_e| net_error::DeserializeError("Failed to parse contract name".into()))?;
+        let func_name = ClarityName::try_from(captures["function"].to_string())
+            .map_err(|_e| net_error::DeserializeError("Failed to parse function name".into()))?;
 
-        Ok((
+        let body: DryRunCallRequestBody = serde_json::from_reader(fd)
+            .map_err(|_e| net_error::DeserializeError("Failed to parse JSON body".into()))?;
+
+        let sender = PrincipalData::parse(&body.sender)
+            .map_err(|_e| net_error::DeserializeError("Failed to parse sender principal".into()))?;
+
+        let arguments = body
+            .arguments
+            .into_iter()
+            .map(|hex| Value::try_deserialize_hex_untyped(&hex).ok())
+            .collect::<Option<Vec<Value>>>()
+            .ok_or_else(|| {
+                net_error::DeserializeError("Failed to deserialize argument value".into())
+            })?;
+
+        let post_conditions = body
+            .post_conditions
+            .iter()
+            .map(|pc_hex| {
+                let pc_hex = if pc_hex.starts_with("0x") {
+                    &pc_hex[2..]
+                } else {
+                    &pc_hex[..]
+                };
+                let pc_bytes = hex_bytes(pc_hex).ok()?;
+                TransactionPostCondition::consensus_deserialize(&mut &pc_bytes[..]).ok()
+            })
+            .collect::<Option<Vec<TransactionPostCondition>>>()
+            .ok_or_else(|| {
+                net_error::DeserializeError("Failed to deserialize post-condition".into())
+            })?;
+
+        let post_condition_mode = match body.post_condition_mode.as_ref().map(|m| m.as_str()) {
+            None | Some("deny") => TransactionPostConditionMode::Deny,
+            Some("allow") => TransactionPostConditionMode::Allow,
+            Some(_) => {
+                return Err(net_error::DeserializeError(
+                    "Invalid post-condition mode: expected \"allow\" or \"deny\"".into(),
+                ));
+            }
+        };
+
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::DryRunCallFunction(
             HttpRequestMetadata::from_preamble(preamble),
             contract_addr,
             contract_name,
+            sender,
+            func_name,
+            arguments,
+            post_conditions,
+            post_condition_mode,
+            tip,
         ))
     }
 
-    fn parse_get_contract_abi<R: Read>(
-        _protocol: &mut StacksHttp,
+    fn parse_get_contract_arguments(
         preamble: &HttpRequestPreamble,
         captures: &Captures,
-        query: Option<&str>,
-        _fd: &mut R,
+    ) -> Result<(HttpRequestMetadata, StacksAddress, ContractName), net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+
+        let contract_addr = StacksAddress::from_string(&captures["address"]).ok_or_else(|| {
+            net_error::DeserializeError("Failed to parse contract address".into())
+        })?;
+        let contract_name = ContractName::try_from(captures["contract"].to_string())
+            .map_err(|_e| net_error::DeserializeError("Failed to parse contract name".into()))?;
+
+        Ok((
+            HttpRequestMetadata::from_preamble(preamble),
+            contract_addr,
+            contract_name,
+        ))
+    }
+
+    fn parse_get_contract_abi<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
     ) -> Result<HttpRequestType, net_error> {
         let tip = HttpRequestType::get_chain_tip_query(query);
         HttpRequestType::parse_get_contract_arguments(preamble, captures).map(
@@ -1818,6 +2246,66 @@ impl HttpRequestType {
         )
     }
 
+    fn parse_get_contract_events<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let tip = HttpRequestType::get_chain_tip_query(query);
+        let events_query = HttpRequestType::get_contract_events_query(query)?;
+        HttpRequestType::parse_get_contract_arguments(preamble, captures).map(
+            |(preamble, addr, name)| {
+                HttpRequestType::GetContractEvents(preamble, addr, name, events_query, tip)
+            },
+        )
+    }
+
+    /// Get the topic, height range, and page from a contract events query string.
+    /// The page size defaults to, and may not exceed, MAX_CONTRACT_EVENTS_PAGE_SIZE.
+    fn get_contract_events_query(query: Option<&str>) -> Result<ContractEventsQuery, net_error> {
+        let mut events_query = ContractEventsQuery {
+            topic: None,
+            from_height: None,
+            to_height: None,
+            offset: 0,
+            limit: MAX_CONTRACT_EVENTS_PAGE_SIZE,
+        };
+
+        let query_string = match query {
+            Some(query_string) => query_string,
+            None => {
+                return Ok(events_query);
+            }
+        };
+
+        for (key, value) in form_urlencoded::parse(query_string.as_bytes()) {
+            let parse_u64 = |value: &str| {
+                value.parse::<u64>().map_err(|_e| {
+                    net_error::DeserializeError(format!("Failed to parse query parameter {}", key))
+                })
+            };
+            match key.as_ref() {
+                "topic" => events_query.topic = Some(value.to_string()),
+                "from_height" => events_query.from_height = Some(parse_u64(&value)?),
+                "to_height" => events_query.to_height = Some(parse_u64(&value)?),
+                "offset" => events_query.offset = parse_u64(&value)?,
+                "limit" => events_query.limit = parse_u64(&value)?,
+                _ => {}
+            }
+        }
+
+        if events_query.limit == 0 || events_query.limit > MAX_CONTRACT_EVENTS_PAGE_SIZE {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid limit: must be between 1 and {}",
+                MAX_CONTRACT_EVENTS_PAGE_SIZE
+            )));
+        }
+
+        Ok(events_query)
+    }
+
     fn parse_get_contract_source<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -1974,11 +2462,40 @@ impl HttpRequestType {
         _query: Option<&str>,
         fd: &mut R,
     ) -> Result<HttpRequestType, net_error> {
+        let tx = HttpRequestType::parse_transaction_body(preamble, fd, "PostTransaction")?;
+        Ok(HttpRequestType::PostTransaction(
+            HttpRequestMetadata::from_preamble(preamble),
+            tx,
+        ))
+    }
+
+    fn parse_simulate_transaction<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let tx = HttpRequestType::parse_transaction_body(preamble, fd, "SimulateTransaction")?;
+        let tip = HttpRequestType::get_chain_tip_query(query);
+        Ok(HttpRequestType::SimulateTransaction(
+            HttpRequestMetadata::from_preamble(preamble),
+            tx,
+            tip,
+        ))
+    }
+
+    /// Read a consensus-serialized transaction from an application/octet-stream request body
+    fn parse_transaction_body<R: Read>(
+        preamble: &HttpRequestPreamble,
+        fd: &mut R,
+        request_name: &str,
+    ) -> Result<StacksTransaction, net_error> {
         if preamble.get_content_length() == 0 {
-            return Err(net_error::DeserializeError(
-                "Invalid Http request: expected non-zero-length body for PostTransaction"
-                    .to_string(),
-            ));
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: expected non-zero-length body for {}",
+                request_name
+            )));
         }
 
         // content-type must be given, and must be application/octet-stream
@@ -2008,9 +2525,38 @@ impl HttpRequestType {
                 e
             }
         })?;
-        Ok(HttpRequestType::PostTransaction(
+        Ok(tx)
+    }
+
+    fn parse_gettransaction<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetTransaction".to_string(),
+            ));
+        }
+
+        let txid_str = captures
+            .get(2)
+            .ok_or(net_error::DeserializeError(
+                "Failed to match path to txid group".to_string(),
+            ))?
+            .as_str();
+
+        let txid = Txid::from_hex(txid_str)
+            .map_err(|_e| net_error::DeserializeError("Failed to parse txid".to_string()))?;
+
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetTransaction(
             HttpRequestMetadata::from_preamble(preamble),
-            tx,
+            txid,
+            tip,
         ))
     }
 
@@ -2078,14 +2624,21 @@ impl HttpRequestType {
             HttpRequestType::GetMicroblocksConfirmed(ref md, _) => md,
             HttpRequestType::GetMicroblocksUnconfirmed(ref md, _, _) => md,
             HttpRequestType::PostTransaction(ref md, _) => md,
+            HttpRequestType::SimulateTransaction(ref md, ..) => md,
+            HttpRequestType::GetTransaction(ref md, ..) => md,
             HttpRequestType::PostMicroblock(ref md, ..) => md,
             HttpRequestType::GetAccount(ref md, ..) => md,
             HttpRequestType::GetMapEntry(ref md, ..) => md,
             HttpRequestType::GetTransferCost(ref md) => md,
+            HttpRequestType::PostFeeRateEstimate(ref md, ..) => md,
+            HttpRequestType::PostMarfProof(ref md, ..) => md,
             HttpRequestType::GetContractABI(ref md, ..) => md,
+            HttpRequestType::GetContractEvents(ref md, ..) => md,
             HttpRequestType::GetContractSrc(ref md, ..) => md,
             HttpRequestType::CallReadOnlyFunction(ref md, ..) => md,
+            HttpRequestType::DryRunCallFunction(ref md, ..) => md,
             HttpRequestType::OptionsPreflight(ref md, ..) => md,
+            HttpRequestType::Admin(ref md, ..) => md,
             HttpRequestType::ClientError(ref md, ..) => md,
         }
     }
@@ -2100,14 +2653,21 @@ impl HttpRequestType {
             HttpRequestType::GetMicroblocksConfirmed(ref mut md, _) => md,
             HttpRequestType::GetMicroblocksUnconfirmed(ref mut md, _, _) => md,
             HttpRequestType::PostTransaction(ref mut md, _) => md,
+            HttpRequestType::SimulateTransaction(ref mut md, ..) => md,
+            HttpRequestType::GetTransaction(ref mut md, ..) => md,
             HttpRequestType::PostMicroblock(ref mut md, ..) => md,
             HttpRequestType::GetAccount(ref mut md, ..) => md,
             HttpRequestType::GetMapEntry(ref mut md, ..) => md,
             HttpRequestType::GetTransferCost(ref mut md) => md,
+            HttpRequestType::PostFeeRateEstimate(ref mut md, ..) => md,
+            HttpRequestType::PostMarfProof(ref mut md, ..) => md,
             HttpRequestType::GetContractABI(ref mut md, ..) => md,
+            HttpRequestType::GetContractEvents(ref mut md, ..) => md,
             HttpRequestType::GetContractSrc(ref mut md, ..) => md,
             HttpRequestType::CallReadOnlyFunction(ref mut md, ..) => md,
+            HttpRequestType::DryRunCallFunction(ref mut md, ..) => md,
             HttpRequestType::OptionsPreflight(ref mut md, ..) => md,
+            HttpRequestType::Admin(ref mut md, ..) => md,
             HttpRequestType::ClientError(ref mut md, ..) => md,
         }
     }
@@ -2145,6 +2705,15 @@ impl HttpRequestType {
                 min_seq
             ),
             HttpRequestType::PostTransaction(_md, ..) => "/v2/transactions".to_string(),
+            HttpRequestType::SimulateTransaction(_md, _, tip_opt) => format!(
+                "/v2/transactions/simulate{}",
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
+            HttpRequestType::GetTransaction(_md, txid, tip_opt) => format!(
+                "/v2/transactions/{}{}",
+                txid.to_hex(),
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
             HttpRequestType::PostMicroblock(_md, _, tip_opt) => format!(
                 "/v2/microblocks{}",
                 HttpRequestType::make_query_string(tip_opt.as_ref(), true)
@@ -2170,12 +2739,49 @@ impl HttpRequestType {
                 HttpRequestType::make_query_string(tip_opt.as_ref(), *with_proof)
             ),
             HttpRequestType::GetTransferCost(_md) => "/v2/fees/transfer".into(),
+            HttpRequestType::PostFeeRateEstimate(_md, _, _, _, tip_opt) => format!(
+                "/v2/fees/transaction{}",
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
+            HttpRequestType::PostMarfProof(_md, _, tip_opt) => format!(
+                "/v2/proofs{}",
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
             HttpRequestType::GetContractABI(_, contract_addr, contract_name, tip_opt) => format!(
                 "/v2/contracts/interface/{}/{}{}",
                 contract_addr,
                 contract_name.as_str(),
                 HttpRequestType::make_query_string(tip_opt.as_ref(), true)
             ),
+            HttpRequestType::GetContractEvents(
+                _,
+                contract_addr,
+                contract_name,
+                events_query,
+                tip_opt,
+            ) => {
+                let mut query = form_urlencoded::Serializer::new(String::new());
+                if let Some(tip) = tip_opt {
+                    query.append_pair("tip", &tip.to_hex());
+                }
+                if let Some(ref topic) = events_query.topic {
+                    query.append_pair("topic", topic);
+                }
+                if let Some(from_height) = events_query.from_height {
+                    query.append_pair("from_height", &from_height.to_string());
+                }
+                if let Some(to_height) = events_query.to_height {
+                    query.append_pair("to_height", &to_height.to_string());
+                }
+                query.append_pair("offset", &events_query.offset.to_string());
+                query.append_pair("limit", &events_query.limit.to_string());
+                format!(
+                    "/v2/contracts/events/{}/{}?{}",
+                    contract_addr,
+                    contract_name.as_str(),
+                    query.finish()
+                )
+            }
             HttpRequestType::GetContractSrc(
                 _,
                 contract_addr,
@@ -2203,7 +2809,32 @@ impl HttpRequestType {
                 func_name.as_str(),
                 HttpRequestType::make_query_string(tip_opt.as_ref(), true)
             ),
+            HttpRequestType::DryRunCallFunction(
+                _,
+                contract_addr,
+                contract_name,
+                _,
+                func_name,
+                ..,
+                tip_opt,
+            ) => format!(
+                "/v2/contracts/call-dry-run/{}/{}/{}{}",
+                contract_addr,
+                contract_name.as_str(),
+                func_name.as_str(),
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
             HttpRequestType::OptionsPreflight(_md, path) => path.to_string(),
+            HttpRequestType::Admin(_md, _token, admin_req) => match admin_req {
+                AdminRequest::GetPeers => "/v2/admin/peers".to_string(),
+                AdminRequest::BanPeer(..) => "/v2/admin/peers/ban".to_string(),
+                AdminRequest::UnbanPeer(..) => "/v2/admin/peers/unban".to_string(),
+                AdminRequest::GetCidrs => "/v2/admin/cidrs".to_string(),
+                AdminRequest::AddCidr(list, ..) => format!("/v2/admin/cidrs/{}/add", list.as_str()),
+                AdminRequest::RemoveCidr(list, ..) => {
+                    format!("/v2/admin/cidrs/{}/remove", list.as_str())
+                }
+            },
             HttpRequestType::ClientError(_md, e) => match e {
                 ClientError::NotFound(path) => path.to_string(),
                 _ => "error path unknown".into(),
@@ -2230,6 +2861,23 @@ impl HttpRequestType {
                 )?;
                 fd.write_all(&tx_bytes).map_err(net_error::WriteError)?;
             }
+            HttpRequestType::SimulateTransaction(md, tx, ..) => {
+                let mut tx_bytes = vec![];
+                write_next(&mut tx_bytes, tx)?;
+
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    "POST",
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    Some(tx_bytes.len() as u32),
+                    Some(&HttpContentType::Bytes),
+                    empty_headers,
+                )?;
+                fd.write_all(&tx_bytes).map_err(net_error::WriteError)?;
+            }
             HttpRequestType::PostMicroblock(md, mb, ..) => {
                 let mut mb_bytes = vec![];
                 write_next(&mut mb_bytes, mb)?;
@@ -2318,6 +2966,187 @@ impl HttpRequestType {
                 fd.write_all(&request_body_bytes)
                     .map_err(net_error::WriteError)?;
             }
+            HttpRequestType::DryRunCallFunction(
+                md,
+                _contract_addr,
+                _contract_name,
+                sender,
+                _func_name,
+                func_args,
+                post_conditions,
+                post_condition_mode,
+                ..,
+            ) => {
+                let mut args = vec![];
+                for arg in func_args.iter() {
+                    let mut arg_bytes = vec![];
+                    arg.serialize_write(&mut arg_bytes)
+                        .map_err(net_error::WriteError)?;
+                    args.push(to_hex(&arg_bytes));
+                }
+
+                let mut pcs = vec![];
+                for pc in post_conditions.iter() {
+                    let mut pc_bytes = vec![];
+                    write_next(&mut pc_bytes, pc)?;
+                    pcs.push(to_hex(&pc_bytes));
+                }
+
+                let request_body = DryRunCallRequestBody {
+                    sender: sender.to_string(),
+                    arguments: args,
+                    post_conditions: pcs,
+                    post_condition_mode: Some(
+                        match post_condition_mode {
+                            TransactionPostConditionMode::Allow => "allow",
+                            TransactionPostConditionMode::Deny => "deny",
+                        }
+                        .to_string(),
+                    ),
+                };
+
+                let mut request_body_bytes = vec![];
+                serde_json::to_writer(&mut request_body_bytes, &request_body).map_err(|e| {
+                    net_error::SerializeError(format!(
+                        "Failed to serialize dry-run call to JSON: {:?}",
+                        &e
+                    ))
+                })?;
+
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    "POST",
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    Some(request_body_bytes.len() as u32),
+                    Some(&HttpContentType::JSON),
+                    empty_headers,
+                )?;
+                fd.write_all(&request_body_bytes)
+                    .map_err(net_error::WriteError)?;
+            }
+            HttpRequestType::PostFeeRateEstimate(md, payload, sender, estimated_len, ..) => {
+                let mut payload_bytes = vec![];
+                write_next(&mut payload_bytes, payload)?;
+
+                let request_body = FeeRateEstimateRequestBody {
+                    transaction_payload: format!("0x{}", to_hex(&payload_bytes)),
+                    sender: sender.as_ref().map(|sender| sender.to_string()),
+                    estimated_len: estimated_len.clone(),
+                };
+
+                let mut request_body_bytes = vec![];
+                serde_json::to_writer(&mut request_body_bytes, &request_body).map_err(|e| {
+                    net_error::SerializeError(format!(
+                        "Failed to serialize fee rate estimate request to JSON: {:?}",
+                        &e
+                    ))
+                })?;
+
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    "POST",
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    Some(request_body_bytes.len() as u32),
+                    Some(&HttpContentType::JSON),
+                    empty_headers,
+                )?;
+                fd.write_all(&request_body_bytes)
+                    .map_err(net_error::WriteError)?;
+            }
+            HttpRequestType::PostMarfProof(md, keys, ..) => {
+                let request_body = MarfProofRequestBody { keys: keys.clone() };
+
+                let mut request_body_bytes = vec![];
+                serde_json::to_writer(&mut request_body_bytes, &request_body).map_err(|e| {
+                    net_error::SerializeError(format!(
+                        "Failed to serialize MARF proof request to JSON: {:?}",
+                        &e
+                    ))
+                })?;
+
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    "POST",
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    Some(request_body_bytes.len() as u32),
+                    Some(&HttpContentType::JSON),
+                    empty_headers,
+                )?;
+                fd.write_all(&request_body_bytes)
+                    .map_err(net_error::WriteError)?;
+            }
+            HttpRequestType::Admin(md, token_opt, admin_req) => {
+                let request_body_res = match admin_req {
+                    AdminRequest::GetPeers | AdminRequest::GetCidrs => None,
+                    AdminRequest::BanPeer(addr, port, duration_opt) => {
+                        Some(serde_json::to_vec(&AdminPeerRequestBody {
+                            ip: addr.clone(),
+                            port: *port,
+                            duration: duration_opt.clone(),
+                        }))
+                    }
+                    AdminRequest::UnbanPeer(addr, port) => {
+                        Some(serde_json::to_vec(&AdminPeerRequestBody {
+                            ip: addr.clone(),
+                            port: *port,
+                            duration: None,
+                        }))
+                    }
+                    AdminRequest::AddCidr(_, prefix, mask)
+                    | AdminRequest::RemoveCidr(_, prefix, mask) => {
+                        Some(serde_json::to_vec(&AdminCidrRequestBody {
+                            cidr: prefix.to_cidr_string(*mask),
+                        }))
+                    }
+                };
+
+                match request_body_res {
+                    None => {
+                        HttpRequestPreamble::new_serialized(
+                            fd,
+                            &md.version,
+                            "GET",
+                            &self.request_path(),
+                            &md.peer,
+                            md.keep_alive,
+                            None,
+                            None,
+                            |ref mut fd| bearer_token_headers(fd, token_opt.as_ref()),
+                        )?;
+                    }
+                    Some(request_body_res) => {
+                        let request_body_bytes = request_body_res.map_err(|e| {
+                            net_error::SerializeError(format!(
+                                "Failed to serialize admin request to JSON: {:?}",
+                                &e
+                            ))
+                        })?;
+
+                        HttpRequestPreamble::new_serialized(
+                            fd,
+                            &md.version,
+                            "POST",
+                            &self.request_path(),
+                            &md.peer,
+                            md.keep_alive,
+                            Some(request_body_bytes.len() as u32),
+                            Some(&HttpContentType::JSON),
+                            |ref mut fd| bearer_token_headers(fd, token_opt.as_ref()),
+                        )?;
+                        fd.write_all(&request_body_bytes)
+                            .map_err(net_error::WriteError)?;
+                    }
+                }
+            }
             other_type => {
                 let md = other_type.metadata();
                 let request_path = other_type.request_path();
@@ -2577,6 +3406,14 @@ impl HttpResponseType {
                 &HttpResponseType::parse_microblocks_unconfirmed,
             ),
             (&PATH_POSTTRANSACTION, &HttpResponseType::parse_txid),
+            (
+                &PATH_POST_SIMULATE_TRANSACTION,
+                &HttpResponseType::parse_transaction_simulation,
+            ),
+            (
+                &PATH_GETTRANSACTION,
+                &HttpResponseType::parse_transaction_status,
+            ),
             (
                 &PATH_POSTMICROBLOCK,
                 &HttpResponseType::parse_microblock_hash,
@@ -2590,11 +3427,34 @@ impl HttpResponseType {
                 &PATH_GET_CONTRACT_ABI,
                 &HttpResponseType::parse_get_contract_abi,
             ),
+            (
+                &PATH_GET_CONTRACT_EVENTS,
+                &HttpResponseType::parse_get_contract_events,
+            ),
             (
                 &PATH_POST_CALL_READ_ONLY,
                 &HttpResponseType::parse_call_read_only,
             ),
+            (
+                &PATH_POST_CALL_DRY_RUN,
+                &HttpResponseType::parse_call_dry_run,
+            ),
             (&PATH_GET_MAP_ENTRY, &HttpResponseType::parse_get_map_entry),
+            (
+                &PATH_POST_FEE_RATE_ESTIMATE,
+                &HttpResponseType::parse_fee_rate_estimate,
+            ),
+            (&PATH_POST_MARF_PROOF, &HttpResponseType::parse_marf_proof),
+            (&PATH_ADMIN_PEERS, &HttpResponseType::parse_admin_peers),
+            (
+                &PATH_ADMIN_PEERS_UPDATE,
+                &HttpResponseType::parse_admin_update,
+            ),
+            (&PATH_ADMIN_CIDRS, &HttpResponseType::parse_admin_cidrs),
+            (
+                &PATH_ADMIN_CIDRS_UPDATE,
+                &HttpResponseType::parse_admin_update,
+            ),
         ];
 
         // use url::Url to parse path and query string
@@ -2777,6 +3637,62 @@ impl HttpResponseType {
         ))
     }
 
+    fn parse_get_contract_events<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let events = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::GetContractEvents(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            events,
+        ))
+    }
+
+    fn parse_admin_peers<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let peers = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::AdminPeers(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            peers,
+        ))
+    }
+
+    fn parse_admin_cidrs<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let cidrs = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::AdminCidrs(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            cidrs,
+        ))
+    }
+
+    fn parse_admin_update<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let update = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::AdminUpdate(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            update,
+        ))
+    }
+
     fn parse_call_read_only<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
@@ -2788,7 +3704,65 @@ impl HttpResponseType {
             HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
         Ok(HttpResponseType::CallReadOnlyFunction(
             HttpResponseMetadata::from_preamble(request_version, preamble),
-            call_data,
+            call_data,
+        ))
+    }
+
+    fn parse_transaction_simulation<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let simulation =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::TransactionSimulation(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            simulation,
+        ))
+    }
+
+    fn parse_call_dry_run<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let dry_run = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::DryRunCallFunction(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            dry_run,
+        ))
+    }
+
+    fn parse_fee_rate_estimate<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let estimate =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::FeeRateEstimate(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            estimate,
+        ))
+    }
+
+    fn parse_marf_proof<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let proof = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::MarfProof(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            proof,
         ))
     }
 
@@ -2852,6 +3826,21 @@ impl HttpResponseType {
         ))
     }
 
+    fn parse_transaction_status<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let tx_status =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::TransactionStatus(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            tx_status,
+        ))
+    }
+
     fn parse_microblock_hash<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
@@ -2919,13 +3908,22 @@ impl HttpResponseType {
             HttpResponseType::Microblocks(ref md, _) => md,
             HttpResponseType::MicroblockStream(ref md) => md,
             HttpResponseType::TransactionID(ref md, _) => md,
+            HttpResponseType::TransactionSimulation(ref md, _) => md,
+            HttpResponseType::TransactionStatus(ref md, _) => md,
             HttpResponseType::MicroblockHash(ref md, _) => md,
             HttpResponseType::TokenTransferCost(ref md, _) => md,
+            HttpResponseType::FeeRateEstimate(ref md, _) => md,
             HttpResponseType::GetMapEntry(ref md, _) => md,
+            HttpResponseType::MarfProof(ref md, _) => md,
             HttpResponseType::GetAccount(ref md, _) => md,
             HttpResponseType::GetContractABI(ref md, _) => md,
+            HttpResponseType::GetContractEvents(ref md, _) => md,
             HttpResponseType::GetContractSrc(ref md, _) => md,
+            HttpResponseType::AdminPeers(ref md, _) => md,
+            HttpResponseType::AdminCidrs(ref md, _) => md,
+            HttpResponseType::AdminUpdate(ref md, _) => md,
             HttpResponseType::CallReadOnlyFunction(ref md, _) => md,
+            HttpResponseType::DryRunCallFunction(ref md, _) => md,
             HttpResponseType::OptionsPreflight(ref md) => md,
             // errors
             HttpResponseType::BadRequestJSON(ref md, _) => md,
@@ -3006,6 +4004,10 @@ impl HttpResponseType {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, account_data)?;
             }
+            HttpResponseType::GetContractEvents(ref md, ref data) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, data)?;
+            }
             HttpResponseType::GetContractABI(ref md, ref data) => {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, data)?;
@@ -3014,18 +4016,46 @@ impl HttpResponseType {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, data)?;
             }
+            HttpResponseType::AdminPeers(ref md, ref data) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, data)?;
+            }
+            HttpResponseType::AdminCidrs(ref md, ref data) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, data)?;
+            }
+            HttpResponseType::AdminUpdate(ref md, ref data) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, data)?;
+            }
             HttpResponseType::TokenTransferCost(ref md, ref cost) => {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, cost)?;
             }
+            HttpResponseType::FeeRateEstimate(ref md, ref estimate) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, estimate)?;
+            }
             HttpResponseType::CallReadOnlyFunction(ref md, ref data) => {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, data)?;
             }
+            HttpResponseType::TransactionSimulation(ref md, ref data) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, data)?;
+            }
+            HttpResponseType::DryRunCallFunction(ref md, ref data) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, data)?;
+            }
             HttpResponseType::GetMapEntry(ref md, ref map_data) => {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, map_data)?;
             }
+            HttpResponseType::MarfProof(ref md, ref proof) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, proof)?;
+            }
             HttpResponseType::PeerInfo(ref md, ref peer_info) => {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, peer_info)?;
@@ -3088,6 +4118,10 @@ impl HttpResponseType {
                     |ref mut fd| keep_alive_headers(fd, md),
                 )?;
             }
+            HttpResponseType::TransactionStatus(ref md, ref tx_status) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, tx_status)?;
+            }
             HttpResponseType::TransactionID(ref md, ref txid) => {
                 let txid_bytes = txid.to_hex();
                 HttpResponsePreamble::new_serialized(
@@ -3214,23 +4248,37 @@ impl MessageSequence for StacksHttpMessage {
                     "HTTP(GetMicroblocksUnconfirmed)"
                 }
                 HttpRequestType::PostTransaction(_, _) => "HTTP(PostTransaction)",
+                HttpRequestType::SimulateTransaction(..) => "HTTP(SimulateTransaction)",
+                HttpRequestType::GetTransaction(..) => "HTTP(GetTransaction)",
                 HttpRequestType::PostMicroblock(..) => "HTTP(PostMicroblock)",
                 HttpRequestType::GetAccount(..) => "HTTP(GetAccount)",
                 HttpRequestType::GetMapEntry(..) => "HTTP(GetMapEntry)",
                 HttpRequestType::GetTransferCost(_) => "HTTP(GetTransferCost)",
+                HttpRequestType::PostFeeRateEstimate(..) => "HTTP(PostFeeRateEstimate)",
+                HttpRequestType::PostMarfProof(..) => "HTTP(PostMarfProof)",
                 HttpRequestType::GetContractABI(..) => "HTTP(GetContractABI)",
+                HttpRequestType::GetContractEvents(..) => "HTTP(GetContractEvents)",
                 HttpRequestType::GetContractSrc(..) => "HTTP(GetContractSrc)",
                 HttpRequestType::CallReadOnlyFunction(..) => "HTTP(CallReadOnlyFunction)",
+                HttpRequestType::DryRunCallFunction(..) => "HTTP(DryRunCallFunction)",
                 HttpRequestType::OptionsPreflight(..) => "HTTP(OptionsPreflight)",
+                HttpRequestType::Admin(..) => "HTTP(Admin)",
                 HttpRequestType::ClientError(..) => "HTTP(ClientError)",
             },
             StacksHttpMessage::Response(ref res) => match res {
                 HttpResponseType::TokenTransferCost(_, _) => "HTTP(TokenTransferCost)",
+                HttpResponseType::FeeRateEstimate(_, _) => "HTTP(FeeRateEstimate)",
                 HttpResponseType::GetMapEntry(_, _) => "HTTP(GetMapEntry)",
+                HttpResponseType::MarfProof(_, _) => "HTTP(MarfProof)",
                 HttpResponseType::GetAccount(_, _) => "HTTP(GetAccount)",
                 HttpResponseType::GetContractABI(..) => "HTTP(GetContractABI)",
+                HttpResponseType::GetContractEvents(..) => "HTTP(GetContractEvents)",
                 HttpResponseType::GetContractSrc(..) => "HTTP(GetContractSrc)",
+                HttpResponseType::AdminPeers(..) => "HTTP(AdminPeers)",
+                HttpResponseType::AdminCidrs(..) => "HTTP(AdminCidrs)",
+                HttpResponseType::AdminUpdate(..) => "HTTP(AdminUpdate)",
                 HttpResponseType::CallReadOnlyFunction(..) => "HTTP(CallReadOnlyFunction)",
+                HttpResponseType::DryRunCallFunction(..) => "HTTP(DryRunCallFunction)",
                 HttpResponseType::PeerInfo(_, _) => "HTTP(PeerInfo)",
                 HttpResponseType::PoxInfo(_, _) => "HTTP(PeerInfo)",
                 HttpResponseType::Neighbors(_, _) => "HTTP(Neighbors)",
@@ -3239,6 +4287,8 @@ impl MessageSequence for StacksHttpMessage {
                 HttpResponseType::Microblocks(_, _) => "HTTP(Microblocks)",
                 HttpResponseType::MicroblockStream(_) => "HTTP(MicroblockStream)",
                 HttpResponseType::TransactionID(_, _) => "HTTP(Transaction)",
+                HttpResponseType::TransactionSimulation(..) => "HTTP(TransactionSimulation)",
+                HttpResponseType::TransactionStatus(_, _) => "HTTP(TransactionStatus)",
                 HttpResponseType::MicroblockHash(_, _) => "HTTP(Microblock)",
                 HttpResponseType::OptionsPreflight(_) => "HTTP(OptionsPreflight)",
                 HttpResponseType::BadRequestJSON(..) | HttpResponseType::BadRequest(..) => {
@@ -3758,6 +4808,7 @@ mod test {
     use net::test::*;
     use net::RPCNeighbor;
     use net::RPCNeighborsInfo;
+    use net::{RPCAdminCidrs, RPCAdminPeer, RPCAdminUpdateResponse};
     use std::error::Error;
 
     use burnchains::Txid;
@@ -3773,6 +4824,8 @@ mod test {
     use chainstate::stacks::TransactionPayload;
     use chainstate::stacks::TransactionPostConditionMode;
     use chainstate::stacks::TransactionVersion;
+    use chainstate::stacks::{FungibleConditionCode, PostConditionPrincipal};
+    use vm::types::StandardPrincipalData;
 
     use chainstate::stacks::StacksPrivateKey;
 
@@ -5712,6 +6765,545 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_http_fee_rate_estimate_roundtrip() {
+        let payload = TransactionPayload::new_contract_call(
+            StacksAddress {
+                version: 1,
+                bytes: Hash160([0xff; 20]),
+            },
+            "hello-world",
+            "add-unit",
+            vec![Value::Int(1)],
+        )
+        .unwrap();
+
+        let tests = vec![
+            HttpRequestType::PostFeeRateEstimate(
+                HttpRequestMetadata::from_host(PeerHost::DNS("localhost".to_string(), 20443)),
+                payload.clone(),
+                None,
+                None,
+                None,
+            ),
+            HttpRequestType::PostFeeRateEstimate(
+                HttpRequestMetadata::from_host(PeerHost::DNS("localhost".to_string(), 20443)),
+                payload.clone(),
+                Some(PrincipalData::Standard(StandardPrincipalData(
+                    26, [0x22; 20],
+                ))),
+                Some(180),
+                Some(StacksBlockId([0x11; 32])),
+            ),
+        ];
+
+        for test in tests {
+            let mut bytes = vec![];
+            let mut http = StacksHttp::new();
+            http.write_message(&mut bytes, &StacksHttpMessage::Request(test.clone()))
+                .unwrap();
+
+            let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+            let (message, _) = http.read_payload(&preamble, &bytes[offset..]).unwrap();
+            match message {
+                StacksHttpMessage::Request(req) => assert_eq!(req, test),
+                _ => panic!("Did not parse a request"),
+            }
+        }
+
+        // payload must be valid hex
+        let bad_payload = "{\"transaction_payload\":\"0xzz\"}";
+        let bad_request = format!("POST /v2/fees/transaction HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", bad_payload.len(), bad_payload);
+        let mut http = StacksHttp::new();
+        let (preamble, offset) = http.read_preamble(bad_request.as_bytes()).unwrap();
+        assert!(http
+            .read_payload(&preamble, &bad_request.as_bytes()[offset..])
+            .is_err());
+
+        // sender must be a principal
+        let mut payload_bytes = vec![];
+        payload.consensus_serialize(&mut payload_bytes).unwrap();
+        let bad_sender = format!(
+            "{{\"transaction_payload\":\"0x{}\",\"sender\":\"not-a-principal\"}}",
+            to_hex(&payload_bytes)
+        );
+        let bad_request = format!("POST /v2/fees/transaction HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", bad_sender.len(), bad_sender);
+        let mut http = StacksHttp::new();
+        let (preamble, offset) = http.read_preamble(bad_request.as_bytes()).unwrap();
+        assert!(http
+            .read_payload(&preamble, &bad_request.as_bytes()[offset..])
+            .is_err());
+    }
+
+    #[test]
+    fn test_http_marf_proof_roundtrip() {
+        let tests = vec![
+            HttpRequestType::PostMarfProof(
+                HttpRequestMetadata::from_host(PeerHost::DNS("localhost".to_string(), 20443)),
+                vec!["vm::ST000000000000000000002AMW42H::balance".to_string()],
+                None,
+            ),
+            HttpRequestType::PostMarfProof(
+                HttpRequestMetadata::from_host(PeerHost::DNS("localhost".to_string(), 20443)),
+                vec!["key-1".to_string(), "key-2".to_string()],
+                Some(StacksBlockId([0x11; 32])),
+            ),
+        ];
+
+        for test in tests {
+            let mut bytes = vec![];
+            let mut http = StacksHttp::new();
+            http.write_message(&mut bytes, &StacksHttpMessage::Request(test.clone()))
+                .unwrap();
+
+            let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+            let (message, _) = http.read_payload(&preamble, &bytes[offset..]).unwrap();
+            match message {
+                StacksHttpMessage::Request(req) => assert_eq!(req, test),
+                _ => panic!("Did not parse a request"),
+            }
+        }
+
+        // at least one key, and not too many
+        let too_many_keys: Vec<String> = (0..MAX_MARF_PROOF_KEYS + 1)
+            .map(|i| format!("key-{}", i))
+            .collect();
+        for keys in vec![vec![], too_many_keys] {
+            let bad_payload = serde_json::to_string(&MarfProofRequestBody { keys }).unwrap();
+            let bad_request = format!("POST /v2/proofs HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", bad_payload.len(), bad_payload);
+            let mut http = StacksHttp::new();
+            let (preamble, offset) = http.read_preamble(bad_request.as_bytes()).unwrap();
+            assert!(http
+                .read_payload(&preamble, &bad_request.as_bytes()[offset..])
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_http_get_transaction_roundtrip() {
+        let tests = vec![
+            HttpRequestType::GetTransaction(
+                HttpRequestMetadata::from_host(PeerHost::DNS("localhost".to_string(), 20443)),
+                Txid([0x22; 32]),
+                None,
+            ),
+            HttpRequestType::GetTransaction(
+                HttpRequestMetadata::from_host(PeerHost::DNS("localhost".to_string(), 20443)),
+                Txid([0x22; 32]),
+                Some(StacksBlockId([0x11; 32])),
+            ),
+        ];
+
+        for test in tests {
+            let mut bytes = vec![];
+            let mut http = StacksHttp::new();
+            http.write_message(&mut bytes, &StacksHttpMessage::Request(test.clone()))
+                .unwrap();
+
+            let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+            let (message, _) = http.read_payload(&preamble, &bytes[offset..]).unwrap();
+            match message {
+                StacksHttpMessage::Request(req) => assert_eq!(req, test),
+                _ => panic!("Did not parse a request"),
+            }
+        }
+
+        // a 0x-prefixed txid is accepted too
+        let request = format!("GET /v2/transactions/0x{} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\n\r\n", Txid([0x22; 32]).to_hex());
+        let mut http = StacksHttp::new();
+        let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(HttpRequestType::GetTransaction(_, txid, tip_opt)) => {
+                assert_eq!(txid, Txid([0x22; 32]));
+                assert_eq!(tip_opt, None);
+            }
+            _ => panic!("Did not parse a GetTransaction request"),
+        }
+    }
+
+    #[test]
+    fn test_http_admin_roundtrip() {
+        let md = HttpRequestMetadata::from_host(PeerHost::DNS("localhost".to_string(), 20443));
+        let token = Some("hunter2".to_string());
+        let tests = vec![
+            HttpRequestType::Admin(md.clone(), token.clone(), AdminRequest::GetPeers),
+            HttpRequestType::Admin(md.clone(), None, AdminRequest::GetCidrs),
+            HttpRequestType::Admin(
+                md.clone(),
+                token.clone(),
+                AdminRequest::BanPeer(PeerAddress::from_ipv4(10, 0, 0, 5), 20444, Some(3600)),
+            ),
+            HttpRequestType::Admin(
+                md.clone(),
+                token.clone(),
+                AdminRequest::UnbanPeer(PeerAddress::from_ipv4(10, 0, 0, 5), 20444),
+            ),
+            HttpRequestType::Admin(
+                md.clone(),
+                token.clone(),
+                AdminRequest::AddCidr(
+                    AdminCidrList::Deny,
+                    PeerAddress::from_ipv4(192, 168, 1, 0),
+                    120,
+                ),
+            ),
+            HttpRequestType::Admin(
+                md.clone(),
+                token.clone(),
+                AdminRequest::RemoveCidr(
+                    AdminCidrList::Allow,
+                    PeerAddress::from_ip(&"fd00::".parse().unwrap()),
+                    8,
+                ),
+            ),
+        ];
+
+        for test in tests {
+            let mut bytes = vec![];
+            let mut http = StacksHttp::new();
+            http.write_message(&mut bytes, &StacksHttpMessage::Request(test.clone()))
+                .unwrap();
+
+            let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+            let (message, _) = http.read_payload(&preamble, &bytes[offset..]).unwrap();
+            match message {
+                StacksHttpMessage::Request(req) => assert_eq!(req, test),
+                _ => panic!("Did not parse a request"),
+            }
+        }
+
+        // only bearer tokens are accepted
+        let request = "GET /v2/admin/peers HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\nAuthorization: Basic aHVudGVyMg==\r\n\r\n";
+        let mut http = StacksHttp::new();
+        let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(HttpRequestType::Admin(_, token_opt, _)) => {
+                assert_eq!(token_opt, None);
+            }
+            _ => panic!("Did not parse an Admin request"),
+        }
+
+        // CIDRs must be well-formed
+        for bad_cidr in &["10.0.0.0", "10.0.0.0/33", "::/0", "foo/8"] {
+            let body = format!("{{\"cidr\":\"{}\"}}", bad_cidr);
+            let request = format!("POST /v2/admin/cidrs/deny/add HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+            let mut http = StacksHttp::new();
+            let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+            let (message, _) = http
+                .read_payload(&preamble, &request.as_bytes()[offset..])
+                .unwrap();
+            match message {
+                StacksHttpMessage::Request(HttpRequestType::ClientError(
+                    _,
+                    ClientError::Message(_),
+                )) => {}
+                _ => panic!("Accepted bad CIDR {}", bad_cidr),
+            }
+        }
+
+        // ban durations are bounded
+        for (duration, accepted) in &[
+            (MAX_ADMIN_BAN_DURATION, true),
+            (MAX_ADMIN_BAN_DURATION + 1, false),
+            (u64::max_value(), false),
+        ] {
+            let body = format!(
+                "{{\"ip\":\"10.0.0.5\",\"port\":20444,\"duration\":{}}}",
+                duration
+            );
+            let request = format!("POST /v2/admin/peers/ban HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+            let mut http = StacksHttp::new();
+            let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+            let (message, _) = http
+                .read_payload(&preamble, &request.as_bytes()[offset..])
+                .unwrap();
+            match message {
+                StacksHttpMessage::Request(HttpRequestType::Admin(
+                    _,
+                    _,
+                    AdminRequest::BanPeer(_, _, Some(parsed_duration)),
+                )) if *accepted => assert_eq!(parsed_duration, *duration),
+                StacksHttpMessage::Request(HttpRequestType::ClientError(
+                    _,
+                    ClientError::Message(_),
+                )) if !*accepted => {}
+                _ => panic!("Mishandled ban duration {}", duration),
+            }
+        }
+
+        // responses
+        let responses = vec![
+            (
+                HttpResponseType::AdminPeers(
+                    HttpResponseMetadata::new(HttpVersion::Http11, 123, None, true),
+                    vec![RPCAdminPeer {
+                        event_id: 5,
+                        network_id: 1,
+                        peer_version: 2,
+                        addrbytes: PeerAddress::from_ipv4(10, 0, 0, 5),
+                        port: 20444,
+                        public_key_hash: Some(Hash160([0x11; 20])),
+                        authenticated: true,
+                        outbound: false,
+                        first_contact_time: 1,
+                        last_contact_time: 2,
+                        last_send_time: 3,
+                        last_recv_time: 4,
+                        last_handshake_time: 5,
+                        bytes_tx: 6,
+                        bytes_rx: 7,
+                        msgs_tx: 8,
+                        msgs_rx: 9,
+                        msgs_rx_unsolicited: 10,
+                        msgs_err: 11,
+                        health_score: 0.5,
+                    }],
+                ),
+                "/v2/admin/peers",
+            ),
+            (
+                HttpResponseType::AdminCidrs(
+                    HttpResponseMetadata::new(HttpVersion::Http11, 123, None, true),
+                    RPCAdminCidrs {
+                        allow: vec!["10.0.0.0/8".to_string()],
+                        deny: vec!["fd00::/8".to_string()],
+                    },
+                ),
+                "/v2/admin/cidrs",
+            ),
+            (
+                HttpResponseType::AdminUpdate(
+                    HttpResponseMetadata::new(HttpVersion::Http11, 123, None, true),
+                    RPCAdminUpdateResponse { disconnected: 2 },
+                ),
+                "/v2/admin/cidrs/deny/add",
+            ),
+            (
+                HttpResponseType::AdminUpdate(
+                    HttpResponseMetadata::new(HttpVersion::Http11, 123, None, true),
+                    RPCAdminUpdateResponse { disconnected: 0 },
+                ),
+                "/v2/admin/peers/unban",
+            ),
+        ];
+
+        for (test, request_path) in responses {
+            let mut http = StacksHttp::new();
+            let mut bytes = vec![];
+            http.begin_request(HttpVersion::Http11, request_path.to_string());
+            http.write_message(&mut bytes, &StacksHttpMessage::Response(test.clone()))
+                .unwrap();
+
+            let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+            let (msg_opt, _) = http
+                .stream_payload(&preamble, &mut &bytes[offset..])
+                .unwrap();
+            assert_eq!(msg_opt.unwrap().0, StacksHttpMessage::Response(test));
+        }
+    }
+
+    #[test]
+    fn test_cidr_strings() {
+        assert_eq!(
+            PeerAddress::from_cidr_str("192.168.1.0/24"),
+            Some((PeerAddress::from_ipv4(192, 168, 1, 0), 120))
+        );
+        assert_eq!(
+            PeerAddress::from_cidr_str("0.0.0.0/0"),
+            Some((PeerAddress::from_ipv4(0, 0, 0, 0), 96))
+        );
+        assert_eq!(
+            PeerAddress::from_cidr_str("fd00::/8"),
+            Some((PeerAddress::from_ip(&"fd00::".parse().unwrap()), 8))
+        );
+        assert_eq!(PeerAddress::from_cidr_str("::/0"), None);
+        assert_eq!(PeerAddress::from_cidr_str("1.2.3.4/33"), None);
+        assert_eq!(PeerAddress::from_cidr_str("1.2.3.4"), None);
+
+        for cidr in &["192.168.1.0/24", "0.0.0.0/0", "fd00::/8", "::ffff:0:0/96"] {
+            let (prefix, mask) = PeerAddress::from_cidr_str(cidr).unwrap();
+            assert_eq!(
+                PeerAddress::from_cidr_str(&prefix.to_cidr_string(mask)),
+                Some((prefix, mask))
+            );
+        }
+    }
+
+    #[test]
+    fn test_http_call_dry_run_roundtrip() {
+        let contract_addr = StacksAddress {
+            version: 1,
+            bytes: Hash160([0xff; 20]),
+        };
+        let sender = PrincipalData::Standard(StandardPrincipalData(2, [0x22; 20]));
+        let post_condition = TransactionPostCondition::STX(
+            PostConditionPrincipal::Origin,
+            FungibleConditionCode::SentLe,
+            100,
+        );
+
+        let tests = vec![
+            HttpRequestType::DryRunCallFunction(
+                HttpRequestMetadata::from_host(PeerHost::DNS("localhost".to_string(), 20443)),
+                contract_addr.clone(),
+                "hello-world".into(),
+                sender.clone(),
+                "add-unit".into(),
+                vec![Value::Int(1), Value::UInt(2)],
+                vec![],
+                TransactionPostConditionMode::Allow,
+                None,
+            ),
+            HttpRequestType::DryRunCallFunction(
+                HttpRequestMetadata::from_host(PeerHost::DNS("localhost".to_string(), 20443)),
+                contract_addr.clone(),
+                "hello-world".into(),
+                sender.clone(),
+                "add-unit".into(),
+                vec![],
+                vec![post_condition],
+                TransactionPostConditionMode::Deny,
+                Some(StacksBlockId([0x11; 32])),
+            ),
+        ];
+
+        for test in tests {
+            let mut bytes = vec![];
+            let mut http = StacksHttp::new();
+            http.write_message(&mut bytes, &StacksHttpMessage::Request(test.clone()))
+                .unwrap();
+
+            let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+            let (message, _) = http.read_payload(&preamble, &bytes[offset..]).unwrap();
+            match message {
+                StacksHttpMessage::Request(req) => assert_eq!(req, test),
+                _ => panic!("Did not parse a request"),
+            }
+        }
+
+        // the post-condition mode must be "allow" or "deny"
+        let bad_payload = format!(
+            "{{\"sender\":\"{}\",\"arguments\":[],\"post_condition_mode\":\"maybe\"}}",
+            &sender
+        );
+        let bad_request = format!("POST /v2/contracts/call-dry-run/{}/hello-world/add-unit HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", &contract_addr, bad_payload.len(), bad_payload);
+        let mut http = StacksHttp::new();
+        let (preamble, offset) = http.read_preamble(bad_request.as_bytes()).unwrap();
+        assert!(http
+            .read_payload(&preamble, &bad_request.as_bytes()[offset..])
+            .is_err());
+    }
+
+    #[test]
+    fn test_http_simulate_transaction_roundtrip() {
+        let tests = vec![
+            HttpRequestType::SimulateTransaction(
+                HttpRequestMetadata::from_host(PeerHost::DNS("localhost".to_string(), 20443)),
+                make_test_transaction(),
+                None,
+            ),
+            HttpRequestType::SimulateTransaction(
+                HttpRequestMetadata::from_host(PeerHost::DNS("localhost".to_string(), 20443)),
+                make_test_transaction(),
+                Some(StacksBlockId([0x11; 32])),
+            ),
+        ];
+
+        for test in tests {
+            let mut bytes = vec![];
+            let mut http = StacksHttp::new();
+            http.write_message(&mut bytes, &StacksHttpMessage::Request(test.clone()))
+                .unwrap();
+
+            let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+            let (message, _) = http.read_payload(&preamble, &bytes[offset..]).unwrap();
+            match message {
+                StacksHttpMessage::Request(req) => assert_eq!(req, test),
+                _ => panic!("Did not parse a request"),
+            }
+        }
+
+        // the transaction must be sent as application/octet-stream
+        let mut tx_body = vec![];
+        make_test_transaction()
+            .consensus_serialize(&mut tx_body)
+            .unwrap();
+        let mut bad_request = format!("POST /v2/transactions/simulate HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n", tx_body.len()).into_bytes();
+        bad_request.extend_from_slice(&tx_body);
+        let mut http = StacksHttp::new();
+        let (preamble, offset) = http.read_preamble(&bad_request).unwrap();
+        assert!(http
+            .read_payload(&preamble, &bad_request[offset..])
+            .is_err());
+    }
+
+    #[test]
+    fn test_http_get_contract_events_roundtrip() {
+        let contract_addr = StacksAddress {
+            version: 1,
+            bytes: Hash160([0xff; 20]),
+        };
+
+        let tests = vec![
+            HttpRequestType::GetContractEvents(
+                HttpRequestMetadata::from_host(PeerHost::DNS("localhost".to_string(), 20443)),
+                contract_addr.clone(),
+                "hello-world".into(),
+                ContractEventsQuery {
+                    topic: None,
+                    from_height: None,
+                    to_height: None,
+                    offset: 0,
+                    limit: MAX_CONTRACT_EVENTS_PAGE_SIZE,
+                },
+                None,
+            ),
+            HttpRequestType::GetContractEvents(
+                HttpRequestMetadata::from_host(PeerHost::DNS("localhost".to_string(), 20443)),
+                contract_addr.clone(),
+                "hello-world".into(),
+                ContractEventsQuery {
+                    topic: Some("print me & you".to_string()),
+                    from_height: Some(10),
+                    to_height: Some(20),
+                    offset: 30,
+                    limit: 5,
+                },
+                Some(StacksBlockId([0x11; 32])),
+            ),
+        ];
+
+        for test in tests {
+            let mut bytes = vec![];
+            let mut http = StacksHttp::new();
+            http.write_message(&mut bytes, &StacksHttpMessage::Request(test.clone()))
+                .unwrap();
+
+            let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+            let (message, _) = http.read_payload(&preamble, &bytes[offset..]).unwrap();
+            match message {
+                StacksHttpMessage::Request(req) => assert_eq!(req, test),
+                _ => panic!("Did not parse a request"),
+            }
+        }
+
+        // pages are bounded
+        for bad_query in &["limit=0", "limit=101", "offset=-1", "from_height=abc"] {
+            let bad_request = format!("GET /v2/contracts/events/{}/hello-world?{} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\n\r\n", &contract_addr, bad_query);
+            let mut http = StacksHttp::new();
+            let (preamble, offset) = http.read_preamble(bad_request.as_bytes()).unwrap();
+            assert!(http
+                .read_payload(&preamble, &bad_request.as_bytes()[offset..])
+                .is_err());
+        }
+    }
+
     #[test]
     fn test_http_live_headers() {
         // headers pulled from prod
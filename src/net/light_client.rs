@@ -0,0 +1,890 @@
+// Copyright (C) 2013-2020 Blocstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+/// This module contains the logic for checking the MARF proofs in RPC responses, for clients
+/// that trust a chain of burnchain headers but not the node they are talking to.
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use burnchains::BurnchainBlockHeader;
+use burnchains::BurnchainHeaderHash;
+use chainstate::burn::{BlockHeaderHash, ConsensusHash};
+use chainstate::stacks::db::StacksHeaderInfo;
+use chainstate::stacks::index::node::TriePath;
+use chainstate::stacks::index::proofs::{
+    TrieMerkleMultiProof, TrieMerkleProof, TrieMerkleProofType,
+};
+use chainstate::stacks::index::{MARFValue, TrieHash};
+use chainstate::stacks::{StacksBlockHeader, StacksBlockId};
+use net::codec::read_next;
+use net::{AccountEntryResponse, ContractSrcResponse, MapEntryResponse, MarfProofResponse};
+use util::hash::{hex_bytes, Sha512Trunc256Sum};
+use vm::database::marf::ContractCommitment;
+use vm::database::{ClarityDatabase, ClaritySerializable, MarfedKV, STXBalance};
+use vm::types::{PrincipalData, QualifiedContractIdentifier};
+use vm::{ClarityName, Value};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// The Stacks header chain is not linked, or does not match the trusted burnchain headers
+    InvalidHeaderChain(String),
+    /// The response could not be decoded
+    MalformedResponse(String),
+    /// The response does not carry a proof for a value
+    MissingProof(String),
+    /// The proof does not prove the value as of the trusted chain tip
+    InvalidProof(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidHeaderChain(ref s) => write!(f, "Invalid header chain: {}", s),
+            Error::MalformedResponse(ref s) => write!(f, "Malformed response: {}", s),
+            Error::MissingProof(ref s) => write!(f, "Missing proof: {}", s),
+            Error::InvalidProof(ref s) => write!(f, "Invalid proof: {}", s),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn cause(&self) -> Option<&dyn error::Error> {
+        None
+    }
+}
+
+/// A burnchain header, as it appears in a header chain file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LightClientBurnHeader {
+    pub block_height: u64,
+    pub block_hash: String,
+    pub parent_block_hash: String,
+}
+
+/// An anchored Stacks block header and the sortition that chose it, as it appears in a header
+/// chain file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LightClientStacksHeader {
+    pub consensus_hash: String,
+    pub burn_header_hash: String,
+    pub burn_header_height: u32,
+    /// hex-encoded, consensus-serialized anchored block header
+    pub anchored_header: String,
+}
+
+/// A header chain file: the burnchain headers the client trusts, and the Stacks block headers
+/// from some ancestor up to the chain tip that proofs are made at.  Both are ordered from
+/// oldest to newest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LightClientHeaderChain {
+    pub burn_headers: Vec<LightClientBurnHeader>,
+    pub stacks_headers: Vec<LightClientStacksHeader>,
+}
+
+/// Checks MARF proofs against the state root of a trusted chain tip.
+pub struct ProofVerifier {
+    tip: StacksBlockId,
+    state_root: TrieHash,
+    root_to_block: HashMap<TrieHash, StacksBlockId>,
+}
+
+fn decode_hex(field: &str, hex: &str) -> Result<Vec<u8>, Error> {
+    let hex = if hex.starts_with("0x") {
+        &hex[2..]
+    } else {
+        hex
+    };
+    hex_bytes(hex).map_err(|_e| Error::MalformedResponse(format!("Invalid hex in {}", field)))
+}
+
+impl ProofVerifier {
+    /// Make a verifier for the tip of the given Stacks header chain.  The chain must be linked by
+    /// parent block hashes, each block must have been chosen in a burnchain block from the
+    /// trusted burnchain header chain, and the tip must be the block with the trusted block hash
+    /// (i.e. the winning block-commit's `block_header_hash`) chosen in the sortition with the
+    /// trusted consensus hash.
+    ///
+    /// Proofs pass through the tries of the blocks where their values were last written, so the
+    /// header chain needs to reach back at least that far.  The genesis header does not carry
+    /// the boot code's state root; pass it in the genesis header's `state_index_root` to check
+    /// values that have not changed since genesis.
+    pub fn new(
+        burn_headers: &[BurnchainBlockHeader],
+        stacks_headers: &[StacksHeaderInfo],
+        consensus_hash: &ConsensusHash,
+        block_hash: &BlockHeaderHash,
+    ) -> Result<ProofVerifier, Error> {
+        let mut burn_hashes: HashMap<u64, BurnchainHeaderHash> = HashMap::new();
+        for (i, burn_header) in burn_headers.iter().enumerate() {
+            if i > 0 {
+                let parent = &burn_headers[i - 1];
+                if burn_header.parent_block_hash != parent.block_hash
+                    || burn_header.block_height != parent.block_height + 1
+                {
+                    return Err(Error::InvalidHeaderChain(format!(
+                        "Burnchain block {} at {} does not build on {}",
+                        &burn_header.block_hash, burn_header.block_height, &parent.block_hash
+                    )));
+                }
+            }
+            burn_hashes.insert(burn_header.block_height, burn_header.block_hash.clone());
+        }
+
+        let tip_info = stacks_headers
+            .last()
+            .ok_or_else(|| Error::InvalidHeaderChain("No Stacks block headers".into()))?;
+        if tip_info.consensus_hash != *consensus_hash {
+            return Err(Error::InvalidHeaderChain(format!(
+                "Chain tip was chosen in sortition {}, not {}",
+                &tip_info.consensus_hash, consensus_hash
+            )));
+        }
+        if tip_info.anchored_header.block_hash() != *block_hash {
+            return Err(Error::InvalidHeaderChain(format!(
+                "Chain tip is block {}, not {}",
+                &tip_info.anchored_header.block_hash(),
+                block_hash
+            )));
+        }
+
+        let mut root_to_block = HashMap::new();
+        for (i, header_info) in stacks_headers.iter().enumerate() {
+            let header = &header_info.anchored_header;
+            let is_genesis = header.total_work.work == 0;
+
+            if i > 0 {
+                let parent_info = &stacks_headers[i - 1];
+                let parent = &parent_info.anchored_header;
+                if header.parent_block != parent.block_hash()
+                    || header.total_work.work != parent.total_work.work + 1
+                    || header_info.burn_header_height <= parent_info.burn_header_height
+                {
+                    return Err(Error::InvalidHeaderChain(format!(
+                        "Stacks block {}/{} does not build on {}/{}",
+                        &header_info.consensus_hash,
+                        &header.block_hash(),
+                        &parent_info.consensus_hash,
+                        &parent.block_hash()
+                    )));
+                }
+            }
+
+            // the boot code's block was not chosen in a sortition
+            if !is_genesis {
+                match burn_hashes.get(&(header_info.burn_header_height as u64)) {
+                    Some(burn_hash) if *burn_hash == header_info.burn_header_hash => {}
+                    _ => {
+                        return Err(Error::InvalidHeaderChain(format!(
+                            "Stacks block {}/{} was not chosen in trusted burnchain block {} at {}",
+                            &header_info.consensus_hash,
+                            &header.block_hash(),
+                            &header_info.burn_header_hash,
+                            header_info.burn_header_height
+                        )));
+                    }
+                }
+            }
+
+            if header.state_index_root != TrieHash([0u8; 32]) {
+                root_to_block.insert(
+                    header.state_index_root.clone(),
+                    header_info.index_block_hash(),
+                );
+            }
+        }
+
+        Ok(ProofVerifier {
+            tip: tip_info.index_block_hash(),
+            state_root: tip_info.anchored_header.state_index_root.clone(),
+            root_to_block,
+        })
+    }
+
+    /// Make a verifier from a JSON-encoded `LightClientHeaderChain`
+    pub fn from_json(
+        json: &str,
+        consensus_hash: &ConsensusHash,
+        block_hash: &BlockHeaderHash,
+    ) -> Result<ProofVerifier, Error> {
+        let chain: LightClientHeaderChain = serde_json::from_str(json).map_err(|e| {
+            Error::InvalidHeaderChain(format!("Failed to parse header chain: {:?}", &e))
+        })?;
+
+        let mut burn_headers = Vec::with_capacity(chain.burn_headers.len());
+        for burn_header in chain.burn_headers.iter() {
+            let block_hash = BurnchainHeaderHash::from_hex(&burn_header.block_hash)
+                .map_err(|_e| Error::InvalidHeaderChain("Invalid burnchain block hash".into()))?;
+            let parent_block_hash = BurnchainHeaderHash::from_hex(&burn_header.parent_block_hash)
+                .map_err(|_e| {
+                Error::InvalidHeaderChain("Invalid burnchain parent block hash".into())
+            })?;
+            burn_headers.push(BurnchainBlockHeader {
+                block_height: burn_header.block_height,
+                block_hash,
+                parent_block_hash,
+                num_txs: 0,
+                timestamp: 0,
+            });
+        }
+
+        let mut stacks_headers = Vec::with_capacity(chain.stacks_headers.len());
+        for stacks_header in chain.stacks_headers.iter() {
+            let header_consensus_hash = ConsensusHash::from_hex(&stacks_header.consensus_hash)
+                .map_err(|_e| Error::InvalidHeaderChain("Invalid consensus hash".into()))?;
+            let burn_header_hash = BurnchainHeaderHash::from_hex(&stacks_header.burn_header_hash)
+                .map_err(|_e| {
+                Error::InvalidHeaderChain("Invalid burnchain header hash".into())
+            })?;
+            let header_bytes = decode_hex("anchored_header", &stacks_header.anchored_header)
+                .map_err(|_e| Error::InvalidHeaderChain("Invalid anchored header hex".into()))?;
+            let anchored_header: StacksBlockHeader = read_next(&mut &header_bytes[..])
+                .map_err(|_e| Error::InvalidHeaderChain("Invalid anchored header".into()))?;
+
+            // only the anchored header and the sortition it was chosen in are needed
+            stacks_headers.push(StacksHeaderInfo {
+                block_height: anchored_header.total_work.work,
+                anchored_header,
+                microblock_tail: None,
+                index_root: TrieHash([0u8; 32]),
+                consensus_hash: header_consensus_hash,
+                burn_header_hash,
+                burn_header_height: stacks_header.burn_header_height,
+                burn_header_timestamp: 0,
+                total_liquid_ustx: 0,
+            });
+        }
+
+        ProofVerifier::new(&burn_headers, &stacks_headers, consensus_hash, block_hash)
+    }
+
+    /// The index block hash of the trusted chain tip
+    pub fn tip(&self) -> &StacksBlockId {
+        &self.tip
+    }
+
+    /// The state root of the trusted chain tip
+    pub fn state_root(&self) -> &TrieHash {
+        &self.state_root
+    }
+
+    fn decode_proof(proof_hex: &str) -> Result<TrieMerkleProof<StacksBlockId>, Error> {
+        let proof_bytes = decode_hex("proof", proof_hex)?;
+        let proof: Vec<TrieMerkleProofType<StacksBlockId>> = read_next(&mut &proof_bytes[..])
+            .map_err(|_e| Error::MalformedResponse("Failed to decode MARF proof".into()))?;
+        Ok(TrieMerkleProof(proof))
+    }
+
+    /// Check a proof that `key` maps to the Clarity-serialized `value` at the chain tip.
+    pub fn verify_value(&self, key: &str, value: &str, proof_hex: &str) -> Result<(), Error> {
+        if proof_hex.len() == 0 {
+            return Err(Error::MissingProof(format!("No proof for {}", key)));
+        }
+        let proof = ProofVerifier::decode_proof(proof_hex)?;
+        if proof.len() == 0 {
+            // the node could not make a proof, e.g. because it pruned the tries it passes through
+            return Err(Error::MissingProof(format!("Empty proof for {}", key)));
+        }
+        let marf_value = MARFValue::from_value(value);
+        if !proof.verify(
+            &TriePath::from_key(key),
+            &marf_value,
+            &self.state_root,
+            &self.root_to_block,
+        ) {
+            return Err(Error::InvalidProof(format!(
+                "Proof of {} does not match state root {}",
+                key, &self.state_root
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check the proofs in a `GET /v2/accounts` response.  The stored balance is rebuilt from the
+    /// reported balance, locked amount and unlock height, so a balance whose lock has expired
+    /// but has not been consolidated yet will not verify.
+    pub fn verify_account(
+        &self,
+        principal: &PrincipalData,
+        response: &AccountEntryResponse,
+    ) -> Result<(), Error> {
+        let parse_amount = |field: &str, amount_hex: &str| -> Result<u128, Error> {
+            let amount_bytes = decode_hex(field, amount_hex)?;
+            let amount_bytes = <[u8; 16]>::try_from(&amount_bytes[..]).map_err(|_e| {
+                Error::MalformedResponse(format!("{} is not a 16-byte amount", field))
+            })?;
+            Ok(u128::from_be_bytes(amount_bytes))
+        };
+
+        let balance = STXBalance {
+            amount_unlocked: parse_amount("balance", &response.balance)?,
+            amount_locked: parse_amount("locked", &response.locked)?,
+            unlock_height: response.unlock_height,
+        };
+        let balance_proof = response
+            .balance_proof
+            .as_ref()
+            .ok_or_else(|| Error::MissingProof("No balance proof".into()))?;
+        self.verify_value(
+            &ClarityDatabase::make_key_for_account_balance(principal),
+            &ClaritySerializable::serialize(&balance),
+            balance_proof,
+        )?;
+
+        let nonce_proof = response
+            .nonce_proof
+            .as_ref()
+            .ok_or_else(|| Error::MissingProof("No nonce proof".into()))?;
+        self.verify_value(
+            &ClarityDatabase::make_key_for_account_nonce(principal),
+            &ClaritySerializable::serialize(&response.nonce),
+            nonce_proof,
+        )
+    }
+
+    /// Check the proof in a `POST /v2/map_entry` response.  Only entries that are present can be
+    /// proven.
+    pub fn verify_map_entry(
+        &self,
+        contract_identifier: &QualifiedContractIdentifier,
+        map_name: &ClarityName,
+        key: &Value,
+        response: &MapEntryResponse,
+    ) -> Result<(), Error> {
+        let data = if response.data.starts_with("0x") {
+            &response.data[2..]
+        } else {
+            &response.data
+        };
+        let value = Value::try_deserialize_hex_untyped(data)
+            .map_err(|_e| Error::MalformedResponse("Failed to decode map entry".into()))?;
+        if value == Value::none() {
+            return Err(Error::MissingProof(
+                "Absent map entries cannot be proven".into(),
+            ));
+        }
+        let proof = response
+            .marf_proof
+            .as_ref()
+            .ok_or_else(|| Error::MissingProof("No map entry proof".into()))?;
+        self.verify_value(
+            &ClarityDatabase::make_key_for_data_map_entry(contract_identifier, map_name, key),
+            data,
+            proof,
+        )
+    }
+
+    /// Check the proof in a `GET /v2/contracts/source` response.  The proof is of the hash of
+    /// the source and the block height it was published at.
+    pub fn verify_contract_src(
+        &self,
+        contract_identifier: &QualifiedContractIdentifier,
+        response: &ContractSrcResponse,
+    ) -> Result<(), Error> {
+        let commitment = ContractCommitment {
+            hash: Sha512Trunc256Sum::from_data(response.source.as_bytes()),
+            block_height: response.publish_height,
+        };
+        let proof = response
+            .marf_proof
+            .as_ref()
+            .ok_or_else(|| Error::MissingProof("No contract source proof".into()))?;
+        self.verify_value(
+            &MarfedKV::make_contract_hash_key(contract_identifier),
+            &commitment.serialize(),
+            proof,
+        )
+    }
+
+    /// Check a `POST /v2/proofs` response for the given keys.  Returns the keys' values, which
+    /// are None for keys the node says are absent -- absence is not proven.
+    pub fn verify_marf_proof(
+        &self,
+        keys: &[String],
+        response: &MarfProofResponse,
+    ) -> Result<Vec<Option<String>>, Error> {
+        if keys.len() != response.data.len() {
+            return Err(Error::MalformedResponse(format!(
+                "Expected {} values, got {}",
+                keys.len(),
+                response.data.len()
+            )));
+        }
+        let proof_bytes = decode_hex("proof", &response.proof)?;
+        let proof: TrieMerkleMultiProof<StacksBlockId> = read_next(&mut &proof_bytes[..])
+            .map_err(|_e| Error::MalformedResponse("Failed to decode MARF proof".into()))?;
+
+        let entries: Vec<(String, MARFValue)> = keys
+            .iter()
+            .zip(response.data.iter())
+            .filter_map(|(key, data)| {
+                data.as_ref()
+                    .map(|value| (key.clone(), MARFValue::from_value(value)))
+            })
+            .collect();
+        if entries.len() > 0 && proof.key_proofs.len() == 0 {
+            return Err(Error::MissingProof(format!(
+                "Empty proof for {} keys",
+                entries.len()
+            )));
+        }
+        if !proof.verify(&entries, &self.state_root, &self.root_to_block) {
+            return Err(Error::InvalidProof(format!(
+                "Proof of {} keys does not match state root {}",
+                entries.len(),
+                &self.state_root
+            )));
+        }
+        Ok(response.data.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chainstate::burn::BlockHeaderHash;
+    use chainstate::stacks::index::marf::MARF;
+    use chainstate::stacks::index::MarfTrieId;
+    use chainstate::stacks::StacksWorkScore;
+    use core::{
+        EMPTY_MICROBLOCK_PARENT_HASH, FIRST_BURNCHAIN_CONSENSUS_HASH, FIRST_STACKS_BLOCK_HASH,
+    };
+    use net::StacksMessageCodec;
+    use util::hash::{to_hex, Hash160};
+    use util::vrf::VRFProof;
+    use vm::types::TupleData;
+
+    /// Commit a block of key/value pairs on top of `parent`, and make its header info
+    fn commit_block(
+        marf: &mut MARF<StacksBlockId>,
+        parent: &StacksBlockId,
+        items: &[(String, String)],
+        make_header_info: &dyn Fn(TrieHash) -> StacksHeaderInfo,
+    ) -> StacksHeaderInfo {
+        let temp_block = StacksBlockId([0xfe; 32]);
+        marf.begin(parent, &temp_block).unwrap();
+        for (key, value) in items.iter() {
+            marf.insert(key, MARFValue::from_value(value)).unwrap();
+        }
+        let root_hash = marf.get_root_hash().unwrap();
+        let header_info = make_header_info(root_hash);
+        marf.commit_to(&header_info.index_block_hash()).unwrap();
+        header_info
+    }
+
+    fn make_header_info(
+        parent: &StacksHeaderInfo,
+        burn_height: u32,
+        state_index_root: TrieHash,
+    ) -> StacksHeaderInfo {
+        let anchored_header = StacksBlockHeader {
+            version: 0,
+            total_work: StacksWorkScore {
+                burn: burn_height as u64,
+                work: parent.anchored_header.total_work.work + 1,
+            },
+            proof: VRFProof::empty(),
+            parent_block: parent.anchored_header.block_hash(),
+            parent_microblock: EMPTY_MICROBLOCK_PARENT_HASH.clone(),
+            parent_microblock_sequence: 0,
+            tx_merkle_root: Sha512Trunc256Sum([burn_height as u8; 32]),
+            state_index_root,
+            microblock_pubkey_hash: Hash160([burn_height as u8; 20]),
+        };
+        StacksHeaderInfo {
+            block_height: anchored_header.total_work.work,
+            anchored_header,
+            microblock_tail: None,
+            index_root: TrieHash([0u8; 32]),
+            consensus_hash: ConsensusHash([burn_height as u8; 20]),
+            burn_header_hash: BurnchainHeaderHash([burn_height as u8; 32]),
+            burn_header_height: burn_height,
+            burn_header_timestamp: 0,
+            total_liquid_ustx: 0,
+        }
+    }
+
+    fn make_burn_headers(first_height: u64, last_height: u64) -> Vec<BurnchainBlockHeader> {
+        (first_height..(last_height + 1))
+            .map(|height| BurnchainBlockHeader {
+                block_height: height,
+                block_hash: BurnchainHeaderHash([height as u8; 32]),
+                parent_block_hash: BurnchainHeaderHash([(height - 1) as u8; 32]),
+                num_txs: 0,
+                timestamp: 0,
+            })
+            .collect()
+    }
+
+    fn proof_hex(marf: &mut MARF<StacksBlockId>, tip: &StacksBlockId, key: &str) -> String {
+        let (_, proof) = marf.get_with_proof(tip, key).unwrap().unwrap();
+        format!("0x{}", proof.to_hex())
+    }
+
+    #[test]
+    fn test_verify_proof_responses() {
+        let mut marf: MARF<StacksBlockId> = MARF::from_path(":memory:").unwrap();
+        let mut stored = HashMap::new();
+
+        let boot_contract =
+            QualifiedContractIdentifier::parse("ST000000000000000000002AMW42H.pox").unwrap();
+        let boot_source = "(define-constant boot-code true)".to_string();
+        let boot_commitment = ContractCommitment {
+            hash: Sha512Trunc256Sum::from_data(boot_source.as_bytes()),
+            block_height: 0,
+        };
+
+        let principal = PrincipalData::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap();
+        let contract = QualifiedContractIdentifier::parse(
+            "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.hello-world",
+        )
+        .unwrap();
+        let map_name = ClarityName::try_from("unit-map".to_string()).unwrap();
+        let map_key = Value::Tuple(
+            TupleData::from_data(vec![(
+                "account".into(),
+                Value::Principal(principal.clone()),
+            )])
+            .unwrap(),
+        );
+        let map_value = Value::some(Value::Tuple(
+            TupleData::from_data(vec![("units".into(), Value::Int(123))]).unwrap(),
+        ))
+        .unwrap();
+        let balance = STXBalance {
+            amount_unlocked: 1000,
+            amount_locked: 500,
+            unlock_height: 200,
+        };
+
+        let balance_key = ClarityDatabase::make_key_for_account_balance(&principal);
+        let nonce_key = ClarityDatabase::make_key_for_account_nonce(&principal);
+        let map_entry_key =
+            ClarityDatabase::make_key_for_data_map_entry(&contract, &map_name, &map_key);
+        let boot_key = MarfedKV::make_contract_hash_key(&boot_contract);
+
+        // genesis block, with the boot code
+        let genesis_items = vec![(boot_key.clone(), boot_commitment.serialize())];
+        let genesis = commit_block(
+            &mut marf,
+            &StacksBlockId::sentinel(),
+            &genesis_items,
+            &|root_hash| {
+                let mut header_info = StacksHeaderInfo::genesis_block_header_info(root_hash, 0);
+                header_info.anchored_header.state_index_root = root_hash;
+                header_info
+            },
+        );
+        assert_eq!(
+            genesis.index_block_hash(),
+            StacksBlockHeader::make_index_block_hash(
+                &FIRST_BURNCHAIN_CONSENSUS_HASH,
+                &FIRST_STACKS_BLOCK_HASH
+            )
+        );
+
+        let block_1_items = vec![
+            (
+                balance_key.clone(),
+                ClaritySerializable::serialize(&balance),
+            ),
+            (nonce_key.clone(), ClaritySerializable::serialize(&1u64)),
+            (
+                map_entry_key.clone(),
+                ClaritySerializable::serialize(&map_value),
+            ),
+        ];
+        let block_1 = commit_block(
+            &mut marf,
+            &genesis.index_block_hash(),
+            &block_1_items,
+            &|root_hash| make_header_info(&genesis, 101, root_hash),
+        );
+
+        let block_2_items = vec![(nonce_key.clone(), ClaritySerializable::serialize(&2u64))];
+        let block_2 = commit_block(
+            &mut marf,
+            &block_1.index_block_hash(),
+            &block_2_items,
+            &|root_hash| make_header_info(&block_1, 102, root_hash),
+        );
+
+        let block_3_items = vec![("unrelated".to_string(), "value".to_string())];
+        let block_3 = commit_block(
+            &mut marf,
+            &block_2.index_block_hash(),
+            &block_3_items,
+            &|root_hash| make_header_info(&block_2, 104, root_hash),
+        );
+
+        for (key, value) in genesis_items
+            .into_iter()
+            .chain(block_1_items.into_iter())
+            .chain(block_2_items.into_iter())
+            .chain(block_3_items.into_iter())
+        {
+            stored.insert(key, value);
+        }
+
+        let tip = block_3.index_block_hash();
+        let headers = vec![genesis, block_1, block_2, block_3.clone()];
+        let burn_headers = make_burn_headers(100, 104);
+
+        let verifier = ProofVerifier::new(
+            &burn_headers,
+            &headers,
+            &block_3.consensus_hash,
+            &block_3.anchored_header.block_hash(),
+        )
+        .unwrap();
+        assert_eq!(verifier.tip(), &tip);
+
+        // account
+        let account = AccountEntryResponse {
+            balance: format!("0x{}", to_hex(&balance.amount_unlocked.to_be_bytes())),
+            locked: format!("0x{}", to_hex(&balance.amount_locked.to_be_bytes())),
+            unlock_height: balance.unlock_height,
+            nonce: 2,
+            balance_proof: Some(proof_hex(&mut marf, &tip, &balance_key)),
+            nonce_proof: Some(proof_hex(&mut marf, &tip, &nonce_key)),
+        };
+        verifier.verify_account(&principal, &account).unwrap();
+
+        let mut stale_account = account.clone();
+        stale_account.nonce = 1;
+        match verifier.verify_account(&principal, &stale_account) {
+            Err(Error::InvalidProof(_)) => {}
+            x => panic!("Expected invalid proof, got {:?}", &x),
+        }
+
+        let mut unproven_account = account.clone();
+        unproven_account.nonce_proof = None;
+        match verifier.verify_account(&principal, &unproven_account) {
+            Err(Error::MissingProof(_)) => {}
+            x => panic!("Expected missing proof, got {:?}", &x),
+        }
+
+        // map entry
+        let map_entry = MapEntryResponse {
+            data: format!("0x{}", ClaritySerializable::serialize(&map_value)),
+            marf_proof: Some(proof_hex(&mut marf, &tip, &map_entry_key)),
+        };
+        verifier
+            .verify_map_entry(&contract, &map_name, &map_key, &map_entry)
+            .unwrap();
+        let other_key =
+            Value::Tuple(TupleData::from_data(vec![("account".into(), Value::Int(1))]).unwrap());
+        assert!(verifier
+            .verify_map_entry(&contract, &map_name, &other_key, &map_entry)
+            .is_err());
+
+        // contract source, written at genesis
+        let contract_src = ContractSrcResponse {
+            source: boot_source.clone(),
+            publish_height: 0,
+            marf_proof: Some(proof_hex(&mut marf, &tip, &boot_key)),
+        };
+        verifier
+            .verify_contract_src(&boot_contract, &contract_src)
+            .unwrap();
+
+        let mut forged_src = contract_src.clone();
+        forged_src.source = "(define-constant boot-code false)".to_string();
+        match verifier.verify_contract_src(&boot_contract, &forged_src) {
+            Err(Error::InvalidProof(_)) => {}
+            x => panic!("Expected invalid proof, got {:?}", &x),
+        }
+
+        // without the genesis header, the boot code can't be checked, but later writes can
+        let partial_verifier = ProofVerifier::new(
+            &burn_headers,
+            &headers[1..],
+            &block_3.consensus_hash,
+            &block_3.anchored_header.block_hash(),
+        )
+        .unwrap();
+        assert!(partial_verifier
+            .verify_contract_src(&boot_contract, &contract_src)
+            .is_err());
+        partial_verifier
+            .verify_account(&principal, &account)
+            .unwrap();
+
+        // multi-key proof
+        let keys = vec![
+            balance_key.clone(),
+            "no-such-key".to_string(),
+            nonce_key.clone(),
+            boot_key.clone(),
+        ];
+        let (marf_values, multi_proof) = marf.get_with_multi_proof(&tip, &keys).unwrap();
+        let data: Vec<Option<String>> = keys
+            .iter()
+            .zip(marf_values.iter())
+            .map(|(key, value)| value.as_ref().map(|_| stored.get(key).unwrap().clone()))
+            .collect();
+        let marf_proof = MarfProofResponse {
+            data: data.clone(),
+            proof: format!("0x{}", multi_proof.to_hex()),
+        };
+        assert_eq!(
+            verifier.verify_marf_proof(&keys, &marf_proof).unwrap(),
+            data
+        );
+
+        let mut forged_proof = marf_proof.clone();
+        forged_proof.data[2] = Some(ClaritySerializable::serialize(&1u64));
+        match verifier.verify_marf_proof(&keys, &forged_proof) {
+            Err(Error::InvalidProof(_)) => {}
+            x => panic!("Expected invalid proof, got {:?}", &x),
+        }
+
+        // the header chain file format gives the same verifier
+        let chain = LightClientHeaderChain {
+            burn_headers: burn_headers
+                .iter()
+                .map(|burn_header| LightClientBurnHeader {
+                    block_height: burn_header.block_height,
+                    block_hash: burn_header.block_hash.to_hex(),
+                    parent_block_hash: burn_header.parent_block_hash.to_hex(),
+                })
+                .collect(),
+            stacks_headers: headers
+                .iter()
+                .map(|header_info| {
+                    let mut header_bytes = vec![];
+                    header_info
+                        .anchored_header
+                        .consensus_serialize(&mut header_bytes)
+                        .unwrap();
+                    LightClientStacksHeader {
+                        consensus_hash: header_info.consensus_hash.to_hex(),
+                        burn_header_hash: header_info.burn_header_hash.to_hex(),
+                        burn_header_height: header_info.burn_header_height,
+                        anchored_header: to_hex(&header_bytes),
+                    }
+                })
+                .collect(),
+        };
+        let json_verifier = ProofVerifier::from_json(
+            &serde_json::to_string(&chain).unwrap(),
+            &block_3.consensus_hash,
+            &block_3.anchored_header.block_hash(),
+        )
+        .unwrap();
+        assert_eq!(json_verifier.tip(), &tip);
+        json_verifier.verify_account(&principal, &account).unwrap();
+    }
+
+    #[test]
+    fn test_reject_bad_header_chains() {
+        let genesis = StacksHeaderInfo::genesis_block_header_info(TrieHash([0u8; 32]), 0);
+        let block_1 = make_header_info(&genesis, 101, TrieHash([1u8; 32]));
+        let block_2 = make_header_info(&block_1, 102, TrieHash([2u8; 32]));
+        let burn_headers = make_burn_headers(100, 102);
+
+        let block_2_hash = block_2.anchored_header.block_hash();
+
+        let headers = vec![genesis.clone(), block_1.clone(), block_2.clone()];
+        ProofVerifier::new(
+            &burn_headers,
+            &headers,
+            &block_2.consensus_hash,
+            &block_2_hash,
+        )
+        .unwrap();
+
+        // no headers
+        assert!(
+            ProofVerifier::new(&burn_headers, &[], &block_2.consensus_hash, &block_2_hash).is_err()
+        );
+
+        // tip is not in the trusted sortition
+        assert!(ProofVerifier::new(
+            &burn_headers,
+            &headers,
+            &block_1.consensus_hash,
+            &block_2_hash
+        )
+        .is_err());
+
+        // tip is not the trusted block
+        assert!(ProofVerifier::new(
+            &burn_headers,
+            &headers,
+            &block_2.consensus_hash,
+            &block_1.anchored_header.block_hash()
+        )
+        .is_err());
+
+        // tip is a different block chosen in the trusted sortition
+        let mut other_block_2 = block_2.clone();
+        other_block_2.anchored_header.state_index_root = TrieHash([0xcc; 32]);
+        let other = vec![genesis.clone(), block_1.clone(), other_block_2];
+        assert!(ProofVerifier::new(
+            &burn_headers,
+            &other,
+            &block_2.consensus_hash,
+            &block_2_hash
+        )
+        .is_err());
+
+        // headers are not linked
+        let unlinked = vec![genesis.clone(), block_2.clone()];
+        assert!(ProofVerifier::new(
+            &burn_headers,
+            &unlinked,
+            &block_2.consensus_hash,
+            &block_2_hash
+        )
+        .is_err());
+
+        // block was not chosen in a trusted burnchain block
+        let mut forked_block_2 = block_2.clone();
+        forked_block_2.burn_header_hash = BurnchainHeaderHash([0xaa; 32]);
+        let forked = vec![genesis.clone(), block_1.clone(), forked_block_2];
+        assert!(ProofVerifier::new(
+            &burn_headers,
+            &forked,
+            &block_2.consensus_hash,
+            &block_2_hash
+        )
+        .is_err());
+
+        // block was chosen after the trusted burnchain headers end
+        assert!(ProofVerifier::new(
+            &make_burn_headers(100, 101),
+            &headers,
+            &block_2.consensus_hash,
+            &block_2_hash
+        )
+        .is_err());
+
+        // burnchain headers are not linked
+        let mut bad_burn_headers = burn_headers.clone();
+        bad_burn_headers[1].parent_block_hash = BurnchainHeaderHash([0xbb; 32]);
+        assert!(ProofVerifier::new(
+            &bad_burn_headers,
+            &headers,
+            &block_2.consensus_hash,
+            &block_2_hash
+        )
+        .is_err());
+    }
+}
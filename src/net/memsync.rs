@@ -0,0 +1,433 @@
+/*
+ copyright: (c) 2013-2020 by Blockstack PBC, a public benefit corporation.
+
+ This file is part of Blockstack.
+
+ Blockstack is free software. You may redistribute or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License or
+ (at your option) any later version.
+
+ Blockstack is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY, including without the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with Blockstack. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use std::cmp;
+
+use burnchains::Txid;
+
+use core::mempool::MemPoolDB;
+
+use net::connection::ReplyHandleP2P;
+use net::p2p::PeerNetwork;
+use net::Error as net_error;
+use net::*;
+
+use util::get_epoch_time_secs;
+use util::hash::Sha512Trunc256Sum;
+use util::log;
+
+use rand::thread_rng;
+use rand::Rng;
+
+#[cfg(not(test))]
+pub const MEMPOOL_SYNC_INTERVAL: u64 = 300;
+#[cfg(test)]
+pub const MEMPOOL_SYNC_INTERVAL: u64 = 10;
+
+/// Number of bloom filter bits to allocate per txid we already have.  With
+/// MEMPOOL_SYNC_BLOOM_HASHES hash functions, this gives a false-positive rate of about 1%.
+pub const MEMPOOL_SYNC_BLOOM_BITS_PER_TX: u32 = 10;
+pub const MEMPOOL_SYNC_BLOOM_HASHES: u8 = 7;
+
+/// Smallest bloom filter we'll send
+pub const MEMPOOL_SYNC_BLOOM_MIN_BITLEN: u32 = 1024;
+
+/// When answering a GetMempool request, scan at most this many mempool transactions for each
+/// transaction the requester asked for.  This bounds the I/O a peer can make us do when it
+/// already has most of our mempool.
+pub const MEMPOOL_SYNC_SCAN_FACTOR: u64 = 8;
+
+impl TxidBloomFilter {
+    /// Make an empty bloom filter
+    pub fn new(seed: u32, num_hashes: u8, bitlen: u32) -> TxidBloomFilter {
+        assert!(num_hashes > 0 && num_hashes <= MEMPOOL_SYNC_BLOOM_MAX_HASHES);
+        assert!(bitlen > 0 && bitlen <= MEMPOOL_SYNC_BLOOM_MAX_BITLEN);
+        let num_bytes = bitlen / 8 + if bitlen % 8 > 0 { 1 } else { 0 };
+        TxidBloomFilter {
+            seed: seed,
+            num_hashes: num_hashes,
+            bitlen: bitlen,
+            bitvec: vec![0u8; num_bytes as usize],
+        }
+    }
+
+    /// Make a bloom filter sized for, and populated with, the given txids
+    pub fn from_txids(txids: &[Txid], seed: u32) -> TxidBloomFilter {
+        let bitlen = (txids.len() as u64) * (MEMPOOL_SYNC_BLOOM_BITS_PER_TX as u64);
+        let bitlen = cmp::max(
+            MEMPOOL_SYNC_BLOOM_MIN_BITLEN as u64,
+            cmp::min(MEMPOOL_SYNC_BLOOM_MAX_BITLEN as u64, bitlen),
+        );
+
+        let mut filter = TxidBloomFilter::new(seed, MEMPOOL_SYNC_BLOOM_HASHES, bitlen as u32);
+        for txid in txids.iter() {
+            filter.insert(txid);
+        }
+        filter
+    }
+
+    /// Get the bit indexes that correspond to this txid
+    fn bit_indexes(&self, txid: &Txid) -> Vec<u32> {
+        let mut preimage = Vec::with_capacity(4 + 32);
+        preimage.extend_from_slice(&self.seed.to_be_bytes());
+        preimage.extend_from_slice(txid.as_bytes());
+
+        let hash = Sha512Trunc256Sum::from_data(&preimage);
+        let hash_bytes = hash.as_bytes();
+
+        let mut indexes = Vec::with_capacity(self.num_hashes as usize);
+        for i in 0..(self.num_hashes as usize) {
+            let mut index_bytes = [0u8; 4];
+            index_bytes.copy_from_slice(&hash_bytes[4 * i..4 * (i + 1)]);
+            indexes.push(u32::from_be_bytes(index_bytes) % self.bitlen);
+        }
+        indexes
+    }
+
+    /// Add a txid to this filter
+    pub fn insert(&mut self, txid: &Txid) -> () {
+        for index in self.bit_indexes(txid).into_iter() {
+            self.bitvec[(index / 8) as usize] |= 1 << (index % 8);
+        }
+    }
+
+    /// Is this txid (probably) in this filter?  May return false positives, but never false
+    /// negatives.
+    pub fn contains(&self, txid: &Txid) -> bool {
+        for index in self.bit_indexes(txid).into_iter() {
+            match self.bitvec.get((index / 8) as usize) {
+                Some(byte) => {
+                    if byte & (1 << (index % 8)) == 0 {
+                        return false;
+                    }
+                }
+                None => {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+/// State of an ongoing mempool sync with one of our outbound neighbors
+pub struct MempoolSyncState {
+    pub event_id: usize,
+    pub tx_filter: TxidBloomFilter,
+    pub last_txid: Txid,
+    pub num_pages: u64,
+    pub num_txs: u64,
+    reply_handle: Option<ReplyHandleP2P>,
+}
+
+impl MempoolSyncState {
+    pub fn new(event_id: usize, tx_filter: TxidBloomFilter) -> MempoolSyncState {
+        MempoolSyncState {
+            event_id: event_id,
+            tx_filter: tx_filter,
+            last_txid: Txid([0u8; 32]),
+            num_pages: 0,
+            num_txs: 0,
+            reply_handle: None,
+        }
+    }
+}
+
+impl PeerNetwork {
+    /// Build a page of transactions from our mempool that the requester does not have.
+    /// Transactions are scanned in txid order, starting after the request's last_txid.  The page
+    /// is bounded by the requested number of transactions, by the number of transactions scanned,
+    /// and by the maximum message size.
+    pub fn make_mempool_txs_response(
+        mempool: &MemPoolDB,
+        request: &GetMempoolData,
+    ) -> Result<MempoolTxsData, net_error> {
+        let max_txs = cmp::min(request.max_txs, MEMPOOL_SYNC_PAGE_MAX) as u64;
+        let max_scan = max_txs * MEMPOOL_SYNC_SCAN_FACTOR;
+        let max_bytes = (MAX_PAYLOAD_LEN / 2) as u64;
+
+        let mut txs = vec![];
+        let mut num_bytes = 0;
+        let mut num_scanned = 0;
+        let mut last_txid = request.last_txid.clone();
+
+        loop {
+            let tx_infos = MemPoolDB::get_txs_after_txid(mempool.conn(), &last_txid, max_txs)?;
+            if tx_infos.len() == 0 {
+                // scanned the whole mempool
+                return Ok(MempoolTxsData {
+                    txs: txs,
+                    last_txid: last_txid,
+                    has_more: false,
+                });
+            }
+
+            for tx_info in tx_infos.into_iter() {
+                if (txs.len() as u64) >= max_txs
+                    || num_scanned >= max_scan
+                    || (txs.len() > 0 && num_bytes + tx_info.metadata.len > max_bytes)
+                {
+                    // page is full
+                    return Ok(MempoolTxsData {
+                        txs: txs,
+                        last_txid: last_txid,
+                        has_more: true,
+                    });
+                }
+
+                num_scanned += 1;
+                last_txid = tx_info.metadata.txid.clone();
+
+                if request.tx_filter.contains(&last_txid) {
+                    // requester (probably) already has it
+                    continue;
+                }
+
+                num_bytes += tx_info.metadata.len;
+                txs.push(tx_info.tx);
+            }
+        }
+    }
+
+    /// Start a mempool sync with a randomly-chosen authenticated outbound neighbor that
+    /// advertised mempool sync support in its handshake.
+    /// Return Ok(true) if we started one; Ok(false) if there is no one to sync with.
+    fn begin_mempool_sync(&mut self, mempool: &MemPoolDB) -> Result<bool, net_error> {
+        let mut event_ids = vec![];
+        for (event_id, convo) in self.peers.iter() {
+            if convo.is_authenticated() && convo.is_outbound() && convo.supports_mempool_sync() {
+                event_ids.push(*event_id);
+            }
+        }
+
+        if event_ids.len() == 0 {
+            return Ok(false);
+        }
+
+        let event_id = event_ids[thread_rng().gen::<usize>() % event_ids.len()];
+        let txids = MemPoolDB::get_all_txids(mempool.conn())?;
+
+        // use a fresh seed each time, so a txid that collides in this sync probably won't
+        // collide in the next one.
+        let tx_filter = TxidBloomFilter::from_txids(&txids, thread_rng().gen::<u32>());
+
+        debug!(
+            "{:?}: begin mempool sync with event {} ({} txids, {}-bit filter)",
+            &self.local_peer,
+            event_id,
+            txids.len(),
+            tx_filter.bitlen
+        );
+
+        self.mempool_sync = Some(MempoolSyncState::new(event_id, tx_filter));
+        Ok(true)
+    }
+
+    /// Send the next GetMempool request if we haven't yet, and poll for its reply.
+    /// Fetched transactions are added to network_result.synced_transactions.
+    /// Return Ok(true) if the sync is finished.
+    fn mempool_sync_step(
+        &mut self,
+        state: &mut MempoolSyncState,
+        network_result: &mut NetworkResult,
+    ) -> Result<bool, net_error> {
+        if state.reply_handle.is_none() {
+            let request = GetMempoolData {
+                tx_filter: state.tx_filter.clone(),
+                last_txid: state.last_txid.clone(),
+                max_txs: cmp::min(
+                    self.connection_opts.mempool_sync_page_size,
+                    MEMPOOL_SYNC_PAGE_MAX,
+                ),
+            };
+
+            let mut rh = match self.peers.get_mut(&state.event_id) {
+                Some(convo) => {
+                    let message = convo.sign_message(
+                        &self.chain_view,
+                        &self.local_peer.private_key,
+                        StacksMessageType::GetMempool(request),
+                    )?;
+                    convo.send_signed_request(message, self.connection_opts.timeout)?
+                }
+                None => {
+                    return Err(net_error::PeerNotConnected);
+                }
+            };
+
+            self.saturate_p2p_socket(state.event_id, &mut rh)?;
+            state.reply_handle = Some(rh);
+        }
+
+        let mut rh = state
+            .reply_handle
+            .take()
+            .expect("BUG: no reply handle for mempool sync");
+
+        self.saturate_p2p_socket(rh.get_event_id(), &mut rh)?;
+
+        match rh.try_send_recv() {
+            Ok(message) => match message.payload {
+                StacksMessageType::MempoolTxs(data) => {
+                    state.num_pages += 1;
+                    state.num_txs += data.txs.len() as u64;
+                    network_result.synced_transactions.extend(data.txs);
+
+                    if !data.has_more
+                        || data.last_txid.as_bytes() <= state.last_txid.as_bytes()
+                        || state.num_pages >= self.connection_opts.mempool_sync_max_pages
+                    {
+                        // no more pages, or the neighbor isn't making progress, or we've
+                        // fetched enough for this pass.
+                        return Ok(true);
+                    }
+
+                    state.last_txid = data.last_txid;
+                    Ok(false)
+                }
+                StacksMessageType::Nack(ref data) => {
+                    debug!(
+                        "{:?}: event {} NACK'ed mempool sync with code {}",
+                        &self.local_peer, state.event_id, data.error_code
+                    );
+                    Ok(true)
+                }
+                other_payload => {
+                    debug!(
+                        "{:?}: Got unexpected payload {:?}",
+                        &self.local_peer, &other_payload
+                    );
+                    Err(net_error::InvalidMessage)
+                }
+            },
+            Err(req_res) => match req_res {
+                Ok(same_req) => {
+                    // try again
+                    state.reply_handle = Some(same_req);
+                    Ok(false)
+                }
+                Err(e) => Err(e),
+            },
+        }
+    }
+
+    /// Synchronize our mempool with a neighbor's, by asking it for the transactions we don't
+    /// have.  We do this once every mempool_sync_interval seconds, fetching at most one page
+    /// per call.  Fetched transactions are given to the relayer via the network result.
+    /// Return true if there is no mempool sync in progress.
+    pub fn do_network_mempool_sync(
+        &mut self,
+        mempool: &MemPoolDB,
+        network_result: &mut NetworkResult,
+    ) -> Result<bool, net_error> {
+        if cfg!(test) && self.connection_opts.disable_mempool_sync {
+            test_debug!("{:?}: mempool sync is disabled", &self.local_peer);
+            return Ok(true);
+        }
+
+        if self.mempool_sync.is_none() {
+            if self.mempool_sync_deadline > get_epoch_time_secs() {
+                return Ok(true);
+            }
+
+            if !self.begin_mempool_sync(mempool)? {
+                // no one to talk to yet
+                return Ok(true);
+            }
+
+            self.mempool_sync_deadline =
+                get_epoch_time_secs() + self.connection_opts.mempool_sync_interval;
+        }
+
+        let mut state = self
+            .mempool_sync
+            .take()
+            .expect("BUG: no mempool sync state");
+
+        match self.mempool_sync_step(&mut state, network_result) {
+            Ok(false) => {
+                self.mempool_sync = Some(state);
+                Ok(false)
+            }
+            Ok(true) => {
+                debug!(
+                    "{:?}: finished mempool sync with event {}: got {} transaction(s) in {} page(s)",
+                    &self.local_peer, state.event_id, state.num_txs, state.num_pages
+                );
+                Ok(true)
+            }
+            Err(e) => {
+                debug!(
+                    "{:?}: failed mempool sync with event {}: {:?}",
+                    &self.local_peer, state.event_id, &e
+                );
+                Ok(true)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_txid_bloom_filter() {
+        let txids: Vec<Txid> = (0..1000u32)
+            .map(|i| Txid(Sha512Trunc256Sum::from_data(&i.to_be_bytes()).0))
+            .collect();
+
+        let filter = TxidBloomFilter::from_txids(&txids, 0x01020304);
+        assert_eq!(filter.bitlen, 1000 * MEMPOOL_SYNC_BLOOM_BITS_PER_TX);
+        assert_eq!(filter.num_hashes, MEMPOOL_SYNC_BLOOM_HASHES);
+
+        // no false negatives
+        for txid in txids.iter() {
+            assert!(filter.contains(txid));
+        }
+
+        // few false positives
+        let mut false_positives = 0;
+        for i in 1000..11000u32 {
+            let txid = Txid(Sha512Trunc256Sum::from_data(&i.to_be_bytes()).0);
+            if filter.contains(&txid) {
+                false_positives += 1;
+            }
+        }
+        assert!(false_positives < 300, "{} false positives", false_positives);
+
+        // a different seed sets different bits
+        let other_filter = TxidBloomFilter::from_txids(&txids, 0x05060708);
+        assert!(other_filter.bitvec != filter.bitvec);
+
+        // small and empty sets still get a usable filter
+        let empty_filter = TxidBloomFilter::from_txids(&[], 0);
+        assert_eq!(empty_filter.bitlen, MEMPOOL_SYNC_BLOOM_MIN_BITLEN);
+        assert!(!empty_filter.contains(&txids[0]));
+
+        // the largest allowed filter
+        let mut capped_filter = TxidBloomFilter::new(0, 1, MEMPOOL_SYNC_BLOOM_MAX_BITLEN);
+        capped_filter.insert(&txids[0]);
+        assert!(capped_filter.contains(&txids[0]));
+        assert_eq!(
+            capped_filter.bitvec.len() as u32,
+            MEMPOOL_SYNC_BLOOM_MAX_BITLEN / 8
+        );
+    }
+}
@@ -23,6 +23,8 @@ pub mod dns;
 pub mod download;
 pub mod http;
 pub mod inv;
+pub mod light_client;
+pub mod memsync;
 pub mod neighbors;
 pub mod p2p;
 pub mod poll;
@@ -74,15 +76,16 @@ use chainstate::burn::db::sortdb::PoxId;
 
 use chainstate::stacks::db::blocks::MemPoolRejection;
 use chainstate::stacks::{
-    Error as chain_error, StacksAddress, StacksBlock, StacksBlockId, StacksMicroblock,
-    StacksPublicKey, StacksTransaction,
+    Error as chain_error, StacksAddress, StacksBlock, StacksBlockHeader, StacksBlockId,
+    StacksMicroblock, StacksPublicKey, StacksTransaction, TransactionPayload,
+    TransactionPostCondition, TransactionPostConditionMode,
 };
 
 use chainstate::stacks::Error as chainstate_error;
 
 use vm::{
-    analysis::contract_interface_builder::ContractInterface, types::PrincipalData, ClarityName,
-    ContractName, Value,
+    analysis::contract_interface_builder::ContractInterface, costs::ExecutionCost,
+    types::PrincipalData, ClarityName, ContractName, Value,
 };
 
 use util::hash::Hash160;
@@ -578,6 +581,35 @@ impl PeerAddress {
     pub fn is_anynet(&self) -> bool {
         self.0 == [0x00; 16] || self == &PeerAddress::from_ipv4(0, 0, 0, 0)
     }
+
+    /// Parse a CIDR string like "10.0.0.0/8" or "fd00::/8" into a prefix and a mask over the
+    /// full 128-bit address (so IPv4 masks are offset by 96 bits).
+    /// Returns None if the string is malformed or the mask would be empty or out of range.
+    pub fn from_cidr_str(cidr: &str) -> Option<(PeerAddress, u32)> {
+        let mut parts = cidr.splitn(2, '/');
+        let ip = parts.next()?.parse::<IpAddr>().ok()?;
+        let mask = parts.next()?.parse::<u32>().ok()?;
+        let mask = match ip {
+            IpAddr::V4(_) if mask <= 32 => mask + 96,
+            IpAddr::V6(_) if mask <= 128 => mask,
+            _ => {
+                return None;
+            }
+        };
+        if mask == 0 {
+            return None;
+        }
+        Some((PeerAddress::from_ip(&ip), mask))
+    }
+
+    /// Inverse of from_cidr_str
+    pub fn to_cidr_string(&self, mask: u32) -> String {
+        if self.is_ipv4() && mask >= 96 {
+            format!("{}/{}", self.to_socketaddr(0).ip(), mask - 96)
+        } else {
+            format!("{}/{}", self.to_socketaddr(0).ip(), mask)
+        }
+    }
 }
 
 /// A container for public keys (compressed secp256k1 public keys)
@@ -719,6 +751,59 @@ pub struct PoxInvData {
     pub pox_bitvec: Vec<u8>, // a bit will be '1' if the node knows for sure the status of its reward cycle's anchor block; 0 if not.
 }
 
+/// Bloom filter over a set of txids.  The bits for a txid are derived from
+/// SHA512/256(seed || txid), so that a different seed yields different false positives.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxidBloomFilter {
+    pub seed: u32,
+    pub num_hashes: u8, // number of bits set per txid (not to exceed MEMPOOL_SYNC_BLOOM_MAX_HASHES)
+    pub bitlen: u32, // number of bits represented in bitvec (not to exceed MEMPOOL_SYNC_BLOOM_MAX_BITLEN)
+    pub bitvec: Vec<u8>,
+}
+
+/// Request for a page of mempool transactions that the requester does not have.
+/// The remote peer scans its mempool in txid order, starting after last_txid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetMempoolData {
+    pub tx_filter: TxidBloomFilter, // txids the requester already has
+    pub last_txid: Txid,            // only consider txids strictly greater than this one
+    pub max_txs: u32,               // maximum number of transactions to send back
+}
+
+/// Response to a GetMempool request
+#[derive(Debug, Clone, PartialEq)]
+pub struct MempoolTxsData {
+    pub txs: Vec<StacksTransaction>,
+    pub last_txid: Txid, // last txid scanned by the sender; pass as last_txid to get the next page
+    pub has_more: bool,  // false if the sender scanned the remainder of its mempool
+}
+
+/// An anchored block announced by its header and the short IDs of its transactions, so the
+/// receiver can rebuild it from its mempool instead of downloading it in full.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompactBlockData {
+    pub consensus_hash: ConsensusHash,
+    pub header: StacksBlockHeader,
+    pub short_txids: Vec<u64>, // short ID of every transaction in the block, in block order
+    pub prefilled_txs: Vec<(u16, StacksTransaction)>, // transactions sent in full (i.e. the coinbase), by index
+}
+
+/// Request for the transactions at the given indexes of an anchored block
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetBlockTxsData {
+    pub consensus_hash: ConsensusHash,
+    pub block_hash: BlockHeaderHash,
+    pub indexes: Vec<u16>, // strictly increasing
+}
+
+/// Response to a GetBlockTxs request
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockTxsData {
+    pub consensus_hash: ConsensusHash,
+    pub block_hash: BlockHeaderHash,
+    pub txs: Vec<StacksTransaction>, // one per requested index, in request order
+}
+
 /// Blocks pushed
 #[derive(Debug, Clone, PartialEq)]
 pub struct BlocksData {
@@ -812,8 +897,15 @@ pub struct HandshakeData {
 pub enum ServiceFlags {
     RELAY = 0x01,
     RPC = 0x02,
+    MEMPOOL_SYNC = 0x04,
+    COMPACT_BLOCKS = 0x08,
 }
 
+/// Services this node advertises in its handshakes
+pub const LOCAL_PEER_SERVICES: u16 = ServiceFlags::RELAY as u16
+    | ServiceFlags::MEMPOOL_SYNC as u16
+    | ServiceFlags::COMPACT_BLOCKS as u16;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct HandshakeAcceptData {
     pub handshake: HandshakeData, // this peer's handshake information
@@ -830,6 +922,7 @@ pub mod NackErrorCodes {
     pub const Throttled: u32 = 3;
     pub const InvalidPoxFork: u32 = 4;
     pub const InvalidMessage: u32 = 5;
+    pub const NoSuchBlock: u32 = 6;
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -879,6 +972,11 @@ pub enum StacksMessageType {
     Pong(PongData),
     NatPunchRequest(u32),
     NatPunchReply(NatPunchData),
+    GetMempool(GetMempoolData),
+    MempoolTxs(MempoolTxsData),
+    CompactBlock(CompactBlockData),
+    GetBlockTxs(GetBlockTxsData),
+    BlockTxs(BlockTxsData),
 }
 
 /// Peer address variants
@@ -1056,6 +1154,127 @@ pub struct CallReadOnlyResponse {
     pub cause: Option<String>,
 }
 
+/// The data we return on POST /v2/contracts/call-dry-run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DryRunCallResponse {
+    pub okay: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cause: Option<String>,
+    /// true if the call would have been aborted by its post-conditions
+    pub post_condition_aborted: bool,
+    pub events: Vec<serde_json::Value>,
+    pub execution_cost: ExecutionCost,
+}
+
+/// A suggested fee rate, and the fee a transaction of the estimated length would pay at it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCFeeEstimate {
+    pub fee_rate: u64,
+    pub fee: u64,
+}
+
+/// The data we return on POST /v2/fees/transaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCFeeEstimateResponse {
+    pub estimated_cost: ExecutionCost,
+    pub estimated_len: u64,
+    /// low, medium, and high fee suggestions, in that order
+    pub estimations: Vec<RPCFeeEstimate>,
+}
+
+/// Mempool status of an unconfirmed transaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCPendingTxInfo {
+    pub accept_time: u64,
+    pub fee_rate: u64,
+    pub len: u64,
+}
+
+/// Receipt of a confirmed transaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCConfirmedTxInfo {
+    pub index_block_hash: String,
+    pub block_height: u64,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub microblock_hash: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub microblock_sequence: Option<u16>,
+    pub tx_index: u32,
+    pub result: String,
+    pub post_condition_aborted: bool,
+    pub events: Vec<serde_json::Value>,
+    pub execution_cost: ExecutionCost,
+}
+
+/// The data we return on GET /v2/transactions/{txid}.  Exactly one of `pending` and
+/// `confirmed` is set, depending on `status`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionStatusResponse {
+    pub txid: String,
+    pub status: String,
+    pub tx: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending: Option<RPCPendingTxInfo>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirmed: Option<RPCConfirmedTxInfo>,
+}
+
+/// The data we return on POST /v2/transactions/simulate.  Mirrors a StacksTransactionReceipt,
+/// plus the reason the transaction would be rejected if it could not be mined.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionSimulationResponse {
+    pub txid: String,
+    /// true if the transaction could be mined
+    pub okay: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cause: Option<String>,
+    pub fee: u64,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    pub post_condition_aborted: bool,
+    pub stx_burned: u128,
+    pub events: Vec<serde_json::Value>,
+    pub execution_cost: ExecutionCost,
+}
+
+/// A contract event, as returned by GET /v2/contracts/events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCContractEvent {
+    pub txid: String,
+    pub index_block_hash: String,
+    pub block_height: u64,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub microblock_hash: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub microblock_sequence: Option<u16>,
+    pub tx_index: u32,
+    pub event_index: u32,
+    pub topic: String,
+    /// hex-encoded, consensus-serialized Clarity value
+    pub value: String,
+}
+
+/// The data we return on GET /v2/contracts/events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractEventsResponse {
+    pub contract_identifier: String,
+    pub offset: u64,
+    pub limit: u64,
+    pub events: Vec<RPCContractEvent>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AccountEntryResponse {
     pub balance: String,
@@ -1108,6 +1327,56 @@ pub struct CallReadOnlyRequestBody {
     pub arguments: Vec<String>,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct DryRunCallRequestBody {
+    pub sender: String,
+    pub arguments: Vec<String>,
+    /// hex-encoded, consensus-serialized post-conditions
+    #[serde(default)]
+    pub post_conditions: Vec<String>,
+    /// "allow" or "deny"; defaults to "deny"
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_condition_mode: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FeeRateEstimateRequestBody {
+    /// hex-encoded, consensus-serialized transaction payload
+    pub transaction_payload: String,
+    /// principal that will send the transaction; required to estimate a contract-call
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sender: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_len: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MarfProofRequestBody {
+    /// MARF keys to look up, in the order they are proven
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarfProofResponse {
+    /// the value stored under each requested key, or null if there is none
+    pub data: Vec<Option<String>>,
+    /// hex-encoded, consensus-serialized multi-key proof of the keys that have values
+    pub proof: String,
+}
+
+/// Filter and paging parameters for GET /v2/contracts/events
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractEventsQuery {
+    pub topic: Option<String>,
+    pub from_height: Option<u64>,
+    pub to_height: Option<u64>,
+    pub offset: u64,
+    pub limit: u64,
+}
+
 /// Items in the NeighborsInfo -- combines NeighborKey and NeighborAddress
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RPCNeighbor {
@@ -1141,6 +1410,90 @@ pub struct RPCNeighborsInfo {
     pub outbound: Vec<RPCNeighbor>,
 }
 
+/// A connected p2p peer and its conversation statistics, as reported by `/v2/admin/peers`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAdminPeer {
+    pub event_id: usize,
+    pub network_id: u32,
+    pub peer_version: u32,
+    #[serde(rename = "ip")]
+    pub addrbytes: PeerAddress,
+    pub port: u16,
+    pub public_key_hash: Option<Hash160>,
+    pub authenticated: bool,
+    pub outbound: bool,
+    pub first_contact_time: u64,
+    pub last_contact_time: u64,
+    pub last_send_time: u64,
+    pub last_recv_time: u64,
+    pub last_handshake_time: u64,
+    pub bytes_tx: u64,
+    pub bytes_rx: u64,
+    pub msgs_tx: u64,
+    pub msgs_rx: u64,
+    pub msgs_rx_unsolicited: u64,
+    pub msgs_err: u64,
+    pub health_score: f64,
+}
+
+/// Struct given back from a call to `/v2/admin/cidrs`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAdminCidrs {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+/// Struct given back from an admin call that changes the peer DB
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAdminUpdateResponse {
+    /// number of connected peers that were dropped as a result of the update
+    pub disconnected: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminPeerRequestBody {
+    pub ip: PeerAddress,
+    pub port: u16,
+    /// ban duration in seconds; only used when banning
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminCidrRequestBody {
+    pub cidr: String,
+}
+
+/// Which CIDR list an admin request refers to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AdminCidrList {
+    Allow,
+    Deny,
+}
+
+impl AdminCidrList {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            AdminCidrList::Allow => "allow",
+            AdminCidrList::Deny => "deny",
+        }
+    }
+}
+
+/// Peer-management operations on the admin RPC interface
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdminRequest {
+    GetPeers,
+    /// ban the peer at (address, port), optionally for the given number of seconds
+    BanPeer(PeerAddress, u16, Option<u64>),
+    UnbanPeer(PeerAddress, u16),
+    GetCidrs,
+    /// add the (prefix, mask) CIDR to the list.  The mask is over the full 128-bit address.
+    AddCidr(AdminCidrList, PeerAddress, u32),
+    RemoveCidr(AdminCidrList, PeerAddress, u32),
+}
+
 /// All HTTP request paths we support, and the arguments they carry in their paths
 #[derive(Debug, Clone, PartialEq)]
 pub enum HttpRequestType {
@@ -1152,6 +1505,12 @@ pub enum HttpRequestType {
     GetMicroblocksConfirmed(HttpRequestMetadata, StacksBlockId),
     GetMicroblocksUnconfirmed(HttpRequestMetadata, StacksBlockId, u16),
     PostTransaction(HttpRequestMetadata, StacksTransaction),
+    SimulateTransaction(
+        HttpRequestMetadata,
+        StacksTransaction,
+        Option<StacksBlockId>,
+    ),
+    GetTransaction(HttpRequestMetadata, Txid, Option<StacksBlockId>),
     PostMicroblock(HttpRequestMetadata, StacksMicroblock, Option<StacksBlockId>),
     GetAccount(
         HttpRequestMetadata,
@@ -1177,7 +1536,26 @@ pub enum HttpRequestType {
         Vec<Value>,
         Option<StacksBlockId>,
     ),
+    DryRunCallFunction(
+        HttpRequestMetadata,
+        StacksAddress,
+        ContractName,
+        PrincipalData,
+        ClarityName,
+        Vec<Value>,
+        Vec<TransactionPostCondition>,
+        TransactionPostConditionMode,
+        Option<StacksBlockId>,
+    ),
     GetTransferCost(HttpRequestMetadata),
+    PostFeeRateEstimate(
+        HttpRequestMetadata,
+        TransactionPayload,
+        Option<PrincipalData>,
+        Option<u64>,
+        Option<StacksBlockId>,
+    ),
+    PostMarfProof(HttpRequestMetadata, Vec<String>, Option<StacksBlockId>),
     GetContractSrc(
         HttpRequestMetadata,
         StacksAddress,
@@ -1191,7 +1569,16 @@ pub enum HttpRequestType {
         ContractName,
         Option<StacksBlockId>,
     ),
+    GetContractEvents(
+        HttpRequestMetadata,
+        StacksAddress,
+        ContractName,
+        ContractEventsQuery,
+        Option<StacksBlockId>,
+    ),
     OptionsPreflight(HttpRequestMetadata, String),
+    /// peer-management request, with the bearer token from the Authorization header (if given)
+    Admin(HttpRequestMetadata, Option<String>, AdminRequest),
     /// catch-all for any errors we should surface from parsing
     ClientError(HttpRequestMetadata, ClientError),
 }
@@ -1274,13 +1661,22 @@ pub enum HttpResponseType {
     Microblocks(HttpResponseMetadata, Vec<StacksMicroblock>),
     MicroblockStream(HttpResponseMetadata),
     TransactionID(HttpResponseMetadata, Txid),
+    TransactionStatus(HttpResponseMetadata, TransactionStatusResponse),
+    TransactionSimulation(HttpResponseMetadata, TransactionSimulationResponse),
     MicroblockHash(HttpResponseMetadata, BlockHeaderHash),
     TokenTransferCost(HttpResponseMetadata, u64),
+    FeeRateEstimate(HttpResponseMetadata, RPCFeeEstimateResponse),
     GetMapEntry(HttpResponseMetadata, MapEntryResponse),
+    MarfProof(HttpResponseMetadata, MarfProofResponse),
     CallReadOnlyFunction(HttpResponseMetadata, CallReadOnlyResponse),
+    DryRunCallFunction(HttpResponseMetadata, DryRunCallResponse),
     GetAccount(HttpResponseMetadata, AccountEntryResponse),
     GetContractABI(HttpResponseMetadata, ContractInterface),
+    GetContractEvents(HttpResponseMetadata, ContractEventsResponse),
     GetContractSrc(HttpResponseMetadata, ContractSrcResponse),
+    AdminPeers(HttpResponseMetadata, Vec<RPCAdminPeer>),
+    AdminCidrs(HttpResponseMetadata, RPCAdminCidrs),
+    AdminUpdate(HttpResponseMetadata, RPCAdminUpdateResponse),
     OptionsPreflight(HttpResponseMetadata),
     // peer-given error responses
     BadRequest(HttpResponseMetadata, String),
@@ -1322,6 +1718,11 @@ pub enum StacksMessageID {
     Pong = 16,
     NatPunchRequest = 17,
     NatPunchReply = 18,
+    GetMempool = 19,
+    MempoolTxs = 20,
+    CompactBlock = 21,
+    GetBlockTxs = 22,
+    BlockTxs = 23,
     Reserved = 255,
 }
 
@@ -1441,6 +1842,16 @@ pub const GETPOXINV_MAX_BITLEN: u64 = 8;
 // message.
 pub const BLOCKS_PUSHED_MAX: u32 = 32;
 
+// maximum number of transactions that can be sent in a single MempoolTxs page
+pub const MEMPOOL_SYNC_PAGE_MAX: u32 = 256;
+
+// maximum number of bits and hash functions in a GetMempool txid bloom filter
+pub const MEMPOOL_SYNC_BLOOM_MAX_BITLEN: u32 = 8 * 65536;
+pub const MEMPOOL_SYNC_BLOOM_MAX_HASHES: u8 = 8;
+
+// maximum number of transactions in a compact block (bounded by the u16 transaction indexes)
+pub const COMPACT_BLOCK_MAX_TXS: u32 = 65535;
+
 macro_rules! impl_byte_array_message_codec {
     ($thing:ident, $len:expr) => {
         impl ::net::StacksMessageCodec for $thing {
@@ -1468,6 +1879,7 @@ impl_byte_array_message_codec!(StacksBlockId, 32);
 impl_byte_array_message_codec!(MessageSignature, 65);
 impl_byte_array_message_codec!(PeerAddress, 16);
 impl_byte_array_message_codec!(StacksPublicKeyBuffer, 33);
+impl_byte_array_message_codec!(Txid, 32);
 
 impl_byte_array_serde!(ConsensusHash);
 
@@ -1585,6 +1997,12 @@ impl Neighbor {
 
 pub const NUM_NEIGHBORS: usize = 32;
 
+// maximum number of contract events returned in one page
+pub const MAX_CONTRACT_EVENTS_PAGE_SIZE: u64 = 100;
+
+// maximum number of keys that can be proven in one POST /v2/proofs request
+pub const MAX_MARF_PROOF_KEYS: usize = 64;
+
 // maximum number of unconfirmed microblocks can get streamed to us
 pub const MAX_MICROBLOCKS_UNCONFIRMED: usize = 1024;
 
@@ -1596,6 +2014,9 @@ pub const DENY_BAN_DURATION: u64 = 86400; // seconds (1 day)
 
 pub const DENY_MIN_BAN_DURATION: u64 = 2;
 
+// longest ban the admin RPC endpoint will accept
+pub const MAX_ADMIN_BAN_DURATION: u64 = 86400 * 365 * 10; // seconds (10 years)
+
 /// Result of doing network work
 pub struct NetworkResult {
     pub download_pox_id: Option<PoxId>, // PoX ID as it was when we begin downloading blocks (set if we have downloaded new blocks)
@@ -1607,6 +2028,7 @@ pub struct NetworkResult {
     pub pushed_microblocks: HashMap<NeighborKey, Vec<(Vec<RelayData>, MicroblocksData)>>, // all microblocks pushed to us, and the relay hints from the message
     pub uploaded_transactions: Vec<StacksTransaction>, // transactions sent to us by the http server
     pub uploaded_microblocks: Vec<MicroblocksData>,    // microblocks sent to us by the http server
+    pub synced_transactions: Vec<StacksTransaction>,   // transactions fetched from a peer's mempool
 }
 
 impl NetworkResult {
@@ -1621,6 +2043,7 @@ impl NetworkResult {
             pushed_microblocks: HashMap::new(),
             uploaded_transactions: vec![],
             uploaded_microblocks: vec![],
+            synced_transactions: vec![],
         }
     }
 
@@ -1635,7 +2058,9 @@ impl NetworkResult {
     }
 
     pub fn has_transactions(&self) -> bool {
-        self.pushed_transactions.len() > 0 || self.uploaded_transactions.len() > 0
+        self.pushed_transactions.len() > 0
+            || self.uploaded_transactions.len() > 0
+            || self.synced_transactions.len() > 0
     }
 
     pub fn transactions(&self) -> Vec<StacksTransaction> {
@@ -1643,6 +2068,7 @@ impl NetworkResult {
             .values()
             .flat_map(|pushed_txs| pushed_txs.iter().map(|(_, tx)| tx.clone()))
             .chain(self.uploaded_transactions.iter().map(|x| x.clone()))
+            .chain(self.synced_transactions.iter().map(|x| x.clone()))
             .collect()
     }
 
@@ -2003,6 +2429,7 @@ pub mod test {
         pub initial_balances: Vec<(PrincipalData, u64)>,
         pub spending_account: TestMiner,
         pub setup_code: String,
+        pub services: u16,
     }
 
     impl TestPeerConfig {
@@ -2046,6 +2473,7 @@ pub mod test {
                 initial_balances: vec![],
                 spending_account: spending_account,
                 setup_code: "".into(),
+                services: LOCAL_PEER_SERVICES,
             }
         }
 
@@ -2276,7 +2704,7 @@ pub mod test {
                     config.server_port,
                 )
                 .unwrap();
-                PeerDB::set_local_services(&mut tx, ServiceFlags::RELAY as u16).unwrap();
+                PeerDB::set_local_services(&mut tx, config.services).unwrap();
                 PeerDB::set_local_private_key(
                     &mut tx,
                     &config.private_key,
@@ -2749,6 +3177,14 @@ pub mod test {
             &mut self.stacks_node.as_mut().unwrap().chainstate
         }
 
+        /// Index the transactions in the blocks this peer processes from now on.  Blocks are
+        /// processed through the coordinator's chainstate but served from the node's, so both
+        /// need the index enabled.
+        pub fn enable_transaction_index(&mut self) {
+            self.coord.chainstate().enable_transaction_index();
+            self.chainstate().enable_transaction_index();
+        }
+
         pub fn sortdb(&mut self) -> &mut SortitionDB {
             self.sortdb.as_mut().unwrap()
         }
@@ -77,10 +77,13 @@ use burnchains::BurnchainView;
 use burnchains::PublicKey;
 
 use chainstate::burn::db::sortdb::{BlockHeaderCache, PoxId, SortitionDB, SortitionId};
+use chainstate::burn::{BlockSnapshot, ConsensusHash};
 
 use chainstate::stacks::db::StacksChainState;
 
-use chainstate::stacks::{MAX_BLOCK_LEN, MAX_TRANSACTION_LEN};
+use chainstate::stacks::{
+    StacksBlockHeader, StacksTransaction, MAX_BLOCK_LEN, MAX_TRANSACTION_LEN,
+};
 
 use util::get_epoch_time_secs;
 use util::log;
@@ -92,6 +95,7 @@ use mio;
 use mio::net as mio_net;
 
 use net::inv::*;
+use net::memsync::*;
 use net::relay::*;
 use net::rpc::RPCHandlerArgs;
 
@@ -103,6 +107,7 @@ pub enum NetworkRequest {
     AdvertizeMicroblocks(BlocksAvailableMap), // announce to all wanting neighbors that we have these confirmed microblock streams
     Relay(NeighborKey, StacksMessage),
     Broadcast(Vec<RelayData>, StacksMessageType),
+    BroadcastBlock(ConsensusHash, StacksBlock), // announce a block we produced, compactly where we can
 }
 
 /// Handle for other threads to use to issue p2p network requests.
@@ -179,6 +184,17 @@ impl NetworkHandle {
         let req = NetworkRequest::Broadcast(relay_hints, msg);
         self.send_request(req)
     }
+
+    /// Broadcast a block we produced to our neighbors via the p2p network thread.
+    /// Neighbors that support compact blocks get a CompactBlock; the rest get the whole block.
+    pub fn broadcast_block(
+        &mut self,
+        consensus_hash: ConsensusHash,
+        block: StacksBlock,
+    ) -> Result<(), net_error> {
+        let req = NetworkRequest::BroadcastBlock(consensus_hash, block);
+        self.send_request(req)
+    }
 }
 
 impl NetworkHandleServer {
@@ -204,6 +220,18 @@ pub enum PeerNetworkWorkState {
 
 pub type PeerMap = HashMap<usize, ConversationP2P>;
 
+/// A compact block that we could not rebuild from our mempool alone, and whose missing
+/// transactions we have asked the sender for.
+pub struct PendingCompactBlock {
+    pub event_id: usize,
+    pub neighbor_key: NeighborKey,
+    pub consensus_hash: ConsensusHash,
+    pub header: StacksBlockHeader,
+    pub txs: Vec<Option<StacksTransaction>>,
+    pub missing: Vec<u16>, // indexes of the transactions we asked for
+    pub reply_handle: ReplyHandleP2P,
+}
+
 pub struct PeerNetwork {
     pub local_peer: LocalPeer,
     pub peer_version: u32,
@@ -264,6 +292,13 @@ pub struct PeerNetwork {
     // peer block download state
     pub block_downloader: Option<BlockDownloader>,
 
+    // mempool sync state
+    pub mempool_sync: Option<MempoolSyncState>,
+    pub mempool_sync_deadline: u64,
+
+    // compact blocks waiting on transactions from their senders
+    pub pending_compact_blocks: Vec<PendingCompactBlock>,
+
     // do we need to do a prune at the end of the work state cycle?
     pub do_prune: bool,
 
@@ -351,6 +386,11 @@ impl PeerNetwork {
 
             block_downloader: None,
 
+            mempool_sync: None,
+            mempool_sync_deadline: 0,
+
+            pending_compact_blocks: vec![],
+
             do_prune: false,
 
             prune_deadline: 0,
@@ -936,9 +976,50 @@ impl PeerNetwork {
                 self.broadcast_message(neighbor_keys, relay_hints, msg);
                 Ok(())
             }
+            NetworkRequest::BroadcastBlock(consensus_hash, block) => {
+                let neighbor_keys = self.sample_broadcast_peers(&vec![], &block)?;
+                let (compact_neighbor_keys, full_neighbor_keys) =
+                    self.split_compact_block_recipients(neighbor_keys);
+
+                if compact_neighbor_keys.len() > 0 {
+                    // these peers will already have most of this block's transactions in their
+                    // mempools
+                    let compact_block = CompactBlockData::from_block(&consensus_hash, &block);
+                    self.broadcast_message(
+                        compact_neighbor_keys,
+                        vec![],
+                        StacksMessageType::CompactBlock(compact_block),
+                    );
+                }
+                if full_neighbor_keys.len() > 0 {
+                    self.broadcast_message(
+                        full_neighbor_keys,
+                        vec![],
+                        StacksMessageType::Blocks(BlocksData {
+                            blocks: vec![(consensus_hash, block)],
+                        }),
+                    );
+                }
+                Ok(())
+            }
         }
     }
 
+    /// Split a list of broadcast recipients into the ones that advertised compact block support
+    /// in their handshakes, and the ones that did not (and must be sent whole blocks).
+    pub fn split_compact_block_recipients(
+        &self,
+        neighbor_keys: Vec<NeighborKey>,
+    ) -> (Vec<NeighborKey>, Vec<NeighborKey>) {
+        neighbor_keys.into_iter().partition(|nk| {
+            self.events
+                .get(nk)
+                .and_then(|event_id| self.peers.get(event_id))
+                .map(|convo| convo.supports_compact_blocks())
+                .unwrap_or(false)
+        })
+    }
+
     /// Process any handle requests from other threads.
     /// Returns the number of requests dispatched.
     /// This method does not block.
@@ -2755,6 +2836,47 @@ impl PeerNetwork {
         }
     }
 
+    /// Find the sortition in which a pushed block was chosen.  Returns None if the sortition
+    /// is unknown, not on the valid PoX fork, or chose a different block.
+    fn find_pushed_block_sortition(
+        &self,
+        sortdb: &SortitionDB,
+        consensus_hash: &ConsensusHash,
+        block_hash: &BlockHeaderHash,
+    ) -> Option<BlockSnapshot> {
+        let sn = match SortitionDB::get_block_snapshot_consensus(&sortdb.conn(), consensus_hash) {
+            Ok(Some(sn)) => sn,
+            Ok(None) => {
+                // ignore
+                return None;
+            }
+            Err(e) => {
+                info!(
+                    "{:?}: Failed to query block snapshot for {}: {:?}",
+                    &self.local_peer, consensus_hash, &e
+                );
+                return None;
+            }
+        };
+
+        if !sn.pox_valid {
+            info!(
+                "{:?}: Failed to query snapshot for {}: not on the valid PoX fork",
+                &self.local_peer, consensus_hash
+            );
+            return None;
+        }
+
+        if sn.winning_stacks_block_hash != *block_hash {
+            info!(
+                "{:?}: Ignoring block {} -- winning block was {} (sortition: {})",
+                &self.local_peer, block_hash, sn.winning_stacks_block_hash, sn.sortition
+            );
+            return None;
+        }
+        Some(sn)
+    }
+
     /// Handle unsolicited BlocksData.
     /// Don't (yet) validate the data, but do update our inv for the peer that sent it, if we have
     /// an outbound connection to that peer.  Accept the blocks data either way if it corresponds
@@ -2802,40 +2924,14 @@ impl PeerNetwork {
 
         for (consensus_hash, block) in new_blocks.blocks.iter() {
             let sn =
-                match SortitionDB::get_block_snapshot_consensus(&sortdb.conn(), &consensus_hash) {
-                    Ok(Some(sn)) => sn,
-                    Ok(None) => {
-                        // ignore
-                        continue;
-                    }
-                    Err(e) => {
-                        info!(
-                            "{:?}: Failed to query block snapshot for {}: {:?}",
-                            &self.local_peer, consensus_hash, &e
-                        );
+                match self.find_pushed_block_sortition(sortdb, consensus_hash, &block.block_hash())
+                {
+                    Some(sn) => sn,
+                    None => {
                         continue;
                     }
                 };
 
-            if !sn.pox_valid {
-                info!(
-                    "{:?}: Failed to query snapshot for {}: not on the valid PoX fork",
-                    &self.local_peer, consensus_hash
-                );
-                continue;
-            }
-
-            if sn.winning_stacks_block_hash != block.block_hash() {
-                info!(
-                    "{:?}: Ignoring block {} -- winning block was {} (sortition: {})",
-                    &self.local_peer,
-                    block.block_hash(),
-                    sn.winning_stacks_block_hash,
-                    sn.sortition
-                );
-                continue;
-            }
-
             // only bother updating the inventory for this event's peer if we have an outbound
             // connection to it.
             if let Some(outbound_neighbor_key) = outbound_neighbor_key_opt.as_ref() {
@@ -2850,12 +2946,318 @@ impl PeerNetwork {
         }
     }
 
+    /// Handle an unsolicited CompactBlock.
+    /// Update our inv for the peer that sent it as with BlocksData, and then try to rebuild the
+    /// block from our mempool.  Returns the block if we could rebuild it.  If we're only
+    /// missing some of its transactions, ask the sender for them and finish the block once they
+    /// arrive (see process_pending_compact_blocks()).  If the block can't be rebuilt at all,
+    /// have the downloader fetch it in full instead.
+    /// Mask errors.
+    fn handle_unsolicited_CompactBlock(
+        &mut self,
+        sortdb: &SortitionDB,
+        mempool: &MemPoolDB,
+        event_id: usize,
+        compact_block: &CompactBlockData,
+    ) -> Option<StacksBlock> {
+        let (remote_neighbor_key, remote_is_authenticated) = match self.peers.get(&event_id) {
+            Some(convo) => (convo.to_neighbor_key(), convo.is_authenticated()),
+            None => {
+                test_debug!(
+                    "{:?}: No such neighbor event={}",
+                    &self.local_peer,
+                    event_id
+                );
+                return None;
+            }
+        };
+
+        if !remote_is_authenticated {
+            // drop -- a correct peer will have authenticated before sending this message
+            test_debug!(
+                "{:?}: Drop unauthenticated CompactBlock from {:?}",
+                &self.local_peer,
+                &remote_neighbor_key
+            );
+            return None;
+        }
+
+        let block_hash = compact_block.block_hash();
+        let sn =
+            self.find_pushed_block_sortition(sortdb, &compact_block.consensus_hash, &block_hash)?;
+
+        // only bother updating the inventory for this event's peer if we have an outbound
+        // connection to it.
+        if let Some(outbound_neighbor_key) = self.find_outbound_neighbor(event_id) {
+            self.handle_unsolicited_inv_update(
+                sortdb,
+                event_id,
+                &outbound_neighbor_key,
+                &sn.consensus_hash,
+                false,
+            );
+        }
+
+        if self
+            .pending_compact_blocks
+            .iter()
+            .any(|pending| pending.header.block_hash() == block_hash)
+        {
+            // already fetching the rest of this block
+            return None;
+        }
+
+        let txs = match Relayer::fill_compact_block(mempool, compact_block) {
+            Ok(txs) => txs,
+            Err(e) => {
+                info!(
+                    "{:?}: Failed to fill compact block {}/{} from mempool: {:?}",
+                    &self.local_peer, &compact_block.consensus_hash, &block_hash, &e
+                );
+                self.hint_download_rescan();
+                return None;
+            }
+        };
+
+        let missing: Vec<u16> = txs
+            .iter()
+            .enumerate()
+            .filter_map(|(i, tx_opt)| {
+                if tx_opt.is_none() {
+                    Some(i as u16)
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        debug!(
+            "{:?}: Process CompactBlock {}/{} from {:?}: missing {} of {} transactions",
+            &self.local_peer,
+            &compact_block.consensus_hash,
+            &block_hash,
+            &remote_neighbor_key,
+            missing.len(),
+            txs.len()
+        );
+
+        if missing.len() == 0 {
+            let block_opt = Relayer::assemble_compact_block(&compact_block.header, &txs);
+            if block_opt.is_none() {
+                self.hint_download_rescan();
+            }
+            return block_opt;
+        }
+
+        if (self.pending_compact_blocks.len() as u64) >= self.connection_opts.max_inflight_blocks {
+            debug!(
+                "{:?}: Too many pending compact blocks; will download {}/{} instead",
+                &self.local_peer, &compact_block.consensus_hash, &block_hash
+            );
+            self.hint_download_rescan();
+            return None;
+        }
+
+        let request = GetBlockTxsData {
+            consensus_hash: compact_block.consensus_hash.clone(),
+            block_hash: block_hash.clone(),
+            indexes: missing.clone(),
+        };
+        match self.request_block_txs(event_id, request) {
+            Ok(reply_handle) => {
+                self.pending_compact_blocks.push(PendingCompactBlock {
+                    event_id,
+                    neighbor_key: remote_neighbor_key,
+                    consensus_hash: compact_block.consensus_hash.clone(),
+                    header: compact_block.header.clone(),
+                    txs,
+                    missing,
+                    reply_handle,
+                });
+            }
+            Err(e) => {
+                info!(
+                    "{:?}: Failed to request transactions for compact block {}/{}: {:?}",
+                    &self.local_peer, &compact_block.consensus_hash, &block_hash, &e
+                );
+                self.hint_download_rescan();
+            }
+        }
+        None
+    }
+
+    /// Ask a neighbor for some of the transactions in a block
+    fn request_block_txs(
+        &mut self,
+        event_id: usize,
+        request: GetBlockTxsData,
+    ) -> Result<ReplyHandleP2P, net_error> {
+        let mut reply_handle = match self.peers.get_mut(&event_id) {
+            Some(convo) => {
+                let message = convo.sign_message(
+                    &self.chain_view,
+                    &self.local_peer.private_key,
+                    StacksMessageType::GetBlockTxs(request),
+                )?;
+                convo.send_signed_request(message, self.connection_opts.timeout)?
+            }
+            None => {
+                return Err(net_error::PeerNotConnected);
+            }
+        };
+
+        self.saturate_p2p_socket(event_id, &mut reply_handle)?;
+        Ok(reply_handle)
+    }
+
+    /// Finish the compact blocks whose missing transactions have arrived, and add them to the
+    /// blocks pushed to us.  Compact blocks that can't be finished will be downloaded in full.
+    fn process_pending_compact_blocks(&mut self, network_result: &mut NetworkResult) -> () {
+        let mut failed = false;
+        let mut still_pending = vec![];
+        let pending_compact_blocks = mem::replace(&mut self.pending_compact_blocks, vec![]);
+        for mut pending in pending_compact_blocks.into_iter() {
+            let block_hash = pending.header.block_hash();
+            if let Err(e) = self.saturate_p2p_socket(pending.event_id, &mut pending.reply_handle) {
+                debug!(
+                    "{:?}: Failed to request transactions for compact block {}/{}: {:?}",
+                    &self.local_peer, &pending.consensus_hash, &block_hash, &e
+                );
+                failed = true;
+                continue;
+            }
+
+            let message = match pending.reply_handle.try_send_recv() {
+                Ok(message) => message,
+                Err(Ok(reply_handle)) => {
+                    // try again later
+                    pending.reply_handle = reply_handle;
+                    still_pending.push(pending);
+                    continue;
+                }
+                Err(Err(e)) => {
+                    debug!(
+                        "{:?}: Failed to get transactions for compact block {}/{}: {:?}",
+                        &self.local_peer, &pending.consensus_hash, &block_hash, &e
+                    );
+                    failed = true;
+                    continue;
+                }
+            };
+
+            let block_txs = match message.payload {
+                StacksMessageType::BlockTxs(data) => data,
+                StacksMessageType::Nack(ref data) => {
+                    debug!(
+                        "{:?}: Event {} NACK'ed transactions for compact block {}/{} with code {}",
+                        &self.local_peer,
+                        pending.event_id,
+                        &pending.consensus_hash,
+                        &block_hash,
+                        data.error_code
+                    );
+                    failed = true;
+                    continue;
+                }
+                _ => {
+                    debug!(
+                        "{:?}: Got unexpected reply to GetBlockTxs: {}",
+                        &self.local_peer,
+                        message.payload.get_message_name()
+                    );
+                    failed = true;
+                    continue;
+                }
+            };
+
+            if block_txs.consensus_hash != pending.consensus_hash
+                || block_txs.block_hash != block_hash
+                || block_txs.txs.len() != pending.missing.len()
+            {
+                debug!(
+                    "{:?}: Got mismatched transactions for compact block {}/{}",
+                    &self.local_peer, &pending.consensus_hash, &block_hash
+                );
+                failed = true;
+                continue;
+            }
+
+            for (index, tx) in pending.missing.iter().zip(block_txs.txs.into_iter()) {
+                pending.txs[*index as usize] = Some(tx);
+            }
+
+            match Relayer::assemble_compact_block(&pending.header, &pending.txs) {
+                Some(block) => {
+                    let blocks_data = BlocksData {
+                        blocks: vec![(pending.consensus_hash, block)],
+                    };
+                    if let Some(blocks_msgs) =
+                        network_result.pushed_blocks.get_mut(&pending.neighbor_key)
+                    {
+                        blocks_msgs.push(blocks_data);
+                    } else {
+                        network_result
+                            .pushed_blocks
+                            .insert(pending.neighbor_key, vec![blocks_data]);
+                    }
+                }
+                None => {
+                    failed = true;
+                }
+            }
+        }
+
+        self.pending_compact_blocks = still_pending;
+        if failed {
+            // fall back to downloading them
+            self.hint_download_rescan();
+        }
+    }
+
+    /// Handle a GetMempool request that a conversation forwarded to us (it has already been
+    /// rate-limited).  Reply with the page of our mempool transactions that the requester is
+    /// missing.
+    fn handle_unsolicited_GetMempool(
+        &mut self,
+        mempool: &MemPoolDB,
+        event_id: usize,
+        preamble: &Preamble,
+        request: &GetMempoolData,
+    ) -> Result<(), net_error> {
+        let payload = StacksMessageType::MempoolTxs(PeerNetwork::make_mempool_txs_response(
+            mempool, request,
+        )?);
+
+        let mut reply_handle = match self.peers.get_mut(&event_id) {
+            Some(convo) => {
+                let reply = convo.sign_reply(
+                    &self.chain_view,
+                    &self.local_peer.private_key,
+                    payload,
+                    preamble.seq,
+                )?;
+                convo.relay_signed_message(reply)?
+            }
+            None => {
+                return Err(net_error::PeerNotConnected);
+            }
+        };
+
+        let (num_sent, flushed) = self.saturate_p2p_socket(event_id, &mut reply_handle)?;
+        if num_sent > 0 || !flushed {
+            // keep trying to send
+            self.add_relay_handle(event_id, reply_handle);
+        }
+        Ok(())
+    }
+
     /// Handle unsolicited messages propagated up to us from our ongoing ConversationP2Ps.
     /// Return messages that we couldn't handle here, but key them by neighbor, not event.
     /// Drop invalid messages.
     fn handle_unsolicited_messages(
         &mut self,
         sortdb: &SortitionDB,
+        mempool: &MemPoolDB,
         mut unsolicited: HashMap<usize, Vec<StacksMessage>>,
     ) -> Result<HashMap<NeighborKey, Vec<StacksMessage>>, net_error> {
         let mut unhandled: HashMap<NeighborKey, Vec<StacksMessage>> = HashMap::new();
@@ -2881,6 +3283,44 @@ impl PeerNetwork {
                     StacksMessageType::MicroblocksAvailable(ref new_mblocks) => {
                         self.handle_unsolicited_MicroblocksAvailable(sortdb, event_id, new_mblocks);
                     }
+                    StacksMessageType::GetMempool(ref request) => {
+                        // answer it here, since we have the mempool
+                        if let Err(_e) = self.handle_unsolicited_GetMempool(
+                            mempool,
+                            event_id,
+                            &message.preamble,
+                            request,
+                        ) {
+                            debug!(
+                                "{:?}: Failed to answer GetMempool from event {}: {:?}",
+                                &self.local_peer, event_id, &_e
+                            );
+                        }
+                    }
+                    StacksMessageType::CompactBlock(ref compact_block) => {
+                        // update inv state for this peer, and rebuild the block if we can
+                        if let Some(block) = self.handle_unsolicited_CompactBlock(
+                            sortdb,
+                            mempool,
+                            event_id,
+                            compact_block,
+                        ) {
+                            // forward to relayer for processing, as if it had been pushed in
+                            // full
+                            let blocks_message = StacksMessage {
+                                preamble: message.preamble.clone(),
+                                relayers: message.relayers.clone(),
+                                payload: StacksMessageType::Blocks(BlocksData {
+                                    blocks: vec![(compact_block.consensus_hash.clone(), block)],
+                                }),
+                            };
+                            if let Some(msgs) = unhandled.get_mut(&neighbor_key) {
+                                msgs.push(blocks_message);
+                            } else {
+                                unhandled.insert(neighbor_key.clone(), vec![blocks_message]);
+                            }
+                        }
+                    }
                     StacksMessageType::Blocks(ref new_blocks) => {
                         // update inv state for this peer
                         self.handle_unsolicited_BlocksData(sortdb, event_id, new_blocks);
@@ -3065,6 +3505,7 @@ impl PeerNetwork {
         network_result: &mut NetworkResult,
         sortdb: &SortitionDB,
         chainstate: &mut StacksChainState,
+        mempool: &MemPoolDB,
         dns_client_opt: Option<&mut DNSClient>,
         download_backpressure: bool,
         mut poll_state: NetworkPollState,
@@ -3102,9 +3543,13 @@ impl PeerNetwork {
             );
             self.deregister_peer(error_event);
         }
-        let unhandled_messages = self.handle_unsolicited_messages(sortdb, unsolicited_messages)?;
+        let unhandled_messages =
+            self.handle_unsolicited_messages(sortdb, mempool, unsolicited_messages)?;
         network_result.consume_unsolicited(unhandled_messages);
 
+        // finish compact blocks whose missing transactions have arrived
+        self.process_pending_compact_blocks(network_result);
+
         // schedule now-authenticated inbound convos for pingback
         self.schedule_network_pingbacks(unauthenticated_inbounds)?;
 
@@ -3136,6 +3581,9 @@ impl PeerNetwork {
         // In parallel, do a neighbor walk
         self.do_network_neighbor_walk()?;
 
+        // In parallel, fetch transactions we're missing from a neighbor's mempool
+        self.do_network_mempool_sync(mempool, network_result)?;
+
         // remove timed-out requests from other threads
         for (_, convo) in self.peers.iter_mut() {
             convo.clear_timeouts();
@@ -3223,7 +3671,7 @@ impl PeerNetwork {
                 network.chain_view.clone(),
                 &network.peers,
                 sortdb,
-                &network.peerdb,
+                &mut network.peerdb,
                 chainstate,
                 mempool,
                 http_poll_state,
@@ -3233,10 +3681,16 @@ impl PeerNetwork {
             Ok(())
         })?;
 
+        // drop any connected peers that the admin RPC interface just denied
+        for event_id in self.http.take_p2p_disconnects() {
+            self.deregister_peer(event_id);
+        }
+
         self.dispatch_network(
             &mut result,
             sortdb,
             chainstate,
+            mempool,
             dns_client_opt,
             download_backpressure,
             p2p_poll_state,
@@ -37,12 +37,14 @@ use net::*;
 
 use chainstate::burn::ConsensusHash;
 use chainstate::coordinator::comm::CoordinatorChannels;
+use chainstate::stacks::db::unconfirmed::UnconfirmedMicroblockReceipts;
 use chainstate::stacks::db::{StacksChainState, StacksEpochReceipt, StacksHeaderInfo};
 use chainstate::stacks::events::StacksTransactionReceipt;
 use chainstate::stacks::StacksBlockHeader;
 use chainstate::stacks::StacksBlockId;
 
 use core::mempool::*;
+use monitoring;
 
 use chainstate::burn::db::sortdb::{
     PoxId, SortitionDB, SortitionDBConn, SortitionHandleConn, SortitionId,
@@ -50,6 +52,7 @@ use chainstate::burn::db::sortdb::{
 
 use burnchains::Burnchain;
 use burnchains::BurnchainView;
+use burnchains::Txid;
 
 use util::get_epoch_time_secs;
 use util::hash::Sha512Trunc256Sum;
@@ -90,6 +93,14 @@ pub struct RelayerStats {
 
 pub struct ProcessedNetReceipts {
     pub mempool_txs_added: Vec<StacksTransaction>,
+    /// txids of transactions that were replaced (i.e. fee-bumped) out of the mempool
+    pub mempool_txs_dropped: Vec<Txid>,
+    /// txids of transactions that were garbage-collected out of the mempool
+    pub mempool_txs_garbage_collected: Vec<Txid>,
+    /// txids of transactions that were evicted from the mempool when it was full
+    pub mempool_txs_evicted: Vec<Txid>,
+    /// receipts of the microblocks newly applied to the unconfirmed state
+    pub processed_unconfirmed_microblocks: Vec<UnconfirmedMicroblockReceipts>,
 }
 
 /// Private trait for keeping track of messages that can be relayed, so we can identify the peers
@@ -124,6 +135,17 @@ impl RelayPayload for StacksBlock {
     }
 }
 
+impl RelayPayload for CompactBlockData {
+    fn get_digest(&self) -> Sha512Trunc256Sum {
+        // same digest as the block it encodes, so relay stats count them as duplicates
+        let h = self.block_hash();
+        Sha512Trunc256Sum(h.0)
+    }
+    fn get_id(&self) -> String {
+        format!("CompactBlock({})", self.block_hash())
+    }
+}
+
 impl RelayPayload for StacksMicroblock {
     fn get_digest(&self) -> Sha512Trunc256Sum {
         let h = self.block_hash();
@@ -897,11 +919,12 @@ impl Relayer {
         Ok((mblock_datas, bad_neighbors))
     }
 
-    /// Set up the unconfirmed chain state off of the canonical chain tip
+    /// Set up the unconfirmed chain state off of the canonical chain tip.
+    /// Returns the receipts of any microblocks that were newly applied to it.
     pub fn setup_unconfirmed_state(
         chainstate: &mut StacksChainState,
         sortdb: &SortitionDB,
-    ) -> Result<(), Error> {
+    ) -> Result<Vec<UnconfirmedMicroblockReceipts>, Error> {
         let (canonical_consensus_hash, canonical_block_hash) =
             SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn())?;
         let canonical_tip = StacksBlockHeader::make_index_block_hash(
@@ -910,8 +933,9 @@ impl Relayer {
         );
         // setup unconfirmed state off of this tip
         debug!("Reload unconfirmed state");
-        chainstate.reload_unconfirmed_state(&sortdb.index_conn(), canonical_tip)?;
-        Ok(())
+        let (_, _, microblock_receipts) =
+            chainstate.reload_unconfirmed_state(&sortdb.index_conn(), canonical_tip)?;
+        Ok(microblock_receipts)
     }
 
     /// Process blocks and microblocks that we recieved, both downloaded (confirmed) and streamed
@@ -920,6 +944,7 @@ impl Relayer {
     /// * list of confirmed microblock consensus hashes for newly-discovered microblock streams, so we can turn them into MicroblocksAvailable messages
     /// * list of unconfirmed microblocks that got pushed to us, as well as their relayers (so we can forward them)
     /// * list of neighbors that served us invalid data (so we can ban them)
+    /// * receipts of the microblocks that were newly applied to the unconfirmed state
     pub fn process_new_blocks(
         network_result: &mut NetworkResult,
         sortdb: &mut SortitionDB,
@@ -931,6 +956,7 @@ impl Relayer {
             Vec<ConsensusHash>,
             Vec<(Vec<RelayData>, MicroblocksData)>,
             Vec<NeighborKey>,
+            Vec<UnconfirmedMicroblockReceipts>,
         ),
         net_error,
     > {
@@ -1002,8 +1028,9 @@ impl Relayer {
             }
         }
 
-        if network_result.uploaded_microblocks.len() > 0 {
-            Relayer::setup_unconfirmed_state(chainstate, sortdb)?;
+        let mut microblock_receipts = vec![];
+        if network_result.uploaded_microblocks.len() > 0 || new_microblocks.len() > 0 {
+            microblock_receipts = Relayer::setup_unconfirmed_state(chainstate, sortdb)?;
         }
 
         Ok((
@@ -1011,6 +1038,7 @@ impl Relayer {
             new_confirmed_microblocks.into_iter().collect(),
             new_microblocks,
             bad_neighbors,
+            microblock_receipts,
         ))
     }
 
@@ -1057,13 +1085,22 @@ impl Relayer {
     }
 
     /// Store all new transactions we received, and return the list of transactions that we need to
-    /// forward (as well as their relay hints).  Also, garbage-collect the mempool.
+    /// forward (as well as their relay hints), and the list of transactions we stored from a mempool
+    /// sync (which we do not forward).  Also, garbage-collect the mempool, and return the
+    /// txids of the transactions that were collected.
     fn process_transactions(
         network_result: &mut NetworkResult,
         sortdb: &SortitionDB,
         chainstate: &StacksChainState,
         mempool: &mut MemPoolDB,
-    ) -> Result<Vec<(Vec<RelayData>, StacksTransaction)>, net_error> {
+    ) -> Result<
+        (
+            Vec<(Vec<RelayData>, StacksTransaction)>,
+            Vec<StacksTransaction>,
+            Vec<Txid>,
+        ),
+        net_error,
+    > {
         let (consensus_hash, block_hash, chain_height) =
             match chainstate.get_stacks_chain_tip(sortdb)? {
                 Some(tip) => (tip.consensus_hash, tip.anchored_block_hash, tip.height),
@@ -1072,7 +1109,7 @@ impl Relayer {
                         "No Stacks chain tip; dropping {} transaction(s)",
                         network_result.pushed_transactions.len()
                     );
-                    return Ok(vec![]);
+                    return Ok((vec![], vec![], vec![]));
                 }
             };
 
@@ -1093,7 +1130,17 @@ impl Relayer {
             ret.push((vec![], tx.clone()));
         }
 
+        // fetched from a neighbor's mempool.  Store them, but don't forward them -- they were
+        // already relayed when they were new, and the neighbor we got them from has them.
+        let mut synced = vec![];
+        for tx in network_result.synced_transactions.iter() {
+            if Relayer::store_transaction(mempool, &consensus_hash, &block_hash, tx.clone()) {
+                synced.push(tx.clone());
+            }
+        }
+
         // garbage-collect
+        let mut garbage_collected = vec![];
         if chain_height > MEMPOOL_MAX_TRANSACTION_AGE {
             let min_height = chain_height - MEMPOOL_MAX_TRANSACTION_AGE;
             let mut mempool_tx = mempool.tx_begin()?;
@@ -1102,11 +1149,15 @@ impl Relayer {
                 "Remove all transactions beneath block height {}",
                 min_height
             );
-            MemPoolDB::garbage_collect(&mut mempool_tx, min_height)?;
+            garbage_collected = MemPoolDB::garbage_collect(&mut mempool_tx, min_height)?;
             mempool_tx.commit()?;
         }
 
-        Ok(ret)
+        let (mempool_count, mempool_bytes) = MemPoolDB::get_mempool_size(mempool.conn())?;
+        monitoring::update_mempool_tx_count_gauge(mempool_count as i64);
+        monitoring::update_mempool_tx_bytes_gauge(mempool_bytes as i64);
+
+        Ok((ret, synced, garbage_collected))
     }
 
     pub fn advertize_blocks(&mut self, available: BlocksAvailableMap) -> Result<(), net_error> {
@@ -1118,11 +1169,96 @@ impl Relayer {
         consensus_hash: ConsensusHash,
         block: StacksBlock,
     ) -> Result<(), net_error> {
-        let blocks_data = BlocksData {
-            blocks: vec![(consensus_hash, block)],
-        };
-        self.p2p
-            .broadcast_message(vec![], StacksMessageType::Blocks(blocks_data))
+        self.p2p.broadcast_block(consensus_hash, block)
+    }
+
+    /// Find as many of a compact block's transactions as we can.  Prefilled transactions are
+    /// taken from the message, and the rest are looked up by short ID in the mempool.
+    /// Returns one slot per transaction in the block, which is None if we don't have it.
+    pub fn fill_compact_block(
+        mempool: &MemPoolDB,
+        compact_block: &CompactBlockData,
+    ) -> Result<Vec<Option<StacksTransaction>>, net_error> {
+        let mut txs: Vec<Option<StacksTransaction>> = vec![None; compact_block.short_txids.len()];
+        for (index, tx) in compact_block.prefilled_txs.iter() {
+            txs[*index as usize] = Some(tx.clone());
+        }
+
+        // short IDs of the transactions we still need.  If two of them collide, we'll have to
+        // ask for both.
+        let mut missing: HashMap<u64, usize> = HashMap::new();
+        let mut collisions = HashSet::new();
+        for (i, short_txid) in compact_block.short_txids.iter().enumerate() {
+            if txs[i].is_some() {
+                continue;
+            }
+            if missing.insert(*short_txid, i).is_some() {
+                collisions.insert(*short_txid);
+            }
+        }
+        for short_txid in collisions.iter() {
+            missing.remove(short_txid);
+        }
+
+        if missing.len() == 0 {
+            return Ok(txs);
+        }
+
+        let block_hash = compact_block.block_hash();
+        let mempool_txids = MemPoolDB::get_all_txids(mempool.conn())?;
+        for txid in mempool_txids.iter() {
+            let short_txid = CompactBlockData::short_txid(&block_hash, txid);
+            if let Some(i) = missing.remove(&short_txid) {
+                if let Some(tx_info) = MemPoolDB::get_tx(mempool.conn(), txid)? {
+                    txs[i] = Some(tx_info.tx);
+                }
+                if missing.len() == 0 {
+                    break;
+                }
+            }
+        }
+
+        Ok(txs)
+    }
+
+    /// Put a compact block back together from its header and its transactions.
+    /// Returns None if any transaction is missing, or if the block is not well-formed (i.e. a
+    /// mempool transaction matched a short ID but isn't the one the block committed to).
+    pub fn assemble_compact_block(
+        header: &StacksBlockHeader,
+        txs: &Vec<Option<StacksTransaction>>,
+    ) -> Option<StacksBlock> {
+        let mut block_txs = Vec::with_capacity(txs.len());
+        for tx_opt in txs.iter() {
+            match tx_opt {
+                Some(tx) => block_txs.push(tx.clone()),
+                None => {
+                    return None;
+                }
+            }
+        }
+
+        // run the block through the same checks (including the tx merkle root) as a block we
+        // had received in full.
+        let mut block_bytes = vec![];
+        header
+            .consensus_serialize(&mut block_bytes)
+            .expect("BUG: failed to serialize to a vec");
+        block_txs
+            .consensus_serialize(&mut block_bytes)
+            .expect("BUG: failed to serialize to a vec");
+
+        match StacksBlock::consensus_deserialize(&mut &block_bytes[..]) {
+            Ok(block) => Some(block),
+            Err(e) => {
+                debug!(
+                    "Failed to reassemble compact block {}: {:?}",
+                    header.block_hash(),
+                    &e
+                );
+                None
+            }
+        }
     }
 
     pub fn broadcast_microblock(
@@ -1161,8 +1297,17 @@ impl Relayer {
         mempool: &mut MemPoolDB,
         coord_comms: Option<&CoordinatorChannels>,
     ) -> Result<ProcessedNetReceipts, net_error> {
+        let mut processed_unconfirmed_microblocks = vec![];
         match Relayer::process_new_blocks(network_result, sortdb, chainstate, coord_comms) {
-            Ok((new_blocks, new_confirmed_microblocks, new_microblocks, bad_block_neighbors)) => {
+            Ok((
+                new_blocks,
+                new_confirmed_microblocks,
+                new_microblocks,
+                bad_block_neighbors,
+                microblock_receipts,
+            )) => {
+                processed_unconfirmed_microblocks = microblock_receipts;
+
                 // attempt to relay messages (note that this is all best-effort).
                 // punish bad peers
                 if bad_block_neighbors.len() > 0 {
@@ -1231,7 +1376,8 @@ impl Relayer {
             &_local_peer,
             network_result.pushed_transactions.len()
         );
-        let new_txs = Relayer::process_transactions(network_result, sortdb, chainstate, mempool)?;
+        let (new_txs, synced_txs, mempool_txs_garbage_collected) =
+            Relayer::process_transactions(network_result, sortdb, chainstate, mempool)?;
 
         if new_txs.len() > 0 {
             debug!(
@@ -1241,7 +1387,15 @@ impl Relayer {
             );
         }
 
-        let mut mempool_txs_added = vec![];
+        if synced_txs.len() > 0 {
+            debug!(
+                "{:?}: Stored {} transactions from mempool sync",
+                &_local_peer,
+                synced_txs.len()
+            );
+        }
+
+        let mut mempool_txs_added = synced_txs;
         for (relayers, tx) in new_txs.into_iter() {
             debug!("{:?}: Broadcast tx {}", &_local_peer, &tx.txid());
             mempool_txs_added.push(tx.clone());
@@ -1251,7 +1405,23 @@ impl Relayer {
             }
         }
 
-        let receipts = ProcessedNetReceipts { mempool_txs_added };
+        // find out which transactions were replaced since the last time we checked.  This
+        // includes replacements made by transactions uploaded via HTTP.
+        let (mempool_txs_dropped, mempool_txs_evicted) = {
+            let mut mempool_tx = mempool.tx_begin()?;
+            let dropped = MemPoolDB::take_unannounced_replaced_txids(&mut mempool_tx)?;
+            let evicted = MemPoolDB::take_unannounced_evicted_txids(&mut mempool_tx)?;
+            mempool_tx.commit()?;
+            (dropped, evicted)
+        };
+
+        let receipts = ProcessedNetReceipts {
+            mempool_txs_added,
+            mempool_txs_dropped,
+            mempool_txs_garbage_collected,
+            mempool_txs_evicted,
+            processed_unconfirmed_microblocks,
+        };
 
         Ok(receipts)
     }
@@ -1536,6 +1706,7 @@ mod test {
     use super::*;
     use chainstate::stacks::db::blocks::MINIMUM_TX_FEE;
     use chainstate::stacks::db::blocks::MINIMUM_TX_FEE_RATE_PER_BYTE;
+    use chainstate::stacks::db::test::{chainstate_path, instantiate_chainstate};
     use chainstate::stacks::*;
     use net::asn::*;
     use net::chat::*;
@@ -1711,6 +1882,88 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_relay_fill_and_assemble_compact_block() {
+        let _chainstate = instantiate_chainstate(
+            false,
+            0x80000000,
+            "test_relay_fill_and_assemble_compact_block",
+        );
+        let chainstate_path = chainstate_path("test_relay_fill_and_assemble_compact_block");
+        let mempool = MemPoolDB::open(false, 0x80000000, &chainstate_path).unwrap();
+
+        let block = make_codec_test_block(5);
+        let compact_block = CompactBlockData::from_block(&ConsensusHash([0x11; 20]), &block);
+
+        // empty mempool -- only the prefilled coinbase is available
+        let mut txs = Relayer::fill_compact_block(&mempool, &compact_block).unwrap();
+        assert_eq!(txs.len(), block.txs.len());
+        assert_eq!(txs[0], Some(block.txs[0].clone()));
+        for tx_opt in txs[1..].iter() {
+            assert!(tx_opt.is_none());
+        }
+        assert!(Relayer::assemble_compact_block(&compact_block.header, &txs).is_none());
+
+        // with the rest of the transactions, we get the block back
+        for i in 1..txs.len() {
+            txs[i] = Some(block.txs[i].clone());
+        }
+        assert_eq!(
+            Relayer::assemble_compact_block(&compact_block.header, &txs),
+            Some(block.clone())
+        );
+
+        // transactions in the wrong order don't match the header
+        txs.swap(1, 2);
+        assert!(Relayer::assemble_compact_block(&compact_block.header, &txs).is_none());
+    }
+
+    #[test]
+    fn test_relay_compact_block_recipients() {
+        with_timeout(600, || {
+            let mut peer_1_config = TestPeerConfig::from_port(42000);
+            let peer_2_config = TestPeerConfig::from_port(42002);
+            let mut peer_3_config = TestPeerConfig::from_port(42004);
+
+            // peer 3 predates compact blocks
+            peer_3_config.services = ServiceFlags::RELAY as u16;
+
+            peer_1_config.add_neighbor(&peer_2_config.to_neighbor());
+            peer_1_config.add_neighbor(&peer_3_config.to_neighbor());
+
+            let mut peer_1 = TestPeer::new(peer_1_config);
+            let mut peer_2 = TestPeer::new(peer_2_config);
+            let mut peer_3 = TestPeer::new(peer_3_config);
+
+            let nk_2 = peer_2.to_neighbor().addr;
+            let nk_3 = peer_3.to_neighbor().addr;
+
+            // wait for peer 1 to handshake with both peers
+            loop {
+                let _ = peer_1.step();
+                let _ = peer_2.step();
+                let _ = peer_3.step();
+
+                let handshaked = |nk: &NeighborKey| {
+                    peer_1
+                        .network
+                        .get_neighbor_stats(nk)
+                        .map(|stats| stats.last_handshake_time > 0)
+                        .unwrap_or(false)
+                };
+                if handshaked(&nk_2) && handshaked(&nk_3) {
+                    break;
+                }
+            }
+
+            let (compact, full) = peer_1
+                .network
+                .split_compact_block_recipients(vec![nk_2.clone(), nk_3.clone()]);
+            assert_eq!(compact, vec![nk_2]);
+            assert_eq!(full, vec![nk_3]);
+        })
+    }
+
     #[test]
     fn test_relay_inbound_peer_rankings() {
         let mut relay_stats = RelayerStats::new();
@@ -17,11 +17,13 @@
  along with Blockstack. If not, see <http://www.gnu.org/licenses/>.
 */
 
+use std::cmp;
 use std::fmt;
 use std::io;
 use std::io::prelude::*;
 use std::io::{Read, Seek, SeekFrom, Write};
-use std::net::SocketAddr;
+use std::mem;
+use std::net::{IpAddr, SocketAddr};
 
 use core::mempool::*;
 use net::connection::ConnectionHttp;
@@ -32,6 +34,7 @@ use net::http::*;
 use net::p2p::PeerMap;
 use net::p2p::PeerNetwork;
 use net::ClientError;
+use net::DryRunCallResponse;
 use net::Error as net_error;
 use net::HttpRequestMetadata;
 use net::HttpRequestType;
@@ -47,10 +50,19 @@ use net::StacksHttp;
 use net::StacksHttpMessage;
 use net::StacksMessageCodec;
 use net::StacksMessageType;
+use net::TransactionSimulationResponse;
 use net::UrlString;
+use net::DENY_BAN_DURATION;
 use net::HTTP_REQUEST_ID_RESERVED;
 use net::MAX_NEIGHBORS_DATA_LEN;
-use net::{AccountEntryResponse, CallReadOnlyResponse, ContractSrcResponse, MapEntryResponse};
+use net::{
+    AccountEntryResponse, CallReadOnlyResponse, ContractSrcResponse, MapEntryResponse,
+    MarfProofResponse,
+};
+use net::{AdminCidrList, AdminRequest, RPCAdminCidrs, RPCAdminPeer, RPCAdminUpdateResponse};
+use net::{ContractEventsQuery, ContractEventsResponse, RPCContractEvent};
+use net::{RPCConfirmedTxInfo, RPCPendingTxInfo, TransactionStatusResponse};
+use net::{RPCFeeEstimate, RPCFeeEstimateResponse};
 use net::{RPCNeighbor, RPCNeighborsInfo};
 use net::{RPCPeerInfoData, RPCPoxInfoData};
 use std::collections::HashMap;
@@ -67,11 +79,12 @@ use chainstate::burn::ConsensusHash;
 use chainstate::stacks::db::{
     blocks::MINIMUM_TX_FEE_RATE_PER_BYTE, BlockStreamData, StacksChainState,
 };
+use chainstate::stacks::events::StacksTransactionEvent;
 use chainstate::stacks::Error as chain_error;
 use chainstate::stacks::*;
 use monitoring;
 
-use rusqlite::{DatabaseName, NO_PARAMS};
+use rusqlite::{DatabaseName, Transaction, NO_PARAMS};
 
 use util::db::DBConn;
 use util::db::Error as db_error;
@@ -82,11 +95,12 @@ use util::hash::Hash160;
 use crate::version_string;
 
 use vm::{
-    clarity::ClarityConnection,
+    clarity::{ClarityConnection, Error as clarity_error},
     costs::{ExecutionCost, LimitedCostTracker},
     database::{
         marf::ContractCommitment, ClarityDatabase, ClaritySerializable, MarfedKV, STXBalance,
     },
+    errors::{Error as vm_error, RuntimeErrorType},
     types::{PrincipalData, QualifiedContractIdentifier, StandardPrincipalData},
     ClarityName, ContractName, SymbolicExpression, Value,
 };
@@ -96,6 +110,9 @@ use rand::thread_rng;
 
 pub const STREAM_CHUNK_SIZE: u64 = 4096;
 
+/// How many recent anchored blocks to consider when estimating fee rates
+pub const FEE_ESTIMATE_NUM_BLOCKS: u64 = 10;
+
 #[derive(Default)]
 pub struct RPCHandlerArgs<'a> {
     pub exit_at_block_height: Option<&'a u64>,
@@ -128,6 +145,9 @@ pub struct ConversationHttp {
     pending_request: Option<ReplyHandleHttp>,
     pending_response: Option<HttpResponseType>,
     pending_error_response: Option<HttpResponseType>,
+
+    // p2p peers that an admin request denied, which the peer network should drop
+    p2p_disconnects: Vec<usize>,
 }
 
 impl fmt::Display for ConversationHttp {
@@ -375,6 +395,7 @@ impl ConversationHttp {
             pending_request: None,
             pending_response: None,
             pending_error_response: None,
+            p2p_disconnects: vec![],
             keep_alive: true,
             total_request_count: 0,
             total_reply_count: 0,
@@ -728,6 +749,192 @@ impl ConversationHttp {
         response.send(http, fd).map(|_| ())
     }
 
+    /// Handle a POST to estimate the cost and fee rate of a transaction payload, given the chain
+    /// tip.  The execution cost is measured by running the payload in a throwaway block on top of
+    /// the tip, which is always rolled back; the fee rates are the 25th, 50th, and 75th percentiles of the fee
+    /// rates paid in the last FEE_ESTIMATE_NUM_BLOCKS blocks, and never less than the minimum.
+    fn handle_fee_rate_estimate<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        payload: &TransactionPayload,
+        sender: Option<&PrincipalData>,
+        estimated_len: Option<u64>,
+        options: &ConnectionOptions,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+
+        let estimated_len = match estimated_len {
+            Some(len) => len,
+            None => StacksTransaction::new(
+                TransactionVersion::Testnet,
+                TransactionAuth::Standard(TransactionSpendingCondition::new_initial_sighash()),
+                payload.clone(),
+            )
+            .tx_len(),
+        };
+
+        let run_result = match payload {
+            TransactionPayload::ContractCall(ref cc) => {
+                // the call may branch on or debit tx-sender, so it must be the real sender
+                let sender = match sender {
+                    Some(sender) => sender.clone(),
+                    None => {
+                        return HttpResponseType::BadRequestJSON(
+                            response_metadata,
+                            json!({
+                                "error": "Failed to estimate transaction cost",
+                                "reason": "A sender is required to estimate a contract-call",
+                            }),
+                        )
+                        .send(http, fd)
+                        .map(|_| ());
+                    }
+                };
+                let contract_identifier = QualifiedContractIdentifier::new(
+                    cc.address.clone().into(),
+                    cc.contract_name.clone(),
+                );
+
+                // the call may write, so run it in a throwaway block instead of a read-only one
+                chainstate.with_throwaway_clarity_tx(
+                    &sortdb.index_conn(),
+                    tip,
+                    &options.simulation_call_limit,
+                    |clarity_tx| {
+                        clarity_tx.connection().as_transaction(|tx_conn| {
+                            let cost_before = tx_conn.cost_so_far();
+                            tx_conn.run_contract_call(
+                                &sender,
+                                &contract_identifier,
+                                cc.function_name.as_str(),
+                                &cc.function_args,
+                                |_, _| false,
+                            )?;
+                            let mut total_cost = tx_conn.cost_so_far();
+                            total_cost
+                                .sub(&cost_before)
+                                .expect("BUG: total block cost decreased");
+                            Ok(total_cost)
+                        })
+                    },
+                )
+            }
+            TransactionPayload::SmartContract(ref sc) => {
+                // instantiate under the sender if given, and a placeholder principal otherwise
+                let sender = match sender {
+                    Some(PrincipalData::Standard(sender)) => sender.clone(),
+                    Some(PrincipalData::Contract(_)) => {
+                        return HttpResponseType::BadRequestJSON(
+                            response_metadata,
+                            json!({
+                                "error": "Failed to estimate transaction cost",
+                                "reason": "Contracts can only be instantiated by a standard principal",
+                            }),
+                        )
+                        .send(http, fd)
+                        .map(|_| ());
+                    }
+                    None => StandardPrincipalData::transient(),
+                };
+                let contract_identifier =
+                    QualifiedContractIdentifier::new(sender.clone(), sc.name.clone());
+                let code_body = sc.code_body.to_string();
+
+                chainstate.with_throwaway_clarity_tx(
+                    &sortdb.index_conn(),
+                    tip,
+                    &options.simulation_call_limit,
+                    |clarity_tx| {
+                        clarity_tx.connection().as_transaction(|tx_conn| {
+                            let cost_before = tx_conn.cost_so_far();
+                            let (contract_ast, _) =
+                                tx_conn.analyze_smart_contract(&contract_identifier, &code_body)?;
+                            tx_conn.initialize_smart_contract(
+                                &contract_identifier,
+                                &contract_ast,
+                                &code_body,
+                                |_, _| false,
+                            )?;
+                            let mut total_cost = tx_conn.cost_so_far();
+                            total_cost
+                                .sub(&cost_before)
+                                .expect("BUG: total block cost decreased");
+                            Ok(total_cost)
+                        })
+                    },
+                )
+            }
+            _ => Ok(Some(Ok(ExecutionCost::zero()))),
+        };
+
+        let cost_res: Result<ExecutionCost, clarity_error> = match run_result {
+            Ok(Some(cost_res)) => cost_res,
+            Ok(None) => {
+                return HttpResponseType::NotFound(
+                    response_metadata,
+                    format!("No such chain tip {}", tip),
+                )
+                .send(http, fd)
+                .map(|_| ());
+            }
+            Err(e) => {
+                warn!("Failed to estimate transaction cost: {:?}", &e);
+                return HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to instantiate throwaway block".to_string(),
+                )
+                .send(http, fd)
+                .map(|_| ());
+            }
+        };
+
+        let response = match cost_res {
+            Ok(estimated_cost) => {
+                let mut fee_rates =
+                    chainstate.get_recent_fee_rates(tip, FEE_ESTIMATE_NUM_BLOCKS)?;
+                fee_rates.sort();
+
+                let estimations = [25, 50, 75]
+                    .iter()
+                    .map(|percentile| {
+                        let fee_rate = if fee_rates.len() > 0 {
+                            let idx = (fee_rates.len() - 1) * percentile / 100;
+                            cmp::max(fee_rates[idx], MINIMUM_TX_FEE_RATE_PER_BYTE)
+                        } else {
+                            MINIMUM_TX_FEE_RATE_PER_BYTE
+                        };
+                        RPCFeeEstimate {
+                            fee_rate,
+                            fee: fee_rate.saturating_mul(estimated_len),
+                        }
+                    })
+                    .collect();
+
+                HttpResponseType::FeeRateEstimate(
+                    response_metadata,
+                    RPCFeeEstimateResponse {
+                        estimated_cost,
+                        estimated_len,
+                        estimations,
+                    },
+                )
+            }
+            Err(e) => HttpResponseType::BadRequestJSON(
+                response_metadata,
+                json!({
+                    "error": "Failed to estimate transaction cost",
+                    "reason": e.to_string(),
+                }),
+            ),
+        };
+
+        response.send(http, fd).map(|_| ())
+    }
+
     /// Handle a GET on an existing account, given the current chain tip.  Optionally supplies a
     /// MARF proof for each account detail loaded from the chain tip.
     fn handle_get_account_entry<W: Write>(
@@ -743,24 +950,32 @@ impl ConversationHttp {
         let response_metadata = HttpResponseMetadata::from(req);
 
         let data = chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
-            clarity_tx.with_clarity_db_readonly(|clarity_db| {
+            clarity_tx.with_clarity_db_readonly(|clarity_db| -> Result<_, vm_error> {
                 let key = ClarityDatabase::make_key_for_account_balance(&account);
                 let block_height = clarity_db.get_current_burnchain_block_height() as u64;
-                let (balance, balance_proof) = clarity_db
-                    .get_with_proof::<STXBalance>(&key)
-                    .map(|(a, b)| (a, format!("0x{}", b.to_hex())))
-                    .unwrap_or_else(|| (STXBalance::zero(), "".into()));
-                let balance_proof = if with_proof {
-                    Some(balance_proof)
+                let (balance, balance_proof) = if with_proof {
+                    clarity_db
+                        .get_with_proof::<STXBalance>(&key)?
+                        .map(|(a, b)| (a, Some(format!("0x{}", b.to_hex()))))
+                        .unwrap_or_else(|| (STXBalance::zero(), Some("".into())))
                 } else {
-                    None
+                    clarity_db
+                        .get::<STXBalance>(&key)
+                        .map(|a| (a, None))
+                        .unwrap_or_else(|| (STXBalance::zero(), None))
                 };
                 let key = ClarityDatabase::make_key_for_account_nonce(&account);
-                let (nonce, nonce_proof) = clarity_db
-                    .get_with_proof(&key)
-                    .map(|(a, b)| (a, format!("0x{}", b.to_hex())))
-                    .unwrap_or_else(|| (0, "".into()));
-                let nonce_proof = if with_proof { Some(nonce_proof) } else { None };
+                let (nonce, nonce_proof) = if with_proof {
+                    clarity_db
+                        .get_with_proof(&key)?
+                        .map(|(a, b)| (a, Some(format!("0x{}", b.to_hex()))))
+                        .unwrap_or_else(|| (0, Some("".into())))
+                } else {
+                    clarity_db
+                        .get(&key)
+                        .map(|a| (a, None))
+                        .unwrap_or_else(|| (0, None))
+                };
 
                 let unlocked = balance.get_available_balance_at_block(block_height);
                 let (locked, unlock_height) = balance.get_locked_balance_at_block(block_height);
@@ -768,18 +983,21 @@ impl ConversationHttp {
                 let balance = format!("0x{}", to_hex(&unlocked.to_be_bytes()));
                 let locked = format!("0x{}", to_hex(&locked.to_be_bytes()));
 
-                AccountEntryResponse {
+                Ok(AccountEntryResponse {
                     balance,
                     locked,
                     unlock_height,
                     nonce,
                     balance_proof,
                     nonce_proof,
-                }
+                })
             })
         });
 
-        let response = HttpResponseType::GetAccount(response_metadata, data);
+        let response = match data {
+            Ok(data) => HttpResponseType::GetAccount(response_metadata, data),
+            Err(e) => ConversationHttp::proof_error_response(response_metadata, tip, e),
+        };
 
         response.send(http, fd).map(|_| ())
     }
@@ -804,36 +1022,77 @@ impl ConversationHttp {
             QualifiedContractIdentifier::new(contract_addr.clone().into(), contract_name.clone());
 
         let data = chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
-            clarity_tx.with_clarity_db_readonly(|clarity_db| {
+            clarity_tx.with_clarity_db_readonly(|clarity_db| -> Result<_, vm_error> {
                 let key = ClarityDatabase::make_key_for_data_map_entry(
                     &contract_identifier,
                     map_name,
                     key,
                 );
-                let (value, marf_proof) = clarity_db
-                    .get_with_proof::<Value>(&key)
-                    .map(|(a, b)| (a, format!("0x{}", b.to_hex())))
-                    .unwrap_or_else(|| {
-                        test_debug!("No value for '{}' in {}", &key, tip);
-                        (Value::none(), "".into())
-                    });
-                let marf_proof = if with_proof {
+                let (value, marf_proof) = if with_proof {
+                    let (value, marf_proof) = clarity_db
+                        .get_with_proof::<Value>(&key)?
+                        .map(|(a, b)| (a, format!("0x{}", b.to_hex())))
+                        .unwrap_or_else(|| {
+                            test_debug!("No value for '{}' in {}", &key, tip);
+                            (Value::none(), "".into())
+                        });
                     test_debug!(
                         "Return a MARF proof of '{}' of {} bytes",
                         &key,
                         marf_proof.as_bytes().len()
                     );
-                    Some(marf_proof)
+                    (value, Some(marf_proof))
                 } else {
-                    None
+                    let value = clarity_db.get::<Value>(&key).unwrap_or_else(|| {
+                        test_debug!("No value for '{}' in {}", &key, tip);
+                        Value::none()
+                    });
+                    (value, None)
                 };
 
                 let data = format!("0x{}", value.serialize());
-                MapEntryResponse { data, marf_proof }
+                Ok(MapEntryResponse { data, marf_proof })
+            })
+        });
+
+        let response = match data {
+            Ok(data) => HttpResponseType::GetMapEntry(response_metadata, data),
+            Err(e) => ConversationHttp::proof_error_response(response_metadata, tip, e),
+        };
+
+        response.send(http, fd).map(|_| ())
+    }
+
+    /// Handle a POST for the values of a list of MARF keys at the given chain tip, along with a
+    /// single proof of all of the values that are present.
+    fn handle_post_marf_proof<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        keys: &[String],
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+
+        let data = chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
+            clarity_tx.with_clarity_db_readonly(|clarity_db| -> Result<_, vm_error> {
+                let (data, proof) = clarity_db.get_with_multi_proof(keys)?;
+                let proof = format!("0x{}", proof.to_hex());
+                test_debug!(
+                    "Return a MARF proof of {} keys of {} bytes",
+                    keys.len(),
+                    proof.as_bytes().len()
+                );
+                Ok(MarfProofResponse { data, proof })
             })
         });
 
-        let response = HttpResponseType::GetMapEntry(response_metadata, data);
+        let response = match data {
+            Ok(data) => HttpResponseType::MarfProof(response_metadata, data),
+            Err(e) => ConversationHttp::proof_error_response(response_metadata, tip, e),
+        };
 
         response.send(http, fd).map(|_| ())
     }
@@ -888,6 +1147,277 @@ impl ConversationHttp {
         response.send(http, fd).map(|_| ())
     }
 
+    /// Handle a POST to dry-run a public function call with the given parameters on the given
+    /// chain tip.  The call runs in a throwaway block that is always rolled back, so it can
+    /// exercise writes without persisting them.  Returns a DryRunCallResponse with the call's
+    /// result, its events, whether or not its post-conditions would have aborted it, and what it
+    /// cost to run.
+    fn handle_dry_run_function_call<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        contract_addr: &StacksAddress,
+        contract_name: &ContractName,
+        function: &ClarityName,
+        sender: &PrincipalData,
+        args: &[Value],
+        post_conditions: &Vec<TransactionPostCondition>,
+        post_condition_mode: &TransactionPostConditionMode,
+        options: &ConnectionOptions,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let contract_identifier =
+            QualifiedContractIdentifier::new(contract_addr.clone().into(), contract_name.clone());
+
+        let run_result = chainstate.with_throwaway_clarity_tx(
+            &sortdb.index_conn(),
+            tip,
+            &options.simulation_call_limit,
+            |clarity_tx| {
+                clarity_tx.connection().as_transaction(|tx_conn| {
+                    let origin_account = StacksChainState::get_account(tx_conn, sender);
+                    let cost_before = tx_conn.cost_so_far();
+                    let call_result = tx_conn.run_contract_call(
+                        sender,
+                        &contract_identifier,
+                        function.as_str(),
+                        args,
+                        |asset_map, _| {
+                            !StacksChainState::check_transaction_postconditions(
+                                post_conditions,
+                                post_condition_mode,
+                                &origin_account,
+                                asset_map,
+                            )
+                        },
+                    );
+
+                    let mut total_cost = tx_conn.cost_so_far();
+                    total_cost
+                        .sub(&cost_before)
+                        .expect("BUG: total block cost decreased");
+
+                    (call_result, total_cost)
+                })
+            },
+        );
+
+        // dry-run events don't belong to any transaction, so they have no txid
+        let events_to_json = |events: Vec<StacksTransactionEvent>, committed: bool| {
+            events
+                .iter()
+                .map(|event| {
+                    let mut event_json = event.json_serialize(&Txid([0u8; 32]), committed);
+                    if let Some(obj) = event_json.as_object_mut() {
+                        obj.remove("txid");
+                    }
+                    event_json
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let response = match run_result {
+            Ok(Some((call_result, execution_cost))) => {
+                let dry_run = match call_result {
+                    Ok((value, _, events)) => DryRunCallResponse {
+                        okay: true,
+                        result: Some(format!("0x{}", value.serialize())),
+                        cause: None,
+                        post_condition_aborted: false,
+                        events: events_to_json(events, true),
+                        execution_cost,
+                    },
+                    Err(clarity_error::AbortedByCallback(value_opt, _, events)) => {
+                        DryRunCallResponse {
+                            okay: false,
+                            result: value_opt.map(|value| format!("0x{}", value.serialize())),
+                            cause: Some("Post condition aborted transaction".to_string()),
+                            post_condition_aborted: true,
+                            events: events_to_json(events, false),
+                            execution_cost,
+                        }
+                    }
+                    Err(e) => DryRunCallResponse {
+                        okay: false,
+                        result: None,
+                        cause: Some(e.to_string()),
+                        post_condition_aborted: false,
+                        events: vec![],
+                        execution_cost,
+                    },
+                };
+                HttpResponseType::DryRunCallFunction(response_metadata, dry_run)
+            }
+            Ok(None) => {
+                HttpResponseType::NotFound(response_metadata, format!("No such chain tip {}", tip))
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to dry-run {}.{}: {:?}",
+                    &contract_identifier,
+                    function.as_str(),
+                    &e
+                );
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to instantiate throwaway block".to_string(),
+                )
+            }
+        };
+
+        response.send(http, fd).map(|_| ())
+    }
+
+    /// Handle a POST to simulate mining a transaction on the given chain tip.  The transaction is
+    /// processed in a throwaway block that is always rolled back.  Returns a
+    /// TransactionSimulationResponse with the would-be receipt, or the reason the transaction
+    /// could not be mined (e.g. a bad nonce, or an unaffordable fee).
+    fn handle_simulate_transaction<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        tx: &StacksTransaction,
+        options: &ConnectionOptions,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let txid = tx.txid();
+
+        let run_result = chainstate.with_throwaway_clarity_tx(
+            &sortdb.index_conn(),
+            tip,
+            &options.simulation_call_limit,
+            |clarity_tx| StacksChainState::process_transaction(clarity_tx, tx, true),
+        );
+
+        let response = match run_result {
+            Ok(Some(Ok((fee, receipt)))) => {
+                let committed = !receipt.post_condition_aborted;
+                let simulation = TransactionSimulationResponse {
+                    txid: format!("0x{}", &txid),
+                    okay: true,
+                    cause: None,
+                    fee,
+                    result: Some(format!("0x{}", receipt.result.serialize())),
+                    post_condition_aborted: receipt.post_condition_aborted,
+                    stx_burned: receipt.stx_burned,
+                    events: receipt
+                        .events
+                        .iter()
+                        .map(|event| event.json_serialize(&txid, committed))
+                        .collect(),
+                    execution_cost: receipt.execution_cost,
+                };
+                HttpResponseType::TransactionSimulation(response_metadata, simulation)
+            }
+            Ok(Some(Err(e))) => {
+                let simulation = TransactionSimulationResponse {
+                    txid: format!("0x{}", &txid),
+                    okay: false,
+                    cause: Some(e.to_string()),
+                    fee: 0,
+                    result: None,
+                    post_condition_aborted: false,
+                    stx_burned: 0,
+                    events: vec![],
+                    execution_cost: ExecutionCost::zero(),
+                };
+                HttpResponseType::TransactionSimulation(response_metadata, simulation)
+            }
+            Ok(None) => {
+                HttpResponseType::NotFound(response_metadata, format!("No such chain tip {}", tip))
+            }
+            Err(e) => {
+                warn!("Failed to simulate transaction {}: {:?}", &txid, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to instantiate throwaway block".to_string(),
+                )
+            }
+        };
+
+        response.send(http, fd).map(|_| ())
+    }
+
+    /// Handle a GET to page through the events a contract emitted in the fork ending at the given
+    /// chain tip.  Replies with a 404 if this node doesn't maintain a contract event index.
+    fn handle_get_contract_events<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        chainstate: &StacksChainState,
+        tip: &StacksBlockId,
+        contract_addr: &StacksAddress,
+        contract_name: &ContractName,
+        events_query: &ContractEventsQuery,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let contract_identifier =
+            QualifiedContractIdentifier::new(contract_addr.clone().into(), contract_name.clone());
+
+        let response = match chainstate.get_contract_events(
+            &contract_identifier,
+            tip,
+            events_query.topic.as_ref().map(|topic| topic.as_str()),
+            events_query.from_height.unwrap_or(0),
+            events_query
+                .to_height
+                .unwrap_or(i64::max_value() as u64)
+                .min(i64::max_value() as u64),
+            events_query.offset,
+            events_query.limit,
+        ) {
+            Ok(Some(entries)) => {
+                let events = entries
+                    .into_iter()
+                    .map(|entry| RPCContractEvent {
+                        txid: format!("0x{}", &entry.txid),
+                        index_block_hash: format!("{}", &entry.index_block_hash),
+                        block_height: entry.block_height,
+                        microblock_hash: entry
+                            .microblock_hash
+                            .map(|mblock_hash| format!("0x{}", mblock_hash)),
+                        microblock_sequence: entry.microblock_sequence,
+                        tx_index: entry.tx_index,
+                        event_index: entry.event_index,
+                        topic: entry.topic,
+                        value: format!("0x{}", entry.value.serialize()),
+                    })
+                    .collect();
+                HttpResponseType::GetContractEvents(
+                    response_metadata,
+                    ContractEventsResponse {
+                        contract_identifier: contract_identifier.to_string(),
+                        offset: events_query.offset,
+                        limit: events_query.limit,
+                        events,
+                    },
+                )
+            }
+            Ok(None) => HttpResponseType::NotFound(
+                response_metadata,
+                "Contract event index is not enabled on this node".to_string(),
+            ),
+            Err(e) => {
+                warn!(
+                    "Failed to load events for {}: {:?}",
+                    &contract_identifier, &e
+                );
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query contract events".to_string(),
+                )
+            }
+        };
+
+        response.send(http, fd).map(|_| ())
+    }
+
     /// Handle a GET to fetch a contract's source code, given the chain tip.  Optionally returns a
     /// MARF proof as well.
     fn handle_get_contract_src<W: Write>(
@@ -906,32 +1436,39 @@ impl ConversationHttp {
             QualifiedContractIdentifier::new(contract_addr.clone().into(), contract_name.clone());
 
         let data = chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
-            clarity_tx.with_clarity_db_readonly(|db| {
-                let source = db.get_contract_src(&contract_identifier)?;
+            clarity_tx.with_clarity_db_readonly(|db| -> Result<_, vm_error> {
+                let source = match db.get_contract_src(&contract_identifier) {
+                    Some(source) => source,
+                    None => return Ok(None),
+                };
                 let contract_commit_key = MarfedKV::make_contract_hash_key(&contract_identifier);
-                let (contract_commit, proof) = db
-                    .get_with_proof::<ContractCommitment>(&contract_commit_key)
-                    .expect("BUG: obtained source, but couldn't get MARF proof.");
-                let marf_proof = if with_proof {
-                    Some(proof.to_hex())
+                let (contract_commit, marf_proof) = if with_proof {
+                    let (contract_commit, proof) = db
+                        .get_with_proof::<ContractCommitment>(&contract_commit_key)?
+                        .expect("BUG: obtained source, but couldn't get MARF proof.");
+                    (contract_commit, Some(proof.to_hex()))
                 } else {
-                    None
+                    let contract_commit = db
+                        .get::<ContractCommitment>(&contract_commit_key)
+                        .expect("BUG: obtained source, but couldn't get contract commitment.");
+                    (contract_commit, None)
                 };
                 let publish_height = contract_commit.block_height;
-                Some(ContractSrcResponse {
+                Ok(Some(ContractSrcResponse {
                     source,
                     publish_height,
                     marf_proof,
-                })
+                }))
             })
         });
 
         let response = match data {
-            Some(data) => HttpResponseType::GetContractSrc(response_metadata, data),
-            None => HttpResponseType::NotFound(
+            Ok(Some(data)) => HttpResponseType::GetContractSrc(response_metadata, data),
+            Ok(None) => HttpResponseType::NotFound(
                 response_metadata,
                 "No contract source data found".into(),
             ),
+            Err(e) => ConversationHttp::proof_error_response(response_metadata, tip, e),
         };
 
         response.send(http, fd).map(|_| ())
@@ -1068,6 +1605,61 @@ impl ConversationHttp {
         }
     }
 
+    /// Make the reply for a failure to load a MARF proof at the given tip.  Replies 404 if the
+    /// tries the proof would pass through have been pruned.
+    fn proof_error_response(
+        response_metadata: HttpResponseMetadata,
+        tip: &StacksBlockId,
+        e: vm_error,
+    ) -> HttpResponseType {
+        match e {
+            vm_error::Runtime(RuntimeErrorType::PrunedBlockHeaderHash(_), _) => {
+                debug!("Cannot prove state at {}: chain state has been pruned", tip);
+                HttpResponseType::NotFound(
+                    response_metadata,
+                    format!("Cannot prove state at {}: chain state has been pruned", tip),
+                )
+            }
+            e => {
+                warn!("Failed to load MARF proof at {}: {:?}", tip, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to load MARF proof".to_string(),
+                )
+            }
+        }
+    }
+
+    /// Like handle_load_stacks_chain_tip(), but for requests that read the Clarity state at the
+    /// tip.  Replies 404 if that state has been pruned.
+    fn handle_load_clarity_chain_tip<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        tip_opt: Option<&StacksBlockId>,
+        sortdb: &SortitionDB,
+        chainstate: &StacksChainState,
+    ) -> Result<Option<StacksBlockId>, net_error> {
+        let tip = match ConversationHttp::handle_load_stacks_chain_tip(
+            http, fd, req, tip_opt, sortdb, chainstate,
+        )? {
+            Some(tip) => tip,
+            None => {
+                return Ok(None);
+            }
+        };
+        if chainstate.is_clarity_state_pruned(&tip)? {
+            let response_metadata = HttpResponseMetadata::from(req);
+            debug!("Chain state at {} has been pruned", &tip);
+            let response = HttpResponseType::NotFound(
+                response_metadata,
+                format!("Chain state at {} has been pruned", &tip),
+            );
+            return response.send(http, fd).and_then(|_| Ok(None));
+        }
+        Ok(Some(tip))
+    }
+
     fn handle_load_stacks_chain_tip_hashes<W: Write>(
         http: &mut StacksHttp,
         fd: &mut W,
@@ -1099,6 +1691,73 @@ impl ConversationHttp {
         response.send(http, fd).and_then(|_| Ok(None))
     }
 
+    /// Handle a GET on a transaction, given the chain tip.  Replies with the transaction's receipt
+    /// if it was confirmed in the fork ending at the tip and this node maintains a transaction
+    /// index, or with its mempool status if it is still pending.  Replies 404 otherwise.
+    fn handle_get_transaction<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        chainstate: &StacksChainState,
+        mempool: &MemPoolDB,
+        tip: &StacksBlockId,
+        txid: &Txid,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+
+        let response = if let Some(entry) = chainstate.get_indexed_transaction(txid, tip)? {
+            HttpResponseType::TransactionStatus(
+                response_metadata,
+                TransactionStatusResponse {
+                    txid: format!("0x{}", txid),
+                    status: "confirmed".to_string(),
+                    tx: format!("0x{}", to_hex(&entry.tx.serialize_to_vec())),
+                    pending: None,
+                    confirmed: Some(RPCConfirmedTxInfo {
+                        index_block_hash: format!("0x{}", entry.index_block_hash),
+                        block_height: entry.block_height,
+                        microblock_hash: entry
+                            .microblock_hash
+                            .map(|mblock_hash| format!("0x{}", mblock_hash)),
+                        microblock_sequence: entry.microblock_sequence,
+                        tx_index: entry.tx_index,
+                        result: format!("0x{}", entry.result.serialize()),
+                        post_condition_aborted: entry.post_condition_aborted,
+                        events: entry.events,
+                        execution_cost: entry.execution_cost,
+                    }),
+                },
+            )
+        } else if let Some(tx_info) = MemPoolDB::get_tx(mempool.conn(), txid)? {
+            HttpResponseType::TransactionStatus(
+                response_metadata,
+                TransactionStatusResponse {
+                    txid: format!("0x{}", txid),
+                    status: "pending".to_string(),
+                    tx: format!("0x{}", to_hex(&tx_info.tx.serialize_to_vec())),
+                    pending: Some(RPCPendingTxInfo {
+                        accept_time: tx_info.metadata.accept_time,
+                        fee_rate: tx_info.metadata.fee_rate,
+                        len: tx_info.metadata.len,
+                    }),
+                    confirmed: None,
+                },
+            )
+        } else if !chainstate.transaction_index_enabled() {
+            HttpResponseType::NotFound(
+                response_metadata,
+                format!(
+                    "No such pending transaction {}; confirmed transactions are not indexed by this node",
+                    txid
+                ),
+            )
+        } else {
+            HttpResponseType::NotFound(response_metadata, format!("No such transaction {}", txid))
+        };
+
+        response.send(http, fd).map(|_| ())
+    }
+
     /// Handle a transaction.  Directly submit it to the mempool so the client can see any
     /// rejection reasons up-front (different from how the peer network handles it).  Indicate
     /// whether or not the transaction was accepted (and thus needs to be forwarded) in the return
@@ -1185,20 +1844,242 @@ impl ConversationHttp {
         response.send(http, fd).and_then(|_| Ok(accepted))
     }
 
-    /// Handle an external HTTP request.
-    /// Some requests, such as those for blocks, will create new reply streams.  This method adds
-    /// those new streams into the `reply_streams` set.
-    /// Returns a StacksMessageType option -- it's Some(...) if we need to forward a message to the
-    /// peer network (like a transaction or a block or microblock)
-    pub fn handle_request(
-        &mut self,
-        req: HttpRequestType,
-        chain_view: &BurnchainView,
-        peers: &PeerMap,
-        sortdb: &SortitionDB,
-        peerdb: &PeerDB,
-        chainstate: &mut StacksChainState,
-        mempool: &mut MemPoolDB,
+    /// Compare a client-given bearer token against the configured one without short-circuiting
+    /// on the first mismatched byte.
+    fn admin_token_matches(given: &str, expected: &str) -> bool {
+        if given.len() != expected.len() {
+            return false;
+        }
+        given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+    }
+
+    /// Build the list of connected p2p peers and their conversation stats
+    fn make_admin_peers(peers: &PeerMap) -> Vec<RPCAdminPeer> {
+        let mut admin_peers: Vec<RPCAdminPeer> = peers
+            .iter()
+            .map(|(event_id, convo)| {
+                let nk = convo.to_neighbor_key();
+                RPCAdminPeer {
+                    event_id: *event_id,
+                    network_id: nk.network_id,
+                    peer_version: nk.peer_version,
+                    addrbytes: nk.addrbytes,
+                    port: nk.port,
+                    public_key_hash: convo.get_public_key_hash(),
+                    authenticated: convo.is_authenticated(),
+                    outbound: convo.is_outbound(),
+                    first_contact_time: convo.stats.first_contact_time,
+                    last_contact_time: convo.stats.last_contact_time,
+                    last_send_time: convo.stats.last_send_time,
+                    last_recv_time: convo.stats.last_recv_time,
+                    last_handshake_time: convo.stats.last_handshake_time,
+                    bytes_tx: convo.stats.bytes_tx,
+                    bytes_rx: convo.stats.bytes_rx,
+                    msgs_tx: convo.stats.msgs_tx,
+                    msgs_rx: convo.stats.msgs_rx,
+                    msgs_rx_unsolicited: convo.stats.msgs_rx_unsolicited,
+                    msgs_err: convo.stats.msgs_err,
+                    health_score: convo.stats.get_health_score(),
+                }
+            })
+            .collect();
+        admin_peers.sort_by_key(|p| p.event_id);
+        admin_peers
+    }
+
+    /// Apply an admin change to the peer DB, and find the connected peers that are now denied.
+    fn handle_admin_peerdb_update<F>(
+        network_id: u32,
+        peers: &PeerMap,
+        peerdb: &mut PeerDB,
+        update: F,
+    ) -> Result<Vec<usize>, net_error>
+    where
+        F: FnOnce(&mut Transaction) -> Result<(), db_error>,
+    {
+        let mut tx = peerdb.tx_begin()?;
+        update(&mut tx)?;
+        tx.commit().map_err(db_error::SqliteError)?;
+
+        let mut disconnect = vec![];
+        for (event_id, convo) in peers.iter() {
+            let nk = convo.to_neighbor_key();
+            if PeerDB::is_peer_denied(peerdb.conn(), network_id, &nk.addrbytes, nk.port)? {
+                debug!(
+                    "Admin update denies connected peer {:?}; will disconnect",
+                    &nk
+                );
+                disconnect.push(*event_id);
+            }
+        }
+        Ok(disconnect)
+    }
+
+    /// Is this client connected over the loopback interface?
+    fn is_loopback_client(peer_addr: &SocketAddr) -> bool {
+        match peer_addr.ip() {
+            IpAddr::V4(ip) => ip.is_loopback(),
+            IpAddr::V6(ip) => {
+                ip.is_loopback() || ip.to_ipv4().map(|ip| ip.is_loopback()).unwrap_or(false)
+            }
+        }
+    }
+
+    /// Handle a peer-management request on the admin interface.
+    /// The interface is disabled unless an admin bearer token is configured.  Every request
+    /// must carry that token, and must come from the loopback interface.
+    /// Returns the event IDs of the connected p2p peers that should be dropped.
+    fn handle_admin<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        peer_addr: &SocketAddr,
+        network_id: u32,
+        peers: &PeerMap,
+        peerdb: &mut PeerDB,
+        token_opt: Option<&String>,
+        admin_req: &AdminRequest,
+        options: &ConnectionOptions,
+    ) -> Result<Vec<usize>, net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let expected_token = match options.admin_rpc_token {
+            Some(ref token) => token,
+            None => {
+                let response = HttpResponseType::Forbidden(
+                    response_metadata,
+                    "Admin RPC interface is disabled".to_string(),
+                );
+                return response.send(http, fd).and_then(|_| Ok(vec![]));
+            }
+        };
+
+        if !ConversationHttp::is_loopback_client(peer_addr) {
+            warn!(
+                "Rejecting admin request from non-loopback client {}",
+                peer_addr
+            );
+            let response = HttpResponseType::Forbidden(
+                response_metadata,
+                "Admin requests are only accepted from the loopback interface".to_string(),
+            );
+            return response.send(http, fd).and_then(|_| Ok(vec![]));
+        }
+
+        let authorized = match token_opt {
+            Some(token) => ConversationHttp::admin_token_matches(token, expected_token),
+            None => false,
+        };
+        if !authorized {
+            let response = HttpResponseType::Unauthorized(
+                response_metadata,
+                "Missing or invalid admin bearer token".to_string(),
+            );
+            return response.send(http, fd).and_then(|_| Ok(vec![]));
+        }
+
+        let disconnect = match admin_req {
+            AdminRequest::GetPeers => {
+                let response = HttpResponseType::AdminPeers(
+                    response_metadata,
+                    ConversationHttp::make_admin_peers(peers),
+                );
+                return response.send(http, fd).and_then(|_| Ok(vec![]));
+            }
+            AdminRequest::GetCidrs => {
+                let allow = PeerDB::get_allowed_cidrs(peerdb.conn())?
+                    .into_iter()
+                    .map(|(prefix, mask)| prefix.to_cidr_string(mask))
+                    .collect();
+                let deny = PeerDB::get_denied_cidrs(peerdb.conn())?
+                    .into_iter()
+                    .map(|(prefix, mask)| prefix.to_cidr_string(mask))
+                    .collect();
+                let response =
+                    HttpResponseType::AdminCidrs(response_metadata, RPCAdminCidrs { allow, deny });
+                return response.send(http, fd).and_then(|_| Ok(vec![]));
+            }
+            AdminRequest::BanPeer(addr, port, duration_opt) => {
+                let deadline =
+                    get_epoch_time_secs().saturating_add(duration_opt.unwrap_or(DENY_BAN_DURATION));
+                info!(
+                    "Admin ban of {}:{} until {}",
+                    &addr.to_socketaddr(*port).ip(),
+                    port,
+                    deadline
+                );
+                ConversationHttp::handle_admin_peerdb_update(network_id, peers, peerdb, |tx| {
+                    PeerDB::set_deny_peer(tx, network_id, addr, *port, deadline)
+                })?
+            }
+            AdminRequest::UnbanPeer(addr, port) => {
+                info!(
+                    "Admin unban of {}:{}",
+                    &addr.to_socketaddr(*port).ip(),
+                    port
+                );
+                ConversationHttp::handle_admin_peerdb_update(network_id, peers, peerdb, |tx| {
+                    // don't preemptively insert a row for a peer we never heard of
+                    if PeerDB::get_peer(tx, network_id, addr, *port)?.is_some() {
+                        PeerDB::set_deny_peer(tx, network_id, addr, *port, 0)?;
+                    }
+                    Ok(())
+                })?
+            }
+            AdminRequest::AddCidr(list, prefix, mask) => {
+                info!(
+                    "Admin add {} CIDR {}",
+                    list.as_str(),
+                    prefix.to_cidr_string(*mask)
+                );
+                ConversationHttp::handle_admin_peerdb_update(network_id, peers, peerdb, |tx| {
+                    match list {
+                        AdminCidrList::Allow => PeerDB::add_allow_cidr(tx, prefix, *mask),
+                        AdminCidrList::Deny => PeerDB::add_deny_cidr(tx, prefix, *mask),
+                    }
+                })?
+            }
+            AdminRequest::RemoveCidr(list, prefix, mask) => {
+                info!(
+                    "Admin remove {} CIDR {}",
+                    list.as_str(),
+                    prefix.to_cidr_string(*mask)
+                );
+                ConversationHttp::handle_admin_peerdb_update(network_id, peers, peerdb, |tx| {
+                    match list {
+                        AdminCidrList::Allow => PeerDB::remove_allow_cidr(tx, prefix, *mask),
+                        AdminCidrList::Deny => PeerDB::remove_deny_cidr(tx, prefix, *mask),
+                    }
+                })?
+            }
+        };
+
+        let response = HttpResponseType::AdminUpdate(
+            response_metadata,
+            RPCAdminUpdateResponse {
+                disconnected: disconnect.len() as u64,
+            },
+        );
+        response.send(http, fd).and_then(|_| Ok(disconnect))
+    }
+
+    /// Handle an external HTTP request.
+    /// Some requests, such as those for blocks, will create new reply streams.  This method adds
+    /// those new streams into the `reply_streams` set.
+    /// Returns a StacksMessageType option -- it's Some(...) if we need to forward a message to the
+    /// peer network (like a transaction or a block or microblock)
+    pub fn handle_request(
+        &mut self,
+        req: HttpRequestType,
+        chain_view: &BurnchainView,
+        peers: &PeerMap,
+        sortdb: &SortitionDB,
+        peerdb: &mut PeerDB,
+        chainstate: &mut StacksChainState,
+        mempool: &mut MemPoolDB,
         handler_opts: &RPCHandlerArgs,
     ) -> Result<Option<StacksMessageType>, net_error> {
         monitoring::increment_rpc_calls_counter();
@@ -1222,7 +2103,7 @@ impl ConversationHttp {
                 None
             }
             HttpRequestType::GetPoxInfo(ref _md, ref tip_opt) => {
-                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                if let Some(tip) = ConversationHttp::handle_load_clarity_chain_tip(
                     &mut self.connection.protocol,
                     &mut reply,
                     &req,
@@ -1294,7 +2175,7 @@ impl ConversationHttp {
                 chainstate,
             )?,
             HttpRequestType::GetAccount(ref _md, ref principal, ref tip_opt, ref with_proof) => {
-                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                if let Some(tip) = ConversationHttp::handle_load_clarity_chain_tip(
                     &mut self.connection.protocol,
                     &mut reply,
                     &req,
@@ -1324,7 +2205,7 @@ impl ConversationHttp {
                 ref tip_opt,
                 ref with_proof,
             ) => {
-                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                if let Some(tip) = ConversationHttp::handle_load_clarity_chain_tip(
                     &mut self.connection.protocol,
                     &mut reply,
                     &req,
@@ -1356,13 +2237,64 @@ impl ConversationHttp {
                 )?;
                 None
             }
+            HttpRequestType::PostFeeRateEstimate(
+                ref _md,
+                ref payload,
+                ref sender,
+                ref estimated_len,
+                ref tip_opt,
+            ) => {
+                if let Some(tip) = ConversationHttp::handle_load_clarity_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_fee_rate_estimate(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        payload,
+                        sender.as_ref(),
+                        *estimated_len,
+                        &self.connection.options,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::PostMarfProof(ref _md, ref keys, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_clarity_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_post_marf_proof(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        keys,
+                    )?;
+                }
+                None
+            }
             HttpRequestType::GetContractABI(
                 ref _md,
                 ref contract_addr,
                 ref contract_name,
                 ref tip_opt,
             ) => {
-                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                if let Some(tip) = ConversationHttp::handle_load_clarity_chain_tip(
                     &mut self.connection.protocol,
                     &mut reply,
                     &req,
@@ -1383,6 +2315,34 @@ impl ConversationHttp {
                 }
                 None
             }
+            HttpRequestType::GetContractEvents(
+                ref _md,
+                ref contract_addr,
+                ref contract_name,
+                ref events_query,
+                ref tip_opt,
+            ) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_get_contract_events(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        chainstate,
+                        &tip,
+                        contract_addr,
+                        contract_name,
+                        events_query,
+                    )?;
+                }
+                None
+            }
             HttpRequestType::CallReadOnlyFunction(
                 ref _md,
                 ref ctrct_addr,
@@ -1392,7 +2352,7 @@ impl ConversationHttp {
                 ref args,
                 ref tip_opt,
             ) => {
-                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                if let Some(tip) = ConversationHttp::handle_load_clarity_chain_tip(
                     &mut self.connection.protocol,
                     &mut reply,
                     &req,
@@ -1417,6 +2377,44 @@ impl ConversationHttp {
                 }
                 None
             }
+            HttpRequestType::DryRunCallFunction(
+                ref _md,
+                ref ctrct_addr,
+                ref ctrct_name,
+                ref as_sender,
+                ref func_name,
+                ref args,
+                ref post_conditions,
+                ref post_condition_mode,
+                ref tip_opt,
+            ) => {
+                if let Some(tip) = ConversationHttp::handle_load_clarity_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_dry_run_function_call(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        ctrct_addr,
+                        ctrct_name,
+                        func_name,
+                        as_sender,
+                        args,
+                        post_conditions,
+                        post_condition_mode,
+                        &self.connection.options,
+                    )?;
+                }
+                None
+            }
             HttpRequestType::GetContractSrc(
                 ref _md,
                 ref contract_addr,
@@ -1424,7 +2422,7 @@ impl ConversationHttp {
                 ref tip_opt,
                 ref with_proof,
             ) => {
-                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                if let Some(tip) = ConversationHttp::handle_load_clarity_chain_tip(
                     &mut self.connection.protocol,
                     &mut reply,
                     &req,
@@ -1475,6 +2473,56 @@ impl ConversationHttp {
                 }
                 None
             }
+            HttpRequestType::SimulateTransaction(ref _md, ref tx, ref tip_opt) => {
+                // simulate on top of the unconfirmed state by default
+                let tip_opt = tip_opt.clone().or_else(|| {
+                    chainstate
+                        .unconfirmed_state
+                        .as_ref()
+                        .map(|unconfirmed| unconfirmed.unconfirmed_chain_tip.clone())
+                });
+                if let Some(tip) = ConversationHttp::handle_load_clarity_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_simulate_transaction(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        tx,
+                        &self.connection.options,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetTransaction(ref _md, ref txid, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_get_transaction(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        chainstate,
+                        mempool,
+                        &tip,
+                        txid,
+                    )?;
+                }
+                None
+            }
             HttpRequestType::PostMicroblock(ref _md, ref mblock, ref tip_opt) => {
                 if let Some((consensus_hash, block_hash)) =
                     ConversationHttp::handle_load_stacks_chain_tip_hashes(
@@ -1507,6 +2555,22 @@ impl ConversationHttp {
                 }
                 None
             }
+            HttpRequestType::Admin(ref _md, ref token_opt, ref admin_req) => {
+                let mut disconnect = ConversationHttp::handle_admin(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    &self.peer_addr,
+                    self.network_id,
+                    peers,
+                    peerdb,
+                    token_opt.as_ref(),
+                    admin_req,
+                    &self.connection.options,
+                )?;
+                self.p2p_disconnects.append(&mut disconnect);
+                None
+            }
             HttpRequestType::OptionsPreflight(ref _md, ref _path) => {
                 let response_metadata = HttpResponseMetadata::from(&req);
                 let response = HttpResponseType::OptionsPreflight(response_metadata);
@@ -1778,7 +2842,7 @@ impl ConversationHttp {
         chain_view: &BurnchainView,
         peers: &PeerMap,
         sortdb: &SortitionDB,
-        peerdb: &PeerDB,
+        peerdb: &mut PeerDB,
         chainstate: &mut StacksChainState,
         mempool: &mut MemPoolDB,
         handler_args: &RPCHandlerArgs,
@@ -1842,6 +2906,11 @@ impl ConversationHttp {
         Ok(ret)
     }
 
+    /// Take the event IDs of the p2p peers that admin requests on this conversation have denied
+    pub fn take_p2p_disconnects(&mut self) -> Vec<usize> {
+        mem::replace(&mut self.p2p_disconnects, vec![])
+    }
+
     /// Remove all timed-out messages, and ding the remote peer as unhealthy
     pub fn clear_timeouts(&mut self) -> () {
         self.connection.drain_timeouts();
@@ -2065,27 +3134,141 @@ impl ConversationHttp {
             tip_opt,
         )
     }
-}
-
-#[cfg(test)]
-mod test {
-    use super::*;
-    use net::codec::*;
-    use net::http::*;
-    use net::test::*;
-    use net::*;
-    use std::cell::RefCell;
 
-    use burnchains::Burnchain;
-    use burnchains::BurnchainHeaderHash;
-    use burnchains::BurnchainView;
+    /// Make a new request for a page of a contract's events
+    pub fn new_getcontractevents(
+        &self,
+        contract_addr: StacksAddress,
+        contract_name: ContractName,
+        events_query: ContractEventsQuery,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetContractEvents(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            contract_addr,
+            contract_name,
+            events_query,
+            tip_opt,
+        )
+    }
 
-    use burnchains::*;
-    use chainstate::burn::BlockHeaderHash;
-    use chainstate::burn::ConsensusHash;
+    /// Make a new request to simulate mining a transaction
+    pub fn new_simulate_transaction(
+        &self,
+        tx: StacksTransaction,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::SimulateTransaction(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            tx,
+            tip_opt,
+        )
+    }
+
+    /// Make a new request to dry-run a public function
+    pub fn new_dryruncallfunction(
+        &self,
+        contract_addr: StacksAddress,
+        contract_name: ContractName,
+        sender: PrincipalData,
+        function_name: ClarityName,
+        function_args: Vec<Value>,
+        post_conditions: Vec<TransactionPostCondition>,
+        post_condition_mode: TransactionPostConditionMode,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::DryRunCallFunction(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            contract_addr,
+            contract_name,
+            sender,
+            function_name,
+            function_args,
+            post_conditions,
+            post_condition_mode,
+            tip_opt,
+        )
+    }
+
+    /// Make a new request to look up a transaction
+    pub fn new_gettransaction(
+        &self,
+        txid: Txid,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetTransaction(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            txid,
+            tip_opt,
+        )
+    }
+
+    /// Make a new request to estimate a transaction's cost and fee rate
+    pub fn new_post_fee_rate_estimate(
+        &self,
+        payload: TransactionPayload,
+        sender: Option<PrincipalData>,
+        estimated_len: Option<u64>,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::PostFeeRateEstimate(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            payload,
+            sender,
+            estimated_len,
+            tip_opt,
+        )
+    }
+
+    /// Make a new request for a proof of the values of the given MARF keys
+    pub fn new_post_marf_proof(
+        &self,
+        keys: Vec<String>,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::PostMarfProof(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            keys,
+            tip_opt,
+        )
+    }
+
+    /// Make a new admin request to this endpoint, authenticated with the given bearer token
+    pub fn new_admin_request(
+        &self,
+        token: Option<String>,
+        admin_req: AdminRequest,
+    ) -> HttpRequestType {
+        HttpRequestType::Admin(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            token,
+            admin_req,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use net::codec::*;
+    use net::http::*;
+    use net::test::*;
+    use net::*;
+    use std::cell::RefCell;
+
+    use burnchains::Burnchain;
+    use burnchains::BurnchainHeaderHash;
+    use burnchains::BurnchainView;
+
+    use burnchains::*;
+    use chainstate::burn::BlockHeaderHash;
+    use chainstate::burn::ConsensusHash;
     use chainstate::stacks::db::blocks::test::*;
     use chainstate::stacks::db::BlockStreamData;
+    use chainstate::stacks::db::StacksAccount;
     use chainstate::stacks::db::StacksChainState;
+    use chainstate::stacks::index::proofs::TrieMerkleMultiProof;
+    use chainstate::stacks::index::TrieHash;
     use chainstate::stacks::miner::*;
     use chainstate::stacks::test::*;
     use chainstate::stacks::Error as chain_error;
@@ -2111,6 +3294,8 @@ mod test {
           (begin 
             (map-set unit-map ((account tx-sender)) ((units 1)) )
             (ok 1)))
+        (define-public (log-set-bar (x int) (y int))
+          (begin (var-set bar (/ x y)) (print (var-get bar)) (ok (var-get bar))))
         (begin
           (map-set unit-map ((account 'ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R)) ((units 123))))";
 
@@ -2220,6 +3405,10 @@ mod test {
         let mut peer_1 = TestPeer::new(peer_1_config);
         let mut peer_2 = TestPeer::new(peer_2_config);
 
+        // index the transactions in the blocks mined below, so they can be looked up
+        peer_1.enable_transaction_index();
+        peer_2.enable_transaction_index();
+
         // mine one block with a contract in it
         // first the coinbase
         // make a coinbase for this miner
@@ -2476,7 +3665,7 @@ mod test {
                 &view_1,
                 &PeerMap::new(),
                 &mut peer_1_sortdb,
-                &peer_1.network.peerdb,
+                &mut peer_1.network.peerdb,
                 &mut peer_1_stacks_node.chainstate,
                 &mut peer_1_mempool,
                 &RPCHandlerArgs::default(),
@@ -2499,7 +3688,7 @@ mod test {
                 &view_2,
                 &PeerMap::new(),
                 &mut peer_2_sortdb,
-                &peer_2.network.peerdb,
+                &mut peer_2.network.peerdb,
                 &mut peer_2_stacks_node.chainstate,
                 &mut peer_2_mempool,
                 &RPCHandlerArgs::default(),
@@ -2536,7 +3725,7 @@ mod test {
                 &view_1,
                 &PeerMap::new(),
                 &mut peer_1_sortdb,
-                &peer_1.network.peerdb,
+                &mut peer_1.network.peerdb,
                 &mut peer_1_stacks_node.chainstate,
                 &mut peer_1_mempool,
                 &RPCHandlerArgs::default(),
@@ -2557,6 +3746,66 @@ mod test {
         assert!(check_result(&req, &resp, &mut peer_1, &mut peer_2));
     }
 
+    #[test]
+    fn test_admin_token_matches() {
+        assert!(ConversationHttp::admin_token_matches("hunter2", "hunter2"));
+        assert!(!ConversationHttp::admin_token_matches("hunter3", "hunter2"));
+        assert!(!ConversationHttp::admin_token_matches("hunter", "hunter2"));
+        assert!(!ConversationHttp::admin_token_matches("", "hunter2"));
+    }
+
+    #[test]
+    fn test_admin_loopback_only() {
+        let loopback: Vec<SocketAddr> = vec![
+            "127.0.0.1:20443".parse().unwrap(),
+            "127.1.2.3:20443".parse().unwrap(),
+            "[::1]:20443".parse().unwrap(),
+            "[::ffff:127.0.0.1]:20443".parse().unwrap(),
+        ];
+        for addr in loopback.iter() {
+            assert!(ConversationHttp::is_loopback_client(addr), "{}", addr);
+        }
+
+        let remote: Vec<SocketAddr> = vec![
+            "0.0.0.0:20443".parse().unwrap(),
+            "10.0.0.1:20443".parse().unwrap(),
+            "192.168.1.2:20443".parse().unwrap(),
+            "[::]:20443".parse().unwrap(),
+            "[2001:db8::1]:20443".parse().unwrap(),
+            "[::ffff:10.0.0.1]:20443".parse().unwrap(),
+        ];
+        for addr in remote.iter() {
+            assert!(!ConversationHttp::is_loopback_client(addr), "{}", addr);
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_admin_disabled() {
+        test_rpc(
+            "test_rpc_admin_disabled",
+            40250,
+            40251,
+            50250,
+            50251,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_admin_request(Some("hunter2".to_string()), AdminRequest::GetPeers)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::Forbidden(..) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
     #[test]
     #[ignore]
     fn test_rpc_getinfo() {
@@ -2575,7 +3824,7 @@ mod test {
                     &peer_server.config.burnchain,
                     peer_server.sortdb.as_mut().unwrap(),
                     &peer_server.stacks_node.as_ref().unwrap().chainstate,
-                    &peer_server.network.peerdb,
+                    &mut peer_server.network.peerdb,
                     &None,
                 )
                 .unwrap();
@@ -3376,6 +4625,72 @@ mod test {
         );
     }
 
+    #[test]
+    #[ignore]
+    fn test_rpc_marf_proof() {
+        test_rpc(
+            "test_rpc_marf_proof",
+            40260,
+            40261,
+            50260,
+            50261,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let principal =
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal();
+                let contract_identifier = QualifiedContractIdentifier::new(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .into(),
+                    "hello-world".try_into().unwrap(),
+                );
+                let key = ClarityDatabase::make_key_for_data_map_entry(
+                    &contract_identifier,
+                    "unit-map",
+                    &Value::Tuple(
+                        TupleData::from_data(vec![("account".into(), Value::Principal(principal))])
+                            .unwrap(),
+                    ),
+                );
+                convo_client.new_post_marf_proof(vec![key, "no-such-key".to_string()], None)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::MarfProof(response_md, data) => {
+                        assert_eq!(data.data.len(), 2);
+                        assert_eq!(
+                            Value::try_deserialize_hex_untyped(data.data[0].as_ref().unwrap())
+                                .unwrap(),
+                            Value::some(Value::Tuple(
+                                TupleData::from_data(vec![("units".into(), Value::Int(123))])
+                                    .unwrap()
+                            ))
+                            .unwrap()
+                        );
+                        assert!(data.data[1].is_none());
+
+                        // only the key that's present is proven
+                        let proof_bytes = hex_bytes(&data.proof[2..]).unwrap();
+                        let proof: TrieMerkleMultiProof<StacksBlockId> =
+                            read_next(&mut &proof_bytes[..]).unwrap();
+                        assert_eq!(proof.key_proofs.len(), 1);
+                        assert!(proof.entries.len() > 0);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
     #[test]
     #[ignore]
     fn test_rpc_get_map_entry_unconfirmed() {
@@ -3609,4 +4924,602 @@ mod test {
             },
         );
     }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_fee_rate_estimate() {
+        test_rpc(
+            "test_rpc_fee_rate_estimate",
+            40190,
+            40191,
+            50190,
+            50191,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_post_fee_rate_estimate(
+                    TransactionPayload::new_contract_call(
+                        StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                            .unwrap(),
+                        "hello-world",
+                        "add-unit",
+                        vec![],
+                    )
+                    .unwrap(),
+                    Some(
+                        PrincipalData::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap(),
+                    ),
+                    None,
+                    None,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::FeeRateEstimate(response_md, data) => {
+                        assert!(data.estimated_cost.runtime > 0);
+                        assert!(data.estimated_len > 0);
+                        assert_eq!(data.estimations.len(), 3);
+                        for estimate in data.estimations.iter() {
+                            assert!(estimate.fee_rate >= MINIMUM_TX_FEE_RATE_PER_BYTE);
+                            assert_eq!(estimate.fee, estimate.fee_rate * data.estimated_len);
+                        }
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_fee_rate_estimate_over_limit() {
+        test_rpc(
+            "test_rpc_fee_rate_estimate_over_limit",
+            40290,
+            40291,
+            50290,
+            50291,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_post_fee_rate_estimate(
+                    TransactionPayload::new_contract_call(
+                        StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                            .unwrap(),
+                        "hello-world",
+                        "add-unit",
+                        vec![],
+                    )
+                    .unwrap(),
+                    Some(
+                        PrincipalData::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap(),
+                    ),
+                    None,
+                    None,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::FeeRateEstimate(..) => {}
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        return false;
+                    }
+                }
+
+                // the same request fails against a budget that allows no runtime.  The client
+                // can't decode a JSON error, so check the handler's raw response instead.
+                let mut options = ConnectionOptions::default();
+                options.simulation_call_limit.runtime = 0;
+
+                let (payload, sender) = match http_request {
+                    HttpRequestType::PostFeeRateEstimate(_, payload, sender, ..) => {
+                        (payload.clone(), sender.clone())
+                    }
+                    _ => panic!("not a fee rate estimate request"),
+                };
+
+                let sortdb = peer_server.sortdb.take().unwrap();
+                let tip = peer_server
+                    .chainstate()
+                    .get_stacks_chain_tip(&sortdb)
+                    .unwrap()
+                    .unwrap();
+                let tip_index_hash = StacksBlockHeader::make_index_block_hash(
+                    &tip.consensus_hash,
+                    &tip.anchored_block_hash,
+                );
+
+                let mut reply = vec![];
+                ConversationHttp::handle_fee_rate_estimate(
+                    &mut StacksHttp::new(),
+                    &mut reply,
+                    http_request,
+                    &sortdb,
+                    peer_server.chainstate(),
+                    &tip_index_hash,
+                    &payload,
+                    sender.as_ref(),
+                    None,
+                    &options,
+                )
+                .unwrap();
+                peer_server.sortdb = Some(sortdb);
+
+                let reply = String::from_utf8(reply).unwrap();
+                assert!(reply.starts_with("HTTP/1.1 400"));
+                assert!(reply.find("Failed to estimate transaction cost").is_some());
+                assert!(reply.find("exceeded budget").is_some());
+                true
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_transaction() {
+        test_rpc(
+            "test_rpc_get_transaction",
+            40200,
+            40201,
+            50200,
+            50201,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                // look up the smart contract transaction in the anchored block
+                let sortdb = peer_client.sortdb.take().unwrap();
+                let tip = peer_client
+                    .chainstate()
+                    .get_stacks_chain_tip(&sortdb)
+                    .unwrap()
+                    .unwrap();
+                peer_client.sortdb = Some(sortdb);
+
+                let block = StacksChainState::load_block(
+                    &peer_client.chainstate().blocks_path,
+                    &tip.consensus_hash,
+                    &tip.anchored_block_hash,
+                )
+                .unwrap()
+                .unwrap();
+
+                convo_client.new_gettransaction(block.txs[1].txid(), None)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::TransactionStatus(response_md, data) => {
+                        assert_eq!(data.status, "confirmed");
+                        assert!(data.pending.is_none());
+
+                        let confirmed = data.confirmed.clone().unwrap();
+                        assert_eq!(confirmed.tx_index, 1);
+                        assert!(confirmed.microblock_hash.is_none());
+                        assert!(!confirmed.post_condition_aborted);
+                        assert!(confirmed.execution_cost.runtime > 0);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_call_dry_run() {
+        test_rpc(
+            "test_rpc_call_dry_run",
+            40210,
+            40211,
+            50210,
+            50211,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_dryruncallfunction(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world".try_into().unwrap(),
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    "set-bar".try_into().unwrap(),
+                    vec![Value::Int(10), Value::Int(2)],
+                    vec![],
+                    TransactionPostConditionMode::Deny,
+                    None,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::DryRunCallFunction(response_md, data) => {
+                        assert!(data.okay);
+                        assert_eq!(
+                            Value::try_deserialize_hex_untyped(&data.result.clone().unwrap())
+                                .unwrap(),
+                            Value::okay(Value::Int(5)).unwrap()
+                        );
+                        assert!(data.cause.is_none());
+                        assert!(!data.post_condition_aborted);
+                        assert!(data.execution_cost.runtime > 0);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_call_dry_run_over_limit() {
+        test_rpc(
+            "test_rpc_call_dry_run_over_limit",
+            40270,
+            40271,
+            50270,
+            50271,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                // the same call as test_rpc_call_dry_run, but the server allows no writes
+                convo_server.connection.options.simulation_call_limit = ExecutionCost {
+                    write_length: 0,
+                    write_count: 0,
+                    ..convo_server
+                        .connection
+                        .options
+                        .simulation_call_limit
+                        .clone()
+                };
+                convo_client.new_dryruncallfunction(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world".try_into().unwrap(),
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    "set-bar".try_into().unwrap(),
+                    vec![Value::Int(10), Value::Int(2)],
+                    vec![],
+                    TransactionPostConditionMode::Deny,
+                    None,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::DryRunCallFunction(response_md, data) => {
+                        assert!(!data.okay);
+                        assert!(data.result.is_none());
+                        assert!(data.cause.as_ref().unwrap().contains("exceeded budget"));
+                        assert!(!data.post_condition_aborted);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_simulate_transaction() {
+        test_rpc(
+            "test_rpc_simulate_transaction",
+            40220,
+            40221,
+            50220,
+            50221,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                // replay the smart contract transaction in the anchored block
+                let sortdb = peer_client.sortdb.take().unwrap();
+                let tip = peer_client
+                    .chainstate()
+                    .get_stacks_chain_tip(&sortdb)
+                    .unwrap()
+                    .unwrap();
+                peer_client.sortdb = Some(sortdb);
+
+                let block = StacksChainState::load_block(
+                    &peer_client.chainstate().blocks_path,
+                    &tip.consensus_hash,
+                    &tip.anchored_block_hash,
+                )
+                .unwrap()
+                .unwrap();
+
+                convo_client.new_simulate_transaction(block.txs[1].clone(), None)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::TransactionSimulation(response_md, data) => {
+                        // already mined, so its nonce is stale
+                        assert!(!data.okay);
+                        assert!(data.cause.clone().unwrap().find("nonce").is_some());
+                        assert!(data.result.is_none());
+                        assert!(data.events.is_empty());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    /// The chain tip, the MARF's open block, the root hash at the chain tip, and an account as seen
+    /// from the unconfirmed state, so a test can check that a request left the node's state alone.
+    fn get_chainstate_snapshot(
+        peer: &mut TestPeer,
+        principal: &PrincipalData,
+    ) -> (
+        StacksBlockId,
+        Option<StacksBlockId>,
+        TrieHash,
+        StacksAccount,
+    ) {
+        let sortdb = peer.sortdb.take().unwrap();
+        let tip = peer
+            .chainstate()
+            .get_stacks_chain_tip(&sortdb)
+            .unwrap()
+            .unwrap();
+        let tip_index_hash =
+            StacksBlockHeader::make_index_block_hash(&tip.consensus_hash, &tip.anchored_block_hash);
+        let (open_tip, root_hash) = peer.chainstate().with_clarity_marf(|marf| {
+            (
+                marf.get_open_chain_tip().cloned(),
+                marf.get_root_hash_at(&tip_index_hash).unwrap(),
+            )
+        });
+        let unconfirmed_tip = peer
+            .chainstate()
+            .unconfirmed_state
+            .as_ref()
+            .unwrap()
+            .unconfirmed_chain_tip
+            .clone();
+        let account = peer.chainstate().with_read_only_clarity_tx(
+            &sortdb.index_conn(),
+            &unconfirmed_tip,
+            |clarity_tx| StacksChainState::get_account(clarity_tx, principal),
+        );
+        peer.sortdb = Some(sortdb);
+        (tip_index_hash, open_tip, root_hash, account)
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_simulate_contract_call() {
+        let before_cell = RefCell::new(None);
+        test_rpc(
+            "test_rpc_simulate_contract_call",
+            40240,
+            40241,
+            50240,
+            50241,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                // STVN97YYA10MY5F6KQJHKNYJNM24C4A1AT39WRW, which hasn't sent any transactions yet
+                let privk = StacksPrivateKey::from_hex(
+                    "94c319327cc5cd04da7147d32d836eb2e4c44f4db39aa5ede7314a761183d0c701",
+                )
+                .unwrap();
+                let mut tx = StacksTransaction::new(
+                    TransactionVersion::Testnet,
+                    TransactionAuth::from_p2pkh(&privk).unwrap(),
+                    TransactionPayload::new_contract_call(
+                        StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                            .unwrap(),
+                        "hello-world",
+                        "log-set-bar",
+                        vec![Value::Int(10), Value::Int(2)],
+                    )
+                    .unwrap(),
+                );
+                tx.chain_id = 0x80000000;
+                tx.auth.set_origin_nonce(0);
+                tx.set_fee_rate(1000);
+
+                let mut tx_signer = StacksTransactionSigner::new(&tx);
+                tx_signer.sign_origin(&privk).unwrap();
+                let tx_signed = tx_signer.get_tx().unwrap();
+
+                let principal =
+                    PrincipalData::parse("STVN97YYA10MY5F6KQJHKNYJNM24C4A1AT39WRW").unwrap();
+                *before_cell.borrow_mut() = Some(get_chainstate_snapshot(peer_server, &principal));
+
+                convo_client.new_simulate_transaction(tx_signed, None)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::TransactionSimulation(response_md, data) => {
+                        assert!(data.okay);
+                        assert!(data.cause.is_none());
+                        assert_eq!(data.fee, 1000);
+                        assert_eq!(
+                            Value::try_deserialize_hex_untyped(&data.result.clone().unwrap())
+                                .unwrap(),
+                            Value::okay(Value::Int(5)).unwrap()
+                        );
+                        assert!(!data.post_condition_aborted);
+                        assert!(data.execution_cost.runtime > 0);
+
+                        assert_eq!(data.events.len(), 1);
+                        assert_eq!(data.events[0]["type"], "contract_event");
+                        assert_eq!(data.events[0]["committed"], true);
+
+                        // the throwaway block left no trace: the chain tip, the MARF and the
+                        // sender's nonce and balance are all as they were before the request
+                        let principal =
+                            PrincipalData::parse("STVN97YYA10MY5F6KQJHKNYJNM24C4A1AT39WRW")
+                                .unwrap();
+                        let before = before_cell.borrow_mut().take().unwrap();
+                        let after = get_chainstate_snapshot(peer_server, &principal);
+                        assert_eq!(before, after);
+                        assert_eq!(after.3.nonce, 0);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_simulate_contract_call_over_limit() {
+        test_rpc(
+            "test_rpc_simulate_contract_call_over_limit",
+            40280,
+            40281,
+            50280,
+            50281,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                // the same call as test_rpc_simulate_contract_call, but the server allows no writes
+                convo_server.connection.options.simulation_call_limit = ExecutionCost {
+                    write_length: 0,
+                    write_count: 0,
+                    ..convo_server
+                        .connection
+                        .options
+                        .simulation_call_limit
+                        .clone()
+                };
+
+                let privk = StacksPrivateKey::from_hex(
+                    "94c319327cc5cd04da7147d32d836eb2e4c44f4db39aa5ede7314a761183d0c701",
+                )
+                .unwrap();
+                let mut tx = StacksTransaction::new(
+                    TransactionVersion::Testnet,
+                    TransactionAuth::from_p2pkh(&privk).unwrap(),
+                    TransactionPayload::new_contract_call(
+                        StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                            .unwrap(),
+                        "hello-world",
+                        "log-set-bar",
+                        vec![Value::Int(10), Value::Int(2)],
+                    )
+                    .unwrap(),
+                );
+                tx.chain_id = 0x80000000;
+                tx.auth.set_origin_nonce(0);
+                tx.set_fee_rate(1000);
+
+                let mut tx_signer = StacksTransactionSigner::new(&tx);
+                tx_signer.sign_origin(&privk).unwrap();
+                let tx_signed = tx_signer.get_tx().unwrap();
+
+                convo_client.new_simulate_transaction(tx_signed, None)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::TransactionSimulation(response_md, data) => {
+                        assert!(!data.okay);
+                        assert!(data.cause.clone().unwrap().find("Cost overflow").is_some());
+                        assert!(data.result.is_none());
+                        assert!(data.events.is_empty());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_contract_events() {
+        test_rpc(
+            "test_rpc_get_contract_events",
+            40230,
+            40231,
+            50230,
+            50231,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                peer_server
+                    .chainstate()
+                    .enable_contract_event_index()
+                    .unwrap();
+                convo_client.new_getcontractevents(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world".try_into().unwrap(),
+                    ContractEventsQuery {
+                        topic: None,
+                        from_height: None,
+                        to_height: None,
+                        offset: 0,
+                        limit: 10,
+                    },
+                    None,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::GetContractEvents(response_md, data) => {
+                        assert_eq!(
+                            data.contract_identifier,
+                            "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.hello-world"
+                        );
+                        assert_eq!(data.limit, 10);
+                        // the test contract doesn't print anything
+                        assert!(data.events.is_empty());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
 }
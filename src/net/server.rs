@@ -23,6 +23,7 @@ use std::io::{Read, Write};
 
 use std::collections::HashMap;
 use std::collections::VecDeque;
+use std::mem;
 
 use std::sync::mpsc::sync_channel;
 use std::sync::mpsc::Receiver;
@@ -79,6 +80,9 @@ pub struct HttpPeer {
 
     // connection options
     pub connection_opts: ConnectionOptions,
+
+    // p2p peers that admin requests have denied, which the peer network should drop
+    p2p_disconnects: Vec<usize>,
 }
 
 impl HttpPeer {
@@ -100,9 +104,16 @@ impl HttpPeer {
 
             burnchain: burnchain,
             connection_opts: conn_opts,
+
+            p2p_disconnects: vec![],
         }
     }
 
+    /// Take the event IDs of the p2p peers that admin requests have denied since the last call
+    pub fn take_p2p_disconnects(&mut self) -> Vec<usize> {
+        mem::replace(&mut self.p2p_disconnects, vec![])
+    }
+
     pub fn set_server_handle(&mut self, h: usize) -> () {
         self.http_server_handle = h;
     }
@@ -431,7 +442,7 @@ impl HttpPeer {
         chain_view: &BurnchainView,
         peers: &PeerMap,
         sortdb: &SortitionDB,
-        peerdb: &PeerDB,
+        peerdb: &mut PeerDB,
         chainstate: &mut StacksChainState,
         mempool: &mut MemPoolDB,
         event_id: usize,
@@ -583,7 +594,7 @@ impl HttpPeer {
         poll_state: &mut NetworkPollState,
         peers: &PeerMap,
         sortdb: &SortitionDB,
-        peerdb: &PeerDB,
+        peerdb: &mut PeerDB,
         chainstate: &mut StacksChainState,
         mempool: &mut MemPoolDB,
         handler_args: &RPCHandlerArgs,
@@ -680,7 +691,7 @@ impl HttpPeer {
         new_chain_view: BurnchainView,
         p2p_peers: &PeerMap,
         sortdb: &SortitionDB,
-        peerdb: &PeerDB,
+        peerdb: &mut PeerDB,
         chainstate: &mut StacksChainState,
         mempool: &mut MemPoolDB,
         mut poll_state: NetworkPollState,
@@ -705,6 +716,12 @@ impl HttpPeer {
             mempool,
             handler_args,
         );
+        // collect p2p peers to drop before any conversations get closed
+        for (_, convo) in self.peers.iter_mut() {
+            self.p2p_disconnects
+                .append(&mut convo.take_p2p_disconnects());
+        }
+
         for error_event in error_events {
             debug!("Failed HTTP connection on event {}", error_event);
             self.deregister_http(network_state, error_event);
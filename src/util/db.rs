@@ -527,6 +527,17 @@ pub fn get_ancestor_block_height<T: MarfTrieId>(
     Ok(height_opt)
 }
 
+/// Fork-specific key/value storage, indexed via a MARF.
+/// Each row is guaranteed to be unique.
+pub const SQL_FORK_STORAGE_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS __fork_storage(
+    value_hash TEXT NOT NULL,
+    value TEXT NOT NULL,
+
+    PRIMARY KEY(value_hash)
+);
+"#;
+
 /// Load some index data
 fn load_indexed(conn: &DBConn, marf_value: &MARFValue) -> Result<Option<String>, Error> {
     let mut stmt = conn
@@ -612,19 +623,7 @@ impl<'a, C: Clone, T: MarfTrieId> IndexDBTx<'a, C, T> {
 
     pub fn instantiate_index(&mut self) -> Result<(), Error> {
         self.tx()
-            .execute(
-                r#"
-        -- fork-specific key/value storage, indexed via a MARF.
-        -- each row is guaranteed to be unique
-        CREATE TABLE IF NOT EXISTS __fork_storage(
-            value_hash TEXT NOT NULL,
-            value TEXT NOT NULL,
-
-            PRIMARY KEY(value_hash)
-        );
-        "#,
-                NO_PARAMS,
-            )
+            .execute(SQL_FORK_STORAGE_TABLE, NO_PARAMS)
             .map_err(Error::SqliteError)?;
         Ok(())
     }
@@ -15,7 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::{BTreeMap, BTreeSet};
-use vm::analysis::types::ContractAnalysis;
+use vm::analysis::types::{ContractAnalysis, FungibleTokenBound};
 use vm::types::{FixedFunction, FunctionArg, FunctionType, TupleTypeSignature, TypeSignature};
 use vm::ClarityName;
 
@@ -30,6 +30,7 @@ pub fn build_contract_interface(contract_analysis: &ContractAnalysis) -> Contrac
         persisted_variable_types,
         map_types,
         fungible_tokens,
+        fungible_token_bounds,
         non_fungible_tokens,
         defined_traits: _,
         implemented_traits: _,
@@ -87,6 +88,7 @@ pub fn build_contract_interface(contract_analysis: &ContractAnalysis) -> Contrac
         .fungible_tokens
         .append(&mut ContractInterfaceFungibleTokens::from_set(
             fungible_tokens,
+            fungible_token_bounds,
         ));
 
     contract_interface
@@ -141,6 +143,14 @@ pub enum ContractInterfaceAtomType {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ContractInterfaceFungibleTokens {
     pub name: String,
+    /// Whether the token was defined with a max supply.  Interfaces stored before this was
+    /// recorded load as unbounded.
+    #[serde(default)]
+    pub bounded: bool,
+    /// The token's configured max supply. This is only known when the token's
+    /// definition bounds it with a uint literal, so a bounded token whose bound is
+    /// computed by an expression reports `None` here.
+    pub max_supply: Option<u128>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -289,11 +299,23 @@ pub struct ContractInterfaceVariable {
 }
 
 impl ContractInterfaceFungibleTokens {
-    pub fn from_set(tokens: &BTreeSet<ClarityName>) -> Vec<Self> {
+    pub fn from_set(
+        tokens: &BTreeSet<ClarityName>,
+        bounds: &BTreeMap<ClarityName, FungibleTokenBound>,
+    ) -> Vec<Self> {
         tokens
             .iter()
-            .map(|name| Self {
-                name: name.to_string(),
+            .map(|name| {
+                let (bounded, max_supply) = match bounds.get(name) {
+                    Some(FungibleTokenBound::Static(max_supply)) => (true, Some(*max_supply)),
+                    Some(FungibleTokenBound::Computed) => (true, None),
+                    Some(FungibleTokenBound::Unbounded) | None => (false, None),
+                };
+                Self {
+                    name: name.to_string(),
+                    bounded,
+                    max_supply,
+                }
             })
             .collect()
     }
@@ -69,6 +69,7 @@ pub enum CheckErrors {
     CouldNotDetermineResponseErrType,
 
     CouldNotDetermineMatchTypes,
+    CouldNotDetermineSerializationType,
 
     // Checker runtime failures
     TypeAlreadyAnnotatedFailure,
@@ -81,11 +82,13 @@ pub enum CheckErrors {
     DefineNFTBadSignature,
     NoSuchNFT(String),
     NoSuchFT(String),
+    UnboundedTokenSupply(String),
 
     BadTransferSTXArguments,
     BadTransferFTArguments,
     BadTransferNFTArguments,
     BadMintFTArguments,
+    BadBurnFTArguments,
 
     // tuples
     BadTupleFieldName,
@@ -333,6 +336,7 @@ impl DiagnosableError for CheckErrors {
             CheckErrors::CouldNotDetermineResponseOkType => format!("attempted to obtain 'ok' value from response, but 'ok' type is indeterminate"),
             CheckErrors::CouldNotDetermineResponseErrType => format!("attempted to obtain 'err' value from response, but 'err' type is indeterminate"),
             CheckErrors::CouldNotDetermineMatchTypes => format!("attempted to match on an (optional) or (response) type where either the some, ok, or err type is indeterminate. you may wish to use unwrap-panic or unwrap-err-panic instead."),
+            CheckErrors::CouldNotDetermineSerializationType => format!("could not determine the input type for the serialization function"),
             CheckErrors::BadTupleFieldName => format!("invalid tuple field name"),
             CheckErrors::ExpectedTuple(type_signature) => format!("expecting tuple, found '{}'", type_signature),
             CheckErrors::NoSuchTupleField(field_name, tuple_signature) => format!("cannot find field '{}' in tuple '{}'", field_name, tuple_signature),
@@ -343,6 +347,7 @@ impl DiagnosableError for CheckErrors {
             CheckErrors::BadTransferFTArguments => format!("transfer expects an int amount, from principal, to principal"),
             CheckErrors::BadTransferNFTArguments => format!("transfer expects an asset, from principal, to principal"),
             CheckErrors::BadMintFTArguments => format!("mint expects an int amount and from principal"),
+            CheckErrors::BadBurnFTArguments => format!("burn expects an int amount and from principal"),
             CheckErrors::BadMapName => format!("invalid map name"),
             CheckErrors::NoSuchMap(map_name) => format!("use of unresolved map '{}'", map_name),
             CheckErrors::DefineFunctionBadSignature => format!("invalid function definition"),
@@ -385,6 +390,7 @@ impl DiagnosableError for CheckErrors {
             CheckErrors::DefineNFTBadSignature => format!("(define-asset ...) expects an asset name and an asset identifier type signature as arguments"),
             CheckErrors::NoSuchNFT(asset_name) => format!("tried to use asset function with a undefined asset ('{}')", asset_name),
             CheckErrors::NoSuchFT(asset_name) => format!("tried to use token function with a undefined token ('{}')", asset_name),
+            CheckErrors::UnboundedTokenSupply(asset_name) => format!("tried to get the supply of a token defined without a total supply ('{}')", asset_name),
             CheckErrors::TraitReferenceUnknown(trait_name) => format!("use of undeclared trait <{}>", trait_name),
             CheckErrors::TraitMethodUnknown(trait_name, func_name) => format!("method '{}' unspecified in trait <{}>", func_name, trait_name),
             CheckErrors::ImportTraitBadSignature => format!("(use-trait ...) expects a trait name and a trait identifier"),
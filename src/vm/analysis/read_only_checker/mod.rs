@@ -198,8 +198,14 @@ impl<'a, 'b> ReadOnlyChecker<'a, 'b> {
             | UnwrapErrRet | IsOkay | IsNone | Asserts | Unwrap | UnwrapErr | Match | IsErr
             | IsSome | TryRet | ToUInt | ToInt | Append | Concat | AsMaxLen | ContractOf
             | PrincipalOf | ListCons | GetBlockInfo | TupleGet | Len | Print | AsContract
-            | Begin | FetchVar | GetStxBalance | GetTokenBalance | GetAssetOwner => {
-                self.check_all_read_only(args)
+            | Begin | FetchVar | GetStxBalance | GetStxAccount | GetTokenBalance
+            | GetTokenSupply | GetAssetOwner | ElementAt | IndexOf | Slice | ToConsensusBuff
+            | IntToAscii | IntToUtf8 | StringToInt | StringToUInt | BuffToAscii | BuffToUtf8
+            | AsciiToBuff | Utf8ToBuff => self.check_all_read_only(args),
+            FromConsensusBuff => {
+                // the first argument is a type description, not an expression
+                check_argument_count(2, args)?;
+                self.check_read_only(&args[1])
             }
             AtBlock => {
                 check_argument_count(2, args)?;
@@ -223,7 +229,7 @@ impl<'a, 'b> ReadOnlyChecker<'a, 'b> {
                 res
             }
             StxTransfer | StxBurn | SetEntry | DeleteEntry | InsertEntry | SetVar | MintAsset
-            | MintToken | TransferAsset | TransferToken => Ok(false),
+            | MintToken | BurnAsset | BurnToken | TransferAsset | TransferToken => Ok(false),
             Let => {
                 check_arguments_at_least(2, args)?;
 
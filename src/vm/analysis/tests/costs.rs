@@ -59,7 +59,7 @@ pub fn test_tracked_costs(prog: &str) -> ExecutionCost {
     let contract_self = format!(
         "(define-map map-foo ((a int)) ((b int)))
                          (define-non-fungible-token nft-foo int)
-                         (define-fungible-token ft-foo)
+                         (define-fungible-token ft-foo u1000000)
                          (define-data-var var-foo int 0)
                          (define-constant tuple-foo (tuple (a 1)))
                          (define-constant list-foo (list true))
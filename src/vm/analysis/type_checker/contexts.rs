@@ -22,7 +22,7 @@ use vm::types::{FunctionType, TraitIdentifier, TypeSignature};
 use vm::contexts::MAX_CONTEXT_DEPTH;
 
 use vm::analysis::errors::{CheckError, CheckErrors, CheckResult};
-use vm::analysis::types::ContractAnalysis;
+use vm::analysis::types::{ContractAnalysis, FungibleTokenBound};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct TypeMap {
@@ -43,7 +43,7 @@ pub struct ContractContext {
     public_function_types: HashMap<ClarityName, FunctionType>,
     read_only_function_types: HashMap<ClarityName, FunctionType>,
     persisted_variable_types: HashMap<ClarityName, TypeSignature>,
-    fungible_tokens: HashSet<ClarityName>,
+    fungible_tokens: HashMap<ClarityName, FungibleTokenBound>,
     non_fungible_tokens: HashMap<ClarityName, TypeSignature>,
     traits: HashMap<ClarityName, BTreeMap<ClarityName, FunctionSignature>>,
     pub implemented_traits: HashSet<TraitIdentifier>,
@@ -82,7 +82,7 @@ impl ContractContext {
             read_only_function_types: HashMap::new(),
             map_types: HashMap::new(),
             persisted_variable_types: HashMap::new(),
-            fungible_tokens: HashSet::new(),
+            fungible_tokens: HashMap::new(),
             non_fungible_tokens: HashMap::new(),
             traits: HashMap::new(),
             implemented_traits: HashSet::new(),
@@ -94,7 +94,7 @@ impl ContractContext {
             || self.persisted_variable_types.contains_key(name)
             || self.private_function_types.contains_key(name)
             || self.public_function_types.contains_key(name)
-            || self.fungible_tokens.contains(name)
+            || self.fungible_tokens.contains_key(name)
             || self.non_fungible_tokens.contains_key(name)
             || self.traits.contains_key(name)
             || self.map_types.contains_key(name)
@@ -113,7 +113,11 @@ impl ContractContext {
     }
 
     pub fn ft_exists(&self, name: &str) -> bool {
-        self.fungible_tokens.contains(name)
+        self.fungible_tokens.contains_key(name)
+    }
+
+    pub fn get_ft_bound(&self, name: &str) -> Option<&FungibleTokenBound> {
+        self.fungible_tokens.get(name)
     }
 
     pub fn get_nft_type(&self, name: &str) -> Option<&TypeSignature> {
@@ -180,9 +184,9 @@ impl ContractContext {
         Ok(())
     }
 
-    pub fn add_ft(&mut self, token_name: ClarityName) -> CheckResult<()> {
+    pub fn add_ft(&mut self, token_name: ClarityName, bound: FungibleTokenBound) -> CheckResult<()> {
         self.check_name_used(&token_name)?;
-        self.fungible_tokens.insert(token_name);
+        self.fungible_tokens.insert(token_name, bound);
         Ok(())
     }
 
@@ -263,8 +267,8 @@ impl ContractContext {
             contract_analysis.add_persisted_variable_type(name.into(), persisted_variable_type);
         }
 
-        for name in self.fungible_tokens.drain() {
-            contract_analysis.add_fungible_token(name.into());
+        for (name, bound) in self.fungible_tokens.drain() {
+            contract_analysis.add_fungible_token(name.into(), bound);
         }
 
         for (name, nft_type) in self.non_fungible_tokens.drain() {
@@ -37,7 +37,7 @@ use vm::types::{
 };
 use vm::variables::NativeVariables;
 
-pub use super::types::{AnalysisPass, ContractAnalysis};
+pub use super::types::{AnalysisPass, ContractAnalysis, FungibleTokenBound};
 use super::AnalysisDatabase;
 
 use self::contexts::{ContractContext, TypeMap, TypingContext};
@@ -685,17 +685,26 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
         Ok((var_name.clone(), expected_type))
     }
 
+    /// Type check a fungible token definition, returning the token's name and
+    /// the kind of max supply it was defined with.
     fn type_check_define_ft(
         &mut self,
         token_name: &ClarityName,
         bound: Option<&SymbolicExpression>,
         context: &mut TypingContext,
-    ) -> CheckResult<ClarityName> {
-        if let Some(bound) = bound {
-            self.type_check_expects(bound, context, &TypeSignature::UIntType)?;
-        }
+    ) -> CheckResult<(ClarityName, FungibleTokenBound)> {
+        let bound = match bound {
+            Some(bound) => {
+                self.type_check_expects(bound, context, &TypeSignature::UIntType)?;
+                match bound.match_literal_value() {
+                    Some(Value::UInt(max_supply)) => FungibleTokenBound::Static(*max_supply),
+                    _ => FungibleTokenBound::Computed,
+                }
+            }
+            None => FungibleTokenBound::Unbounded,
+        };
 
-        Ok(token_name.clone())
+        Ok((token_name.clone(), bound))
     }
 
     fn type_check_define_nft(
@@ -808,22 +817,23 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
                         .add_persisted_variable_type(v_name, v_type)?;
                 }
                 DefineFunctionsParsed::BoundedFungibleToken { name, max_supply } => {
-                    let token_name = self.type_check_define_ft(name, Some(max_supply), context)?;
+                    let (token_name, bound) =
+                        self.type_check_define_ft(name, Some(max_supply), context)?;
                     runtime_cost!(
                         cost_functions::ANALYSIS_BIND_NAME,
                         self,
                         TypeSignature::UIntType.type_size()?
                     )?;
-                    self.contract_context.add_ft(token_name)?;
+                    self.contract_context.add_ft(token_name, bound)?;
                 }
                 DefineFunctionsParsed::UnboundedFungibleToken { name } => {
-                    let token_name = self.type_check_define_ft(name, None, context)?;
+                    let (token_name, bound) = self.type_check_define_ft(name, None, context)?;
                     runtime_cost!(
                         cost_functions::ANALYSIS_BIND_NAME,
                         self,
                         TypeSignature::UIntType.type_size()?
                     )?;
-                    self.contract_context.add_ft(token_name)?;
+                    self.contract_context.add_ft(token_name, bound)?;
                 }
                 DefineFunctionsParsed::NonFungibleToken { name, nft_type } => {
                     let (token_name, token_type) =
@@ -0,0 +1,145 @@
+// Copyright (C) 2013-2020 Blocstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::convert::TryFrom;
+use vm::representations::SymbolicExpression;
+use vm::types::signatures::{BufferLength, StringUTF8Length};
+use vm::types::{SequenceSubtype::*, StringSubtype::*, TypeSignature};
+
+use vm::analysis::type_checker::{
+    check_argument_count, CheckErrors, CheckResult, TypeChecker, TypeResult, TypingContext,
+};
+
+use vm::costs::cost_functions;
+
+/// `to-consensus-buff` returns a buffer sized to hold the serialization of any value of its
+///   argument's static type.
+pub fn check_special_to_consensus_buff(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(1, args)?;
+
+    let input_type = checker.type_check(&args[0], context)?;
+    runtime_cost!(
+        cost_functions::ANALYSIS_TYPE_CHECK,
+        checker,
+        input_type.type_size()?
+    )?;
+
+    let buffer_length = BufferLength::try_from(input_type.max_serialized_size()?)?;
+    Ok(TypeSignature::SequenceType(BufferType(buffer_length)))
+}
+
+/// `from-consensus-buff` takes a type literal, rather than an expression, as its first argument.
+pub fn check_special_from_consensus_buff(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(2, args)?;
+
+    let result_type = TypeSignature::parse_type_repr(&args[0], checker)
+        .map_err(|_| CheckErrors::InvalidTypeDescription)?;
+    // make sure the type is one that has a serialization at all
+    result_type.max_serialized_size()?;
+
+    checker.type_check_expects(&args[1], context, &TypeSignature::max_buffer())?;
+
+    Ok(TypeSignature::new_option(result_type)?)
+}
+
+/// Returns the length of a buffer argument, for sizing the string it converts into.
+fn check_buffer_argument(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> CheckResult<u32> {
+    check_argument_count(1, args)?;
+
+    let input_type = checker.type_check(&args[0], context)?;
+    runtime_cost!(cost_functions::ANALYSIS_ITERABLE_FUNC, checker, 1)?;
+
+    match input_type {
+        TypeSignature::SequenceType(BufferType(len)) => Ok(u32::from(len)),
+        _ => Err(CheckErrors::TypeError(TypeSignature::max_buffer(), input_type).into()),
+    }
+}
+
+pub fn check_special_buff_to_ascii(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    let len = check_buffer_argument(checker, args, context)?;
+    let string_type = TypeSignature::SequenceType(StringType(ASCII(BufferLength::try_from(len)?)));
+    Ok(TypeSignature::new_option(string_type)?)
+}
+
+/// Every UTF-8 character is at least one byte, so a buffer of N bytes holds at most N characters.
+pub fn check_special_buff_to_utf8(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    let len = check_buffer_argument(checker, args, context)?;
+    let string_type =
+        TypeSignature::SequenceType(StringType(UTF8(StringUTF8Length::try_from(len)?)));
+    Ok(TypeSignature::new_option(string_type)?)
+}
+
+pub fn check_special_ascii_to_buff(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(1, args)?;
+
+    let input_type = checker.type_check(&args[0], context)?;
+    runtime_cost!(cost_functions::ANALYSIS_ITERABLE_FUNC, checker, 1)?;
+
+    match input_type {
+        TypeSignature::SequenceType(StringType(ASCII(len))) => {
+            Ok(TypeSignature::SequenceType(BufferType(len)))
+        }
+        _ => Err(CheckErrors::TypeError(TypeSignature::max_string_ascii(), input_type).into()),
+    }
+}
+
+/// Every UTF-8 character is at most four bytes.
+pub fn check_special_utf8_to_buff(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(1, args)?;
+
+    let input_type = checker.type_check(&args[0], context)?;
+    runtime_cost!(cost_functions::ANALYSIS_ITERABLE_FUNC, checker, 1)?;
+
+    match input_type {
+        TypeSignature::SequenceType(StringType(UTF8(len))) => {
+            let byte_len = u32::from(&len)
+                .checked_mul(4)
+                .ok_or(CheckErrors::ValueTooLarge)?;
+            Ok(TypeSignature::SequenceType(BufferType(
+                BufferLength::try_from(byte_len)?,
+            )))
+        }
+        _ => Err(CheckErrors::TypeError(TypeSignature::max_string_utf8(), input_type).into()),
+    }
+}
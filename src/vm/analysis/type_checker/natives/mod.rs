@@ -20,17 +20,20 @@ use super::{
 use std::convert::TryFrom;
 use vm::analysis::errors::{CheckError, CheckErrors, CheckResult};
 use vm::errors::{Error as InterpError, RuntimeErrorType};
-use vm::functions::{handle_binding_list, NativeFunctions};
+use vm::functions::{handle_binding_list, NativeFunctions, MAX_INT_STRING_LEN};
+use vm::types::signatures::{BufferLength, StringUTF8Length};
 use vm::types::{
     BlockInfoProperty, FixedFunction, FunctionArg, FunctionSignature, FunctionType, PrincipalData,
     TupleTypeSignature, TypeSignature, Value, BUFF_20, BUFF_32, BUFF_33, BUFF_64, BUFF_65,
     MAX_VALUE_SIZE,
 };
+use vm::types::{SequenceSubtype::*, StringSubtype::*};
 use vm::{ClarityName, SymbolicExpression, SymbolicExpressionType};
 
 use vm::costs::{analysis_typecheck_cost, cost_functions, CostOverflowingMath};
 
 mod assets;
+mod conversions;
 mod maps;
 mod options;
 mod sequences;
@@ -608,6 +611,20 @@ impl TypedNativeFunction {
                 )],
                 returns: TypeSignature::UIntType,
             }))),
+            GetStxAccount => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    TypeSignature::PrincipalType,
+                    ClarityName::try_from("owner".to_owned())
+                        .expect("FAIL: ClarityName failed to accept default arg name"),
+                )],
+                returns: TupleTypeSignature::try_from(vec![
+                    ("locked".into(), TypeSignature::UIntType),
+                    ("unlocked".into(), TypeSignature::UIntType),
+                    ("unlock-height".into(), TypeSignature::UIntType),
+                ])
+                .expect("FAIL: failed to construct the stx-account return type")
+                .into(),
+            }))),
             StxTransfer => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
                 args: vec![
                     FunctionArg::new(
@@ -652,11 +669,16 @@ impl TypedNativeFunction {
                 .unwrap(),
             }))),
             GetTokenBalance => Special(SpecialNativeFunction(&assets::check_special_get_balance)),
+            GetTokenSupply => Special(SpecialNativeFunction(
+                &assets::check_special_get_token_supply,
+            )),
             GetAssetOwner => Special(SpecialNativeFunction(&assets::check_special_get_owner)),
             TransferToken => Special(SpecialNativeFunction(&assets::check_special_transfer_token)),
             TransferAsset => Special(SpecialNativeFunction(&assets::check_special_transfer_asset)),
             MintAsset => Special(SpecialNativeFunction(&assets::check_special_mint_asset)),
             MintToken => Special(SpecialNativeFunction(&assets::check_special_mint_token)),
+            BurnAsset => Special(SpecialNativeFunction(&assets::check_special_burn_asset)),
+            BurnToken => Special(SpecialNativeFunction(&assets::check_special_burn_token)),
             Equals => Special(SpecialNativeFunction(&check_special_equals)),
             If => Special(SpecialNativeFunction(&check_special_if)),
             Let => Special(SpecialNativeFunction(&check_special_let)),
@@ -669,6 +691,9 @@ impl TypedNativeFunction {
             Concat => Special(SpecialNativeFunction(&sequences::check_special_concat)),
             AsMaxLen => Special(SpecialNativeFunction(&sequences::check_special_as_max_len)),
             Len => Special(SpecialNativeFunction(&sequences::check_special_len)),
+            ElementAt => Special(SpecialNativeFunction(&sequences::check_special_element_at)),
+            IndexOf => Special(SpecialNativeFunction(&sequences::check_special_index_of)),
+            Slice => Special(SpecialNativeFunction(&sequences::check_special_slice)),
             ListCons => Special(SpecialNativeFunction(&check_special_list_cons)),
             FetchEntry => Special(SpecialNativeFunction(&maps::check_special_fetch_entry)),
             SetEntry => Special(SpecialNativeFunction(&maps::check_special_set_entry)),
@@ -701,6 +726,52 @@ impl TypedNativeFunction {
             IsNone => Special(SpecialNativeFunction(&options::check_special_is_optional)),
             IsSome => Special(SpecialNativeFunction(&options::check_special_is_optional)),
             AtBlock => Special(SpecialNativeFunction(&check_special_at_block)),
+            ToConsensusBuff => Special(SpecialNativeFunction(
+                &conversions::check_special_to_consensus_buff,
+            )),
+            FromConsensusBuff => Special(SpecialNativeFunction(
+                &conversions::check_special_from_consensus_buff,
+            )),
+            IntToAscii => Simple(SimpleNativeFunction(FunctionType::UnionArgs(
+                vec![TypeSignature::IntType, TypeSignature::UIntType],
+                TypeSignature::SequenceType(StringType(ASCII(
+                    BufferLength::try_from(MAX_INT_STRING_LEN)
+                        .expect("FAIL: failed to construct the int-to-ascii return type"),
+                ))),
+            ))),
+            IntToUtf8 => Simple(SimpleNativeFunction(FunctionType::UnionArgs(
+                vec![TypeSignature::IntType, TypeSignature::UIntType],
+                TypeSignature::SequenceType(StringType(UTF8(
+                    StringUTF8Length::try_from(MAX_INT_STRING_LEN)
+                        .expect("FAIL: failed to construct the int-to-utf8 return type"),
+                ))),
+            ))),
+            StringToInt => Simple(SimpleNativeFunction(FunctionType::UnionArgs(
+                vec![
+                    TypeSignature::max_string_ascii(),
+                    TypeSignature::max_string_utf8(),
+                ],
+                TypeSignature::OptionalType(Box::new(TypeSignature::IntType)),
+            ))),
+            StringToUInt => Simple(SimpleNativeFunction(FunctionType::UnionArgs(
+                vec![
+                    TypeSignature::max_string_ascii(),
+                    TypeSignature::max_string_utf8(),
+                ],
+                TypeSignature::OptionalType(Box::new(TypeSignature::UIntType)),
+            ))),
+            BuffToAscii => Special(SpecialNativeFunction(
+                &conversions::check_special_buff_to_ascii,
+            )),
+            BuffToUtf8 => Special(SpecialNativeFunction(
+                &conversions::check_special_buff_to_utf8,
+            )),
+            AsciiToBuff => Special(SpecialNativeFunction(
+                &conversions::check_special_ascii_to_buff,
+            )),
+            Utf8ToBuff => Special(SpecialNativeFunction(
+                &conversions::check_special_utf8_to_buff,
+            )),
         }
     }
 }
@@ -321,3 +321,76 @@ pub fn check_special_len(
 
     Ok(TypeSignature::UIntType)
 }
+
+/// The type of a single element of a sequence: the entry type of a list, or a length-1
+/// buffer/string.
+fn sequence_element_type(sequence: &TypeSignature) -> CheckResult<TypeSignature> {
+    match sequence {
+        TypeSignature::SequenceType(ListType(list)) => Ok(list.get_list_item_type().clone()),
+        TypeSignature::SequenceType(BufferType(_)) => Ok(TypeSignature::min_buffer()),
+        TypeSignature::SequenceType(StringType(ASCII(_))) => Ok(TypeSignature::min_string_ascii()),
+        TypeSignature::SequenceType(StringType(UTF8(_))) => Ok(TypeSignature::min_string_utf8()),
+        _ => Err(CheckErrors::ExpectedSequence(sequence.clone()).into()),
+    }
+}
+
+pub fn check_special_element_at(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(2, args)?;
+
+    let sequence = checker.type_check(&args[0], context)?;
+    runtime_cost!(cost_functions::ANALYSIS_ITERABLE_FUNC, checker, 1)?;
+
+    checker.type_check_expects(&args[1], context, &TypeSignature::UIntType)?;
+
+    let element_type = sequence_element_type(&sequence)?;
+    Ok(TypeSignature::new_option(element_type)?)
+}
+
+pub fn check_special_index_of(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(2, args)?;
+
+    let sequence = checker.type_check(&args[0], context)?;
+    runtime_cost!(cost_functions::ANALYSIS_ITERABLE_FUNC, checker, 1)?;
+
+    let expected_item_type = sequence_element_type(&sequence)?;
+    if expected_item_type.is_no_type() {
+        checker.type_check(&args[1], context)?;
+    } else {
+        let item_type = checker.type_check(&args[1], context)?;
+        analysis_typecheck_cost(checker, &expected_item_type, &item_type)?;
+        if !expected_item_type.admits_type(&item_type) {
+            return Err(CheckErrors::TypeError(expected_item_type, item_type).into());
+        }
+    }
+
+    Ok(TypeSignature::new_option(TypeSignature::UIntType)?)
+}
+
+pub fn check_special_slice(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(3, args)?;
+
+    let sequence = checker.type_check(&args[0], context)?;
+    runtime_cost!(cost_functions::ANALYSIS_ITERABLE_FUNC, checker, 1)?;
+
+    match sequence {
+        TypeSignature::SequenceType(_) => Ok(()),
+        _ => Err(CheckErrors::ExpectedSequence(sequence.clone())),
+    }?;
+
+    checker.type_check_expects(&args[1], context, &TypeSignature::UIntType)?;
+    checker.type_check_expects(&args[2], context, &TypeSignature::UIntType)?;
+
+    Ok(TypeSignature::new_option(sequence)?)
+}
@@ -29,10 +29,13 @@ fn string_ascii_type(size: u32) -> TypeSignature {
 }
 
 const FIRST_CLASS_TOKENS: &str = "(define-fungible-token stackaroos)
+         (define-fungible-token stacka-capped u1000000)
          (define-non-fungible-token stacka-nfts (string-ascii 10))
          (nft-get-owner? stacka-nfts \"1234567890\" )
          (define-read-only (my-ft-get-balance (account principal))
             (ft-get-balance stackaroos account))
+         (define-read-only (my-ft-get-supply)
+            (ft-get-supply stacka-capped))
          (define-public (my-token-transfer (to principal) (amount uint))
             (ft-transfer? stackaroos amount tx-sender to))
          (define-public (faucet)
@@ -122,6 +125,10 @@ fn test_bad_asset_usage() {
         "(ft-get-balance 1234 tx-sender)",
         "(ft-get-balance stackaroos u100)",
         "(ft-get-balance stackaroos 100)",
+        "(ft-get-supply stackoos)",
+        "(ft-get-supply u1234)",
+        "(ft-get-supply stackaroos tx-sender)",
+        "(ft-get-supply stackaroos)",
         "(nft-get-owner? u1234 \"abc\")",
         "(nft-get-owner? stackoos \"abc\")",
         "(nft-get-owner? stacka-nfts u1234 )",
@@ -157,6 +164,10 @@ fn test_bad_asset_usage() {
         CheckErrors::BadTokenName,
         CheckErrors::TypeError(TypeSignature::PrincipalType, TypeSignature::UIntType),
         CheckErrors::TypeError(TypeSignature::PrincipalType, TypeSignature::IntType),
+        CheckErrors::NoSuchFT("stackoos".to_string()),
+        CheckErrors::BadTokenName,
+        CheckErrors::IncorrectArgumentCount(1, 2),
+        CheckErrors::UnboundedTokenSupply("stackaroos".to_string()),
         CheckErrors::BadTokenName,
         CheckErrors::NoSuchNFT("stackoos".to_string()),
         CheckErrors::TypeError(string_ascii_type(10), TypeSignature::UIntType),
@@ -20,9 +20,10 @@ use serde_json;
 use vm::analysis::errors::CheckErrors;
 use vm::analysis::mem_type_check;
 use vm::analysis::type_check;
+use vm::analysis::ContractAnalysis;
 use vm::analysis::{contract_interface_builder::build_contract_interface, AnalysisDatabase};
 use vm::ast::parse;
-use vm::database::MemoryBackingStore;
+use vm::database::{ClarityDeserializable, ClaritySerializable, MemoryBackingStore};
 use vm::types::QualifiedContractIdentifier;
 
 const SIMPLE_TOKENS: &str = "(define-map tokens ((account principal)) ((balance uint)))
@@ -150,6 +151,10 @@ fn test_names_tokens_contracts_interface() {
 
         (define-read-only (ro-f01) 0)
         (define-read-only (ro-f02 (a1 int)) 0)
+
+        (define-fungible-token ft1)
+        (define-fungible-token ft2 u1000000)
+        (define-fungible-token ft3 (+ u1 u2))
     ";
 
     let contract_analysis = mem_type_check(INTERFACE_TEST_CONTRACT).unwrap().1;
@@ -356,7 +361,11 @@ fn test_names_tokens_contracts_interface() {
             { "name": "d-var2", "access": "variable", "type": "int128" },
             { "name": "d-var3", "access": "variable", "type": { "buffer": { "length": 5 } } }
         ],
-        "fungible_tokens": [],
+        "fungible_tokens": [
+            { "name": "ft1", "bounded": false, "max_supply": null },
+            { "name": "ft2", "bounded": true, "max_supply": 1000000 },
+            { "name": "ft3", "bounded": true, "max_supply": null }
+        ],
         "non_fungible_tokens": []
     }"#).unwrap();
 
@@ -365,6 +374,50 @@ fn test_names_tokens_contracts_interface() {
     assert_json_eq!(test_contract_json, test_contract_json_expected);
 }
 
+#[test]
+fn test_load_analysis_without_ft_bounds() {
+    const FT_CONTRACT: &str = "
+        (define-fungible-token ft1)
+        (define-fungible-token ft2 u1000000)
+    ";
+
+    let mut contract_analysis = mem_type_check(FT_CONTRACT).unwrap().1;
+    contract_analysis.contract_interface = Some(build_contract_interface(&contract_analysis));
+
+    // strip what the analysis and its interface didn't record before token bounds were added
+    let mut legacy_json: serde_json::Value =
+        serde_json::from_str(&contract_analysis.serialize()).unwrap();
+    legacy_json
+        .as_object_mut()
+        .unwrap()
+        .remove("fungible_token_bounds");
+    for token in legacy_json["contract_interface"]["fungible_tokens"]
+        .as_array_mut()
+        .unwrap()
+    {
+        let token = token.as_object_mut().unwrap();
+        token.remove("bounded");
+        token.remove("max_supply");
+    }
+    assert_eq!(legacy_json["fungible_tokens"], json!(["ft1", "ft2"]));
+
+    let legacy_analysis = ContractAnalysis::deserialize(&legacy_json.to_string());
+    assert_eq!(
+        legacy_analysis.fungible_tokens,
+        contract_analysis.fungible_tokens
+    );
+    assert!(legacy_analysis.fungible_token_bounds.is_empty());
+    for token in legacy_analysis
+        .contract_interface
+        .unwrap()
+        .fungible_tokens
+        .iter()
+    {
+        assert!(!token.bounded);
+        assert!(token.max_supply.is_none());
+    }
+}
+
 #[test]
 fn test_names_tokens_contracts() {
     let tokens_contract_id = QualifiedContractIdentifier::local("tokens").unwrap();
@@ -502,6 +555,7 @@ fn test_same_function_name() {
 #[test]
 fn test_expects() {
     use vm::analysis::type_check;
+    use vm::analysis::ContractAnalysis;
     let okay = "(define-map tokens ((id int)) ((balance int)))
          (define-private (my-get-token-balance)
             (let ((balance (unwrap!
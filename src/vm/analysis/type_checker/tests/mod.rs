@@ -181,8 +181,14 @@ fn test_stx_ops() {
         "(stx-burn? u10 'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G)",
         "(stx-transfer? u10 tx-sender 'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G)",
         "(stx-get-balance 'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G)",
+        "(stx-account 'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G)",
+    ];
+    let expected = [
+        "(response bool uint)",
+        "(response bool uint)",
+        "uint",
+        "(tuple (locked uint) (unlock-height uint) (unlocked uint))",
     ];
-    let expected = ["(response bool uint)", "(response bool uint)", "uint"];
 
     let bad = [
         "(stx-transfer? u4 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
@@ -194,7 +200,9 @@ fn test_stx_ops() {
         "(stx-burn? u4 true)",
         "(stx-burn? u4 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
         "(stx-get-balance true)",
-        "(stx-get-balance 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)"
+        "(stx-get-balance 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        "(stx-account u1)",
+        "(stx-account)",
     ];
     let bad_expected = [
         CheckErrors::IncorrectArgumentCount(3, 2),
@@ -207,6 +215,8 @@ fn test_stx_ops() {
         CheckErrors::IncorrectArgumentCount(2, 3),
         CheckErrors::TypeError(PrincipalType, BoolType),
         CheckErrors::IncorrectArgumentCount(1, 2),
+        CheckErrors::TypeError(PrincipalType, UIntType),
+        CheckErrors::IncorrectArgumentCount(1, 0),
     ];
 
     for (good_test, expected) in good.iter().zip(expected.iter()) {
@@ -812,12 +822,12 @@ fn test_buff_fold() {
     let good = [
         "(define-private (get-len (x (buff 1)) (acc uint)) (+ acc u1))
         (fold get-len 0x000102030405 u0)",
-        "(define-private (slice (x (buff 1)) (acc (tuple (limit uint) (cursor uint) (data (buff 10)))))
+        "(define-private (take-prefix (x (buff 1)) (acc (tuple (limit uint) (cursor uint) (data (buff 10)))))
             (if (< (get cursor acc) (get limit acc))
                 (let ((data (default-to (get data acc) (as-max-len? (concat (get data acc) x) u10))))
                     (tuple (limit (get limit acc)) (cursor (+ u1 (get cursor acc))) (data data)))
                 acc))
-        (fold slice 0x00010203040506070809 (tuple (limit u5) (cursor u0) (data 0x)))"];
+        (fold take-prefix 0x00010203040506070809 (tuple (limit u5) (cursor u0) (data 0x)))"];
     let expected = [
         "uint",
         "(tuple (cursor uint) (data (buff 10)) (limit uint))",
@@ -873,6 +883,125 @@ fn test_buff_as_max_len() {
     }
 }
 
+#[test]
+fn test_element_at() {
+    let good = [
+        "(element-at (list 1 2 3 4 5) u100)",
+        "(element-at (list (some 1) none) u0)",
+        "(element-at 0x0102 u1)",
+        "(element-at \"abc\" u1)",
+        "(element-at u\"abc\" u1)",
+    ];
+    let expected = [
+        "(optional int)",
+        "(optional (optional int))",
+        "(optional (buff 1))",
+        "(optional (string-ascii 1))",
+        "(optional (string-utf8 1))",
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    let bad = [
+        "(element-at (list 1 2 3) 1)",
+        "(element-at 3 u1)",
+        "(element-at (list 1 2 3))",
+    ];
+    let bad_expected = [
+        CheckErrors::TypeError(UIntType, IntType),
+        CheckErrors::ExpectedSequence(IntType),
+        CheckErrors::IncorrectArgumentCount(2, 1),
+    ];
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
+#[test]
+fn test_index_of() {
+    let good = [
+        "(index-of (list 1 2 3 4 5) 3)",
+        "(index-of (list (some 1) none) none)",
+        "(index-of (list) 1)",
+        "(index-of 0x0102 0x02)",
+        "(index-of \"abc\" \"b\")",
+        "(index-of u\"abc\" u\"b\")",
+    ];
+
+    for good_test in good.iter() {
+        assert_eq!(
+            "(optional uint)",
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    let bad = [
+        "(index-of (list 1 2 3) u1)",
+        "(index-of 0x0102 0x0102)",
+        "(index-of \"abc\" u\"b\")",
+        "(index-of 3 1)",
+    ];
+    let bad_expected = [
+        CheckErrors::TypeError(IntType, UIntType),
+        CheckErrors::TypeError(buff_type(1), buff_type(2)),
+        CheckErrors::TypeError(
+            ascii_type(1),
+            SequenceType(StringType(UTF8(1_u32.try_into().unwrap()))),
+        ),
+        CheckErrors::ExpectedSequence(IntType),
+    ];
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
+#[test]
+fn test_slice() {
+    let good = [
+        "(slice (list 1 2 3 4 5) u1 u3)",
+        "(slice 0x010203 u0 u2)",
+        "(slice \"abc\" u1 u2)",
+        "(slice u\"abc\" u1 u2)",
+    ];
+    let expected = [
+        "(optional (list 5 int))",
+        "(optional (buff 3))",
+        "(optional (string-ascii 3))",
+        "(optional (string-utf8 3))",
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    let bad = [
+        "(slice (list 1 2 3) 1 u2)",
+        "(slice (list 1 2 3) u1 2)",
+        "(slice 3 u1 u2)",
+        "(slice 0x0102 u1)",
+    ];
+    let bad_expected = [
+        CheckErrors::TypeError(UIntType, IntType),
+        CheckErrors::TypeError(UIntType, IntType),
+        CheckErrors::ExpectedSequence(IntType),
+        CheckErrors::IncorrectArgumentCount(3, 2),
+    ];
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
 #[test]
 fn test_native_append() {
     let good = ["(append (list 2 3) 4)", "(append (list u0) u0)"];
@@ -1919,12 +2048,12 @@ fn test_string_ascii_fold() {
     let good = [
         "(define-private (get-len (x (string-ascii 1)) (acc uint)) (+ acc u1))
         (fold get-len \"blockstack\" u0)",
-        "(define-private (slice (x (string-ascii 1)) (acc (tuple (limit uint) (cursor uint) (data (string-ascii 10)))))
+        "(define-private (take-prefix (x (string-ascii 1)) (acc (tuple (limit uint) (cursor uint) (data (string-ascii 10)))))
             (if (< (get cursor acc) (get limit acc))
                 (let ((data (default-to (get data acc) (as-max-len? (concat (get data acc) x) u10))))
                     (tuple (limit (get limit acc)) (cursor (+ u1 (get cursor acc))) (data data)))
                 acc))
-        (fold slice \"blockstack\" (tuple (limit u5) (cursor u0) (data \"\")))"];
+        (fold take-prefix \"blockstack\" (tuple (limit u5) (cursor u0) (data \"\")))"];
     let expected = [
         "uint",
         "(tuple (cursor uint) (data (string-ascii 10)) (limit uint))",
@@ -1972,12 +2101,12 @@ fn test_string_utf8_fold() {
     let good = [
         "(define-private (get-len (x (string-utf8 1)) (acc uint)) (+ acc u1))
         (fold get-len u\"blockstack\" u0)",
-        "(define-private (slice (x (string-utf8 1)) (acc (tuple (limit uint) (cursor uint) (data (string-utf8 11)))))
+        "(define-private (take-prefix (x (string-utf8 1)) (acc (tuple (limit uint) (cursor uint) (data (string-utf8 11)))))
             (if (< (get cursor acc) (get limit acc))
                 (let ((data (default-to (get data acc) (as-max-len? (concat (get data acc) x) u11))))
                     (tuple (limit (get limit acc)) (cursor (+ u1 (get cursor acc))) (data data)))
                 acc))
-        (fold slice u\"blockstack\\u{1F926}\" (tuple (limit u5) (cursor u0) (data u\"\")))"];
+        (fold take-prefix u\"blockstack\\u{1F926}\" (tuple (limit u5) (cursor u0) (data u\"\")))"];
     let expected = [
         "uint",
         "(tuple (cursor uint) (data (string-utf8 11)) (limit uint))",
@@ -36,6 +36,18 @@ pub trait AnalysisPass {
     ) -> CheckResult<()>;
 }
 
+/// The max supply a fungible token was defined with.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum FungibleTokenBound {
+    /// Defined without a max supply.
+    Unbounded,
+    /// Defined with a uint literal max supply.
+    Static(u128),
+    /// Defined with a max supply computed by an expression, which is only evaluated when the
+    /// contract is initialized.
+    Computed,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ContractAnalysis {
     pub contract_identifier: QualifiedContractIdentifier,
@@ -46,6 +58,10 @@ pub struct ContractAnalysis {
     pub map_types: BTreeMap<ClarityName, (TypeSignature, TypeSignature)>,
     pub persisted_variable_types: BTreeMap<ClarityName, TypeSignature>,
     pub fungible_tokens: BTreeSet<ClarityName>,
+    /// How each of `fungible_tokens` was bounded.  Analyses stored before bounds were recorded
+    /// don't have this, and load with it empty.
+    #[serde(default)]
+    pub fungible_token_bounds: BTreeMap<ClarityName, FungibleTokenBound>,
     pub non_fungible_tokens: BTreeMap<ClarityName, TypeSignature>,
     pub defined_traits: BTreeMap<ClarityName, BTreeMap<ClarityName, FunctionSignature>>,
     pub implemented_traits: BTreeSet<TraitIdentifier>,
@@ -78,6 +94,7 @@ impl ContractAnalysis {
             defined_traits: BTreeMap::new(),
             implemented_traits: BTreeSet::new(),
             fungible_tokens: BTreeSet::new(),
+            fungible_token_bounds: BTreeMap::new(),
             non_fungible_tokens: BTreeMap::new(),
             cost_track: Some(cost_track),
         }
@@ -132,8 +149,9 @@ impl ContractAnalysis {
         self.non_fungible_tokens.insert(name, nft_type);
     }
 
-    pub fn add_fungible_token(&mut self, name: ClarityName) {
-        self.fungible_tokens.insert(name);
+    pub fn add_fungible_token(&mut self, name: ClarityName, bound: FungibleTokenBound) {
+        self.fungible_tokens.insert(name.clone());
+        self.fungible_token_bounds.insert(name, bound);
     }
 
     pub fn add_defined_trait(
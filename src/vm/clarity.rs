@@ -21,9 +21,11 @@ use vm::ast;
 use vm::ast::{errors::ParseError, errors::ParseErrors, ContractAST};
 use vm::contexts::{AssetMap, Environment, OwnedEnvironment};
 use vm::costs::{CostTracker, ExecutionCost, LimitedCostTracker};
+use vm::database::marf::ContractCommitment;
 use vm::database::{
-    BurnStateDB, ClarityDatabase, HeadersDB, MarfedKV, RollbackWrapper,
-    RollbackWrapperPersistedLog, SqliteConnection,
+    BurnStateDB, ClarityBackingStore, ClarityDatabase, ClarityDeserializable, HeadersDB, MarfedKV,
+    RollbackWrapper, RollbackWrapperPersistedLog, SqliteConnection, StoreType, NULL_BURN_STATE_DB,
+    NULL_HEADER_DB,
 };
 use vm::errors::Error as InterpreterError;
 use vm::representations::SymbolicExpression;
@@ -34,12 +36,21 @@ use vm::types::{
 use chainstate::burn::BlockHeaderHash;
 use chainstate::stacks::events::StacksTransactionEvent;
 use chainstate::stacks::index::marf::MARF;
-use chainstate::stacks::index::{MarfTrieId, TrieHash};
-use chainstate::stacks::StacksBlockId;
+use chainstate::stacks::index::prune::MarfPruneSummary;
+use chainstate::stacks::index::snapshot::ClarityMetadataRow;
+use chainstate::stacks::index::{Error as MarfError, MarfTrieId, TrieHash};
+use chainstate::stacks::{
+    StacksBlockHeader, StacksBlockId, MINER_BLOCK_CONSENSUS_HASH, MINER_BLOCK_HEADER_HASH,
+};
+
+use util::hash::Sha512Trunc256Sum;
 
+use std::collections::{BTreeMap, HashMap};
 use std::error;
 use std::fmt;
 
+use serde_json::Value as JsonValue;
+
 ///
 /// A high-level interface for interacting with the Clarity VM.
 ///
@@ -203,6 +214,12 @@ impl ClarityBlockConnection<'_> {
             None => ExecutionCost::zero(),
         }
     }
+
+    /// Replace the block's cost tracker with a fresh one that allows at most `limit`, instead of
+    /// the block limit.  What the block spent so far is forgotten.
+    pub fn set_cost_limit(&mut self, limit: ExecutionCost) -> () {
+        self.cost_track = Some(LimitedCostTracker::new(limit));
+    }
 }
 
 impl ClarityInstance {
@@ -228,6 +245,28 @@ impl ClarityInstance {
         f(datastore.get_marf())
     }
 
+    /// Prune the Clarity state of blocks more than `keep_depth` blocks below `tip`.  No block
+    /// connection may be open.
+    pub fn prune(
+        &mut self,
+        tip: &StacksBlockId,
+        keep_depth: u32,
+    ) -> Result<MarfPruneSummary, MarfError> {
+        let datastore = self
+            .datastore
+            .as_mut()
+            .expect("FAIL: use of prune while prior block neither committed nor rolled back.");
+        datastore.prune(tip, keep_depth)
+    }
+
+    /// Has the Clarity state at the given block been pruned?
+    pub fn is_block_pruned(&self, bhh: &StacksBlockId) -> Result<bool, MarfError> {
+        let datastore = self.datastore.as_ref().expect(
+            "FAIL: use of is_block_pruned while prior block neither committed nor rolled back.",
+        );
+        datastore.is_block_pruned(bhh)
+    }
+
     pub fn begin_block<'a>(
         &'a mut self,
         current: &StacksBlockId,
@@ -281,6 +320,233 @@ impl ClarityInstance {
         }
     }
 
+    /// Begin an ephemeral block `next` on top of `current`.  The block can run transactions like
+    /// any other, but its writes are only held in memory and it can only be rolled back, so it
+    /// never takes a write lock on the underlying datastore.
+    pub fn begin_ephemeral<'a>(
+        &'a mut self,
+        current: &StacksBlockId,
+        next: &StacksBlockId,
+        header_db: &'a dyn HeadersDB,
+        burn_state_db: &'a dyn BurnStateDB,
+    ) -> ClarityBlockConnection<'a> {
+        let mut datastore = self
+            .datastore
+            .take()
+            // this is a panicking failure, because there should be _no instance_ in which a ClarityBlockConnection
+            //   doesn't restore it's parent's datastore
+            .expect(
+                "FAIL: use of begin_ephemeral while prior block neither committed nor rolled back.",
+            );
+
+        datastore.begin_ephemeral(current, next);
+
+        let cost_track = Some(LimitedCostTracker::new(self.block_limit.clone()));
+
+        ClarityBlockConnection {
+            datastore,
+            header_db,
+            burn_state_db,
+            parent: self,
+            cost_track,
+        }
+    }
+
+    /// Verify and store the Clarity contract metadata of a MARF snapshot that was just imported
+    /// into this instance's MARF (see `chainstate::stacks::index::snapshot`).  The MARF only
+    /// commits to the hash of each contract's source, so each contract's source is checked
+    /// against the hash committed at `tip`.  The rest of its metadata -- the parsed contract,
+    /// its analysis and the types of its definitions -- is re-derived from the source, by
+    /// deploying each block's contracts again in an ephemeral block on top of the block's
+    /// parent.  Every row of the snapshot must agree with the re-derived one, and only the
+    /// re-derived rows are stored.  Returns the number of contracts imported.
+    pub fn import_contract_metadata(
+        &mut self,
+        tip: &StacksBlockId,
+        rows: Vec<ClarityMetadataRow<StacksBlockId>>,
+    ) -> Result<u32, Error> {
+        let mut claimed: HashMap<
+            QualifiedContractIdentifier,
+            (StacksBlockId, HashMap<String, String>),
+        > = HashMap::new();
+        for row in rows.into_iter() {
+            let (contract, key) = parse_metadata_key(&row.key).ok_or_else(|| {
+                Error::BadTransaction(format!("Malformed Clarity metadata key {}", &row.key))
+            })?;
+            let (block, entries) = claimed
+                .entry(contract.clone())
+                .or_insert_with(|| (row.block_hash.clone(), HashMap::new()));
+            if *block != row.block_hash {
+                return Err(Error::BadTransaction(format!(
+                    "Clarity metadata of {} belongs to both {} and {}",
+                    &contract, block, &row.block_hash
+                )));
+            }
+            entries.insert(key, row.value);
+        }
+
+        let contract_src_key =
+            ClarityDatabase::make_metadata_key(StoreType::Contract, "contract-src");
+
+        // check each contract's source against its commitment, and group the contracts by the
+        // height of the block that deployed them.  Each block's contracts are deployed again on
+        // top of its parent, so they see the same block height; the first block has no parent
+        // in the MARF, so its contracts are deployed on top of it instead.
+        let mut blocks: BTreeMap<u32, (StacksBlockId, StacksBlockId, Vec<_>)> = BTreeMap::new();
+        {
+            let datastore = self.datastore.as_mut().expect(
+                "FAIL: use of import_contract_metadata while prior block neither committed nor rolled back.",
+            );
+            datastore.set_chain_tip(tip);
+            for (contract, (block, entries)) in claimed.iter() {
+                let source = entries.get(&contract_src_key).ok_or_else(|| {
+                    Error::BadTransaction(format!("No source for contract {}", contract))
+                })?;
+                let commitment = datastore
+                    .get(&MarfedKV::make_contract_hash_key(contract))
+                    .map(|commitment| ContractCommitment::deserialize(&commitment))
+                    .ok_or_else(|| {
+                        Error::BadTransaction(format!(
+                            "Contract {} does not exist at {}",
+                            contract, tip
+                        ))
+                    })?;
+                let height = commitment.block_height;
+                if datastore.get_block_at_height(height).as_ref() != Some(block) {
+                    return Err(Error::BadTransaction(format!(
+                        "Contract {} was not deployed in {}",
+                        contract, block
+                    )));
+                }
+                if Sha512Trunc256Sum::from_data(source.as_bytes()) != commitment.hash {
+                    return Err(Error::BadTransaction(format!(
+                        "Source of contract {} does not match its hash {}",
+                        contract, &commitment.hash
+                    )));
+                }
+                if !blocks.contains_key(&height) {
+                    let parent = match height {
+                        0 => block.clone(),
+                        _ => datastore
+                            .get_block_at_height(height - 1)
+                            .expect("FATAL: no parent for an imported block"),
+                    };
+                    blocks.insert(height, (block.clone(), parent, vec![]));
+                }
+                if let Some((_, _, contracts)) = blocks.get_mut(&height) {
+                    contracts.push((contract.clone(), source.clone()));
+                }
+            }
+        }
+
+        // contracts read the metadata of the contracts they depend on, so each block's metadata
+        // is stored before moving on to the next one
+        self.datastore.as_mut().unwrap().get_side_store().begin(tip);
+        let mut num_contracts = 0;
+        for (_, (block, parent, contracts)) in blocks.into_iter() {
+            let result = self
+                .rederive_contract_metadata(&parent, &contracts)
+                .and_then(|derived| {
+                    for (contract, _) in contracts.iter() {
+                        for (key, value) in claimed[contract].1.iter() {
+                            match derived.get(&(contract.to_string(), key.clone())) {
+                                Some(derived_value)
+                                    if metadata_agrees(key, derived_value, value) => {}
+                                _ => {
+                                    return Err(Error::BadTransaction(format!(
+                                        "Metadata {} of contract {} does not match its source",
+                                        key, contract
+                                    )))
+                                }
+                            }
+                        }
+                    }
+                    Ok(derived)
+                });
+            let side_store = self.datastore.as_mut().unwrap().get_side_store();
+            let derived = match result {
+                Ok(derived) => derived,
+                Err(e) => {
+                    side_store.rollback(tip);
+                    return Err(e);
+                }
+            };
+            for ((contract, key), value) in derived.iter() {
+                side_store.insert_metadata(&block, contract, key, value);
+            }
+            num_contracts += contracts.len() as u32;
+        }
+        self.datastore
+            .as_mut()
+            .unwrap()
+            .get_side_store()
+            .commit(tip);
+
+        Ok(num_contracts)
+    }
+
+    /// Deploy `contracts` (with their sources) again in an ephemeral block on top of `parent`,
+    /// and return the metadata they write, keyed by contract and key.  The order they were
+    /// deployed in isn't known, so those that fail are retried until no more succeed, in case
+    /// they depend on one another.
+    fn rederive_contract_metadata(
+        &mut self,
+        parent: &StacksBlockId,
+        contracts: &[(QualifiedContractIdentifier, String)],
+    ) -> Result<HashMap<(String, String), String>, Error> {
+        let ephemeral_block = StacksBlockHeader::make_index_block_hash(
+            &MINER_BLOCK_CONSENSUS_HASH,
+            &MINER_BLOCK_HEADER_HASH,
+        );
+        let mut conn = self.begin_ephemeral(
+            parent,
+            &ephemeral_block,
+            &NULL_HEADER_DB,
+            &NULL_BURN_STATE_DB,
+        );
+        conn.set_cost_limit(ExecutionCost::max_value());
+
+        let mut pending: Vec<_> = contracts.iter().collect();
+        let mut last_error = None;
+        loop {
+            let num_pending = pending.len();
+            pending.retain(|(contract, source)| {
+                let result = conn.as_transaction(|tx| -> Result<(), Error> {
+                    let (contract_ast, contract_analysis) =
+                        tx.analyze_smart_contract(contract, source)?;
+                    tx.initialize_smart_contract(contract, &contract_ast, source, |_, _| false)?;
+                    tx.save_analysis(contract, &contract_analysis)?;
+                    Ok(())
+                });
+                match result {
+                    Ok(()) => false,
+                    Err(e) => {
+                        last_error = Some((contract.clone(), e));
+                        true
+                    }
+                }
+            });
+            if pending.is_empty() || pending.len() == num_pending {
+                break;
+            }
+        }
+
+        let derived = conn
+            .datastore
+            .get_ephemeral_metadata()
+            .cloned()
+            .unwrap_or_default();
+        conn.rollback_block();
+
+        match last_error {
+            Some((contract, e)) if !pending.is_empty() => Err(Error::BadTransaction(format!(
+                "Failed to deploy contract {} again: {}",
+                contract, e
+            ))),
+            _ => Ok(derived),
+        }
+    }
+
     pub fn read_only_connection<'a>(
         &'a mut self,
         at_block: &StacksBlockId,
@@ -334,6 +600,65 @@ impl ClarityInstance {
     }
 }
 
+/// Split a side-store metadata key, `clr-meta::<contract>::<key>`, into its contract and key.
+fn parse_metadata_key(key: &str) -> Option<(QualifiedContractIdentifier, String)> {
+    let mut parts = key.strip_prefix("clr-meta::")?.splitn(2, "::");
+    let contract = QualifiedContractIdentifier::parse(parts.next()?).ok()?;
+    let key = parts.next()?;
+    Some((contract, key.to_string()))
+}
+
+/// Does a snapshot's value of a contract metadata entry agree with the re-derived one?  Values
+/// that are JSON are compared as JSON, so that neither the order of a map's entries nor a field
+/// added to the format since the snapshot was taken counts as a difference.  The parsed
+/// contract also holds sets, which serialize in no particular order, so those are sorted first.
+fn metadata_agrees(key: &str, derived: &str, claimed: &str) -> bool {
+    if derived == claimed {
+        return true;
+    }
+    let (mut derived, mut claimed) = match (
+        serde_json::from_str::<JsonValue>(derived),
+        serde_json::from_str::<JsonValue>(claimed),
+    ) {
+        (Ok(derived), Ok(claimed)) => (derived, claimed),
+        _ => return false,
+    };
+    if key == ClarityDatabase::make_metadata_key(StoreType::Contract, "contract") {
+        for contract in [&mut derived, &mut claimed].iter_mut() {
+            for set in ["implemented_traits", "persisted_names"].iter() {
+                if let Some(JsonValue::Array(members)) = contract
+                    .get_mut("contract_context")
+                    .and_then(|context| context.get_mut(*set))
+                {
+                    members.sort_by_key(|member| member.to_string());
+                }
+            }
+        }
+    }
+    json_agrees(&derived, &claimed)
+}
+
+/// Is every field of `claimed` in `derived`, with the same value?
+fn json_agrees(derived: &JsonValue, claimed: &JsonValue) -> bool {
+    match (derived, claimed) {
+        (JsonValue::Object(derived), JsonValue::Object(claimed)) => {
+            claimed.iter().all(|(field, claimed_value)| {
+                derived.get(field).map_or(false, |derived_value| {
+                    json_agrees(derived_value, claimed_value)
+                })
+            })
+        }
+        (JsonValue::Array(derived), JsonValue::Array(claimed)) => {
+            derived.len() == claimed.len()
+                && derived
+                    .iter()
+                    .zip(claimed.iter())
+                    .all(|(derived, claimed)| json_agrees(derived, claimed))
+        }
+        _ => derived == claimed,
+    }
+}
+
 pub trait ClarityConnection {
     /// Do something to the underlying DB that involves only reading.
     fn with_clarity_db_readonly_owned<F, R>(&mut self, to_do: F) -> R
@@ -1267,6 +1592,379 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_ephemeral() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(marf, ExecutionCost::max_value());
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let ephemeral_identifier = QualifiedContractIdentifier::local("baz").unwrap();
+        let sender: PrincipalData = StandardPrincipalData::transient().into();
+
+        let contract = "
+        (define-data-var bar int 0)
+        (define-public (get-bar) (ok (var-get bar)))
+        (define-public (set-bar (x int) (y int))
+          (begin (var-set bar (/ x y)) (ok (var-get bar))))";
+
+        let ephemeral_contract = "(define-public (get-baz) (ok 2))";
+
+        {
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId::sentinel(),
+                &StacksBlockId([0 as u8; 32]),
+                &NULL_HEADER_DB,
+                &NULL_BURN_STATE_DB,
+            );
+
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn
+                    .analyze_smart_contract(&contract_identifier, &contract)
+                    .unwrap();
+                conn.initialize_smart_contract(&contract_identifier, &ct_ast, &contract, |_, _| {
+                    false
+                })
+                .unwrap();
+                conn.save_analysis(&contract_identifier, &ct_analysis)
+                    .unwrap();
+            });
+
+            conn.commit_block();
+        }
+
+        // writes and contract deployments are visible within the ephemeral block
+        {
+            let mut conn = clarity_instance.begin_ephemeral(
+                &StacksBlockId([0 as u8; 32]),
+                &StacksBlockId([1 as u8; 32]),
+                &NULL_HEADER_DB,
+                &NULL_BURN_STATE_DB,
+            );
+
+            assert_eq!(
+                conn.as_transaction(|tx| tx.run_contract_call(
+                    &sender,
+                    &contract_identifier,
+                    "set-bar",
+                    &[Value::Int(6), Value::Int(2)],
+                    |_, _| false
+                ))
+                .unwrap()
+                .0,
+                Value::okay(Value::Int(3)).unwrap()
+            );
+
+            assert_eq!(
+                conn.as_transaction(|tx| tx.run_contract_call(
+                    &sender,
+                    &contract_identifier,
+                    "get-bar",
+                    &[],
+                    |_, _| false
+                ))
+                .unwrap()
+                .0,
+                Value::okay(Value::Int(3)).unwrap()
+            );
+
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn
+                    .analyze_smart_contract(&ephemeral_identifier, &ephemeral_contract)
+                    .unwrap();
+                conn.initialize_smart_contract(
+                    &ephemeral_identifier,
+                    &ct_ast,
+                    &ephemeral_contract,
+                    |_, _| false,
+                )
+                .unwrap();
+                conn.save_analysis(&ephemeral_identifier, &ct_analysis)
+                    .unwrap();
+            });
+
+            assert_eq!(
+                conn.as_transaction(|tx| tx.run_contract_call(
+                    &sender,
+                    &ephemeral_identifier,
+                    "get-baz",
+                    &[],
+                    |_, _| false
+                ))
+                .unwrap()
+                .0,
+                Value::okay(Value::Int(2)).unwrap()
+            );
+
+            conn.rollback_block();
+        }
+
+        // the ephemeral block left nothing behind, not even a lock on its block hash
+        {
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId([0 as u8; 32]),
+                &StacksBlockId([1 as u8; 32]),
+                &NULL_HEADER_DB,
+                &NULL_BURN_STATE_DB,
+            );
+
+            assert_eq!(
+                conn.as_transaction(|tx| tx.run_contract_call(
+                    &sender,
+                    &contract_identifier,
+                    "get-bar",
+                    &[],
+                    |_, _| false
+                ))
+                .unwrap()
+                .0,
+                Value::okay(Value::Int(0)).unwrap()
+            );
+
+            conn.as_transaction(|conn| {
+                conn.with_clarity_db_readonly(|ref mut tx| {
+                    assert!(tx.get_contract_src(&ephemeral_identifier).is_none());
+                });
+            });
+
+            conn.commit_block();
+        }
+
+        let mut marf = clarity_instance.destroy();
+        let sql = marf.get_side_store();
+        // only the confirmed contract wrote metadata
+        assert_eq!(
+            0,
+            sql.mut_conn()
+                .query_row::<u32, _, _>(
+                    "SELECT COUNT(value) FROM metadata_table WHERE key LIKE '%baz%'",
+                    NO_PARAMS,
+                    |row| row.get(0)
+                )
+                .unwrap()
+        );
+    }
+
+    fn commit_empty_parent_block(clarity_instance: &mut ClarityInstance) {
+        let conn = clarity_instance.begin_block(
+            &StacksBlockId::sentinel(),
+            &StacksBlockId([0 as u8; 32]),
+            &NULL_HEADER_DB,
+            &NULL_BURN_STATE_DB,
+        );
+        conn.commit_block();
+    }
+
+    #[test]
+    #[should_panic(expected = "ephemeral blocks can only be rolled back")]
+    fn test_ephemeral_commit_refused() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(marf, ExecutionCost::max_value());
+        commit_empty_parent_block(&mut clarity_instance);
+
+        let conn = clarity_instance.begin_ephemeral(
+            &StacksBlockId([0 as u8; 32]),
+            &StacksBlockId([1 as u8; 32]),
+            &NULL_HEADER_DB,
+            &NULL_BURN_STATE_DB,
+        );
+        conn.commit_block();
+    }
+
+    #[test]
+    #[should_panic(expected = "ephemeral blocks can only be rolled back")]
+    fn test_ephemeral_commit_mined_refused() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(marf, ExecutionCost::max_value());
+        commit_empty_parent_block(&mut clarity_instance);
+
+        let conn = clarity_instance.begin_ephemeral(
+            &StacksBlockId([0 as u8; 32]),
+            &StacksBlockId([1 as u8; 32]),
+            &NULL_HEADER_DB,
+            &NULL_BURN_STATE_DB,
+        );
+        conn.commit_mined_block(&StacksBlockId([2 as u8; 32]));
+    }
+
+    fn deploy_contract(conn: &mut ClarityBlockConnection, contract: &str, name: &str) {
+        let contract_identifier = QualifiedContractIdentifier::local(name).unwrap();
+        conn.as_transaction(|conn| {
+            let (ct_ast, ct_analysis) = conn
+                .analyze_smart_contract(&contract_identifier, contract)
+                .unwrap();
+            conn.initialize_smart_contract(&contract_identifier, &ct_ast, contract, |_, _| false)
+                .unwrap();
+            conn.save_analysis(&contract_identifier, &ct_analysis)
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn test_import_contract_metadata() {
+        let src_name = "/tmp/clarity_test_import_contract_metadata_src";
+        let dst_name = "/tmp/clarity_test_import_contract_metadata_dst";
+        for name in [src_name, dst_name].iter() {
+            if fs::metadata(name).is_ok() {
+                fs::remove_dir_all(name).unwrap();
+            }
+        }
+
+        // baz depends on foo, in the same block, and qux on baz, in the next one
+        let marf = MarfedKV::open(src_name, None).unwrap();
+        let mut clarity_instance = ClarityInstance::new(marf, ExecutionCost::max_value());
+        let tip = StacksBlockId([1 as u8; 32]);
+        {
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId::sentinel(),
+                &StacksBlockId([0 as u8; 32]),
+                &NULL_HEADER_DB,
+                &NULL_BURN_STATE_DB,
+            );
+            deploy_contract(
+                &mut conn,
+                "(define-trait getter ((get-bar () (response int int))))
+                 (define-data-var bar int 7)
+                 (define-fungible-token tok u100)
+                 (define-public (get-bar) (ok (var-get bar)))",
+                "foo",
+            );
+            deploy_contract(
+                &mut conn,
+                "(impl-trait .foo.getter)
+                 (define-public (get-bar) (contract-call? .foo get-bar))",
+                "baz",
+            );
+            conn.commit_block();
+
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId([0 as u8; 32]),
+                &tip,
+                &NULL_HEADER_DB,
+                &NULL_BURN_STATE_DB,
+            );
+            deploy_contract(
+                &mut conn,
+                "(define-constant deployed-at block-height)
+                 (define-read-only (get-deployed-at) deployed-at)
+                 (define-public (get-bar) (contract-call? .baz get-bar))",
+                "qux",
+            );
+            conn.commit_block();
+        }
+
+        let mut marf = clarity_instance.destroy();
+        let mut snapshot = vec![];
+        let mut storage = marf.get_marf().reopen_storage_readonly().unwrap();
+        storage
+            .export_snapshot(&tip, Some(marf.get_side_store().mut_conn()), &mut snapshot)
+            .unwrap();
+
+        fs::create_dir_all(dst_name).unwrap();
+        let mut imported_storage =
+            TrieFileStorage::<StacksBlockId>::open(&format!("{}/marf", dst_name)).unwrap();
+        let mut imported_side_store =
+            SqliteConnection::initialize(&format!("{}/data.sqlite", dst_name)).unwrap();
+        let (_, rows) = imported_storage
+            .import_snapshot(
+                &mut &snapshot[..],
+                Some(imported_side_store.mut_conn()),
+                None,
+            )
+            .unwrap();
+        drop(imported_storage);
+        drop(imported_side_store);
+
+        let marf = MarfedKV::open(dst_name, None).unwrap();
+        let mut clarity_instance = ClarityInstance::new(marf, ExecutionCost::max_value());
+
+        let qux = QualifiedContractIdentifier::local("qux").unwrap();
+        let tamper = |contract: &QualifiedContractIdentifier, key: &str, from: &str, to: &str| {
+            let key = format!(
+                "clr-meta::{}::{}",
+                contract,
+                ClarityDatabase::make_metadata_key(StoreType::Contract, key)
+            );
+            let mut tampered = rows.clone();
+            let row = tampered.iter_mut().find(|row| row.key == key).unwrap();
+            assert!(row.value.contains(from));
+            row.value = row.value.replace(from, to);
+            tampered
+        };
+
+        // a source that doesn't match its commitment
+        assert!(clarity_instance
+            .import_contract_metadata(&tip, tamper(&qux, "contract-src", "baz", "foo"))
+            .unwrap_err()
+            .to_string()
+            .contains("does not match its hash"));
+
+        // a constant that doesn't match the one evaluated from the source
+        assert!(clarity_instance
+            .import_contract_metadata(
+                &tip,
+                tamper(&qux, "contract", "{\"UInt\":1}", "{\"UInt\":2}")
+            )
+            .unwrap_err()
+            .to_string()
+            .contains("does not match its source"));
+
+        // nothing was stored by the failed imports
+        assert_eq!(
+            0,
+            clarity_instance
+                .destroy()
+                .get_side_store()
+                .mut_conn()
+                .query_row::<u32, _, _>(
+                    "SELECT COUNT(value) FROM metadata_table",
+                    NO_PARAMS,
+                    |row| { row.get(0) }
+                )
+                .unwrap()
+        );
+
+        let marf = MarfedKV::open(dst_name, None).unwrap();
+        let mut clarity_instance = ClarityInstance::new(marf, ExecutionCost::max_value());
+        assert_eq!(
+            clarity_instance
+                .import_contract_metadata(&tip, rows)
+                .unwrap(),
+            3
+        );
+
+        // the re-derived metadata serves contract calls
+        assert_eq!(
+            clarity_instance
+                .eval_read_only(
+                    &tip,
+                    &NULL_HEADER_DB,
+                    &NULL_BURN_STATE_DB,
+                    &qux,
+                    "(get-deployed-at)"
+                )
+                .unwrap(),
+            Value::UInt(1)
+        );
+        let mut conn = clarity_instance.begin_block(
+            &tip,
+            &StacksBlockId([2 as u8; 32]),
+            &NULL_HEADER_DB,
+            &NULL_BURN_STATE_DB,
+        );
+        assert_eq!(
+            conn.as_transaction(|tx| tx.run_contract_call(
+                &StandardPrincipalData::transient().into(),
+                &qux,
+                "get-bar",
+                &[],
+                |_, _| false
+            ))
+            .unwrap()
+            .0,
+            Value::okay(Value::Int(7)).unwrap()
+        );
+        conn.rollback_block();
+    }
+
     #[test]
     pub fn test_tx_roll_backs() {
         let marf = MarfedKV::temporary();
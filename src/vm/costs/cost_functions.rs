@@ -151,11 +151,25 @@ def_runtime_cost!(AND { Linear(1, 1) });
 def_runtime_cost!(APPEND { Linear(1, 1) });
 def_runtime_cost!(CONCAT { Linear(1, 1) });
 def_runtime_cost!(AS_MAX_LEN { Constant(1) });
+def_runtime_cost!(ELEMENT_AT { Constant(1) });
+def_runtime_cost!(INDEX_OF { Linear(1, 1) });
+def_runtime_cost!(SLICE { Linear(1, 1) });
 
 def_runtime_cost!(CONTRACT_CALL { Constant(1) });
 def_runtime_cost!(CONTRACT_OF { Constant(1) });
 def_runtime_cost!(PRINCIPAL_OF { Constant(1) });
 
+def_runtime_cost!(TO_CONSENSUS_BUFF { Linear(1, 1) });
+def_runtime_cost!(FROM_CONSENSUS_BUFF { NLogN(1, 1) });
+def_runtime_cost!(INT_TO_ASCII { Constant(1) });
+def_runtime_cost!(INT_TO_UTF8 { Constant(1) });
+def_runtime_cost!(STRING_TO_INT { Constant(1) });
+def_runtime_cost!(STRING_TO_UINT { Constant(1) });
+def_runtime_cost!(BUFF_TO_ASCII { Linear(1, 1) });
+def_runtime_cost!(BUFF_TO_UTF8 { Linear(1, 1) });
+def_runtime_cost!(ASCII_TO_BUFF { Linear(1, 1) });
+def_runtime_cost!(UTF8_TO_BUFF { Linear(1, 1) });
+
 pub const AT_BLOCK: SimpleCostSpecification = SimpleCostSpecification {
     write_length: Constant(0),
     write_count: Constant(0),
@@ -260,6 +274,14 @@ pub const STX_BALANCE: SimpleCostSpecification = SimpleCostSpecification {
     read_length: Constant(1),
 };
 
+pub const STX_GET_ACCOUNT: SimpleCostSpecification = SimpleCostSpecification {
+    write_length: Constant(0),
+    write_count: Constant(0),
+    runtime: Constant(1),
+    read_count: Constant(1),
+    read_length: Constant(1),
+};
+
 pub const STX_TRANSFER: SimpleCostSpecification = SimpleCostSpecification {
     write_length: Constant(1),
     write_count: Constant(1),
@@ -284,6 +306,14 @@ pub const FT_TRANSFER: SimpleCostSpecification = SimpleCostSpecification {
     read_length: Constant(1),
 };
 
+pub const FT_BURN: SimpleCostSpecification = SimpleCostSpecification {
+    write_length: Constant(1),
+    write_count: Constant(2),
+    runtime: Constant(1),
+    read_count: Constant(2),
+    read_length: Constant(1),
+};
+
 pub const FT_BALANCE: SimpleCostSpecification = SimpleCostSpecification {
     write_length: Constant(0),
     write_count: Constant(0),
@@ -292,6 +322,14 @@ pub const FT_BALANCE: SimpleCostSpecification = SimpleCostSpecification {
     read_length: Constant(1),
 };
 
+pub const FT_SUPPLY: SimpleCostSpecification = SimpleCostSpecification {
+    write_length: Constant(0),
+    write_count: Constant(0),
+    runtime: Constant(1),
+    read_count: Constant(1),
+    read_length: Constant(1),
+};
+
 pub const NFT_MINT: SimpleCostSpecification = SimpleCostSpecification {
     write_length: Constant(1),
     write_count: Constant(1),
@@ -308,6 +346,14 @@ pub const NFT_TRANSFER: SimpleCostSpecification = SimpleCostSpecification {
     read_length: Constant(1),
 };
 
+pub const NFT_BURN: SimpleCostSpecification = SimpleCostSpecification {
+    write_length: Constant(1),
+    write_count: Constant(1),
+    runtime: Linear(1, 1),
+    read_count: Constant(1),
+    read_length: Constant(1),
+};
+
 pub const NFT_OWNER: SimpleCostSpecification = SimpleCostSpecification {
     write_length: Constant(0),
     write_count: Constant(0),
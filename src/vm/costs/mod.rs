@@ -227,6 +227,42 @@ pub struct ExecutionCost {
     pub runtime: u64,
 }
 
+/// One of the independently-budgeted dimensions of an ExecutionCost
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExecutionCostDimension {
+    Runtime,
+    WriteLength,
+    WriteCount,
+    ReadLength,
+    ReadCount,
+}
+
+impl ExecutionCostDimension {
+    pub const ALL: [ExecutionCostDimension; 5] = [
+        ExecutionCostDimension::Runtime,
+        ExecutionCostDimension::WriteLength,
+        ExecutionCostDimension::WriteCount,
+        ExecutionCostDimension::ReadLength,
+        ExecutionCostDimension::ReadCount,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExecutionCostDimension::Runtime => "runtime",
+            ExecutionCostDimension::WriteLength => "write_length",
+            ExecutionCostDimension::WriteCount => "write_count",
+            ExecutionCostDimension::ReadLength => "read_length",
+            ExecutionCostDimension::ReadCount => "read_count",
+        }
+    }
+}
+
+impl fmt::Display for ExecutionCostDimension {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 impl fmt::Display for ExecutionCost {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{{\"runtime\": {}, \"write_length\": {}, \"write_count\": {}, \"read_length\": {}, \"read_count\": {}}}",
@@ -344,6 +380,25 @@ impl ExecutionCost {
             || self.read_length > other.read_length
     }
 
+    pub fn get(&self, dimension: ExecutionCostDimension) -> u64 {
+        match dimension {
+            ExecutionCostDimension::Runtime => self.runtime,
+            ExecutionCostDimension::WriteLength => self.write_length,
+            ExecutionCostDimension::WriteCount => self.write_count,
+            ExecutionCostDimension::ReadLength => self.read_length,
+            ExecutionCostDimension::ReadCount => self.read_count,
+        }
+    }
+
+    /// Returns the dimensions in which this cost exceeds the other cost.
+    pub fn exceeded_dimensions(&self, other: &ExecutionCost) -> Vec<ExecutionCostDimension> {
+        ExecutionCostDimension::ALL
+            .iter()
+            .filter(|dimension| self.get(**dimension) > other.get(**dimension))
+            .cloned()
+            .collect()
+    }
+
     pub fn max_cost(first: ExecutionCost, second: ExecutionCost) -> ExecutionCost {
         Self {
             runtime: first.runtime.max(second.runtime),
@@ -443,6 +498,34 @@ mod unit_tests {
         );
     }
 
+    #[test]
+    fn test_exceeded_dimensions() {
+        let budget = ExecutionCost {
+            write_length: 10,
+            write_count: 10,
+            read_length: 10,
+            read_count: 10,
+            runtime: 10,
+        };
+        let cost = ExecutionCost {
+            write_length: 10,
+            write_count: 11,
+            read_length: 0,
+            read_count: 20,
+            runtime: 9,
+        };
+        assert_eq!(
+            cost.exceeded_dimensions(&budget),
+            vec![
+                ExecutionCostDimension::WriteCount,
+                ExecutionCostDimension::ReadCount
+            ]
+        );
+        assert!(cost.exceeds(&budget));
+        assert_eq!(budget.exceeded_dimensions(&budget), vec![]);
+        assert!(!budget.exceeds(&budget));
+    }
+
     #[test]
     fn test_simple_sub() {
         assert_eq!(0u64.cost_overflow_sub(1), Err(CostErrors::CostOverflow));
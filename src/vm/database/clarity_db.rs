@@ -31,7 +31,7 @@ use vm::types::{
 use burnchains::BurnchainHeaderHash;
 use chainstate::burn::{BlockHeaderHash, ConsensusHash, VRFSeed};
 use chainstate::stacks::db::{MinerPaymentSchedule, StacksHeaderInfo};
-use chainstate::stacks::index::proofs::TrieMerkleProof;
+use chainstate::stacks::index::proofs::{TrieMerkleMultiProof, TrieMerkleProof};
 use chainstate::stacks::StacksBlockHeader;
 use chainstate::stacks::{StacksAddress, StacksBlockId};
 
@@ -396,13 +396,23 @@ impl<'a> ClarityDatabase<'a> {
         self.store.get_value(key, expected)
     }
 
-    pub fn get_with_proof<T>(&mut self, key: &str) -> Option<(T, TrieMerkleProof<StacksBlockId>)>
+    pub fn get_with_proof<T>(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<(T, TrieMerkleProof<StacksBlockId>)>>
     where
         T: ClarityDeserializable<T>,
     {
         self.store.get_with_proof(key)
     }
 
+    pub fn get_with_multi_proof(
+        &mut self,
+        keys: &[String],
+    ) -> Result<(Vec<Option<String>>, TrieMerkleMultiProof<StacksBlockId>)> {
+        self.store.get_with_multi_proof(keys)
+    }
+
     pub fn make_key_for_trip(
         contract_identifier: &QualifiedContractIdentifier,
         data: StoreType,
@@ -976,6 +986,54 @@ impl<'a> ClarityDatabase<'a> {
         }
     }
 
+    pub fn checked_decrease_token_supply(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        token_name: &str,
+        amount: u128,
+    ) -> Result<()> {
+        let descriptor = self.load_ft(contract_identifier, token_name)?;
+
+        if descriptor.total_supply.is_some() {
+            let key = ClarityDatabase::make_key_for_trip(
+                contract_identifier,
+                StoreType::CirculatingSupply,
+                token_name,
+            );
+            let current_supply: u128 = self
+                .get(&key)
+                .expect("ERROR: Clarity VM failed to track token supply.");
+
+            let new_supply = current_supply
+                .checked_sub(amount)
+                .ok_or(RuntimeErrorType::ArithmeticUnderflow)?;
+
+            self.put(&key, &new_supply);
+        }
+        Ok(())
+    }
+
+    /// The circulating supply of a token.  Only tokens defined with a max supply track it, so
+    /// this fails with UntrackedTokenSupply for the others.
+    pub fn get_ft_supply(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        token_name: &str,
+    ) -> Result<u128> {
+        let descriptor = self.load_ft(contract_identifier, token_name)?;
+        if descriptor.total_supply.is_none() {
+            return Err(RuntimeErrorType::UntrackedTokenSupply.into());
+        }
+
+        let key = ClarityDatabase::make_key_for_trip(
+            contract_identifier,
+            StoreType::CirculatingSupply,
+            token_name,
+        );
+        self.get(&key)
+            .ok_or_else(|| RuntimeErrorType::UntrackedTokenSupply.into())
+    }
+
     pub fn get_ft_balance(
         &mut self,
         contract_identifier: &QualifiedContractIdentifier,
@@ -1034,8 +1092,12 @@ impl<'a> ClarityDatabase<'a> {
             asset.serialize(),
         );
 
-        let result = self.get(&key);
-        result.ok_or(RuntimeErrorType::NoSuchToken.into())
+        // a burned asset is recorded as `None`, and is treated as non-existent.
+        let result: Option<Option<PrincipalData>> = self.get(&key);
+        match result {
+            Some(Some(owner)) => Ok(owner),
+            _ => Err(RuntimeErrorType::NoSuchToken.into()),
+        }
     }
 
     pub fn get_nft_key_type(
@@ -1070,6 +1132,29 @@ impl<'a> ClarityDatabase<'a> {
 
         Ok(())
     }
+
+    pub fn burn_nft(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        asset_name: &str,
+        asset: &Value,
+    ) -> Result<()> {
+        let descriptor = self.load_nft(contract_identifier, asset_name)?;
+        if !descriptor.key_type.admits(asset) {
+            return Err(CheckErrors::TypeValueError(descriptor.key_type, (*asset).clone()).into());
+        }
+
+        let key = ClarityDatabase::make_key_for_quad(
+            contract_identifier,
+            StoreType::NonFungibleToken,
+            asset_name,
+            asset.serialize(),
+        );
+
+        self.put(&key, &(None as Option<PrincipalData>));
+
+        Ok(())
+    }
 }
 
 // load/store STX token state and account nonces
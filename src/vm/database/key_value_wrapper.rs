@@ -16,7 +16,9 @@
 
 use super::{ClarityBackingStore, ClarityDeserializable, MarfedKV};
 use chainstate::{
-    burn::BlockHeaderHash, stacks::index::proofs::TrieMerkleProof, stacks::StacksBlockId,
+    burn::BlockHeaderHash,
+    stacks::index::proofs::{TrieMerkleMultiProof, TrieMerkleProof},
+    stacks::StacksBlockId,
 };
 use std::collections::HashMap;
 use std::{clone::Clone, cmp::Eq, hash::Hash};
@@ -303,13 +305,26 @@ impl<'a> RollbackWrapper<'a> {
 
     /// this function will only return commitment proofs for values _already_ materialized
     ///  in the underlying store. otherwise it returns None.
-    pub fn get_with_proof<T>(&mut self, key: &str) -> Option<(T, TrieMerkleProof<StacksBlockId>)>
+    pub fn get_with_proof<T>(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<(T, TrieMerkleProof<StacksBlockId>)>>
     where
         T: ClarityDeserializable<T>,
     {
-        self.store
-            .get_with_proof(key)
-            .map(|(value, proof)| (T::deserialize(&value), proof))
+        Ok(self
+            .store
+            .get_with_proof(key)?
+            .map(|(value, proof)| (T::deserialize(&value), proof)))
+    }
+
+    /// this function will only return values and commitment proofs for entries _already_
+    ///  materialized in the underlying store.
+    pub fn get_with_multi_proof(
+        &mut self,
+        keys: &[String],
+    ) -> Result<(Vec<Option<String>>, TrieMerkleMultiProof<StacksBlockId>)> {
+        self.store.get_with_multi_proof(keys)
     }
 
     pub fn get<T>(&mut self, key: &str) -> Option<T>
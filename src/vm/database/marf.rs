@@ -14,12 +14,14 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use burnchains::BurnchainHeaderHash;
 use chainstate::burn::{BlockHeaderHash, VRFSeed};
 use chainstate::stacks::index::marf::{MarfConnection, MARF};
-use chainstate::stacks::index::proofs::TrieMerkleProof;
+use chainstate::stacks::index::proofs::{TrieMerkleMultiProof, TrieMerkleProof};
+use chainstate::stacks::index::prune::MarfPruneSummary;
 use chainstate::stacks::index::storage::TrieFileStorage;
 use chainstate::stacks::index::{Error as MarfError, MARFValue, MarfTrieId, TrieHash};
 use chainstate::stacks::StacksBlockId;
@@ -48,6 +50,19 @@ pub struct MarfedKV {
     // Since the MARF only stores 32 bytes of value,
     //   we need another storage
     side_store: SqliteConnection,
+    // Set while an ephemeral block is open (see begin_ephemeral()).
+    ephemeral: Option<EphemeralBlock>,
+}
+
+/// An ephemeral block is evaluated on top of a confirmed chain tip without extending the MARF.
+/// Its writes are held in memory, and are discarded when the block is rolled back.  Nothing is
+/// written to the MARF or the side store, so no write locks are taken on either.
+struct EphemeralBlock {
+    parent: StacksBlockId,
+    block: StacksBlockId,
+    height: u32,
+    data: HashMap<String, String>,
+    metadata: HashMap<(String, String), String>,
 }
 
 pub struct MemoryBackingStore {
@@ -62,7 +77,18 @@ pub trait ClarityBackingStore {
     fn put_all(&mut self, items: Vec<(String, String)>);
     /// fetch K-V out of the committed datastore
     fn get(&mut self, key: &str) -> Option<String>;
-    fn get_with_proof(&mut self, key: &str) -> Option<(String, TrieMerkleProof<StacksBlockId>)>;
+    /// fetch K-V out of the committed datastore, with a proof.  Fails with
+    /// RuntimeErrorType::PrunedBlockHeaderHash if the proof would pass through pruned tries.
+    fn get_with_proof(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<(String, TrieMerkleProof<StacksBlockId>)>>;
+    /// fetch several K-V pairs out of the committed datastore, with one proof for the
+    /// entries that are present.  Fails like get_with_proof().
+    fn get_with_multi_proof(
+        &mut self,
+        keys: &[String],
+    ) -> Result<(Vec<Option<String>>, TrieMerkleMultiProof<StacksBlockId>)>;
     fn has_entry(&mut self, key: &str) -> bool {
         self.get(key).is_some()
     }
@@ -209,6 +235,7 @@ impl MarfedKV {
             marf,
             chain_tip,
             side_store,
+            ephemeral: None,
         })
     }
 
@@ -223,6 +250,7 @@ impl MarfedKV {
             marf,
             chain_tip,
             side_store,
+            ephemeral: None,
         })
     }
 
@@ -248,6 +276,7 @@ impl MarfedKV {
             marf,
             chain_tip,
             side_store,
+            ephemeral: None,
         }
     }
 
@@ -312,7 +341,63 @@ impl MarfedKV {
         self.side_store.begin(&self.chain_tip);
     }
 
+    /// Begin an ephemeral block `next` on top of the confirmed block `current`.  Reads are
+    /// served from `current`, and writes are kept in memory until rollback().  An ephemeral
+    /// block can never be committed.
+    pub fn begin_ephemeral(&mut self, current: &StacksBlockId, next: &StacksBlockId) {
+        let parent_height = self
+            .marf
+            .get_block_height_of(current, current)
+            .expect("ERROR: Unexpected MARF failure")
+            .expect(&format!("ERROR: No such block {}", current));
+
+        self.ephemeral = Some(EphemeralBlock {
+            parent: current.clone(),
+            block: next.clone(),
+            height: parent_height + 1,
+            data: HashMap::new(),
+            metadata: HashMap::new(),
+        });
+        self.chain_tip = next.clone();
+    }
+
+    /// The metadata written so far by the open ephemeral block, keyed by contract and key.
+    pub fn get_ephemeral_metadata(&self) -> Option<&HashMap<(String, String), String>> {
+        self.ephemeral.as_ref().map(|ephemeral| &ephemeral.metadata)
+    }
+
+    /// The block whose MARF trie serves reads at the current chain tip.  This is the chain tip
+    /// itself, unless it is an ephemeral block.
+    fn read_tip(&self) -> StacksBlockId {
+        match self.ephemeral {
+            Some(ref ephemeral) if ephemeral.block == self.chain_tip => ephemeral.parent.clone(),
+            _ => self.chain_tip.clone(),
+        }
+    }
+
+    /// The ephemeral block's writes, if the current chain tip is an open ephemeral block.
+    fn ephemeral_at_tip(&mut self) -> Option<&mut EphemeralBlock> {
+        let chain_tip = &self.chain_tip;
+        self.ephemeral
+            .as_mut()
+            .filter(|ephemeral| ephemeral.block == *chain_tip)
+    }
+
+    /// Ephemeral blocks never begin a MARF block, so they can only be rolled back.
+    fn check_not_ephemeral(&self, action: &str) {
+        if let Some(ref ephemeral) = self.ephemeral {
+            panic!(
+                "FATAL: attempted to {} ephemeral block {}; ephemeral blocks can only be rolled back",
+                action, &ephemeral.block
+            );
+        }
+    }
+
     pub fn rollback(&mut self) {
+        if self.ephemeral.take().is_some() {
+            self.chain_tip = StacksBlockId::sentinel();
+            return;
+        }
         self.marf.drop_current();
         self.side_store.rollback(&self.chain_tip);
         self.chain_tip = StacksBlockId::sentinel();
@@ -338,6 +423,7 @@ impl MarfedKV {
             "commit_mined_block: ({}->{})",
             &self.chain_tip, will_move_to
         );
+        self.check_not_ephemeral("commit as a mined block");
         // rollback the side_store
         //    the side_store shouldn't commit data for blocks that won't be
         //    included in the processed chainstate (like a block constructed during mining)
@@ -355,6 +441,7 @@ impl MarfedKV {
 
     pub fn commit_to(&mut self, final_bhh: &StacksBlockId) {
         debug!("commit_to({})", final_bhh);
+        self.check_not_ephemeral("commit");
         self.side_store
             .commit_metadata_to(&self.chain_tip, final_bhh);
         self.side_store.commit(&self.chain_tip);
@@ -366,6 +453,7 @@ impl MarfedKV {
 
     pub fn commit_unconfirmed(&mut self) {
         debug!("commit_unconfirmed()");
+        self.check_not_ephemeral("commit as an unconfirmed block");
         // NOTE: Can omit commit_metadata_to, since the block header hash won't change
         // self.side_store.commit_metadata_to(&self.chain_tip, final_bhh);
         self.side_store.commit(&self.chain_tip);
@@ -403,6 +491,22 @@ impl MarfedKV {
             .expect("ERROR: Unexpected MARF Failure")
     }
 
+    /// Prune the MARF's tries more than `keep_depth` blocks below `tip`, and the side-store
+    /// values that only they referred to.
+    pub fn prune(
+        &mut self,
+        tip: &StacksBlockId,
+        keep_depth: u32,
+    ) -> std::result::Result<MarfPruneSummary, MarfError> {
+        let summary = self.marf.prune(tip, keep_depth)?;
+        self.side_store.delete_all(&summary.dead_value_hashes);
+        Ok(summary)
+    }
+
+    pub fn is_block_pruned(&self, bhh: &StacksBlockId) -> std::result::Result<bool, MarfError> {
+        self.marf.is_block_pruned(bhh)
+    }
+
     pub fn make_contract_hash_key(contract: &QualifiedContractIdentifier) -> String {
         format!("clarity-contract::{}", contract)
     }
@@ -413,7 +517,49 @@ impl ClarityBackingStore for MarfedKV {
         &mut self.side_store
     }
 
+    fn insert_metadata(&mut self, contract: &QualifiedContractIdentifier, key: &str, value: &str) {
+        if let Some(ref mut ephemeral) = self.ephemeral {
+            ephemeral
+                .metadata
+                .insert((contract.to_string(), key.to_string()), value.to_string());
+            return;
+        }
+        let bhh = self.get_open_chain_tip();
+        self.side_store
+            .insert_metadata(&bhh, &contract.to_string(), key, value)
+    }
+
+    fn get_metadata(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+        key: &str,
+    ) -> Result<Option<String>> {
+        let (bhh, _) = self.get_contract_hash(contract)?;
+        if let Some(ref ephemeral) = self.ephemeral {
+            if ephemeral.block == bhh {
+                return Ok(ephemeral
+                    .metadata
+                    .get(&(contract.to_string(), key.to_string()))
+                    .cloned());
+            }
+        }
+        Ok(self
+            .side_store
+            .get_metadata(&bhh, &contract.to_string(), key))
+    }
+
     fn set_block_hash(&mut self, bhh: StacksBlockId) -> Result<StacksBlockId> {
+        if let Some(ref ephemeral) = self.ephemeral {
+            if ephemeral.block == bhh {
+                return Ok(std::mem::replace(&mut self.chain_tip, bhh));
+            }
+            // ancestors of an ephemeral block are ancestors of its parent
+            let parent = ephemeral.parent.clone();
+            self.marf
+                .open_block(&parent)
+                .expect("ERROR: Unexpected MARF failure");
+        }
+
         self.marf
             .check_ancestor_block_hash(&bhh)
             .map_err(|e| match e {
@@ -430,6 +576,10 @@ impl ClarityBackingStore for MarfedKV {
                     );
                     RuntimeErrorType::UnknownBlockHeaderHash(BlockHeaderHash(bhh.0))
                 }
+                MarfError::PrunedError => {
+                    test_debug!("Block {:?} has been pruned", &bhh);
+                    RuntimeErrorType::PrunedBlockHeaderHash(BlockHeaderHash(bhh.0))
+                }
                 _ => panic!("ERROR: Unexpected MARF failure: {}", e),
             })?;
 
@@ -440,6 +590,9 @@ impl ClarityBackingStore for MarfedKV {
     }
 
     fn get_current_block_height(&mut self) -> u32 {
+        if let Some(ephemeral) = self.ephemeral_at_tip() {
+            return ephemeral.height;
+        }
         self.marf
             .get_block_height_of(&self.chain_tip, &self.chain_tip)
             .expect("Unexpected MARF failure.")
@@ -447,13 +600,22 @@ impl ClarityBackingStore for MarfedKV {
     }
 
     fn get_block_at_height(&mut self, block_height: u32) -> Option<StacksBlockId> {
+        if let Some(ephemeral) = self.ephemeral_at_tip() {
+            if ephemeral.height == block_height {
+                return Some(ephemeral.block.clone());
+            }
+        }
+        let read_tip = self.read_tip();
         self.marf
-            .get_bhh_at_height(&self.chain_tip, block_height)
+            .get_bhh_at_height(&read_tip, block_height)
             .expect("Unexpected MARF failure.")
             .map(|x| StacksBlockId(x.to_bytes()))
     }
 
     fn get_open_chain_tip(&mut self) -> StacksBlockId {
+        if let Some(ref ephemeral) = self.ephemeral {
+            return ephemeral.block.clone();
+        }
         StacksBlockId(
             self.marf
                 .get_open_chain_tip()
@@ -464,14 +626,33 @@ impl ClarityBackingStore for MarfedKV {
     }
 
     fn get_open_chain_tip_height(&mut self) -> u32 {
+        if let Some(ref ephemeral) = self.ephemeral {
+            return ephemeral.height;
+        }
         self.marf
             .get_open_chain_tip_height()
             .expect("Attempted to get the open chain tip from an unopened context.")
     }
 
-    fn get_with_proof(&mut self, key: &str) -> Option<(String, TrieMerkleProof<StacksBlockId>)> {
-        self.marf
-            .get_with_proof(&self.chain_tip, key)
+    fn get_with_proof(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<(String, TrieMerkleProof<StacksBlockId>)>> {
+        let proof_result = match self.marf.get_with_proof(&self.chain_tip, key) {
+            Err(MarfError::PrunedError) => {
+                // the value may still be there, but the tries a proof would pass through are not
+                debug!(
+                    "Cannot prove {} at {}: ancestor trie pruned",
+                    key, &self.chain_tip
+                );
+                return Err(RuntimeErrorType::PrunedBlockHeaderHash(BlockHeaderHash(
+                    self.chain_tip.0,
+                ))
+                .into());
+            }
+            x => x,
+        };
+        Ok(proof_result
             .or_else(|e| match e {
                 MarfError::NotFoundError => Ok(None),
                 _ => Err(e),
@@ -484,13 +665,52 @@ impl ClarityBackingStore for MarfedKV {
                     side_key
                 ));
                 (data, proof)
+            }))
+    }
+
+    fn get_with_multi_proof(
+        &mut self,
+        keys: &[String],
+    ) -> Result<(Vec<Option<String>>, TrieMerkleMultiProof<StacksBlockId>)> {
+        let (marf_values, proof) = match self.marf.get_with_multi_proof(&self.chain_tip, keys) {
+            Err(MarfError::PrunedError) => {
+                debug!(
+                    "Cannot prove {} keys at {}: ancestor trie pruned",
+                    keys.len(),
+                    &self.chain_tip
+                );
+                return Err(RuntimeErrorType::PrunedBlockHeaderHash(BlockHeaderHash(
+                    self.chain_tip.0,
+                ))
+                .into());
+            }
+            x => x.expect("ERROR: Unexpected MARF Failure on GET"),
+        };
+        let data = marf_values
+            .into_iter()
+            .map(|marf_value_opt| {
+                marf_value_opt.map(|marf_value| {
+                    let side_key = marf_value.to_hex();
+                    self.side_store.get(&side_key).expect(&format!(
+                        "ERROR: MARF contained value_hash not found in side storage: {}",
+                        side_key
+                    ))
+                })
             })
+            .collect();
+        Ok((data, proof))
     }
 
     fn get(&mut self, key: &str) -> Option<String> {
         trace!("MarfedKV get: {:?} tip={}", key, &self.chain_tip);
+        if let Some(ephemeral) = self.ephemeral_at_tip() {
+            if let Some(value) = ephemeral.data.get(key) {
+                return Some(value.clone());
+            }
+        }
+        let read_tip = self.read_tip();
         self.marf
-            .get(&self.chain_tip, key)
+            .get(&read_tip, key)
             .or_else(|e| match e {
                 MarfError::NotFoundError => {
                     trace!(
@@ -514,6 +734,14 @@ impl ClarityBackingStore for MarfedKV {
     }
 
     fn put_all(&mut self, mut items: Vec<(String, String)>) {
+        if let Some(ref mut ephemeral) = self.ephemeral {
+            for (key, value) in items.drain(..) {
+                trace!("MarfedKV ephemeral put '{}' = '{}'", &key, &value);
+                ephemeral.data.insert(key, value);
+            }
+            return;
+        }
+
         let mut keys = Vec::new();
         let mut values = Vec::new();
         for (key, value) in items.drain(..) {
@@ -558,10 +786,28 @@ impl ClarityBackingStore for MemoryBackingStore {
         self.side_store.get(key)
     }
 
-    fn get_with_proof(&mut self, key: &str) -> Option<(String, TrieMerkleProof<StacksBlockId>)> {
-        self.side_store
+    fn get_with_proof(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<(String, TrieMerkleProof<StacksBlockId>)>> {
+        Ok(self
+            .side_store
             .get(key)
-            .map(|x| (x, TrieMerkleProof(vec![])))
+            .map(|x| (x, TrieMerkleProof(vec![]))))
+    }
+
+    fn get_with_multi_proof(
+        &mut self,
+        keys: &[String],
+    ) -> Result<(Vec<Option<String>>, TrieMerkleMultiProof<StacksBlockId>)> {
+        let data = keys.iter().map(|key| self.side_store.get(key)).collect();
+        Ok((
+            data,
+            TrieMerkleMultiProof {
+                entries: vec![],
+                key_proofs: vec![],
+            },
+        ))
     }
 
     fn get_side_store(&mut self) -> &mut SqliteConnection {
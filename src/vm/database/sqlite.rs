@@ -83,6 +83,27 @@ impl SqliteConnection {
         sqlite_get(&self.conn, key)
     }
 
+    /// Drop values that nothing refers to any longer, i.e. once their MARF leaves are pruned
+    pub fn delete_all(&mut self, keys: &[String]) {
+        let tx = match self.conn.transaction() {
+            Ok(tx) => tx,
+            Err(e) => {
+                error!("Failed to begin transaction: {:?}", &e);
+                panic!("{}", SQL_FAIL_MESSAGE);
+            }
+        };
+        for key in keys.iter() {
+            if let Err(e) = tx.execute("DELETE FROM data_table WHERE key = ?", &[key]) {
+                error!("Failed to delete '{}': {:?}", key, &e);
+                panic!("{}", SQL_FAIL_MESSAGE);
+            }
+        }
+        if let Err(e) = tx.commit() {
+            error!("Failed to commit deletions: {:?}", &e);
+            panic!("{}", SQL_FAIL_MESSAGE);
+        }
+    }
+
     pub fn insert_metadata(
         &mut self,
         bhh: &StacksBlockId,
@@ -42,7 +42,7 @@ impl ClarityDeserializable<String> for String {
 }
 
 macro_rules! clarity_serializable {
-    ($Name:ident) => {
+    ($Name:ty) => {
         impl ClaritySerializable for $Name {
             fn serialize(&self) -> String {
                 serde_json::to_string(self).expect("Failed to serialize vm.Value")
@@ -104,6 +104,10 @@ pub struct SimmedBlock {
 clarity_serializable!(SimmedBlock);
 
 clarity_serializable!(PrincipalData);
+// NFT ownership records. Because serde serializes `Some(x)` exactly as `x`,
+//  ownership records written as a bare `PrincipalData` deserialize as `Some(..)`,
+//  while a burned asset is stored as `None`.
+clarity_serializable!(Option<PrincipalData>);
 clarity_serializable!(i128);
 clarity_serializable!(u128);
 clarity_serializable!(u64);
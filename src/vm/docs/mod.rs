@@ -159,6 +159,49 @@ const TO_UINT_API: SimpleFunctionAPI = SimpleFunctionAPI {
     example: "(to-uint 238) ;; Returns u238"
 };
 
+const INT_TO_ASCII_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(int-to-ascii value)",
+    description: "Converts an `int` or `uint` to its decimal representation as a `string-ascii`.",
+    example: "(int-to-ascii 1) ;; Returns \"1\"
+(int-to-ascii u24) ;; Returns \"24\"
+(int-to-ascii -17) ;; Returns \"-17\"
+",
+};
+
+const INT_TO_UTF8_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(int-to-utf8 value)",
+    description: "Converts an `int` or `uint` to its decimal representation as a `string-utf8`.",
+    example: "(int-to-utf8 1) ;; Returns u\"1\"
+(int-to-utf8 u24) ;; Returns u\"24\"
+(int-to-utf8 -17) ;; Returns u\"-17\"
+",
+};
+
+const STRING_TO_INT_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(string-to-int? string)",
+    description:
+        "Parses a `string-ascii` or `string-utf8` holding a decimal number, with an optional
+leading sign, into an `int`. Returns `none` if the string is not a valid `int`.",
+    example: "(string-to-int? \"1\") ;; Returns (some 1)
+(string-to-int? u\"-1\") ;; Returns (some -1)
+(string-to-int? \"a\") ;; Returns none
+",
+};
+
+const STRING_TO_UINT_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(string-to-uint? string)",
+    description: "Parses a `string-ascii` or `string-utf8` holding a decimal number into a `uint`.
+Returns `none` if the string is not a valid `uint`.",
+    example: "(string-to-uint? \"1\") ;; Returns (some u1)
+(string-to-uint? u\"1\") ;; Returns (some u1)
+(string-to-uint? \"-1\") ;; Returns none
+",
+};
+
 const TO_INT_API: SimpleFunctionAPI = SimpleFunctionAPI {
     name: None,
     signature: "(to-int u)",
@@ -523,6 +566,100 @@ const LEN_API: SpecialAPI = SpecialAPI {
 ",
 };
 
+const ELEMENT_AT_API: SpecialAPI = SpecialAPI {
+    input_type: "sequence_A, uint",
+    output_type: "(optional A)",
+    signature: "(element-at sequence index)",
+    description:
+        "The `element-at` function returns the element at `index` in the provided sequence.
+If `index` is greater than or equal to `(len sequence)`, this function returns `none`.
+For strings and buffers, this function will return 1-length strings or buffers.",
+    example: "(element-at \"blockstack\" u5) ;; Returns (some \"s\")
+(element-at (list 1 2 3 4 5) u5) ;; Returns none
+(element-at (list 1 2 3 4 5) (+ u1 u2)) ;; Returns (some 4)
+(element-at \"abcd\" u1) ;; Returns (some \"b\")
+(element-at 0xfb01 u1) ;; Returns (some 0x01)
+",
+};
+
+const INDEX_OF_API: SpecialAPI = SpecialAPI {
+    input_type: "sequence_A, A",
+    output_type: "(optional uint)",
+    signature: "(index-of sequence item)",
+    description: "The `index-of` function returns the first index at which `item` can be
+found, using `is-eq` checks, in the provided sequence.
+Applicable sequence types are `(list A)`, `buff`, `string-ascii` and `string-utf8`,
+for which the corresponding element types are, respectively, `A`, `(buff 1)`, `(string-ascii 1)` and `(string-utf8 1)`.
+If the target item is not found in the sequence (or if an empty string or buffer is
+supplied), this function returns `none`.",
+    example: "(index-of \"blockstack\" \"b\") ;; Returns (some u0)
+(index-of \"blockstack\" \"k\") ;; Returns (some u4)
+(index-of \"blockstack\" \"\") ;; Returns none
+(index-of (list 1 2 3 4 5) 6) ;; Returns none
+(index-of 0xfb01 0x01) ;; Returns (some u1)
+",
+};
+
+const SLICE_API: SpecialAPI = SpecialAPI {
+    input_type: "sequence_A, uint, uint",
+    output_type: "(optional sequence_A)",
+    signature: "(slice sequence left-position right-position)",
+    description: "The `slice` function attempts to return a sub-sequence of the provided sequence,
+starting at `left-position` (inclusive) and ending at `right-position` (non-inclusive).
+If `right-position` is less than `left-position`, or if `right-position` is greater than
+`(len sequence)`, this function returns `none`.",
+    example: "(slice \"blockstack\" u5 u10) ;; Returns (some \"stack\")
+(slice (list 1 2 3 4 5) u5 u9) ;; Returns none
+(slice (list 1 2 3 4 5) u3 u4) ;; Returns (some (4))
+(slice \"abcd\" u1 u3) ;; Returns (some \"bc\")
+(slice \"abcd\" u2 u2) ;; Returns (some \"\")
+(slice \"abcd\" u3 u1) ;; Returns none
+",
+};
+
+const BUFF_TO_ASCII_API: SpecialAPI = SpecialAPI {
+    input_type: "(buff N)",
+    output_type: "(optional (string-ascii N))",
+    signature: "(buff-to-ascii? buffer)",
+    description:
+        "The `buff-to-ascii?` function interprets the bytes of a buffer as an ASCII string.
+It returns `none` if any byte is not a character allowed in a `string-ascii`.",
+    example: "(buff-to-ascii? 0x616263) ;; Returns (some \"abc\")
+(buff-to-ascii? 0xff) ;; Returns none
+",
+};
+
+const BUFF_TO_UTF8_API: SpecialAPI = SpecialAPI {
+    input_type: "(buff N)",
+    output_type: "(optional (string-utf8 N))",
+    signature: "(buff-to-utf8? buffer)",
+    description: "The `buff-to-utf8?` function decodes the bytes of a buffer as a UTF-8 string.
+It returns `none` if the buffer is not valid UTF-8.",
+    example: "(buff-to-utf8? 0x616263) ;; Returns (some u\"abc\")
+(buff-to-utf8? 0xff) ;; Returns none
+",
+};
+
+const ASCII_TO_BUFF_API: SpecialAPI = SpecialAPI {
+    input_type: "(string-ascii N)",
+    output_type: "(buff N)",
+    signature: "(ascii-to-buff string)",
+    description: "The `ascii-to-buff` function returns the bytes of an ASCII string.",
+    example: "(ascii-to-buff \"abc\") ;; Returns 0x616263
+",
+};
+
+const UTF8_TO_BUFF_API: SpecialAPI = SpecialAPI {
+    input_type: "(string-utf8 N)",
+    output_type: "(buff 4N)",
+    signature: "(utf8-to-buff string)",
+    description: "The `utf8-to-buff` function returns the UTF-8 encoding of a string. Since a character
+encodes to at most four bytes, the output buffer is typed with four times the length of the input string.",
+    example: "(utf8-to-buff u\"abc\") ;; Returns 0x616263
+(utf8-to-buff u\"\\u{1F624}\") ;; Returns 0xf09f98a4
+",
+};
+
 const LIST_API: SpecialAPI = SpecialAPI {
     input_type: "A, ...",
     output_type: "(list A)",
@@ -760,6 +897,38 @@ const PRINCIPAL_OF_API: SpecialAPI = SpecialAPI {
     example: "(principal-of? 0x03adb8de4bfb65db2cfd6120d55c6526ae9c52e675db7e47308636534ba7786110) ;; Returns (ok ST1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582VF9HSCP)"
 };
 
+const TO_CONSENSUS_BUFF: SpecialAPI = SpecialAPI {
+    input_type: "any",
+    output_type: "(buff N)",
+    signature: "(to-consensus-buff value)",
+    description: "The `to-consensus-buff` function returns the consensus serialization of `value`, the
+same encoding used to store values in the Clarity database. The length `N` of the output buffer type is
+the largest size that the serialization of any value of the input's type can have, so inputs whose type
+cannot be serialized into a buffer of the maximum value size (or which contain trait references) are
+rejected by the type checker.",
+    example: "(to-consensus-buff 1) ;; Returns 0x0000000000000000000000000000000001
+(to-consensus-buff u1) ;; Returns 0x0100000000000000000000000000000001
+(to-consensus-buff true) ;; Returns 0x03
+(to-consensus-buff none) ;; Returns 0x09
+(to-consensus-buff (tuple (a 1))) ;; Returns 0x0c0000000101610000000000000000000000000000000001
+",
+};
+
+const FROM_CONSENSUS_BUFF: SpecialAPI = SpecialAPI {
+    input_type: "type-signature(t), buff",
+    output_type: "(optional t)",
+    signature: "(from-consensus-buff type-signature buffer)",
+    description: "The `from-consensus-buff` function deserializes `buffer` as a value of type `type-signature`.
+It returns `(some value)` if `buffer` is exactly the consensus serialization of a value of that type
+(as produced by `to-consensus-buff`), and `none` otherwise, e.g., if the buffer has trailing bytes or
+encodes a value of a different type.",
+    example: "(from-consensus-buff int 0x0000000000000000000000000000000001) ;; Returns (some 1)
+(from-consensus-buff uint 0x0000000000000000000000000000000001) ;; Returns none
+(from-consensus-buff (tuple (a int)) 0x0c0000000101610000000000000000000000000000000001) ;; Returns (some (tuple (a 1)))
+(from-consensus-buff bool 0x0303) ;; Returns none
+",
+};
+
 const AT_BLOCK: SpecialAPI = SpecialAPI {
     input_type: "(buff 32), A",
     output_type: "A",
@@ -1064,7 +1233,8 @@ of tokens passed that amount, that invocation of `ft-mint?` will result in a run
 Like other kinds of definition statements, `define-fungible-token` may only be used at the top level of a smart contract
 definition (i.e., you cannot put a define statement in the middle of a function body).
 
-Tokens defined using `define-fungible-token` may be used in `ft-transfer?`, `ft-mint?`, and `ft-get-balance` functions",
+Tokens defined using `define-fungible-token` may be used in `ft-transfer?`, `ft-mint?`, and `ft-get-balance` functions,
+and tokens defined with a total supply may also be used in `ft-get-supply`",
     example: "
 (define-fungible-token stacks)
 (define-fungible-token limited-supply-stacks u100)
@@ -1310,6 +1480,52 @@ Otherwise, on successfuly mint, it returns `(ok true)`.
 "
 };
 
+const BURN_TOKEN: SpecialAPI = SpecialAPI {
+    input_type: "TokenName, uint, principal",
+    output_type: "(response bool uint)",
+    signature: "(ft-burn? token-name amount sender)",
+    description: "`ft-burn?` is used to decrease the token balance for the `sender` principal for a token
+type defined using `define-fungible-token`. The decreased token balance is _not_ transfered to another principal, but
+rather destroyed, reducing the circulating supply.
+
+This function returns (ok true) if the burn is successful. In the event of an unsuccessful burn it returns
+one of the following error codes:
+
+`(err u1)` -- `sender` does not have enough balance to burn
+`(err u3)` -- amount to burn is non-positive
+",
+    example: "
+(define-fungible-token stackaroo)
+(ft-mint? stackaroo u100 'SPAXYA5XS51713FDTQ8H94EJ4V579CXMTRNBZKSF)
+(ft-burn? stackaroo u50 'SPAXYA5XS51713FDTQ8H94EJ4V579CXMTRNBZKSF) ;; returns (ok true)
+(ft-burn? stackaroo u60 'SPAXYA5XS51713FDTQ8H94EJ4V579CXMTRNBZKSF) ;; returns (err u1)
+"
+};
+
+const BURN_ASSET: SpecialAPI = SpecialAPI {
+    input_type: "AssetName, A, principal",
+    output_type: "(response bool uint)",
+    signature: "(nft-burn? asset-class asset-identifier sender)",
+    description: "`nft-burn?` is used to destroy an asset identified by `asset-identifier` that is owned by
+the `sender` principal. The asset must have been defined using `define-non-fungible-token`, and the supplied
+`asset-identifier` must be of the same type specified in that definition. A burned asset no longer has an owner,
+and may be minted again.
+
+This function returns (ok true) if the burn is successful. In the event of an unsuccessful burn it returns
+one of the following error codes:
+
+`(err u1)` -- `sender` does not own the asset
+`(err u3)` -- asset identified by asset-identifier does not exist
+",
+    example: "
+(define-non-fungible-token stackaroo (string-ascii 40))
+(nft-mint? stackaroo \"Roo\" 'SPAXYA5XS51713FDTQ8H94EJ4V579CXMTRNBZKSF)
+(nft-burn? stackaroo \"Roo\" 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR) ;; returns (err u1)
+(nft-burn? stackaroo \"Roo\" 'SPAXYA5XS51713FDTQ8H94EJ4V579CXMTRNBZKSF) ;; returns (ok true)
+(nft-burn? stackaroo \"Roo\" 'SPAXYA5XS51713FDTQ8H94EJ4V579CXMTRNBZKSF) ;; returns (err u3)
+"
+};
+
 const GET_OWNER: SpecialAPI = SpecialAPI {
     input_type: "AssetName, A",
     output_type: "(optional principal)",
@@ -1338,6 +1554,21 @@ The token type must have been defined using `define-fungible-token`.",
 ",
 };
 
+const GET_SUPPLY: SpecialAPI = SpecialAPI {
+    input_type: "TokenName",
+    output_type: "uint",
+    signature: "(ft-get-supply token-name)",
+    description: "`ft-get-supply` returns the circulating supply of `token-name`, i.e. the number of tokens
+minted with `ft-mint?` less the number burned with `ft-burn?`. The token type must have been defined using
+`define-fungible-token` with a total supply, since only those tokens track their supply: using `ft-get-supply`
+with a token defined without a total supply is rejected when the contract is analyzed.",
+    example: "
+(define-fungible-token stackaroo u1000000)
+(ft-mint? stackaroo u100 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)
+(ft-get-supply stackaroo) ;; Returns u100
+",
+};
+
 const TOKEN_TRANSFER: SpecialAPI = SpecialAPI {
     input_type: "TokenName, uint, principal, principal",
     output_type: "(response bool uint)",
@@ -1398,6 +1629,23 @@ principal isn't materialized, it returns 0.
 ",
 };
 
+const STX_GET_ACCOUNT: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(stx-account owner)",
+    description: "`stx-account` is used to query the STX account of the `owner` principal.
+
+This function returns a tuple with the unlocked and locked amounts of the `owner` principal's STX
+balance, along with the burnchain block height at which the locked amount unlocks. Once that height
+has been reached, the previously locked STX are reported in `unlocked`, and `locked` and
+`unlock-height` are both 0. In the event that the `owner` principal isn't materialized, all three
+fields are 0.
+",
+    example: "
+(stx-account 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR) ;; Returns (tuple (locked u0) (unlock-height u0) (unlocked u0))
+(stx-account (as-contract tx-sender)) ;; Returns (tuple (locked u0) (unlock-height u0) (unlocked u1000))
+",
+};
+
 const STX_TRANSFER: SimpleFunctionAPI = SimpleFunctionAPI {
     name: None,
     signature: "(stx-transfer? amount sender recipient)",
@@ -1474,6 +1722,9 @@ fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
         Concat => make_for_special(&CONCAT_API, name),
         AsMaxLen => make_for_special(&ASSERTS_MAX_LEN_API, name),
         Len => make_for_special(&LEN_API, name),
+        ElementAt => make_for_special(&ELEMENT_AT_API, name),
+        IndexOf => make_for_special(&INDEX_OF_API, name),
+        Slice => make_for_special(&SLICE_API, name),
         ListCons => make_for_special(&LIST_API, name),
         FetchEntry => make_for_special(&FETCH_ENTRY_API, name),
         SetEntry => make_for_special(&SET_ENTRY_API, name),
@@ -1493,6 +1744,16 @@ fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
         ContractCall => make_for_special(&CONTRACT_CALL_API, name),
         ContractOf => make_for_special(&CONTRACT_OF_API, name),
         PrincipalOf => make_for_special(&PRINCIPAL_OF_API, name),
+        ToConsensusBuff => make_for_special(&TO_CONSENSUS_BUFF, name),
+        FromConsensusBuff => make_for_special(&FROM_CONSENSUS_BUFF, name),
+        IntToAscii => make_for_simple_native(&INT_TO_ASCII_API, &IntToAscii, name),
+        IntToUtf8 => make_for_simple_native(&INT_TO_UTF8_API, &IntToUtf8, name),
+        StringToInt => make_for_simple_native(&STRING_TO_INT_API, &StringToInt, name),
+        StringToUInt => make_for_simple_native(&STRING_TO_UINT_API, &StringToUInt, name),
+        BuffToAscii => make_for_special(&BUFF_TO_ASCII_API, name),
+        BuffToUtf8 => make_for_special(&BUFF_TO_UTF8_API, name),
+        AsciiToBuff => make_for_special(&ASCII_TO_BUFF_API, name),
+        Utf8ToBuff => make_for_special(&UTF8_TO_BUFF_API, name),
         AsContract => make_for_special(&AS_CONTRACT_API, name),
         GetBlockInfo => make_for_special(&GET_BLOCK_INFO_API, name),
         ConsOkay => make_for_special(&CONS_OK_API, name),
@@ -1512,12 +1773,16 @@ fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
         IsSome => make_for_special(&IS_SOME_API, name),
         MintAsset => make_for_special(&MINT_ASSET, name),
         MintToken => make_for_special(&MINT_TOKEN, name),
+        BurnAsset => make_for_special(&BURN_ASSET, name),
+        BurnToken => make_for_special(&BURN_TOKEN, name),
         GetTokenBalance => make_for_special(&GET_BALANCE, name),
+        GetTokenSupply => make_for_special(&GET_SUPPLY, name),
         GetAssetOwner => make_for_special(&GET_OWNER, name),
         TransferToken => make_for_special(&TOKEN_TRANSFER, name),
         TransferAsset => make_for_special(&ASSET_TRANSFER, name),
         AtBlock => make_for_special(&AT_BLOCK, name),
         GetStxBalance => make_for_simple_native(&STX_GET_BALANCE, &GetStxBalance, name),
+        GetStxAccount => make_for_simple_native(&STX_GET_ACCOUNT, &GetStxAccount, name),
         StxTransfer => make_for_simple_native(&STX_TRANSFER, &StxTransfer, name),
         StxBurn => make_for_simple_native(&STX_BURN, &StxBurn, name),
     }
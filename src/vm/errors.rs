@@ -86,10 +86,12 @@ pub enum RuntimeErrorType {
     NotImplemented,
     NoSenderInContext,
     NonPositiveTokenSupply,
+    UntrackedTokenSupply,
     JSONParseError(IncomparableError<SerdeJSONErr>),
     AttemptToFetchInTransientContext,
     BadNameValue(&'static str, String),
     UnknownBlockHeaderHash(BlockHeaderHash),
+    PrunedBlockHeaderHash(BlockHeaderHash),
     BadBlockHash(Vec<u8>),
     UnwrapFailure,
 }
@@ -25,7 +25,8 @@ use vm::errors::{
 };
 use vm::representations::SymbolicExpression;
 use vm::types::{
-    AssetIdentifier, BlockInfoProperty, BuffData, OptionalData, PrincipalData, TypeSignature, Value,
+    AssetIdentifier, BlockInfoProperty, BuffData, OptionalData, PrincipalData, TupleData,
+    TypeSignature, Value,
 };
 use vm::{eval, Environment, LocalContext};
 
@@ -48,6 +49,14 @@ enum TransferTokenErrorCodes {
     SENDER_IS_RECIPIENT = 2,
     NON_POSITIVE_AMOUNT = 3,
 }
+enum BurnAssetErrorCodes {
+    NOT_OWNED_BY = 1,
+    DOES_NOT_EXIST = 3,
+}
+enum BurnTokenErrorCodes {
+    NOT_ENOUGH_BALANCE = 1,
+    NON_POSITIVE_AMOUNT = 3,
+}
 enum StxErrorCodes {
     NOT_ENOUGH_BALANCE = 1,
     SENDER_IS_RECIPIENT = 2,
@@ -104,6 +113,40 @@ pub fn special_stx_balance(
     }
 }
 
+/// Get the `{locked, unlocked, unlock-height}` breakdown of a principal's STX balance.
+/// As with `stx-get-balance`, a lock that has expired counts as unlocked, and then
+/// `locked` and `unlock-height` are both 0.
+pub fn special_stx_account(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(1, args)?;
+
+    runtime_cost!(cost_functions::STX_GET_ACCOUNT, env, 0)?;
+
+    let owner = eval(&args[0], env, context)?;
+
+    if let Value::Principal(ref principal) = owner {
+        let (balance, block_height) =
+            get_stx_balance_snapshot(&mut env.global_context.database, principal);
+        let (locked, unlock_height) = balance.get_locked_balance_at_block(block_height);
+        let unlocked = balance.get_available_balance_at_block(block_height);
+
+        let account = TupleData::from_data(vec![
+            ("locked".into(), Value::UInt(locked)),
+            ("unlocked".into(), Value::UInt(unlocked)),
+            (
+                "unlock-height".into(),
+                Value::UInt(u128::from(unlock_height)),
+            ),
+        ])?;
+        Ok(Value::Tuple(account))
+    } else {
+        Err(CheckErrors::TypeValueError(TypeSignature::PrincipalType, owner).into())
+    }
+}
+
 /// Do a "consolidated" STX transfer.
 /// If the 'from' principal has locked STX, and they have unlocked, then process the STX unlock
 /// and update its balance in addition to spending tokens out of it.
@@ -521,6 +564,142 @@ pub fn special_transfer_token(
     }
 }
 
+pub fn special_burn_token(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(3, args)?;
+
+    runtime_cost!(cost_functions::FT_BURN, env, 0)?;
+
+    let token_name = args[0].match_atom().ok_or(CheckErrors::BadTokenName)?;
+
+    let amount = eval(&args[1], env, context)?;
+    let from = eval(&args[2], env, context)?;
+
+    if let (Value::UInt(amount), Value::Principal(ref burner)) = (amount, from) {
+        if amount <= 0 {
+            return clarity_ecode!(BurnTokenErrorCodes::NON_POSITIVE_AMOUNT);
+        }
+
+        let burner_bal = env.global_context.database.get_ft_balance(
+            &env.contract_context.contract_identifier,
+            token_name,
+            burner,
+        )?;
+
+        if burner_bal < amount {
+            return clarity_ecode!(BurnTokenErrorCodes::NOT_ENOUGH_BALANCE);
+        }
+
+        env.global_context.database.checked_decrease_token_supply(
+            &env.contract_context.contract_identifier,
+            token_name,
+            amount,
+        )?;
+
+        let final_burner_bal = burner_bal - amount;
+
+        env.add_memory(TypeSignature::PrincipalType.size() as u64)?;
+        env.add_memory(TypeSignature::UIntType.size() as u64)?;
+
+        env.global_context.database.set_ft_balance(
+            &env.contract_context.contract_identifier,
+            token_name,
+            burner,
+            final_burner_bal,
+        )?;
+
+        // a burn debits the burner, so it is logged like an outbound transfer
+        //   for the purposes of post-condition checks.
+        env.global_context.log_token_transfer(
+            burner,
+            &env.contract_context.contract_identifier,
+            token_name,
+            amount,
+        )?;
+
+        let asset_identifier = AssetIdentifier {
+            contract_identifier: env.contract_context.contract_identifier.clone(),
+            asset_name: token_name.clone(),
+        };
+        env.register_ft_burn_event(burner.clone(), amount, asset_identifier)?;
+
+        Ok(Value::okay_true())
+    } else {
+        Err(CheckErrors::BadBurnFTArguments.into())
+    }
+}
+
+pub fn special_burn_asset(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(3, args)?;
+
+    let asset_name = args[0].match_atom().ok_or(CheckErrors::BadTokenName)?;
+
+    let asset = eval(&args[1], env, context)?;
+    let sender = eval(&args[2], env, context)?;
+
+    let expected_asset_type = env
+        .global_context
+        .database
+        .get_nft_key_type(&env.contract_context.contract_identifier, asset_name)?;
+
+    runtime_cost!(cost_functions::NFT_BURN, env, expected_asset_type.size())?;
+
+    if !expected_asset_type.admits(&asset) {
+        return Err(CheckErrors::TypeValueError(expected_asset_type, asset).into());
+    }
+
+    if let Value::Principal(ref sender_principal) = sender {
+        let current_owner = match env.global_context.database.get_nft_owner(
+            &env.contract_context.contract_identifier,
+            asset_name,
+            &asset,
+        ) {
+            Ok(owner) => Ok(owner),
+            Err(Error::Runtime(RuntimeErrorType::NoSuchToken, _)) => {
+                return clarity_ecode!(BurnAssetErrorCodes::DOES_NOT_EXIST)
+            }
+            Err(e) => Err(e),
+        }?;
+
+        if current_owner != *sender_principal {
+            return clarity_ecode!(BurnAssetErrorCodes::NOT_OWNED_BY);
+        }
+
+        env.add_memory(TypeSignature::PrincipalType.size() as u64)?;
+        env.add_memory(expected_asset_type.size() as u64)?;
+
+        env.global_context.database.burn_nft(
+            &env.contract_context.contract_identifier,
+            asset_name,
+            &asset,
+        )?;
+
+        env.global_context.log_asset_transfer(
+            sender_principal,
+            &env.contract_context.contract_identifier,
+            asset_name,
+            asset.clone(),
+        );
+
+        let asset_identifier = AssetIdentifier {
+            contract_identifier: env.contract_context.contract_identifier.clone(),
+            asset_name: asset_name.clone(),
+        };
+        env.register_nft_burn_event(sender_principal.clone(), asset, asset_identifier)?;
+
+        Ok(Value::okay_true())
+    } else {
+        Err(CheckErrors::TypeValueError(TypeSignature::PrincipalType, sender).into())
+    }
+}
+
 pub fn special_get_balance(
     args: &[SymbolicExpression],
     env: &mut Environment,
@@ -546,6 +725,24 @@ pub fn special_get_balance(
     }
 }
 
+pub fn special_get_token_supply(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    _context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(1, args)?;
+
+    runtime_cost!(cost_functions::FT_SUPPLY, env, 0)?;
+
+    let token_name = args[0].match_atom().ok_or(CheckErrors::BadTokenName)?;
+
+    let supply = env
+        .global_context
+        .database
+        .get_ft_supply(&env.contract_context.contract_identifier, token_name)?;
+    Ok(Value::UInt(supply))
+}
+
 pub fn special_get_owner(
     args: &[SymbolicExpression],
     env: &mut Environment,
@@ -0,0 +1,216 @@
+// Copyright (C) 2013-2020 Blocstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::str;
+use vm::costs::cost_functions;
+use vm::errors::{check_argument_count, CheckErrors, InterpreterResult as Result};
+use vm::representations::SymbolicExpression;
+use vm::types::{CharType, SequenceData, TypeSignature, Value};
+use vm::{eval, Environment, LocalContext};
+
+/// The longest decimal rendering of a Clarity integer: a sign and the 39 digits of
+///   `i128::min_value()`.
+pub const MAX_INT_STRING_LEN: u32 = 40;
+
+fn int_to_string(value: Value) -> Result<String> {
+    match value {
+        Value::Int(value) => Ok(value.to_string()),
+        Value::UInt(value) => Ok(value.to_string()),
+        _ => Err(CheckErrors::UnionTypeValueError(
+            vec![TypeSignature::IntType, TypeSignature::UIntType],
+            value,
+        )
+        .into()),
+    }
+}
+
+pub fn native_int_to_ascii(value: Value) -> Result<Value> {
+    Value::string_ascii_from_bytes(int_to_string(value)?.into_bytes())
+}
+
+pub fn native_int_to_utf8(value: Value) -> Result<Value> {
+    Value::string_utf8_from_bytes(int_to_string(value)?.into_bytes())
+}
+
+/// Returns the text of a string value, or None if it is too long to be an integer.
+fn string_to_int_text(value: Value) -> Result<Option<String>> {
+    let bytes = match value {
+        Value::Sequence(SequenceData::String(CharType::ASCII(data))) => data.data,
+        Value::Sequence(SequenceData::String(CharType::UTF8(data))) => {
+            // each char takes at least one byte, so check the char count before joining them up
+            if data.data.len() > MAX_INT_STRING_LEN as usize {
+                return Ok(None);
+            }
+            data.data.concat()
+        }
+        _ => {
+            return Err(CheckErrors::UnionTypeValueError(
+                vec![
+                    TypeSignature::max_string_ascii(),
+                    TypeSignature::max_string_utf8(),
+                ],
+                value,
+            )
+            .into())
+        }
+    };
+    if bytes.len() > MAX_INT_STRING_LEN as usize {
+        return Ok(None);
+    }
+    Ok(str::from_utf8(&bytes).ok().map(|text| text.to_string()))
+}
+
+pub fn native_string_to_int(value: Value) -> Result<Value> {
+    match string_to_int_text(value)?.and_then(|text| text.parse::<i128>().ok()) {
+        Some(parsed) => Value::some(Value::Int(parsed)),
+        None => Ok(Value::none()),
+    }
+}
+
+pub fn native_string_to_uint(value: Value) -> Result<Value> {
+    match string_to_int_text(value)?.and_then(|text| text.parse::<u128>().ok()) {
+        Some(parsed) => Value::some(Value::UInt(parsed)),
+        None => Ok(Value::none()),
+    }
+}
+
+pub fn special_buff_to_ascii(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(1, args)?;
+
+    let input = eval(&args[0], env, context)?;
+
+    runtime_cost!(cost_functions::BUFF_TO_ASCII, env, u64::from(input.size()))?;
+
+    match input {
+        Value::Sequence(SequenceData::Buffer(buff_data)) => {
+            match Value::string_ascii_from_bytes(buff_data.data) {
+                Ok(string) => Value::some(string),
+                Err(_) => Ok(Value::none()),
+            }
+        }
+        _ => Err(CheckErrors::TypeValueError(TypeSignature::max_buffer(), input).into()),
+    }
+}
+
+pub fn special_buff_to_utf8(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(1, args)?;
+
+    let input = eval(&args[0], env, context)?;
+
+    runtime_cost!(cost_functions::BUFF_TO_UTF8, env, u64::from(input.size()))?;
+
+    match input {
+        Value::Sequence(SequenceData::Buffer(buff_data)) => {
+            match Value::string_utf8_from_bytes(buff_data.data) {
+                Ok(string) => Value::some(string),
+                Err(_) => Ok(Value::none()),
+            }
+        }
+        _ => Err(CheckErrors::TypeValueError(TypeSignature::max_buffer(), input).into()),
+    }
+}
+
+pub fn special_ascii_to_buff(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(1, args)?;
+
+    let input = eval(&args[0], env, context)?;
+
+    runtime_cost!(cost_functions::ASCII_TO_BUFF, env, u64::from(input.size()))?;
+
+    match input {
+        Value::Sequence(SequenceData::String(CharType::ASCII(data))) => Value::buff_from(data.data),
+        _ => Err(CheckErrors::TypeValueError(TypeSignature::max_string_ascii(), input).into()),
+    }
+}
+
+pub fn special_utf8_to_buff(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(1, args)?;
+
+    let input = eval(&args[0], env, context)?;
+
+    runtime_cost!(cost_functions::UTF8_TO_BUFF, env, u64::from(input.size()))?;
+
+    match input {
+        Value::Sequence(SequenceData::String(CharType::UTF8(data))) => {
+            Value::buff_from(data.data.concat())
+        }
+        _ => Err(CheckErrors::TypeValueError(TypeSignature::max_string_utf8(), input).into()),
+    }
+}
+
+pub fn special_to_consensus_buff(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(1, args)?;
+
+    let value = eval(&args[0], env, context)?;
+
+    runtime_cost!(
+        cost_functions::TO_CONSENSUS_BUFF,
+        env,
+        u64::from(value.size())
+    )?;
+
+    Value::buff_from(value.serialize_to_vec())
+}
+
+/// Deserializes a buffer into a value of the given type. Returns `none` if the buffer is not
+///   exactly the consensus serialization of some value of that type.
+pub fn special_from_consensus_buff(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(2, args)?;
+
+    let expected_type = TypeSignature::parse_type_repr(&args[0], env)?;
+    let input = eval(&args[1], env, context)?;
+
+    let input_bytes = match input {
+        Value::Sequence(SequenceData::Buffer(buff_data)) => buff_data.data,
+        _ => return Err(CheckErrors::TypeValueError(TypeSignature::max_buffer(), input).into()),
+    };
+
+    runtime_cost!(
+        cost_functions::FROM_CONSENSUS_BUFF,
+        env,
+        input_bytes.len() as u64
+    )?;
+
+    let mut reader = input_bytes.as_slice();
+    match Value::deserialize_read(&mut reader, Some(&expected_type)) {
+        Ok(value) if reader.is_empty() => Value::some(value),
+        _ => Ok(Value::none()),
+    }
+}
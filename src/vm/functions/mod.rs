@@ -17,6 +17,7 @@
 mod arithmetic;
 mod assets;
 mod boolean;
+mod conversions;
 mod crypto;
 mod database;
 pub mod define;
@@ -25,6 +26,8 @@ mod sequences;
 mod special;
 pub mod tuples;
 
+pub use vm::functions::conversions::MAX_INT_STRING_LEN;
+
 use util::hash;
 use vm::callables::{CallableType, NativeHandle};
 use vm::costs::{constants as cost_constants, cost_functions, CostTracker, MemoryConsumer};
@@ -72,6 +75,9 @@ define_named_enum!(NativeFunctions {
     Concat("concat"),
     AsMaxLen("as-max-len?"),
     Len("len"),
+    ElementAt("element-at"),
+    IndexOf("index-of"),
+    Slice("slice"),
     ListCons("list"),
     FetchVar("var-get"),
     SetVar("var-set"),
@@ -113,14 +119,28 @@ define_named_enum!(NativeFunctions {
     IsSome("is-some"),
     Filter("filter"),
     GetTokenBalance("ft-get-balance"),
+    GetTokenSupply("ft-get-supply"),
     GetAssetOwner("nft-get-owner?"),
     TransferToken("ft-transfer?"),
     TransferAsset("nft-transfer?"),
     MintAsset("nft-mint?"),
     MintToken("ft-mint?"),
     GetStxBalance("stx-get-balance"),
+    GetStxAccount("stx-account"),
     StxTransfer("stx-transfer?"),
     StxBurn("stx-burn?"),
+    BurnToken("ft-burn?"),
+    BurnAsset("nft-burn?"),
+    ToConsensusBuff("to-consensus-buff"),
+    FromConsensusBuff("from-consensus-buff"),
+    IntToAscii("int-to-ascii"),
+    IntToUtf8("int-to-utf8"),
+    StringToInt("string-to-int?"),
+    StringToUInt("string-to-uint?"),
+    BuffToAscii("buff-to-ascii?"),
+    BuffToUtf8("buff-to-utf8?"),
+    AsciiToBuff("ascii-to-buff"),
+    Utf8ToBuff("utf8-to-buff"),
 });
 
 pub fn lookup_reserved_functions(name: &str) -> Option<CallableType> {
@@ -225,6 +245,9 @@ pub fn lookup_reserved_functions(name: &str) -> Option<CallableType> {
                 NativeHandle::SingleArg(&sequences::native_len),
                 cost_functions::LEN,
             ),
+            ElementAt => SpecialFunction("special_element_at", &sequences::special_element_at),
+            IndexOf => SpecialFunction("special_index_of", &sequences::special_index_of),
+            Slice => SpecialFunction("special_slice", &sequences::special_slice),
             ListCons => SpecialFunction("special_list_cons", &sequences::list_cons),
             FetchEntry => SpecialFunction("special_map-get?", &database::special_fetch_entry),
             SetEntry => SpecialFunction("special_set-entry", &database::special_set_entry),
@@ -355,11 +378,58 @@ pub fn lookup_reserved_functions(name: &str) -> Option<CallableType> {
                 SpecialFunction("special_transfer_token", &assets::special_transfer_token)
             }
             GetTokenBalance => SpecialFunction("special_get_balance", &assets::special_get_balance),
+            GetTokenSupply => SpecialFunction(
+                "special_get_token_supply",
+                &assets::special_get_token_supply,
+            ),
             GetAssetOwner => SpecialFunction("special_get_owner", &assets::special_get_owner),
             AtBlock => SpecialFunction("special_at_block", &database::special_at_block),
             GetStxBalance => SpecialFunction("special_stx_balance", &assets::special_stx_balance),
+            GetStxAccount => SpecialFunction("special_stx_account", &assets::special_stx_account),
             StxTransfer => SpecialFunction("special_stx_transfer", &assets::special_stx_transfer),
             StxBurn => SpecialFunction("special_stx_burn", &assets::special_stx_burn),
+            BurnToken => SpecialFunction("special_burn_token", &assets::special_burn_token),
+            BurnAsset => SpecialFunction("special_burn_asset", &assets::special_burn_asset),
+            ToConsensusBuff => SpecialFunction(
+                "special_to_consensus_buff",
+                &conversions::special_to_consensus_buff,
+            ),
+            FromConsensusBuff => SpecialFunction(
+                "special_from_consensus_buff",
+                &conversions::special_from_consensus_buff,
+            ),
+            IntToAscii => NativeFunction(
+                "native_int_to_ascii",
+                NativeHandle::SingleArg(&conversions::native_int_to_ascii),
+                cost_functions::INT_TO_ASCII,
+            ),
+            IntToUtf8 => NativeFunction(
+                "native_int_to_utf8",
+                NativeHandle::SingleArg(&conversions::native_int_to_utf8),
+                cost_functions::INT_TO_UTF8,
+            ),
+            StringToInt => NativeFunction(
+                "native_string_to_int",
+                NativeHandle::SingleArg(&conversions::native_string_to_int),
+                cost_functions::STRING_TO_INT,
+            ),
+            StringToUInt => NativeFunction(
+                "native_string_to_uint",
+                NativeHandle::SingleArg(&conversions::native_string_to_uint),
+                cost_functions::STRING_TO_UINT,
+            ),
+            BuffToAscii => {
+                SpecialFunction("special_buff_to_ascii", &conversions::special_buff_to_ascii)
+            }
+            BuffToUtf8 => {
+                SpecialFunction("special_buff_to_utf8", &conversions::special_buff_to_utf8)
+            }
+            AsciiToBuff => {
+                SpecialFunction("special_ascii_to_buff", &conversions::special_ascii_to_buff)
+            }
+            Utf8ToBuff => {
+                SpecialFunction("special_utf8_to_buff", &conversions::special_utf8_to_buff)
+            }
         };
         Some(callable)
     } else {
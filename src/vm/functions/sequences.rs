@@ -15,7 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::cmp;
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 use vm::costs::{cost_functions, CostOverflowingMath};
 use vm::errors::{
     check_argument_count, CheckErrors, InterpreterResult as Result, RuntimeErrorType,
@@ -240,3 +240,85 @@ pub fn native_len(sequence: Value) -> Result<Value> {
         _ => Err(CheckErrors::ExpectedSequence(TypeSignature::type_of(&sequence)).into()),
     }
 }
+
+/// Converts a `uint` index argument into a `usize`, or None if it cannot address any sequence.
+fn sequence_index(index: Value) -> Result<Option<usize>> {
+    match index {
+        Value::UInt(index) => Ok(usize::try_from(index).ok()),
+        _ => Err(CheckErrors::TypeValueError(TypeSignature::UIntType, index).into()),
+    }
+}
+
+pub fn special_element_at(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(2, args)?;
+
+    let sequence = eval(&args[0], env, context)?;
+    let index = eval(&args[1], env, context)?;
+
+    runtime_cost!(cost_functions::ELEMENT_AT, env, 0)?;
+
+    let sequence_data = match sequence {
+        Value::Sequence(sequence_data) => sequence_data,
+        _ => return Err(CheckErrors::ExpectedSequence(TypeSignature::type_of(&sequence)).into()),
+    };
+
+    match sequence_index(index)?.and_then(|index| sequence_data.element_at(index)) {
+        Some(element) => Value::some(element),
+        None => Ok(Value::none()),
+    }
+}
+
+pub fn special_index_of(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(2, args)?;
+
+    let sequence = eval(&args[0], env, context)?;
+    let to_find = eval(&args[1], env, context)?;
+
+    runtime_cost!(cost_functions::INDEX_OF, env, u64::from(sequence.size()))?;
+
+    let sequence_data = match sequence {
+        Value::Sequence(ref sequence_data) => sequence_data,
+        _ => return Err(CheckErrors::ExpectedSequence(TypeSignature::type_of(&sequence)).into()),
+    };
+
+    match sequence_data.contains(to_find)? {
+        Some(index) => Value::some(Value::UInt(index as u128)),
+        None => Ok(Value::none()),
+    }
+}
+
+pub fn special_slice(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(3, args)?;
+
+    let sequence = eval(&args[0], env, context)?;
+    let left = eval(&args[1], env, context)?;
+    let right = eval(&args[2], env, context)?;
+
+    runtime_cost!(cost_functions::SLICE, env, u64::from(sequence.size()))?;
+
+    let sequence_data = match sequence {
+        Value::Sequence(sequence_data) => sequence_data,
+        _ => return Err(CheckErrors::ExpectedSequence(TypeSignature::type_of(&sequence)).into()),
+    };
+
+    let sliced = match (sequence_index(left)?, sequence_index(right)?) {
+        (Some(left), Some(right)) => sequence_data.slice(left, right)?,
+        _ => None,
+    };
+    match sliced {
+        Some(sliced) => Value::some(sliced),
+        None => Ok(Value::none()),
+    }
+}
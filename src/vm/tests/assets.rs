@@ -18,6 +18,7 @@ use chainstate::stacks::events::StacksTransactionEvent;
 use util::hash::hex_bytes;
 use vm::contexts::{AssetMap, AssetMapEntry, GlobalContext, OwnedEnvironment};
 use vm::contracts::Contract;
+use vm::database::STXBalance;
 use vm::errors::{CheckErrors, Error, RuntimeErrorType};
 use vm::execute as vm_execute;
 use vm::representations::SymbolicExpression;
@@ -25,7 +26,9 @@ use vm::tests::{
     execute, is_committed, is_err_code, symbols_from_values, with_marfed_environment,
     with_memory_environment,
 };
-use vm::types::{AssetIdentifier, PrincipalData, QualifiedContractIdentifier, ResponseData, Value};
+use vm::types::{
+    AssetIdentifier, PrincipalData, QualifiedContractIdentifier, ResponseData, TupleData, Value,
+};
 
 const FIRST_CLASS_TOKENS: &str = "(define-fungible-token stackaroos)
          (define-read-only (my-ft-get-balance (account principal))
@@ -519,6 +522,90 @@ fn test_native_stx_ops(owned_env: &mut OwnedEnvironment) {
     );
 }
 
+fn test_stx_account(owned_env: &mut OwnedEnvironment) {
+    let p1 = execute("'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR");
+    let p2 = execute("'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G");
+    let p3 = execute("'SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY");
+
+    let (p1_principal, p2_principal) = match (&p1, &p2) {
+        (Value::Principal(ref p1), Value::Principal(ref p2)) => (p1.clone(), p2.clone()),
+        _ => panic!(),
+    };
+
+    let (burn_height, _asset_map, _events) = owned_env
+        .execute_in_env(p1.clone(), |env| {
+            Ok(env
+                .global_context
+                .database
+                .get_current_burnchain_block_height() as u64)
+        })
+        .unwrap();
+
+    // p1's lock has not expired yet, p2's lock is unlockable at the current height.
+    owned_env
+        .execute_in_env(p1.clone(), |env| {
+            env.global_context.database.set_account_stx_balance(
+                &p1_principal,
+                &STXBalance {
+                    amount_unlocked: 100,
+                    amount_locked: 1000,
+                    unlock_height: burn_height + 10,
+                },
+            );
+            env.global_context.database.set_account_stx_balance(
+                &p2_principal,
+                &STXBalance {
+                    amount_unlocked: 100,
+                    amount_locked: 1000,
+                    unlock_height: burn_height,
+                },
+            );
+            Ok(())
+        })
+        .unwrap();
+
+    let account = |locked: u128, unlocked: u128, unlock_height: u64| {
+        Value::Tuple(
+            TupleData::from_data(vec![
+                ("locked".into(), Value::UInt(locked)),
+                ("unlocked".into(), Value::UInt(unlocked)),
+                (
+                    "unlock-height".into(),
+                    Value::UInt(u128::from(unlock_height)),
+                ),
+            ])
+            .unwrap(),
+        )
+    };
+
+    let (result, _asset_map, _events) = owned_env
+        .eval_raw(&format!("(stx-account '{})", p1))
+        .unwrap();
+    assert_eq!(result, account(1000, 100, burn_height + 10));
+
+    let (result, _asset_map, _events) = owned_env
+        .eval_raw(&format!("(stx-account '{})", p2))
+        .unwrap();
+    assert_eq!(result, account(0, 1100, 0));
+
+    let (result, _asset_map, _events) = owned_env
+        .eval_raw(&format!("(stx-account '{})", p3))
+        .unwrap();
+    assert_eq!(result, account(0, 0, 0));
+
+    // stx-get-balance agrees with the unlocked portion
+    let (result, _asset_map, _events) = owned_env
+        .eval_raw(&format!("(stx-get-balance '{})", p1))
+        .unwrap();
+    assert_eq!(result, Value::UInt(100));
+
+    let err = owned_env.eval_raw("(stx-account u1)").unwrap_err();
+    assert!(match err {
+        Error::Unchecked(CheckErrors::TypeValueError(_, _)) => true,
+        _ => false,
+    });
+}
+
 fn test_simple_token_system(owned_env: &mut OwnedEnvironment) {
     let tokens_contract = FIRST_CLASS_TOKENS;
 
@@ -794,6 +881,293 @@ fn total_supply(owned_env: &mut OwnedEnvironment) {
     });
 }
 
+fn test_burn_assets(owned_env: &mut OwnedEnvironment) {
+    let contract = "(define-fungible-token stackaroos u5)
+         (define-non-fungible-token names int)
+         (define-read-only (get-balance (account principal))
+            (ft-get-balance stackaroos account))
+         (define-read-only (get-owner (name int))
+            (nft-get-owner? names name))
+         (define-public (faucet (amount uint))
+            (ft-mint? stackaroos amount tx-sender))
+         (define-public (burn (amount uint))
+            (ft-burn? stackaroos amount tx-sender))
+         (define-public (mint-name (name int))
+            (nft-mint? names name tx-sender))
+         (define-public (burn-name (name int))
+            (nft-burn? names name tx-sender))";
+
+    let p1 = execute("'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR");
+    let p2 = execute("'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G");
+
+    let p1_principal = match p1 {
+        Value::Principal(PrincipalData::Standard(ref data)) => data.clone(),
+        _ => panic!(),
+    };
+
+    let token_contract_id = QualifiedContractIdentifier::new(p1_principal.clone(), "tokens".into());
+    let token_identifier = AssetIdentifier {
+        contract_identifier: token_contract_id.clone(),
+        asset_name: "stackaroos".into(),
+    };
+    let names_identifier = AssetIdentifier {
+        contract_identifier: token_contract_id.clone(),
+        asset_name: "names".into(),
+    };
+
+    owned_env
+        .initialize_contract(token_contract_id.clone(), contract)
+        .unwrap();
+
+    let (result, _asset_map, _events) = execute_transaction(
+        owned_env,
+        p1.clone(),
+        &token_contract_id,
+        "faucet",
+        &symbols_from_values(vec![Value::UInt(5)]),
+    )
+    .unwrap();
+    assert!(is_committed(&result));
+
+    // can't burn a non-positive amount, or more than the balance
+    let (result, _asset_map, _events) = execute_transaction(
+        owned_env,
+        p1.clone(),
+        &token_contract_id,
+        "burn",
+        &symbols_from_values(vec![Value::UInt(0)]),
+    )
+    .unwrap();
+    assert!(is_err_code(&result, 3));
+
+    let (result, _asset_map, _events) = execute_transaction(
+        owned_env,
+        p1.clone(),
+        &token_contract_id,
+        "burn",
+        &symbols_from_values(vec![Value::UInt(6)]),
+    )
+    .unwrap();
+    assert!(is_err_code(&result, 1));
+
+    let (result, asset_map, _events) = execute_transaction(
+        owned_env,
+        p1.clone(),
+        &token_contract_id,
+        "burn",
+        &symbols_from_values(vec![Value::UInt(3)]),
+    )
+    .unwrap();
+    assert!(is_committed(&result));
+
+    let table = asset_map.to_table();
+    assert_eq!(
+        table[&PrincipalData::Standard(p1_principal.clone())][&token_identifier],
+        AssetMapEntry::Token(3)
+    );
+
+    let (result, _asset_map, _events) = execute_transaction(
+        owned_env,
+        p1.clone(),
+        &token_contract_id,
+        "get-balance",
+        &symbols_from_values(vec![p1.clone()]),
+    )
+    .unwrap();
+    assert_eq!(result, Value::UInt(2));
+
+    // burned tokens no longer count against the total supply
+    let (result, _asset_map, _events) = execute_transaction(
+        owned_env,
+        p1.clone(),
+        &token_contract_id,
+        "faucet",
+        &symbols_from_values(vec![Value::UInt(3)]),
+    )
+    .unwrap();
+    assert!(is_committed(&result));
+
+    let err = execute_transaction(
+        owned_env,
+        p1.clone(),
+        &token_contract_id,
+        "faucet",
+        &symbols_from_values(vec![Value::UInt(1)]),
+    )
+    .unwrap_err();
+    assert!(match err {
+        Error::Runtime(RuntimeErrorType::SupplyOverflow(x, y), _) => (x, y) == (6, 5),
+        _ => false,
+    });
+
+    let (result, _asset_map, _events) = execute_transaction(
+        owned_env,
+        p1.clone(),
+        &token_contract_id,
+        "mint-name",
+        &symbols_from_values(vec![Value::Int(1)]),
+    )
+    .unwrap();
+    assert!(is_committed(&result));
+
+    // only the owner can burn an asset
+    let (result, _asset_map, _events) = execute_transaction(
+        owned_env,
+        p2.clone(),
+        &token_contract_id,
+        "burn-name",
+        &symbols_from_values(vec![Value::Int(1)]),
+    )
+    .unwrap();
+    assert!(is_err_code(&result, 1));
+
+    let (result, asset_map, _events) = execute_transaction(
+        owned_env,
+        p1.clone(),
+        &token_contract_id,
+        "burn-name",
+        &symbols_from_values(vec![Value::Int(1)]),
+    )
+    .unwrap();
+    assert!(is_committed(&result));
+
+    let table = asset_map.to_table();
+    assert_eq!(
+        table[&PrincipalData::Standard(p1_principal.clone())][&names_identifier],
+        AssetMapEntry::Asset(vec![Value::Int(1)])
+    );
+
+    let (result, _asset_map, _events) = execute_transaction(
+        owned_env,
+        p1.clone(),
+        &token_contract_id,
+        "get-owner",
+        &symbols_from_values(vec![Value::Int(1)]),
+    )
+    .unwrap();
+    assert_eq!(result, Value::none());
+
+    let (result, _asset_map, _events) = execute_transaction(
+        owned_env,
+        p1.clone(),
+        &token_contract_id,
+        "burn-name",
+        &symbols_from_values(vec![Value::Int(1)]),
+    )
+    .unwrap();
+    assert!(is_err_code(&result, 3));
+
+    // a burned asset can be minted again
+    let (result, _asset_map, _events) = execute_transaction(
+        owned_env,
+        p2.clone(),
+        &token_contract_id,
+        "mint-name",
+        &symbols_from_values(vec![Value::Int(1)]),
+    )
+    .unwrap();
+    assert!(is_committed(&result));
+
+    let (result, _asset_map, _events) = execute_transaction(
+        owned_env,
+        p1.clone(),
+        &token_contract_id,
+        "get-owner",
+        &symbols_from_values(vec![Value::Int(1)]),
+    )
+    .unwrap();
+    assert_eq!(result, Value::some(p2.clone()).unwrap());
+}
+
+fn test_get_token_supply(owned_env: &mut OwnedEnvironment) {
+    let contract = "(define-fungible-token capped u10)
+         (define-fungible-token uncapped)
+         (define-read-only (get-supply)
+            (ft-get-supply capped))
+         (define-read-only (get-uncapped-supply)
+            (ft-get-supply uncapped))
+         (define-public (mint (amount uint))
+            (begin (try! (ft-mint? capped amount tx-sender))
+                   (ft-mint? uncapped amount tx-sender)))
+         (define-public (burn (amount uint))
+            (begin (try! (ft-burn? capped amount tx-sender))
+                   (ft-burn? uncapped amount tx-sender)))";
+
+    let p1 = execute("'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR");
+
+    let p1_principal = match p1 {
+        Value::Principal(PrincipalData::Standard(ref data)) => data.clone(),
+        _ => panic!(),
+    };
+
+    let token_contract_id = QualifiedContractIdentifier::new(p1_principal.clone(), "tokens".into());
+
+    owned_env
+        .initialize_contract(token_contract_id.clone(), contract)
+        .unwrap();
+
+    let assert_supplies = |owned_env: &mut OwnedEnvironment, expected: u128| {
+        let (result, _asset_map, _events) =
+            execute_transaction(owned_env, p1.clone(), &token_contract_id, "get-supply", &[])
+                .unwrap();
+        assert_eq!(result, Value::UInt(expected));
+
+        // only tokens with a max supply track their supply; analysis rejects this call, but the
+        // interpreter still refuses it for contracts that were never type checked
+        let err = execute_transaction(
+            owned_env,
+            p1.clone(),
+            &token_contract_id,
+            "get-uncapped-supply",
+            &[],
+        )
+        .unwrap_err();
+        assert!(match err {
+            Error::Runtime(RuntimeErrorType::UntrackedTokenSupply, _) => true,
+            _ => false,
+        });
+    };
+
+    assert_supplies(owned_env, 0);
+
+    let (result, _asset_map, _events) = execute_transaction(
+        owned_env,
+        p1.clone(),
+        &token_contract_id,
+        "mint",
+        &symbols_from_values(vec![Value::UInt(7)]),
+    )
+    .unwrap();
+    assert!(is_committed(&result));
+    assert_supplies(owned_env, 7);
+
+    let (result, _asset_map, _events) = execute_transaction(
+        owned_env,
+        p1.clone(),
+        &token_contract_id,
+        "burn",
+        &symbols_from_values(vec![Value::UInt(3)]),
+    )
+    .unwrap();
+    assert!(is_committed(&result));
+    assert_supplies(owned_env, 4);
+
+    // overflowing the capped token aborts the whole transaction
+    let err = execute_transaction(
+        owned_env,
+        p1.clone(),
+        &token_contract_id,
+        "mint",
+        &symbols_from_values(vec![Value::UInt(7)]),
+    )
+    .unwrap_err();
+    assert!(match err {
+        Error::Runtime(RuntimeErrorType::SupplyOverflow(x, y), _) => (x, y) == (11, 10),
+        _ => false,
+    });
+    assert_supplies(owned_env, 4);
+}
+
 fn test_overlapping_nfts(owned_env: &mut OwnedEnvironment) {
     let tokens_contract = FIRST_CLASS_TOKENS;
     let names_contract = ASSET_NAMES;
@@ -1113,7 +1487,10 @@ fn test_all() {
         test_simple_token_system,
         test_simple_naming_system,
         total_supply,
+        test_get_token_supply,
+        test_burn_assets,
         test_native_stx_ops,
+        test_stx_account,
     ];
     for test in to_test.iter() {
         with_memory_environment(test, true);
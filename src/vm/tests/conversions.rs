@@ -0,0 +1,399 @@
+// Copyright (C) 2013-2020 Blocstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use vm::analysis::mem_type_check;
+use vm::docs::make_json_api_reference;
+use vm::errors::{CheckErrors, Error};
+use vm::execute;
+use vm::types::{TypeSignature, Value};
+
+/// A value of every kind of type signature, paired with the type it is round-tripped through.
+///   The last two entries are maximal values of their types, whose serialization must fill
+///   the `to-consensus-buff` output type exactly.
+const ROUND_TRIPS: &[(&str, &str)] = &[
+    ("int", "-170141183460469231731687303715884105728"),
+    ("uint", "u340282366920938463463374607431768211455"),
+    ("bool", "true"),
+    ("bool", "false"),
+    ("principal", "'S1G2081040G2081040G2081040G208105NK8PE5"),
+    (
+        "principal",
+        "'S1G2081040G2081040G2081040G208105NK8PE5.tokens",
+    ),
+    ("(buff 3)", "0x0102"),
+    ("(string-ascii 10)", "\"hello\""),
+    ("(string-utf8 10)", "u\"hello \\u{1F926}\""),
+    ("(list 3 int)", "(list 1 2)"),
+    (
+        "(list 2 (list 2 bool))",
+        "(list (list true) (list false true))",
+    ),
+    ("(tuple (a int) (b (buff 2)))", "(tuple (a 1) (b 0x01))"),
+    ("(optional int)", "(some 1)"),
+    ("(optional int)", "none"),
+    ("(response int bool)", "(ok 1)"),
+    ("(response int bool)", "(err false)"),
+    (
+        "(tuple (ids (list 2 uint)) (name (string-ascii 3)))",
+        "(tuple (ids (list u1 u2)) (name \"abc\"))",
+    ),
+    ("(optional (buff 4))", "(some 0x01020304)"),
+];
+
+#[test]
+fn test_consensus_buff_round_trips() {
+    for (type_repr, value_repr) in ROUND_TRIPS.iter() {
+        let value = execute(value_repr).unwrap().unwrap();
+        let serialized = Value::buff_from(value.serialize_to_vec()).unwrap();
+
+        assert_eq!(
+            serialized,
+            execute(&format!("(to-consensus-buff {})", value_repr))
+                .unwrap()
+                .unwrap()
+        );
+        assert_eq!(
+            Value::some(value).unwrap(),
+            execute(&format!(
+                "(from-consensus-buff {} (to-consensus-buff {}))",
+                type_repr, value_repr
+            ))
+            .unwrap()
+            .unwrap()
+        );
+
+        let expected_type = format!("(optional {})", type_repr);
+        let round_trip_type = mem_type_check(&format!(
+            "(from-consensus-buff {} (to-consensus-buff {}))",
+            type_repr, value_repr
+        ))
+        .unwrap()
+        .0
+        .unwrap();
+        assert_eq!(expected_type, format!("{}", round_trip_type));
+    }
+}
+
+#[test]
+fn test_to_consensus_buff_type_is_max_serialized_size() {
+    for (type_repr, value_repr) in ROUND_TRIPS.iter() {
+        let program = format!(
+            "(define-private (ser (x {})) (to-consensus-buff x)) (ser {})",
+            type_repr, value_repr
+        );
+        let buff_type = mem_type_check(&program).unwrap().0.unwrap();
+        let value = execute(value_repr).unwrap().unwrap();
+        let serialized_len = value.serialize_to_vec().len() as u32;
+
+        let max_len = match buff_type {
+            TypeSignature::SequenceType(_) => buff_type.size() - 4,
+            _ => panic!("Expected a buffer type, found {}", buff_type),
+        };
+        assert!(serialized_len <= max_len);
+    }
+
+    for (type_repr, value_repr) in ROUND_TRIPS.iter().rev().take(2) {
+        let program = format!(
+            "(define-private (ser (x {})) (to-consensus-buff x)) (ser {})",
+            type_repr, value_repr
+        );
+        let buff_type = mem_type_check(&program).unwrap().0.unwrap();
+        let serialized_len = execute(value_repr)
+            .unwrap()
+            .unwrap()
+            .serialize_to_vec()
+            .len() as u32;
+        assert_eq!(buff_type.size() - 4, serialized_len);
+    }
+}
+
+#[test]
+fn test_from_consensus_buff_rejects_bad_input() {
+    let tests = [
+        // wrong type
+        "(from-consensus-buff uint 0x0000000000000000000000000000000001)",
+        // trailing bytes
+        "(from-consensus-buff bool 0x0303)",
+        // truncated
+        "(from-consensus-buff int 0x00000000)",
+        // too long for the declared type
+        "(from-consensus-buff (buff 1) 0x02000000020102)",
+        "(from-consensus-buff (list 1 int) (to-consensus-buff (list 1 2)))",
+        // empty
+        "(from-consensus-buff int 0x)",
+    ];
+
+    for test in tests.iter() {
+        assert_eq!(Value::none(), execute(test).unwrap().unwrap());
+    }
+
+    assert_eq!(
+        execute("(from-consensus-buff int 1)").unwrap_err(),
+        Error::from(CheckErrors::TypeValueError(
+            TypeSignature::max_buffer(),
+            Value::Int(1)
+        ))
+    );
+}
+
+#[test]
+fn test_consensus_buff_type_checks() {
+    let bad = [
+        (
+            "(from-consensus-buff not-a-type 0x00)",
+            CheckErrors::InvalidTypeDescription,
+        ),
+        (
+            "(from-consensus-buff int 1)",
+            CheckErrors::TypeError(TypeSignature::max_buffer(), TypeSignature::IntType),
+        ),
+        (
+            "(to-consensus-buff 1 2)",
+            CheckErrors::IncorrectArgumentCount(1, 2),
+        ),
+        (
+            // (list 65000 int) is a legal type, but its values serialize to more than 1MB
+            "(define-private (big (x (list 65000 int))) (to-consensus-buff x))",
+            CheckErrors::ValueTooLarge,
+        ),
+    ];
+
+    for (program, expected) in bad.iter() {
+        assert_eq!(expected, &mem_type_check(program).unwrap_err().err);
+    }
+}
+
+#[test]
+fn test_int_to_string() {
+    let tests = [
+        ("(int-to-ascii 0)", "0"),
+        ("(int-to-ascii -17)", "-17"),
+        (
+            "(int-to-ascii -170141183460469231731687303715884105728)",
+            "-170141183460469231731687303715884105728",
+        ),
+        (
+            "(int-to-ascii u340282366920938463463374607431768211455)",
+            "340282366920938463463374607431768211455",
+        ),
+    ];
+
+    for (program, expected) in tests.iter() {
+        assert_eq!(
+            Value::string_ascii_from_bytes(expected.as_bytes().to_vec()).unwrap(),
+            execute(program).unwrap().unwrap()
+        );
+        let utf8_program = program.replace("int-to-ascii", "int-to-utf8");
+        assert_eq!(
+            Value::string_utf8_from_bytes(expected.as_bytes().to_vec()).unwrap(),
+            execute(&utf8_program).unwrap().unwrap()
+        );
+    }
+
+    assert_eq!(
+        execute("(int-to-ascii \"1\")").unwrap_err(),
+        Error::from(CheckErrors::UnionTypeValueError(
+            vec![TypeSignature::IntType, TypeSignature::UIntType],
+            Value::string_ascii_from_bytes(b"1".to_vec()).unwrap()
+        ))
+    );
+}
+
+#[test]
+fn test_string_to_int() {
+    let tests = [
+        (
+            "(string-to-int? \"1\")",
+            Value::some(Value::Int(1)).unwrap(),
+        ),
+        (
+            "(string-to-int? u\"-42\")",
+            Value::some(Value::Int(-42)).unwrap(),
+        ),
+        (
+            "(string-to-int? \"-170141183460469231731687303715884105728\")",
+            Value::some(Value::Int(i128::min_value())).unwrap(),
+        ),
+        (
+            "(string-to-int? \"170141183460469231731687303715884105728\")",
+            Value::none(),
+        ),
+        ("(string-to-int? \"\")", Value::none()),
+        ("(string-to-int? \"1a\")", Value::none()),
+        ("(string-to-int? u\"\\u{1F624}\")", Value::none()),
+        (
+            "(string-to-int? \"00000000000000000000000000000000000000000001\")",
+            Value::none(),
+        ),
+        (
+            "(string-to-int? u\"00000000000000000000000000000000000000000001\")",
+            Value::none(),
+        ),
+        (
+            "(string-to-uint? \"42\")",
+            Value::some(Value::UInt(42)).unwrap(),
+        ),
+        (
+            "(string-to-uint? u\"340282366920938463463374607431768211455\")",
+            Value::some(Value::UInt(u128::max_value())).unwrap(),
+        ),
+        ("(string-to-uint? \"-1\")", Value::none()),
+        ("(string-to-uint? \" 1\")", Value::none()),
+    ];
+
+    for (program, expected) in tests.iter() {
+        assert_eq!(expected, &execute(program).unwrap().unwrap());
+    }
+
+    assert_eq!(
+        execute("(string-to-uint? 0x31)").unwrap_err(),
+        Error::from(CheckErrors::UnionTypeValueError(
+            vec![
+                TypeSignature::max_string_ascii(),
+                TypeSignature::max_string_utf8()
+            ],
+            Value::buff_from(vec![0x31]).unwrap()
+        ))
+    );
+}
+
+#[test]
+fn test_buff_string_conversions() {
+    let tests = [
+        (
+            "(buff-to-ascii? 0x616263)",
+            Value::some(Value::string_ascii_from_bytes(b"abc".to_vec()).unwrap()).unwrap(),
+        ),
+        ("(buff-to-ascii? 0x61ff)", Value::none()),
+        (
+            "(buff-to-utf8? 0x61f09f98a4)",
+            Value::some(Value::string_utf8_from_bytes("a\u{1F624}".into()).unwrap()).unwrap(),
+        ),
+        ("(buff-to-utf8? 0xf09f98)", Value::none()),
+        (
+            "(ascii-to-buff \"abc\")",
+            Value::buff_from(b"abc".to_vec()).unwrap(),
+        ),
+        (
+            "(utf8-to-buff u\"a\\u{1F624}\")",
+            Value::buff_from(vec![0x61, 0xf0, 0x9f, 0x98, 0xa4]).unwrap(),
+        ),
+        (
+            "(buff-to-utf8? (utf8-to-buff u\"a\\u{1F624}\"))",
+            Value::some(Value::string_utf8_from_bytes("a\u{1F624}".into()).unwrap()).unwrap(),
+        ),
+    ];
+
+    for (program, expected) in tests.iter() {
+        assert_eq!(expected, &execute(program).unwrap().unwrap());
+    }
+
+    assert_eq!(
+        execute("(ascii-to-buff u\"abc\")").unwrap_err(),
+        Error::from(CheckErrors::TypeValueError(
+            TypeSignature::max_string_ascii(),
+            Value::string_utf8_from_bytes(b"abc".to_vec()).unwrap()
+        ))
+    );
+}
+
+#[test]
+fn test_string_conversion_type_checks() {
+    let good = [
+        ("(int-to-ascii 1)", "(string-ascii 40)"),
+        ("(int-to-utf8 u1)", "(string-utf8 40)"),
+        ("(string-to-int? \"1\")", "(optional int)"),
+        ("(string-to-uint? u\"1\")", "(optional uint)"),
+        ("(buff-to-ascii? 0x616263)", "(optional (string-ascii 3))"),
+        ("(buff-to-utf8? 0x616263)", "(optional (string-utf8 3))"),
+        ("(ascii-to-buff \"abc\")", "(buff 3)"),
+        ("(utf8-to-buff u\"abc\")", "(buff 12)"),
+    ];
+
+    for (program, expected) in good.iter() {
+        assert_eq!(
+            expected,
+            &format!("{}", mem_type_check(program).unwrap().0.unwrap())
+        );
+    }
+
+    let bad = [
+        (
+            "(int-to-ascii true)",
+            CheckErrors::UnionTypeError(
+                vec![TypeSignature::IntType, TypeSignature::UIntType],
+                TypeSignature::BoolType,
+            ),
+        ),
+        (
+            "(string-to-int? 1)",
+            CheckErrors::UnionTypeError(
+                vec![
+                    TypeSignature::max_string_ascii(),
+                    TypeSignature::max_string_utf8(),
+                ],
+                TypeSignature::IntType,
+            ),
+        ),
+        (
+            "(buff-to-ascii? \"abc\")",
+            CheckErrors::TypeError(
+                TypeSignature::max_buffer(),
+                mem_type_check("\"abc\"").unwrap().0.unwrap(),
+            ),
+        ),
+        (
+            "(ascii-to-buff 0x01)",
+            CheckErrors::TypeError(
+                TypeSignature::max_string_ascii(),
+                mem_type_check("0x01").unwrap().0.unwrap(),
+            ),
+        ),
+        (
+            "(utf8-to-buff \"abc\")",
+            CheckErrors::TypeError(
+                TypeSignature::max_string_utf8(),
+                mem_type_check("\"abc\"").unwrap().0.unwrap(),
+            ),
+        ),
+    ];
+
+    for (program, expected) in bad.iter() {
+        assert_eq!(expected, &mem_type_check(program).unwrap_err().err);
+    }
+}
+
+#[test]
+fn test_string_conversion_docs_are_generated() {
+    let json_reference = make_json_api_reference();
+    assert!(json_reference.contains(
+        "\"name\":\"int-to-ascii\",\"input_type\":\"int | uint\",\"output_type\":\"(string-ascii 40)\""
+    ));
+
+    for name in [
+        "int-to-utf8",
+        "string-to-int?",
+        "string-to-uint?",
+        "buff-to-ascii?",
+        "buff-to-utf8?",
+        "ascii-to-buff",
+        "utf8-to-buff",
+    ]
+    .iter()
+    {
+        assert!(json_reference.contains(&format!("\"name\":\"{}\"", name)));
+    }
+}
@@ -74,6 +74,9 @@ pub fn get_simple_test(function: &NativeFunctions) -> &'static str {
         Concat => "(concat list-bar list-bar)",
         AsMaxLen => "(as-max-len? list-bar u3)",
         Len => "(len list-bar)",
+        ElementAt => "(element-at list-bar u2)",
+        IndexOf => "(index-of list-bar 1)",
+        Slice => "(slice list-bar u1 u3)",
         ListCons => "(list 1 2 3 4)",
         FetchEntry => "(map-get? map-foo {a: 1})",
         SetEntry => "(map-set map-foo {a: 1} {b: 2})",
@@ -113,13 +116,27 @@ pub fn get_simple_test(function: &NativeFunctions) -> &'static str {
         MintAsset => "(ft-mint? ft-foo u1 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
         MintToken => "(nft-mint? nft-foo 1 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
         GetTokenBalance => "(ft-get-balance ft-foo 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        GetTokenSupply => "(ft-get-supply ft-foo)",
         GetAssetOwner => "(nft-get-owner? nft-foo 1)",
         TransferToken => "(ft-transfer? ft-foo u1 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
         TransferAsset => "(nft-transfer? nft-foo 1 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
         AtBlock => "(at-block 0x55c9861be5cff984a20ce6d99d4aa65941412889bdc665094136429b84f8c2ee 1)",   // first stacksblockid
         GetStxBalance => "(stx-get-balance 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        GetStxAccount => "(stx-account 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
         StxTransfer => "(stx-transfer? u1 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
         StxBurn => "(stx-burn? u1 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        BurnToken => "(ft-burn? ft-foo u1 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        BurnAsset => "(nft-burn? nft-foo 1 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        ToConsensusBuff => "(to-consensus-buff list-bar)",
+        FromConsensusBuff => "(from-consensus-buff (list 4 int) 0x0b00000000)",
+        IntToAscii => "(int-to-ascii 1)",
+        IntToUtf8 => "(int-to-utf8 1)",
+        StringToInt => "(string-to-int? \"1\")",
+        StringToUInt => "(string-to-uint? \"1\")",
+        BuffToAscii => "(buff-to-ascii? 0x31)",
+        BuffToUtf8 => "(buff-to-utf8? 0x31)",
+        AsciiToBuff => "(ascii-to-buff \"1\")",
+        Utf8ToBuff => "(utf8-to-buff u\"1\")",
     }
 }
 
@@ -144,7 +161,7 @@ fn test_tracked_costs(prog: &str) -> ExecutionCost {
     let contract_self = format!(
         "(define-map map-foo ((a int)) ((b int)))
                          (define-non-fungible-token nft-foo int)
-                         (define-fungible-token ft-foo)
+                         (define-fungible-token ft-foo u1000000)
                          (define-data-var var-foo int 0)
                          (define-constant tuple-foo (tuple (a 1)))
                          (define-constant list-foo (list true))
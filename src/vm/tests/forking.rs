@@ -16,7 +16,9 @@
 
 use vm::analysis::errors::CheckErrors;
 use vm::contexts::OwnedEnvironment;
-use vm::database::{ClarityDatabase, MarfedKV, NULL_BURN_STATE_DB, NULL_HEADER_DB};
+use vm::database::{
+    ClarityBackingStore, ClarityDatabase, MarfedKV, NULL_BURN_STATE_DB, NULL_HEADER_DB,
+};
 use vm::errors::{Error, InterpreterResult as Result, RuntimeErrorType};
 use vm::representations::SymbolicExpression;
 use vm::types::Value;
@@ -119,6 +121,101 @@ fn test_at_block_good() {
     );
 }
 
+#[test]
+fn test_at_block_pruned() {
+    let c = QualifiedContractIdentifier::local("contract").unwrap();
+    let contract = "(define-data-var datum int 1)
+         (define-public (set-val (val int))
+           (begin
+             (var-set datum val)
+             (ok val)))
+         (define-read-only (get-at-1)
+           (at-block 0x0101010101010101010101010101010101010101010101010101010101010101 (var-get datum)))
+         (define-read-only (get-at-2)
+           (at-block 0x0202020202020202020202020202020202020202020202020202020202020202 (var-get datum)))";
+
+    let mut marf_kv = MarfedKV::temporary();
+    marf_kv.begin(&StacksBlockId::sentinel(), &StacksBlockId([0 as u8; 32]));
+    {
+        marf_kv
+            .as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB)
+            .initialize();
+    }
+    marf_kv.test_commit();
+
+    marf_kv.begin(&StacksBlockId([0 as u8; 32]), &StacksBlockId([1 as u8; 32]));
+    {
+        let mut owned_env =
+            OwnedEnvironment::new(marf_kv.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB));
+        owned_env.initialize_contract(c.clone(), &contract).unwrap();
+    }
+    marf_kv.test_commit();
+
+    for i in 2..4 {
+        marf_kv.begin(&StacksBlockId([i - 1; 32]), &StacksBlockId([i; 32]));
+        {
+            let mut owned_env =
+                OwnedEnvironment::new(marf_kv.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB));
+            owned_env
+                .execute_transaction(
+                    execute(p1_str),
+                    c.clone(),
+                    "set-val",
+                    &symbols_from_values(vec![Value::Int(i as i128)]),
+                )
+                .unwrap();
+        }
+        marf_kv.test_commit();
+    }
+
+    let summary = marf_kv.prune(&StacksBlockId([3 as u8; 32]), 1).unwrap();
+    assert_eq!(summary.num_pruned_tries, 2);
+    assert!(marf_kv
+        .is_block_pruned(&StacksBlockId([1 as u8; 32]))
+        .unwrap());
+
+    // the contract is still readable, but it can no longer be proven
+    let contract_key = MarfedKV::make_contract_hash_key(&c);
+    assert!(marf_kv.get(&contract_key).is_some());
+    match marf_kv.get_with_proof(&contract_key).unwrap_err() {
+        Error::Runtime(x, _) => assert_eq!(
+            x,
+            RuntimeErrorType::PrunedBlockHeaderHash(BlockHeaderHash([3 as u8; 32]))
+        ),
+        e => panic!("Unexpected error: {}", e),
+    }
+    match marf_kv
+        .get_with_multi_proof(&[contract_key.clone()])
+        .unwrap_err()
+    {
+        Error::Runtime(x, _) => assert_eq!(
+            x,
+            RuntimeErrorType::PrunedBlockHeaderHash(BlockHeaderHash([3 as u8; 32]))
+        ),
+        e => panic!("Unexpected error: {}", e),
+    }
+
+    marf_kv.begin(&StacksBlockId([3 as u8; 32]), &StacksBlockId([4 as u8; 32]));
+    {
+        let mut owned_env =
+            OwnedEnvironment::new(marf_kv.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB));
+        let mut env = owned_env.get_exec_environment(None);
+        assert_eq!(
+            env.eval_read_only(&c, "(var-get datum)").unwrap(),
+            Value::Int(3)
+        );
+        assert_eq!(env.eval_read_only(&c, "(get-at-2)").unwrap(), Value::Int(2));
+        match env.eval_read_only(&c, "(get-at-1)").unwrap_err() {
+            Error::Runtime(x, _) => assert_eq!(
+                x,
+                RuntimeErrorType::PrunedBlockHeaderHash(BlockHeaderHash([1 as u8; 32]))
+            ),
+            e => panic!("Unexpected error: {}", e),
+        }
+    }
+    marf_kv.test_commit();
+}
+
 #[test]
 fn test_at_block_missing_defines() {
     fn initialize_1(owned_env: &mut OwnedEnvironment) {
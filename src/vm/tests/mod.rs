@@ -34,6 +34,7 @@ use core::{FIRST_BURNCHAIN_CONSENSUS_HASH, FIRST_STACKS_BLOCK_HASH};
 
 mod assets;
 mod contracts;
+mod conversions;
 pub mod costs;
 mod datamaps;
 mod defines;
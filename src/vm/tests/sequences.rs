@@ -429,6 +429,159 @@ fn test_simple_list_assert_max_len() {
     }
 }
 
+#[test]
+fn test_element_at() {
+    let tests = [
+        "(element-at (list 1 2 3) u0)",
+        "(element-at (list 1 2 3) u2)",
+        "(element-at (list 1 2 3) u3)",
+        "(element-at 0x313233 u1)",
+        "(element-at 0x313233 u3)",
+        "(element-at \"abc\" u2)",
+        "(element-at u\"ab\\u{1F926}\" u2)",
+        "(element-at u\"ab\\u{1F926}\" u3)",
+        "(element-at (list) u0)",
+    ];
+
+    let expected = [
+        Value::some(Value::Int(1)).unwrap(),
+        Value::some(Value::Int(3)).unwrap(),
+        Value::none(),
+        Value::some(Value::buff_from_byte(50)).unwrap(),
+        Value::none(),
+        Value::some(Value::string_ascii_from_bytes("c".into()).unwrap()).unwrap(),
+        Value::some(Value::string_utf8_from_bytes("\u{1F926}".into()).unwrap()).unwrap(),
+        Value::none(),
+        Value::none(),
+    ];
+
+    for (test, expected) in tests.iter().zip(expected.iter()) {
+        assert_eq!(expected.clone(), execute(test).unwrap().unwrap());
+    }
+
+    assert_eq!(
+        execute("(element-at (list 1 2 3) 1)").unwrap_err(),
+        CheckErrors::TypeValueError(UIntType, Value::Int(1)).into()
+    );
+
+    assert_eq!(
+        execute("(element-at 1 u0)").unwrap_err(),
+        CheckErrors::ExpectedSequence(IntType).into()
+    );
+}
+
+#[test]
+fn test_index_of() {
+    let tests = [
+        "(index-of (list 1 2 3 2) 2)",
+        "(index-of (list 1 2 3) 4)",
+        "(index-of (list (some 1) none) none)",
+        "(index-of 0x313233 0x33)",
+        "(index-of 0x313233 0x3132)",
+        "(index-of 0x313233 0x)",
+        "(index-of \"blockstack\" \"k\")",
+        "(index-of \"blockstack\" \"z\")",
+        "(index-of u\"ab\\u{1F926}c\" u\"\\u{1F926}\")",
+        "(index-of u\"ab\\u{1F926}c\" u\"c\")",
+    ];
+
+    let expected = [
+        Value::some(Value::UInt(1)).unwrap(),
+        Value::none(),
+        Value::some(Value::UInt(1)).unwrap(),
+        Value::some(Value::UInt(2)).unwrap(),
+        Value::none(),
+        Value::none(),
+        Value::some(Value::UInt(4)).unwrap(),
+        Value::none(),
+        Value::some(Value::UInt(2)).unwrap(),
+        Value::some(Value::UInt(3)).unwrap(),
+    ];
+
+    for (test, expected) in tests.iter().zip(expected.iter()) {
+        assert_eq!(expected.clone(), execute(test).unwrap().unwrap());
+    }
+
+    assert_eq!(
+        execute("(index-of 0x313233 1)").unwrap_err(),
+        CheckErrors::TypeValueError(TypeSignature::min_buffer(), Value::Int(1)).into()
+    );
+
+    assert_eq!(
+        execute("(index-of \"abc\" u\"a\")").unwrap_err(),
+        CheckErrors::TypeValueError(
+            TypeSignature::min_string_ascii(),
+            Value::string_utf8_from_bytes("a".into()).unwrap()
+        )
+        .into()
+    );
+}
+
+#[test]
+fn test_slice() {
+    let tests = [
+        "(slice (list 1 2 3 4 5) u1 u3)",
+        "(slice (list 1 2 3 4 5) u0 u5)",
+        "(slice (list 1 2 3 4 5) u2 u2)",
+        "(slice (list 1 2 3 4 5) u3 u2)",
+        "(slice (list 1 2 3 4 5) u4 u6)",
+        "(slice 0x31323334 u1 u3)",
+        "(slice \"blockstack\" u5 u10)",
+        "(slice u\"ab\\u{1F926}c\" u2 u4)",
+    ];
+
+    let expected = [
+        Value::some(Value::list_from(vec![Value::Int(2), Value::Int(3)]).unwrap()).unwrap(),
+        Value::some(
+            Value::list_from(vec![
+                Value::Int(1),
+                Value::Int(2),
+                Value::Int(3),
+                Value::Int(4),
+                Value::Int(5),
+            ])
+            .unwrap(),
+        )
+        .unwrap(),
+        Value::some(Value::list_from(vec![]).unwrap()).unwrap(),
+        Value::none(),
+        Value::none(),
+        Value::some(Value::buff_from(vec![50, 51]).unwrap()).unwrap(),
+        Value::some(Value::string_ascii_from_bytes("stack".into()).unwrap()).unwrap(),
+        Value::some(Value::string_utf8_from_bytes("\u{1F926}c".into()).unwrap()).unwrap(),
+    ];
+
+    for (test, expected) in tests.iter().zip(expected.iter()) {
+        assert_eq!(expected.clone(), execute(test).unwrap().unwrap());
+    }
+
+    // a list slice has the type of the list it would be if written out
+    assert_eq!(
+        execute("(is-eq (unwrap-panic (slice (list 1 2 3) u0 u2)) (list 1 2))")
+            .unwrap()
+            .unwrap(),
+        Value::Bool(true)
+    );
+    assert_eq!(
+        TypeSignature::type_of(
+            &execute("(unwrap-panic (slice (list 1 2 3) u0 u2))")
+                .unwrap()
+                .unwrap()
+        ),
+        TypeSignature::type_of(&execute("(list 1 2)").unwrap().unwrap())
+    );
+
+    assert_eq!(
+        execute("(slice 0x313233 u0)").unwrap_err(),
+        CheckErrors::IncorrectArgumentCount(3, 2).into()
+    );
+
+    assert_eq!(
+        execute("(slice 0x313233 0 u1)").unwrap_err(),
+        CheckErrors::TypeValueError(UIntType, Value::Int(0)).into()
+    );
+}
+
 #[test]
 fn test_simple_map_buffer() {
     let test1 = "(define-private (incr (x (buff 1))) 0x31)
@@ -521,12 +674,12 @@ fn test_simple_folds_string() {
     let tests =
         ["(define-private (get-len (x (string-ascii 1)) (acc int)) (+ acc 1))
          (fold get-len \"blockstack\" 0)",
-        "(define-private (slice (x (string-ascii 1)) (acc (tuple (limit uint) (cursor uint) (data (string-ascii 10)))))
+        "(define-private (take-prefix (x (string-ascii 1)) (acc (tuple (limit uint) (cursor uint) (data (string-ascii 10)))))
             (if (< (get cursor acc) (get limit acc))
                 (let ((data (default-to (get data acc) (as-max-len? (concat (get data acc) x) u10))))
                     (tuple (limit (get limit acc)) (cursor (+ u1 (get cursor acc))) (data data))) 
                 acc))
-        (get data (fold slice \"0123456789\" (tuple (limit u5) (cursor u0) (data \"\"))))"];
+        (get data (fold take-prefix \"0123456789\" (tuple (limit u5) (cursor u0) (data \"\"))))"];
 
     let expected = [
         Value::Int(10),
@@ -306,6 +306,93 @@ impl SequenceData {
         }?;
         Ok(())
     }
+
+    /// Returns the element at `index`, or None if the index is out of bounds.
+    /// Buffers and strings yield a sequence of length 1.
+    pub fn element_at(self, index: usize) -> Option<Value> {
+        if index >= self.len() {
+            return None;
+        }
+        let result = match self {
+            SequenceData::Buffer(data) => BuffData::to_value(&data.data[index]),
+            SequenceData::List(mut data) => data.data.swap_remove(index),
+            SequenceData::String(CharType::ASCII(data)) => ASCIIData::to_value(&data.data[index]),
+            SequenceData::String(CharType::UTF8(data)) => UTF8Data::to_value(&data.data[index]),
+        };
+        Some(result)
+    }
+
+    /// Returns the index of the first element equal to `to_find`, or None if there is no such
+    /// element.  For buffers and strings, `to_find` must be a sequence of the same kind; only a
+    /// sequence of length 1 can ever be found.
+    pub fn contains(&self, to_find: Value) -> Result<Option<usize>> {
+        macro_rules! find_unit {
+            ($data:expr, $to_find_data:expr) => {
+                if $to_find_data.data.len() != 1 {
+                    Ok(None)
+                } else {
+                    Ok($data
+                        .data
+                        .iter()
+                        .position(|item| *item == $to_find_data.data[0]))
+                }
+            };
+        }
+
+        match (self, to_find) {
+            (SequenceData::List(data), to_find) => {
+                Ok(data.data.iter().position(|item| *item == to_find))
+            }
+            (SequenceData::Buffer(data), Value::Sequence(SequenceData::Buffer(to_find_data))) => {
+                find_unit!(data, to_find_data)
+            }
+            (
+                SequenceData::String(CharType::ASCII(data)),
+                Value::Sequence(SequenceData::String(CharType::ASCII(to_find_data))),
+            ) => find_unit!(data, to_find_data),
+            (
+                SequenceData::String(CharType::UTF8(data)),
+                Value::Sequence(SequenceData::String(CharType::UTF8(to_find_data))),
+            ) => find_unit!(data, to_find_data),
+            (SequenceData::Buffer(_), to_find) => {
+                Err(CheckErrors::TypeValueError(TypeSignature::min_buffer(), to_find).into())
+            }
+            (SequenceData::String(CharType::ASCII(_)), to_find) => {
+                Err(CheckErrors::TypeValueError(TypeSignature::min_string_ascii(), to_find).into())
+            }
+            (SequenceData::String(CharType::UTF8(_)), to_find) => {
+                Err(CheckErrors::TypeValueError(TypeSignature::min_string_utf8(), to_find).into())
+            }
+        }
+    }
+
+    /// Returns the elements in `[left, right)`, or None if `right < left` or `right` is past the
+    /// end of the sequence.  A list slice gets the type signature of its own elements, like any
+    /// other list.
+    pub fn slice(self, left: usize, right: usize) -> Result<Option<Value>> {
+        if right < left || right > self.len() {
+            return Ok(None);
+        }
+        let result = match self {
+            SequenceData::Buffer(data) => SequenceData::Buffer(BuffData {
+                data: data.data[left..right].to_vec(),
+            }),
+            SequenceData::List(data) => {
+                return Value::list_from(data.data[left..right].to_vec()).map(Some);
+            }
+            SequenceData::String(CharType::ASCII(data)) => {
+                SequenceData::String(CharType::ASCII(ASCIIData {
+                    data: data.data[left..right].to_vec(),
+                }))
+            }
+            SequenceData::String(CharType::UTF8(data)) => {
+                SequenceData::String(CharType::UTF8(UTF8Data {
+                    data: data.data[left..right].to_vec(),
+                }))
+            }
+        };
+        Ok(Some(Value::Sequence(result)))
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -36,7 +36,7 @@ use util::hash::{hex_bytes, to_hex};
 use util::retry::BoundReader;
 
 use std::io::{Read, Write};
-use std::{error, fmt, str};
+use std::{cmp, error, fmt, str};
 
 /// Errors that may occur in serialization or deserialization
 /// If deserialization failed because the described type is a bad type and
@@ -650,12 +650,107 @@ impl Value {
     }
 }
 
-impl ClaritySerializable for Value {
-    fn serialize(&self) -> String {
+impl Value {
+    /// Serialize this value into its consensus byte encoding.
+    pub fn serialize_to_vec(&self) -> Vec<u8> {
         let mut byte_serialization = Vec::new();
         self.serialize_write(&mut byte_serialization)
             .expect("IOError filling byte buffer.");
-        to_hex(byte_serialization.as_slice())
+        byte_serialization
+    }
+}
+
+impl TypeSignature {
+    /// The largest number of bytes that the consensus serialization of any value of this type
+    ///   can occupy. Fails with `ValueTooLarge` if that bound would not fit in a Clarity buffer,
+    ///   and with `CouldNotDetermineSerializationType` for types that have no serialization.
+    pub fn max_serialized_size(&self) -> Result<u32, CheckErrors> {
+        let size = self
+            .inner_max_serialized_size()?
+            .ok_or(CheckErrors::ValueTooLarge)?;
+        if size > MAX_VALUE_SIZE {
+            Err(CheckErrors::ValueTooLarge)
+        } else {
+            Ok(size)
+        }
+    }
+
+    fn inner_max_serialized_size(&self) -> Result<Option<u32>, CheckErrors> {
+        // every serialized value begins with a 1-byte type prefix
+        let type_prefix_size = 1u32;
+        // sequences and tuples then write a 4-byte length
+        let length_prefix_size = 4u32;
+
+        let size = match self {
+            // NoType's only appear as the unknown half of a value like `none` or `(ok 1)`,
+            //   which is never serialized, so a lone prefix byte is a safe bound.
+            TypeSignature::NoType => Some(type_prefix_size),
+            TypeSignature::IntType | TypeSignature::UIntType => Some(type_prefix_size + 16),
+            TypeSignature::BoolType => Some(type_prefix_size),
+            // a contract principal is the larger of the two: version, hash160, and then a
+            //   length-prefixed contract name.
+            TypeSignature::PrincipalType => {
+                Some(type_prefix_size + 1 + 20 + 1 + u32::from(MAX_STRING_LEN))
+            }
+            TypeSignature::SequenceType(SequenceSubtype::BufferType(len))
+            | TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(len))) => {
+                (type_prefix_size + length_prefix_size).checked_add(u32::from(len))
+            }
+            TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::UTF8(len))) => {
+                // each UTF-8 code point is at most 4 bytes
+                u32::from(len)
+                    .checked_mul(4)
+                    .and_then(|len| len.checked_add(type_prefix_size + length_prefix_size))
+            }
+            TypeSignature::SequenceType(SequenceSubtype::ListType(list_type)) => {
+                match list_type.get_list_item_type().inner_max_serialized_size()? {
+                    Some(item_size) => item_size
+                        .checked_mul(list_type.get_max_len())
+                        .and_then(|len| len.checked_add(type_prefix_size + length_prefix_size)),
+                    None => None,
+                }
+            }
+            TypeSignature::TupleType(tuple_type) => {
+                let mut total_size = Some(type_prefix_size + length_prefix_size);
+                for (name, field_type) in tuple_type.get_type_map().iter() {
+                    // names are written with a 1-byte length prefix
+                    let field_size = match field_type.inner_max_serialized_size()? {
+                        Some(field_size) => field_size.checked_add(1 + name.len() as u32),
+                        None => None,
+                    };
+                    total_size = match (total_size, field_size) {
+                        (Some(total_size), Some(field_size)) => total_size.checked_add(field_size),
+                        _ => None,
+                    };
+                }
+                total_size
+            }
+            TypeSignature::OptionalType(some_type) => some_type
+                .inner_max_serialized_size()?
+                .and_then(|size| size.checked_add(type_prefix_size)),
+            TypeSignature::ResponseType(response_types) => {
+                let (ok_type, err_type) = response_types.as_ref();
+                match (
+                    ok_type.inner_max_serialized_size()?,
+                    err_type.inner_max_serialized_size()?,
+                ) {
+                    (Some(ok_size), Some(err_size)) => {
+                        cmp::max(ok_size, err_size).checked_add(type_prefix_size)
+                    }
+                    _ => None,
+                }
+            }
+            TypeSignature::TraitReferenceType(_) => {
+                return Err(CheckErrors::CouldNotDetermineSerializationType)
+            }
+        };
+        Ok(size)
+    }
+}
+
+impl ClaritySerializable for Value {
+    fn serialize(&self) -> String {
+        to_hex(self.serialize_to_vec().as_slice())
     }
 }
 
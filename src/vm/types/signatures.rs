@@ -640,6 +640,20 @@ impl TypeSignature {
         )))
     }
 
+    pub fn max_string_ascii() -> TypeSignature {
+        SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(
+            BufferLength::try_from(MAX_VALUE_SIZE)
+                .expect("FAIL: Max Clarity Value Size is no longer realizable in ASCII Type"),
+        )))
+    }
+
+    pub fn max_string_utf8() -> TypeSignature {
+        SequenceType(SequenceSubtype::StringType(StringSubtype::UTF8(
+            StringUTF8Length::try_from(MAX_VALUE_SIZE / 4)
+                .expect("FAIL: Max Clarity Value Size is no longer realizable in UTF8 Type"),
+        )))
+    }
+
     /// If one of the types is a NoType, return Ok(the other type), otherwise return least_supertype(a, b)
     pub fn factor_out_no_type(a: &TypeSignature, b: &TypeSignature) -> Result<TypeSignature> {
         if a.is_no_type() {
@@ -8,6 +8,7 @@ use rand::RngCore;
 use stacks::burnchains::bitcoin::indexer::FIRST_BLOCK_MAINNET;
 use stacks::burnchains::bitcoin::BitcoinNetworkType;
 use stacks::burnchains::{MagicBytes, BLOCKSTACK_MAGIC_MAINNET};
+use stacks::core::mempool::MemPoolSettings;
 use stacks::net::connection::ConnectionOptions;
 use stacks::net::{Neighbor, NeighborKey, PeerAddress};
 use stacks::util::hash::{hex_bytes, to_hex};
@@ -19,6 +20,7 @@ use super::neon_node::TESTNET_PEER_VERSION;
 use super::node::TESTNET_CHAIN_ID;
 
 const MINIMUM_DUST_FEE: u64 = 5500;
+const DEFAULT_MAX_PENDING_EVENTS: u64 = 10_000;
 
 #[derive(Clone, Deserialize, Default)]
 pub struct ConfigFile {
@@ -28,6 +30,7 @@ pub struct ConfigFile {
     pub events_observer: Option<Vec<EventObserverConfigFile>>,
     pub connection_options: Option<ConnectionOptionsFile>,
     pub block_limit: Option<BlockLimitFile>,
+    pub mempool: Option<MemPoolSettingsFile>,
 }
 
 impl ConfigFile {
@@ -276,6 +279,7 @@ pub struct Config {
     pub events_observers: Vec<EventObserverConfig>,
     pub connection_options: ConnectionOptions,
     pub block_limit: ExecutionCost,
+    pub mempool: MemPoolSettings,
 }
 
 lazy_static! {
@@ -358,6 +362,13 @@ impl Config {
                     pox_sync_sample_secs: node
                         .pox_sync_sample_secs
                         .unwrap_or(default_node_config.pox_sync_sample_secs),
+                    prune_depth: node.prune_depth,
+                    contract_event_index: node
+                        .contract_event_index
+                        .unwrap_or(default_node_config.contract_event_index),
+                    transaction_index: node
+                        .transaction_index
+                        .unwrap_or(default_node_config.transaction_index),
                 };
                 node_config.set_bootstrap_node(node.bootstrap_node);
                 node_config
@@ -465,9 +476,21 @@ impl Config {
 
                     let endpoint = format!("{}", observer.endpoint);
 
+                    let backlog_policy = match observer.backlog_policy {
+                        Some(policy) => EventBacklogPolicy::from_string(&policy).expect(&format!(
+                            "Invalid event observer backlog policy: {}",
+                            policy
+                        )),
+                        None => EventBacklogPolicy::Block,
+                    };
+
                     observers.push(EventObserverConfig {
                         endpoint,
                         events_keys,
+                        max_pending_events: observer
+                            .max_pending_events
+                            .unwrap_or(DEFAULT_MAX_PENDING_EVENTS),
+                        backlog_policy,
                     });
                 }
                 observers
@@ -480,6 +503,7 @@ impl Config {
             Ok(val) => events_observers.push(EventObserverConfig {
                 endpoint: val,
                 events_keys: vec![EventKeyType::AnyEvent],
+                ..EventObserverConfig::default()
             }),
             _ => (),
         };
@@ -512,8 +536,27 @@ impl Config {
                 opts.read_only_call_limit_runtime.map(|x| {
                     read_only_call_limit.runtime = x;
                 });
+                let mut simulation_call_limit = HELIUM_DEFAULT_CONNECTION_OPTIONS
+                    .simulation_call_limit
+                    .clone();
+                opts.simulation_call_limit_write_length.map(|x| {
+                    simulation_call_limit.write_length = x;
+                });
+                opts.simulation_call_limit_write_count.map(|x| {
+                    simulation_call_limit.write_count = x;
+                });
+                opts.simulation_call_limit_read_length.map(|x| {
+                    simulation_call_limit.read_length = x;
+                });
+                opts.simulation_call_limit_read_count.map(|x| {
+                    simulation_call_limit.read_count = x;
+                });
+                opts.simulation_call_limit_runtime.map(|x| {
+                    simulation_call_limit.runtime = x;
+                });
                 ConnectionOptions {
                     read_only_call_limit,
+                    simulation_call_limit,
                     inbox_maxlen: opts
                         .inbox_maxlen
                         .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.inbox_maxlen.clone()),
@@ -601,7 +644,20 @@ impl Config {
                     inv_sync_interval: opts.inv_sync_interval.unwrap_or_else(|| {
                         HELIUM_DEFAULT_CONNECTION_OPTIONS.inv_sync_interval.clone()
                     }),
+                    mempool_sync_interval: opts.mempool_sync_interval.unwrap_or_else(|| {
+                        HELIUM_DEFAULT_CONNECTION_OPTIONS
+                            .mempool_sync_interval
+                            .clone()
+                    }),
+                    max_mempool_sync_request_rate: opts
+                        .max_mempool_sync_request_rate
+                        .unwrap_or_else(|| {
+                            HELIUM_DEFAULT_CONNECTION_OPTIONS
+                                .max_mempool_sync_request_rate
+                                .clone()
+                        }),
                     public_ip_address: ip_addr,
+                    admin_rpc_token: opts.admin_rpc_token,
                     ..ConnectionOptions::default()
                 }
             }
@@ -627,6 +683,18 @@ impl Config {
             None => HELIUM_BLOCK_LIMIT.clone(),
         };
 
+        let default_mempool = MemPoolSettings::default();
+        let mempool = match config_file.mempool {
+            Some(opts) => MemPoolSettings {
+                min_rbf_fee_rate_increment: opts
+                    .min_rbf_fee_rate_increment
+                    .unwrap_or(default_mempool.min_rbf_fee_rate_increment),
+                max_tx_count: opts.max_tx_count.unwrap_or(default_mempool.max_tx_count),
+                max_tx_bytes: opts.max_tx_bytes.unwrap_or(default_mempool.max_tx_bytes),
+            },
+            None => default_mempool,
+        };
+
         Config {
             node,
             burnchain,
@@ -634,6 +702,7 @@ impl Config {
             events_observers,
             connection_options,
             block_limit,
+            mempool,
         }
     }
 
@@ -652,6 +721,10 @@ impl Config {
         )
     }
 
+    pub fn get_event_observers_path(&self) -> String {
+        format!("{}/event_observers", self.node.working_dir)
+    }
+
     pub fn get_chainstate_path(&self) -> String {
         format!("{}/chainstate/", self.node.working_dir)
     }
@@ -702,6 +775,7 @@ impl std::default::Default for Config {
             events_observers: vec![],
             connection_options,
             block_limit,
+            mempool: MemPoolSettings::default(),
         }
     }
 }
@@ -818,6 +892,18 @@ pub struct NodeConfig {
     pub wait_time_for_microblocks: u64,
     pub prometheus_bind: Option<String>,
     pub pox_sync_sample_secs: u64,
+    /// If set, drop the Clarity state of blocks more than this many blocks below the chain
+    /// tip when the node starts.  State queries at those blocks will fail, as will proofs that
+    /// pass through them.
+    pub prune_depth: Option<u32>,
+    /// If set, index the events contracts emit as blocks are processed, so they can be paged
+    /// through with GET /v2/contracts/events.  Only blocks processed while this is set are
+    /// indexed.
+    pub contract_event_index: bool,
+    /// If set, index the transactions confirmed by blocks as they are processed, along with
+    /// their results and events, so they can be looked up with GET /v2/transactions.  Only
+    /// blocks processed while this is set are indexed.
+    pub transaction_index: bool,
 }
 
 impl NodeConfig {
@@ -852,6 +938,9 @@ impl NodeConfig {
             wait_time_for_microblocks: 5000,
             prometheus_bind: None,
             pox_sync_sample_secs: 30,
+            prune_depth: None,
+            contract_event_index: false,
+            transaction_index: false,
         }
     }
 
@@ -923,10 +1012,18 @@ pub struct ConnectionOptionsFile {
     pub read_only_call_limit_write_count: Option<u64>,
     pub read_only_call_limit_read_count: Option<u64>,
     pub read_only_call_limit_runtime: Option<u64>,
+    pub simulation_call_limit_write_length: Option<u64>,
+    pub simulation_call_limit_read_length: Option<u64>,
+    pub simulation_call_limit_write_count: Option<u64>,
+    pub simulation_call_limit_read_count: Option<u64>,
+    pub simulation_call_limit_runtime: Option<u64>,
     pub maximum_call_argument_size: Option<u32>,
     pub download_interval: Option<u64>,
     pub inv_sync_interval: Option<u64>,
+    pub mempool_sync_interval: Option<u64>,
+    pub max_mempool_sync_request_rate: Option<u64>,
     pub public_ip_address: Option<String>,
+    pub admin_rpc_token: Option<String>,
 }
 
 #[derive(Clone, Default, Deserialize)]
@@ -938,6 +1035,13 @@ pub struct BlockLimitFile {
     pub runtime: Option<u64>,
 }
 
+#[derive(Clone, Default, Deserialize)]
+pub struct MemPoolSettingsFile {
+    pub min_rbf_fee_rate_increment: Option<u64>,
+    pub max_tx_count: Option<u64>,
+    pub max_tx_bytes: Option<u64>,
+}
+
 #[derive(Clone, Deserialize, Default)]
 pub struct NodeConfigFile {
     pub name: Option<String>,
@@ -954,18 +1058,56 @@ pub struct NodeConfigFile {
     pub wait_time_for_microblocks: Option<u64>,
     pub prometheus_bind: Option<String>,
     pub pox_sync_sample_secs: Option<u64>,
+    pub prune_depth: Option<u32>,
+    pub contract_event_index: Option<bool>,
+    pub transaction_index: Option<bool>,
 }
 
 #[derive(Clone, Deserialize, Default)]
 pub struct EventObserverConfigFile {
     pub endpoint: String,
     pub events_keys: Vec<String>,
+    pub max_pending_events: Option<u64>,
+    pub backlog_policy: Option<String>,
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct EventObserverConfig {
     pub endpoint: String,
     pub events_keys: Vec<EventKeyType>,
+    /// How many undelivered payloads may be queued before `backlog_policy` applies
+    pub max_pending_events: u64,
+    pub backlog_policy: EventBacklogPolicy,
+}
+
+impl Default for EventObserverConfig {
+    fn default() -> EventObserverConfig {
+        EventObserverConfig {
+            endpoint: "".to_string(),
+            events_keys: vec![],
+            max_pending_events: DEFAULT_MAX_PENDING_EVENTS,
+            backlog_policy: EventBacklogPolicy::Block,
+        }
+    }
+}
+
+/// What to do when an event observer has `max_pending_events` undelivered payloads
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventBacklogPolicy {
+    /// Stall the node until the observer catches up
+    Block,
+    /// Discard the oldest undelivered payloads
+    Drop,
+}
+
+impl EventBacklogPolicy {
+    fn from_string(raw_policy: &str) -> Option<EventBacklogPolicy> {
+        match raw_policy {
+            "block" => Some(EventBacklogPolicy::Block),
+            "drop" => Some(EventBacklogPolicy::Drop),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -974,6 +1116,10 @@ pub enum EventKeyType {
     AssetEvent(AssetIdentifier),
     STXEvent,
     MemPoolTransactions,
+    DroppedMemPoolTransactions,
+    Microblocks,
+    BurnchainBlocks,
+    MinedBlocks,
     AnyEvent,
 }
 
@@ -991,6 +1137,22 @@ impl EventKeyType {
             return Some(EventKeyType::MemPoolTransactions);
         }
 
+        if raw_key == "memtx_drop" {
+            return Some(EventKeyType::DroppedMemPoolTransactions);
+        }
+
+        if raw_key == "microblocks" {
+            return Some(EventKeyType::Microblocks);
+        }
+
+        if raw_key == "burn_blocks" {
+            return Some(EventKeyType::BurnchainBlocks);
+        }
+
+        if raw_key == "mined_blocks" {
+            return Some(EventKeyType::MinedBlocks);
+        }
+
         let comps: Vec<_> = raw_key.split("::").collect();
         if comps.len() == 1 {
             let split: Vec<_> = comps[0].split(".").collect();
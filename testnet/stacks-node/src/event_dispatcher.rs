@@ -1,15 +1,13 @@
+use stacks::chainstate::burn::BlockSnapshot;
 use stacks::chainstate::coordinator::BlockEventDispatcher;
+use stacks::chainstate::stacks::db::unconfirmed::UnconfirmedMicroblockReceipts;
 use stacks::chainstate::stacks::db::StacksHeaderInfo;
 use stacks::chainstate::stacks::events::StacksTransactionReceipt;
+use stacks::chainstate::stacks::miner::BlockCostUtilization;
 use stacks::chainstate::stacks::StacksBlock;
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
-use std::thread::sleep;
-use std::time::Duration;
-
-use async_h1::client;
-use async_std::net::TcpStream;
-use http_types::{Method, Request, Url};
+use std::sync::Arc;
 
 use serde_json::json;
 
@@ -17,19 +15,23 @@ use stacks::burnchains::Txid;
 use stacks::chainstate::stacks::events::{
     FTEventType, NFTEventType, STXEventType, StacksTransactionEvent,
 };
+use stacks::chainstate::stacks::StacksAddress;
 use stacks::chainstate::stacks::StacksBlockId;
 use stacks::chainstate::stacks::StacksTransaction;
+use stacks::core::mempool::MemPoolDropReason;
 use stacks::net::StacksMessageCodec;
 use stacks::util::hash::bytes_to_hex;
 use stacks::vm::analysis::contract_interface_builder::build_contract_interface;
+use stacks::vm::costs::ExecutionCostDimension;
 use stacks::vm::types::{AssetIdentifier, QualifiedContractIdentifier, Value};
 
 use super::config::{EventKeyType, EventObserverConfig};
+use super::event_outbox::EventOutbox;
 use super::node::ChainTip;
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 struct EventObserver {
-    endpoint: String,
+    outbox: Arc<EventOutbox>,
 }
 
 const STATUS_RESP_TRUE: &str = "success";
@@ -37,69 +39,15 @@ const STATUS_RESP_NOT_COMMITTED: &str = "abort_by_response";
 const STATUS_RESP_POST_CONDITION: &str = "abort_by_post_condition";
 
 pub const PATH_MEMPOOL_TX_SUBMIT: &str = "new_mempool_tx";
+pub const PATH_MEMPOOL_TX_DROP: &str = "drop_mempool_tx";
+pub const PATH_MICROBLOCK_SUBMIT: &str = "new_microblocks";
+pub const PATH_BURN_BLOCK_SUBMIT: &str = "new_burn_block";
+pub const PATH_MINED_BLOCK: &str = "mined_block";
 pub const PATH_BLOCK_PROCESSED: &str = "new_block";
 
 impl EventObserver {
     fn send_payload(&self, payload: &serde_json::Value, path: &str) {
-        let body = match serde_json::to_vec(&payload) {
-            Ok(body) => body,
-            Err(err) => {
-                error!("Event dispatcher: serialization failed  - {:?}", err);
-                return;
-            }
-        };
-
-        let url = {
-            let joined_components = match path.starts_with("/") {
-                true => format!("{}{}", &self.endpoint, path),
-                false => format!("{}/{}", &self.endpoint, path),
-            };
-            let url = format!("http://{}", joined_components);
-            Url::parse(&url).expect(&format!(
-                "Event dispatcher: unable to parse {} as a URL",
-                url
-            ))
-        };
-
-        let backoff = Duration::from_millis((1.0 * 1_000.0) as u64);
-
-        loop {
-            let body = body.clone();
-            let mut req = Request::new(Method::Post, url.clone());
-            req.append_header("Content-Type", "application/json")
-                .expect("Unable to set header");
-            req.set_body(body);
-
-            let response = async_std::task::block_on(async {
-                let stream = match TcpStream::connect(self.endpoint.clone()).await {
-                    Ok(stream) => stream,
-                    Err(err) => {
-                        println!("Event dispatcher: connection failed  - {:?}", err);
-                        return None;
-                    }
-                };
-
-                match client::connect(stream, req).await {
-                    Ok(response) => Some(response),
-                    Err(err) => {
-                        println!("Event dispatcher: rpc invokation failed  - {:?}", err);
-                        return None;
-                    }
-                }
-            });
-
-            if let Some(response) = response {
-                if response.status().is_success() {
-                    break;
-                } else {
-                    error!(
-                        "Event dispatcher: POST {} failed with error {:?}",
-                        self.endpoint, response
-                    );
-                }
-            }
-            sleep(backoff);
-        }
+        self.outbox.enqueue(path, payload);
     }
 
     fn make_new_mempool_txs_payload(transactions: Vec<StacksTransaction>) -> serde_json::Value {
@@ -113,6 +61,115 @@ impl EventObserver {
         serde_json::Value::Array(raw_txs)
     }
 
+    fn make_dropped_mempool_txs_payload(
+        txids: Vec<Txid>,
+        reason: MemPoolDropReason,
+    ) -> serde_json::Value {
+        let dropped_txids: Vec<_> = txids
+            .into_iter()
+            .map(|txid| serde_json::Value::String(format!("0x{}", &txid)))
+            .collect();
+
+        json!({
+            "dropped_txids": serde_json::Value::Array(dropped_txids),
+            "reason": reason.to_string(),
+        })
+    }
+
+    fn make_new_microblocks_payload(
+        microblocks: &[UnconfirmedMicroblockReceipts],
+    ) -> serde_json::Value {
+        let serialized_microblocks: Vec<serde_json::Value> = microblocks
+            .iter()
+            .map(|microblock| {
+                let mut serialized_txs = vec![];
+                let mut serialized_events = vec![];
+                for (tx_index, receipt) in microblock.receipts.iter().enumerate() {
+                    let txid = receipt.transaction.txid();
+                    for event in receipt.events.iter() {
+                        serialized_events
+                            .push(event.json_serialize(&txid, !receipt.post_condition_aborted));
+                    }
+                    serialized_txs.push(EventObserver::make_new_block_txs_payload(
+                        receipt,
+                        tx_index as u32,
+                    ));
+                }
+                json!({
+                    "microblock_hash": format!("0x{}", microblock.header.block_hash()),
+                    "microblock_sequence": microblock.header.sequence,
+                    "microblock_parent_hash": format!("0x{}", microblock.header.prev_block),
+                    "events": serialized_events,
+                    "transactions": serialized_txs,
+                })
+            })
+            .collect();
+
+        // all microblocks in a batch come from the same unconfirmed state
+        let parent_index_block_hash = microblocks
+            .first()
+            .map(|microblock| format!("0x{}", microblock.anchored_block_id))
+            .unwrap_or_default();
+
+        json!({
+            "parent_index_block_hash": parent_index_block_hash,
+            "microblocks": serialized_microblocks,
+        })
+    }
+
+    fn make_new_burn_block_payload(
+        snapshot: &BlockSnapshot,
+        rewards: &[(StacksAddress, u64)],
+        burns: u64,
+    ) -> serde_json::Value {
+        let reward_recipients: Vec<serde_json::Value> = rewards
+            .iter()
+            .map(|(recipient, amount)| {
+                json!({
+                    "recipient": recipient.to_string(),
+                    "amt": amount,
+                })
+            })
+            .collect();
+
+        json!({
+            "burn_block_hash": format!("0x{}", snapshot.burn_header_hash),
+            "burn_block_height": snapshot.block_height,
+            "burn_block_time": snapshot.burn_header_timestamp,
+            "parent_burn_block_hash": format!("0x{}", snapshot.parent_burn_header_hash),
+            "consensus_hash": format!("0x{}", snapshot.consensus_hash),
+            "sortition": snapshot.sortition,
+            "winning_block_txid": format!("0x{}", snapshot.winning_block_txid),
+            "winning_stacks_block_hash": format!("0x{}", snapshot.winning_stacks_block_hash),
+            "reward_recipients": reward_recipients,
+            "burn_amount": burns,
+        })
+    }
+
+    fn make_mined_block_payload(
+        block: &StacksBlock,
+        utilization: &BlockCostUtilization,
+    ) -> serde_json::Value {
+        let mut percent_used = serde_json::Map::new();
+        for dimension in ExecutionCostDimension::ALL.iter() {
+            percent_used.insert(
+                dimension.name().to_string(),
+                json!(utilization.percent(*dimension)),
+            );
+        }
+        let saturated: Vec<&str> = utilization.saturated.iter().map(|d| d.name()).collect();
+
+        json!({
+            "block_hash": format!("0x{}", block.block_hash()),
+            "tx_count": block.txs.len(),
+            "execution_cost": utilization.consumed,
+            "execution_budget": utilization.budget,
+            "percent_used": percent_used,
+            "saturated_dimensions": saturated,
+            "skipped_tx_count": utilization.skipped_txs,
+        })
+    }
+
     fn make_new_block_txs_payload(
         receipt: &StacksTransactionReceipt,
         tx_index: u32,
@@ -167,6 +224,22 @@ impl EventObserver {
         self.send_payload(payload, PATH_MEMPOOL_TX_SUBMIT);
     }
 
+    fn send_dropped_mempool_txs(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_MEMPOOL_TX_DROP);
+    }
+
+    fn send_new_microblocks(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_MICROBLOCK_SUBMIT);
+    }
+
+    fn send_new_burn_block(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_BURN_BLOCK_SUBMIT);
+    }
+
+    fn send_mined_block(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_MINED_BLOCK);
+    }
+
     fn send(
         &self,
         filtered_events: Vec<&(bool, Txid, &StacksTransactionEvent)>,
@@ -225,6 +298,10 @@ pub struct EventDispatcher {
     contract_events_observers_lookup: HashMap<(QualifiedContractIdentifier, String), HashSet<u16>>,
     assets_observers_lookup: HashMap<AssetIdentifier, HashSet<u16>>,
     mempool_observers_lookup: HashSet<u16>,
+    mempool_drop_observers_lookup: HashSet<u16>,
+    microblock_observers_lookup: HashSet<u16>,
+    burn_block_observers_lookup: HashSet<u16>,
+    mined_block_observers_lookup: HashSet<u16>,
     stx_observers_lookup: HashSet<u16>,
     any_event_observers_lookup: HashSet<u16>,
     boot_receipts: Vec<StacksTransactionReceipt>,
@@ -250,6 +327,15 @@ impl BlockEventDispatcher for EventDispatcher {
     fn dispatch_boot_receipts(&mut self, receipts: Vec<StacksTransactionReceipt>) {
         self.process_boot_receipts(receipts)
     }
+
+    fn announce_burn_block(
+        &self,
+        snapshot: &BlockSnapshot,
+        rewards: Vec<(StacksAddress, u64)>,
+        burns: u64,
+    ) {
+        self.process_burn_block(snapshot, rewards, burns)
+    }
 }
 
 impl EventDispatcher {
@@ -261,6 +347,10 @@ impl EventDispatcher {
             stx_observers_lookup: HashSet::new(),
             any_event_observers_lookup: HashSet::new(),
             mempool_observers_lookup: HashSet::new(),
+            mempool_drop_observers_lookup: HashSet::new(),
+            microblock_observers_lookup: HashSet::new(),
+            burn_block_observers_lookup: HashSet::new(),
+            mined_block_observers_lookup: HashSet::new(),
             boot_receipts: vec![],
         }
     }
@@ -322,6 +412,13 @@ impl EventDispatcher {
                             &mut dispatch_matrix,
                         );
                     }
+                    StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(event_data)) => {
+                        self.update_dispatch_matrix_if_observer_subscribed(
+                            &event_data.asset_identifier,
+                            i,
+                            &mut dispatch_matrix,
+                        );
+                    }
                     StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(event_data)) => {
                         self.update_dispatch_matrix_if_observer_subscribed(
                             &event_data.asset_identifier,
@@ -336,6 +433,13 @@ impl EventDispatcher {
                             &mut dispatch_matrix,
                         );
                     }
+                    StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(event_data)) => {
+                        self.update_dispatch_matrix_if_observer_subscribed(
+                            &event_data.asset_identifier,
+                            i,
+                            &mut dispatch_matrix,
+                        );
+                    }
                 }
                 events.push((!receipt.post_condition_aborted, tx_hash, event));
                 for o_i in &self.any_event_observers_lookup {
@@ -383,6 +487,103 @@ impl EventDispatcher {
         }
     }
 
+    /// Only observers that subscribed with `memtx_drop` get these.  Observers registered for
+    /// `*` or `memtx` predate this payload and may not serve its path.
+    pub fn process_dropped_mempool_txs(&self, txids: Vec<Txid>, reason: MemPoolDropReason) {
+        // lazily assemble payload only if we have observers
+        let interested_observers: Vec<_> = self
+            .registered_observers
+            .iter()
+            .enumerate()
+            .filter(|(obs_id, _observer)| {
+                self.mempool_drop_observers_lookup
+                    .contains(&(*obs_id as u16))
+            })
+            .collect();
+        if interested_observers.len() < 1 {
+            return;
+        }
+
+        let payload = EventObserver::make_dropped_mempool_txs_payload(txids, reason);
+
+        for (_, observer) in interested_observers.iter() {
+            observer.send_dropped_mempool_txs(&payload);
+        }
+    }
+
+    /// Only observers that subscribed with `microblocks` get these.
+    pub fn process_new_microblocks(&self, microblocks: Vec<UnconfirmedMicroblockReceipts>) {
+        // lazily assemble payload only if we have observers
+        let interested_observers: Vec<_> = self
+            .registered_observers
+            .iter()
+            .enumerate()
+            .filter(|(obs_id, _observer)| {
+                self.microblock_observers_lookup.contains(&(*obs_id as u16))
+            })
+            .collect();
+        if interested_observers.len() < 1 {
+            return;
+        }
+
+        let payload = EventObserver::make_new_microblocks_payload(&microblocks);
+
+        for (_, observer) in interested_observers.iter() {
+            observer.send_new_microblocks(&payload);
+        }
+    }
+
+    /// Only observers that subscribed with `burn_blocks` get these.
+    pub fn process_burn_block(
+        &self,
+        snapshot: &BlockSnapshot,
+        rewards: Vec<(StacksAddress, u64)>,
+        burns: u64,
+    ) {
+        // lazily assemble payload only if we have observers
+        let interested_observers: Vec<_> = self
+            .registered_observers
+            .iter()
+            .enumerate()
+            .filter(|(obs_id, _observer)| {
+                self.burn_block_observers_lookup.contains(&(*obs_id as u16))
+            })
+            .collect();
+        if interested_observers.len() < 1 {
+            return;
+        }
+
+        let payload = EventObserver::make_new_burn_block_payload(snapshot, &rewards, burns);
+
+        for (_, observer) in interested_observers.iter() {
+            observer.send_new_burn_block(&payload);
+        }
+    }
+
+    /// Report a block this node mined and won the sortition for, along with how much of the
+    /// execution budget it used.  Only observers that subscribed with `mined_blocks` get these.
+    pub fn process_mined_block(&self, block: &StacksBlock, utilization: &BlockCostUtilization) {
+        // lazily assemble payload only if we have observers
+        let interested_observers: Vec<_> = self
+            .registered_observers
+            .iter()
+            .enumerate()
+            .filter(|(obs_id, _observer)| {
+                self.mined_block_observers_lookup
+                    .contains(&(*obs_id as u16))
+            })
+            .collect();
+        if interested_observers.len() < 1 {
+            return;
+        }
+
+        let payload = EventObserver::make_mined_block_payload(block, utilization);
+
+        for (_, observer) in interested_observers.iter() {
+            observer.send_mined_block(&payload);
+        }
+    }
+
     pub fn process_boot_receipts(&mut self, receipts: Vec<StacksTransactionReceipt>) {
         self.boot_receipts = receipts;
     }
@@ -400,11 +601,12 @@ impl EventDispatcher {
         }
     }
 
-    pub fn register_observer(&mut self, conf: &EventObserverConfig) {
-        // let event_observer = EventObserver::new(&conf.address, conf.port);
+    /// Register an observer.  Payloads destined for it are queued in a durable outbox
+    /// under `outbox_dir`, and delivered in the background.
+    pub fn register_observer(&mut self, conf: &EventObserverConfig, outbox_dir: &str) {
         info!("Registering event observer at: {}", conf.endpoint);
         let event_observer = EventObserver {
-            endpoint: conf.endpoint.clone(),
+            outbox: EventOutbox::open(outbox_dir, conf),
         };
 
         let observer_index = self.registered_observers.len() as u16;
@@ -429,6 +631,18 @@ impl EventDispatcher {
                 EventKeyType::MemPoolTransactions => {
                     self.mempool_observers_lookup.insert(observer_index);
                 }
+                EventKeyType::DroppedMemPoolTransactions => {
+                    self.mempool_drop_observers_lookup.insert(observer_index);
+                }
+                EventKeyType::Microblocks => {
+                    self.microblock_observers_lookup.insert(observer_index);
+                }
+                EventKeyType::BurnchainBlocks => {
+                    self.burn_block_observers_lookup.insert(observer_index);
+                }
+                EventKeyType::MinedBlocks => {
+                    self.mined_block_observers_lookup.insert(observer_index);
+                }
                 EventKeyType::STXEvent => {
                     self.stx_observers_lookup.insert(observer_index);
                 }
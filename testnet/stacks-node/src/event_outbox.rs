@@ -0,0 +1,580 @@
+use std::cmp;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use async_h1::client;
+use async_std::net::TcpStream;
+use http_types::{Method, Request, Url};
+
+use rusqlite::types::ToSql;
+use rusqlite::{Connection, OpenFlags, OptionalExtension, NO_PARAMS};
+
+use super::config::{EventBacklogPolicy, EventObserverConfig};
+
+/// Header carrying the outbox sequence number of each delivered payload.
+/// Sequence numbers are strictly increasing per observer and survive node
+/// restarts, so an observer can discard redeliveries and detect gaps.
+pub const EVENT_SEQUENCE_HEADER: &str = "X-Stacks-Event-Sequence";
+
+const INITIAL_DELIVERY_BACKOFF_MS: u64 = 250;
+const MAX_DELIVERY_BACKOFF_MS: u64 = 60_000;
+const BLOCKED_WARN_INTERVAL_SECS: u64 = 10;
+
+const OUTBOX_SQL: &'static [&'static str] = &[r#"
+    CREATE TABLE IF NOT EXISTS outbox(
+        seq INTEGER PRIMARY KEY AUTOINCREMENT,
+        path TEXT NOT NULL,
+        payload TEXT NOT NULL
+    );
+    "#];
+
+lazy_static! {
+    /// Outboxes opened by this process, keyed by database path.  Dispatchers that are created
+    /// more than once for the same working directory share an outbox (and its delivery thread).
+    static ref OPEN_OUTBOXES: Mutex<HashMap<String, Arc<EventOutbox>>> = Mutex::new(HashMap::new());
+}
+
+struct OutboxState {
+    conn: Connection,
+    pending: u64,
+}
+
+/// The outcome of one attempt to POST a payload.
+enum Delivery {
+    /// The observer accepted the payload (2xx).
+    Acknowledged,
+    /// The observer will never accept the payload (4xx).
+    Rejected,
+    /// The payload may be accepted later: the connection failed, or the observer erred (5xx).
+    Failed,
+}
+
+/// A durable, ordered queue of payloads destined for a single event observer.
+///
+/// Payloads are written to a SQLite database before `enqueue` returns, and are delivered by a
+/// background thread which retries with exponential backoff.  A payload is only removed once the
+/// observer has acknowledged it with a 2xx response, so delivery is at-least-once.  A 4xx response
+/// means the observer will never accept the payload, so it is logged and dropped instead of
+/// blocking the queue.
+pub struct EventOutbox {
+    endpoint: String,
+    db_path: String,
+    max_pending: u64,
+    backlog_policy: EventBacklogPolicy,
+    state: Mutex<OutboxState>,
+    changed: Condvar,
+}
+
+impl EventOutbox {
+    /// Open (or reuse) the outbox for the given observer under `outbox_dir`, and make sure its
+    /// delivery thread is running.
+    pub fn open(outbox_dir: &str, conf: &EventObserverConfig) -> Arc<EventOutbox> {
+        let db_path = format!(
+            "{}/{}.sqlite",
+            outbox_dir,
+            EventOutbox::sanitize_endpoint(&conf.endpoint)
+        );
+
+        let mut open_outboxes = OPEN_OUTBOXES.lock().unwrap();
+        if let Some(outbox) = open_outboxes.get(&db_path) {
+            return outbox.clone();
+        }
+
+        fs::create_dir_all(outbox_dir).expect(&format!(
+            "Event dispatcher: failed to create outbox directory {}",
+            outbox_dir
+        ));
+
+        let outbox = Arc::new(EventOutbox::new(&db_path, conf));
+
+        let delivery_outbox = outbox.clone();
+        thread::Builder::new()
+            .name(format!("event-observer-{}", conf.endpoint))
+            .spawn(move || delivery_outbox.run_delivery())
+            .expect("Event dispatcher: failed to spawn delivery thread");
+
+        open_outboxes.insert(db_path, outbox.clone());
+        outbox
+    }
+
+    /// Open the outbox database at `db_path`, recovering any payloads that were not delivered
+    /// before the node last stopped.  No delivery thread is started.
+    fn new(db_path: &str, conf: &EventObserverConfig) -> EventOutbox {
+        let conn = Connection::open_with_flags(
+            &db_path,
+            OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE,
+        )
+        .expect(&format!(
+            "Event dispatcher: failed to open outbox {}",
+            db_path
+        ));
+
+        for cmd in OUTBOX_SQL {
+            conn.execute_batch(cmd).expect(&format!(
+                "Event dispatcher: failed to instantiate outbox {}",
+                db_path
+            ));
+        }
+
+        let pending: i64 = conn
+            .query_row("SELECT COUNT(*) FROM outbox", NO_PARAMS, |row| row.get(0))
+            .expect("Event dispatcher: failed to count pending payloads");
+
+        if pending > 0 {
+            info!(
+                "Event dispatcher: {} undelivered payloads for {} recovered from {}",
+                pending, conf.endpoint, db_path
+            );
+        }
+
+        EventOutbox {
+            endpoint: conf.endpoint.clone(),
+            db_path: db_path.to_string(),
+            max_pending: cmp::max(conf.max_pending_events, 1),
+            backlog_policy: conf.backlog_policy,
+            state: Mutex::new(OutboxState {
+                conn,
+                pending: pending as u64,
+            }),
+            changed: Condvar::new(),
+        }
+    }
+
+    fn sanitize_endpoint(endpoint: &str) -> String {
+        endpoint
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect()
+    }
+
+    /// Durably record a payload for delivery.  If the observer is `max_pending_events` payloads
+    /// behind, this either waits for it to catch up or discards the oldest pending payloads,
+    /// depending on the configured backlog policy.
+    pub fn enqueue(&self, path: &str, payload: &serde_json::Value) {
+        let body = match serde_json::to_string(payload) {
+            Ok(body) => body,
+            Err(err) => {
+                error!("Event dispatcher: serialization failed  - {:?}", err);
+                return;
+            }
+        };
+
+        let mut state = self.state.lock().unwrap();
+        if state.pending >= self.max_pending {
+            state = match self.backlog_policy {
+                EventBacklogPolicy::Block => self.wait_for_backlog(state),
+                EventBacklogPolicy::Drop => self.drop_backlog(state),
+            };
+        }
+
+        let args: &[&dyn ToSql] = &[&path, &body];
+        state
+            .conn
+            .execute("INSERT INTO outbox (path, payload) VALUES (?1, ?2)", args)
+            .expect(&format!(
+                "Event dispatcher: failed to store payload in {}",
+                self.db_path
+            ));
+        state.pending += 1;
+
+        self.changed.notify_all();
+    }
+
+    fn wait_for_backlog<'a>(
+        &self,
+        mut state: MutexGuard<'a, OutboxState>,
+    ) -> MutexGuard<'a, OutboxState> {
+        while state.pending >= self.max_pending {
+            warn!(
+                "Event dispatcher: observer {} is {} payloads behind; waiting for it to catch up",
+                self.endpoint, state.pending
+            );
+            state = self
+                .changed
+                .wait_timeout(state, Duration::from_secs(BLOCKED_WARN_INTERVAL_SECS))
+                .unwrap()
+                .0;
+        }
+        state
+    }
+
+    fn drop_backlog<'a>(
+        &self,
+        mut state: MutexGuard<'a, OutboxState>,
+    ) -> MutexGuard<'a, OutboxState> {
+        // make room for exactly one more payload
+        let excess = (state.pending + 1 - self.max_pending) as i64;
+        let args: &[&dyn ToSql] = &[&excess];
+        let dropped = state
+            .conn
+            .execute(
+                "DELETE FROM outbox WHERE seq IN (SELECT seq FROM outbox ORDER BY seq ASC LIMIT ?1)",
+                args,
+            )
+            .expect(&format!(
+                "Event dispatcher: failed to drop payloads from {}",
+                self.db_path
+            ));
+
+        warn!(
+            "Event dispatcher: observer {} is {} payloads behind; dropped the {} oldest",
+            self.endpoint, state.pending, dropped
+        );
+        state.pending -= cmp::min(state.pending, dropped as u64);
+        state
+    }
+
+    /// Block until there is a pending payload, and return the oldest one.
+    fn next_payload(&self) -> (i64, String, String) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            let next = state
+                .conn
+                .query_row(
+                    "SELECT seq, path, payload FROM outbox ORDER BY seq ASC LIMIT 1",
+                    NO_PARAMS,
+                    |row| (row.get(0), row.get(1), row.get(2)),
+                )
+                .optional()
+                .expect(&format!(
+                    "Event dispatcher: failed to read from outbox {}",
+                    self.db_path
+                ));
+
+            match next {
+                Some(payload) => return payload,
+                None => {
+                    state = self.changed.wait(state).unwrap();
+                }
+            }
+        }
+    }
+
+    fn acknowledge(&self, seq: i64) {
+        let mut state = self.state.lock().unwrap();
+        let args: &[&dyn ToSql] = &[&seq];
+        let deleted = state
+            .conn
+            .execute("DELETE FROM outbox WHERE seq = ?1", args)
+            .expect(&format!(
+                "Event dispatcher: failed to acknowledge payload in {}",
+                self.db_path
+            ));
+        // the payload may have been dropped while it was in flight
+        state.pending -= cmp::min(state.pending, deleted as u64);
+
+        self.changed.notify_all();
+    }
+
+    fn run_delivery(&self) {
+        let mut backoff = INITIAL_DELIVERY_BACKOFF_MS;
+        loop {
+            if self.deliver_next() {
+                backoff = INITIAL_DELIVERY_BACKOFF_MS;
+            } else {
+                thread::sleep(Duration::from_millis(backoff));
+                backoff = EventOutbox::next_backoff(backoff);
+            }
+        }
+    }
+
+    /// Block until there is a pending payload, and make one attempt to deliver the oldest.
+    /// Returns true if the payload is done with, i.e. the observer acknowledged or rejected it.
+    fn deliver_next(&self) -> bool {
+        let (seq, path, payload) = self.next_payload();
+        match self.post(seq, &path, payload.into_bytes()) {
+            Delivery::Acknowledged => {
+                self.acknowledge(seq);
+                true
+            }
+            Delivery::Rejected => {
+                error!(
+                    "Event dispatcher: {} rejected payload {} for {}; dropping it",
+                    self.endpoint, seq, path
+                );
+                self.acknowledge(seq);
+                true
+            }
+            Delivery::Failed => false,
+        }
+    }
+
+    fn next_backoff(backoff_ms: u64) -> u64 {
+        cmp::min(backoff_ms * 2, MAX_DELIVERY_BACKOFF_MS)
+    }
+
+    fn post(&self, seq: i64, path: &str, body: Vec<u8>) -> Delivery {
+        let url = {
+            let joined_components = match path.starts_with("/") {
+                true => format!("{}{}", &self.endpoint, path),
+                false => format!("{}/{}", &self.endpoint, path),
+            };
+            let url = format!("http://{}", joined_components);
+            Url::parse(&url).expect(&format!(
+                "Event dispatcher: unable to parse {} as a URL",
+                url
+            ))
+        };
+
+        let mut req = Request::new(Method::Post, url);
+        req.append_header("Content-Type", "application/json")
+            .expect("Unable to set header");
+        req.append_header(EVENT_SEQUENCE_HEADER, format!("{}", seq))
+            .expect("Unable to set header");
+        req.set_body(body);
+
+        let response = async_std::task::block_on(async {
+            let stream = match TcpStream::connect(self.endpoint.clone()).await {
+                Ok(stream) => stream,
+                Err(err) => {
+                    warn!("Event dispatcher: connection failed  - {:?}", err);
+                    return None;
+                }
+            };
+
+            match client::connect(stream, req).await {
+                Ok(response) => Some(response),
+                Err(err) => {
+                    warn!("Event dispatcher: rpc invokation failed  - {:?}", err);
+                    return None;
+                }
+            }
+        });
+
+        match response {
+            Some(response) if response.status().is_success() => Delivery::Acknowledged,
+            Some(response) if response.status().is_client_error() => Delivery::Rejected,
+            Some(response) => {
+                error!(
+                    "Event dispatcher: POST {} failed with error {:?}",
+                    self.endpoint, response
+                );
+                Delivery::Failed
+            }
+            None => Delivery::Failed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    fn make_outbox(test_name: &str, max_pending: u64, policy: EventBacklogPolicy) -> EventOutbox {
+        let db_path = format!("/tmp/stacks-node-tests/event-outbox-{}.sqlite", test_name);
+        if fs::metadata(&db_path).is_ok() {
+            fs::remove_file(&db_path).unwrap();
+        }
+        fs::create_dir_all("/tmp/stacks-node-tests").unwrap();
+        reopen_outbox(test_name, max_pending, policy)
+    }
+
+    fn reopen_outbox(test_name: &str, max_pending: u64, policy: EventBacklogPolicy) -> EventOutbox {
+        let db_path = format!("/tmp/stacks-node-tests/event-outbox-{}.sqlite", test_name);
+        let conf = EventObserverConfig {
+            endpoint: "127.0.0.1:1".to_string(),
+            max_pending_events: max_pending,
+            backlog_policy: policy,
+            ..EventObserverConfig::default()
+        };
+        EventOutbox::new(&db_path, &conf)
+    }
+
+    fn pending(outbox: &EventOutbox) -> u64 {
+        outbox.state.lock().unwrap().pending
+    }
+
+    /// Serve one POST per entry in `statuses`, replying with that status.  Returns the sequence
+    /// header, path, and body of each request received.
+    fn spawn_observer(
+        statuses: Vec<u16>,
+    ) -> (String, thread::JoinHandle<Vec<(String, String, String)>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut requests = vec![];
+            for status in statuses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let path = request_line.split(' ').nth(1).unwrap().to_string();
+
+                let mut seq = String::new();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    let mut parts = line.splitn(2, ':');
+                    let name = parts.next().unwrap().trim().to_lowercase();
+                    let value = parts.next().unwrap_or("").trim().to_string();
+                    if name == EVENT_SEQUENCE_HEADER.to_lowercase() {
+                        seq = value;
+                    } else if name == "content-length" {
+                        content_length = value.parse().unwrap();
+                    }
+                }
+
+                let mut body = vec![0u8; content_length];
+                reader.read_exact(&mut body).unwrap();
+                requests.push((seq, path, String::from_utf8(body).unwrap()));
+
+                let mut stream = reader.into_inner();
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Status\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                )
+                .unwrap();
+                stream.flush().unwrap();
+            }
+            requests
+        });
+        (endpoint, handle)
+    }
+
+    #[test]
+    fn test_outbox_ordering_and_sequence_numbers() {
+        let outbox = make_outbox("ordering", 10, EventBacklogPolicy::Block);
+
+        outbox.enqueue("/new_block", &json!({"n": 1}));
+        outbox.enqueue("/new_mempool_tx", &json!({"n": 2}));
+        outbox.enqueue("/new_block", &json!({"n": 3}));
+        assert_eq!(pending(&outbox), 3);
+
+        // payloads come out in the order they went in
+        let (seq, path, payload) = outbox.next_payload();
+        assert_eq!(seq, 1);
+        assert_eq!(path, "/new_block");
+        assert_eq!(payload, "{\"n\":1}");
+
+        // a payload stays at the head of the queue until it is acknowledged
+        assert_eq!(outbox.next_payload().0, 1);
+        outbox.acknowledge(1);
+        assert_eq!(pending(&outbox), 2);
+
+        let (seq, path, payload) = outbox.next_payload();
+        assert_eq!(seq, 2);
+        assert_eq!(path, "/new_mempool_tx");
+        assert_eq!(payload, "{\"n\":2}");
+        outbox.acknowledge(2);
+        outbox.acknowledge(3);
+        assert_eq!(pending(&outbox), 0);
+
+        // sequence numbers are never reused, even once the outbox is empty
+        outbox.enqueue("/new_block", &json!({"n": 4}));
+        assert_eq!(outbox.next_payload().0, 4);
+    }
+
+    #[test]
+    fn test_outbox_drop_backlog_leaves_sequence_gap() {
+        let outbox = make_outbox("drop-backlog", 2, EventBacklogPolicy::Drop);
+
+        outbox.enqueue("/new_block", &json!({"n": 1}));
+        outbox.enqueue("/new_block", &json!({"n": 2}));
+        outbox.enqueue("/new_block", &json!({"n": 3}));
+        assert_eq!(pending(&outbox), 2);
+
+        // the oldest payload was dropped, so the observer sees a gap in the sequence
+        let (seq, _, payload) = outbox.next_payload();
+        assert_eq!(seq, 2);
+        assert_eq!(payload, "{\"n\":2}");
+    }
+
+    #[test]
+    fn test_outbox_persists_across_restart() {
+        {
+            let outbox = make_outbox("restart", 10, EventBacklogPolicy::Block);
+            outbox.enqueue("/new_block", &json!({"n": 1}));
+            outbox.enqueue("/new_burn_block", &json!({"n": 2}));
+            outbox.acknowledge(1);
+        }
+
+        let outbox = reopen_outbox("restart", 10, EventBacklogPolicy::Block);
+        assert_eq!(pending(&outbox), 1);
+
+        let (seq, path, payload) = outbox.next_payload();
+        assert_eq!(seq, 2);
+        assert_eq!(path, "/new_burn_block");
+        assert_eq!(payload, "{\"n\":2}");
+
+        // numbering picks up where it left off
+        outbox.enqueue("/new_block", &json!({"n": 3}));
+        outbox.acknowledge(2);
+        assert_eq!(outbox.next_payload().0, 3);
+    }
+
+    #[test]
+    fn test_outbox_retries_failed_delivery() {
+        let (endpoint, observer) = spawn_observer(vec![500, 200]);
+        let mut outbox = make_outbox("retry", 10, EventBacklogPolicy::Block);
+        outbox.endpoint = endpoint;
+
+        outbox.enqueue("/new_block", &json!({"n": 1}));
+
+        // a failed POST leaves the payload in the outbox
+        assert!(!outbox.deliver_next());
+        assert_eq!(pending(&outbox), 1);
+
+        // and it is sent again, unchanged, on the next attempt
+        assert!(outbox.deliver_next());
+        assert_eq!(pending(&outbox), 0);
+
+        let requests = observer.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        for (seq, path, body) in requests {
+            assert_eq!(seq, "1");
+            assert_eq!(path, "/new_block");
+            assert_eq!(body, "{\"n\":1}");
+        }
+    }
+
+    #[test]
+    fn test_outbox_drops_rejected_payload() {
+        let (endpoint, observer) = spawn_observer(vec![400, 200]);
+        let mut outbox = make_outbox("rejected", 10, EventBacklogPolicy::Block);
+        outbox.endpoint = endpoint;
+
+        outbox.enqueue("/new_burn_block", &json!({"n": 1}));
+        outbox.enqueue("/new_block", &json!({"n": 2}));
+
+        // the observer won't ever take the first payload, so it's dropped rather than retried
+        assert!(outbox.deliver_next());
+        assert_eq!(pending(&outbox), 1);
+
+        // and the queue moves on to the next one
+        assert!(outbox.deliver_next());
+        assert_eq!(pending(&outbox), 0);
+
+        let requests = observer.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].0, "1");
+        assert_eq!(requests[0].1, "/new_burn_block");
+        assert_eq!(requests[1].0, "2");
+        assert_eq!(requests[1].1, "/new_block");
+    }
+
+    #[test]
+    fn test_outbox_backoff() {
+        let mut backoff = INITIAL_DELIVERY_BACKOFF_MS;
+        backoff = EventOutbox::next_backoff(backoff);
+        assert_eq!(backoff, 2 * INITIAL_DELIVERY_BACKOFF_MS);
+        backoff = EventOutbox::next_backoff(backoff);
+        assert_eq!(backoff, 4 * INITIAL_DELIVERY_BACKOFF_MS);
+
+        for _ in 0..20 {
+            backoff = EventOutbox::next_backoff(backoff);
+        }
+        assert_eq!(backoff, MAX_DELIVERY_BACKOFF_MS);
+    }
+}
@@ -1,5 +1,6 @@
 extern crate libc;
 extern crate rand;
+extern crate rusqlite;
 extern crate serde;
 
 #[macro_use]
@@ -22,6 +23,7 @@ pub mod monitoring;
 pub mod burnchains;
 pub mod config;
 pub mod event_dispatcher;
+pub mod event_outbox;
 pub mod keychain;
 pub mod neon_node;
 pub mod node;
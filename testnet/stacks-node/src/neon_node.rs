@@ -21,13 +21,16 @@ use stacks::chainstate::stacks::db::{ClarityTx, StacksChainState};
 use stacks::chainstate::stacks::Error as ChainstateError;
 use stacks::chainstate::stacks::StacksBlockId;
 use stacks::chainstate::stacks::StacksPublicKey;
-use stacks::chainstate::stacks::{miner::StacksMicroblockBuilder, StacksBlockBuilder};
+use stacks::chainstate::stacks::{
+    miner::{BlockCostUtilization, FeeRateSelector, StacksMicroblockBuilder},
+    StacksBlockBuilder,
+};
 use stacks::chainstate::stacks::{
     CoinbasePayload, StacksAddress, StacksBlock, StacksBlockHeader, StacksMicroblock,
     StacksTransaction, StacksTransactionSigner, TransactionAnchorMode, TransactionPayload,
     TransactionVersion,
 };
-use stacks::core::mempool::MemPoolDB;
+use stacks::core::mempool::{MemPoolDB, MemPoolDropReason};
 use stacks::net::{
     db::{LocalPeer, PeerDB},
     dns::DNSResolver,
@@ -52,7 +55,11 @@ use stacks::chainstate::coordinator::comm::CoordinatorChannels;
 use stacks::chainstate::coordinator::{get_next_recipients, OnChainRewardSetProvider};
 use stacks::vm::database::BurnStateDB;
 
-use stacks::monitoring::{increment_stx_blocks_mined_counter, update_active_miners_count_gauge};
+use stacks::monitoring::{
+    increment_stx_blocks_mined_counter, update_active_miners_count_gauge,
+    update_mined_block_cost_utilization_gauge,
+};
+use stacks::vm::costs::ExecutionCostDimension;
 
 pub const TESTNET_CHAIN_ID: u32 = 0x80000000;
 pub const TESTNET_PEER_VERSION: u32 = 0xfacade01;
@@ -62,7 +69,7 @@ struct AssembledAnchorBlock {
     parent_consensus_hash: ConsensusHash,
     my_burn_hash: BurnchainHeaderHash,
     anchored_block: StacksBlock,
-    consumed_execution: ExecutionCost,
+    cost_utilization: BlockCostUtilization,
     bytes_so_far: u64,
     attempt: u64,
 }
@@ -256,8 +263,24 @@ fn spawn_peer(
     )
     .map_err(|e| NetError::ChainstateError(e.to_string()))?;
 
-    let mut mem_pool = MemPoolDB::open(false, TESTNET_CHAIN_ID, &stacks_chainstate_path)
-        .map_err(NetError::DBError)?;
+    // the coordinator maintains the contract event and transaction indexes; this thread only
+    // serves them
+    if config.node.contract_event_index {
+        chainstate
+            .enable_contract_event_index()
+            .map_err(|e| NetError::ChainstateError(e.to_string()))?;
+    }
+    if config.node.transaction_index {
+        chainstate.enable_transaction_index();
+    }
+
+    let mut mem_pool = MemPoolDB::open_with_settings(
+        false,
+        TESTNET_CHAIN_ID,
+        &stacks_chainstate_path,
+        config.mempool.clone(),
+    )
+    .map_err(NetError::DBError)?;
 
     // buffer up blocks to store without stalling the p2p thread
     let mut results_with_data = VecDeque::new();
@@ -378,8 +401,13 @@ fn spawn_miner_relayer(
     )
     .map_err(|e| NetError::ChainstateError(e.to_string()))?;
 
-    let mut mem_pool = MemPoolDB::open(false, TESTNET_CHAIN_ID, &stacks_chainstate_path)
-        .map_err(NetError::DBError)?;
+    let mut mem_pool = MemPoolDB::open_with_settings(
+        false,
+        TESTNET_CHAIN_ID,
+        &stacks_chainstate_path,
+        config.mempool.clone(),
+    )
+    .map_err(NetError::DBError)?;
 
     let mut last_mined_blocks = vec![];
     let burn_fee_cap = config.burnchain.burn_fee_cap;
@@ -407,6 +435,33 @@ fn spawn_miner_relayer(
                     if mempool_txs_added > 0 {
                         event_dispatcher.process_new_mempool_txs(net_receipts.mempool_txs_added);
                     }
+
+                    if net_receipts.mempool_txs_dropped.len() > 0 {
+                        event_dispatcher.process_dropped_mempool_txs(
+                            net_receipts.mempool_txs_dropped,
+                            MemPoolDropReason::ReplaceByFee,
+                        );
+                    }
+
+                    if net_receipts.mempool_txs_garbage_collected.len() > 0 {
+                        event_dispatcher.process_dropped_mempool_txs(
+                            net_receipts.mempool_txs_garbage_collected,
+                            MemPoolDropReason::StaleGarbageCollect,
+                        );
+                    }
+
+                    if net_receipts.mempool_txs_evicted.len() > 0 {
+                        event_dispatcher.process_dropped_mempool_txs(
+                            net_receipts.mempool_txs_evicted,
+                            MemPoolDropReason::Evicted,
+                        );
+                    }
+
+                    if net_receipts.processed_unconfirmed_microblocks.len() > 0 {
+                        event_dispatcher.process_new_microblocks(
+                            net_receipts.processed_unconfirmed_microblocks,
+                        );
+                    }
                 }
                 RelayerDirective::ProcessTenure(consensus_hash, burn_hash, block_header_hash) => {
                     debug!("Relayer: Process tenure");
@@ -415,7 +470,7 @@ fn spawn_miner_relayer(
                             parent_consensus_hash,
                             anchored_block: mined_block,
                             my_burn_hash: mined_burn_hash,
-                            consumed_execution,
+                            cost_utilization,
                             bytes_so_far,
                             attempt: _,
                         } = last_mined_block;
@@ -429,6 +484,13 @@ fn spawn_miner_relayer(
                             );
 
                             increment_stx_blocks_mined_counter();
+                            for dimension in ExecutionCostDimension::ALL.iter() {
+                                update_mined_block_cost_utilization_gauge(
+                                    dimension.name(),
+                                    cost_utilization.percent(*dimension) as i64,
+                                );
+                            }
+                            event_dispatcher.process_mined_block(&mined_block, &cost_utilization);
 
                             match inner_process_tenure(
                                 &mined_block,
@@ -493,7 +555,7 @@ fn spawn_miner_relayer(
                                                 &mut chainstate,
                                                 &sortdb.index_conn(),
                                                 &keychain,
-                                                consumed_execution,
+                                                cost_utilization.consumed.clone(),
                                                 bytes_so_far,
                                                 &mem_pool,
                                             );
@@ -527,10 +589,19 @@ fn spawn_miner_relayer(
                                             }
                                         }
                                         // update unconfirmed state
-                                        if let Err(e) = chainstate
+                                        match chainstate
                                             .refresh_unconfirmed_state(&sortdb.index_conn())
                                         {
-                                            warn!("Failed to refresh unconfirmed state after processing microblock {}/{}-{}: {:?}", &mined_burn_hash, &block_header_hash, mined_microblock.block_hash(), &e);
+                                            Ok((_, _, microblock_receipts)) => {
+                                                if microblock_receipts.len() > 0 {
+                                                    event_dispatcher.process_new_microblocks(
+                                                        microblock_receipts,
+                                                    );
+                                                }
+                                            }
+                                            Err(e) => {
+                                                warn!("Failed to refresh unconfirmed state after processing microblock {}/{}-{}: {:?}", &mined_burn_hash, &block_header_hash, mined_microblock.block_hash(), &e);
+                                            }
                                         }
                                         // broadcast to peers
                                         let microblock_hash = mined_microblock.header.block_hash();
@@ -1002,8 +1073,8 @@ impl InitializedNeonNode {
 
         let coinbase_tx = inner_generate_coinbase_tx(keychain, coinbase_nonce);
 
-        let (anchored_block, consumed_execution, bytes_so_far) =
-            match StacksBlockBuilder::build_anchored_block(
+        let (anchored_block, cost_utilization, bytes_so_far) =
+            match StacksBlockBuilder::build_anchored_block_with_selector(
                 chain_state,
                 &burn_db.index_conn(),
                 mem_pool,
@@ -1013,6 +1084,7 @@ impl InitializedNeonNode {
                 mblock_pubkey_hash,
                 &coinbase_tx,
                 HELIUM_BLOCK_LIMIT.clone(),
+                &FeeRateSelector,
             ) {
                 Ok(block) => block,
                 Err(e) => {
@@ -1032,6 +1104,16 @@ impl InitializedNeonNode {
             anchored_block.txs.len(),
             attempt
         );
+        if cost_utilization.saturated.len() > 0 {
+            info!(
+                "Block {} used {} of budget {}; saturated {:?}, skipped {} txs",
+                anchored_block.block_hash(),
+                &cost_utilization.consumed,
+                &cost_utilization.budget,
+                &cost_utilization.saturated,
+                cost_utilization.skipped_txs
+            );
+        }
 
         // let's figure out the recipient set!
         let recipients = match get_next_recipients(
@@ -1066,7 +1148,7 @@ impl InitializedNeonNode {
         Some(AssembledAnchorBlock {
             parent_consensus_hash: parent_consensus_hash,
             my_burn_hash: burn_block.burn_header_hash,
-            consumed_execution,
+            cost_utilization,
             anchored_block,
             bytes_so_far,
             attempt,
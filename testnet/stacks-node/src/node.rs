@@ -20,7 +20,7 @@ use stacks::chainstate::stacks::{
     StacksTransaction, StacksTransactionSigner, TransactionAnchorMode, TransactionPayload,
     TransactionVersion,
 };
-use stacks::core::mempool::MemPoolDB;
+use stacks::core::mempool::{MemPoolDB, MemPoolDropReason, MemPoolSettings};
 use stacks::net::{
     db::PeerDB, p2p::PeerNetwork, rpc::RPCHandlerArgs, Error as NetError, PeerAddress,
 };
@@ -75,9 +75,12 @@ fn spawn_peer(
     rpc_sock: &SocketAddr,
     burn_db_path: String,
     stacks_chainstate_path: String,
+    mempool_settings: MemPoolSettings,
     event_dispatcher: EventDispatcher,
     exit_at_block_height: Option<u64>,
     poll_timeout: u64,
+    contract_event_index: bool,
+    transaction_index: bool,
 ) -> Result<JoinHandle<()>, NetError> {
     this.bind(p2p_sock, rpc_sock).unwrap();
     let server_thread = thread::spawn(move || {
@@ -104,16 +107,33 @@ fn spawn_peer(
                         continue;
                     }
                 };
+            if contract_event_index {
+                if let Err(e) = chainstate.enable_contract_event_index() {
+                    warn!(
+                        "Error while enabling the contract event index in peer loop: {}",
+                        e
+                    );
+                    thread::sleep(time::Duration::from_secs(1));
+                    continue;
+                }
+            }
+            if transaction_index {
+                chainstate.enable_transaction_index();
+            }
 
-            let mut mem_pool =
-                match MemPoolDB::open(false, TESTNET_CHAIN_ID, &stacks_chainstate_path) {
-                    Ok(x) => x,
-                    Err(e) => {
-                        warn!("Error while connecting to mempool db in peer loop: {}", e);
-                        thread::sleep(time::Duration::from_secs(1));
-                        continue;
-                    }
-                };
+            let mut mem_pool = match MemPoolDB::open_with_settings(
+                false,
+                TESTNET_CHAIN_ID,
+                &stacks_chainstate_path,
+                mempool_settings.clone(),
+            ) {
+                Ok(x) => x,
+                Err(e) => {
+                    warn!("Error while connecting to mempool db in peer loop: {}", e);
+                    thread::sleep(time::Duration::from_secs(1));
+                    continue;
+                }
+            };
 
             let net_result = this
                 .run(
@@ -129,6 +149,28 @@ fn spawn_peer(
             if net_result.has_transactions() {
                 event_dispatcher.process_new_mempool_txs(net_result.transactions())
             }
+
+            let (dropped_txids, evicted_txids) =
+                match mem_pool.tx_begin().and_then(|mut mempool_tx| {
+                    let dropped = MemPoolDB::take_unannounced_replaced_txids(&mut mempool_tx)?;
+                    let evicted = MemPoolDB::take_unannounced_evicted_txids(&mut mempool_tx)?;
+                    mempool_tx.commit()?;
+                    Ok((dropped, evicted))
+                }) {
+                    Ok(x) => x,
+                    Err(e) => {
+                        warn!("Error while loading dropped mempool transactions: {}", e);
+                        (vec![], vec![])
+                    }
+                };
+            if dropped_txids.len() > 0 {
+                event_dispatcher
+                    .process_dropped_mempool_txs(dropped_txids, MemPoolDropReason::ReplaceByFee);
+            }
+            if evicted_txids.len() > 0 {
+                event_dispatcher
+                    .process_dropped_mempool_txs(evicted_txids, MemPoolDropReason::Evicted);
+            }
         }
     });
     Ok(server_thread)
@@ -157,7 +199,7 @@ impl Node {
             config.block_limit.clone(),
         );
 
-        let (chain_state, receipts) = match chain_state_result {
+        let (mut chain_state, receipts) = match chain_state_result {
             Ok(res) => res,
             Err(err) => panic!(
                 "Error while opening chain state at path {}: {:?}",
@@ -165,10 +207,18 @@ impl Node {
                 err
             ),
         };
+        if config.node.contract_event_index {
+            chain_state
+                .enable_contract_event_index()
+                .expect("Error while enabling the contract event index");
+        }
+        if config.node.transaction_index {
+            chain_state.enable_transaction_index();
+        }
         let mut event_dispatcher = EventDispatcher::new();
 
         for observer in &config.events_observers {
-            event_dispatcher.register_observer(observer);
+            event_dispatcher.register_observer(observer, &config.get_event_observers_path());
         }
 
         event_dispatcher.process_boot_receipts(receipts);
@@ -198,17 +248,25 @@ impl Node {
         let mut event_dispatcher = EventDispatcher::new();
 
         for observer in &config.events_observers {
-            event_dispatcher.register_observer(observer);
+            event_dispatcher.register_observer(observer, &config.get_event_observers_path());
         }
 
         let chainstate_path = config.get_chainstate_path();
         let sortdb_path = config.get_burn_db_file_path();
 
-        let (chain_state, _) =
+        let (mut chain_state, _) =
             match StacksChainState::open(false, TESTNET_CHAIN_ID, &chainstate_path) {
                 Ok(x) => x,
                 Err(_e) => panic!(),
             };
+        if config.node.contract_event_index {
+            chain_state
+                .enable_contract_event_index()
+                .expect("Error while enabling the contract event index");
+        }
+        if config.node.transaction_index {
+            chain_state.enable_transaction_index();
+        }
 
         let mut node = Node {
             active_registered_key: None,
@@ -340,9 +398,12 @@ impl Node {
             &rpc_sock,
             self.config.get_burn_db_file_path(),
             self.config.get_chainstate_path(),
+            self.config.mempool.clone(),
             event_dispatcher,
             exit_at_block_height,
             1000,
+            self.config.node.contract_event_index,
+            self.config.node.transaction_index,
         )
         .unwrap();
 
@@ -470,8 +531,13 @@ impl Node {
             },
         };
 
-        let mem_pool = MemPoolDB::open(false, TESTNET_CHAIN_ID, &self.chain_state.root_path)
-            .expect("FATAL: failed to open mempool");
+        let mem_pool = MemPoolDB::open_with_settings(
+            false,
+            TESTNET_CHAIN_ID,
+            &self.chain_state.root_path,
+            self.config.mempool.clone(),
+        )
+        .expect("FATAL: failed to open mempool");
 
         // Construct the coinbase transaction - 1st txn that should be handled and included in
         // the upcoming tenure.
@@ -8,7 +8,9 @@ use stacks::burnchains::{Address, Burnchain};
 use stacks::chainstate::burn::db::sortdb::SortitionDB;
 use stacks::chainstate::coordinator::comm::{CoordinatorChannels, CoordinatorReceivers};
 use stacks::chainstate::coordinator::{ChainsCoordinator, CoordinatorCommunication};
+use stacks::chainstate::stacks::db::StacksChainState;
 use std::cmp;
+use std::fs;
 use std::thread;
 
 use super::RunLoopCallbacks;
@@ -77,6 +79,58 @@ impl RunLoop {
     #[cfg(not(test))]
     fn bump_blocks_processed(&self) {}
 
+    /// Drop the Clarity state of blocks more than `prune_depth` blocks below the canonical
+    /// chain tip, if there is a chainstate yet.
+    fn prune_chainstate(&self, prune_depth: u32) {
+        let chainstate_path = self.config.get_chainstate_path();
+        if fs::metadata(&chainstate_path).is_err() {
+            return;
+        }
+
+        let sortdb = match SortitionDB::open(&self.config.get_burn_db_file_path(), false) {
+            Ok(sortdb) => sortdb,
+            Err(e) => {
+                warn!(
+                    "Not pruning chainstate: failed to open sortition DB: {:?}",
+                    &e
+                );
+                return;
+            }
+        };
+        let (mut chainstate, _) = match StacksChainState::open_with_block_limit(
+            false,
+            neon_node::TESTNET_CHAIN_ID,
+            &chainstate_path,
+            self.config.block_limit.clone(),
+        ) {
+            Ok(x) => x,
+            Err(e) => {
+                warn!(
+                    "Not pruning chainstate: failed to open chainstate: {:?}",
+                    &e
+                );
+                return;
+            }
+        };
+
+        match chainstate.prune_clarity_state(&sortdb, prune_depth) {
+            Ok(Some(summary)) => {
+                info!(
+                    "Pruned Clarity state deeper than {} blocks: {} tries, {} -> {} bytes, {} side-store values dropped",
+                    prune_depth,
+                    summary.num_pruned_tries,
+                    summary.bytes_before,
+                    summary.bytes_after,
+                    summary.dead_value_hashes.len()
+                );
+            }
+            Ok(None) => {}
+            Err(e) => {
+                warn!("Failed to prune chainstate: {:?}", &e);
+            }
+        }
+    }
+
     /// Starts the testnet runloop.
     ///
     /// This function will block by looping infinitely.
@@ -143,13 +197,21 @@ impl RunLoop {
         // setup dispatcher
         let mut event_dispatcher = EventDispatcher::new();
         for observer in self.config.events_observers.iter() {
-            event_dispatcher.register_observer(observer);
+            event_dispatcher.register_observer(observer, &self.config.get_event_observers_path());
         }
 
         let mut coordinator_dispatcher = event_dispatcher.clone();
         let burnchain_config = burnchain.get_burnchain();
         let chainstate_path = self.config.get_chainstate_path();
         let coordinator_burnchain_config = burnchain_config.clone();
+        let contract_event_index = self.config.node.contract_event_index;
+        let transaction_index = self.config.node.transaction_index;
+
+        if let Some(prune_depth) = self.config.node.prune_depth {
+            // pruning moves trie nodes around, and the other threads' MARF reads aren't isolated
+            // from that, so it has to happen before anything else opens the chainstate
+            self.prune_chainstate(prune_depth);
+        }
 
         thread::spawn(move || {
             ChainsCoordinator::run(
@@ -161,6 +223,8 @@ impl RunLoop {
                 block_limit,
                 &mut coordinator_dispatcher,
                 coordinator_receivers,
+                contract_event_index,
+                transaction_index,
                 |_| {},
             );
         });